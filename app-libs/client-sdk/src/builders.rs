@@ -0,0 +1,362 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Typed builders for trusted calls and getters.
+//!
+//! The signing is done by the user (e.g. by a browser extension), the builders only provide
+//! the payload that has to be signed and assemble the signed operation afterwards.
+
+use crate::types::{
+	Getter, PublicGetter, TrustedCall, TrustedCallSigned, TrustedGetter, TrustedGetterSigned,
+};
+use alloc::{boxed::Box, vec::Vec};
+use itp_stf_primitives::{
+	account_export::ExportKey,
+	pagination::PageRequest,
+	session_keys::SessionKey,
+	traits::TrustedCallSigning,
+	types::{AccountId, KeyPair, ShardIdentifier, Signature, TrustedOperation},
+};
+use itp_types::{parentchain::ParentchainAssetId, Balance, Index, SidechainTimestamp};
+
+/// Composes trusted calls for a given worker (identified by its mrenclave) and shard.
+#[derive(Clone, Debug)]
+pub struct TrustedCallBuilder {
+	mrenclave: [u8; 32],
+	shard: ShardIdentifier,
+	nonce: Index,
+}
+
+impl TrustedCallBuilder {
+	pub fn new(mrenclave: [u8; 32], shard: ShardIdentifier) -> Self {
+		TrustedCallBuilder { mrenclave, shard, nonce: 0 }
+	}
+
+	/// Nonce of the sender account, can be queried with [GetterBuilder::nonce].
+	pub fn nonce(mut self, nonce: Index) -> Self {
+		self.nonce = nonce;
+		self
+	}
+
+	pub fn balance_transfer(
+		&self,
+		from: AccountId,
+		to: AccountId,
+		amount: Balance,
+	) -> UnsignedTrustedCall {
+		self.call(TrustedCall::balance_transfer(from, to, amount))
+	}
+
+	pub fn balance_unshield(
+		&self,
+		from: AccountId,
+		beneficiary: AccountId,
+		amount: Balance,
+	) -> UnsignedTrustedCall {
+		self.call(TrustedCall::balance_unshield(from, beneficiary, amount, self.shard))
+	}
+
+	/// Unshielding that is paid out with the next batch of the shard vault.
+	pub fn balance_unshield_batched(
+		&self,
+		from: AccountId,
+		beneficiary: AccountId,
+		amount: Balance,
+	) -> UnsignedTrustedCall {
+		self.call(TrustedCall::balance_unshield_batched(from, beneficiary, amount))
+	}
+
+	pub fn assets_transfer(
+		&self,
+		from: AccountId,
+		to: AccountId,
+		asset_id: ParentchainAssetId,
+		amount: Balance,
+	) -> UnsignedTrustedCall {
+		self.call(TrustedCall::assets_transfer(from, to, asset_id, amount))
+	}
+
+	pub fn assets_unshield(
+		&self,
+		from: AccountId,
+		beneficiary: AccountId,
+		asset_id: ParentchainAssetId,
+		amount: Balance,
+	) -> UnsignedTrustedCall {
+		self.call(TrustedCall::assets_unshield(from, beneficiary, asset_id, amount))
+	}
+
+	/// Registers a session key of `owner` that may submit the calls with the given
+	/// [names](TrustedCall::name) until the sidechain timestamp `valid_until` (ms).
+	pub fn session_key_register(
+		&self,
+		owner: AccountId,
+		delegate: AccountId,
+		valid_until: SidechainTimestamp,
+		call_names: &[&str],
+	) -> UnsignedTrustedCall {
+		let scope = call_names.iter().map(|name| name.as_bytes().to_vec()).collect();
+		self.call(TrustedCall::session_key_register(
+			owner,
+			delegate,
+			SessionKey::new(valid_until, scope),
+		))
+	}
+
+	pub fn session_key_revoke(&self, owner: AccountId, delegate: AccountId) -> UnsignedTrustedCall {
+		self.call(TrustedCall::session_key_revoke(owner, delegate))
+	}
+
+	/// Call of the owner of a session key that is signed by the session key instead, the
+	/// [nonce](Self::nonce) is the one of the session key.
+	pub fn session_call(&self, delegate: AccountId, call: TrustedCall) -> UnsignedTrustedCall {
+		self.call(TrustedCall::session_call(delegate, Box::new(call)))
+	}
+
+	/// Sponsors a call that has been signed by its sender, the [nonce](Self::nonce) is the one
+	/// of the sponsor.
+	pub fn sponsored_call(
+		&self,
+		sponsor: AccountId,
+		signed_call: TrustedCallSigned,
+	) -> UnsignedTrustedCall {
+		self.call(TrustedCall::sponsored_call(sponsor, Box::new(signed_call)))
+	}
+
+	/// Any other trusted call.
+	pub fn call(&self, call: TrustedCall) -> UnsignedTrustedCall {
+		UnsignedTrustedCall {
+			call,
+			nonce: self.nonce,
+			mrenclave: self.mrenclave,
+			shard: self.shard,
+		}
+	}
+}
+
+/// Trusted call that still has to be signed by its sender account.
+#[derive(Clone, Debug)]
+pub struct UnsignedTrustedCall {
+	call: TrustedCall,
+	nonce: Index,
+	mrenclave: [u8; 32],
+	shard: ShardIdentifier,
+}
+
+impl UnsignedTrustedCall {
+	pub fn call(&self) -> &TrustedCall {
+		&self.call
+	}
+
+	pub fn shard(&self) -> ShardIdentifier {
+		self.shard
+	}
+
+	pub fn signing_payload(&self) -> Vec<u8> {
+		self.call.signing_payload(self.nonce, &self.mrenclave, &self.shard)
+	}
+
+	pub fn sign(&self, pair: &KeyPair) -> TrustedCallSigned {
+		self.call.sign(pair, self.nonce, &self.mrenclave, &self.shard)
+	}
+
+	pub fn with_signature(&self, signature: Signature) -> TrustedCallSigned {
+		self.call.with_signature(self.nonce, signature)
+	}
+
+	/// Convenience function to get the operation that can be passed to [crate::rpc::submit_request].
+	pub fn into_trusted_operation(
+		self,
+		signature: Signature,
+		direct: bool,
+	) -> TrustedOperation<TrustedCallSigned, Getter> {
+		self.with_signature(signature).into_trusted_operation(direct)
+	}
+}
+
+/// Composes getters that are valid until the given sidechain timestamp (unix millis).
+///
+/// Signed getters expire, so that they can't be replayed to obtain fresh state. Getters are
+/// usually signed right before they are sent, with an expiry shortly after the current time,
+/// e.g. the [crate::types::DEFAULT_GETTER_VALIDITY].
+#[derive(Clone, Debug)]
+pub struct GetterBuilder {
+	valid_until: SidechainTimestamp,
+}
+
+impl GetterBuilder {
+	pub fn new(valid_until: SidechainTimestamp) -> Self {
+		GetterBuilder { valid_until }
+	}
+
+	pub fn free_balance(&self, account: AccountId) -> UnsignedTrustedGetter {
+		self.getter(TrustedGetter::free_balance(account))
+	}
+
+	pub fn reserved_balance(&self, account: AccountId) -> UnsignedTrustedGetter {
+		self.getter(TrustedGetter::reserved_balance(account))
+	}
+
+	pub fn nonce(&self, account: AccountId) -> UnsignedTrustedGetter {
+		self.getter(TrustedGetter::nonce(account))
+	}
+
+	pub fn rent_status(&self, account: AccountId) -> UnsignedTrustedGetter {
+		self.getter(TrustedGetter::rent_status(account))
+	}
+
+	pub fn asset_balance(
+		&self,
+		account: AccountId,
+		asset_id: ParentchainAssetId,
+	) -> UnsignedTrustedGetter {
+		self.getter(TrustedGetter::asset_balance(account, asset_id))
+	}
+
+	pub fn confidential_events(&self, account: AccountId) -> UnsignedTrustedGetter {
+		self.getter(TrustedGetter::confidential_events(account))
+	}
+
+	/// Returns a `Page<EncryptedEventRecord>`, continue with the cursor in `Page::next`. The
+	/// records are encrypted to the account, see `ConfidentialEventRecord::decrypt`.
+	pub fn confidential_events_page(
+		&self,
+		account: AccountId,
+		request: PageRequest,
+	) -> UnsignedTrustedGetter {
+		self.getter(TrustedGetter::confidential_events_page(account, request))
+	}
+
+	/// Audit log of the dropped operations, has to be signed by the root of the shard and sent
+	/// with [crate::rpc::audit_log_request].
+	pub fn audit_log(&self, root: AccountId, request: PageRequest) -> UnsignedTrustedGetter {
+		self.getter(TrustedGetter::audit_log(root, request))
+	}
+
+	/// All data stored for the account, encrypted to `key`, the JSON encoded RSA3072 public key
+	/// of the user. Decrypts to an encoded `AccountDataExport`.
+	pub fn account_data_export(&self, account: AccountId, key: ExportKey) -> UnsignedTrustedGetter {
+		self.getter(TrustedGetter::account_data_export(account, key))
+	}
+
+	/// The fee config and multiplier of the shard, a public getter that needn't be signed.
+	pub fn fee_config() -> Getter {
+		Self::public(PublicGetter::fee_config)
+	}
+
+	/// Fee that will be charged for the call, a public getter that needn't be signed.
+	pub fn fee_estimate(call: &UnsignedTrustedCall) -> Getter {
+		Self::public(PublicGetter::fee_estimate(call.call().clone()))
+	}
+
+	/// The latest reserves report of the shard, a public getter that needn't be signed.
+	pub fn reserves_report() -> Getter {
+		Self::public(PublicGetter::reserves_report)
+	}
+
+	pub fn public(getter: PublicGetter) -> Getter {
+		Getter::public(getter)
+	}
+
+	fn getter(&self, getter: TrustedGetter) -> UnsignedTrustedGetter {
+		UnsignedTrustedGetter { getter, valid_until: self.valid_until }
+	}
+}
+
+/// Trusted getter that still has to be signed by the account whose state is queried.
+#[derive(Clone, Debug)]
+pub struct UnsignedTrustedGetter {
+	getter: TrustedGetter,
+	valid_until: SidechainTimestamp,
+}
+
+impl UnsignedTrustedGetter {
+	pub fn signing_payload(&self) -> Vec<u8> {
+		self.getter.signing_payload(self.valid_until)
+	}
+
+	pub fn sign(&self, pair: &KeyPair) -> Getter {
+		Getter::trusted(self.getter.sign(pair, self.valid_until))
+	}
+
+	pub fn with_signature(&self, signature: Signature) -> Getter {
+		Getter::trusted(TrustedGetterSigned {
+			getter: self.getter.clone(),
+			valid_until: self.valid_until,
+			signature,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use sp_keyring::AccountKeyring;
+
+	// The signatures are verified against the `ita_stf` types in `ita_stf::trusted_call`.
+
+	#[test]
+	fn signing_payload_covers_nonce_mrenclave_and_shard() {
+		let mrenclave = [2u8; 32];
+		let shard = ShardIdentifier::repeat_byte(1);
+		let alice = KeyPair::Sr25519(Box::new(AccountKeyring::Alice.pair()));
+
+		let unsigned_call = TrustedCallBuilder::new(mrenclave, shard).nonce(5).balance_transfer(
+			AccountKeyring::Alice.to_account_id(),
+			AccountKeyring::Bob.to_account_id(),
+			1000,
+		);
+		let signed_call =
+			unsigned_call.with_signature(alice.sign(&unsigned_call.signing_payload()));
+
+		let mut expected_payload = unsigned_call.call().encode();
+		expected_payload.append(&mut (5 as Index, mrenclave, shard).encode());
+		assert_eq!(unsigned_call.signing_payload(), expected_payload);
+		assert_eq!(signed_call.nonce, 5);
+	}
+
+	#[test]
+	fn session_call_wraps_the_call_of_the_owner() {
+		let builder = TrustedCallBuilder::new([2u8; 32], ShardIdentifier::repeat_byte(1));
+		let transfer = builder
+			.balance_transfer(
+				AccountKeyring::Alice.to_account_id(),
+				AccountKeyring::Bob.to_account_id(),
+				1000,
+			)
+			.call()
+			.clone();
+
+		let session_call =
+			builder.session_call(AccountKeyring::Dave.to_account_id(), transfer.clone());
+
+		assert_eq!(
+			session_call.call(),
+			&TrustedCall::session_call(AccountKeyring::Dave.to_account_id(), Box::new(transfer))
+		);
+	}
+
+	#[test]
+	fn getter_expiry_is_covered_by_signature() {
+		let getter = GetterBuilder::new(1_000).nonce(AccountKeyring::Alice.to_account_id());
+		let extended_getter =
+			GetterBuilder::new(2_000).nonce(AccountKeyring::Alice.to_account_id());
+
+		assert_ne!(getter.signing_payload(), extended_getter.signing_payload());
+	}
+}
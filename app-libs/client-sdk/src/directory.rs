@@ -0,0 +1,177 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Resolution of a worker for a shard from the worker directory.
+//!
+//! Workers publish a [SignedWorkerRecord] with the enclave bridge's `publish_hash` call. A client
+//! collects the `PublishedHash` events, decodes them with [decode_published_record] and picks the
+//! candidates with [select_workers]. Whether the signer is a registered enclave, and when it has
+//! been attested, has to be looked up in the teerex registry on the parentchain, the SDK takes
+//! the lookup as a function.
+
+use crate::error::{Error, Result};
+use alloc::{collections::BTreeMap, format, vec::Vec};
+use codec::Decode;
+use itp_stf_primitives::types::ShardIdentifier;
+use itp_types::worker_directory::SignedWorkerRecord;
+use sp_core::ed25519;
+
+/// Decodes the `data` of a `PublishedHash` event and checks that it is a validly signed record
+/// committed to by the event's `hash`.
+pub fn decode_published_record(hash: &[u8; 32], data: &[u8]) -> Result<SignedWorkerRecord> {
+	let signed_record = SignedWorkerRecord::decode(&mut &data[..])?;
+	if &signed_record.hash() != hash {
+		return Err(Error::InvalidWorkerRecord(format!(
+			"published hash {:?} does not commit to the record",
+			hash
+		)))
+	}
+	if !signed_record.verify() {
+		return Err(Error::InvalidWorkerRecord("invalid signature".into()))
+	}
+	Ok(signed_record)
+}
+
+/// Workers serving `shard` whose latest record is at most `max_age_millis` old at `now`, most
+/// recently attested first.
+///
+/// `attested_at` is called with the signer and MRENCLAVE of the record and should return the
+/// attestation timestamp of the signer's registration on the parentchain, `None` if the signer
+/// isn't registered with that MRENCLAVE.
+pub fn select_workers<F>(
+	records: impl IntoIterator<Item = SignedWorkerRecord>,
+	shard: &ShardIdentifier,
+	now: u64,
+	max_age_millis: u64,
+	attested_at: F,
+) -> Vec<SignedWorkerRecord>
+where
+	F: Fn(&ed25519::Public, &[u8; 32]) -> Option<u64>,
+{
+	let mut latest: BTreeMap<ed25519::Public, SignedWorkerRecord> = BTreeMap::new();
+	for signed_record in records {
+		match latest.get(&signed_record.signer) {
+			Some(known) if known.record.published_at >= signed_record.record.published_at => {},
+			_ => {
+				latest.insert(signed_record.signer, signed_record);
+			},
+		}
+	}
+
+	let mut candidates: Vec<(u64, SignedWorkerRecord)> = latest
+		.into_values()
+		.filter(|r| r.record.serves(shard) && r.record.is_fresh(now, max_age_millis))
+		.filter_map(|r| Some((attested_at(&r.signer, &r.record.mrenclave)?, r)))
+		.collect();
+	candidates.sort_by(|(a_attested_at, a), (b_attested_at, b)| {
+		(b_attested_at, b.record.published_at).cmp(&(a_attested_at, a.record.published_at))
+	});
+	candidates.into_iter().map(|(_, r)| r).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use itp_types::worker_directory::WorkerRecord;
+	use sp_core::Pair;
+
+	const NOW: u64 = 1_700_001_000_000;
+	const MAX_AGE: u64 = 1_800_000;
+
+	fn shard() -> ShardIdentifier {
+		ShardIdentifier::repeat_byte(2)
+	}
+
+	fn signed_record(seed: u8, published_at: u64) -> SignedWorkerRecord {
+		let record = WorkerRecord {
+			mrenclave: [1; 32],
+			trusted_rpc_url: format!("wss://worker-{}.example:2000", seed).into_bytes(),
+			untrusted_rpc_url: format!("ws://worker-{}.example:2001", seed).into_bytes(),
+			shards: vec![shard()],
+			stf_version: 1,
+			published_at,
+		};
+		SignedWorkerRecord::new(record, &ed25519::Pair::from_seed(&[seed; 32]))
+	}
+
+	#[test]
+	fn published_record_is_decoded() {
+		let signed = signed_record(1, NOW);
+
+		let decoded = decode_published_record(&signed.hash(), &signed.encode()).unwrap();
+
+		assert_eq!(decoded, signed);
+	}
+
+	#[test]
+	fn record_not_matching_the_published_hash_is_rejected() {
+		let signed = signed_record(1, NOW);
+
+		let result = decode_published_record(&[0; 32], &signed.encode());
+
+		assert!(matches!(result, Err(Error::InvalidWorkerRecord(_))));
+	}
+
+	#[test]
+	fn record_with_invalid_signature_is_rejected() {
+		let mut signed = signed_record(1, NOW);
+		signed.record.trusted_rpc_url = b"wss://attacker.example:2000".to_vec();
+
+		let result = decode_published_record(&signed.hash(), &signed.encode());
+
+		assert!(matches!(result, Err(Error::InvalidWorkerRecord(_))));
+	}
+
+	#[test]
+	fn select_workers_keeps_latest_fresh_registered_records() {
+		let outdated = signed_record(1, NOW - 1000);
+		let latest = signed_record(1, NOW - 10);
+		let recently_attested = signed_record(2, NOW - 10);
+		let stale = signed_record(3, NOW - MAX_AGE - 1);
+		let unregistered = signed_record(4, NOW);
+		let recently_attested_signer = recently_attested.signer;
+		let unregistered_signer = unregistered.signer;
+
+		let selected = select_workers(
+			vec![outdated, latest.clone(), recently_attested.clone(), stale, unregistered],
+			&shard(),
+			NOW,
+			MAX_AGE,
+			|signer, _| match signer {
+				s if s == &unregistered_signer => None,
+				s if s == &recently_attested_signer => Some(NOW - 10),
+				_ => Some(NOW - 1000),
+			},
+		);
+
+		assert_eq!(selected, vec![recently_attested, latest]);
+	}
+
+	#[test]
+	fn select_workers_ignores_other_shards() {
+		let selected = select_workers(
+			vec![signed_record(1, NOW)],
+			&ShardIdentifier::repeat_byte(9),
+			NOW,
+			MAX_AGE,
+			|_, _| Some(NOW),
+		);
+
+		assert!(selected.is_empty());
+	}
+}
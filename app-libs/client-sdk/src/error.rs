@@ -0,0 +1,48 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use alloc::string::String;
+use derive_more::{Display, From};
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[derive(Debug, Display, From)]
+pub enum Error {
+	#[display(fmt = "Invalid shielding key: {}", _0)]
+	#[from(ignore)]
+	InvalidShieldingKey(String),
+	#[display(fmt = "Encryption failed: {}", _0)]
+	#[from(ignore)]
+	Encryption(String),
+	#[display(fmt = "Invalid rpc response: {}", _0)]
+	#[from(ignore)]
+	InvalidRpcResponse(String),
+	#[display(fmt = "Worker returned an error: {}", _0)]
+	#[from(ignore)]
+	Worker(String),
+	/// JSON-RPC error, `code` is one of [itp_rpc::error_codes] for errors of the worker.
+	#[display(fmt = "Worker returned error {}: {}", code, message)]
+	#[from(ignore)]
+	Rpc { code: i64, message: String },
+	#[display(fmt = "Invalid worker record: {}", _0)]
+	#[from(ignore)]
+	InvalidWorkerRecord(String),
+	#[display(fmt = "Codec error: {:?}", _0)]
+	Codec(codec::Error),
+	#[display(fmt = "Serialization error: {:?}", _0)]
+	Serialization(serde_json::Error),
+}
@@ -0,0 +1,57 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Client SDK to compose, sign and encrypt trusted operations for an integritee worker.
+//!
+//! Without the default `std` feature the crate is `no_std` and compiles to wasm32, so dApp
+//! frontends can use it directly (with the `wasm` feature, bindings are generated with
+//! wasm-bindgen). It doesn't depend on `ita-stf`, the trusted calls and getters are
+//! composed with the wire compatible [types] instead. It does not open
+//! any connections itself, it composes the JSON-RPC requests and decodes the responses,
+//! the transport (usually a websocket to the worker) is up to the user.
+//!
+//! A trusted call is submitted as follows:
+//! 1. fetch the shielding key with [rpc::shielding_key_request] and
+//!    [rpc::decode_shielding_key_response].
+//! 2. compose the call with a [TrustedCallBuilder] and sign its signing payload.
+//! 3. send the request composed by [rpc::submit_request], which encrypts the call
+//!    with the shielding key.
+//!
+//! Instead of a hard-coded worker URL, the worker can be picked from the records the workers
+//! publish on the parentchain, see [directory].
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(all(feature = "std", feature = "wasm"))]
+compile_error!("feature \"wasm\" requires the crate to be built with `--no-default-features`");
+
+extern crate alloc;
+
+pub use builders::*;
+pub use error::*;
+pub use shielding_key::ShieldingPublicKey;
+pub use types::*;
+
+pub mod builders;
+pub mod directory;
+pub mod error;
+pub mod rpc;
+pub mod shielding_key;
+pub mod types;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,263 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Composition of the worker's direct JSON-RPC requests and decoding of the responses.
+
+use crate::{
+	error::{Error, Result},
+	shielding_key::ShieldingPublicKey,
+	types::{Getter, TrustedCallSigned},
+};
+use alloc::{borrow::ToOwned, string::String, vec, vec::Vec};
+use codec::{Decode, Encode};
+use itp_rpc::{RpcErrorResponse, RpcRequest, RpcResponse, RpcReturnValue};
+use itp_stf_primitives::types::{ShardIdentifier, TrustedOperation};
+use itp_types::{
+	getter_stream::{GetterStreamChunk, GetterStreamRequest},
+	submission_receipt::SignedSubmissionReceipt,
+	DirectRequestStatus, Request,
+};
+use itp_utils::{FromHexPrefixed, ToHexPrefixed};
+
+pub fn shielding_key_request() -> Result<String> {
+	Ok(RpcRequest::compose_jsonrpc_call("author_getShieldingKey".to_owned(), vec![])?)
+}
+
+pub fn decode_shielding_key_response(response: &str) -> Result<ShieldingPublicKey> {
+	let key_json = String::decode(&mut decode_response(response)?.as_slice())?;
+	ShieldingPublicKey::from_json(&key_json)
+}
+
+/// Request to submit a trusted call, the call is encrypted with the `shielding_key`.
+///
+/// The worker will send status updates of the operation on the same connection.
+pub fn submit_request(
+	shard: ShardIdentifier,
+	operation: &TrustedOperation<TrustedCallSigned, Getter>,
+	shielding_key: &ShieldingPublicKey,
+) -> Result<String> {
+	let request = Request { shard, cyphertext: shielding_key.encrypt(&operation.encode())? };
+	Ok(RpcRequest::compose_jsonrpc_call(
+		"author_submitAndWatchExtrinsic".to_owned(),
+		vec![request.to_hex()],
+	)?)
+}
+
+/// Request to submit a trusted call without watching it, the worker returns a signed receipt.
+///
+/// Decode the response with [decode_submission_receipt_response].
+pub fn submit_with_receipt_request(
+	shard: ShardIdentifier,
+	operation: &TrustedOperation<TrustedCallSigned, Getter>,
+	shielding_key: &ShieldingPublicKey,
+) -> Result<String> {
+	let request = Request { shard, cyphertext: shielding_key.encrypt(&operation.encode())? };
+	Ok(RpcRequest::compose_jsonrpc_call(
+		"author_submitExtrinsicWithReceipt".to_owned(),
+		vec![request.to_hex()],
+	)?)
+}
+
+/// Decodes the receipt and checks that it is validly signed and commits to `operation`.
+///
+/// Whether the signer is a registered enclave of the shard has to be checked on the parentchain.
+pub fn decode_submission_receipt_response(
+	response: &str,
+	operation: &TrustedOperation<TrustedCallSigned, Getter>,
+) -> Result<SignedSubmissionReceipt> {
+	let receipt = SignedSubmissionReceipt::decode(&mut decode_response(response)?.as_slice())?;
+	if !receipt.verify() || receipt.receipt.operation_hash != operation.hash() {
+		return Err(Error::InvalidRpcResponse("invalid submission receipt".to_owned()))
+	}
+	Ok(receipt)
+}
+
+pub fn getter_request(shard: ShardIdentifier, getter: &Getter) -> Result<String> {
+	let request = Request { shard, cyphertext: getter.encode() };
+	Ok(RpcRequest::compose_jsonrpc_call("state_executeGetter".to_owned(), vec![request.to_hex()])?)
+}
+
+/// Request for a page of the audit log of dropped operations, see
+/// [crate::builders::GetterBuilder::audit_log]. The response is an encoded `Page<AuditEntry>`.
+pub fn audit_log_request(shard: ShardIdentifier, getter: &Getter) -> Result<String> {
+	let request = Request { shard, cyphertext: getter.encode() };
+	Ok(RpcRequest::compose_jsonrpc_call("state_getAuditLog".to_owned(), vec![request.to_hex()])?)
+}
+
+/// Request for chunk `chunk` of a (large) getter result, start with chunk 0.
+///
+/// The same signed getter has to be used for all chunks. The concatenated chunks can be decoded
+/// with [decode_streamed_getter_result].
+pub fn getter_stream_request(
+	shard: ShardIdentifier,
+	getter: &Getter,
+	chunk: u32,
+) -> Result<String> {
+	let request =
+		GetterStreamRequest { request: Request { shard, cyphertext: getter.encode() }, chunk };
+	Ok(RpcRequest::compose_jsonrpc_call(
+		"state_executeGetterStream".to_owned(),
+		vec![request.to_hex()],
+	)?)
+}
+
+pub fn decode_getter_stream_response(response: &str) -> Result<GetterStreamChunk> {
+	Ok(GetterStreamChunk::decode(&mut decode_response(response)?.as_slice())?)
+}
+
+/// Decodes the concatenated data of all chunks of a getter stream.
+pub fn decode_streamed_getter_result<T: Decode>(data: &[u8]) -> Result<Option<T>> {
+	let maybe_value: Option<Vec<u8>> = Option::decode(&mut &data[..])?;
+	maybe_value
+		.map(|value| T::decode(&mut value.as_slice()).map_err(Error::from))
+		.transpose()
+}
+
+/// Decodes the value returned by a getter, `None` if the state did not contain a value.
+pub fn decode_getter_response<T: Decode>(response: &str) -> Result<Option<T>> {
+	decode_streamed_getter_result(&decode_response(response)?)
+}
+
+/// Decodes the response of any request and returns the encoded return value.
+pub fn decode_response(response: &str) -> Result<Vec<u8>> {
+	if let Ok(error_response) = serde_json::from_str::<RpcErrorResponse>(response) {
+		return Err(Error::Rpc {
+			code: error_response.error.code,
+			message: error_response.error.message,
+		})
+	}
+	let rpc_response: RpcResponse = serde_json::from_str(response)?;
+	let return_value = RpcReturnValue::from_hex(&rpc_response.result)
+		.map_err(|e| Error::InvalidRpcResponse(alloc::format!("{:?}", e)))?;
+
+	if return_value.status == DirectRequestStatus::Error {
+		let error_msg = String::decode(&mut return_value.value.as_slice())?;
+		return Err(Error::Worker(error_msg))
+	}
+	Ok(return_value.value)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::types::PublicGetter;
+
+	fn rpc_response(return_value: RpcReturnValue) -> String {
+		serde_json::to_string(&RpcResponse {
+			jsonrpc: "2.0".to_owned(),
+			result: return_value.to_hex(),
+			id: 1,
+		})
+		.unwrap()
+	}
+
+	#[test]
+	fn getter_request_contains_plain_getter() {
+		let shard = ShardIdentifier::repeat_byte(3);
+		let getter = Getter::public(PublicGetter::some_value);
+
+		let request: RpcRequest =
+			serde_json::from_str(&getter_request(shard, &getter).unwrap()).unwrap();
+
+		assert_eq!(request.method, "state_executeGetter");
+		let decoded = Request::from_hex(&request.params[0]).unwrap();
+		assert_eq!(decoded.shard, shard);
+		assert_eq!(decoded.cyphertext, getter.encode());
+	}
+
+	#[test]
+	fn streamed_getter_chunks_are_decoded() {
+		let balance: u128 = 42;
+		let encoded_result = Some(balance.encode()).encode();
+		let (first, second) = encoded_result.split_at(3);
+
+		let mut data = Vec::new();
+		for (chunk, part) in [first, second].iter().enumerate() {
+			let response = rpc_response(RpcReturnValue::new(
+				GetterStreamChunk { chunk: chunk as u32, total_chunks: 2, data: part.to_vec() }
+					.encode(),
+				false,
+				DirectRequestStatus::Ok,
+			));
+			data.extend(decode_getter_stream_response(&response).unwrap().data);
+		}
+
+		assert_eq!(decode_streamed_getter_result::<u128>(&data).unwrap(), Some(42));
+	}
+
+	#[test]
+	fn submission_receipt_must_commit_to_the_operation() {
+		use crate::builders::TrustedCallBuilder;
+		use itp_stf_primitives::types::KeyPair;
+		use itp_types::submission_receipt::SubmissionReceipt;
+		use sp_core::{ed25519, Pair};
+		use sp_keyring::AccountKeyring;
+
+		let shard = ShardIdentifier::repeat_byte(1);
+		let alice = KeyPair::Sr25519(alloc::boxed::Box::new(AccountKeyring::Alice.pair()));
+		let operation = |amount| {
+			let call = TrustedCallBuilder::new([0u8; 32], shard).balance_transfer(
+				AccountKeyring::Alice.to_account_id(),
+				AccountKeyring::Bob.to_account_id(),
+				amount,
+			);
+			let signature = alice.sign(&call.signing_payload());
+			TrustedOperation::<TrustedCallSigned, Getter>::direct_call(
+				call.with_signature(signature),
+			)
+		};
+		let receipt = SignedSubmissionReceipt::new(
+			SubmissionReceipt { shard, operation_hash: operation(10).hash(), accepted_at: 1 },
+			&ed25519::Pair::from_seed(&[5u8; 32]),
+		);
+		let response =
+			rpc_response(RpcReturnValue::new(receipt.encode(), false, DirectRequestStatus::Ok));
+
+		assert_eq!(decode_submission_receipt_response(&response, &operation(10)).unwrap(), receipt);
+		assert!(decode_submission_receipt_response(&response, &operation(11)).is_err());
+	}
+
+	#[test]
+	fn decode_getter_response_works() {
+		let balance: u128 = 42;
+		let response = rpc_response(RpcReturnValue::new(
+			Some(balance.encode()).encode(),
+			false,
+			DirectRequestStatus::Ok,
+		));
+
+		assert_eq!(decode_getter_response::<u128>(&response).unwrap(), Some(42));
+	}
+
+	#[test]
+	fn decode_response_returns_worker_error() {
+		let response = rpc_response(RpcReturnValue::from_error_message("nope"));
+
+		assert!(matches!(decode_response(&response), Err(Error::Worker(msg)) if msg == "nope"));
+	}
+
+	#[test]
+	fn decode_response_returns_rpc_error_code() {
+		let response =
+			r#"{"jsonrpc":"2.0","error":{"code":2003,"message":"Shard vault undefined"},"id":1}"#;
+
+		assert!(matches!(
+			decode_response(response),
+			Err(Error::Rpc { code: itp_rpc::error_codes::SHARD_VAULT_NOT_SET, .. })
+		));
+	}
+}
@@ -0,0 +1,115 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Encryption of trusted operations with the worker's RSA3072 shielding key.
+//!
+//! The envelope is compatible with the `Rsa3072PubKey` of the sgx crypto helper the
+//! enclave uses: the plaintext is split into chunks that are encrypted separately with
+//! RSA-OAEP (SHA-256) and concatenated.
+
+use crate::error::{Error, Result};
+use alloc::{format, vec::Vec};
+use rand_core::OsRng;
+use rsa::{traits::PublicKeyParts, BigUint, Oaep, RsaPublicKey};
+use serde::Deserialize;
+use sha2::Sha256;
+
+const SHA256_SIZE: usize = 32;
+
+/// JSON representation of the shielding key, as returned by `author_getShieldingKey`.
+/// Modulus and public exponent are little endian.
+#[derive(Deserialize)]
+struct Rsa3072PubKeyJson {
+	n: Vec<u8>,
+	e: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShieldingPublicKey(RsaPublicKey);
+
+impl ShieldingPublicKey {
+	pub fn from_json(json: &str) -> Result<Self> {
+		let key: Rsa3072PubKeyJson = serde_json::from_str(json)?;
+		Self::from_le_components(&key.n, &key.e)
+	}
+
+	pub fn from_le_components(modulus: &[u8], exponent: &[u8]) -> Result<Self> {
+		RsaPublicKey::new(BigUint::from_bytes_le(modulus), BigUint::from_bytes_le(exponent))
+			.map(ShieldingPublicKey)
+			.map_err(|e| Error::InvalidShieldingKey(format!("{:?}", e)))
+	}
+
+	pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+		let mut ciphertext = Vec::new();
+		for chunk in plaintext.chunks(self.max_chunk_size()) {
+			let mut encrypted_chunk = self
+				.0
+				.encrypt(&mut OsRng, Oaep::new::<Sha256>(), chunk)
+				.map_err(|e| Error::Encryption(format!("{:?}", e)))?;
+			ciphertext.append(&mut encrypted_chunk);
+		}
+		Ok(ciphertext)
+	}
+
+	/// Maximal plaintext size that fits into one OAEP block.
+	fn max_chunk_size(&self) -> usize {
+		self.0.size() - 2 * SHA256_SIZE - 2
+	}
+}
+
+impl From<RsaPublicKey> for ShieldingPublicKey {
+	fn from(key: RsaPublicKey) -> Self {
+		ShieldingPublicKey(key)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rsa::RsaPrivateKey;
+
+	#[test]
+	fn encrypted_chunks_can_be_decrypted() {
+		let private_key = RsaPrivateKey::new(&mut OsRng, 2048).unwrap();
+		let shielding_key = ShieldingPublicKey::from(RsaPublicKey::from(&private_key));
+		let plaintext: Vec<u8> = (0..500u32).map(|i| i as u8).collect();
+
+		let ciphertext = shielding_key.encrypt(&plaintext).unwrap();
+
+		// 500 bytes don't fit into one 190 byte chunk of a 2048 bit key.
+		assert_eq!(ciphertext.len(), 3 * 256);
+		let decrypted: Vec<u8> = ciphertext
+			.chunks(256)
+			.flat_map(|chunk| private_key.decrypt(Oaep::new::<Sha256>(), chunk).unwrap())
+			.collect();
+		assert_eq!(decrypted, plaintext);
+	}
+
+	#[test]
+	fn from_json_works() {
+		let private_key = RsaPrivateKey::new(&mut OsRng, 2048).unwrap();
+		let json = format!(
+			"{{\"n\":{:?},\"e\":{:?}}}",
+			private_key.n().to_bytes_le(),
+			private_key.e().to_bytes_le()
+		);
+
+		let shielding_key = ShieldingPublicKey::from_json(&json).unwrap();
+
+		assert_eq!(shielding_key, ShieldingPublicKey::from(RsaPublicKey::from(&private_key)));
+	}
+}
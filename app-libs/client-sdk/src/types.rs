@@ -0,0 +1,181 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Trusted calls and getters as they are sent to the worker.
+//!
+//! `ita-stf` can't be built for wasm32 (it contains the whole sidechain runtime), so the SDK
+//! has its own copies of the types. They only contain the variants the SDK composes, but
+//! encode exactly like the `ita_stf` types, the variant indices are given explicitly. The
+//! tests in `ita_stf::trusted_call` check that both stay in sync.
+
+use alloc::{boxed::Box, vec::Vec};
+use codec::{Decode, Encode};
+use itp_stf_primitives::{
+	account_export::ExportKey,
+	pagination::PageRequest,
+	session_keys::SessionKey,
+	traits::TrustedCallSigning,
+	types::{AccountId, KeyPair, ShardIdentifier, Signature, TrustedOperation},
+};
+use itp_types::{parentchain::ParentchainAssetId, Balance, Index, SidechainTimestamp};
+
+/// Validity of a getter that is signed right before it is sent, same as the
+/// `ita_stf::DEFAULT_GETTER_VALIDITY`.
+pub const DEFAULT_GETTER_VALIDITY: SidechainTimestamp = 60_000;
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum TrustedCall {
+	#[codec(index = 2)]
+	balance_transfer(AccountId, AccountId, Balance),
+	#[codec(index = 3)]
+	balance_unshield(AccountId, AccountId, Balance, ShardIdentifier),
+	#[codec(index = 7)]
+	balance_unshield_batched(AccountId, AccountId, Balance),
+	#[codec(index = 11)]
+	assets_transfer(AccountId, AccountId, ParentchainAssetId, Balance),
+	#[codec(index = 12)]
+	assets_unshield(AccountId, AccountId, ParentchainAssetId, Balance),
+	#[codec(index = 16)]
+	session_key_register(AccountId, AccountId, SessionKey),
+	#[codec(index = 17)]
+	session_key_revoke(AccountId, AccountId),
+	#[codec(index = 18)]
+	session_call(AccountId, Box<TrustedCall>),
+	#[codec(index = 19)]
+	sponsored_call(AccountId, Box<TrustedCallSigned>),
+}
+
+impl TrustedCall {
+	/// Name of the call variant, as used in the scope of a [SessionKey].
+	pub fn name(&self) -> &'static str {
+		match self {
+			Self::balance_transfer(..) => "balance_transfer",
+			Self::balance_unshield(..) => "balance_unshield",
+			Self::balance_unshield_batched(..) => "balance_unshield_batched",
+			Self::assets_transfer(..) => "assets_transfer",
+			Self::assets_unshield(..) => "assets_unshield",
+			Self::session_key_register(..) => "session_key_register",
+			Self::session_key_revoke(..) => "session_key_revoke",
+			Self::session_call(..) => "session_call",
+			Self::sponsored_call(..) => "sponsored_call",
+		}
+	}
+}
+
+impl TrustedCallSigning<TrustedCallSigned> for TrustedCall {
+	fn signing_payload(
+		&self,
+		nonce: Index,
+		mrenclave: &[u8; 32],
+		shard: &ShardIdentifier,
+	) -> Vec<u8> {
+		let mut payload = self.encode();
+		payload.append(&mut nonce.encode());
+		payload.append(&mut mrenclave.encode());
+		payload.append(&mut shard.encode());
+		payload
+	}
+
+	fn with_signature(&self, nonce: Index, signature: Signature) -> TrustedCallSigned {
+		TrustedCallSigned { call: self.clone(), nonce, signature }
+	}
+}
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct TrustedCallSigned {
+	pub call: TrustedCall,
+	pub nonce: Index,
+	pub signature: Signature,
+}
+
+impl TrustedCallSigned {
+	pub fn into_trusted_operation(
+		self,
+		direct: bool,
+	) -> TrustedOperation<TrustedCallSigned, Getter> {
+		match direct {
+			true => TrustedOperation::direct_call(self),
+			false => TrustedOperation::indirect_call(self),
+		}
+	}
+}
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum Getter {
+	#[codec(index = 0)]
+	public(PublicGetter),
+	#[codec(index = 1)]
+	trusted(TrustedGetterSigned),
+}
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum PublicGetter {
+	#[codec(index = 0)]
+	some_value,
+	#[codec(index = 1)]
+	fee_config,
+	#[codec(index = 2)]
+	fee_estimate(TrustedCall),
+	#[codec(index = 3)]
+	reserves_report,
+}
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum TrustedGetter {
+	#[codec(index = 0)]
+	free_balance(AccountId),
+	#[codec(index = 1)]
+	reserved_balance(AccountId),
+	#[codec(index = 2)]
+	nonce(AccountId),
+	#[codec(index = 3)]
+	rent_status(AccountId),
+	#[codec(index = 4)]
+	asset_balance(AccountId, ParentchainAssetId),
+	#[codec(index = 5)]
+	confidential_events(AccountId),
+	#[codec(index = 6)]
+	confidential_events_page(AccountId, PageRequest),
+	#[codec(index = 7)]
+	audit_log(AccountId, PageRequest),
+	#[codec(index = 8)]
+	account_data_export(AccountId, ExportKey),
+}
+
+impl TrustedGetter {
+	/// The payload that has to be signed by the sender account, the getter is rejected after
+	/// the sidechain timestamp `valid_until` (unix millis).
+	pub fn signing_payload(&self, valid_until: SidechainTimestamp) -> Vec<u8> {
+		(self, valid_until).encode()
+	}
+
+	pub fn sign(&self, pair: &KeyPair, valid_until: SidechainTimestamp) -> TrustedGetterSigned {
+		let signature = pair.sign(self.signing_payload(valid_until).as_slice());
+		TrustedGetterSigned { getter: self.clone(), valid_until, signature }
+	}
+}
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct TrustedGetterSigned {
+	pub getter: TrustedGetter,
+	pub valid_until: SidechainTimestamp,
+	pub signature: Signature,
+}
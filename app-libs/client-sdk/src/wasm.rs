@@ -0,0 +1,223 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Javascript bindings.
+//!
+//! Accounts, hashes and signatures are passed as `0x` prefixed hex strings, balances as
+//! decimal strings (they don't fit into a javascript number). Signatures are expected to be
+//! SCALE encoded `MultiSignature`s, as returned by the `signRaw` of the polkadot-js extension.
+
+use crate::{
+	builders::{GetterBuilder, TrustedCallBuilder, UnsignedTrustedCall, UnsignedTrustedGetter},
+	rpc,
+	shielding_key::ShieldingPublicKey,
+	Error,
+};
+use alloc::{string::String, vec::Vec};
+use codec::Decode;
+use itp_stf_primitives::types::{AccountId, ShardIdentifier, Signature};
+use itp_types::Balance;
+use itp_utils::FromHexPrefixed;
+use wasm_bindgen::prelude::*;
+
+impl From<Error> for JsValue {
+	fn from(error: Error) -> Self {
+		JsValue::from_str(&alloc::format!("{}", error))
+	}
+}
+
+#[wasm_bindgen]
+pub struct Client {
+	mrenclave: [u8; 32],
+	shard: ShardIdentifier,
+	shielding_key: ShieldingPublicKey,
+}
+
+#[wasm_bindgen]
+impl Client {
+	/// `shielding_key_response` is the raw response to [shielding_key_request].
+	#[wasm_bindgen(constructor)]
+	pub fn new(
+		mrenclave: &str,
+		shard: &str,
+		shielding_key_response: &str,
+	) -> Result<Client, JsValue> {
+		Ok(Client {
+			mrenclave: decode_hex(mrenclave)?,
+			shard: decode_hex(shard)?,
+			shielding_key: rpc::decode_shielding_key_response(shielding_key_response)?,
+		})
+	}
+
+	pub fn balance_transfer(
+		&self,
+		from: &str,
+		to: &str,
+		amount: &str,
+		nonce: u32,
+	) -> Result<TrustedCall, JsValue> {
+		let call = TrustedCallBuilder::new(self.mrenclave, self.shard)
+			.nonce(nonce)
+			.balance_transfer(decode_hex(from)?, decode_hex(to)?, parse_balance(amount)?);
+		Ok(TrustedCall(call))
+	}
+
+	pub fn balance_unshield(
+		&self,
+		from: &str,
+		beneficiary: &str,
+		amount: &str,
+		nonce: u32,
+	) -> Result<TrustedCall, JsValue> {
+		let call = TrustedCallBuilder::new(self.mrenclave, self.shard)
+			.nonce(nonce)
+			.balance_unshield(decode_hex(from)?, decode_hex(beneficiary)?, parse_balance(amount)?);
+		Ok(TrustedCall(call))
+	}
+
+	/// Registers a session key, `call_names` is a comma separated list of the calls it may
+	/// submit, e.g. `balance_transfer`, and `valid_until` the unix timestamp in millis after
+	/// which it expires.
+	pub fn session_key_register(
+		&self,
+		owner: &str,
+		delegate: &str,
+		call_names: &str,
+		valid_until: u64,
+		nonce: u32,
+	) -> Result<TrustedCall, JsValue> {
+		let call_names: Vec<&str> = call_names.split(',').map(str::trim).collect();
+		let call = TrustedCallBuilder::new(self.mrenclave, self.shard)
+			.nonce(nonce)
+			.session_key_register(
+				decode_hex(owner)?,
+				decode_hex(delegate)?,
+				valid_until,
+				&call_names,
+			);
+		Ok(TrustedCall(call))
+	}
+
+	pub fn session_key_revoke(
+		&self,
+		owner: &str,
+		delegate: &str,
+		nonce: u32,
+	) -> Result<TrustedCall, JsValue> {
+		let call = TrustedCallBuilder::new(self.mrenclave, self.shard)
+			.nonce(nonce)
+			.session_key_revoke(decode_hex(owner)?, decode_hex(delegate)?);
+		Ok(TrustedCall(call))
+	}
+
+	/// Transfer from `from` that is signed by its session key `delegate`, `nonce` is the one of
+	/// the session key.
+	pub fn session_balance_transfer(
+		&self,
+		delegate: &str,
+		from: &str,
+		to: &str,
+		amount: &str,
+		nonce: u32,
+	) -> Result<TrustedCall, JsValue> {
+		let builder = TrustedCallBuilder::new(self.mrenclave, self.shard).nonce(nonce);
+		let transfer =
+			builder.balance_transfer(decode_hex(from)?, decode_hex(to)?, parse_balance(amount)?);
+		let call = builder.session_call(decode_hex(delegate)?, transfer.call().clone());
+		Ok(TrustedCall(call))
+	}
+
+	/// JSON-RPC request submitting the signed call, encrypted with the shielding key.
+	pub fn submit_request(&self, call: &TrustedCall, signature: &str) -> Result<String, JsValue> {
+		let operation = call.0.clone().into_trusted_operation(decode_hex(signature)?, true);
+		Ok(rpc::submit_request(self.shard, &operation, &self.shielding_key)?)
+	}
+
+	/// JSON-RPC request estimating the fee of the call, the result is a SCALE encoded `u128`.
+	pub fn fee_estimate_request(&self, call: &TrustedCall) -> Result<String, JsValue> {
+		Ok(rpc::getter_request(self.shard, &GetterBuilder::fee_estimate(&call.0))?)
+	}
+
+	/// JSON-RPC request executing the signed getter.
+	pub fn getter_request(
+		&self,
+		getter: &TrustedGetter,
+		signature: &str,
+	) -> Result<String, JsValue> {
+		let getter = getter.0.with_signature(decode_hex::<Signature>(signature)?);
+		Ok(rpc::getter_request(self.shard, &getter)?)
+	}
+}
+
+#[wasm_bindgen]
+pub struct TrustedCall(UnsignedTrustedCall);
+
+#[wasm_bindgen]
+impl TrustedCall {
+	pub fn signing_payload(&self) -> Vec<u8> {
+		self.0.signing_payload()
+	}
+}
+
+#[wasm_bindgen]
+pub struct TrustedGetter(UnsignedTrustedGetter);
+
+#[wasm_bindgen]
+impl TrustedGetter {
+	/// `valid_until` is the unix timestamp in millis after which the worker rejects the getter.
+	pub fn free_balance(account: &str, valid_until: u64) -> Result<TrustedGetter, JsValue> {
+		Ok(TrustedGetter(
+			GetterBuilder::new(valid_until).free_balance(decode_hex::<AccountId>(account)?),
+		))
+	}
+
+	/// `valid_until` is the unix timestamp in millis after which the worker rejects the getter.
+	pub fn nonce(account: &str, valid_until: u64) -> Result<TrustedGetter, JsValue> {
+		Ok(TrustedGetter(GetterBuilder::new(valid_until).nonce(decode_hex::<AccountId>(account)?)))
+	}
+
+	pub fn signing_payload(&self) -> Vec<u8> {
+		self.0.signing_payload()
+	}
+}
+
+#[wasm_bindgen]
+pub fn shielding_key_request() -> Result<String, JsValue> {
+	Ok(rpc::shielding_key_request()?)
+}
+
+/// Decodes the response of a balance getter as decimal string, `undefined` if there is no balance.
+#[wasm_bindgen]
+pub fn decode_balance_response(response: &str) -> Result<Option<String>, JsValue> {
+	Ok(rpc::decode_getter_response::<Balance>(response)?.map(|b| alloc::format!("{}", b)))
+}
+
+/// Decodes the response of a nonce getter, `undefined` if the account does not exist yet.
+#[wasm_bindgen]
+pub fn decode_nonce_response(response: &str) -> Result<Option<u32>, JsValue> {
+	Ok(rpc::decode_getter_response::<u32>(response)?)
+}
+
+fn decode_hex<T: Decode>(hex: &str) -> Result<T, JsValue> {
+	T::from_hex(hex).map_err(|e| JsValue::from_str(&alloc::format!("invalid hex value: {:?}", e)))
+}
+
+fn parse_balance(amount: &str) -> Result<Balance, JsValue> {
+	amount
+		.parse()
+		.map_err(|_| JsValue::from_str(&alloc::format!("invalid balance: {}", amount)))
+}
@@ -34,6 +34,10 @@ pub enum Error {
 	InvalidCryptoCurrencyId,
 	#[error("Invalid id for fiat currency")]
 	InvalidFiatCurrencyId,
+	#[error("Unknown data source: {0}")]
+	UnknownDataSource(String),
+	#[error("Only {responded} of the required {required} data sources responded for {feed}")]
+	NotEnoughSources { feed: String, responded: usize, required: usize },
 	#[error(transparent)]
 	Other(#[from] Box<dyn std::error::Error + Sync + Send + 'static>),
 }
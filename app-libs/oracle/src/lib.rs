@@ -32,9 +32,11 @@ pub mod sgx_reexport_prelude {
 	pub use url_sgx as url;
 }
 
-use crate::{error::Error, metrics_exporter::MetricsExporter};
+use crate::{
+	error::Error, metrics_exporter::MetricsExporter, traits::DataSource, types::TradingPair,
+};
 use itp_ocall_api::EnclaveMetricsOCallApi;
-use std::sync::Arc;
+use std::{boxed::Box, string::String, sync::Arc, vec::Vec};
 
 pub mod error;
 pub mod metrics_exporter;
@@ -42,12 +44,15 @@ pub mod traits;
 pub mod types;
 
 pub mod oracles;
-pub use oracles::{exchange_rate_oracle::ExchangeRateOracle, weather_oracle::WeatherOracle};
+pub use oracles::{
+	exchange_rate_oracle::ExchangeRateOracle, median_oracle::MedianOracle,
+	weather_oracle::WeatherOracle,
+};
 
 pub mod oracle_sources;
 pub use oracle_sources::{
 	coin_gecko::CoinGeckoSource, coin_market_cap::CoinMarketCapSource,
-	weather_oracle_source::WeatherOracleSource,
+	exchange_rate_data_source::ExchangeRateDataSource, weather_oracle_source::WeatherOracleSource,
 };
 
 #[cfg(test)]
@@ -62,6 +67,8 @@ pub type CoinGeckoExchangeRateOracle<OCallApi> =
 pub type CoinMarketCapExchangeRateOracle<OCallApi> =
 	ExchangeRateOracle<CoinMarketCapSource, MetricsExporter<OCallApi>>;
 
+pub type MedianExchangeRateOracle<OCallApi> = MedianOracle<TradingPair, MetricsExporter<OCallApi>>;
+
 pub type OpenMeteoWeatherOracle<OCallApi> =
 	WeatherOracle<WeatherOracleSource, MetricsExporter<OCallApi>>;
 
@@ -82,3 +89,25 @@ pub fn create_open_meteo_weather_oracle<OCallApi: EnclaveMetricsOCallApi>(
 ) -> OpenMeteoWeatherOracle<OCallApi> {
 	WeatherOracle::new(WeatherOracleSource {}, Arc::new(MetricsExporter::new(ocall_api)))
 }
+
+/// Creates the exchange rate data source with the given id, e.g. `coin_gecko`.
+pub fn create_exchange_rate_source(id: &str) -> Result<Box<dyn DataSource<TradingPair>>, Error> {
+	match id {
+		"coin_gecko" => Ok(Box::new(ExchangeRateDataSource(CoinGeckoSource))),
+		"coin_market_cap" => Ok(Box::new(ExchangeRateDataSource(CoinMarketCapSource))),
+		_ => Err(Error::UnknownDataSource(id.into())),
+	}
+}
+
+/// Creates an oracle that publishes the median exchange rate of the sources with the given ids.
+pub fn create_median_exchange_rate_oracle<OCallApi: EnclaveMetricsOCallApi>(
+	ocall_api: Arc<OCallApi>,
+	source_ids: &[String],
+	min_sources: usize,
+) -> Result<MedianExchangeRateOracle<OCallApi>, Error> {
+	let sources = source_ids
+		.iter()
+		.map(|id| create_exchange_rate_source(id))
+		.collect::<Result<Vec<_>, _>>()?;
+	Ok(MedianOracle::new(sources, min_sources, Arc::new(MetricsExporter::new(ocall_api))))
+}
@@ -24,8 +24,8 @@ use std::sync::RwLock;
 use crate::{
 	error::Error,
 	metrics_exporter::ExportMetrics,
-	traits::OracleSource,
-	types::{ExchangeRate, TradingPair},
+	traits::{DataSource, OracleSource},
+	types::{ExchangeRate, OracleValue, TradingPair},
 };
 use itc_rest_client::{
 	http_client::{HttpClient, SendWithCertificateVerification},
@@ -118,3 +118,42 @@ impl<OracleSourceInfo> OracleSource<OracleSourceInfo> for OracleSourceMock {
 		Ok(42.3f32)
 	}
 }
+
+/// Mock data source, returns the configured value or an error if there is none.
+pub(crate) struct DataSourceMock {
+	id: String,
+	value: Option<OracleValue>,
+}
+
+impl DataSourceMock {
+	pub fn new(id: &str, value: Option<OracleValue>) -> Self {
+		DataSourceMock { id: id.to_string(), value }
+	}
+}
+
+impl<Feed> DataSource<Feed> for DataSourceMock {
+	fn id(&self) -> String {
+		self.id.clone()
+	}
+
+	fn url(&self) -> Result<Url, Error> {
+		Url::parse("https://mock.base.url").map_err(|e| Error::Other(format!("{:?}", e).into()))
+	}
+
+	fn pinned_certificates(&self) -> Vec<String> {
+		vec!["MOCK_CERTIFICATE".to_string()]
+	}
+
+	fn timeout(&self) -> Option<Duration> {
+		None
+	}
+
+	fn fetch(
+		&self,
+		_rest_client: &mut RestClient<HttpClient<SendWithCertificateVerification>>,
+		_feed: &Feed,
+	) -> Result<OracleValue, Error> {
+		self.value
+			.ok_or_else(|| Error::NoValidData(self.id.clone(), "mock".to_string()))
+	}
+}
@@ -0,0 +1,64 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use crate::sgx_reexport_prelude::*;
+
+use crate::{
+	traits::{DataSource, OracleSource},
+	types::{OracleValue, TradingInfo, TradingPair},
+	Error,
+};
+use core::time::Duration;
+use itc_rest_client::{
+	http_client::{HttpClient, SendWithCertificateVerification},
+	rest_client::RestClient,
+};
+use std::{string::String, vec::Vec};
+use url::Url;
+
+/// Adapts an exchange rate [OracleSource] to a [DataSource] of trading pairs.
+#[derive(Default)]
+pub struct ExchangeRateDataSource<OracleSourceType>(pub OracleSourceType);
+
+impl<OracleSourceType> DataSource<TradingPair> for ExchangeRateDataSource<OracleSourceType>
+where
+	OracleSourceType: OracleSource<TradingInfo> + Send + Sync,
+{
+	fn id(&self) -> String {
+		self.0.metrics_id()
+	}
+
+	fn url(&self) -> Result<Url, Error> {
+		self.0.base_url()
+	}
+
+	fn pinned_certificates(&self) -> Vec<String> {
+		self.0.root_certificates_content()
+	}
+
+	fn timeout(&self) -> Option<Duration> {
+		self.0.request_timeout()
+	}
+
+	fn fetch(
+		&self,
+		rest_client: &mut RestClient<HttpClient<SendWithCertificateVerification>>,
+		feed: &TradingPair,
+	) -> Result<OracleValue, Error> {
+		self.0.execute_exchange_rate_request(rest_client, feed.clone())
+	}
+}
@@ -0,0 +1,199 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use crate::sgx_reexport_prelude::*;
+
+use crate::{metrics_exporter::ExportMetrics, traits::DataSource, types::OracleValue, Error};
+use core::fmt::Debug;
+use itc_rest_client::{
+	http_client::{HttpClient, SendWithCertificateVerification},
+	rest_client::RestClient,
+};
+use log::*;
+use std::{boxed::Box, format, string::String, sync::Arc, time::Instant, vec::Vec};
+
+/// Value aggregated over several data sources.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregatedValue {
+	pub value: OracleValue,
+	/// Ids of the sources that responded and were included in the aggregation.
+	pub sources: Vec<String>,
+}
+
+pub trait GetAggregatedValue<Feed> {
+	/// Fetch `feed` from every data source and aggregate the responses.
+	fn get_aggregated_value(&self, feed: &Feed) -> Result<AggregatedValue, Error>;
+}
+
+/// Oracle that publishes the median over the values of all its data sources.
+///
+/// Sources that fail are skipped, as long as at least `min_sources` respond.
+pub struct MedianOracle<Feed, MetricsExporter> {
+	sources: Vec<Box<dyn DataSource<Feed>>>,
+	min_sources: usize,
+	metrics_exporter: Arc<MetricsExporter>,
+}
+
+impl<Feed, MetricsExporter> MedianOracle<Feed, MetricsExporter> {
+	pub fn new(
+		sources: Vec<Box<dyn DataSource<Feed>>>,
+		min_sources: usize,
+		metrics_exporter: Arc<MetricsExporter>,
+	) -> Self {
+		MedianOracle { sources, min_sources: min_sources.max(1), metrics_exporter }
+	}
+
+	pub fn source_ids(&self) -> Vec<String> {
+		self.sources.iter().map(|s| s.id()).collect()
+	}
+}
+
+impl<Feed, MetricsExporter> MedianOracle<Feed, MetricsExporter>
+where
+	MetricsExporter: ExportMetrics<Feed>,
+{
+	fn fetch(&self, source: &dyn DataSource<Feed>, feed: &Feed) -> Result<OracleValue, Error> {
+		self.metrics_exporter.increment_number_requests(source.id());
+		let timer_start = Instant::now();
+
+		let http_client = HttpClient::new(
+			SendWithCertificateVerification::new(source.pinned_certificates()),
+			true,
+			source.timeout(),
+			None,
+			None,
+		);
+		let mut rest_client = RestClient::new(http_client, source.url()?);
+		let value = source.fetch(&mut rest_client, feed)?;
+
+		self.metrics_exporter.record_response_time(source.id(), timer_start);
+		Ok(value)
+	}
+}
+
+impl<Feed, MetricsExporter> GetAggregatedValue<Feed> for MedianOracle<Feed, MetricsExporter>
+where
+	Feed: Debug,
+	MetricsExporter: ExportMetrics<Feed>,
+{
+	fn get_aggregated_value(&self, feed: &Feed) -> Result<AggregatedValue, Error> {
+		let mut values = Vec::with_capacity(self.sources.len());
+		let mut sources = Vec::with_capacity(self.sources.len());
+
+		for source in self.sources.iter() {
+			match self.fetch(source.as_ref(), feed) {
+				Ok(value) => {
+					debug!("Data source {} returned {:?} for {:?}", source.id(), value, feed);
+					values.push(value);
+					sources.push(source.id());
+				},
+				Err(e) =>
+					error!("Fetching {:?} from data source {} failed: {}", feed, source.id(), e),
+			}
+		}
+
+		if values.len() < self.min_sources {
+			return Err(Error::NotEnoughSources {
+				feed: format!("{:?}", feed),
+				responded: values.len(),
+				required: self.min_sources,
+			})
+		}
+
+		let value = median(values).ok_or_else(|| {
+			Error::NoValidData(self.source_ids().join(","), format!("{:?}", feed))
+		})?;
+		Ok(AggregatedValue { value, sources })
+	}
+}
+
+/// Median of `values`, the mean of the two middle values for an even number of values.
+pub fn median(mut values: Vec<OracleValue>) -> Option<OracleValue> {
+	values.sort();
+	let middle = values.len() / 2;
+	match values.len() {
+		0 => None,
+		len if len % 2 == 1 => Some(values[middle]),
+		_ => {
+			let (lower, upper) = (values[middle - 1], values[middle]);
+			Some(lower + (upper - lower) / OracleValue::from_num(2))
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		mock::{DataSourceMock, MetricsExporterMock},
+		types::TradingPair,
+	};
+	use core::assert_matches::assert_matches;
+
+	fn value(v: f32) -> OracleValue {
+		OracleValue::from_num(v)
+	}
+
+	fn oracle(
+		responses: Vec<Option<f32>>,
+		min_sources: usize,
+	) -> (MedianOracle<TradingPair, MetricsExporterMock>, Arc<MetricsExporterMock>) {
+		let metrics_exporter = Arc::new(MetricsExporterMock::default());
+		let sources = responses
+			.into_iter()
+			.enumerate()
+			.map(|(i, r)| {
+				Box::new(DataSourceMock::new(&format!("source_{}", i), r.map(value)))
+					as Box<dyn DataSource<TradingPair>>
+			})
+			.collect();
+		(MedianOracle::new(sources, min_sources, metrics_exporter.clone()), metrics_exporter)
+	}
+
+	fn trading_pair() -> TradingPair {
+		TradingPair { crypto_currency: "TEER".to_string(), fiat_currency: "USD".to_string() }
+	}
+
+	#[test]
+	fn median_of_odd_and_even_number_of_values() {
+		assert_eq!(median(vec![]), None);
+		assert_eq!(median(vec![value(3.0), value(1.0), value(2.0)]), Some(value(2.0)));
+		assert_eq!(median(vec![value(4.0), value(1.0), value(2.0), value(3.0)]), Some(value(2.5)));
+	}
+
+	#[test]
+	fn failing_sources_are_skipped() {
+		let (oracle, metrics_exporter) = oracle(vec![Some(1.0), None, Some(5.0), Some(2.0)], 2);
+
+		let aggregated = oracle.get_aggregated_value(&trading_pair()).unwrap();
+
+		assert_eq!(aggregated.value, value(2.0));
+		assert_eq!(aggregated.sources, vec!["source_0", "source_2", "source_3"]);
+		assert_eq!(metrics_exporter.get_number_request(), 4);
+		assert_eq!(metrics_exporter.get_response_times().len(), 3);
+	}
+
+	#[test]
+	fn too_few_responding_sources_is_an_error() {
+		let (oracle, _) = oracle(vec![Some(1.0), None, None], 2);
+
+		assert_matches!(
+			oracle.get_aggregated_value(&trading_pair()),
+			Err(Error::NotEnoughSources { responded: 1, required: 2, .. })
+		);
+	}
+}
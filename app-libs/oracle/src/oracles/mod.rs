@@ -15,4 +15,5 @@
 
 */
 pub mod exchange_rate_oracle;
+pub mod median_oracle;
 pub mod weather_oracle;
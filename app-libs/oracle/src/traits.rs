@@ -19,7 +19,7 @@
 use crate::sgx_reexport_prelude::*;
 
 use crate::{
-	types::{ExchangeRate, TradingPair},
+	types::{ExchangeRate, OracleValue, TradingPair},
 	Error,
 };
 use core::time::Duration;
@@ -53,3 +53,26 @@ pub trait OracleSource<OracleSourceInfo>: Default {
 		source_info: OracleSourceInfo,
 	) -> Self::OracleRequestResult;
 }
+
+/// Adapter of a single data source of an aggregating oracle.
+///
+/// In contrast to [OracleSource] this trait is object safe, so the sources an oracle aggregates
+/// over can be chosen per deployment.
+pub trait DataSource<Feed>: Send + Sync {
+	/// Identifier of the source, used for the metrics and in the deployment configuration.
+	fn id(&self) -> String;
+
+	fn url(&self) -> Result<Url, Error>;
+
+	/// Root certificates pinned for this source. The TLS connection is terminated inside the
+	/// enclave and only certificate chains ending in one of these roots are accepted.
+	fn pinned_certificates(&self) -> Vec<String>;
+
+	fn timeout(&self) -> Option<Duration>;
+
+	fn fetch(
+		&self,
+		rest_client: &mut RestClient<HttpClient<SendWithCertificateVerification>>,
+		feed: &Feed,
+	) -> Result<OracleValue, Error>;
+}
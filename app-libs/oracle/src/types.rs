@@ -58,4 +58,7 @@ impl TradingPair {
 /// TODO Fix https://github.com/integritee-network/pallets/issues/71 and get it from https://github.com/integritee-network/pallets.git
 /// Teeracle types
 pub type ExchangeRate = U32F32;
+
+/// Value fetched from a [DataSource](crate::traits::DataSource) and published by the oracle.
+pub type OracleValue = U32F32;
 // pub type Coordinate = U32F32;
@@ -18,7 +18,9 @@
 pub mod invoke;
 pub mod shield_funds;
 pub mod transfer_to_alice_shields_funds;
+pub mod xcm_transact;
 
 pub use invoke::InvokeArgs;
 pub use shield_funds::ShieldFundsArgs;
 pub use transfer_to_alice_shields_funds::{TransferToAliceShieldsFundsArgs, ALICE_ACCOUNT_ID};
+pub use xcm_transact::SetValidationDataArgs;
@@ -0,0 +1,632 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Trusted call invocations that arrive via XCM.
+//!
+//! Users of other parachains can invoke trusted calls by sending an XCM message to the
+//! Integritee parachain, which `Transact`s `EnclaveBridge::invoke` with their derived account.
+//! As such a `Transact` is not an extrinsic of the parentchain block, the inbound XCM messages
+//! are decoded from the `ParachainSystem::set_validation_data` inherent instead.
+//!
+//! Shielding via XCM needs no special handling: a `Transact` or `TransferAsset` to the shard
+//! vault results in a regular transfer event, which is shielded like any other transfer to
+//! the vault.
+//!
+//! Only XCM v3 messages are decoded, messages of other versions and messages that can't be
+//! decoded are ignored with a warning. Only top-level `Transact` instructions are considered,
+//! not the ones in error handlers or appendices. Note that the outcome of the XCM execution is not known here,
+//! so the invocation is submitted even if the message failed to buy execution on the
+//! parentchain. This is the same as for a plain `invoke`, whose trusted call carries its own
+//! signature.
+
+use crate::indirect_calls::InvokeArgs;
+use codec::{Decode, Encode, Input};
+use itp_types::H256;
+use log::warn;
+use std::vec::Vec;
+
+/// Format prefix of XCMP messages containing concatenated versioned XCMs.
+const XCMP_CONCATENATED_VERSIONED_XCM: u8 = 0;
+
+/// Index of the `V3` variant of `VersionedXcm`.
+const XCM_VERSION_3: u8 = 3;
+
+#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+pub struct InboundDownwardMessage {
+	pub sent_at: u32,
+	pub msg: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+pub struct InboundHrmpMessage {
+	pub sent_at: u32,
+	pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+pub struct PersistedValidationData {
+	pub parent_head: Vec<u8>,
+	pub relay_parent_number: u32,
+	pub relay_parent_storage_root: H256,
+	pub max_pov_size: u32,
+}
+
+/// Arguments of the `ParachainSystem::set_validation_data` inherent.
+#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+pub struct SetValidationDataArgs {
+	pub validation_data: PersistedValidationData,
+	pub relay_chain_state: Vec<Vec<u8>>,
+	pub downward_messages: Vec<InboundDownwardMessage>,
+	/// Messages of the sibling parachains, by parachain id.
+	pub horizontal_messages: Vec<(u32, Vec<InboundHrmpMessage>)>,
+}
+
+impl SetValidationDataArgs {
+	/// All `invoke` calls that are transacted by the inbound XCM messages.
+	pub fn xcm_invocations(&self, invoke_call_indexes: [u8; 2]) -> Vec<InvokeArgs> {
+		let downward = self.downward_messages.iter().flat_map(|m| decode_versioned_xcm(&m.msg));
+		let horizontal = self
+			.horizontal_messages
+			.iter()
+			.flat_map(|(_, messages)| messages)
+			.flat_map(|m| decode_xcmp_message(&m.data));
+
+		downward
+			.chain(horizontal)
+			.flat_map(|xcm| xcm.0)
+			.filter_map(|instruction| match instruction {
+				xcm_v3::Instruction::Transact { call, .. } => Some(call),
+				_ => None,
+			})
+			.filter_map(|call| {
+				let (call_indexes, mut call_args) = (call.get(..2)?, call.get(2..)?);
+				if call_indexes != invoke_call_indexes {
+					return None
+				}
+				InvokeArgs::decode(&mut call_args).ok()
+			})
+			.collect()
+	}
+}
+
+fn decode_versioned_xcm(encoded: &[u8]) -> Vec<xcm_v3::Xcm> {
+	match xcm_v3::VersionedXcm::decode(&mut &encoded[..]) {
+		Ok(xcm_v3::VersionedXcm::V3(xcm)) => Vec::from([xcm]),
+		Err(e) => {
+			warn_undecodable(encoded, e);
+			Vec::new()
+		},
+	}
+}
+
+fn warn_undecodable(encoded: &[u8], error: codec::Error) {
+	match encoded.first() {
+		Some(&version) if version != XCM_VERSION_3 =>
+			warn!("Ignoring inbound XCM message of unsupported version {}", version),
+		_ => warn!("Ignoring inbound XCM message that could not be decoded: {:?}", error),
+	}
+}
+
+fn decode_xcmp_message(data: &[u8]) -> Vec<xcm_v3::Xcm> {
+	let mut input = data;
+	match input.read_byte() {
+		Ok(XCMP_CONCATENATED_VERSIONED_XCM) => (),
+		_ => return Vec::new(),
+	}
+	let mut messages = Vec::new();
+	while !input.is_empty() {
+		let message = input;
+		match xcm_v3::VersionedXcm::decode(&mut input) {
+			Ok(xcm_v3::VersionedXcm::V3(xcm)) => messages.push(xcm),
+			Err(e) => {
+				// The remaining messages can't be delimited anymore.
+				warn_undecodable(message, e);
+				break
+			},
+		}
+	}
+	messages
+}
+
+/// Mirror of the XCM v3 types of polkadot `release-v0.9.42`, which are needed to find
+/// `Transact` instructions.
+///
+/// The bounded vectors of the original types are decoded as plain vectors. The fields are only
+/// decoded to skip over them.
+#[allow(dead_code)]
+pub mod xcm_v3 {
+	use codec::{Compact, Decode, Encode};
+	use std::vec::Vec;
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub enum VersionedXcm {
+		#[codec(index = 3)]
+		V3(Xcm),
+	}
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub struct Xcm(pub Vec<Instruction>);
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub enum Instruction {
+		#[codec(index = 0)]
+		WithdrawAsset(Vec<MultiAsset>),
+		#[codec(index = 1)]
+		ReserveAssetDeposited(Vec<MultiAsset>),
+		#[codec(index = 2)]
+		ReceiveTeleportedAsset(Vec<MultiAsset>),
+		#[codec(index = 3)]
+		QueryResponse {
+			query_id: Compact<u64>,
+			response: Response,
+			max_weight: Weight,
+			querier: Option<MultiLocation>,
+		},
+		#[codec(index = 4)]
+		TransferAsset { assets: Vec<MultiAsset>, beneficiary: MultiLocation },
+		#[codec(index = 5)]
+		TransferReserveAsset { assets: Vec<MultiAsset>, dest: MultiLocation, xcm: Xcm },
+		#[codec(index = 6)]
+		Transact { origin_kind: OriginKind, require_weight_at_most: Weight, call: Vec<u8> },
+		#[codec(index = 7)]
+		HrmpNewChannelOpenRequest {
+			sender: Compact<u32>,
+			max_message_size: Compact<u32>,
+			max_capacity: Compact<u32>,
+		},
+		#[codec(index = 8)]
+		HrmpChannelAccepted { recipient: Compact<u32> },
+		#[codec(index = 9)]
+		HrmpChannelClosing {
+			initiator: Compact<u32>,
+			sender: Compact<u32>,
+			recipient: Compact<u32>,
+		},
+		#[codec(index = 10)]
+		ClearOrigin,
+		#[codec(index = 11)]
+		DescendOrigin(Junctions),
+		#[codec(index = 12)]
+		ReportError(QueryResponseInfo),
+		#[codec(index = 13)]
+		DepositAsset { assets: MultiAssetFilter, beneficiary: MultiLocation },
+		#[codec(index = 14)]
+		DepositReserveAsset { assets: MultiAssetFilter, dest: MultiLocation, xcm: Xcm },
+		#[codec(index = 15)]
+		ExchangeAsset { give: MultiAssetFilter, want: Vec<MultiAsset>, maximal: bool },
+		#[codec(index = 16)]
+		InitiateReserveWithdraw { assets: MultiAssetFilter, reserve: MultiLocation, xcm: Xcm },
+		#[codec(index = 17)]
+		InitiateTeleport { assets: MultiAssetFilter, dest: MultiLocation, xcm: Xcm },
+		#[codec(index = 18)]
+		ReportHolding { response_info: QueryResponseInfo, assets: MultiAssetFilter },
+		#[codec(index = 19)]
+		BuyExecution { fees: MultiAsset, weight_limit: WeightLimit },
+		#[codec(index = 20)]
+		RefundSurplus,
+		#[codec(index = 21)]
+		SetErrorHandler(Xcm),
+		#[codec(index = 22)]
+		SetAppendix(Xcm),
+		#[codec(index = 23)]
+		ClearError,
+		#[codec(index = 24)]
+		ClaimAsset { assets: Vec<MultiAsset>, ticket: MultiLocation },
+		#[codec(index = 25)]
+		Trap(Compact<u64>),
+		#[codec(index = 26)]
+		SubscribeVersion { query_id: Compact<u64>, max_response_weight: Weight },
+		#[codec(index = 27)]
+		UnsubscribeVersion,
+		#[codec(index = 28)]
+		BurnAsset(Vec<MultiAsset>),
+		#[codec(index = 29)]
+		ExpectAsset(Vec<MultiAsset>),
+		#[codec(index = 30)]
+		ExpectOrigin(Option<MultiLocation>),
+		#[codec(index = 31)]
+		ExpectError(Option<(u32, Error)>),
+		#[codec(index = 32)]
+		ExpectTransactStatus(MaybeErrorCode),
+		#[codec(index = 33)]
+		QueryPallet { module_name: Vec<u8>, response_info: QueryResponseInfo },
+		#[codec(index = 34)]
+		ExpectPallet {
+			index: Compact<u32>,
+			name: Vec<u8>,
+			module_name: Vec<u8>,
+			crate_major: Compact<u32>,
+			min_crate_minor: Compact<u32>,
+		},
+		#[codec(index = 35)]
+		ReportTransactStatus(QueryResponseInfo),
+		#[codec(index = 36)]
+		ClearTransactStatus,
+		#[codec(index = 37)]
+		UniversalOrigin(Junction),
+		#[codec(index = 38)]
+		ExportMessage { network: NetworkId, destination: Junctions, xcm: Xcm },
+		#[codec(index = 39)]
+		LockAsset { asset: MultiAsset, unlocker: MultiLocation },
+		#[codec(index = 40)]
+		UnlockAsset { asset: MultiAsset, target: MultiLocation },
+		#[codec(index = 41)]
+		NoteUnlockable { asset: MultiAsset, owner: MultiLocation },
+		#[codec(index = 42)]
+		RequestUnlock { asset: MultiAsset, locker: MultiLocation },
+		#[codec(index = 43)]
+		SetFeesMode { jit_withdraw: bool },
+		#[codec(index = 44)]
+		SetTopic([u8; 32]),
+		#[codec(index = 45)]
+		ClearTopic,
+		#[codec(index = 46)]
+		AliasOrigin(MultiLocation),
+		#[codec(index = 47)]
+		UnpaidExecution { weight_limit: WeightLimit, check_origin: Option<MultiLocation> },
+	}
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub struct QueryResponseInfo {
+		pub destination: MultiLocation,
+		pub query_id: Compact<u64>,
+		pub max_weight: Weight,
+	}
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub enum Response {
+		Null,
+		Assets(Vec<MultiAsset>),
+		ExecutionResult(Option<(u32, Error)>),
+		Version(u32),
+		PalletsInfo(Vec<PalletInfo>),
+		DispatchResult(MaybeErrorCode),
+	}
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub struct PalletInfo {
+		pub index: Compact<u32>,
+		pub name: Vec<u8>,
+		pub module_name: Vec<u8>,
+		pub major: Compact<u32>,
+		pub minor: Compact<u32>,
+		pub patch: Compact<u32>,
+	}
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub enum MaybeErrorCode {
+		Success,
+		Error(Vec<u8>),
+		TruncatedError(Vec<u8>),
+	}
+
+	/// The `&'static str` fields of `FailedToTransactAsset` and `Transport` are not encoded.
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub enum Error {
+		Overflow,
+		Unimplemented,
+		UntrustedReserveLocation,
+		UntrustedTeleportLocation,
+		LocationFull,
+		LocationNotInvertible,
+		BadOrigin,
+		InvalidLocation,
+		AssetNotFound,
+		FailedToTransactAsset,
+		NotWithdrawable,
+		LocationCannotHold,
+		ExceedsMaxMessageSize,
+		DestinationUnsupported,
+		Transport,
+		Unroutable,
+		UnknownClaim,
+		FailedToDecode,
+		MaxWeightInvalid,
+		NotHoldingFees,
+		TooExpensive,
+		Trap(u64),
+		ExpectationFalse,
+		PalletNotFound,
+		NameMismatch,
+		VersionIncompatible,
+		HoldingWouldOverflow,
+		ExportError,
+		ReanchorFailed,
+		NoDeal,
+		FeesNotMet,
+		LockError,
+		NoPermission,
+		Unanchored,
+		NotDepositable,
+		UnhandledXcmVersion,
+		WeightLimitReached(Weight),
+		Barrier,
+		WeightNotComputable,
+		ExceedsStackLimit,
+	}
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub enum OriginKind {
+		Native,
+		SovereignAccount,
+		Superuser,
+		Xcm,
+	}
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub struct Weight {
+		pub ref_time: Compact<u64>,
+		pub proof_size: Compact<u64>,
+	}
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub enum WeightLimit {
+		Unlimited,
+		Limited(Weight),
+	}
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub struct MultiLocation {
+		pub parents: u8,
+		pub interior: Junctions,
+	}
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub enum Junctions {
+		Here,
+		X1(Junction),
+		X2(Junction, Junction),
+		X3(Junction, Junction, Junction),
+		X4(Junction, Junction, Junction, Junction),
+		X5(Junction, Junction, Junction, Junction, Junction),
+		X6(Junction, Junction, Junction, Junction, Junction, Junction),
+		X7(Junction, Junction, Junction, Junction, Junction, Junction, Junction),
+		X8(Junction, Junction, Junction, Junction, Junction, Junction, Junction, Junction),
+	}
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub enum Junction {
+		#[codec(index = 0)]
+		Parachain(Compact<u32>),
+		#[codec(index = 1)]
+		AccountId32 { network: Option<NetworkId>, id: [u8; 32] },
+		#[codec(index = 2)]
+		AccountIndex64 { network: Option<NetworkId>, index: Compact<u64> },
+		#[codec(index = 3)]
+		AccountKey20 { network: Option<NetworkId>, key: [u8; 20] },
+		#[codec(index = 4)]
+		PalletInstance(u8),
+		#[codec(index = 5)]
+		GeneralIndex(Compact<u128>),
+		#[codec(index = 6)]
+		GeneralKey { length: u8, data: [u8; 32] },
+		#[codec(index = 7)]
+		OnlyChild,
+		#[codec(index = 8)]
+		Plurality { id: BodyId, part: BodyPart },
+		#[codec(index = 9)]
+		GlobalConsensus(NetworkId),
+	}
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub enum BodyId {
+		Unit,
+		Moniker([u8; 4]),
+		Index(Compact<u32>),
+		Executive,
+		Technical,
+		Legislative,
+		Judicial,
+		Defense,
+		Administration,
+		Treasury,
+	}
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub enum BodyPart {
+		Voice,
+		Members { count: Compact<u32> },
+		Fraction { nom: Compact<u32>, denom: Compact<u32> },
+		AtLeastProportion { nom: Compact<u32>, denom: Compact<u32> },
+		MoreThanProportion { nom: Compact<u32>, denom: Compact<u32> },
+	}
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub enum NetworkId {
+		ByGenesis([u8; 32]),
+		ByFork { block_number: u64, block_hash: [u8; 32] },
+		Polkadot,
+		Kusama,
+		Westend,
+		Rococo,
+		Wococo,
+		Ethereum { chain_id: Compact<u64> },
+		BitcoinCore,
+		BitcoinCash,
+	}
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub struct MultiAsset {
+		pub id: AssetId,
+		pub fun: Fungibility,
+	}
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub enum AssetId {
+		Concrete(MultiLocation),
+		Abstract([u8; 32]),
+	}
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub enum Fungibility {
+		Fungible(Compact<u128>),
+		NonFungible(AssetInstance),
+	}
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub enum AssetInstance {
+		Undefined,
+		Index(Compact<u128>),
+		Array4([u8; 4]),
+		Array8([u8; 8]),
+		Array16([u8; 16]),
+		Array32([u8; 32]),
+	}
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub enum MultiAssetFilter {
+		Definite(Vec<MultiAsset>),
+		Wild(WildMultiAsset),
+	}
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub enum WildMultiAsset {
+		All,
+		AllOf { id: AssetId, fun: WildFungibility },
+		AllCounted(Compact<u32>),
+		AllOfCounted { id: AssetId, fun: WildFungibility, count: Compact<u32> },
+	}
+
+	#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+	pub enum WildFungibility {
+		Fungible,
+		NonFungible,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{xcm_v3::*, *};
+	use codec::Compact;
+	use itp_types::{Request, ShardIdentifier};
+
+	const INVOKE: [u8; 2] = [54, 0];
+
+	fn invoke_call(request: &Request) -> Vec<u8> {
+		let mut call = INVOKE.to_vec();
+		call.extend(request.encode());
+		call
+	}
+
+	fn transact_xcm(call: Vec<u8>) -> VersionedXcm {
+		let native = MultiAsset {
+			id: AssetId::Concrete(MultiLocation { parents: 1, interior: Junctions::Here }),
+			fun: Fungibility::Fungible(Compact(1_000_000_000_000)),
+		};
+		VersionedXcm::V3(Xcm(Vec::from([
+			Instruction::WithdrawAsset(Vec::from([native.clone()])),
+			Instruction::BuyExecution { fees: native, weight_limit: WeightLimit::Unlimited },
+			Instruction::DescendOrigin(Junctions::X1(Junction::AccountId32 {
+				network: None,
+				id: [1u8; 32],
+			})),
+			Instruction::Transact {
+				origin_kind: OriginKind::SovereignAccount,
+				require_weight_at_most: Weight {
+					ref_time: Compact(1_000_000_000),
+					proof_size: Compact(65536),
+				},
+				call,
+			},
+			Instruction::RefundSurplus,
+		])))
+	}
+
+	fn validation_data(
+		downward_messages: Vec<InboundDownwardMessage>,
+		horizontal_messages: Vec<(u32, Vec<InboundHrmpMessage>)>,
+	) -> SetValidationDataArgs {
+		SetValidationDataArgs {
+			validation_data: PersistedValidationData {
+				parent_head: Vec::new(),
+				relay_parent_number: 1,
+				relay_parent_storage_root: H256::default(),
+				max_pov_size: 0,
+			},
+			relay_chain_state: Vec::new(),
+			downward_messages,
+			horizontal_messages,
+		}
+	}
+
+	#[test]
+	fn invocations_are_found_in_downward_and_horizontal_messages() {
+		let request =
+			Request { shard: ShardIdentifier::repeat_byte(3), cyphertext: Vec::from([7u8; 12]) };
+		let mut xcmp_data = Vec::from([XCMP_CONCATENATED_VERSIONED_XCM]);
+		xcmp_data.extend(transact_xcm(Vec::from([0u8, 7])).encode());
+		xcmp_data.extend(transact_xcm(invoke_call(&request)).encode());
+
+		let args = validation_data(
+			Vec::from([InboundDownwardMessage {
+				sent_at: 1,
+				msg: transact_xcm(invoke_call(&request)).encode(),
+			}]),
+			Vec::from([(2000, Vec::from([InboundHrmpMessage { sent_at: 1, data: xcmp_data }]))]),
+		);
+		let decoded = SetValidationDataArgs::decode(&mut args.encode().as_slice()).unwrap();
+
+		let expected = InvokeArgs::decode(&mut request.encode().as_slice()).unwrap();
+		assert_eq!(decoded.xcm_invocations(INVOKE), Vec::from([expected.clone(), expected]));
+	}
+
+	#[test]
+	fn transact_is_found_among_plurality_origins_and_nested_instructions() {
+		let request =
+			Request { shard: ShardIdentifier::repeat_byte(3), cyphertext: Vec::from([7u8; 12]) };
+		let VersionedXcm::V3(Xcm(instructions)) = transact_xcm(invoke_call(&request));
+		let council = Junction::Plurality {
+			id: BodyId::Index(Compact(1)),
+			part: BodyPart::Fraction { nom: Compact(2), denom: Compact(3) },
+		};
+		let response_info = QueryResponseInfo {
+			destination: MultiLocation { parents: 1, interior: Junctions::Here },
+			query_id: Compact(5),
+			max_weight: Weight { ref_time: Compact(0), proof_size: Compact(0) },
+		};
+		let mut xcm = Vec::from([
+			Instruction::DescendOrigin(Junctions::X1(council)),
+			Instruction::SetAppendix(Xcm(Vec::from([Instruction::ReportError(response_info)]))),
+			Instruction::ExpectError(Some((
+				1,
+				Error::WeightLimitReached(Weight { ref_time: Compact(1), proof_size: Compact(2) }),
+			))),
+		]);
+		xcm.extend(instructions);
+
+		let args = validation_data(
+			Vec::from([InboundDownwardMessage {
+				sent_at: 1,
+				msg: VersionedXcm::V3(Xcm(xcm)).encode(),
+			}]),
+			Vec::new(),
+		);
+
+		let expected = InvokeArgs::decode(&mut request.encode().as_slice()).unwrap();
+		assert_eq!(args.xcm_invocations(INVOKE), Vec::from([expected]));
+	}
+
+	#[test]
+	fn undecodable_messages_are_ignored() {
+		let args = validation_data(
+			Vec::from([InboundDownwardMessage { sent_at: 1, msg: Vec::from([2u8, 0, 1]) }]),
+			Vec::new(),
+		);
+
+		assert!(args.xcm_invocations(INVOKE).is_empty());
+	}
+}
@@ -21,7 +21,8 @@ use itp_api_client_types::Events;
 
 use itp_types::{
 	parentchain::{
-		BalanceTransfer, ExtrinsicFailed, ExtrinsicStatus, ExtrinsicSuccess, FilterEvents,
+		AssetTransfer, AssetsTransferred, BalanceTransfer, ExtrinsicFailed, ExtrinsicStatus,
+		ExtrinsicSuccess, FilterEvents, PublishedHash, Remarked, TokensTransfer,
 	},
 	H256,
 };
@@ -82,4 +83,59 @@ impl FilterEvents for FilterableEvents {
 			})
 			.collect())
 	}
+
+	fn get_asset_transfer_events(&self) -> core::result::Result<Vec<AssetTransfer>, Self::Error> {
+		Ok(self
+			.to_events()
+			.iter()
+			.flatten() // flatten filters out the nones
+			.filter_map(|ev| {
+				let event = ev
+					.as_event::<AssetsTransferred>()
+					.map(|maybe_event| maybe_event.map(AssetTransfer::from))
+					.and_then(|maybe_event| match maybe_event {
+						Some(event) => Ok(Some(event)),
+						None => ev
+							.as_event::<TokensTransfer>()
+							.map(|maybe_event| maybe_event.map(AssetTransfer::from)),
+					});
+				match event {
+					Ok(maybe_event) => maybe_event,
+					Err(e) => {
+						log::error!("Could not decode event: {:?}", e);
+						None
+					},
+				}
+			})
+			.collect())
+	}
+
+	fn get_published_hash_events(&self) -> core::result::Result<Vec<PublishedHash>, Self::Error> {
+		Ok(self
+			.to_events()
+			.iter()
+			.flatten() // flatten filters out the nones
+			.filter_map(|ev| match ev.as_event::<PublishedHash>() {
+				Ok(maybe_event) => maybe_event,
+				Err(e) => {
+					log::error!("Could not decode event: {:?}", e);
+					None
+				},
+			})
+			.collect())
+	}
+	fn get_remark_events(&self) -> core::result::Result<Vec<Remarked>, Self::Error> {
+		Ok(self
+			.to_events()
+			.iter()
+			.flatten() // flatten filters out the nones
+			.filter_map(|ev| match ev.as_event::<Remarked>() {
+				Ok(maybe_event) => maybe_event,
+				Err(e) => {
+					log::error!("Could not decode event: {:?}", e);
+					None
+				},
+			})
+			.collect())
+	}
 }
@@ -15,15 +15,26 @@
 
 */
 
-use codec::Encode;
+use codec::{Decode, Encode};
 
 pub use ita_sgx_runtime::{Balance, Index};
 use ita_stf::{Getter, TrustedCall, TrustedCallSigned};
 use itc_parentchain_indirect_calls_executor::error::Error;
-use itp_stf_primitives::{traits::IndirectExecutor, types::TrustedOperation};
-use itp_types::parentchain::{AccountId, FilterEvents, HandleParentchainEvents, ParentchainError};
+use itp_stf_primitives::{
+	cross_shard::{AnchoredCommitment, OutboxCommitment},
+	traits::IndirectExecutor,
+	types::TrustedOperation,
+};
+use itp_types::{
+	parentchain::{
+		AccountId, FilterEvents, HandleParentchainEvents, ParentchainAssetId, ParentchainError,
+		PublishedHash,
+	},
+	H256,
+};
 use itp_utils::hex::hex_encode;
 use log::*;
+use std::vec::Vec;
 
 pub struct ParentchainEventHandler {}
 
@@ -46,6 +57,83 @@ impl ParentchainEventHandler {
 
 		Ok(())
 	}
+
+	fn shield_assets<Executor: IndirectExecutor<TrustedCallSigned, Error>>(
+		executor: &Executor,
+		account: &AccountId,
+		asset_id: ParentchainAssetId,
+		amount: Balance,
+	) -> Result<(), Error> {
+		log::info!("shielding asset {} for {:?} amount {}", asset_id, account, amount,);
+		let shard = executor.get_default_shard();
+		let trusted_call = TrustedCall::assets_shield(
+			executor.get_enclave_account()?,
+			account.clone(),
+			asset_id,
+			amount,
+		);
+		let signed_trusted_call = executor.sign_call_with_self(&trusted_call, &shard)?;
+		let trusted_operation =
+			TrustedOperation::<TrustedCallSigned, Getter>::indirect_call(signed_trusted_call);
+
+		let encrypted_trusted_call = executor.encrypt(&trusted_operation.encode())?;
+		executor.submit_trusted_call(shard, encrypted_trusted_call);
+
+		Ok(())
+	}
+
+	/// Confirms the batched unshield payouts, whose remarks stored by the shard vault have been
+	/// observed on the parentchain.
+	fn confirm_unshield_payouts<Executor: IndirectExecutor<TrustedCallSigned, Error>>(
+		executor: &Executor,
+		remarks: Vec<H256>,
+	) -> Result<(), Error> {
+		log::info!("confirming {} remark(s) of the shard vault", remarks.len());
+		let shard = executor.get_default_shard();
+		let trusted_call =
+			TrustedCall::confirm_unshield_payouts(executor.get_enclave_account()?, remarks);
+		let signed_trusted_call = executor.sign_call_with_self(&trusted_call, &shard)?;
+		let trusted_operation =
+			TrustedOperation::<TrustedCallSigned, Getter>::indirect_call(signed_trusted_call);
+
+		let encrypted_trusted_call = executor.encrypt(&trusted_operation.encode())?;
+		executor.submit_trusted_call(shard, encrypted_trusted_call);
+
+		Ok(())
+	}
+
+	/// Anchors a published cross-shard outbox commitment of a foreign shard in all our shards.
+	/// Published hashes that are no such commitment are ignored.
+	fn anchor_cross_shard_commitment<Executor: IndirectExecutor<TrustedCallSigned, Error>>(
+		executor: &Executor,
+		event: &PublishedHash,
+	) -> Result<(), Error> {
+		let commitment = match OutboxCommitment::decode(&mut event.data.as_slice()) {
+			Ok(commitment) if commitment.root == event.hash => commitment,
+			_ => return Ok(()),
+		};
+		let shards = executor.list_handled_shards();
+		if shards.contains(&commitment.shard) {
+			// Messages between our own shards are delivered by the router.
+			return Ok(())
+		}
+		info!("anchoring cross-shard commitment {:?}", commitment);
+		let anchored =
+			AnchoredCommitment { commitment, enclave_fingerprint: event.enclave_fingerprint };
+		for shard in shards {
+			let trusted_call = TrustedCall::cross_shard_anchor_commitment(
+				executor.get_enclave_account()?,
+				anchored,
+			);
+			let signed_trusted_call = executor.sign_call_with_self(&trusted_call, &shard)?;
+			let trusted_operation =
+				TrustedOperation::<TrustedCallSigned, Getter>::indirect_call(signed_trusted_call);
+
+			let encrypted_trusted_call = executor.encrypt(&trusted_operation.encode())?;
+			executor.submit_trusted_call(shard, encrypted_trusted_call);
+		}
+		Ok(())
+	}
 }
 
 impl<Executor> HandleParentchainEvents<Executor, TrustedCallSigned, Error>
@@ -74,6 +162,38 @@ where
 				})
 				.map_err(|_| ParentchainError::ShieldFundsFailure)?;
 		}
+
+		if let Ok(events) = events.get_remark_events() {
+			let remarks: Vec<H256> = events
+				.iter()
+				.filter(|&event| event.sender == *vault_account)
+				.map(|event| event.hash)
+				.collect();
+			if !remarks.is_empty() {
+				if let Err(e) = Self::confirm_unshield_payouts(executor, remarks) {
+					error!("failed to confirm unshield payouts: {:?}", e);
+				}
+			}
+		}
+
+		if let Ok(events) = events.get_asset_transfer_events() {
+			events
+				.iter()
+				.filter(|&event| event.to == *vault_account)
+				.try_for_each(|event| {
+					info!("found asset transfer_event to vault account: {}", event);
+					Self::shield_assets(executor, &event.from, event.asset_id, event.amount)
+				})
+				.map_err(|_| ParentchainError::ShieldFundsFailure)?;
+		}
+
+		if let Ok(events) = events.get_published_hash_events() {
+			for event in events.iter() {
+				if let Err(e) = Self::anchor_cross_shard_commitment(executor, event) {
+					error!("failed to anchor cross-shard commitment: {:?}", e);
+				}
+			}
+		}
 		Ok(())
 	}
 }
@@ -20,7 +20,9 @@ mod event_handler;
 mod extrinsic_parser;
 use crate::{
 	decode_and_log_error,
-	indirect_calls::{invoke::InvokeArgs, shield_funds::ShieldFundsArgs},
+	indirect_calls::{
+		invoke::InvokeArgs, shield_funds::ShieldFundsArgs, xcm_transact::SetValidationDataArgs,
+	},
 	integritee::extrinsic_parser::ParseExtrinsic,
 };
 use codec::{Decode, Encode};
@@ -36,12 +38,15 @@ use itc_parentchain_indirect_calls_executor::{
 };
 use itp_node_api::metadata::NodeMetadataTrait;
 use itp_stf_primitives::traits::IndirectExecutor;
-use log::trace;
+use log::{error, trace};
+use std::vec::Vec;
 /// The default indirect call (extrinsic-triggered) of the Integritee-Parachain.
 #[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
 pub enum IndirectCall {
 	ShieldFunds(ShieldFundsArgs),
 	Invoke(InvokeArgs),
+	/// Invocations transacted by inbound XCM messages.
+	XcmInvocations(Vec<InvokeArgs>),
 }
 
 impl<Executor: IndirectExecutor<TrustedCallSigned, Error>>
@@ -52,6 +57,15 @@ impl<Executor: IndirectExecutor<TrustedCallSigned, Error>>
 		match self {
 			IndirectCall::ShieldFunds(shieldfunds_args) => shieldfunds_args.dispatch(executor),
 			IndirectCall::Invoke(invoke_args) => invoke_args.dispatch(executor),
+			IndirectCall::XcmInvocations(invocations) => {
+				// The invocations are independent of each other, one failing must not drop the rest.
+				for invoke_args in invocations {
+					if let Err(e) = invoke_args.dispatch(executor) {
+						error!("failed to dispatch invoke call transacted via XCM: {:?}", e);
+					}
+				}
+				Ok(())
+			},
 		}
 	}
 }
@@ -101,6 +115,14 @@ where
 			log::debug!("executing invoke call");
 			let args = decode_and_log_error::<InvokeArgs>(call_args)?;
 			Some(IndirectCall::Invoke(args))
+		} else if index == metadata.set_validation_data_call_indexes().ok()? {
+			let args = decode_and_log_error::<SetValidationDataArgs>(call_args)?;
+			let invocations = args.xcm_invocations(metadata.invoke_call_indexes().ok()?);
+			if invocations.is_empty() {
+				return None
+			}
+			log::debug!("executing {} invoke call(s) transacted via XCM", invocations.len());
+			Some(IndirectCall::XcmInvocations(invocations))
 		} else {
 			None
 		}
@@ -0,0 +1,122 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Export of the state associated with an account, see [itp_stf_primitives::account_export].
+//!
+//! Served by the `account_data_export` trusted getter. The export is encrypted to the key in
+//! the getter by the state getter of the executor, never returned in the clear.
+
+use crate::{
+	assets::AssetsStorage, confidential_events::ConfidentialEventsStorage, rent::RentStorage,
+	scheduler::SchedulerStorage, session_keys::SessionKeysStorage, shard_acl::ShardAclStorage,
+	unshielding::UnshieldingStorage,
+};
+use ita_sgx_runtime::{Runtime, System};
+use itp_stf_primitives::{
+	account_export::{AccountDataEntry, AccountDataExport, AccountStorage},
+	types::AccountId,
+};
+use std::prelude::v1::*;
+
+pub struct SystemStorage;
+
+impl AccountStorage for SystemStorage {
+	const PALLET: &'static str = "System";
+
+	fn account_entries(who: &AccountId) -> Vec<AccountDataEntry> {
+		if !frame_system::Account::<Runtime>::contains_key(who) {
+			return Vec::new()
+		}
+		vec![AccountDataEntry::new("Account", &System::account(who))]
+	}
+}
+
+/// All state of the account, the pallets without entries are omitted.
+pub fn export(who: &AccountId) -> AccountDataExport {
+	let pallets = [
+		SystemStorage::export(who),
+		RentStorage::export(who),
+		AssetsStorage::export(who),
+		UnshieldingStorage::export(who),
+		SchedulerStorage::export(who),
+		SessionKeysStorage::export(who),
+		ShardAclStorage::export(who),
+		ConfidentialEventsStorage::export(who),
+	]
+	.into_iter()
+	.flatten()
+	.collect();
+	AccountDataExport { account: who.clone(), pallets }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{assets, session_keys, unshielding, unshielding::PendingPayout};
+	use codec::{Decode, Encode};
+	use itp_sgx_externalities::{SgxExternalities, SgxExternalitiesTrait};
+	use itp_stf_primitives::session_keys::SessionKey;
+	use itp_types::parentchain::ParentchainAssetId;
+	use sp_keyring::AccountKeyring;
+
+	fn value_of(export: &AccountDataExport, pallet: &str) -> Vec<u8> {
+		export.pallets.iter().find(|p| p.pallet == pallet).unwrap().entries[0]
+			.value
+			.clone()
+	}
+
+	#[test]
+	fn export_contains_the_entries_of_the_account_only() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+		let bob: AccountId = AccountKeyring::Bob.public().into();
+		let charlie: AccountId = AccountKeyring::Charlie.public().into();
+		let session_key = SessionKey::new(u64::MAX, vec![b"balance_transfer".to_vec()]);
+
+		state.execute_with(|| {
+			frame_system::Account::<Runtime>::mutate(&alice, |info| info.data.free = 10);
+			assets::mint(&ParentchainAssetId::Assets(1), &alice, 5).unwrap();
+			assets::mint(&ParentchainAssetId::Assets(2), &bob, 7).unwrap();
+			unshielding::queue_payout(alice.clone(), 3).unwrap();
+			unshielding::queue_payout(bob.clone(), 4).unwrap();
+			session_keys::register(&alice, &bob, session_key.clone()).unwrap();
+			session_keys::register(&alice, &charlie, session_key.clone()).unwrap();
+			session_keys::register(&bob, &charlie, session_key.clone()).unwrap();
+
+			let export = export(&alice);
+
+			assert_eq!(export.account, alice);
+			let pallets: Vec<&str> = export.pallets.iter().map(|p| p.pallet.as_str()).collect();
+			assert_eq!(pallets, vec!["System", "Assets", "Unshielding", "SessionKeys"]);
+			assert_eq!(
+				value_of(&export, "Assets"),
+				vec![(ParentchainAssetId::Assets(1), 5u128)].encode()
+			);
+			assert_eq!(
+				value_of(&export, "Unshielding"),
+				vec![PendingPayout { id: 0, beneficiary: alice.clone(), amount: 3 }].encode()
+			);
+			let delegations = Vec::<(AccountId, SessionKey)>::decode(
+				&mut value_of(&export, "SessionKeys").as_slice(),
+			)
+			.unwrap();
+			assert_eq!(delegations.len(), 2);
+			assert!(delegations.contains(&(charlie, session_key)));
+
+			assert!(super::export(&AccountKeyring::Dave.public().into()).pallets.is_empty());
+		});
+	}
+}
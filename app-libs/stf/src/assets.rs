@@ -0,0 +1,189 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Non-native parentchain assets held in the shard.
+//!
+//! Transfers of `Assets` or ORML `Tokens` to the shard vault are shielded with
+//! [crate::TrustedCall::assets_shield] and credit the corresponding [ParentchainAssetId] in
+//! the shard state. They can be transferred within the shard and unshielded back out via the
+//! vault, just like the native token.
+
+use crate::{
+	helpers::{get_storage_double_map, get_storage_map, storage_map_keys},
+	Balance,
+};
+use codec::Encode;
+use itp_stf_primitives::{
+	account_export::{AccountDataEntry, AccountStorage},
+	error::{StfError, StfResult},
+	types::AccountId,
+};
+use itp_storage::{storage_double_map_key, storage_map_key, storage_value_key, StorageHasher};
+use itp_types::parentchain::ParentchainAssetId;
+use std::prelude::v1::*;
+
+pub const ASSETS_STORAGE_PREFIX: &str = "Assets";
+pub const BALANCES_KEY: &str = "Balances";
+pub const TOTAL_ISSUANCE_KEY: &str = "TotalIssuance";
+
+fn balance_key(asset_id: &ParentchainAssetId, who: &AccountId) -> Vec<u8> {
+	storage_double_map_key(
+		ASSETS_STORAGE_PREFIX,
+		BALANCES_KEY,
+		asset_id,
+		&StorageHasher::Blake2_128Concat,
+		who,
+		&StorageHasher::Blake2_128Concat,
+	)
+}
+
+fn total_issuance_key(asset_id: &ParentchainAssetId) -> Vec<u8> {
+	storage_map_key(
+		ASSETS_STORAGE_PREFIX,
+		TOTAL_ISSUANCE_KEY,
+		asset_id,
+		&StorageHasher::Blake2_128Concat,
+	)
+}
+
+pub fn balance_of(asset_id: &ParentchainAssetId, who: &AccountId) -> Balance {
+	get_storage_double_map(
+		ASSETS_STORAGE_PREFIX,
+		BALANCES_KEY,
+		asset_id,
+		&StorageHasher::Blake2_128Concat,
+		who,
+		&StorageHasher::Blake2_128Concat,
+	)
+	.unwrap_or_default()
+}
+
+/// Amount of the asset that has been shielded and not yet unshielded.
+pub fn total_issuance(asset_id: &ParentchainAssetId) -> Balance {
+	get_storage_map(
+		ASSETS_STORAGE_PREFIX,
+		TOTAL_ISSUANCE_KEY,
+		asset_id,
+		&StorageHasher::Blake2_128Concat,
+	)
+	.unwrap_or_default()
+}
+
+fn set_balance(asset_id: &ParentchainAssetId, who: &AccountId, balance: Balance) {
+	if balance == 0 {
+		sp_io::storage::clear(&balance_key(asset_id, who));
+	} else {
+		sp_io::storage::set(&balance_key(asset_id, who), &balance.encode());
+	}
+}
+
+fn set_total_issuance(asset_id: &ParentchainAssetId, total_issuance: Balance) {
+	sp_io::storage::set(&total_issuance_key(asset_id), &total_issuance.encode());
+}
+
+/// Credits shielded funds.
+pub fn mint(asset_id: &ParentchainAssetId, who: &AccountId, amount: Balance) -> StfResult<()> {
+	let total_issuance = total_issuance(asset_id)
+		.checked_add(amount)
+		.ok_or_else(|| StfError::Dispatch("asset total issuance overflow".into()))?;
+	set_balance(asset_id, who, balance_of(asset_id, who).saturating_add(amount));
+	set_total_issuance(asset_id, total_issuance);
+	Ok(())
+}
+
+/// Burns funds that are unshielded.
+pub fn burn(asset_id: &ParentchainAssetId, who: &AccountId, amount: Balance) -> StfResult<()> {
+	let balance = balance_of(asset_id, who);
+	if balance < amount {
+		return Err(StfError::MissingFunds)
+	}
+	set_balance(asset_id, who, balance - amount);
+	set_total_issuance(asset_id, total_issuance(asset_id).saturating_sub(amount));
+	Ok(())
+}
+
+pub fn transfer(
+	asset_id: &ParentchainAssetId,
+	from: &AccountId,
+	to: &AccountId,
+	amount: Balance,
+) -> StfResult<()> {
+	let from_balance = balance_of(asset_id, from);
+	if from_balance < amount {
+		return Err(StfError::MissingFunds)
+	}
+	if from == to {
+		return Ok(())
+	}
+	set_balance(asset_id, from, from_balance - amount);
+	set_balance(asset_id, to, balance_of(asset_id, to).saturating_add(amount));
+	Ok(())
+}
+
+/// Exports the non-zero balances of the account, of all assets that have been issued.
+pub struct AssetsStorage;
+
+impl AccountStorage for AssetsStorage {
+	const PALLET: &'static str = ASSETS_STORAGE_PREFIX;
+
+	fn account_entries(who: &AccountId) -> Vec<AccountDataEntry> {
+		let prefix = storage_value_key(ASSETS_STORAGE_PREFIX, TOTAL_ISSUANCE_KEY);
+		let balances: Vec<(ParentchainAssetId, Balance)> =
+			storage_map_keys::<ParentchainAssetId>(&prefix)
+				.into_iter()
+				.map(|asset_id| {
+					let balance = balance_of(&asset_id, who);
+					(asset_id, balance)
+				})
+				.filter(|(_, balance)| *balance > 0)
+				.collect();
+		if balances.is_empty() {
+			return Vec::new()
+		}
+		vec![AccountDataEntry::new(BALANCES_KEY, &balances)]
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itp_sgx_externalities::{SgxExternalities, SgxExternalitiesTrait};
+	use sp_keyring::AccountKeyring;
+
+	#[test]
+	fn mint_transfer_and_burn_keep_total_issuance_consistent() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+		let bob: AccountId = AccountKeyring::Bob.public().into();
+		let asset = ParentchainAssetId::Assets(7);
+		let other_asset = ParentchainAssetId::Tokens(7);
+
+		state.execute_with(|| {
+			mint(&asset, &alice, 100).unwrap();
+			transfer(&asset, &alice, &bob, 30).unwrap();
+			burn(&asset, &bob, 10).unwrap();
+
+			assert_eq!(balance_of(&asset, &alice), 70);
+			assert_eq!(balance_of(&asset, &bob), 20);
+			assert_eq!(total_issuance(&asset), 90);
+			assert_eq!(balance_of(&other_asset, &alice), 0);
+
+			assert_eq!(transfer(&asset, &bob, &alice, 21), Err(StfError::MissingFunds));
+			assert_eq!(burn(&other_asset, &alice, 1), Err(StfError::MissingFunds));
+		});
+	}
+}
@@ -0,0 +1,297 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Confidential events of an account.
+//!
+//! Events that concern a single account, e.g. why one of its calls failed, are kept in a ring
+//! buffer of the last [MAX_EVENTS_PER_ACCOUNT] events per account in the shard state. Every
+//! event is encrypted to the public key of the account, see [itp_sgx_crypto::ecies], such that
+//! only the account can read it, even if the state is exported or replicated. The encrypted
+//! events are returned by [crate::TrustedGetter::confidential_events], which has to be signed
+//! by the account.
+//!
+//! The curve of the public key is taken from the signature of the last call the account has
+//! sent, accounts that never sent a call are assumed to be sr25519 accounts. Events of ecdsa
+//! accounts can't be encrypted to their account id and are not recorded.
+
+use crate::{helpers::current_block_number, Balance, Index};
+use codec::{Decode, Encode};
+use itp_sgx_crypto::ecies::{self, AccountKeyScheme, EciesCiphertext};
+use itp_stf_primitives::{
+	account_export::{AccountDataEntry, AccountStorage},
+	cross_shard::MessageNonce,
+	types::{AccountId, ShardIdentifier},
+};
+use itp_storage::{storage_map_key, StorageHasher};
+use itp_types::{parentchain::ParentchainAssetId, SidechainBlockNumber};
+use log::*;
+use sp_runtime::MultiSignature;
+use std::prelude::v1::*;
+
+pub const CONFIDENTIAL_EVENTS_STORAGE_PREFIX: &str = "ConfidentialEvents";
+pub const EVENTS_KEY: &str = "Events";
+pub const KEY_SCHEMES_KEY: &str = "KeySchemes";
+
+/// Number of events kept per account, older ones are dropped.
+pub const MAX_EVENTS_PER_ACCOUNT: usize = 32;
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub enum ConfidentialEvent {
+	/// A call of the account with the given nonce failed.
+	CallFailed {
+		nonce: Index,
+		reason: String,
+	},
+	BalanceSent {
+		to: AccountId,
+		amount: Balance,
+	},
+	BalanceReceived {
+		from: AccountId,
+		amount: Balance,
+	},
+	Shielded {
+		amount: Balance,
+	},
+	/// Funds were burnt on the sidechain to be paid out to `beneficiary` on the parentchain.
+	Unshielded {
+		beneficiary: AccountId,
+		amount: Balance,
+	},
+	AssetSent {
+		to: AccountId,
+		asset: ParentchainAssetId,
+		amount: Balance,
+	},
+	AssetReceived {
+		from: AccountId,
+		asset: ParentchainAssetId,
+		amount: Balance,
+	},
+	/// Funds were burnt to be minted to `to` on the `destination` shard.
+	CrossShardSent {
+		destination: ShardIdentifier,
+		nonce: MessageNonce,
+		to: AccountId,
+		amount: Balance,
+	},
+	/// Funds sent from `from` on the `source` shard were minted.
+	CrossShardReceived {
+		source: ShardIdentifier,
+		nonce: MessageNonce,
+		from: AccountId,
+		amount: Balance,
+	},
+}
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct ConfidentialEventRecord {
+	pub block_number: SidechainBlockNumber,
+	pub event: ConfidentialEvent,
+}
+
+impl ConfidentialEventRecord {
+	/// Decrypts an event that has been encrypted to the account with the given key scheme and
+	/// public key, see [ecies::secret_scalar] for the secret.
+	pub fn decrypt(
+		scheme: AccountKeyScheme,
+		secret: [u8; 32],
+		public: &[u8; 32],
+		encrypted: &EncryptedEventRecord,
+	) -> Option<Self> {
+		let plaintext = ecies::decrypt(scheme, secret, public, encrypted).ok()?;
+		Decode::decode(&mut plaintext.as_slice()).ok()
+	}
+}
+
+/// [ConfidentialEventRecord] encrypted to the public key of the account it concerns.
+pub type EncryptedEventRecord = EciesCiphertext;
+
+fn events_key(who: &AccountId) -> Vec<u8> {
+	storage_map_key(
+		CONFIDENTIAL_EVENTS_STORAGE_PREFIX,
+		EVENTS_KEY,
+		who,
+		&StorageHasher::Blake2_128Concat,
+	)
+}
+
+fn key_scheme_key(who: &AccountId) -> Vec<u8> {
+	storage_map_key(
+		CONFIDENTIAL_EVENTS_STORAGE_PREFIX,
+		KEY_SCHEMES_KEY,
+		who,
+		&StorageHasher::Blake2_128Concat,
+	)
+}
+
+/// Key scheme the events of the account are encrypted with, `None` for ecdsa accounts.
+pub fn key_scheme_of(who: &AccountId) -> Option<AccountKeyScheme> {
+	match sp_io::storage::get(&key_scheme_key(who)) {
+		Some(v) => Decode::decode(&mut v.as_slice()).ok()?,
+		None => Some(AccountKeyScheme::default()),
+	}
+}
+
+/// Records the key scheme of an account from the signature of a call it has sent.
+pub fn note_key_scheme(who: &AccountId, signature: &MultiSignature) {
+	let scheme = match signature {
+		MultiSignature::Sr25519(_) => Some(AccountKeyScheme::Sr25519),
+		MultiSignature::Ed25519(_) => Some(AccountKeyScheme::Ed25519),
+		MultiSignature::Ecdsa(_) => None,
+	};
+	if scheme == key_scheme_of(who) {
+		return
+	}
+	if scheme == Some(AccountKeyScheme::default()) {
+		sp_io::storage::clear(&key_scheme_key(who));
+	} else {
+		sp_io::storage::set(&key_scheme_key(who), &scheme.encode());
+	}
+}
+
+/// Encrypted events of an account, oldest first.
+pub fn events_of(who: &AccountId) -> Vec<EncryptedEventRecord> {
+	sp_io::storage::get(&events_key(who))
+		.and_then(|v| Decode::decode(&mut v.as_slice()).ok())
+		.unwrap_or_default()
+}
+
+pub fn deposit_event(who: &AccountId, event: ConfidentialEvent) {
+	let scheme = match key_scheme_of(who) {
+		Some(scheme) => scheme,
+		None => {
+			debug!("Not recording confidential event of ecdsa account");
+			return
+		},
+	};
+	let block_number = current_block_number();
+	let record = ConfidentialEventRecord { block_number, event };
+	let public: &[u8; 32] = who.as_ref();
+	let encrypted =
+		match ecies::encrypt(scheme, public, &record.encode(), sp_io::offchain::random_seed()) {
+			Ok(encrypted) => encrypted,
+			Err(e) => {
+				warn!("Could not encrypt confidential event to its account: {:?}", e);
+				return
+			},
+		};
+
+	let mut events = events_of(who);
+	if events.len() >= MAX_EVENTS_PER_ACCOUNT {
+		events.drain(..=events.len() - MAX_EVENTS_PER_ACCOUNT);
+	}
+	events.push(encrypted);
+	sp_io::storage::set(&events_key(who), &events.encode());
+}
+
+/// Number of bytes of state occupied by the events of an account.
+pub fn storage_bytes(who: &AccountId) -> usize {
+	[events_key(who), key_scheme_key(who)]
+		.iter()
+		.filter_map(|key| sp_io::storage::get(key).map(|v| key.len() + v.len()))
+		.sum()
+}
+
+pub fn clear_events(who: &AccountId) {
+	sp_io::storage::clear(&events_key(who));
+	sp_io::storage::clear(&key_scheme_key(who));
+}
+
+pub struct ConfidentialEventsStorage;
+
+impl AccountStorage for ConfidentialEventsStorage {
+	const PALLET: &'static str = CONFIDENTIAL_EVENTS_STORAGE_PREFIX;
+
+	fn account_entries(who: &AccountId) -> Vec<AccountDataEntry> {
+		let events = events_of(who);
+		if events.is_empty() {
+			return Vec::new()
+		}
+		vec![AccountDataEntry::new(EVENTS_KEY, &events)]
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itp_sgx_externalities::{SgxExternalities, SgxExternalitiesTrait};
+	use sp_core::{ed25519, Pair};
+	use sp_keyring::AccountKeyring;
+
+	fn decrypt_all<P: Pair>(
+		scheme: AccountKeyScheme,
+		pair: &P,
+		who: &AccountId,
+	) -> Vec<ConfidentialEventRecord> {
+		let secret = ecies::secret_scalar(scheme, &pair.to_raw_vec()).unwrap();
+		events_of(who)
+			.iter()
+			.map(|e| ConfidentialEventRecord::decrypt(scheme, secret, who.as_ref(), e).unwrap())
+			.collect()
+	}
+
+	#[test]
+	fn only_the_latest_events_are_kept() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+
+		state.execute_with(|| {
+			for amount in 0..(MAX_EVENTS_PER_ACCOUNT as Balance + 3) {
+				deposit_event(&alice, ConfidentialEvent::Shielded { amount });
+			}
+
+			let events =
+				decrypt_all(AccountKeyScheme::Sr25519, &AccountKeyring::Alice.pair(), &alice);
+			assert_eq!(events.len(), MAX_EVENTS_PER_ACCOUNT);
+			assert_eq!(events[0].event, ConfidentialEvent::Shielded { amount: 3 });
+			assert!(events_of(&AccountKeyring::Bob.public().into()).is_empty());
+
+			clear_events(&alice);
+			assert!(events_of(&alice).is_empty());
+		});
+	}
+
+	#[test]
+	fn events_are_encrypted_to_the_key_scheme_of_the_account() {
+		let mut state = SgxExternalities::default();
+		let pair = ed25519::Pair::from_seed(&[9u8; 32]);
+		let who: AccountId = pair.public().into();
+
+		state.execute_with(|| {
+			note_key_scheme(&who, &pair.sign(b"call").into());
+			deposit_event(&who, ConfidentialEvent::Shielded { amount: 5 });
+
+			let events = decrypt_all(AccountKeyScheme::Ed25519, &pair, &who);
+			assert_eq!(events[0].event, ConfidentialEvent::Shielded { amount: 5 });
+		});
+	}
+
+	#[test]
+	fn events_of_ecdsa_accounts_are_not_recorded() {
+		let mut state = SgxExternalities::default();
+		let pair = sp_core::ecdsa::Pair::from_seed(&[9u8; 32]);
+		let who: AccountId = AccountKeyring::Dave.public().into();
+
+		state.execute_with(|| {
+			note_key_scheme(&who, &pair.sign(b"call").into());
+			deposit_event(&who, ConfidentialEvent::Shielded { amount: 5 });
+
+			assert!(events_of(&who).is_empty());
+		});
+	}
+}
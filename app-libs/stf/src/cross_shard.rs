@@ -0,0 +1,356 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Cross-shard messaging, see [itp_stf_primitives::cross_shard].
+//!
+//! Messages are emitted by the `cross_shard_transfer` trusted call and delivered with the
+//! enclave-signed `cross_shard_deliver` call on the destination shard. The enclave-signed
+//! `cross_shard_commit_outbox` call commits to the sent messages for shards of other workers,
+//! and `cross_shard_anchor_commitment` records the commitments of other shards.
+
+use codec::{Decode, Encode};
+use itp_stf_primitives::{
+	cross_shard::{
+		message_leaf, AnchoredCommitment, CommitmentIndex, CrossShardMessage, CrossShardPayload,
+		CrossShardProof, MessageNonce, OutboxCommitment, ANCHORED_COMMITMENT_KEY,
+		COMMITMENT_INDEX_KEY, COMMITMENT_KEY, COMMITTED_MESSAGES_KEY, CROSS_SHARD_STORAGE_PREFIX,
+		INBOX_NONCE_KEY, MESSAGE_COMMITMENT_KEY, OUTBOX_KEY, OUTBOX_NONCE_KEY, OUTGOING_KEY,
+		UNCOMMITTED_KEY,
+	},
+	error::{StfError, StfResult},
+	merkle::{merkle_proof, merkle_root},
+	types::{AccountId, ShardIdentifier},
+};
+use itp_storage::{storage_double_map_key, storage_map_key, storage_value_key, StorageHasher};
+use itp_types::SidechainBlockNumber;
+use log::*;
+use sp_core::H256;
+use std::prelude::v1::*;
+
+/// Number of sidechain blocks between two outbox commitments of a shard.
+pub const COMMITMENT_PERIOD: SidechainBlockNumber = 10;
+
+fn outbox_nonce_key(destination: &ShardIdentifier) -> Vec<u8> {
+	storage_map_key(
+		CROSS_SHARD_STORAGE_PREFIX,
+		OUTBOX_NONCE_KEY,
+		destination,
+		&StorageHasher::Blake2_128Concat,
+	)
+}
+
+fn outbox_key(destination: &ShardIdentifier, nonce: MessageNonce) -> Vec<u8> {
+	storage_double_map_key(
+		CROSS_SHARD_STORAGE_PREFIX,
+		OUTBOX_KEY,
+		destination,
+		&StorageHasher::Blake2_128Concat,
+		&nonce,
+		&StorageHasher::Blake2_128Concat,
+	)
+}
+
+fn inbox_nonce_key(source: &ShardIdentifier) -> Vec<u8> {
+	storage_map_key(
+		CROSS_SHARD_STORAGE_PREFIX,
+		INBOX_NONCE_KEY,
+		source,
+		&StorageHasher::Blake2_128Concat,
+	)
+}
+
+fn outgoing_key() -> Vec<u8> {
+	storage_value_key(CROSS_SHARD_STORAGE_PREFIX, OUTGOING_KEY)
+}
+
+fn uncommitted_key() -> Vec<u8> {
+	storage_value_key(CROSS_SHARD_STORAGE_PREFIX, UNCOMMITTED_KEY)
+}
+
+fn commitment_index_key() -> Vec<u8> {
+	storage_value_key(CROSS_SHARD_STORAGE_PREFIX, COMMITMENT_INDEX_KEY)
+}
+
+fn commitment_key(index: CommitmentIndex) -> Vec<u8> {
+	storage_map_key(
+		CROSS_SHARD_STORAGE_PREFIX,
+		COMMITMENT_KEY,
+		&index,
+		&StorageHasher::Blake2_128Concat,
+	)
+}
+
+fn committed_messages_key(index: CommitmentIndex) -> Vec<u8> {
+	storage_map_key(
+		CROSS_SHARD_STORAGE_PREFIX,
+		COMMITTED_MESSAGES_KEY,
+		&index,
+		&StorageHasher::Blake2_128Concat,
+	)
+}
+
+fn message_commitment_key(destination: &ShardIdentifier, nonce: MessageNonce) -> Vec<u8> {
+	storage_double_map_key(
+		CROSS_SHARD_STORAGE_PREFIX,
+		MESSAGE_COMMITMENT_KEY,
+		destination,
+		&StorageHasher::Blake2_128Concat,
+		&nonce,
+		&StorageHasher::Blake2_128Concat,
+	)
+}
+
+fn anchored_commitment_key(source: &ShardIdentifier, index: CommitmentIndex) -> Vec<u8> {
+	storage_double_map_key(
+		CROSS_SHARD_STORAGE_PREFIX,
+		ANCHORED_COMMITMENT_KEY,
+		source,
+		&StorageHasher::Blake2_128Concat,
+		&index,
+		&StorageHasher::Blake2_128Concat,
+	)
+}
+
+fn get_decoded<V: Decode>(key: &[u8]) -> Option<V> {
+	sp_io::storage::get(key).and_then(|v| Decode::decode(&mut v.as_slice()).ok())
+}
+
+/// Nonce of the next message to the destination shard.
+pub fn outbox_nonce(destination: &ShardIdentifier) -> MessageNonce {
+	get_decoded(&outbox_nonce_key(destination)).unwrap_or_default()
+}
+
+/// A sent message, kept in the outbox of the source shard.
+pub fn outbox_message(
+	destination: &ShardIdentifier,
+	nonce: MessageNonce,
+) -> Option<CrossShardMessage> {
+	get_decoded(&outbox_key(destination, nonce))
+}
+
+/// An outbox commitment of this shard.
+pub fn commitment(index: CommitmentIndex) -> Option<OutboxCommitment> {
+	get_decoded(&commitment_key(index))
+}
+
+/// Commits to the messages sent since the last commitment, if there are any.
+///
+/// Messages to shards of the same worker are committed too, they can be relayed like any other
+/// message in case the router failed to deliver them.
+pub fn commit_outbox(shard: ShardIdentifier) -> Option<OutboxCommitment> {
+	let uncommitted: Vec<(ShardIdentifier, MessageNonce)> =
+		get_decoded(&uncommitted_key()).unwrap_or_default();
+	if uncommitted.is_empty() {
+		return None
+	}
+	let leaves: Vec<H256> = uncommitted
+		.iter()
+		.filter_map(|(destination, nonce)| outbox_message(destination, *nonce))
+		.map(|message| message_leaf(&message))
+		.collect();
+	let index: CommitmentIndex = get_decoded(&commitment_index_key()).unwrap_or_default();
+	let commitment = OutboxCommitment {
+		shard,
+		index,
+		root: merkle_root(&leaves),
+		message_count: leaves.len() as u32,
+	};
+	debug!("Committing to {} cross-shard message(s) with {:?}", leaves.len(), commitment);
+
+	for (destination, nonce) in uncommitted.iter() {
+		sp_io::storage::set(&message_commitment_key(destination, *nonce), &index.encode());
+	}
+	sp_io::storage::set(&committed_messages_key(index), &uncommitted.encode());
+	sp_io::storage::set(&commitment_key(index), &commitment.encode());
+	sp_io::storage::set(&commitment_index_key(), &(index + 1).encode());
+	sp_io::storage::clear(&uncommitted_key());
+	Some(commitment)
+}
+
+/// Proof of a sent message against the commitment it is part of, `None` if the message hasn't
+/// been committed yet.
+pub fn message_proof(
+	destination: &ShardIdentifier,
+	nonce: MessageNonce,
+) -> Option<CrossShardProof> {
+	let index: CommitmentIndex = get_decoded(&message_commitment_key(destination, nonce))?;
+	let commitment = commitment(index)?;
+	let committed: Vec<(ShardIdentifier, MessageNonce)> =
+		get_decoded(&committed_messages_key(index))?;
+	let leaf_index = committed.iter().position(|entry| entry == &(*destination, nonce))?;
+	let messages = committed
+		.iter()
+		.map(|(destination, nonce)| outbox_message(destination, *nonce))
+		.collect::<Option<Vec<_>>>()?;
+	let leaves: Vec<H256> = messages.iter().map(message_leaf).collect();
+
+	Some(CrossShardProof {
+		commitment,
+		message: messages[leaf_index].clone(),
+		leaf_index: leaf_index as u32,
+		proof: merkle_proof(&leaves, leaf_index)?,
+	})
+}
+
+/// Records a commitment of another shard that was published on the parentchain.
+pub fn anchor(anchored: &AnchoredCommitment) {
+	let commitment = &anchored.commitment;
+	debug!("Anchoring cross-shard commitment {:?}", commitment);
+	sp_io::storage::set(
+		&anchored_commitment_key(&commitment.shard, commitment.index),
+		&anchored.encode(),
+	);
+}
+
+/// A commitment of the source shard, if it has been anchored in this shard.
+pub fn anchored_commitment(
+	source: &ShardIdentifier,
+	index: CommitmentIndex,
+) -> Option<AnchoredCommitment> {
+	get_decoded(&anchored_commitment_key(source, index))
+}
+
+/// Nonce of the next message expected from the source shard.
+pub fn inbox_nonce(source: &ShardIdentifier) -> MessageNonce {
+	get_decoded(&inbox_nonce_key(source)).unwrap_or_default()
+}
+
+/// Emits a message to the destination shard, numbered with the next nonce of its outbox.
+pub fn send(
+	destination: ShardIdentifier,
+	sender: AccountId,
+	payload: CrossShardPayload,
+) -> CrossShardMessage {
+	let nonce = outbox_nonce(&destination);
+	let message = CrossShardMessage { destination, nonce, sender, payload };
+	debug!("Sending cross-shard message {} to shard {:?}", nonce, destination);
+
+	sp_io::storage::set(&outbox_key(&destination, nonce), &message.encode());
+	sp_io::storage::set(&outbox_nonce_key(&destination), &(nonce + 1).encode());
+
+	let mut outgoing: Vec<CrossShardMessage> = get_decoded(&outgoing_key()).unwrap_or_default();
+	outgoing.push(message.clone());
+	sp_io::storage::set(&outgoing_key(), &outgoing.encode());
+
+	let mut uncommitted: Vec<(ShardIdentifier, MessageNonce)> =
+		get_decoded(&uncommitted_key()).unwrap_or_default();
+	uncommitted.push((destination, nonce));
+	sp_io::storage::set(&uncommitted_key(), &uncommitted.encode());
+	message
+}
+
+/// Removes and returns the messages emitted since the last call.
+pub fn take_outgoing() -> Vec<CrossShardMessage> {
+	let outgoing = get_decoded(&outgoing_key()).unwrap_or_default();
+	sp_io::storage::clear(&outgoing_key());
+	outgoing
+}
+
+/// Accepts a message from the source shard if it has the next expected nonce.
+pub fn receive(source: &ShardIdentifier, message: &CrossShardMessage) -> StfResult<()> {
+	let expected = inbox_nonce(source);
+	if message.nonce != expected {
+		return Err(StfError::UnexpectedMessageNonce(message.nonce, expected))
+	}
+	debug!("Receiving cross-shard message {} from shard {:?}", message.nonce, source);
+	sp_io::storage::set(&inbox_nonce_key(source), &(expected + 1).encode());
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itp_sgx_externalities::{SgxExternalities, SgxExternalitiesTrait};
+	use sp_keyring::AccountKeyring;
+
+	fn transfer(amount: u128) -> CrossShardPayload {
+		CrossShardPayload::Transfer { to: AccountKeyring::Bob.public().into(), amount }
+	}
+
+	#[test]
+	fn sent_messages_are_numbered_per_destination() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+		let (shard_b, shard_c) = (ShardIdentifier::repeat_byte(2), ShardIdentifier::repeat_byte(3));
+
+		state.execute_with(|| {
+			assert_eq!(send(shard_b, alice.clone(), transfer(1)).nonce, 0);
+			assert_eq!(send(shard_b, alice.clone(), transfer(2)).nonce, 1);
+			assert_eq!(send(shard_c, alice.clone(), transfer(3)).nonce, 0);
+
+			assert_eq!(outbox_nonce(&shard_b), 2);
+			assert_eq!(outbox_message(&shard_b, 1).unwrap().payload, transfer(2));
+
+			assert_eq!(take_outgoing().len(), 3);
+			assert!(take_outgoing().is_empty());
+			// The outbox is kept, only the outgoing messages of the batch are drained.
+			assert!(outbox_message(&shard_b, 0).is_some());
+		});
+	}
+
+	#[test]
+	fn committed_messages_can_be_proven() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+		let shard_a = ShardIdentifier::repeat_byte(1);
+		let (shard_b, shard_c) = (ShardIdentifier::repeat_byte(2), ShardIdentifier::repeat_byte(3));
+
+		state.execute_with(|| {
+			send(shard_b, alice.clone(), transfer(1));
+			send(shard_c, alice.clone(), transfer(2));
+			send(shard_b, alice.clone(), transfer(3));
+			assert!(message_proof(&shard_b, 0).is_none());
+
+			let first = commit_outbox(shard_a).unwrap();
+			assert_eq!(first.index, 0);
+			assert_eq!(first.message_count, 3);
+			assert_eq!(commit_outbox(shard_a), None);
+
+			send(shard_b, alice.clone(), transfer(4));
+			assert_eq!(commit_outbox(shard_a).unwrap().index, 1);
+
+			let proof = message_proof(&shard_b, 1).unwrap();
+			assert_eq!(proof.commitment, first);
+			assert_eq!(proof.message.payload, transfer(3));
+			assert!(proof.verify());
+			assert!(message_proof(&shard_b, 2).unwrap().verify());
+			assert!(message_proof(&shard_b, 3).is_none());
+		});
+	}
+
+	#[test]
+	fn messages_are_received_exactly_once_and_in_order() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+		let shard_a = ShardIdentifier::repeat_byte(1);
+		let message = |nonce| CrossShardMessage {
+			destination: ShardIdentifier::repeat_byte(2),
+			nonce,
+			sender: alice.clone(),
+			payload: transfer(1),
+		};
+
+		state.execute_with(|| {
+			assert_eq!(receive(&shard_a, &message(1)), Err(StfError::UnexpectedMessageNonce(1, 0)));
+			assert!(receive(&shard_a, &message(0)).is_ok());
+			assert_eq!(receive(&shard_a, &message(0)), Err(StfError::UnexpectedMessageNonce(0, 1)));
+			assert!(receive(&shard_a, &message(1)).is_ok());
+
+			assert_eq!(inbox_nonce(&shard_a), 2);
+			assert_eq!(inbox_nonce(&ShardIdentifier::repeat_byte(3)), 0);
+		});
+	}
+}
@@ -15,11 +15,12 @@
 
 */
 use crate::helpers::{get_storage_double_map, get_storage_map};
+use ita_sgx_runtime::{AddressMapping, HashedAddressMapping};
 use itp_storage::StorageHasher;
 use itp_types::{AccountId, Nonce};
 use sha3::{Digest, Keccak256};
-use sp_core::{H160, H256};
-use std::prelude::v1::*;
+use sp_core::{ecdsa, H160, H256};
+use std::{format, prelude::v1::*};
 
 pub fn get_evm_account_codes(evm_account: &H160) -> Option<Vec<u8>> {
 	get_storage_map("Evm", "AccountCodes", evm_account, &StorageHasher::Blake2_128Concat)
@@ -63,3 +64,114 @@ pub fn get_evm_account(account: &AccountId) -> H160 {
 	evm_acc_slice.copy_from_slice((<[u8; 32]>::from(account.clone())).get(0..20).unwrap());
 	evm_acc_slice.into()
 }
+
+/// Substrate account an ethereum address is mapped to within the shard.
+pub fn eth_account_id(address: &H160) -> AccountId {
+	HashedAddressMapping::into_account_id(*address)
+}
+
+/// Hash of a message signed according to EIP-191 (`personal_sign`), i.e.
+/// `keccak256("\x19Ethereum Signed Message:\n" ++ len(message) ++ message)`.
+pub fn eth_message_hash(message: &[u8]) -> [u8; 32] {
+	let mut hasher = Keccak256::new();
+	hasher.update(format!("\x19Ethereum Signed Message:\n{}", message.len()).as_bytes());
+	hasher.update(message);
+	hasher.finalize().into()
+}
+
+/// Recovers the ethereum address that signed `message` according to EIP-191.
+///
+/// The signature is expected as `r ++ s ++ v`, with `v` either being 0/1 or 27/28.
+pub fn recover_eth_signer(signature: &ecdsa::Signature, message: &[u8]) -> Option<H160> {
+	let signature: &[u8; 65] = signature.as_ref();
+	let recovery_id = match signature[64] {
+		v @ 0..=1 => v,
+		v @ 27..=28 => v - 27,
+		_ => return None,
+	};
+	let parsed_signature = libsecp256k1::Signature::parse_standard_slice(&signature[..64]).ok()?;
+	let recovery_id = libsecp256k1::RecoveryId::parse(recovery_id).ok()?;
+	let message = libsecp256k1::Message::parse(&eth_message_hash(message));
+
+	let public = libsecp256k1::recover(&message, &parsed_signature, &recovery_id).ok()?;
+	// The address consists of the last 20 bytes of the hashed uncompressed public key
+	// (without the 0x04 prefix).
+	let public_hash = Keccak256::digest(&public.serialize()[1..]);
+	Some(H160::from_slice(&public_hash[12..]))
+}
+
+/// Verifies an EIP-191 signature of the ethereum account that is mapped to `account`.
+pub fn verify_eth_signature(
+	signature: &ecdsa::Signature,
+	message: &[u8],
+	account: &AccountId,
+) -> bool {
+	recover_eth_signer(signature, message)
+		.map_or(false, |address| &eth_account_id(&address) == account)
+}
+
+/// Signs `message` according to EIP-191, like an ethereum wallet would.
+pub fn eth_sign(pair: &ecdsa::Pair, message: &[u8]) -> ecdsa::Signature {
+	pair.sign_prehashed(&eth_message_hash(message))
+}
+
+/// Ethereum address of a secp256k1 key pair.
+pub fn eth_address(pair: &ecdsa::Pair) -> H160 {
+	let public = libsecp256k1::PublicKey::parse_compressed(&pair.public().0)
+		.expect("public key of a valid pair is valid; qed");
+	H160::from_slice(&Keccak256::digest(&public.serialize()[1..])[12..])
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::Pair;
+
+	#[test]
+	fn recover_eth_signer_works() {
+		let pair = ecdsa::Pair::from_seed(&[7u8; 32]);
+		let message = b"trusted call payload";
+
+		let signature = eth_sign(&pair, message);
+
+		assert_eq!(recover_eth_signer(&signature, message), Some(eth_address(&pair)));
+	}
+
+	#[test]
+	fn recover_eth_signer_accepts_wallet_recovery_id() {
+		let pair = ecdsa::Pair::from_seed(&[7u8; 32]);
+		let message = b"trusted call payload";
+		let mut signature = eth_sign(&pair, message);
+		signature.0[64] += 27;
+
+		assert_eq!(recover_eth_signer(&signature, message), Some(eth_address(&pair)));
+	}
+
+	#[test]
+	fn eth_address_of_known_key_is_correct() {
+		// Private key 0x01 has the well-known address 0x7e5f...bdf.
+		let mut seed = [0u8; 32];
+		seed[31] = 1;
+		let pair = ecdsa::Pair::from_seed(&seed);
+
+		assert_eq!(
+			eth_address(&pair),
+			H160::from([
+				0x7e, 0x5f, 0x45, 0x52, 0x09, 0x1a, 0x69, 0x12, 0x5d, 0x5d, 0xfc, 0xb7, 0xb8, 0xc2,
+				0x65, 0x90, 0x29, 0x39, 0x5b, 0xdf
+			])
+		);
+	}
+
+	#[test]
+	fn verify_eth_signature_fails_for_other_account() {
+		let pair = ecdsa::Pair::from_seed(&[7u8; 32]);
+		let other = ecdsa::Pair::from_seed(&[8u8; 32]);
+		let message = b"trusted call payload";
+
+		let signature = eth_sign(&pair, message);
+
+		assert!(verify_eth_signature(&signature, message, &eth_account_id(&eth_address(&pair))));
+		assert!(!verify_eth_signature(&signature, message, &eth_account_id(&eth_address(&other))));
+	}
+}
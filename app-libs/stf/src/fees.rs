@@ -0,0 +1,172 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Execution fees of the shard, see [itp_stf_primitives::fees].
+//!
+//! The fee is charged from the fee payer of a call before it is dispatched, and kept even if
+//! the dispatch fails. Root and the enclave signer don't pay fees, so that the administration
+//! and the maintenance of a shard keep working when its accounts run dry.
+
+use crate::{helpers::get_storage_value, shard_acl::is_exempt, TrustedCall};
+use codec::Encode;
+use frame_support::traits::{Currency, ExistenceRequirement, WithdrawReasons};
+use ita_sgx_runtime::Runtime;
+use itp_stf_primitives::{
+	error::{StfError, StfResult},
+	fees::{
+		default_fee_multiplier, FeeBalance, FeeConfig, FeeDestination, FeeMultiplier,
+		FEES_STORAGE_PREFIX, FEE_CONFIG_KEY, FEE_MULTIPLIER_KEY,
+	},
+	types::AccountId,
+};
+use itp_storage::storage_value_key;
+use itp_utils::stringify::account_id_to_string;
+use log::*;
+
+type Balances = pallet_balances::Pallet<Runtime>;
+
+pub fn fee_config() -> FeeConfig {
+	get_storage_value(FEES_STORAGE_PREFIX, FEE_CONFIG_KEY).unwrap_or_default()
+}
+
+pub fn fee_multiplier() -> FeeMultiplier {
+	get_storage_value(FEES_STORAGE_PREFIX, FEE_MULTIPLIER_KEY)
+		.unwrap_or_else(default_fee_multiplier)
+}
+
+pub fn set_fee_config(config: FeeConfig) {
+	debug!("Setting fee config to {:?}", config);
+	sp_io::storage::set(&storage_value_key(FEES_STORAGE_PREFIX, FEE_CONFIG_KEY), &config.encode());
+}
+
+pub fn set_fee_multiplier(multiplier: FeeMultiplier) {
+	debug!("Setting fee multiplier to {:?}", multiplier);
+	sp_io::storage::set(
+		&storage_value_key(FEES_STORAGE_PREFIX, FEE_MULTIPLIER_KEY),
+		&multiplier.encode(),
+	);
+}
+
+/// Fee of the call with the current fee config and multiplier, as charged by [charge_fee].
+pub fn fee_of(call: &TrustedCall) -> FeeBalance {
+	fee_config().fee(call.encoded_size(), call.weight(), fee_multiplier())
+}
+
+/// Charges the fee of the call from its fee payer, who has to stay above the existential deposit.
+pub fn charge_fee(call: &TrustedCall) -> StfResult<()> {
+	let payer = call.fee_payer();
+	if is_exempt(payer) {
+		return Ok(())
+	}
+	let fee = fee_of(call);
+	if fee == 0 {
+		return Ok(())
+	}
+	debug!("Charging fee of {} from {}", fee, account_id_to_string(payer));
+	let imbalance = Balances::withdraw(
+		payer,
+		fee,
+		WithdrawReasons::TRANSACTION_PAYMENT,
+		ExistenceRequirement::KeepAlive,
+	)
+	.map_err(|_| StfError::CannotPayFee(payer.clone(), fee))?;
+
+	match fee_config().destination {
+		// Dropping the imbalance reduces the total issuance.
+		FeeDestination::Burn => drop(imbalance),
+		FeeDestination::Treasury(treasury) => Balances::resolve_creating(&treasury, imbalance),
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itp_sgx_externalities::{SgxExternalities, SgxExternalitiesTrait};
+	use sp_keyring::AccountKeyring;
+	use sp_runtime::FixedPointNumber;
+
+	fn endow(who: &AccountId, free: FeeBalance) {
+		let _ = Balances::deposit_creating(who, free);
+	}
+
+	fn paid_fee_config(destination: FeeDestination) -> FeeConfig {
+		FeeConfig { base_fee: 1_000, fee_per_byte: 0, fee_per_micros: 0, destination }
+	}
+
+	#[test]
+	fn calls_are_free_without_fee_config() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+
+		state.execute_with(|| {
+			assert_eq!(fee_of(&TrustedCall::noop(alice.clone())), 0);
+			assert!(charge_fee(&TrustedCall::noop(alice)).is_ok());
+		});
+	}
+
+	#[test]
+	fn fee_is_burnt() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+
+		state.execute_with(|| {
+			endow(&alice, 10_000);
+			set_fee_config(paid_fee_config(FeeDestination::Burn));
+
+			charge_fee(&TrustedCall::noop(alice.clone())).unwrap();
+
+			assert_eq!(Balances::free_balance(&alice), 9_000);
+			assert_eq!(Balances::total_issuance(), 9_000);
+		});
+	}
+
+	#[test]
+	fn fee_is_paid_to_treasury_and_scaled_by_multiplier() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+		let treasury: AccountId = AccountKeyring::Ferdie.public().into();
+
+		state.execute_with(|| {
+			endow(&alice, 10_000);
+			set_fee_config(paid_fee_config(FeeDestination::Treasury(treasury.clone())));
+			set_fee_multiplier(FeeMultiplier::saturating_from_integer(2));
+
+			charge_fee(&TrustedCall::noop(alice.clone())).unwrap();
+
+			assert_eq!(Balances::free_balance(&alice), 8_000);
+			assert_eq!(Balances::free_balance(&treasury), 2_000);
+		});
+	}
+
+	#[test]
+	fn fee_payer_has_to_stay_alive() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+
+		state.execute_with(|| {
+			endow(&alice, 1_200);
+			set_fee_config(paid_fee_config(FeeDestination::Burn));
+
+			assert_eq!(
+				charge_fee(&TrustedCall::noop(alice.clone())),
+				Err(StfError::CannotPayFee(alice.clone(), 1_000))
+			);
+			assert_eq!(Balances::free_balance(&alice), 1_200);
+		});
+	}
+}
@@ -15,16 +15,22 @@
 
 */
 
+use crate::{
+	account_export, assets, confidential_events, fees, helpers::verify_trusted_signature, rent,
+	reserves, TrustedCall,
+};
 use codec::{Decode, Encode};
 use ita_sgx_runtime::System;
 use itp_stf_interface::ExecuteGetter;
 use itp_stf_primitives::{
-	traits::GetterAuthorization,
+	account_export::ExportKey,
+	pagination::{paginate, PageRequest},
+	traits::{GetterAuthorization, GetterExpiry, GetterResponseEncryption},
 	types::{AccountId, KeyPair, Signature},
 };
+use itp_types::SidechainTimestamp;
 use itp_utils::stringify::account_id_to_string;
 use log::*;
-use sp_runtime::traits::Verify;
 use sp_std::vec;
 use std::prelude::v1::*;
 
@@ -32,7 +38,9 @@ use std::prelude::v1::*;
 use ita_sgx_runtime::{AddressMapping, HashedAddressMapping};
 
 #[cfg(feature = "evm")]
-use crate::evm_helpers::{get_evm_account, get_evm_account_codes, get_evm_account_storages};
+use crate::evm_helpers::{
+	eth_sign, get_evm_account, get_evm_account_codes, get_evm_account_storages,
+};
 
 use itp_stf_primitives::traits::PoolTransactionValidation;
 #[cfg(feature = "evm")]
@@ -74,6 +82,27 @@ impl GetterAuthorization for Getter {
 	}
 }
 
+impl GetterExpiry for Getter {
+	fn is_expired(&self, now: u64) -> bool {
+		match self {
+			Self::trusted(ref getter) => getter.valid_until < now,
+			Self::public(_) => false,
+		}
+	}
+}
+
+impl GetterResponseEncryption for Getter {
+	fn response_key(&self) -> Option<&ExportKey> {
+		match self {
+			Self::trusted(TrustedGetterSigned {
+				getter: TrustedGetter::account_data_export(_, key),
+				..
+			}) => Some(key),
+			_ => None,
+		}
+	}
+}
+
 impl PoolTransactionValidation for Getter {
 	fn validate(&self) -> Result<ValidTransaction, TransactionValidityError> {
 		match self {
@@ -94,6 +123,12 @@ impl PoolTransactionValidation for Getter {
 #[allow(non_camel_case_types)]
 pub enum PublicGetter {
 	some_value,
+	/// The `FeeConfig` and the `FeeMultiplier` of the shard.
+	fee_config,
+	/// Fee that would be charged for the call, the signature is not needed to estimate it.
+	fee_estimate(TrustedCall),
+	/// The latest `ReservesReport` of the shard.
+	reserves_report,
 }
 
 #[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
@@ -102,6 +137,16 @@ pub enum TrustedGetter {
 	free_balance(AccountId),
 	reserved_balance(AccountId),
 	nonce(AccountId),
+	rent_status(AccountId),
+	asset_balance(AccountId, ParentchainAssetId),
+	confidential_events(AccountId),
+	/// Like `confidential_events`, but returns a `Page` of the events, oldest first.
+	confidential_events_page(AccountId, PageRequest),
+	/// Page of the audit log of dropped operations, see `itp_audit_log`. Only the root of the
+	/// shard may query it and it is served by the `state_getAuditLog` RPC method, not the state.
+	audit_log(AccountId, PageRequest),
+	/// `AccountDataExport` of all state of the account, encrypted to the given key.
+	account_data_export(AccountId, ExportKey),
 	#[cfg(feature = "evm")]
 	evm_nonce(AccountId),
 	#[cfg(feature = "evm")]
@@ -116,6 +161,12 @@ impl TrustedGetter {
 			TrustedGetter::free_balance(sender_account) => sender_account,
 			TrustedGetter::reserved_balance(sender_account) => sender_account,
 			TrustedGetter::nonce(sender_account) => sender_account,
+			TrustedGetter::rent_status(sender_account) => sender_account,
+			TrustedGetter::asset_balance(sender_account, _) => sender_account,
+			TrustedGetter::confidential_events(sender_account) => sender_account,
+			TrustedGetter::confidential_events_page(sender_account, _) => sender_account,
+			TrustedGetter::audit_log(sender_account, _) => sender_account,
+			TrustedGetter::account_data_export(sender_account, _) => sender_account,
 			#[cfg(feature = "evm")]
 			TrustedGetter::evm_nonce(sender_account) => sender_account,
 			#[cfg(feature = "evm")]
@@ -125,26 +176,69 @@ impl TrustedGetter {
 		}
 	}
 
-	pub fn sign(&self, pair: &KeyPair) -> TrustedGetterSigned {
-		let signature = pair.sign(self.encode().as_slice());
-		TrustedGetterSigned { getter: self.clone(), signature }
+	/// The payload that has to be signed by the sender account, the getter is rejected after
+	/// the sidechain timestamp `valid_until` (unix millis).
+	pub fn signing_payload(&self, valid_until: SidechainTimestamp) -> Vec<u8> {
+		(self, valid_until).encode()
+	}
+
+	pub fn sign(&self, pair: &KeyPair, valid_until: SidechainTimestamp) -> TrustedGetterSigned {
+		let signature = pair.sign(self.signing_payload(valid_until).as_slice());
+		TrustedGetterSigned { getter: self.clone(), valid_until, signature }
+	}
+
+	/// Signs the getter with an ethereum key (EIP-191).
+	#[cfg(feature = "evm")]
+	pub fn sign_eth(
+		&self,
+		pair: &sp_core::ecdsa::Pair,
+		valid_until: SidechainTimestamp,
+	) -> TrustedGetterSigned {
+		let signature = eth_sign(pair, self.signing_payload(valid_until).as_slice());
+		TrustedGetterSigned { getter: self.clone(), valid_until, signature: signature.into() }
 	}
 }
 
+/// Validity of a getter that is signed right before it is sent.
+pub const DEFAULT_GETTER_VALIDITY: SidechainTimestamp = 60_000;
+
+/// Expiry of a getter signed now and valid for the [DEFAULT_GETTER_VALIDITY].
+#[cfg(feature = "std")]
+pub fn default_getter_expiry() -> SidechainTimestamp {
+	let now = std::time::SystemTime::now()
+		.duration_since(std::time::UNIX_EPOCH)
+		.map(|d| d.as_millis() as SidechainTimestamp)
+		.unwrap_or_default();
+	now.saturating_add(DEFAULT_GETTER_VALIDITY)
+}
+
+/// Trusted getter signed by its sender account.
+///
+/// The signature covers the expiry, so an intercepted getter can't be replayed to obtain the
+/// state after `valid_until`.
 #[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
 pub struct TrustedGetterSigned {
 	pub getter: TrustedGetter,
+	/// Sidechain timestamp (unix millis) after which the getter is rejected.
+	pub valid_until: SidechainTimestamp,
 	pub signature: Signature,
 }
 
 impl TrustedGetterSigned {
-	pub fn new(getter: TrustedGetter, signature: Signature) -> Self {
-		TrustedGetterSigned { getter, signature }
+	pub fn new(
+		getter: TrustedGetter,
+		valid_until: SidechainTimestamp,
+		signature: Signature,
+	) -> Self {
+		TrustedGetterSigned { getter, valid_until, signature }
 	}
 
 	pub fn verify_signature(&self) -> bool {
-		self.signature
-			.verify(self.getter.encode().as_slice(), self.getter.sender_account())
+		verify_trusted_signature(
+			&self.signature,
+			self.getter.signing_payload(self.valid_until).as_slice(),
+			self.getter.sender_account(),
+		)
 	}
 }
 
@@ -187,6 +281,54 @@ impl ExecuteGetter for TrustedGetterSigned {
 				debug!("Account nonce is {}", nonce);
 				Some(nonce.encode())
 			},
+			TrustedGetter::rent_status(who) => {
+				let status = rent::rent_status(&who);
+				debug!("TrustedGetter rent_status");
+				debug!("Rent status of {} is {:?}", account_id_to_string(&who), status);
+				Some(status.encode())
+			},
+			TrustedGetter::asset_balance(who, asset_id) => {
+				let balance = assets::balance_of(&asset_id, &who);
+				debug!("TrustedGetter asset_balance");
+				debug!(
+					"Balance of {} for asset {} is {}",
+					account_id_to_string(&who),
+					asset_id,
+					balance
+				);
+				Some(balance.encode())
+			},
+			TrustedGetter::confidential_events(who) => {
+				let events = confidential_events::events_of(&who);
+				debug!("TrustedGetter confidential_events");
+				debug!("{} has {} confidential event(s)", account_id_to_string(&who), events.len());
+				Some(events.encode())
+			},
+			TrustedGetter::confidential_events_page(who, request) => {
+				let page = paginate(confidential_events::events_of(&who), &request);
+				debug!("TrustedGetter confidential_events_page");
+				debug!(
+					"Returning {} confidential event(s) of {}, next cursor: {:?}",
+					page.items.len(),
+					account_id_to_string(&who),
+					page.next
+				);
+				Some(page.encode())
+			},
+			TrustedGetter::audit_log(..) => {
+				debug!("TrustedGetter audit_log is not served from the state");
+				None
+			},
+			TrustedGetter::account_data_export(who, _) => {
+				let export = account_export::export(&who);
+				debug!("TrustedGetter account_data_export");
+				debug!(
+					"Exporting {} pallet(s) of {}",
+					export.pallets.len(),
+					account_id_to_string(&who)
+				);
+				Some(export.encode())
+			},
 			#[cfg(feature = "evm")]
 			TrustedGetter::evm_nonce(who) => {
 				let evm_account = get_evm_account(&who);
@@ -228,6 +370,10 @@ impl ExecuteGetter for PublicGetter {
 	fn execute(self) -> Option<Vec<u8>> {
 		match self {
 			PublicGetter::some_value => Some(42u32.encode()),
+			PublicGetter::fee_config => Some((fees::fee_config(), fees::fee_multiplier()).encode()),
+			PublicGetter::fee_estimate(call) => Some(fees::fee_of(&call).encode()),
+			PublicGetter::reserves_report =>
+				reserves::latest_report().map(|report| report.encode()),
 		}
 	}
 
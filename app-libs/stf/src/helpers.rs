@@ -14,12 +14,21 @@
 	limitations under the License.
 
 */
+#[cfg(feature = "evm")]
+use crate::evm_helpers::verify_eth_signature;
 use crate::ENCLAVE_ACCOUNT_KEY;
 use codec::{Decode, Encode};
-use itp_stf_primitives::error::{StfError, StfResult};
+use itp_stf_primitives::{
+	error::{StfError, StfResult},
+	types::{AccountId, Signature},
+};
 use itp_storage::{storage_double_map_key, storage_map_key, storage_value_key, StorageHasher};
+use itp_types::{SidechainBlockNumber, SidechainTimestamp};
 use itp_utils::stringify::account_id_to_string;
 use log::*;
+use sp_runtime::traits::Verify;
+#[cfg(feature = "evm")]
+use sp_runtime::MultiSignature;
 use std::prelude::v1::*;
 
 pub fn get_storage_value<V: Decode>(
@@ -59,6 +68,21 @@ pub fn get_storage_double_map<K: Encode, Q: Encode, V: Decode + Clone>(
 	get_storage_by_key_hash(key)
 }
 
+/// Verifies the signature of a trusted operation.
+///
+/// With the `evm` feature, an ecdsa signature is alternatively accepted if it is an EIP-191
+/// signature of the ethereum account that is mapped to `signer`.
+pub fn verify_trusted_signature(signature: &Signature, payload: &[u8], signer: &AccountId) -> bool {
+	if signature.verify(payload, signer) {
+		return true
+	}
+	#[cfg(feature = "evm")]
+	if let MultiSignature::Ecdsa(eth_signature) = signature {
+		return verify_eth_signature(eth_signature, payload, signer)
+	}
+	false
+}
+
 /// Get value in storage.
 pub fn get_storage_by_key_hash<V: Decode>(key: Vec<u8>) -> Option<V> {
 	if let Some(value_encoded) = sp_io::storage::get(&key) {
@@ -100,6 +124,37 @@ pub fn ensure_enclave_signer_account<AccountId: Encode + Decode + PartialEq>(
 	}
 }
 
-pub fn set_block_number(block_number: u32) {
+/// Trusted time of the sidechain block that is currently being produced, in millis since the
+/// unix epoch.
+///
+/// The block proposer takes it from the enclave's secure time service, which cross-checks the
+/// host clock against the parentchain, so trusted calls can rely on it.
+pub fn trusted_time() -> SidechainTimestamp {
+	get_storage_value("System", "Timestamp").unwrap_or_default()
+}
+
+/// Number of the sidechain block that is currently being produced.
+pub fn current_block_number() -> SidechainBlockNumber {
+	get_storage_value("System", "Number").unwrap_or_default()
+}
+
+/// Keys of the entries of a `Blake2_128Concat` hashed map under `prefix`. For a double map, the
+/// prefix of the entries with a given first key yields the second keys.
+pub fn storage_map_keys<K: Decode>(prefix: &[u8]) -> Vec<K> {
+	let mut keys = Vec::new();
+	let mut next = sp_io::storage::next_key(prefix);
+	while let Some(key) = next.filter(|key| key.starts_with(prefix)) {
+		// Skip the 128 bit hash in front of the encoded key.
+		if let Some(mut encoded) = key.get(prefix.len() + 16..) {
+			if let Ok(decoded) = K::decode(&mut encoded) {
+				keys.push(decoded);
+			}
+		}
+		next = sp_io::storage::next_key(&key);
+	}
+	keys
+}
+
+pub fn set_block_number(block_number: SidechainBlockNumber) {
 	sp_io::storage::set(&storage_value_key("System", "Number"), &block_number.encode());
 }
@@ -30,11 +30,22 @@ pub use getter::*;
 pub use stf_sgx_primitives::{types::*, Stf};
 pub use trusted_call::*;
 
+pub mod account_export;
+pub mod assets;
+pub mod confidential_events;
+pub mod cross_shard;
 #[cfg(feature = "evm")]
 pub mod evm_helpers;
+pub mod fees;
 pub mod getter;
 pub mod hash;
 pub mod helpers;
+pub mod migrations;
+pub mod rent;
+pub mod reserves;
+pub mod scheduler;
+pub mod session_keys;
+pub mod shard_acl;
 pub mod stf_sgx;
 pub mod stf_sgx_primitives;
 #[cfg(all(feature = "test", feature = "sgx"))]
@@ -42,5 +53,7 @@ pub mod stf_sgx_tests;
 #[cfg(all(feature = "test", feature = "sgx"))]
 pub mod test_genesis;
 pub mod trusted_call;
+pub mod unshielding;
+pub mod weights;
 
 pub(crate) const ENCLAVE_ACCOUNT_KEY: &str = "Enclave_Account_Key";
@@ -0,0 +1,63 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Registry of the shard state migrations between STF versions.
+//!
+//! Whenever a change of the STF alters the meaning of existing state, [STF_VERSION] has to be
+//! increased and a migration from the previous version added to [state_migrations]. The
+//! executor runs the migrations when a validateer with the new STF produces its first block,
+//! and validateers with the new STF refuse to import blocks produced by an older STF.
+
+use codec::Decode;
+use itp_sgx_externalities::SgxExternalitiesTrait;
+use itp_stf_interface::{StateMigration, StfVersion, STF_VERSION_KEY};
+use std::{vec, vec::Vec};
+
+/// Version of the STF in this crate.
+pub const STF_VERSION: StfVersion = 1;
+
+/// Version of the STF that last wrote `state`, 0 if it was written before the STF was versioned.
+pub fn state_stf_version<State: SgxExternalitiesTrait>(state: &State) -> StfVersion {
+	state
+		.get(STF_VERSION_KEY.as_bytes())
+		.and_then(|v| Decode::decode(&mut v.as_slice()).ok())
+		.unwrap_or_default()
+}
+
+/// All state migrations, ordered by the version they migrate from.
+pub fn state_migrations<State>() -> Vec<StateMigration<State>> {
+	vec![StateMigration { from_version: 0, migrate: introduce_stf_version::<State> }]
+}
+
+/// States written before the STF was versioned are compatible with version 1,
+/// they only get the version set.
+fn introduce_stf_version<State>(_state: &mut State) {}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn there_is_a_migration_for_every_previous_version() {
+		let migrations = state_migrations::<()>();
+
+		for version in 0..STF_VERSION {
+			assert_eq!(1, migrations.iter().filter(|m| m.from_version == version).count());
+		}
+		assert!(migrations.iter().all(|m| m.from_version < STF_VERSION));
+	}
+}
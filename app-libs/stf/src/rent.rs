@@ -0,0 +1,342 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! State rent of the shard.
+//!
+//! Every account has to hold a storage deposit proportional to the state it occupies and pays
+//! rent for it. The rent is charged by a reaping pass, which is started every
+//! [REAPING_PERIOD] sidechain blocks by the enclave with [TrustedCall::reap_accounts].
+//! A pass processes at most [MAX_ACCOUNTS_PER_REAPING_BATCH] accounts per call and keeps a
+//! cursor in the state, the following calls continue the pass until all accounts have been
+//! visited. Accounts that can't afford the rent anymore, while keeping the storage deposit,
+//! are reaped.
+//!
+//! Reaping an account burns its balance and removes its state, but keeps its nonce. Otherwise
+//! the calls it signed before could be replayed once the account is funded again. Reaped
+//! accounts, which only hold a nonce, are skipped by the following passes.
+
+use crate::{
+	confidential_events,
+	helpers::{account_key_hash, current_block_number, get_storage_value},
+	shard_acl::is_exempt,
+	Balance,
+};
+use codec::{Decode, Encode};
+use frame_support::traits::UnfilteredDispatchable;
+use ita_sgx_runtime::{Runtime, System};
+use itp_stf_primitives::{
+	account_export::{AccountDataEntry, AccountStorage},
+	error::{StfError, StfResult},
+	types::AccountId,
+};
+use itp_storage::{storage_map_key, storage_value_key, StorageHasher};
+use itp_types::SidechainBlockNumber;
+use itp_utils::stringify::account_id_to_string;
+use log::*;
+use sp_runtime::MultiAddress;
+use std::{format, prelude::v1::*};
+
+pub const RENT_STORAGE_PREFIX: &str = "Rent";
+pub const RENT_INFO_KEY: &str = "Accounts";
+pub const LAST_REAPING_KEY: &str = "LastReaping";
+pub const REAPING_CURSOR_KEY: &str = "ReapingCursor";
+
+/// Number of sidechain blocks between two reaping passes.
+pub const REAPING_PERIOD: SidechainBlockNumber = 600;
+
+/// Number of sidechain blocks between two batches of a reaping pass.
+pub const REAPING_BATCH_PERIOD: SidechainBlockNumber = 10;
+
+/// Maximum number of accounts a single [TrustedCall::reap_accounts] processes.
+pub const MAX_ACCOUNTS_PER_REAPING_BATCH: usize = 256;
+
+/// Deposit an account has to hold per byte of state it occupies.
+pub const DEPOSIT_PER_BYTE: Balance = 10;
+
+/// Rent that is charged per byte of state and [REAPING_PERIOD].
+pub const RENT_PER_BYTE_PER_PERIOD: Balance = 1;
+
+/// Rent bookkeeping of an account.
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RentInfo {
+	/// Deposit the account has to hold for the state it occupies.
+	pub storage_deposit: Balance,
+	/// Sidechain block up to which the rent has been paid.
+	pub rent_paid_until: SidechainBlockNumber,
+}
+
+/// Rent status of an account, as returned by [crate::TrustedGetter::rent_status].
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RentStatus {
+	pub storage_bytes: u32,
+	pub storage_deposit: Balance,
+	pub rent_paid_until: SidechainBlockNumber,
+	pub rent_due: Balance,
+	pub free_balance: Balance,
+	/// True if the account will be reaped in the next reaping pass.
+	pub reapable: bool,
+}
+
+fn rent_info_key(who: &AccountId) -> Vec<u8> {
+	storage_map_key(RENT_STORAGE_PREFIX, RENT_INFO_KEY, who, &StorageHasher::Blake2_128Concat)
+}
+
+fn rent_info(who: &AccountId) -> Option<RentInfo> {
+	sp_io::storage::get(&rent_info_key(who)).and_then(|v| Decode::decode(&mut v.as_slice()).ok())
+}
+
+/// Number of bytes of state occupied by an account.
+pub fn storage_bytes(who: &AccountId) -> u32 {
+	let account = account_key_hash(who).len() + System::account(who).encoded_size();
+	let rent = rent_info_key(who).len() + RentInfo::default().encoded_size();
+	(account + rent + confidential_events::storage_bytes(who)) as u32
+}
+
+fn rent_due(storage_bytes: u32, info: &RentInfo, now: SidechainBlockNumber) -> Balance {
+	let elapsed_blocks = now.saturating_sub(info.rent_paid_until) as Balance;
+	(storage_bytes as Balance)
+		.saturating_mul(RENT_PER_BYTE_PER_PERIOD)
+		.saturating_mul(elapsed_blocks)
+		/ REAPING_PERIOD as Balance
+}
+
+/// Computes the storage deposit of an account, after a call has been executed.
+///
+/// Accounts that are seen for the first time start paying rent from the current block on.
+pub fn update_storage_deposit(who: &AccountId) {
+	let storage_deposit = (storage_bytes(who) as Balance).saturating_mul(DEPOSIT_PER_BYTE);
+	let info = match rent_info(who) {
+		Some(info) => RentInfo { storage_deposit, ..info },
+		None => RentInfo { storage_deposit, rent_paid_until: current_block_number() },
+	};
+	sp_io::storage::set(&rent_info_key(who), &info.encode());
+}
+
+pub fn rent_status(who: &AccountId) -> RentStatus {
+	let storage_bytes = storage_bytes(who);
+	let info = rent_info(who).unwrap_or_else(|| RentInfo {
+		storage_deposit: (storage_bytes as Balance).saturating_mul(DEPOSIT_PER_BYTE),
+		rent_paid_until: current_block_number(),
+	});
+	let rent_due = rent_due(storage_bytes, &info, current_block_number());
+	let free_balance = System::account(who).data.free;
+	RentStatus {
+		storage_bytes,
+		storage_deposit: info.storage_deposit,
+		rent_paid_until: info.rent_paid_until,
+		rent_due,
+		free_balance,
+		reapable: free_balance < rent_due.saturating_add(info.storage_deposit),
+	}
+}
+
+fn set_free_balance(who: AccountId, new_free: Balance) -> StfResult<()> {
+	ita_sgx_runtime::BalancesCall::<Runtime>::force_set_balance {
+		who: MultiAddress::Id(who),
+		new_free,
+	}
+	.dispatch_bypass_filter(ita_sgx_runtime::RuntimeOrigin::root())
+	.map_err(|e| StfError::Dispatch(format!("Charge rent error: {:?}", e.error)))?;
+	Ok(())
+}
+
+/// Charges the rent of the next batch of accounts and reaps the ones that can't afford it.
+///
+/// Continues the reaping pass in progress, if there is one. Otherwise, a new pass is started
+/// unless the last one is less than [REAPING_PERIOD] blocks ago, so that maintenance calls
+/// submitted by several validateers don't charge rent twice.
+pub fn reap_accounts() -> StfResult<()> {
+	let now = current_block_number();
+	let cursor_key = storage_value_key(RENT_STORAGE_PREFIX, REAPING_CURSOR_KEY);
+	let cursor: Option<Vec<u8>> = get_storage_value(RENT_STORAGE_PREFIX, REAPING_CURSOR_KEY);
+	let mut account_keys = match cursor {
+		Some(cursor) => frame_system::Account::<Runtime>::iter_keys_from(cursor),
+		None => {
+			let last_reaping: Option<SidechainBlockNumber> =
+				get_storage_value(RENT_STORAGE_PREFIX, LAST_REAPING_KEY);
+			if last_reaping.map_or(false, |last| now < last.saturating_add(REAPING_PERIOD)) {
+				debug!("Skipping reaping pass, last one was at block {:?}", last_reaping);
+				return Ok(())
+			}
+			sp_io::storage::set(
+				&storage_value_key(RENT_STORAGE_PREFIX, LAST_REAPING_KEY),
+				&now.encode(),
+			);
+			frame_system::Account::<Runtime>::iter_keys()
+		},
+	};
+
+	// Collect the batch first, reaping an account modifies the map we iterate.
+	let accounts: Vec<AccountId> =
+		account_keys.by_ref().take(MAX_ACCOUNTS_PER_REAPING_BATCH).collect();
+	match (account_keys.next(), accounts.last()) {
+		(Some(_), Some(last)) => sp_io::storage::set(
+			&cursor_key,
+			&frame_system::Account::<Runtime>::hashed_key_for(last).encode(),
+		),
+		_ => sp_io::storage::clear(&cursor_key),
+	}
+
+	let mut reaped = 0usize;
+	for who in accounts.into_iter().filter(|who| !is_exempt(who) && !is_reaped(who)) {
+		let status = rent_status(&who);
+		if status.reapable {
+			debug!("Reaping account {}", account_id_to_string(&who));
+			sp_io::storage::clear(&rent_info_key(&who));
+			confidential_events::clear_events(&who);
+			let nonce = System::account_nonce(&who);
+			set_free_balance(who.clone(), 0)?;
+			frame_system::Account::<Runtime>::mutate(&who, |info| info.nonce = nonce);
+			reaped += 1;
+		} else {
+			let info = RentInfo { storage_deposit: status.storage_deposit, rent_paid_until: now };
+			sp_io::storage::set(&rent_info_key(&who), &info.encode());
+			if status.rent_due > 0 {
+				set_free_balance(who, status.free_balance - status.rent_due)?;
+			}
+		}
+	}
+	info!("Reaping batch at block {} reaped {} account(s)", now, reaped);
+	Ok(())
+}
+
+/// True if the account has been reaped and only its nonce is left.
+fn is_reaped(who: &AccountId) -> bool {
+	rent_info(who).is_none() && System::account(who).data == Default::default()
+}
+
+/// True if a reaping pass has been started, but not all accounts have been visited yet.
+pub fn is_reaping_in_progress() -> bool {
+	sp_io::storage::exists(&storage_value_key(RENT_STORAGE_PREFIX, REAPING_CURSOR_KEY))
+}
+
+pub struct RentStorage;
+
+impl AccountStorage for RentStorage {
+	const PALLET: &'static str = RENT_STORAGE_PREFIX;
+
+	fn account_entries(who: &AccountId) -> Vec<AccountDataEntry> {
+		rent_info(who)
+			.map(|info| AccountDataEntry::new(RENT_INFO_KEY, &info))
+			.into_iter()
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itp_sgx_externalities::{SgxExternalities, SgxExternalitiesTrait};
+	use sp_keyring::AccountKeyring;
+
+	#[test]
+	fn rent_due_is_proportional_to_elapsed_blocks() {
+		let info = RentInfo { storage_deposit: 0, rent_paid_until: 100 };
+
+		assert_eq!(rent_due(300, &info, 100), 0);
+		assert_eq!(rent_due(300, &info, 100 + REAPING_PERIOD), 300 * RENT_PER_BYTE_PER_PERIOD);
+		assert_eq!(rent_due(300, &info, 100 + REAPING_PERIOD / 2), 150 * RENT_PER_BYTE_PER_PERIOD);
+		assert_eq!(rent_due(300, &info, 50), 0);
+	}
+
+	#[test]
+	fn new_accounts_start_paying_rent_at_the_current_block() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+
+		state.execute_with(|| {
+			sp_io::storage::set(&storage_value_key("System", "Number"), &42u64.encode());
+			update_storage_deposit(&alice);
+
+			let status = rent_status(&alice);
+			assert_eq!(status.rent_paid_until, 42);
+			assert_eq!(status.rent_due, 0);
+			assert_eq!(status.storage_deposit, status.storage_bytes as Balance * DEPOSIT_PER_BYTE);
+			assert!(status.reapable);
+		});
+	}
+
+	#[test]
+	fn reaping_pass_is_split_into_bounded_batches() {
+		let mut state = SgxExternalities::default();
+		let accounts: Vec<AccountId> = (0..MAX_ACCOUNTS_PER_REAPING_BATCH + 10)
+			.map(|i| {
+				let mut raw = [0u8; 32];
+				raw[..4].copy_from_slice(&(i as u32).to_le_bytes());
+				AccountId::from(raw)
+			})
+			.collect();
+
+		state.execute_with(|| {
+			sp_io::storage::set(&storage_value_key("System", "Number"), &1u64.encode());
+			for who in accounts.iter() {
+				set_free_balance(who.clone(), 1_000_000).unwrap();
+				update_storage_deposit(who);
+			}
+			let visited = |now: SidechainBlockNumber| {
+				accounts
+					.iter()
+					.filter(|who| rent_info(who).unwrap().rent_paid_until == now)
+					.count()
+			};
+
+			sp_io::storage::set(&storage_value_key("System", "Number"), &2u64.encode());
+			reap_accounts().unwrap();
+			assert!(is_reaping_in_progress());
+			assert_eq!(visited(2), MAX_ACCOUNTS_PER_REAPING_BATCH);
+
+			// The pass is continued although the reaping period has not elapsed.
+			sp_io::storage::set(&storage_value_key("System", "Number"), &3u64.encode());
+			reap_accounts().unwrap();
+			assert!(!is_reaping_in_progress());
+			assert_eq!(visited(3), 10);
+
+			// A new pass only starts after the reaping period.
+			sp_io::storage::set(&storage_value_key("System", "Number"), &4u64.encode());
+			reap_accounts().unwrap();
+			assert!(!is_reaping_in_progress());
+			assert_eq!(visited(4), 0);
+		});
+	}
+
+	#[test]
+	fn reaped_accounts_keep_their_nonce() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+
+		state.execute_with(|| {
+			sp_io::storage::set(&storage_value_key("System", "Number"), &1u64.encode());
+			set_free_balance(alice.clone(), 1_000).unwrap();
+			frame_system::Account::<Runtime>::mutate(&alice, |info| info.nonce = 3);
+			update_storage_deposit(&alice);
+			assert!(rent_status(&alice).reapable);
+
+			sp_io::storage::set(&storage_value_key("System", "Number"), &2u64.encode());
+			reap_accounts().unwrap();
+
+			assert_eq!(System::account(&alice).data.free, 0);
+			assert!(rent_info(&alice).is_none());
+			assert!(is_reaped(&alice));
+			assert_eq!(System::account_nonce(&alice), 3);
+
+			// Calls signed with the nonces 0 to 2 can't be replayed once the account is funded.
+			set_free_balance(alice.clone(), 1_000_000).unwrap();
+			assert_eq!(System::account_nonce(&alice), 3);
+			assert!(!is_reaped(&alice));
+		});
+	}
+}
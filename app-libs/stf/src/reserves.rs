@@ -0,0 +1,287 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Proof of reserves of the shard vault, see [itp_stf_primitives::reserves].
+//!
+//! Every [ATTESTATION_PERIOD] sidechain blocks, the enclave-signed `reserves_attest` call
+//! starts a report on the funds owed to the owners of the shard, the same ones that would be
+//! paid out when the shard is retired. A call visits at most
+//! [MAX_ACCOUNTS_PER_ATTESTATION_BATCH] accounts and keeps a cursor in the state, the following
+//! calls continue the report until all accounts have been visited. The last call adds the
+//! queued payouts and publishes the report on the parentchain.
+//!
+//! The balances are read batch by batch, so a transfer between a visited and a not yet visited
+//! account during the pass is counted twice or not at all.
+
+use crate::{
+	helpers::{
+		current_block_number, enclave_signer_account, get_storage_by_key_hash, get_storage_value,
+	},
+	unshielding::pending_payouts,
+	Balance,
+};
+use codec::{Decode, Encode};
+use ita_sgx_runtime::Runtime;
+use itp_stf_interface::SHARD_VAULT_KEY;
+use itp_stf_primitives::{
+	error::{StfError, StfResult},
+	reserves::{
+		Liability, ReportIndex, ReservesReport, SumTreeBuilder, CURSOR_KEY, LATEST_REPORT_KEY,
+		PENDING_REPORT_KEY, RESERVES_STORAGE_PREFIX,
+	},
+	types::{AccountId, ShardIdentifier},
+};
+use itp_storage::storage_value_key;
+use itp_types::{BlockNumber, SidechainBlockNumber};
+use log::*;
+use sp_core::H256;
+use sp_io::hashing::blake2_256;
+use std::prelude::v1::*;
+
+/// Number of sidechain blocks between the starts of two reserves reports.
+pub const ATTESTATION_PERIOD: SidechainBlockNumber = 600;
+
+/// Number of sidechain blocks between two batches of a reserves report.
+pub const ATTESTATION_BATCH_PERIOD: SidechainBlockNumber = 10;
+
+/// Maximum number of accounts a single `reserves_attest` call visits.
+pub const MAX_ACCOUNTS_PER_ATTESTATION_BATCH: usize = 256;
+
+/// Report that is being built, while not all accounts have been visited.
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+struct PendingReport {
+	index: ReportIndex,
+	started_at: SidechainBlockNumber,
+	tree: SumTreeBuilder,
+}
+
+fn latest_report_key() -> Vec<u8> {
+	storage_value_key(RESERVES_STORAGE_PREFIX, LATEST_REPORT_KEY)
+}
+
+fn pending_report_key() -> Vec<u8> {
+	storage_value_key(RESERVES_STORAGE_PREFIX, PENDING_REPORT_KEY)
+}
+
+fn cursor_key() -> Vec<u8> {
+	storage_value_key(RESERVES_STORAGE_PREFIX, CURSOR_KEY)
+}
+
+fn get_decoded<V: Decode>(key: &[u8]) -> Option<V> {
+	sp_io::storage::get(key).and_then(|v| Decode::decode(&mut v.as_slice()).ok())
+}
+
+pub fn latest_report() -> Option<ReservesReport> {
+	get_decoded(&latest_report_key())
+}
+
+/// Adds a leaf with a salt drawn from the enclave randomness `seed`, so the published root
+/// can't be matched against guessed balances.
+fn add_liability(
+	tree: &mut SumTreeBuilder,
+	seed: &[u8; 32],
+	account: AccountId,
+	balance: Balance,
+) -> StfResult<()> {
+	let salt: H256 = blake2_256(&(seed, tree.leaf_count()).encode()).into();
+	tree.push(Liability { account, balance, salt }.leaf())
+		.ok_or_else(|| StfError::Dispatch("total liabilities overflow".into()))
+}
+
+/// Continues the reserves report in progress, or starts a new one, and stores the report once
+/// all accounts have been visited.
+///
+/// Returns the report if it has been completed by this call. A new report isn't started if the
+/// shard has no vault, or if the latest report has been started less than [ATTESTATION_PERIOD]
+/// blocks ago, so that the calls submitted by several validateers result in a single report.
+pub fn attest(shard: ShardIdentifier) -> StfResult<Option<ReservesReport>> {
+	let vault: AccountId = match get_storage_by_key_hash(SHARD_VAULT_KEY.into()) {
+		Some(vault) => vault,
+		None => return Ok(None),
+	};
+	let now = current_block_number();
+
+	let cursor: Option<Vec<u8>> = get_storage_value(RESERVES_STORAGE_PREFIX, CURSOR_KEY);
+	let (mut pending, mut accounts) = match cursor {
+		Some(cursor) => (
+			get_decoded::<PendingReport>(&pending_report_key()).unwrap_or_default(),
+			frame_system::Account::<Runtime>::iter_from(cursor),
+		),
+		None => {
+			let previous = latest_report();
+			if let Some(report) = &previous {
+				if now < report.sidechain_block_number.saturating_add(ATTESTATION_PERIOD) {
+					debug!(
+						"Skipping reserves report, the latest one is from block {}",
+						report.sidechain_block_number
+					);
+					return Ok(None)
+				}
+			}
+			let index = previous.map_or(0, |report| report.index + 1);
+			(
+				PendingReport { index, started_at: now, tree: Default::default() },
+				frame_system::Account::<Runtime>::iter(),
+			)
+		},
+	};
+
+	// A fresh seed per batch, the salts never leave the enclave.
+	let seed = sp_io::offchain::random_seed();
+	let enclave_account: AccountId = enclave_signer_account();
+	let batch: Vec<_> = accounts.by_ref().take(MAX_ACCOUNTS_PER_ATTESTATION_BATCH).collect();
+	for (who, info) in batch.iter() {
+		if who != &enclave_account && info.data.free > 0 {
+			add_liability(&mut pending.tree, &seed, who.clone(), info.data.free)?;
+		}
+	}
+
+	if let (Some(_), Some((last, _))) = (accounts.next(), batch.last()) {
+		sp_io::storage::set(
+			&cursor_key(),
+			&frame_system::Account::<Runtime>::hashed_key_for(last).encode(),
+		);
+		sp_io::storage::set(&pending_report_key(), &pending.encode());
+		debug!("Reserves report {} visited {} accounts", pending.index, batch.len());
+		return Ok(None)
+	}
+
+	for payout in pending_payouts() {
+		add_liability(&mut pending.tree, &seed, payout.beneficiary, payout.amount)?;
+	}
+	let root = pending
+		.tree
+		.root()
+		.ok_or_else(|| StfError::Dispatch("total liabilities overflow".into()))?;
+	let report = ReservesReport {
+		shard,
+		index: pending.index,
+		sidechain_block_number: pending.started_at,
+		parentchain_block_number: get_storage_value::<BlockNumber>("Parentchain", "Number")
+			.unwrap_or_default(),
+		parentchain_block_hash: get_storage_value::<H256>("Parentchain", "BlockHash")
+			.unwrap_or_default(),
+		vault,
+		liabilities: root,
+		account_count: pending.tree.leaf_count(),
+	};
+	debug!("Reserves report {:?}", report);
+
+	sp_io::storage::clear(&cursor_key());
+	sp_io::storage::clear(&pending_report_key());
+	sp_io::storage::set(&latest_report_key(), &report.encode());
+	Ok(Some(report))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itp_sgx_externalities::{SgxExternalities, SgxExternalitiesTrait};
+	use sp_keyring::AccountKeyring;
+
+	#[test]
+	fn report_commits_to_the_balances_of_all_owners() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+		let bob: AccountId = AccountKeyring::Bob.public().into();
+		let enclave: AccountId = AccountKeyring::Eve.public().into();
+		let vault: AccountId = AccountKeyring::Ferdie.public().into();
+		let shard = ShardIdentifier::repeat_byte(1);
+
+		state.execute_with(|| {
+			sp_io::storage::set(
+				&storage_value_key("Sudo", crate::ENCLAVE_ACCOUNT_KEY),
+				&enclave.encode(),
+			);
+			assert_eq!(attest(shard), Ok(None));
+
+			sp_io::storage::set(SHARD_VAULT_KEY.as_bytes(), &vault.encode());
+			for (who, free) in [(&alice, 10), (&bob, 25), (&enclave, 100)] {
+				frame_system::Account::<Runtime>::mutate(who, |info| info.data.free = free);
+			}
+			let report = attest(shard).unwrap().unwrap();
+
+			assert_eq!(report.total_liabilities(), 35);
+			assert_eq!(report.account_count, 2);
+		});
+	}
+
+	#[test]
+	fn report_is_built_in_bounded_batches() {
+		let mut state = SgxExternalities::default();
+		let enclave: AccountId = AccountKeyring::Eve.public().into();
+		let shard = ShardIdentifier::repeat_byte(1);
+		let account_count = MAX_ACCOUNTS_PER_ATTESTATION_BATCH + 10;
+
+		state.execute_with(|| {
+			sp_io::storage::set(
+				&storage_value_key("Sudo", crate::ENCLAVE_ACCOUNT_KEY),
+				&enclave.encode(),
+			);
+			sp_io::storage::set(SHARD_VAULT_KEY.as_bytes(), &enclave.encode());
+			sp_io::storage::set(&storage_value_key("System", "Number"), &10u64.encode());
+			for i in 0..account_count {
+				let mut raw = [0u8; 32];
+				raw[..4].copy_from_slice(&(i as u32).to_le_bytes());
+				frame_system::Account::<Runtime>::mutate(AccountId::from(raw), |info| {
+					info.data.free = 2
+				});
+			}
+
+			assert_eq!(attest(shard), Ok(None));
+			assert!(sp_io::storage::exists(&cursor_key()));
+
+			// The report is continued although the attestation period has not elapsed.
+			sp_io::storage::set(&storage_value_key("System", "Number"), &20u64.encode());
+			let report = attest(shard).unwrap().unwrap();
+
+			assert_eq!(report.account_count, account_count as u32);
+			assert_eq!(report.total_liabilities(), 2 * account_count as Balance);
+			assert_eq!(report.sidechain_block_number, 10);
+			assert!(!sp_io::storage::exists(&cursor_key()));
+			assert!(!sp_io::storage::exists(&pending_report_key()));
+		});
+	}
+
+	#[test]
+	fn reports_are_produced_once_per_period() {
+		let mut state = SgxExternalities::default();
+		let enclave: AccountId = AccountKeyring::Eve.public().into();
+		let shard = ShardIdentifier::repeat_byte(1);
+
+		state.execute_with(|| {
+			sp_io::storage::set(
+				&storage_value_key("Sudo", crate::ENCLAVE_ACCOUNT_KEY),
+				&enclave.encode(),
+			);
+			sp_io::storage::set(SHARD_VAULT_KEY.as_bytes(), &enclave.encode());
+			sp_io::storage::set(&storage_value_key("System", "Number"), &10u64.encode());
+			assert_eq!(attest(shard).unwrap().unwrap().index, 0);
+
+			sp_io::storage::set(
+				&storage_value_key("System", "Number"),
+				&(10 + ATTESTATION_PERIOD - 1).encode(),
+			);
+			assert_eq!(attest(shard), Ok(None));
+
+			sp_io::storage::set(
+				&storage_value_key("System", "Number"),
+				&(10 + ATTESTATION_PERIOD).encode(),
+			);
+			assert_eq!(attest(shard).unwrap().unwrap().index, 1);
+		});
+	}
+}
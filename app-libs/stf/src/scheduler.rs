@@ -0,0 +1,230 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Scheduler of the shard STF.
+//!
+//! Trusted calls can be scheduled for execution at a future sidechain block number or timestamp.
+//! The scheduled calls are kept in the state and executed with [execute_due_calls] once they are
+//! due, at the end of each sidechain block. They are dispatched on behalf of the account that
+//! scheduled them, which must still be permitted on the shard and pays the fee at execution.
+
+use crate::{
+	helpers::{current_block_number, get_storage_value, trusted_time},
+	rent, TrustedCall,
+};
+use codec::{Decode, Encode};
+use itp_node_api::metadata::{provider::AccessNodeMetadata, NodeMetadataTrait};
+use itp_stf_primitives::{
+	account_export::{AccountDataEntry, AccountStorage},
+	error::{StfError, StfResult},
+	types::AccountId,
+};
+use itp_storage::storage_value_key;
+use itp_types::{OpaqueCall, ShardIdentifier, SidechainBlockNumber, SidechainTimestamp};
+use itp_utils::stringify::account_id_to_string;
+use log::*;
+use std::{prelude::v1::*, sync::Arc};
+
+pub const SCHEDULER_STORAGE_PREFIX: &str = "Scheduler";
+pub const SCHEDULED_CALLS_KEY: &str = "ScheduledCalls";
+
+/// Maximum number of calls that can be pending in the scheduler of a shard.
+pub const MAX_SCHEDULED_CALLS: usize = 1024;
+
+/// Maximum number of due calls that are executed within one sidechain block.
+pub const MAX_SCHEDULED_CALLS_PER_BLOCK: usize = 32;
+
+/// Point in time at which a scheduled call is due.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub enum ScheduledAt {
+	/// Due when the sidechain block with the given number is produced.
+	SidechainBlock(SidechainBlockNumber),
+	/// Due with the first sidechain block whose timestamp (unix millis) is at or after the given one.
+	Timestamp(SidechainTimestamp),
+}
+
+impl ScheduledAt {
+	fn is_due(&self, block_number: SidechainBlockNumber, now: SidechainTimestamp) -> bool {
+		match self {
+			ScheduledAt::SidechainBlock(number) => *number <= block_number,
+			ScheduledAt::Timestamp(timestamp) => *timestamp <= now,
+		}
+	}
+
+	fn is_in_future(&self, block_number: SidechainBlockNumber, now: SidechainTimestamp) -> bool {
+		!self.is_due(block_number, now)
+	}
+}
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct ScheduledCall {
+	pub when: ScheduledAt,
+	pub call: TrustedCall,
+}
+
+fn scheduled_calls_key() -> Vec<u8> {
+	storage_value_key(SCHEDULER_STORAGE_PREFIX, SCHEDULED_CALLS_KEY)
+}
+
+pub fn scheduled_calls() -> Vec<ScheduledCall> {
+	get_storage_value(SCHEDULER_STORAGE_PREFIX, SCHEDULED_CALLS_KEY).unwrap_or_default()
+}
+
+fn set_scheduled_calls(calls: Vec<ScheduledCall>) {
+	if calls.is_empty() {
+		sp_io::storage::clear(&scheduled_calls_key());
+	} else {
+		sp_io::storage::set(&scheduled_calls_key(), &calls.encode());
+	}
+}
+
+/// Stores a call to be executed at `when`.
+pub fn schedule(when: ScheduledAt, call: TrustedCall) -> StfResult<()> {
+	if !when.is_in_future(current_block_number(), trusted_time()) {
+		return Err(StfError::Dispatch("scheduled call must be due in the future".into()))
+	}
+
+	let mut calls = scheduled_calls();
+	if calls.len() >= MAX_SCHEDULED_CALLS {
+		return Err(StfError::Dispatch("too many scheduled calls".into()))
+	}
+	calls.push(ScheduledCall { when, call });
+	set_scheduled_calls(calls);
+	Ok(())
+}
+
+/// Returns true if at least one scheduled call is due.
+pub fn has_due_calls() -> bool {
+	let (block_number, now) = (current_block_number(), trusted_time());
+	scheduled_calls().iter().any(|c| c.when.is_due(block_number, now))
+}
+
+/// Removes the due calls from the scheduler and returns them in the order they were scheduled.
+///
+/// At most [MAX_SCHEDULED_CALLS_PER_BLOCK] calls are returned, the rest stays in the scheduler
+/// for the next block.
+pub fn take_due_calls() -> Vec<TrustedCall> {
+	let (block_number, now) = (current_block_number(), trusted_time());
+
+	let mut due = Vec::new();
+	let mut pending = Vec::new();
+	for scheduled in scheduled_calls() {
+		if due.len() < MAX_SCHEDULED_CALLS_PER_BLOCK && scheduled.when.is_due(block_number, now) {
+			due.push(scheduled.call);
+		} else {
+			pending.push(scheduled);
+		}
+	}
+	set_scheduled_calls(pending);
+	due
+}
+
+/// Executes the due calls, see [take_due_calls], and returns how many were executed.
+///
+/// Each call goes through the same access check and fee charging as a call from the pool.
+/// A failing scheduled call is dropped, it must not prevent the execution of the others.
+pub fn execute_due_calls<NodeMetadataRepository>(
+	mrenclave: &[u8; 32],
+	shard: &ShardIdentifier,
+	calls: &mut Vec<OpaqueCall>,
+	node_metadata_repo: Arc<NodeMetadataRepository>,
+) -> usize
+where
+	NodeMetadataRepository: AccessNodeMetadata,
+	NodeMetadataRepository::MetadataType: NodeMetadataTrait,
+{
+	let due = take_due_calls();
+	let executed = due.len();
+	for call in due {
+		let sender = call.sender_account().clone();
+		debug!("executing scheduled call of {}", account_id_to_string(&sender));
+		if let Err(e) = call.dispatch_checked(mrenclave, shard, calls, node_metadata_repo.clone()) {
+			warn!("Scheduled call failed: {:?}", e);
+		}
+		rent::update_storage_deposit(&sender);
+	}
+	executed
+}
+
+/// Exports the pending calls the account has scheduled.
+pub struct SchedulerStorage;
+
+impl AccountStorage for SchedulerStorage {
+	const PALLET: &'static str = SCHEDULER_STORAGE_PREFIX;
+
+	fn account_entries(who: &AccountId) -> Vec<AccountDataEntry> {
+		let calls: Vec<ScheduledCall> = scheduled_calls()
+			.into_iter()
+			.filter(|scheduled| scheduled.call.sender_account() == who)
+			.collect();
+		if calls.is_empty() {
+			return Vec::new()
+		}
+		vec![AccountDataEntry::new(SCHEDULED_CALLS_KEY, &calls)]
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itp_sgx_externalities::{SgxExternalities, SgxExternalitiesTrait};
+	use sp_keyring::AccountKeyring;
+
+	fn state_at(block_number: SidechainBlockNumber, now: SidechainTimestamp) -> SgxExternalities {
+		let mut state = SgxExternalities::default();
+		state.execute_with(|| {
+			sp_io::storage::set(&storage_value_key("System", "Number"), &block_number.encode());
+			sp_io::storage::set(&storage_value_key("System", "Timestamp"), &now.encode());
+		});
+		state
+	}
+
+	fn noop_call() -> TrustedCall {
+		TrustedCall::noop(AccountKeyring::Alice.public().into())
+	}
+
+	#[test]
+	fn scheduling_in_the_past_fails() {
+		let mut state = state_at(10, 1_000);
+		state.execute_with(|| {
+			assert!(schedule(ScheduledAt::SidechainBlock(10), noop_call()).is_err());
+			assert!(schedule(ScheduledAt::Timestamp(999), noop_call()).is_err());
+			assert!(scheduled_calls().is_empty());
+		});
+	}
+
+	#[test]
+	fn only_due_calls_are_taken() {
+		let mut state = state_at(10, 1_000);
+		state.execute_with(|| {
+			schedule(ScheduledAt::SidechainBlock(11), noop_call()).unwrap();
+			schedule(ScheduledAt::Timestamp(2_000), noop_call()).unwrap();
+			assert!(!has_due_calls());
+
+			sp_io::storage::set(&storage_value_key("System", "Number"), &11u64.encode());
+			sp_io::storage::set(&storage_value_key("System", "Timestamp"), &1_500u64.encode());
+
+			assert!(has_due_calls());
+			assert_eq!(take_due_calls(), vec![noop_call()]);
+			assert!(!has_due_calls());
+			assert_eq!(
+				scheduled_calls(),
+				vec![ScheduledCall { when: ScheduledAt::Timestamp(2_000), call: noop_call() }]
+			);
+		});
+	}
+}
@@ -0,0 +1,184 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Session keys of accounts, see [itp_stf_primitives::session_keys].
+//!
+//! Session keys are registered and revoked by their owner with the `session_key_*` trusted
+//! calls. They are checked against the sidechain time, so they expire with the first block
+//! produced after `valid_until`. On permissioned shards, both the delegate and the owner have
+//! to be permitted, see [crate::shard_acl].
+
+use crate::helpers::{storage_map_keys, trusted_time};
+use codec::{Decode, Encode};
+use itp_stf_primitives::{
+	account_export::{AccountDataEntry, AccountStorage},
+	error::{StfError, StfResult},
+	session_keys::{SessionKey, DELEGATES_KEY, MAX_SCOPE_LEN, SESSION_KEYS_STORAGE_PREFIX},
+	types::AccountId,
+};
+use itp_storage::{storage_double_map_key, storage_map_key, StorageHasher};
+use itp_utils::stringify::account_id_to_string;
+use log::*;
+use std::{format, prelude::v1::*};
+
+fn session_key_key(owner: &AccountId, delegate: &AccountId) -> Vec<u8> {
+	storage_double_map_key(
+		SESSION_KEYS_STORAGE_PREFIX,
+		DELEGATES_KEY,
+		owner,
+		&StorageHasher::Blake2_128Concat,
+		delegate,
+		&StorageHasher::Blake2_128Concat,
+	)
+}
+
+pub fn session_key(owner: &AccountId, delegate: &AccountId) -> Option<SessionKey> {
+	sp_io::storage::get(&session_key_key(owner, delegate))
+		.and_then(|v| Decode::decode(&mut v.as_slice()).ok())
+}
+
+/// Registers a session key, replacing an existing one of the same delegate.
+pub fn register(owner: &AccountId, delegate: &AccountId, session_key: SessionKey) -> StfResult<()> {
+	debug!(
+		"Registering session key {} of {} until {}",
+		account_id_to_string(delegate),
+		account_id_to_string(owner),
+		session_key.valid_until
+	);
+	if owner == delegate {
+		return Err(StfError::Dispatch("an account can't be its own session key".into()))
+	}
+	if session_key.scope.is_empty() || session_key.scope.len() > MAX_SCOPE_LEN {
+		return Err(StfError::Dispatch(format!(
+			"session key scope must contain 1 to {} calls",
+			MAX_SCOPE_LEN
+		)))
+	}
+	if session_key.is_expired(trusted_time()) {
+		return Err(StfError::Dispatch("session key has already expired".into()))
+	}
+	sp_io::storage::set(&session_key_key(owner, delegate), &session_key.encode());
+	Ok(())
+}
+
+pub fn revoke(owner: &AccountId, delegate: &AccountId) {
+	debug!(
+		"Revoking session key {} of {}",
+		account_id_to_string(delegate),
+		account_id_to_string(owner)
+	);
+	sp_io::storage::clear(&session_key_key(owner, delegate));
+}
+
+/// Ensures the delegate may submit the call with the given name on behalf of the owner.
+pub fn ensure_permitted(owner: &AccountId, delegate: &AccountId, call_name: &str) -> StfResult<()> {
+	match session_key(owner, delegate) {
+		Some(session_key) if session_key.permits(call_name, trusted_time()) => Ok(()),
+		_ => Err(StfError::SessionKeyNotPermitted(delegate.clone(), owner.clone())),
+	}
+}
+
+/// Exports the session keys the account has registered for its delegates.
+pub struct SessionKeysStorage;
+
+impl AccountStorage for SessionKeysStorage {
+	const PALLET: &'static str = SESSION_KEYS_STORAGE_PREFIX;
+
+	fn account_entries(who: &AccountId) -> Vec<AccountDataEntry> {
+		let prefix = storage_map_key(
+			SESSION_KEYS_STORAGE_PREFIX,
+			DELEGATES_KEY,
+			who,
+			&StorageHasher::Blake2_128Concat,
+		);
+		let delegations: Vec<(AccountId, SessionKey)> = storage_map_keys::<AccountId>(&prefix)
+			.into_iter()
+			.filter_map(|delegate| session_key(who, &delegate).map(|key| (delegate, key)))
+			.collect();
+		if delegations.is_empty() {
+			return Vec::new()
+		}
+		vec![AccountDataEntry::new(DELEGATES_KEY, &delegations)]
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itp_sgx_externalities::{SgxExternalities, SgxExternalitiesTrait};
+	use itp_storage::storage_value_key;
+	use sp_keyring::AccountKeyring;
+
+	fn set_trusted_time(now: u64) {
+		sp_io::storage::set(&storage_value_key("System", "Timestamp"), &now.encode());
+	}
+
+	#[test]
+	fn registered_session_key_permits_scoped_calls_until_expiry() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+		let bob: AccountId = AccountKeyring::Bob.public().into();
+
+		state.execute_with(|| {
+			set_trusted_time(100);
+			register(&alice, &bob, SessionKey::new(200, vec![b"balance_transfer".to_vec()]))
+				.unwrap();
+
+			assert!(ensure_permitted(&alice, &bob, "balance_transfer").is_ok());
+			assert!(ensure_permitted(&alice, &bob, "balance_unshield").is_err());
+			assert!(ensure_permitted(&bob, &alice, "balance_transfer").is_err());
+
+			set_trusted_time(201);
+			assert_eq!(
+				ensure_permitted(&alice, &bob, "balance_transfer"),
+				Err(StfError::SessionKeyNotPermitted(bob.clone(), alice.clone()))
+			);
+		});
+	}
+
+	#[test]
+	fn revoked_session_key_is_not_permitted() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+		let bob: AccountId = AccountKeyring::Bob.public().into();
+
+		state.execute_with(|| {
+			register(&alice, &bob, SessionKey::new(200, vec![b"noop".to_vec()])).unwrap();
+			revoke(&alice, &bob);
+
+			assert!(session_key(&alice, &bob).is_none());
+			assert!(ensure_permitted(&alice, &bob, "noop").is_err());
+		});
+	}
+
+	#[test]
+	fn invalid_session_keys_are_rejected() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+		let bob: AccountId = AccountKeyring::Bob.public().into();
+
+		state.execute_with(|| {
+			set_trusted_time(100);
+			let scope = vec![b"noop".to_vec()];
+
+			assert!(register(&alice, &alice, SessionKey::new(200, scope.clone())).is_err());
+			assert!(register(&alice, &bob, SessionKey::new(200, vec![])).is_err());
+			assert!(register(&alice, &bob, SessionKey::new(99, scope)).is_err());
+			assert!(session_key(&alice, &bob).is_none());
+		});
+	}
+}
@@ -0,0 +1,301 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Access control of permissioned shards, see [itp_stf_primitives::shard_acl].
+//!
+//! The access policy and the members are managed by root with the `shard_acl_*` trusted calls.
+//! Root and the enclave signer are always permitted, so that a shard can't lock itself out.
+//! The same holds for the maintenance mode, which is only enforced on submission, by the top
+//! pool author with [SubmissionAccessControl].
+//!
+//! The rules are evaluated on a storage reader, so that the author can apply them to the state
+//! it queries, while the STF uses the state it executes on.
+
+use crate::ENCLAVE_ACCOUNT_KEY;
+use codec::{Decode, Encode};
+use itp_stf_primitives::{
+	account_export::{AccountDataEntry, AccountStorage},
+	error::{StfError, StfResult},
+	shard_acl::{
+		AccessPolicy, MemberRoles, ShardAccessControl, SubmissionDenied, ACCESS_POLICY_KEY,
+		MAINTENANCE_MODE_KEY, MEMBERS_KEY, SHARD_ACL_STORAGE_PREFIX,
+	},
+	types::AccountId,
+};
+use itp_storage::{storage_map_key, storage_value_key, StorageHasher};
+use itp_utils::stringify::account_id_to_string;
+use log::*;
+use std::prelude::v1::*;
+
+/// Reads the plain storage of a shard state.
+type StorageReader<'a> = &'a dyn Fn(&[u8]) -> Option<Vec<u8>>;
+
+/// Storage value of the root account, see `pallet_sudo::Key`.
+const ROOT_KEY: &str = "Key";
+
+fn member_key(who: &AccountId) -> Vec<u8> {
+	storage_map_key(SHARD_ACL_STORAGE_PREFIX, MEMBERS_KEY, who, &StorageHasher::Blake2_128Concat)
+}
+
+fn get_decoded<V: Decode>(storage: StorageReader, key: &[u8]) -> Option<V> {
+	storage(key).and_then(|v| Decode::decode(&mut v.as_slice()).ok())
+}
+
+fn current_state(key: &[u8]) -> Option<Vec<u8>> {
+	sp_io::storage::get(key)
+}
+
+fn access_policy_in(storage: StorageReader) -> AccessPolicy {
+	get_decoded(storage, &storage_value_key(SHARD_ACL_STORAGE_PREFIX, ACCESS_POLICY_KEY))
+		.unwrap_or_default()
+}
+
+fn member_roles_in(storage: StorageReader, who: &AccountId) -> Option<MemberRoles> {
+	get_decoded(storage, &member_key(who))
+}
+
+fn is_in_maintenance_in(storage: StorageReader) -> bool {
+	get_decoded(storage, &storage_value_key(SHARD_ACL_STORAGE_PREFIX, MAINTENANCE_MODE_KEY))
+		.unwrap_or(false)
+}
+
+/// Root and the enclave signer are exempt from the access control, the maintenance mode,
+/// the fees and the rent.
+fn is_exempt_in(storage: StorageReader, who: &AccountId) -> bool {
+	[ROOT_KEY, ENCLAVE_ACCOUNT_KEY].iter().any(|key| {
+		get_decoded::<AccountId>(storage, &storage_value_key("Sudo", key)).as_ref() == Some(who)
+	})
+}
+
+fn is_permitted_in(storage: StorageReader, who: &AccountId) -> bool {
+	is_exempt_in(storage, who)
+		|| access_policy_in(storage).permits(member_roles_in(storage, who).as_deref())
+}
+
+pub fn access_policy() -> AccessPolicy {
+	access_policy_in(&current_state)
+}
+
+pub fn member_roles(who: &AccountId) -> Option<MemberRoles> {
+	member_roles_in(&current_state, who)
+}
+
+pub fn is_in_maintenance() -> bool {
+	is_in_maintenance_in(&current_state)
+}
+
+/// Whether the account is root or the enclave signer of the shard.
+pub fn is_exempt(who: &AccountId) -> bool {
+	is_exempt_in(&current_state, who)
+}
+
+/// Ensures the account is permitted to submit trusted calls to the shard.
+pub fn ensure_permitted(who: &AccountId) -> StfResult<()> {
+	if is_permitted_in(&current_state, who) {
+		return Ok(())
+	}
+	Err(StfError::ShardAccessDenied(who.clone()))
+}
+
+/// Access control of the top pool author, with the same rules as [ensure_permitted] plus the
+/// maintenance mode.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SubmissionAccessControl;
+
+impl ShardAccessControl for SubmissionAccessControl {
+	fn ensure_may_submit(
+		&self,
+		storage: StorageReader,
+		who: &AccountId,
+	) -> Result<(), SubmissionDenied> {
+		if is_exempt_in(storage, who) {
+			return Ok(())
+		}
+		if is_in_maintenance_in(storage) {
+			return Err(SubmissionDenied::ShardInMaintenance)
+		}
+		if !is_permitted_in(storage, who) {
+			return Err(SubmissionDenied::AccessDenied)
+		}
+		Ok(())
+	}
+}
+
+pub fn set_access_policy(policy: AccessPolicy) {
+	debug!("Setting shard access policy to {:?}", policy);
+	sp_io::storage::set(
+		&storage_value_key(SHARD_ACL_STORAGE_PREFIX, ACCESS_POLICY_KEY),
+		&policy.encode(),
+	);
+}
+
+pub fn set_maintenance_mode(enabled: bool) {
+	info!("{} shard maintenance mode", if enabled { "Entering" } else { "Leaving" });
+	sp_io::storage::set(
+		&storage_value_key(SHARD_ACL_STORAGE_PREFIX, MAINTENANCE_MODE_KEY),
+		&enabled.encode(),
+	);
+}
+
+/// Adds or updates a member, or removes it if `roles` is `None`.
+pub fn set_member(who: &AccountId, roles: Option<MemberRoles>) {
+	debug!("Setting shard member {} roles to {:?}", account_id_to_string(who), roles);
+	match roles {
+		Some(roles) => sp_io::storage::set(&member_key(who), &roles.encode()),
+		None => sp_io::storage::clear(&member_key(who)),
+	}
+}
+
+pub struct ShardAclStorage;
+
+impl AccountStorage for ShardAclStorage {
+	const PALLET: &'static str = SHARD_ACL_STORAGE_PREFIX;
+
+	fn account_entries(who: &AccountId) -> Vec<AccountDataEntry> {
+		member_roles(who)
+			.map(|roles| AccountDataEntry::new(MEMBERS_KEY, &roles))
+			.into_iter()
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itp_sgx_externalities::{SgxExternalities, SgxExternalitiesTrait};
+	use sp_keyring::AccountKeyring;
+
+	#[test]
+	fn everyone_is_permitted_by_default() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+
+		state.execute_with(|| assert!(ensure_permitted(&alice).is_ok()));
+	}
+
+	#[test]
+	fn allow_list_denies_removed_members() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+
+		state.execute_with(|| {
+			set_access_policy(AccessPolicy::AllowList);
+			set_member(&alice, Some(vec![]));
+			assert!(ensure_permitted(&alice).is_ok());
+
+			set_member(&alice, None);
+			assert_eq!(ensure_permitted(&alice), Err(StfError::ShardAccessDenied(alice.clone())));
+		});
+	}
+
+	#[test]
+	fn required_role_is_checked() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+		let bob: AccountId = AccountKeyring::Bob.public().into();
+
+		state.execute_with(|| {
+			set_access_policy(AccessPolicy::RequireRole(1));
+			set_member(&alice, Some(vec![1]));
+			set_member(&bob, Some(vec![2]));
+
+			assert!(ensure_permitted(&alice).is_ok());
+			assert!(ensure_permitted(&bob).is_err());
+		});
+	}
+
+	#[test]
+	fn maintenance_mode_can_be_left_again() {
+		let mut state = SgxExternalities::default();
+
+		state.execute_with(|| {
+			assert!(!is_in_maintenance());
+
+			set_maintenance_mode(true);
+			assert!(is_in_maintenance());
+
+			set_maintenance_mode(false);
+			assert!(!is_in_maintenance());
+		});
+	}
+
+	#[test]
+	fn enclave_signer_is_exempt() {
+		let mut state = SgxExternalities::default();
+		let enclave_signer: AccountId = AccountKeyring::Eve.public().into();
+
+		state.execute_with(|| {
+			sp_io::storage::set(
+				&storage_value_key("Sudo", ENCLAVE_ACCOUNT_KEY),
+				&enclave_signer.encode(),
+			);
+			set_access_policy(AccessPolicy::AllowList);
+
+			assert!(ensure_permitted(&enclave_signer).is_ok());
+		});
+	}
+
+	#[test]
+	fn root_is_exempt() {
+		let mut state = SgxExternalities::default();
+		let root: AccountId = AccountKeyring::Alice.public().into();
+
+		state.execute_with(|| {
+			sp_io::storage::set(&storage_value_key("Sudo", ROOT_KEY), &root.encode());
+			set_access_policy(AccessPolicy::AllowList);
+
+			assert!(is_exempt(&root));
+			assert!(ensure_permitted(&root).is_ok());
+		});
+	}
+
+	#[test]
+	fn submission_is_denied_in_maintenance_mode_except_for_root() {
+		let mut state = SgxExternalities::default();
+		let root: AccountId = AccountKeyring::Alice.public().into();
+		let bob: AccountId = AccountKeyring::Bob.public().into();
+		state.execute_with(|| {
+			sp_io::storage::set(&storage_value_key("Sudo", ROOT_KEY), &root.encode());
+			set_maintenance_mode(true);
+		});
+		let storage = |key: &[u8]| state.get(key).cloned();
+
+		assert_eq!(SubmissionAccessControl.ensure_may_submit(&storage, &root), Ok(()));
+		assert_eq!(
+			SubmissionAccessControl.ensure_may_submit(&storage, &bob),
+			Err(SubmissionDenied::ShardInMaintenance)
+		);
+	}
+
+	#[test]
+	fn submission_of_non_member_is_denied() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+		let bob: AccountId = AccountKeyring::Bob.public().into();
+		state.execute_with(|| {
+			set_access_policy(AccessPolicy::AllowList);
+			set_member(&alice, Some(vec![]));
+		});
+		let storage = |key: &[u8]| state.get(key).cloned();
+
+		assert_eq!(SubmissionAccessControl.ensure_may_submit(&storage, &alice), Ok(()));
+		assert_eq!(
+			SubmissionAccessControl.ensure_may_submit(&storage, &bob),
+			Err(SubmissionDenied::AccessDenied)
+		);
+	}
+}
@@ -17,7 +17,12 @@
 
 #[cfg(feature = "test")]
 use crate::test_genesis::test_genesis_setup;
-use crate::{helpers::enclave_signer_account, Stf, ENCLAVE_ACCOUNT_KEY};
+use crate::{
+	cross_shard,
+	helpers::{enclave_signer_account, trusted_time},
+	migrations::{state_migrations, state_stf_version, STF_VERSION},
+	scheduler, shard_acl, unshielding, Stf, TrustedCall, ENCLAVE_ACCOUNT_KEY,
+};
 use codec::{Decode, Encode};
 use frame_support::traits::{OriginTrait, UnfilteredDispatchable};
 use itp_node_api::metadata::{provider::AccessNodeMetadata, NodeMetadataTrait};
@@ -26,14 +31,24 @@ use itp_stf_interface::{
 	parentchain_pallet::ParentchainPalletInterface,
 	sudo_pallet::SudoPalletInterface,
 	system_pallet::{SystemPalletAccountInterface, SystemPalletEventInterface},
-	ExecuteCall, ExecuteGetter, InitState, ShardVaultQuery, StateCallInterface,
-	StateGetterInterface, UpdateState, SHARD_VAULT_KEY,
+	CrossShardMessagingInterface, ExecuteCall, ExecuteGetter, InitShardGenesis, InitState,
+	ScheduledCallsInterface, ShardVaultQuery, StateCallInterface, StateGetterInterface,
+	StateMigration, StfVersion, StfVersioning, UpdateState, SHARD_GENESIS_CONFIG_KEY,
+	SHARD_VAULT_KEY, STF_VERSION_KEY,
+};
+use itp_stf_primitives::{
+	cross_shard::{
+		AnchoredCommitment, CommitmentIndex, CrossShardMessage, CrossShardProof, MessageNonce,
+	},
+	error::{StfError, StfResult},
+	traits::TrustedCallVerification,
+	types::ShardIdentifier,
 };
-use itp_stf_primitives::{error::StfError, traits::TrustedCallVerification};
 use itp_storage::storage_value_key;
 use itp_types::{
 	parentchain::{AccountId, ParentchainId},
-	OpaqueCall,
+	shard_lifecycle::ShardGenesisConfig,
+	Balance, OpaqueCall,
 };
 use itp_utils::stringify::account_id_to_string;
 use log::*;
@@ -88,11 +103,81 @@ where
 			}
 		});
 
+		Self::set_state_stf_version(&mut state, STF_VERSION);
+
 		trace!("Returning updated state: {:?}", state);
 		state
 	}
 }
 
+impl<TCS, G, State, Runtime> InitShardGenesis<State> for Stf<TCS, G, State, Runtime>
+where
+	State: SgxExternalitiesTrait + Debug,
+	Runtime:
+		frame_system::Config<AccountId = AccountId> + pallet_balances::Config<Balance = Balance>,
+	<<Runtime as frame_system::Config>::Lookup as StaticLookup>::Source: From<AccountId>,
+{
+	type Error = StfError;
+
+	fn apply_shard_genesis(
+		state: &mut State,
+		genesis_config: &ShardGenesisConfig,
+	) -> Result<(), Self::Error> {
+		if let Some(version) = genesis_config.stf_version {
+			if version != STF_VERSION {
+				return Err(StfError::IncompatibleStfVersion(version, STF_VERSION))
+			}
+		}
+
+		state.execute_with(|| {
+			for (account, free) in genesis_config.endowed_accounts.iter() {
+				pallet_balances::Call::<Runtime>::force_set_balance {
+					who: account.clone().into(),
+					new_free: *free,
+				}
+				.dispatch_bypass_filter(Runtime::RuntimeOrigin::root())
+				.map_err(|e| {
+					StfError::Dispatch(format!(
+						"Endow genesis account {} error: {:?}",
+						account_id_to_string(account),
+						e.error
+					))
+				})?;
+			}
+
+			shard_acl::set_access_policy(genesis_config.access_policy.clone());
+			for (member, roles) in genesis_config.members.iter() {
+				shard_acl::set_member(member, Some(roles.clone()));
+			}
+			Ok::<_, StfError>(())
+		})?;
+
+		state.insert(SHARD_GENESIS_CONFIG_KEY.into(), genesis_config.encode());
+		Ok(())
+	}
+}
+
+impl<TCS, G, State, Runtime> StfVersioning<State> for Stf<TCS, G, State, Runtime>
+where
+	State: SgxExternalitiesTrait,
+{
+	fn stf_version() -> StfVersion {
+		STF_VERSION
+	}
+
+	fn get_state_stf_version(state: &mut State) -> StfVersion {
+		state_stf_version(state)
+	}
+
+	fn set_state_stf_version(state: &mut State, version: StfVersion) {
+		state.insert(STF_VERSION_KEY.into(), version.encode());
+	}
+
+	fn state_migrations() -> Vec<StateMigration<State>> {
+		state_migrations()
+	}
+}
+
 impl<TCS, G, State, Runtime>
 	UpdateState<State, <State as SgxExternalitiesTrait>::SgxExternalitiesDiffType>
 	for Stf<TCS, G, State, Runtime>
@@ -147,10 +232,12 @@ where
 	fn execute_call(
 		state: &mut State,
 		call: TCS,
+		mrenclave: &[u8; 32],
+		shard: &ShardIdentifier,
 		calls: &mut Vec<OpaqueCall>,
 		node_metadata_repo: Arc<NodeMetadataRepository>,
 	) -> Result<(), Self::Error> {
-		state.execute_with(|| call.execute(calls, node_metadata_repo))
+		state.execute_with(|| call.execute(mrenclave, shard, calls, node_metadata_repo))
 	}
 }
 
@@ -162,6 +249,65 @@ where
 	fn execute_getter(state: &mut State, getter: G) -> Option<Vec<u8>> {
 		state.execute_with(|| getter.execute())
 	}
+
+	fn state_timestamp(state: &mut State) -> u64 {
+		state.execute_with(trusted_time)
+	}
+}
+
+impl<TCS, G, State, Runtime, NodeMetadataRepository>
+	ScheduledCallsInterface<State, NodeMetadataRepository> for Stf<TCS, G, State, Runtime>
+where
+	State: SgxExternalitiesTrait,
+	NodeMetadataRepository: AccessNodeMetadata,
+	NodeMetadataRepository::MetadataType: NodeMetadataTrait,
+{
+	fn execute_due_scheduled_calls(
+		state: &mut State,
+		mrenclave: &[u8; 32],
+		shard: &ShardIdentifier,
+		calls: &mut Vec<OpaqueCall>,
+		node_metadata_repo: Arc<NodeMetadataRepository>,
+	) -> usize {
+		state.execute_with(|| {
+			scheduler::execute_due_calls(mrenclave, shard, calls, node_metadata_repo)
+		})
+	}
+}
+
+impl<TCS, G, State, Runtime> CrossShardMessagingInterface<State> for Stf<TCS, G, State, Runtime>
+where
+	State: SgxExternalitiesTrait,
+{
+	type Call = TrustedCall;
+
+	fn take_outgoing_messages(state: &mut State) -> Vec<CrossShardMessage> {
+		state.execute_with(cross_shard::take_outgoing)
+	}
+
+	fn delivery_call(
+		enclave_account: AccountId,
+		source: ShardIdentifier,
+		message: CrossShardMessage,
+	) -> TrustedCall {
+		TrustedCall::cross_shard_deliver(enclave_account, source, message)
+	}
+
+	fn message_proof(
+		state: &mut State,
+		destination: &ShardIdentifier,
+		nonce: MessageNonce,
+	) -> Option<CrossShardProof> {
+		state.execute_with(|| cross_shard::message_proof(destination, nonce))
+	}
+
+	fn anchored_commitment(
+		state: &mut State,
+		source: &ShardIdentifier,
+		index: CommitmentIndex,
+	) -> Option<AnchoredCommitment> {
+		state.execute_with(|| cross_shard::anchored_commitment(source, index))
+	}
 }
 
 impl<TCS, G, State, Runtime> ShardVaultQuery<State> for Stf<TCS, G, State, Runtime>
@@ -173,6 +319,29 @@ where
 			.get(SHARD_VAULT_KEY.as_bytes())
 			.and_then(|v| Decode::decode(&mut v.clone().as_slice()).ok())
 	}
+
+	fn get_pending_unshield_payouts(state: &mut State) -> u32 {
+		state.execute_with(|| unshielding::pending_payouts().len() as u32)
+	}
+
+	fn get_unconfirmed_payouts(state: &mut State) -> Vec<(AccountId, Balance)> {
+		state.execute_with(|| {
+			unshielding::outstanding_batch()
+				.map(|batch| batch.payouts)
+				.unwrap_or_default()
+				.into_iter()
+				.map(|payout| (payout.beneficiary, payout.amount))
+				.collect()
+		})
+	}
+
+	fn get_payout_batch_to_send(state: &mut State) -> Option<(u32, OpaqueCall)> {
+		state.execute_with(unshielding::batch_to_send)
+	}
+
+	fn take_retirement_payouts(state: &mut State) -> StfResult<Vec<(AccountId, Balance)>> {
+		state.execute_with(unshielding::take_retirement_payouts)
+	}
 }
 
 impl<TCS, G, State, Runtime> SudoPalletInterface<State> for Stf<TCS, G, State, Runtime>
@@ -15,22 +15,35 @@
 
 */
 
-use crate::{Getter, State, Stf, TrustedCall, TrustedCallSigned};
+use crate::{scheduler::ScheduledAt, Getter, Index, State, Stf, TrustedCall, TrustedCallSigned};
+use codec::Encode;
 use ita_sgx_runtime::Runtime;
 use itp_node_api::metadata::{metadata_mocks::NodeMetadataMock, provider::NodeMetadataRepository};
+use itp_sgx_externalities::SgxExternalitiesTrait;
 use itp_stf_interface::{
-	sudo_pallet::SudoPalletInterface, system_pallet::SystemPalletAccountInterface, InitState,
-	StateCallInterface,
+	sudo_pallet::SudoPalletInterface, system_pallet::SystemPalletAccountInterface,
+	InitShardGenesis, InitState, ScheduledCallsInterface, StateCallInterface, StfVersioning,
 };
-use itp_stf_primitives::types::{AccountId, Signature};
+use itp_stf_primitives::{
+	error::StfError,
+	fees::FeeConfig,
+	session_keys::SessionKey,
+	shard_acl::AccessPolicy,
+	traits::TrustedCallSigning,
+	types::{AccountId, KeyPair, ShardIdentifier, Signature},
+};
+use itp_storage::storage_value_key;
+use itp_types::shard_lifecycle::ShardGenesisConfig;
 use sp_core::{
 	ed25519::{Pair as Ed25519Pair, Signature as Ed25519Signature},
 	Pair,
 };
-use std::{sync::Arc, vec::Vec};
+use std::{boxed::Box, sync::Arc, vec, vec::Vec};
 
 pub type StfState = Stf<TrustedCallSigned, Getter, State, Runtime>;
 
+const MRENCLAVE: [u8; 32] = [1u8; 32];
+
 pub fn enclave_account_initialization_works() {
 	let enclave_account = AccountId::new([2u8; 32]);
 	let mut state = StfState::init_state(enclave_account.clone());
@@ -58,7 +71,15 @@ pub fn shield_funds_increments_signer_account_nonce() {
 	);
 
 	let repo = Arc::new(NodeMetadataRepository::new(NodeMetadataMock::new()));
-	StfState::execute_call(&mut state, shield_funds_call, &mut Vec::new(), repo).unwrap();
+	StfState::execute_call(
+		&mut state,
+		shield_funds_call,
+		&MRENCLAVE,
+		&ShardIdentifier::default(),
+		&mut Vec::new(),
+		repo,
+	)
+	.unwrap();
 	assert_eq!(1, StfState::get_account_nonce(&mut state, &enclave_signer_account_id));
 }
 
@@ -70,3 +91,318 @@ pub fn test_root_account_exists_after_initialization() {
 	let account_data = StfState::get_account_data(&mut state, &root_account);
 	assert!(account_data.free > 0);
 }
+
+pub fn shard_genesis_endows_accounts_and_sets_access_control() {
+	let mut state = StfState::init_state(AccountId::new([2u8; 32]));
+	let member = AccountId::new([3u8; 32]);
+	let genesis_config = ShardGenesisConfig {
+		endowed_accounts: vec![(member.clone(), 5_000), (AccountId::new([4u8; 32]), 7_000)],
+		access_policy: AccessPolicy::RequireRole(1),
+		members: vec![(member.clone(), vec![1])],
+		stf_version: Some(StfState::stf_version()),
+		..Default::default()
+	};
+
+	StfState::apply_shard_genesis(&mut state, &genesis_config).unwrap();
+
+	assert_eq!(5_000, StfState::get_account_data(&mut state, &member).free);
+	state.execute_with(|| {
+		assert_eq!(AccessPolicy::RequireRole(1), crate::shard_acl::access_policy());
+		assert_eq!(Some(vec![1]), crate::shard_acl::member_roles(&member));
+		assert!(crate::shard_acl::ensure_permitted(&AccountId::new([4u8; 32])).is_err());
+	});
+}
+
+pub fn shard_genesis_for_other_stf_version_is_rejected() {
+	let mut state = StfState::init_state(AccountId::new([2u8; 32]));
+	let genesis_config =
+		ShardGenesisConfig { stf_version: Some(StfState::stf_version() + 1), ..Default::default() };
+
+	assert_eq!(
+		StfState::apply_shard_genesis(&mut state, &genesis_config),
+		Err(StfError::IncompatibleStfVersion(StfState::stf_version() + 1, StfState::stf_version()))
+	);
+}
+
+pub fn session_call_transfers_on_behalf_of_owner() {
+	let (mut state, owner, delegate) = state_with_session_key(vec![b"balance_transfer".to_vec()]);
+	let to = AccountId::new([5u8; 32]);
+	let transfer = TrustedCall::balance_transfer(owner.clone(), to.clone(), 1_000);
+
+	execute_unsigned(&mut state, TrustedCall::session_call(delegate.clone(), Box::new(transfer)))
+		.unwrap();
+
+	assert_eq!(1_000, StfState::get_account_data(&mut state, &to).free);
+	assert_eq!(4_000, StfState::get_account_data(&mut state, &owner).free);
+	assert_eq!(1, StfState::get_account_nonce(&mut state, &delegate));
+	assert_eq!(1, StfState::get_account_nonce(&mut state, &owner));
+}
+
+pub fn session_call_outside_of_scope_is_rejected() {
+	let (mut state, owner, delegate) = state_with_session_key(vec![b"noop".to_vec()]);
+	let transfer = TrustedCall::balance_transfer(owner.clone(), delegate.clone(), 1_000);
+	let register_other = TrustedCall::session_key_register(
+		owner.clone(),
+		AccountId::new([6u8; 32]),
+		SessionKey::new(u64::MAX, vec![b"noop".to_vec()]),
+	);
+
+	assert_eq!(
+		execute_unsigned(
+			&mut state,
+			TrustedCall::session_call(delegate.clone(), Box::new(transfer))
+		),
+		Err(StfError::SessionKeyNotPermitted(delegate.clone(), owner.clone()))
+	);
+	assert!(execute_unsigned(
+		&mut state,
+		TrustedCall::session_call(delegate.clone(), Box::new(register_other))
+	)
+	.is_err());
+	assert_eq!(5_000, StfState::get_account_data(&mut state, &owner).free);
+}
+
+pub fn session_call_of_delegate_without_shard_access_is_rejected() {
+	let (mut state, owner, delegate) = state_with_session_key(vec![b"balance_transfer".to_vec()]);
+	let root = StfState::get_root(&mut state);
+	let transfer = TrustedCall::balance_transfer(owner.clone(), delegate.clone(), 1_000);
+	execute_unsigned(
+		&mut state,
+		TrustedCall::shard_acl_set_policy(root.clone(), AccessPolicy::AllowList),
+	)
+	.unwrap();
+	execute_unsigned(
+		&mut state,
+		TrustedCall::shard_acl_set_member(root, owner.clone(), Some(vec![])),
+	)
+	.unwrap();
+
+	assert_eq!(
+		execute_unsigned(
+			&mut state,
+			TrustedCall::session_call(delegate.clone(), Box::new(transfer))
+		),
+		Err(StfError::ShardAccessDenied(delegate))
+	);
+	assert_eq!(5_000, StfState::get_account_data(&mut state, &owner).free);
+}
+
+/// State in which the owner (endowed with 5000) registered a session key of the delegate.
+fn state_with_session_key(scope: Vec<Vec<u8>>) -> (State, AccountId, AccountId) {
+	let mut state = StfState::init_state(AccountId::new([2u8; 32]));
+	let owner = AccountId::new([3u8; 32]);
+	let delegate = AccountId::new([4u8; 32]);
+	let genesis_config =
+		ShardGenesisConfig { endowed_accounts: vec![(owner.clone(), 5_000)], ..Default::default() };
+	StfState::apply_shard_genesis(&mut state, &genesis_config).unwrap();
+
+	let register = TrustedCall::session_key_register(
+		owner.clone(),
+		delegate.clone(),
+		SessionKey::new(u64::MAX, scope),
+	);
+	execute_unsigned(&mut state, register).unwrap();
+	(state, owner, delegate)
+}
+
+/// Executes the call with the current nonce of its sender, the signature is not checked.
+fn execute_unsigned(state: &mut State, call: TrustedCall) -> Result<(), StfError> {
+	let nonce = StfState::get_account_nonce(state, call.sender_account());
+	let signed_call =
+		TrustedCallSigned::new(call, nonce, Signature::Ed25519(Ed25519Signature([0u8; 64])));
+	let repo = Arc::new(NodeMetadataRepository::new(NodeMetadataMock::new()));
+	StfState::execute_call(
+		state,
+		signed_call,
+		&MRENCLAVE,
+		&ShardIdentifier::default(),
+		&mut Vec::new(),
+		repo,
+	)
+}
+
+pub fn sponsored_call_increments_nonces_of_sponsor_and_user() {
+	let (mut state, sponsor, user) = state_with_unfunded_user();
+	let register = TrustedCall::session_key_register(
+		user.clone(),
+		AccountId::new([6u8; 32]),
+		SessionKey::new(u64::MAX, vec![b"noop".to_vec()]),
+	);
+
+	execute_unsigned(&mut state, sponsored(sponsor.clone(), register, 0)).unwrap();
+
+	assert_eq!(1, StfState::get_account_nonce(&mut state, &sponsor));
+	assert_eq!(1, StfState::get_account_nonce(&mut state, &user));
+	assert_eq!(0, StfState::get_account_data(&mut state, &user).free);
+}
+
+pub fn sponsored_call_with_outdated_user_nonce_is_rejected() {
+	let (mut state, sponsor, user) = state_with_unfunded_user();
+
+	execute_unsigned(&mut state, sponsored(sponsor.clone(), TrustedCall::noop(user.clone()), 0))
+		.unwrap();
+
+	assert_eq!(
+		execute_unsigned(&mut state, sponsored(sponsor, TrustedCall::noop(user.clone()), 0)),
+		Err(StfError::InvalidNonce(0, 1))
+	);
+	assert_eq!(1, StfState::get_account_nonce(&mut state, &user));
+}
+
+pub fn sponsored_call_with_forged_user_signature_is_rejected() {
+	let (mut state, sponsor, user) = state_with_unfunded_user();
+
+	assert_eq!(
+		execute_unsigned(&mut state, forged_sponsored(sponsor, TrustedCall::noop(user.clone()))),
+		Err(StfError::InvalidSignature(user.clone()))
+	);
+	assert_eq!(0, StfState::get_account_nonce(&mut state, &user));
+}
+
+pub fn sponsored_call_can_not_be_scheduled() {
+	let (mut state, victim, attacker) = state_with_unfunded_user();
+	let theft = TrustedCall::balance_transfer(victim.clone(), attacker.clone(), 1_000);
+	let schedule = TrustedCall::schedule_call(
+		attacker.clone(),
+		ScheduledAt::SidechainBlock(1),
+		Box::new(forged_sponsored(attacker.clone(), theft)),
+	);
+
+	assert!(execute_unsigned(&mut state, schedule).is_err());
+	state.execute_with(|| {
+		sp_io::storage::set(&storage_value_key("System", "Number"), &1u64.encode())
+	});
+	assert_eq!(0, execute_due_scheduled_calls(&mut state));
+	assert_eq!(5_000, StfState::get_account_data(&mut state, &victim).free);
+	assert_eq!(0, StfState::get_account_data(&mut state, &attacker).free);
+}
+
+pub fn sponsored_call_can_not_be_submitted_by_a_session_key() {
+	let (mut state, owner, delegate) = state_with_session_key(vec![b"sponsored_call".to_vec()]);
+	let victim = AccountId::new([7u8; 32]);
+	let root = StfState::get_root(&mut state);
+	execute_unsigned(&mut state, TrustedCall::balance_set_balance(root, victim.clone(), 5_000, 0))
+		.unwrap();
+	let theft = TrustedCall::balance_transfer(victim.clone(), owner.clone(), 1_000);
+	let sponsored = forged_sponsored(owner.clone(), theft);
+
+	assert!(execute_unsigned(&mut state, TrustedCall::session_call(delegate, Box::new(sponsored)))
+		.is_err());
+	assert_eq!(5_000, StfState::get_account_data(&mut state, &victim).free);
+	assert_eq!(5_000, StfState::get_account_data(&mut state, &owner).free);
+}
+
+/// State with a funded sponsor and a user without any balance.
+fn state_with_unfunded_user() -> (State, AccountId, AccountId) {
+	let mut state = StfState::init_state(AccountId::new([2u8; 32]));
+	let sponsor = AccountId::new([7u8; 32]);
+	let genesis_config = ShardGenesisConfig {
+		endowed_accounts: vec![(sponsor.clone(), 5_000)],
+		..Default::default()
+	};
+	StfState::apply_shard_genesis(&mut state, &genesis_config).unwrap();
+	(state, sponsor, user_pair().public().into())
+}
+
+fn user_pair() -> Ed25519Pair {
+	Ed25519Pair::from_seed(&[8u8; 32])
+}
+
+/// Sponsored call signed by the user of [`state_with_unfunded_user`].
+fn sponsored(sponsor: AccountId, call: TrustedCall, user_nonce: Index) -> TrustedCall {
+	let signed_call = call.sign(
+		&KeyPair::Ed25519(Box::new(user_pair())),
+		user_nonce,
+		&MRENCLAVE,
+		&ShardIdentifier::default(),
+	);
+	TrustedCall::sponsored_call(sponsor, Box::new(signed_call))
+}
+
+/// Sponsored call that carries an invalid signature of its sender.
+fn forged_sponsored(sponsor: AccountId, call: TrustedCall) -> TrustedCall {
+	let signed_call =
+		TrustedCallSigned::new(call, 0, Signature::Ed25519(Ed25519Signature([0u8; 64])));
+	TrustedCall::sponsored_call(sponsor, Box::new(signed_call))
+}
+
+pub fn call_fee_is_charged_from_the_sender() {
+	let (mut state, sponsor, user) = state_with_unfunded_user();
+	let root = StfState::get_root(&mut state);
+	let fee_config = FeeConfig { base_fee: 1_000, ..Default::default() };
+	execute_unsigned(&mut state, TrustedCall::fees_set_config(root, fee_config)).unwrap();
+
+	execute_unsigned(&mut state, TrustedCall::noop(sponsor.clone())).unwrap();
+	assert_eq!(
+		execute_unsigned(&mut state, TrustedCall::noop(user.clone())),
+		Err(StfError::CannotPayFee(user.clone(), 1_000))
+	);
+
+	assert_eq!(4_000, StfState::get_account_data(&mut state, &sponsor).free);
+	assert_eq!(1, StfState::get_account_nonce(&mut state, &user));
+}
+
+pub fn scheduled_call_is_executed_on_behalf_of_its_sender() {
+	let (mut state, owner, to) = state_with_scheduled_transfer(1_000);
+
+	assert_eq!(1, execute_due_scheduled_calls(&mut state));
+	assert_eq!(1_000, StfState::get_account_data(&mut state, &to).free);
+	assert_eq!(4_000, StfState::get_account_data(&mut state, &owner).free);
+}
+
+pub fn scheduled_call_of_sender_without_shard_access_is_dropped() {
+	let (mut state, owner, to) = state_with_scheduled_transfer(1_000);
+	let root = StfState::get_root(&mut state);
+	execute_unsigned(&mut state, TrustedCall::shard_acl_set_policy(root, AccessPolicy::AllowList))
+		.unwrap();
+
+	assert_eq!(1, execute_due_scheduled_calls(&mut state));
+	assert_eq!(0, StfState::get_account_data(&mut state, &to).free);
+	assert_eq!(5_000, StfState::get_account_data(&mut state, &owner).free);
+	assert_eq!(0, execute_due_scheduled_calls(&mut state));
+}
+
+pub fn scheduled_call_fails_if_sender_cannot_pay_the_fee() {
+	let (mut state, owner, to) = state_with_scheduled_transfer(1_000);
+	let root = StfState::get_root(&mut state);
+	let fee_config = FeeConfig { base_fee: 10_000, ..Default::default() };
+	execute_unsigned(&mut state, TrustedCall::fees_set_config(root, fee_config)).unwrap();
+
+	assert_eq!(1, execute_due_scheduled_calls(&mut state));
+	assert_eq!(0, StfState::get_account_data(&mut state, &to).free);
+	assert_eq!(5_000, StfState::get_account_data(&mut state, &owner).free);
+}
+
+/// State in which the owner (endowed with 5000) scheduled a transfer for the next sidechain
+/// block, which is already produced.
+fn state_with_scheduled_transfer(amount: u128) -> (State, AccountId, AccountId) {
+	let mut state = StfState::init_state(AccountId::new([2u8; 32]));
+	let owner = AccountId::new([3u8; 32]);
+	let to = AccountId::new([5u8; 32]);
+	let genesis_config =
+		ShardGenesisConfig { endowed_accounts: vec![(owner.clone(), 5_000)], ..Default::default() };
+	StfState::apply_shard_genesis(&mut state, &genesis_config).unwrap();
+
+	let transfer = TrustedCall::balance_transfer(owner.clone(), to.clone(), amount);
+	let schedule = TrustedCall::schedule_call(
+		owner.clone(),
+		ScheduledAt::SidechainBlock(1),
+		Box::new(transfer),
+	);
+	execute_unsigned(&mut state, schedule).unwrap();
+	state.execute_with(|| {
+		sp_io::storage::set(&storage_value_key("System", "Number"), &1u64.encode())
+	});
+	(state, owner, to)
+}
+
+fn execute_due_scheduled_calls(state: &mut State) -> usize {
+	let repo = Arc::new(NodeMetadataRepository::new(NodeMetadataMock::new()));
+	StfState::execute_due_scheduled_calls(
+		state,
+		&MRENCLAVE,
+		&ShardIdentifier::default(),
+		&mut Vec::new(),
+		repo,
+	)
+}
@@ -16,16 +16,23 @@
 */
 
 #[cfg(feature = "evm")]
-use sp_core::{H160, H256, U256};
+use sp_core::{H160, U256};
 
 #[cfg(feature = "evm")]
 use std::vec::Vec;
 
 #[cfg(feature = "evm")]
-use crate::evm_helpers::{create_code_hash, evm_create2_address, evm_create_address};
+use crate::evm_helpers::{
+	create_code_hash, eth_account_id, eth_sign, evm_create2_address, evm_create_address,
+};
 use crate::{
-	helpers::{ensure_enclave_signer_account, get_storage_by_key_hash},
-	Getter,
+	assets,
+	confidential_events::{self, ConfidentialEvent},
+	cross_shard, fees,
+	helpers::{ensure_enclave_signer_account, get_storage_by_key_hash, verify_trusted_signature},
+	rent, reserves,
+	scheduler::{self, ScheduledAt},
+	session_keys, shard_acl, unshielding, weights, Getter,
 };
 use codec::{Compact, Decode, Encode};
 use frame_support::{ensure, traits::UnfilteredDispatchable};
@@ -35,25 +42,36 @@ pub use ita_sgx_runtime::{Balance, Index};
 use ita_sgx_runtime::{Runtime, System};
 use itp_node_api::metadata::{provider::AccessNodeMetadata, NodeMetadataTrait};
 use itp_node_api_metadata::{
-	pallet_balances::BalancesCallIndexes, pallet_enclave_bridge::EnclaveBridgeCallIndexes,
-	pallet_proxy::ProxyCallIndexes,
+	pallet_assets::AssetsCallIndexes, pallet_balances::BalancesCallIndexes,
+	pallet_enclave_bridge::EnclaveBridgeCallIndexes, pallet_proxy::ProxyCallIndexes,
+	pallet_system::SystemCallIndexes, pallet_tokens::TokensCallIndexes,
+	pallet_utility::UtilityCallIndexes,
 };
 use itp_stf_interface::{ExecuteCall, SHARD_VAULT_KEY};
 use itp_stf_primitives::{
+	cross_shard::{AnchoredCommitment, CrossShardMessage, CrossShardPayload},
 	error::StfError,
-	traits::{TrustedCallSigning, TrustedCallVerification},
-	types::{AccountId, KeyPair, ShardIdentifier, Signature, TrustedOperation},
+	fees::{FeeConfig, FeeMultiplier},
+	session_keys::SessionKey,
+	shard_acl::{AccessPolicy, MemberRoles},
+	traits::{TrustedCallSigning, TrustedCallVerification, TrustedCallWeight},
+	types::{AccountId, ShardIdentifier, Signature, TrustedOperation},
+};
+use itp_types::{
+	parentchain::{ParentchainAssetId, ProxyType},
+	Address, OpaqueCall,
 };
-use itp_types::{parentchain::ProxyType, Address, OpaqueCall};
 use itp_utils::stringify::account_id_to_string;
 use log::*;
+#[cfg(feature = "evm")]
+use sp_core::ecdsa;
 use sp_core::{
 	crypto::{AccountId32, UncheckedFrom},
-	ed25519,
+	ed25519, H256,
 };
 use sp_io::hashing::blake2_256;
-use sp_runtime::{traits::Verify, MultiAddress, MultiSignature};
-use std::{format, prelude::v1::*, sync::Arc};
+use sp_runtime::{MultiAddress, MultiSignature};
+use std::{format, prelude::v1::*, sync::Arc, time::Duration};
 
 #[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
@@ -63,8 +81,30 @@ pub enum TrustedCall {
 	balance_transfer(AccountId, AccountId, Balance),
 	balance_unshield(AccountId, AccountId, Balance, ShardIdentifier), // (AccountIncognito, BeneficiaryPublicAccount, Amount, Shard)
 	balance_shield(AccountId, AccountId, Balance), // (Root, AccountIncognito, Amount)
+	schedule_call(AccountId, ScheduledAt, Box<TrustedCall>), // (Origin, When, Call)
+	reap_accounts(AccountId),                      // (EnclaveSigner)
+	balance_unshield_batched(AccountId, AccountId, Balance), // (AccountIncognito, BeneficiaryPublicAccount, Amount)
+	payout_unshield_batch(AccountId),                        // (EnclaveSigner)
+	confirm_unshield_payouts(AccountId, Vec<H256>), // (EnclaveSigner, Hashes of the observed vault remarks)
+	assets_shield(AccountId, AccountId, ParentchainAssetId, Balance), // (EnclaveSigner, AccountIncognito, Asset, Amount)
+	assets_transfer(AccountId, AccountId, ParentchainAssetId, Balance), // (From, To, Asset, Amount)
+	assets_unshield(AccountId, AccountId, ParentchainAssetId, Balance), // (AccountIncognito, BeneficiaryPublicAccount, Asset, Amount)
+	shard_acl_set_policy(AccountId, AccessPolicy),                      // (Root, Policy)
+	shard_acl_set_member(AccountId, AccountId, Option<MemberRoles>), // (Root, Member, Roles or None to remove)
+	shard_acl_set_maintenance_mode(AccountId, bool),                 // (Root, Enabled)
+	session_key_register(AccountId, AccountId, SessionKey),          // (Owner, Delegate, SessionKey)
+	session_key_revoke(AccountId, AccountId),                        // (Owner, Delegate)
+	session_call(AccountId, Box<TrustedCall>), // (Delegate, Call with the owner as sender)
+	sponsored_call(AccountId, Box<TrustedCallSigned>), // (Sponsor, Call signed by its sender)
+	fees_set_config(AccountId, FeeConfig),     // (Root, FeeConfig)
+	fees_set_multiplier(AccountId, FeeMultiplier), // (Root, Multiplier)
+	cross_shard_transfer(AccountId, ShardIdentifier, AccountId, Balance), // (From, DestinationShard, To, Amount)
+	cross_shard_deliver(AccountId, ShardIdentifier, CrossShardMessage), // (EnclaveSigner, SourceShard, Message)
+	cross_shard_commit_outbox(AccountId, ShardIdentifier),              // (EnclaveSigner, Shard)
+	cross_shard_anchor_commitment(AccountId, AnchoredCommitment),       // (EnclaveSigner, Commitment)
+	reserves_attest(AccountId, ShardIdentifier),                        // (EnclaveSigner, Shard)
 	#[cfg(feature = "evm")]
-	evm_withdraw(AccountId, H160, Balance), // (Origin, Address EVM Account, Value)
+	evm_withdraw(AccountId, H160, Balance),     // (Origin, Address EVM Account, Value)
 	// (Origin, Source, Target, Input, Value, Gas limit, Max fee per gas, Max priority fee per gas, Nonce, Access list)
 	#[cfg(feature = "evm")]
 	evm_call(
@@ -116,6 +156,28 @@ impl TrustedCall {
 			Self::balance_transfer(sender_account, ..) => sender_account,
 			Self::balance_unshield(sender_account, ..) => sender_account,
 			Self::balance_shield(sender_account, ..) => sender_account,
+			Self::schedule_call(sender_account, ..) => sender_account,
+			Self::reap_accounts(sender_account) => sender_account,
+			Self::balance_unshield_batched(sender_account, ..) => sender_account,
+			Self::payout_unshield_batch(sender_account) => sender_account,
+			Self::confirm_unshield_payouts(sender_account, ..) => sender_account,
+			Self::assets_shield(sender_account, ..) => sender_account,
+			Self::assets_transfer(sender_account, ..) => sender_account,
+			Self::assets_unshield(sender_account, ..) => sender_account,
+			Self::shard_acl_set_policy(sender_account, ..) => sender_account,
+			Self::shard_acl_set_member(sender_account, ..) => sender_account,
+			Self::shard_acl_set_maintenance_mode(sender_account, ..) => sender_account,
+			Self::session_key_register(sender_account, ..) => sender_account,
+			Self::session_key_revoke(sender_account, ..) => sender_account,
+			Self::session_call(sender_account, ..) => sender_account,
+			Self::sponsored_call(sender_account, ..) => sender_account,
+			Self::fees_set_config(sender_account, ..) => sender_account,
+			Self::fees_set_multiplier(sender_account, ..) => sender_account,
+			Self::cross_shard_transfer(sender_account, ..) => sender_account,
+			Self::cross_shard_deliver(sender_account, ..) => sender_account,
+			Self::cross_shard_commit_outbox(sender_account, ..) => sender_account,
+			Self::cross_shard_anchor_commitment(sender_account, ..) => sender_account,
+			Self::reserves_attest(sender_account, ..) => sender_account,
 			#[cfg(feature = "evm")]
 			Self::evm_withdraw(sender_account, ..) => sender_account,
 			#[cfg(feature = "evm")]
@@ -126,22 +188,98 @@ impl TrustedCall {
 			Self::evm_create2(sender_account, ..) => sender_account,
 		}
 	}
+
+	/// Account that pays for the execution of the call, the sponsor of a sponsored call.
+	pub fn fee_payer(&self) -> &AccountId {
+		match self {
+			Self::sponsored_call(sponsor, ..) => sponsor,
+			_ => self.sender_account(),
+		}
+	}
+
+	/// Weight of the call, including the ones of the calls it wraps.
+	pub fn weight(&self) -> Duration {
+		match self {
+			Self::session_call(_, call) => weights::weight_of(self.name()) + call.weight(),
+			Self::sponsored_call(_, inner) => weights::weight_of(self.name()) + inner.call.weight(),
+			call => weights::weight_of(call.name()),
+		}
+	}
+
+	/// Name of the call variant, as used by the trusted call benchmarks and [weights].
+	pub fn name(&self) -> &'static str {
+		match self {
+			Self::noop(..) => "noop",
+			Self::balance_set_balance(..) => "balance_set_balance",
+			Self::balance_transfer(..) => "balance_transfer",
+			Self::balance_unshield(..) => "balance_unshield",
+			Self::balance_shield(..) => "balance_shield",
+			Self::schedule_call(..) => "schedule_call",
+			Self::reap_accounts(..) => "reap_accounts",
+			Self::balance_unshield_batched(..) => "balance_unshield_batched",
+			Self::payout_unshield_batch(..) => "payout_unshield_batch",
+			Self::confirm_unshield_payouts(..) => "confirm_unshield_payouts",
+			Self::assets_shield(..) => "assets_shield",
+			Self::assets_transfer(..) => "assets_transfer",
+			Self::assets_unshield(..) => "assets_unshield",
+			Self::shard_acl_set_policy(..) => "shard_acl_set_policy",
+			Self::shard_acl_set_member(..) => "shard_acl_set_member",
+			Self::shard_acl_set_maintenance_mode(..) => "shard_acl_set_maintenance_mode",
+			Self::session_key_register(..) => "session_key_register",
+			Self::session_key_revoke(..) => "session_key_revoke",
+			Self::session_call(..) => "session_call",
+			Self::sponsored_call(..) => "sponsored_call",
+			Self::fees_set_config(..) => "fees_set_config",
+			Self::fees_set_multiplier(..) => "fees_set_multiplier",
+			Self::cross_shard_transfer(..) => "cross_shard_transfer",
+			Self::cross_shard_deliver(..) => "cross_shard_deliver",
+			Self::cross_shard_commit_outbox(..) => "cross_shard_commit_outbox",
+			Self::cross_shard_anchor_commitment(..) => "cross_shard_anchor_commitment",
+			Self::reserves_attest(..) => "reserves_attest",
+			#[cfg(feature = "evm")]
+			Self::evm_withdraw(..) => "evm_withdraw",
+			#[cfg(feature = "evm")]
+			Self::evm_call(..) => "evm_call",
+			#[cfg(feature = "evm")]
+			Self::evm_create(..) => "evm_create",
+			#[cfg(feature = "evm")]
+			Self::evm_create2(..) => "evm_create2",
+		}
+	}
 }
 
 impl TrustedCallSigning<TrustedCallSigned> for TrustedCall {
-	fn sign(
+	fn signing_payload(
 		&self,
-		pair: &KeyPair,
 		nonce: Index,
 		mrenclave: &[u8; 32],
 		shard: &ShardIdentifier,
-	) -> TrustedCallSigned {
+	) -> Vec<u8> {
 		let mut payload = self.encode();
 		payload.append(&mut nonce.encode());
 		payload.append(&mut mrenclave.encode());
 		payload.append(&mut shard.encode());
+		payload
+	}
 
-		TrustedCallSigned { call: self.clone(), nonce, signature: pair.sign(payload.as_slice()) }
+	fn with_signature(&self, nonce: Index, signature: Signature) -> TrustedCallSigned {
+		TrustedCallSigned { call: self.clone(), nonce, signature }
+	}
+}
+
+#[cfg(feature = "evm")]
+impl TrustedCall {
+	/// Signs the call with an ethereum key (EIP-191), the sender has to be the account
+	/// the ethereum address is mapped to, see [eth_account_id].
+	pub fn sign_eth(
+		&self,
+		pair: &ecdsa::Pair,
+		nonce: Index,
+		mrenclave: &[u8; 32],
+		shard: &ShardIdentifier,
+	) -> TrustedCallSigned {
+		let payload = self.signing_payload(nonce, mrenclave, shard);
+		self.with_signature(nonce, eth_sign(pair, payload.as_slice()).into())
 	}
 }
 
@@ -186,12 +324,28 @@ impl TrustedCallVerification for TrustedCallSigned {
 		self.nonce
 	}
 
+	fn nonces(&self) -> Vec<(AccountId, Index)> {
+		let mut nonces = vec![(self.call.sender_account().clone(), self.nonce)];
+		if let TrustedCall::sponsored_call(_, inner) = &self.call {
+			nonces.push((inner.call.sender_account().clone(), inner.nonce));
+		}
+		nonces
+	}
+
+	/// The inner call of a sponsored call has to be signed by its sender too.
 	fn verify_signature(&self, mrenclave: &[u8; 32], shard: &ShardIdentifier) -> bool {
-		let mut payload = self.call.encode();
-		payload.append(&mut self.nonce.encode());
-		payload.append(&mut mrenclave.encode());
-		payload.append(&mut shard.encode());
-		self.signature.verify(payload.as_slice(), self.call.sender_account())
+		let payload = self.call.signing_payload(self.nonce, mrenclave, shard);
+		verify_trusted_signature(&self.signature, payload.as_slice(), self.call.sender_account())
+			&& match &self.call {
+				TrustedCall::sponsored_call(_, inner) => inner.verify_signature(mrenclave, shard),
+				_ => true,
+			}
+	}
+}
+
+impl TrustedCallWeight for TrustedCallSigned {
+	fn weight(&self) -> Duration {
+		self.call.weight()
 	}
 }
 
@@ -214,11 +368,12 @@ where
 
 	fn execute(
 		self,
+		mrenclave: &[u8; 32],
+		shard: &ShardIdentifier,
 		calls: &mut Vec<OpaqueCall>,
 		node_metadata_repo: Arc<NodeMetadataRepository>,
 	) -> Result<(), Self::Error> {
 		let sender = self.call.sender_account().clone();
-		let call_hash = blake2_256(&self.call.encode());
 		let system_nonce = System::account_nonce(&sender);
 		ensure!(self.nonce == system_nonce, Self::Error::InvalidNonce(self.nonce, system_nonce));
 
@@ -226,14 +381,127 @@ where
 		// The call must have entered the transaction pool already,
 		// so it should be considered as valid
 		System::inc_account_nonce(&sender);
+		confidential_events::note_key_scheme(&sender, &self.signature);
 
+		let result = self.call.dispatch_checked(mrenclave, shard, calls, node_metadata_repo);
+		if let Err(e) = &result {
+			confidential_events::deposit_event(
+				&sender,
+				ConfidentialEvent::CallFailed { nonce: self.nonce, reason: format!("{:?}", e) },
+			);
+		}
+		rent::update_storage_deposit(&sender);
+		result
+	}
+
+	fn get_storage_hashes_to_update(self) -> Vec<Vec<u8>> {
+		let key_hashes = Vec::new();
 		match self.call {
+			TrustedCall::noop(_) => debug!("No storage updates needed..."),
+			TrustedCall::balance_set_balance(_, _, _, _) => debug!("No storage updates needed..."),
+			TrustedCall::balance_transfer(_, _, _) => debug!("No storage updates needed..."),
+			TrustedCall::balance_unshield(_, _, _, _) => debug!("No storage updates needed..."),
+			TrustedCall::balance_shield(_, _, _) => debug!("No storage updates needed..."),
+			TrustedCall::schedule_call(..) => debug!("No storage updates needed..."),
+			TrustedCall::reap_accounts(_) => debug!("No storage updates needed..."),
+			TrustedCall::balance_unshield_batched(..) => debug!("No storage updates needed..."),
+			TrustedCall::payout_unshield_batch(_) => debug!("No storage updates needed..."),
+			TrustedCall::confirm_unshield_payouts(..) => debug!("No storage updates needed..."),
+			TrustedCall::assets_shield(..) => debug!("No storage updates needed..."),
+			TrustedCall::assets_transfer(..) => debug!("No storage updates needed..."),
+			TrustedCall::assets_unshield(..) => debug!("No storage updates needed..."),
+			TrustedCall::shard_acl_set_policy(..) => debug!("No storage updates needed..."),
+			TrustedCall::shard_acl_set_member(..) => debug!("No storage updates needed..."),
+			TrustedCall::shard_acl_set_maintenance_mode(..) =>
+				debug!("No storage updates needed..."),
+			TrustedCall::session_key_register(..) => debug!("No storage updates needed..."),
+			TrustedCall::session_key_revoke(..) => debug!("No storage updates needed..."),
+			TrustedCall::session_call(..) => debug!("No storage updates needed..."),
+			TrustedCall::sponsored_call(..) => debug!("No storage updates needed..."),
+			TrustedCall::fees_set_config(..) => debug!("No storage updates needed..."),
+			TrustedCall::fees_set_multiplier(..) => debug!("No storage updates needed..."),
+			TrustedCall::cross_shard_transfer(..) => debug!("No storage updates needed..."),
+			TrustedCall::cross_shard_deliver(..) => debug!("No storage updates needed..."),
+			TrustedCall::cross_shard_commit_outbox(..) => debug!("No storage updates needed..."),
+			TrustedCall::cross_shard_anchor_commitment(..) =>
+				debug!("No storage updates needed..."),
+			TrustedCall::reserves_attest(..) => debug!("No storage updates needed..."),
+			#[cfg(feature = "evm")]
+			_ => debug!("No storage updates needed..."),
+		};
+		key_hashes
+	}
+}
+
+impl TrustedCall {
+	/// Calls that can't be scheduled. A sponsored call carries a signature that is only verified
+	/// when it is submitted to the pool, so it must not be stored for later.
+	fn is_scheduler_call(&self) -> bool {
+		matches!(
+			self,
+			Self::schedule_call(..)
+				| Self::reap_accounts(..)
+				| Self::payout_unshield_batch(..)
+				| Self::sponsored_call(..)
+		)
+	}
+
+	/// Whether a session key may submit the call on behalf of its owner. Session keys can't
+	/// manage session keys, nor schedule calls that would outlive the session, nor relay calls
+	/// signed by other accounts.
+	fn is_delegable(&self) -> bool {
+		!self.is_scheduler_call()
+			&& !matches!(
+				self,
+				Self::session_key_register(..)
+					| Self::session_key_revoke(..)
+					| Self::session_call(..)
+					| Self::sponsored_call(..)
+			)
+	}
+
+	/// Dispatches the call on behalf of its sender after checking the shard access and charging
+	/// the fee, without any signature or nonce checks.
+	///
+	/// The access is re-checked because it might have changed since the call entered the pool
+	/// or was scheduled.
+	pub(crate) fn dispatch_checked<NodeMetadataRepository>(
+		self,
+		mrenclave: &[u8; 32],
+		shard: &ShardIdentifier,
+		calls: &mut Vec<OpaqueCall>,
+		node_metadata_repo: Arc<NodeMetadataRepository>,
+	) -> Result<(), StfError>
+	where
+		NodeMetadataRepository: AccessNodeMetadata,
+		NodeMetadataRepository::MetadataType: NodeMetadataTrait,
+	{
+		shard_acl::ensure_permitted(self.sender_account())
+			.and_then(|_| fees::charge_fee(&self))
+			.and_then(|_| self.dispatch(mrenclave, shard, calls, node_metadata_repo))
+	}
+
+	/// Dispatches the call without any signature or nonce checks of its sender. The signatures
+	/// of the calls it carries are verified for `mrenclave` and `shard`.
+	fn dispatch<NodeMetadataRepository>(
+		self,
+		mrenclave: &[u8; 32],
+		shard: &ShardIdentifier,
+		calls: &mut Vec<OpaqueCall>,
+		node_metadata_repo: Arc<NodeMetadataRepository>,
+	) -> Result<(), StfError>
+	where
+		NodeMetadataRepository: AccessNodeMetadata,
+		NodeMetadataRepository::MetadataType: NodeMetadataTrait,
+	{
+		let call_hash = blake2_256(&self.encode());
+		match self {
 			TrustedCall::noop(who) => {
 				debug!("noop called by {}", account_id_to_string(&who),);
-				Ok::<(), Self::Error>(())
+				Ok::<(), StfError>(())
 			},
 			TrustedCall::balance_set_balance(root, who, free_balance, reserved_balance) => {
-				ensure!(is_root::<Runtime, AccountId>(&root), Self::Error::MissingPrivileges(root));
+				ensure!(is_root::<Runtime, AccountId>(&root), StfError::MissingPrivileges(root));
 				debug!(
 					"balance_set_balance({}, {}, {})",
 					account_id_to_string(&who),
@@ -246,7 +514,7 @@ where
 				}
 				.dispatch_bypass_filter(ita_sgx_runtime::RuntimeOrigin::root())
 				.map_err(|e| {
-					Self::Error::Dispatch(format!("Balance Set Balance error: {:?}", e.error))
+					StfError::Dispatch(format!("Balance Set Balance error: {:?}", e.error))
 				})?;
 				// This explicit Error type is somehow still needed, otherwise the compiler complains
 				// 	multiple `impl`s satisfying `StfError: std::convert::From<_>`
@@ -255,7 +523,7 @@ where
 				//
 				// Alternatively, removing the customised "impl From<..> for StfError" and use map_err directly
 				// would also work
-				Ok::<(), Self::Error>(())
+				Ok::<(), StfError>(())
 			},
 			TrustedCall::balance_transfer(from, to, value) => {
 				let origin = ita_sgx_runtime::RuntimeOrigin::signed(from.clone());
@@ -266,13 +534,21 @@ where
 					value
 				);
 				ita_sgx_runtime::BalancesCall::<Runtime>::transfer {
-					dest: MultiAddress::Id(to),
+					dest: MultiAddress::Id(to.clone()),
 					value,
 				}
 				.dispatch_bypass_filter(origin)
 				.map_err(|e| {
-					Self::Error::Dispatch(format!("Balance Transfer error: {:?}", e.error))
+					StfError::Dispatch(format!("Balance Transfer error: {:?}", e.error))
 				})?;
+				confidential_events::deposit_event(
+					&from,
+					ConfidentialEvent::BalanceSent { to: to.clone(), amount: value },
+				);
+				confidential_events::deposit_event(
+					&to,
+					ConfidentialEvent::BalanceReceived { from, amount: value },
+				);
 				Ok(())
 			},
 			TrustedCall::balance_unshield(account_incognito, beneficiary, value, shard) => {
@@ -283,7 +559,14 @@ where
 					value,
 					shard
 				);
-				unshield_funds(account_incognito, value)?;
+				unshield_funds(account_incognito.clone(), value)?;
+				confidential_events::deposit_event(
+					&account_incognito,
+					ConfidentialEvent::Unshielded {
+						beneficiary: beneficiary.clone(),
+						amount: value,
+					},
+				);
 
 				calls.push(OpaqueCall::from_tuple(&(
 					node_metadata_repo
@@ -326,7 +609,11 @@ where
 			TrustedCall::balance_shield(enclave_account, who, value) => {
 				ensure_enclave_signer_account(&enclave_account)?;
 				debug!("balance_shield({}, {})", account_id_to_string(&who), value);
-				shield_funds(who, value)?;
+				shield_funds(who.clone(), value)?;
+				confidential_events::deposit_event(
+					&who,
+					ConfidentialEvent::Shielded { amount: value },
+				);
 
 				// Send proof of execution on chain.
 				calls.push(OpaqueCall::from_tuple(&(
@@ -340,13 +627,382 @@ where
 				)));
 				Ok(())
 			},
+			TrustedCall::schedule_call(who, when, call) => {
+				debug!("schedule_call({}, {:?})", account_id_to_string(&who), when);
+				ensure!(
+					call.sender_account() == &who,
+					StfError::Dispatch("scheduled call must have the same sender".into())
+				);
+				ensure!(
+					!call.is_scheduler_call(),
+					StfError::Dispatch("scheduler calls can't be scheduled".into())
+				);
+				scheduler::schedule(when, *call)
+			},
+			TrustedCall::reap_accounts(enclave_account) => {
+				ensure_enclave_signer_account(&enclave_account)?;
+				debug!("reap_accounts()");
+				rent::reap_accounts()
+			},
+			TrustedCall::balance_unshield_batched(account_incognito, beneficiary, value) => {
+				debug!(
+					"balance_unshield_batched({}, {}, {})",
+					account_id_to_string(&account_incognito),
+					account_id_to_string(&beneficiary),
+					value
+				);
+				unshielding::ensure_payout_capacity()?;
+				unshield_funds(account_incognito.clone(), value)?;
+				confidential_events::deposit_event(
+					&account_incognito,
+					ConfidentialEvent::Unshielded {
+						beneficiary: beneficiary.clone(),
+						amount: value,
+					},
+				);
+				unshielding::queue_payout(beneficiary, value)
+			},
+			TrustedCall::payout_unshield_batch(enclave_account) => {
+				ensure_enclave_signer_account(&enclave_account)?;
+				let batch = match unshielding::take_payout_batch() {
+					Some(batch) => batch,
+					None => return Ok(()),
+				};
+				debug!(
+					"payout_unshield_batch() paying out {} request(s) with nonce {}",
+					batch.payouts.len(),
+					batch.nonce
+				);
+				let vault_pubkey: [u8; 32] = get_storage_by_key_hash(SHARD_VAULT_KEY.into())
+					.ok_or_else(|| {
+						StfError::Dispatch("shard vault key hasn't been set".to_string())
+					})?;
+				let transfer_call_indexes = node_metadata_repo
+					.get_from_metadata(|m| m.transfer_keep_alive_call_indexes())
+					.map_err(|_| StfError::InvalidMetadata)?
+					.map_err(|_| StfError::InvalidMetadata)?;
+				let remark_call_indexes = node_metadata_repo
+					.get_from_metadata(|m| m.remark_with_event_call_indexes())
+					.map_err(|_| StfError::InvalidMetadata)?
+					.map_err(|_| StfError::InvalidMetadata)?;
+				let batch_all_call_indexes = node_metadata_repo
+					.get_from_metadata(|m| m.batch_all_call_indexes())
+					.map_err(|_| StfError::InvalidMetadata)?
+					.map_err(|_| StfError::InvalidMetadata)?;
+				let remark_call = |remark: unshielding::PayoutRemark| {
+					OpaqueCall::from_tuple(&(remark_call_indexes, remark.remark()))
+				};
+				let payout_calls: Vec<OpaqueCall> =
+					[remark_call(unshielding::PayoutRemark::Batch(batch.nonce))]
+						.into_iter()
+						.chain(batch.payouts.into_iter().map(|payout| {
+							// `batch_all`, such that the remark is only stored with the transfer.
+							OpaqueCall::from_tuple(&(
+								batch_all_call_indexes,
+								vec![
+									OpaqueCall::from_tuple(&(
+										transfer_call_indexes,
+										Address::from(payout.beneficiary),
+										Compact(payout.amount),
+									)),
+									remark_call(unshielding::PayoutRemark::Payout(payout.id)),
+								],
+							))
+						}))
+						.collect();
+				// `force_batch`, such that a single failing transfer doesn't revert the others.
+				let batch_call = OpaqueCall::from_tuple(&(
+					node_metadata_repo
+						.get_from_metadata(|m| m.force_batch_call_indexes())
+						.map_err(|_| StfError::InvalidMetadata)?
+						.map_err(|_| StfError::InvalidMetadata)?,
+					payout_calls,
+				));
+				// Signed by the enclave with the payout nonce, see [unshielding].
+				unshielding::set_outstanding_batch_call(&OpaqueCall::from_tuple(&(
+					node_metadata_repo
+						.get_from_metadata(|m| m.proxy_call_indexes())
+						.map_err(|_| StfError::InvalidMetadata)?
+						.map_err(|_| StfError::InvalidMetadata)?,
+					Address::from(AccountId::from(vault_pubkey)),
+					None::<ProxyType>,
+					batch_call,
+				)));
+				Ok(())
+			},
+			TrustedCall::confirm_unshield_payouts(enclave_account, remarks) => {
+				ensure_enclave_signer_account(&enclave_account)?;
+				let confirmed = unshielding::confirm_payouts(remarks);
+				debug!("confirm_unshield_payouts() confirmed {} payout(s)", confirmed);
+				Ok(())
+			},
+			TrustedCall::assets_shield(enclave_account, who, asset_id, value) => {
+				ensure_enclave_signer_account(&enclave_account)?;
+				debug!("assets_shield({}, {}, {})", account_id_to_string(&who), asset_id, value);
+				assets::mint(&asset_id, &who, value)?;
+
+				// Send proof of execution on chain.
+				calls.push(OpaqueCall::from_tuple(&(
+					node_metadata_repo
+						.get_from_metadata(|m| m.publish_hash_call_indexes())
+						.map_err(|_| StfError::InvalidMetadata)?
+						.map_err(|_| StfError::InvalidMetadata)?,
+					call_hash,
+					Vec::<itp_types::H256>::new(),
+					b"shielded some assets!".to_vec(),
+				)));
+				Ok(())
+			},
+			TrustedCall::assets_transfer(from, to, asset_id, value) => {
+				debug!(
+					"assets_transfer({}, {}, {}, {})",
+					account_id_to_string(&from),
+					account_id_to_string(&to),
+					asset_id,
+					value
+				);
+				assets::transfer(&asset_id, &from, &to, value)?;
+				confidential_events::deposit_event(
+					&from,
+					ConfidentialEvent::AssetSent { to: to.clone(), asset: asset_id, amount: value },
+				);
+				confidential_events::deposit_event(
+					&to,
+					ConfidentialEvent::AssetReceived { from, asset: asset_id, amount: value },
+				);
+				Ok(())
+			},
+			TrustedCall::assets_unshield(account_incognito, beneficiary, asset_id, value) => {
+				debug!(
+					"assets_unshield({}, {}, {}, {})",
+					account_id_to_string(&account_incognito),
+					account_id_to_string(&beneficiary),
+					asset_id,
+					value
+				);
+				assets::burn(&asset_id, &account_incognito, value)?;
+
+				let vault_pubkey: [u8; 32] = get_storage_by_key_hash(SHARD_VAULT_KEY.into())
+					.ok_or_else(|| {
+						StfError::Dispatch("shard vault key hasn't been set".to_string())
+					})?;
+				let vault_transfer_call = match asset_id {
+					ParentchainAssetId::Assets(id) => OpaqueCall::from_tuple(&(
+						node_metadata_repo
+							.get_from_metadata(|m| m.assets_transfer_keep_alive_call_indexes())
+							.map_err(|_| StfError::InvalidMetadata)?
+							.map_err(|_| StfError::InvalidMetadata)?,
+						Compact(id),
+						Address::from(beneficiary),
+						Compact(value),
+					)),
+					ParentchainAssetId::Tokens(currency_id) => OpaqueCall::from_tuple(&(
+						node_metadata_repo
+							.get_from_metadata(|m| m.tokens_transfer_keep_alive_call_indexes())
+							.map_err(|_| StfError::InvalidMetadata)?
+							.map_err(|_| StfError::InvalidMetadata)?,
+						Address::from(beneficiary),
+						currency_id,
+						Compact(value),
+					)),
+				};
+				calls.push(OpaqueCall::from_tuple(&(
+					node_metadata_repo
+						.get_from_metadata(|m| m.proxy_call_indexes())
+						.map_err(|_| StfError::InvalidMetadata)?
+						.map_err(|_| StfError::InvalidMetadata)?,
+					Address::from(AccountId::from(vault_pubkey)),
+					None::<ProxyType>,
+					vault_transfer_call,
+				)));
+				Ok(())
+			},
+			TrustedCall::shard_acl_set_policy(root, policy) => {
+				ensure!(is_root::<Runtime, AccountId>(&root), StfError::MissingPrivileges(root));
+				shard_acl::set_access_policy(policy);
+				Ok(())
+			},
+			TrustedCall::shard_acl_set_member(root, who, roles) => {
+				ensure!(is_root::<Runtime, AccountId>(&root), StfError::MissingPrivileges(root));
+				shard_acl::set_member(&who, roles);
+				Ok(())
+			},
+			TrustedCall::shard_acl_set_maintenance_mode(root, enabled) => {
+				ensure!(is_root::<Runtime, AccountId>(&root), StfError::MissingPrivileges(root));
+				shard_acl::set_maintenance_mode(enabled);
+				Ok(())
+			},
+			TrustedCall::session_key_register(owner, delegate, session_key) =>
+				session_keys::register(&owner, &delegate, session_key),
+			TrustedCall::session_key_revoke(owner, delegate) => {
+				session_keys::revoke(&owner, &delegate);
+				Ok(())
+			},
+			TrustedCall::session_call(delegate, call) => {
+				let owner = call.sender_account().clone();
+				debug!(
+					"session_call({}, {}) on behalf of {}",
+					account_id_to_string(&delegate),
+					call.name(),
+					account_id_to_string(&owner)
+				);
+				ensure!(
+					call.is_delegable(),
+					StfError::Dispatch(format!(
+						"{} can't be submitted by a session key",
+						call.name()
+					))
+				);
+				session_keys::ensure_permitted(&owner, &delegate, call.name())?;
+				// Both the session key and its owner must have access to the shard.
+				shard_acl::ensure_permitted(&delegate)?;
+				shard_acl::ensure_permitted(&owner)?;
+				(*call).dispatch(mrenclave, shard, calls, node_metadata_repo)?;
+				rent::update_storage_deposit(&owner);
+				Ok(())
+			},
+			TrustedCall::fees_set_config(root, config) => {
+				ensure!(is_root::<Runtime, AccountId>(&root), StfError::MissingPrivileges(root));
+				fees::set_fee_config(config);
+				Ok(())
+			},
+			TrustedCall::fees_set_multiplier(root, multiplier) => {
+				ensure!(is_root::<Runtime, AccountId>(&root), StfError::MissingPrivileges(root));
+				fees::set_fee_multiplier(multiplier);
+				Ok(())
+			},
+			TrustedCall::cross_shard_transfer(from, destination, to, value) => {
+				debug!(
+					"cross_shard_transfer({}, {:?}, {}, {})",
+					account_id_to_string(&from),
+					destination,
+					account_id_to_string(&to),
+					value
+				);
+				unshield_funds(from.clone(), value)?;
+				let message = cross_shard::send(
+					destination,
+					from.clone(),
+					CrossShardPayload::Transfer { to: to.clone(), amount: value },
+				);
+				confidential_events::deposit_event(
+					&from,
+					ConfidentialEvent::CrossShardSent {
+						destination,
+						nonce: message.nonce,
+						to,
+						amount: value,
+					},
+				);
+				Ok(())
+			},
+			TrustedCall::cross_shard_deliver(enclave_account, source, message) => {
+				ensure_enclave_signer_account(&enclave_account)?;
+				cross_shard::receive(&source, &message)?;
+				match message.payload {
+					CrossShardPayload::Transfer { to, amount } => {
+						debug!(
+							"cross_shard_deliver transfer of {} to {}",
+							amount,
+							account_id_to_string(&to)
+						);
+						shield_funds(to.clone(), amount)?;
+						confidential_events::deposit_event(
+							&to,
+							ConfidentialEvent::CrossShardReceived {
+								source,
+								nonce: message.nonce,
+								from: message.sender,
+								amount,
+							},
+						);
+					},
+				}
+				Ok(())
+			},
+			TrustedCall::cross_shard_commit_outbox(enclave_account, shard) => {
+				ensure_enclave_signer_account(&enclave_account)?;
+				let commitment = match cross_shard::commit_outbox(shard) {
+					Some(commitment) => commitment,
+					None => return Ok(()),
+				};
+				// Publish the commitment, such that the workers of the destination shards can
+				// anchor it.
+				calls.push(OpaqueCall::from_tuple(&(
+					node_metadata_repo
+						.get_from_metadata(|m| m.publish_hash_call_indexes())
+						.map_err(|_| StfError::InvalidMetadata)?
+						.map_err(|_| StfError::InvalidMetadata)?,
+					commitment.root,
+					vec![shard],
+					commitment.encode(),
+				)));
+				Ok(())
+			},
+			TrustedCall::cross_shard_anchor_commitment(enclave_account, anchored) => {
+				ensure_enclave_signer_account(&enclave_account)?;
+				cross_shard::anchor(&anchored);
+				Ok(())
+			},
+			TrustedCall::reserves_attest(enclave_account, shard) => {
+				ensure_enclave_signer_account(&enclave_account)?;
+				let report = match reserves::attest(shard)? {
+					Some(report) => report,
+					None => return Ok(()),
+				};
+				// Publish the report, such that anyone can compare it to the vault balance.
+				calls.push(OpaqueCall::from_tuple(&(
+					node_metadata_repo
+						.get_from_metadata(|m| m.publish_hash_call_indexes())
+						.map_err(|_| StfError::InvalidMetadata)?
+						.map_err(|_| StfError::InvalidMetadata)?,
+					report.hash(),
+					vec![shard],
+					report.encode(),
+				)));
+				Ok(())
+			},
+			TrustedCall::sponsored_call(sponsor, inner) => {
+				// The signature is verified here too, the sponsored call might not have been
+				// submitted directly.
+				ensure!(
+					inner.verify_signature(mrenclave, shard),
+					StfError::InvalidSignature(inner.call.sender_account().clone())
+				);
+				let TrustedCallSigned { call, nonce, .. } = *inner;
+				let user = call.sender_account().clone();
+				debug!(
+					"sponsored_call({}, {}) of {}",
+					account_id_to_string(&sponsor),
+					call.name(),
+					account_id_to_string(&user)
+				);
+				ensure!(
+					!matches!(call, TrustedCall::sponsored_call(..)),
+					StfError::Dispatch("sponsored calls can't be sponsored".into())
+				);
+				let user_nonce = System::account_nonce(&user);
+				ensure!(nonce == user_nonce, StfError::InvalidNonce(nonce, user_nonce));
+				System::inc_account_nonce(&user);
+
+				let result = shard_acl::ensure_permitted(&user)
+					.and_then(|_| call.dispatch(mrenclave, shard, calls, node_metadata_repo));
+				if let Err(e) = &result {
+					confidential_events::deposit_event(
+						&user,
+						ConfidentialEvent::CallFailed { nonce, reason: format!("{:?}", e) },
+					);
+				}
+				rent::update_storage_deposit(&user);
+				result
+			},
 			#[cfg(feature = "evm")]
 			TrustedCall::evm_withdraw(from, address, value) => {
 				debug!("evm_withdraw({}, {}, {})", account_id_to_string(&from), address, value);
 				ita_sgx_runtime::EvmCall::<Runtime>::withdraw { address, value }
 					.dispatch_bypass_filter(ita_sgx_runtime::RuntimeOrigin::signed(from))
 					.map_err(|e| {
-						Self::Error::Dispatch(format!("Evm Withdraw error: {:?}", e.error))
+						StfError::Dispatch(format!("Evm Withdraw error: {:?}", e.error))
 					})?;
 				Ok(())
 			},
@@ -381,7 +1037,7 @@ where
 					access_list,
 				}
 				.dispatch_bypass_filter(ita_sgx_runtime::RuntimeOrigin::signed(from))
-				.map_err(|e| Self::Error::Dispatch(format!("Evm Call error: {:?}", e.error)))?;
+				.map_err(|e| StfError::Dispatch(format!("Evm Call error: {:?}", e.error)))?;
 				Ok(())
 			},
 			#[cfg(feature = "evm")]
@@ -415,7 +1071,7 @@ where
 					access_list,
 				}
 				.dispatch_bypass_filter(ita_sgx_runtime::RuntimeOrigin::signed(from))
-				.map_err(|e| Self::Error::Dispatch(format!("Evm Create error: {:?}", e.error)))?;
+				.map_err(|e| StfError::Dispatch(format!("Evm Create error: {:?}", e.error)))?;
 				let contract_address = evm_create_address(source, nonce_evm_account);
 				info!("Trying to create evm contract with address {:?}", contract_address);
 				Ok(())
@@ -452,27 +1108,12 @@ where
 					access_list,
 				}
 				.dispatch_bypass_filter(ita_sgx_runtime::RuntimeOrigin::signed(from))
-				.map_err(|e| Self::Error::Dispatch(format!("Evm Create2 error: {:?}", e.error)))?;
+				.map_err(|e| StfError::Dispatch(format!("Evm Create2 error: {:?}", e.error)))?;
 				let contract_address = evm_create2_address(source, salt, code_hash);
 				info!("Trying to create evm contract with address {:?}", contract_address);
 				Ok(())
 			},
-		}?;
-		Ok(())
-	}
-
-	fn get_storage_hashes_to_update(self) -> Vec<Vec<u8>> {
-		let key_hashes = Vec::new();
-		match self.call {
-			TrustedCall::noop(_) => debug!("No storage updates needed..."),
-			TrustedCall::balance_set_balance(_, _, _, _) => debug!("No storage updates needed..."),
-			TrustedCall::balance_transfer(_, _, _) => debug!("No storage updates needed..."),
-			TrustedCall::balance_unshield(_, _, _, _) => debug!("No storage updates needed..."),
-			TrustedCall::balance_shield(_, _, _) => debug!("No storage updates needed..."),
-			#[cfg(feature = "evm")]
-			_ => debug!("No storage updates needed..."),
-		};
-		key_hashes
+		}
 	}
 }
 
@@ -538,4 +1179,319 @@ mod tests {
 
 		assert!(signed_call.verify_signature(&mrenclave, &shard));
 	}
+
+	#[test]
+	fn externally_signed_payload_verifies() {
+		let nonce = 3;
+		let mrenclave = [1u8; 32];
+		let shard = ShardIdentifier::default();
+
+		let call = TrustedCall::balance_transfer(
+			AccountKeyring::Alice.public().into(),
+			AccountKeyring::Bob.public().into(),
+			42,
+		);
+		let payload = call.signing_payload(nonce, &mrenclave, &shard);
+		let signature = KeyPair::Sr25519(Box::new(AccountKeyring::Alice.pair())).sign(&payload);
+		let signed_call = call.with_signature(nonce, signature);
+
+		assert!(signed_call.verify_signature(&mrenclave, &shard));
+	}
+
+	fn sponsored_transfer(user: &KeyPair, sponsor: &KeyPair) -> TrustedCallSigned {
+		let (mrenclave, shard) = ([1u8; 32], ShardIdentifier::default());
+		let transfer = TrustedCall::balance_transfer(
+			AccountKeyring::Alice.public().into(),
+			AccountKeyring::Bob.public().into(),
+			42,
+		)
+		.sign(user, 7, &mrenclave, &shard);
+		TrustedCall::sponsored_call(AccountKeyring::Charlie.public().into(), Box::new(transfer))
+			.sign(sponsor, 3, &mrenclave, &shard)
+	}
+
+	#[test]
+	fn sponsored_call_requires_signatures_of_sponsor_and_user() {
+		let (mrenclave, shard) = ([1u8; 32], ShardIdentifier::default());
+		let alice = KeyPair::Sr25519(Box::new(AccountKeyring::Alice.pair()));
+		let charlie = KeyPair::Sr25519(Box::new(AccountKeyring::Charlie.pair()));
+
+		assert!(sponsored_transfer(&alice, &charlie).verify_signature(&mrenclave, &shard));
+		assert!(!sponsored_transfer(&charlie, &charlie).verify_signature(&mrenclave, &shard));
+		assert!(!sponsored_transfer(&alice, &alice).verify_signature(&mrenclave, &shard));
+	}
+
+	#[test]
+	fn sponsored_call_consumes_nonces_of_sponsor_and_user() {
+		let alice = KeyPair::Sr25519(Box::new(AccountKeyring::Alice.pair()));
+		let charlie = KeyPair::Sr25519(Box::new(AccountKeyring::Charlie.pair()));
+		let signed_call = sponsored_transfer(&alice, &charlie);
+
+		assert_eq!(
+			signed_call.nonces(),
+			vec![
+				(AccountKeyring::Charlie.public().into(), 3),
+				(AccountKeyring::Alice.public().into(), 7)
+			]
+		);
+		assert_eq!(signed_call.call.fee_payer(), &AccountKeyring::Charlie.public().into());
+	}
+
+	/// The client SDK has its own copies of the call and getter types, see `ita_client_sdk::types`.
+	fn decode_client_sdk<T: Encode, S: Decode>(value: &T) -> S {
+		S::decode(&mut value.encode().as_slice()).unwrap()
+	}
+
+	#[test]
+	fn client_sdk_calls_decode_to_the_same_calls() {
+		let builder =
+			ita_client_sdk::TrustedCallBuilder::new([2u8; 32], ShardIdentifier::default());
+		let (alice, bob): (AccountId, AccountId) =
+			(AccountKeyring::Alice.public().into(), AccountKeyring::Bob.public().into());
+		let asset = ParentchainAssetId::Assets(7);
+		let session_key = SessionKey::new(1_000, vec![b"balance_transfer".to_vec()]);
+		let transfer = TrustedCall::balance_transfer(alice.clone(), bob.clone(), 42);
+		let sdk_transfer = builder.balance_transfer(alice.clone(), bob.clone(), 42).call().clone();
+		let signed_transfer = builder
+			.balance_transfer(alice.clone(), bob.clone(), 42)
+			.sign(&KeyPair::Sr25519(Box::new(AccountKeyring::Alice.pair())));
+
+		let calls = vec![
+			(sdk_transfer.clone(), transfer.clone()),
+			(
+				builder.balance_unshield(alice.clone(), bob.clone(), 42).call().clone(),
+				TrustedCall::balance_unshield(alice.clone(), bob.clone(), 42, Default::default()),
+			),
+			(
+				builder.balance_unshield_batched(alice.clone(), bob.clone(), 42).call().clone(),
+				TrustedCall::balance_unshield_batched(alice.clone(), bob.clone(), 42),
+			),
+			(
+				builder.assets_transfer(alice.clone(), bob.clone(), asset, 42).call().clone(),
+				TrustedCall::assets_transfer(alice.clone(), bob.clone(), asset, 42),
+			),
+			(
+				builder.assets_unshield(alice.clone(), bob.clone(), asset, 42).call().clone(),
+				TrustedCall::assets_unshield(alice.clone(), bob.clone(), asset, 42),
+			),
+			(
+				builder
+					.session_key_register(alice.clone(), bob.clone(), 1_000, &["balance_transfer"])
+					.call()
+					.clone(),
+				TrustedCall::session_key_register(alice.clone(), bob.clone(), session_key),
+			),
+			(
+				builder.session_key_revoke(alice.clone(), bob.clone()).call().clone(),
+				TrustedCall::session_key_revoke(alice.clone(), bob.clone()),
+			),
+			(
+				builder.session_call(bob.clone(), sdk_transfer).call().clone(),
+				TrustedCall::session_call(bob.clone(), Box::new(transfer)),
+			),
+			(
+				builder.sponsored_call(bob.clone(), signed_transfer.clone()).call().clone(),
+				TrustedCall::sponsored_call(bob, Box::new(decode_client_sdk(&signed_transfer))),
+			),
+		];
+
+		for (sdk_call, call) in calls {
+			assert_eq!(decode_client_sdk::<_, TrustedCall>(&sdk_call), call);
+			assert_eq!(sdk_call.name(), call.name());
+		}
+	}
+
+	#[test]
+	fn client_sdk_signed_call_verifies() {
+		let (mrenclave, shard) = ([2u8; 32], ShardIdentifier::repeat_byte(1));
+		let alice = KeyPair::Sr25519(Box::new(AccountKeyring::Alice.pair()));
+
+		let unsigned_call = ita_client_sdk::TrustedCallBuilder::new(mrenclave, shard)
+			.nonce(5)
+			.balance_transfer(
+				AccountKeyring::Alice.public().into(),
+				AccountKeyring::Bob.public().into(),
+				1000,
+			);
+		let signature = alice.sign(&unsigned_call.signing_payload());
+		let signed_call: TrustedCallSigned =
+			decode_client_sdk(&unsigned_call.with_signature(signature));
+
+		assert_eq!(signed_call.nonce, 5);
+		assert!(signed_call.verify_signature(&mrenclave, &shard));
+	}
+
+	#[test]
+	fn client_sdk_session_call_is_signed_by_the_session_key() {
+		let (mrenclave, shard) = ([2u8; 32], ShardIdentifier::repeat_byte(1));
+		let builder = ita_client_sdk::TrustedCallBuilder::new(mrenclave, shard);
+		let transfer = builder
+			.balance_transfer(
+				AccountKeyring::Alice.public().into(),
+				AccountKeyring::Bob.public().into(),
+				1000,
+			)
+			.call()
+			.clone();
+		let session_call = builder.session_call(AccountKeyring::Dave.public().into(), transfer);
+		let dave = KeyPair::Sr25519(Box::new(AccountKeyring::Dave.pair()));
+		let alice = KeyPair::Sr25519(Box::new(AccountKeyring::Alice.pair()));
+
+		let signed_by_dave: TrustedCallSigned = decode_client_sdk(&session_call.sign(&dave));
+		let signed_by_alice: TrustedCallSigned = decode_client_sdk(&session_call.sign(&alice));
+
+		assert!(signed_by_dave.verify_signature(&mrenclave, &shard));
+		assert!(!signed_by_alice.verify_signature(&mrenclave, &shard));
+	}
+
+	#[test]
+	fn client_sdk_sponsored_call_is_signed_by_sponsor_and_sender() {
+		let (mrenclave, shard) = ([2u8; 32], ShardIdentifier::repeat_byte(1));
+		let alice = KeyPair::Sr25519(Box::new(AccountKeyring::Alice.pair()));
+		let charlie = KeyPair::Sr25519(Box::new(AccountKeyring::Charlie.pair()));
+
+		let transfer = ita_client_sdk::TrustedCallBuilder::new(mrenclave, shard)
+			.balance_transfer(
+				AccountKeyring::Alice.public().into(),
+				AccountKeyring::Bob.public().into(),
+				1000,
+			)
+			.sign(&alice);
+		let sponsored_call: TrustedCallSigned = decode_client_sdk(
+			&ita_client_sdk::TrustedCallBuilder::new(mrenclave, shard)
+				.nonce(3)
+				.sponsored_call(AccountKeyring::Charlie.public().into(), transfer)
+				.sign(&charlie),
+		);
+
+		assert!(sponsored_call.verify_signature(&mrenclave, &shard));
+		assert_eq!(sponsored_call.nonces().len(), 2);
+	}
+
+	#[test]
+	fn client_sdk_getters_decode_to_the_same_getters() {
+		use crate::getter::{PublicGetter, TrustedGetter, DEFAULT_GETTER_VALIDITY};
+		use itp_stf_primitives::pagination::PageRequest;
+
+		let builder = ita_client_sdk::GetterBuilder::new(1_000);
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+		let asset = ParentchainAssetId::Tokens(7);
+		let request = PageRequest { cursor: None, limit: 10 };
+
+		let getters = vec![
+			(builder.free_balance(alice.clone()), TrustedGetter::free_balance(alice.clone())),
+			(
+				builder.reserved_balance(alice.clone()),
+				TrustedGetter::reserved_balance(alice.clone()),
+			),
+			(builder.nonce(alice.clone()), TrustedGetter::nonce(alice.clone())),
+			(builder.rent_status(alice.clone()), TrustedGetter::rent_status(alice.clone())),
+			(
+				builder.asset_balance(alice.clone(), asset),
+				TrustedGetter::asset_balance(alice.clone(), asset),
+			),
+			(
+				builder.confidential_events(alice.clone()),
+				TrustedGetter::confidential_events(alice.clone()),
+			),
+			(
+				builder.confidential_events_page(alice.clone(), request),
+				TrustedGetter::confidential_events_page(alice.clone(), request),
+			),
+			(
+				builder.audit_log(alice.clone(), request),
+				TrustedGetter::audit_log(alice.clone(), request),
+			),
+			(
+				builder.account_data_export(alice.clone(), vec![1, 2, 3]),
+				TrustedGetter::account_data_export(alice, vec![1, 2, 3]),
+			),
+		];
+		for (sdk_getter, getter) in getters {
+			assert_eq!(sdk_getter.signing_payload(), getter.signing_payload(1_000));
+		}
+
+		let transfer =
+			ita_client_sdk::TrustedCallBuilder::new([2u8; 32], ShardIdentifier::default())
+				.balance_transfer(
+					AccountKeyring::Alice.public().into(),
+					AccountKeyring::Bob.public().into(),
+					42,
+				);
+		let public_getters = vec![
+			(
+				ita_client_sdk::GetterBuilder::public(ita_client_sdk::PublicGetter::some_value),
+				PublicGetter::some_value,
+			),
+			(ita_client_sdk::GetterBuilder::fee_config(), PublicGetter::fee_config),
+			(
+				ita_client_sdk::GetterBuilder::fee_estimate(&transfer),
+				PublicGetter::fee_estimate(decode_client_sdk(transfer.call())),
+			),
+			(ita_client_sdk::GetterBuilder::reserves_report(), PublicGetter::reserves_report),
+		];
+		for (sdk_getter, getter) in public_getters {
+			assert_eq!(decode_client_sdk::<_, Getter>(&sdk_getter), Getter::public(getter));
+		}
+
+		assert_eq!(ita_client_sdk::DEFAULT_GETTER_VALIDITY, DEFAULT_GETTER_VALIDITY);
+	}
+
+	#[test]
+	fn client_sdk_signed_getter_is_authorized_until_it_expires() {
+		use itp_stf_primitives::traits::{GetterAuthorization, GetterExpiry};
+		let alice = KeyPair::Sr25519(Box::new(AccountKeyring::Alice.pair()));
+
+		let unsigned_getter =
+			ita_client_sdk::GetterBuilder::new(1_000).nonce(AccountKeyring::Alice.public().into());
+		let signature = alice.sign(&unsigned_getter.signing_payload());
+		let getter: Getter = decode_client_sdk(&unsigned_getter.with_signature(signature.clone()));
+		let extended_getter: Getter = decode_client_sdk(
+			&ita_client_sdk::GetterBuilder::new(2_000)
+				.nonce(AccountKeyring::Alice.public().into())
+				.with_signature(signature),
+		);
+
+		assert!(getter.is_authorized());
+		assert!(!getter.is_expired(1_000));
+		assert!(getter.is_expired(1_001));
+		assert!(!extended_getter.is_authorized());
+	}
+
+	#[cfg(feature = "evm")]
+	#[test]
+	fn eth_signed_call_of_mapped_account_verifies() {
+		use crate::evm_helpers::eth_address;
+		use sp_core::Pair;
+
+		let nonce = 1;
+		let mrenclave = [1u8; 32];
+		let shard = ShardIdentifier::default();
+		let eth_pair = ecdsa::Pair::from_seed(&[9u8; 32]);
+		let sender = eth_account_id(&eth_address(&eth_pair));
+
+		let call = TrustedCall::balance_transfer(sender, AccountKeyring::Bob.public().into(), 42);
+		let signed_call = call.sign_eth(&eth_pair, nonce, &mrenclave, &shard);
+
+		assert!(signed_call.verify_signature(&mrenclave, &shard));
+	}
+
+	#[cfg(feature = "evm")]
+	#[test]
+	fn eth_signed_call_of_other_account_fails() {
+		use sp_core::Pair;
+
+		let mrenclave = [1u8; 32];
+		let shard = ShardIdentifier::default();
+		let eth_pair = ecdsa::Pair::from_seed(&[9u8; 32]);
+
+		let call = TrustedCall::balance_transfer(
+			AccountKeyring::Alice.public().into(),
+			AccountKeyring::Bob.public().into(),
+			42,
+		);
+		let signed_call = call.sign_eth(&eth_pair, 1, &mrenclave, &shard);
+
+		assert!(!signed_call.verify_signature(&mrenclave, &shard));
+	}
 }
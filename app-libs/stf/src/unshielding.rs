@@ -0,0 +1,551 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Batched unshielding of the shard.
+//!
+//! Unshielding with [crate::TrustedCall::balance_unshield] transfers the funds from the shard
+//! vault right after the trusted call has been executed, which lets observers of the parentchain
+//! link the two by their timing. With [crate::TrustedCall::balance_unshield_batched] the funds
+//! are burnt in the shard and the payout is queued in the state instead. Every [PAYOUT_PERIOD]
+//! sidechain blocks, the enclave pays out all queued requests with a single extrinsic from the
+//! shard vault, see [crate::TrustedCall::payout_unshield_batch]. The order of the payouts within
+//! the batch is randomized with enclave randomness.
+//!
+//! Every payout has a unique [PayoutId], which is stored on the parentchain with a remark in the
+//! same `batch_all` as its transfer. The batch itself is marked with a remark of its
+//! [PayoutNonce], the account nonce of the payout signer, which signs nothing but payout batches.
+//! A batch stays outstanding until its remarks are observed on the parentchain and confirmed with
+//! [crate::TrustedCall::confirm_unshield_payouts]. Payouts whose remark is missing from an
+//! executed batch have failed and are queued again for the next batch.
+//!
+//! A batch that hasn't been confirmed within [PAYOUT_CONFIRMATION_TIMEOUT] blocks is sent again
+//! with the same nonce. Only one of the extrinsics can be included, so a late inclusion of the
+//! first one can't lead to a double payment. If the proxy call of an included batch fails as a
+//! whole, none of the remarks are stored and the batch stays outstanding; this needs manual
+//! intervention.
+
+use crate::{
+	helpers::{current_block_number, enclave_signer_account, get_storage_value},
+	Balance,
+};
+use codec::{Decode, Encode};
+use frame_support::traits::UnfilteredDispatchable;
+use ita_sgx_runtime::{Runtime, System};
+use itp_stf_primitives::{
+	account_export::{AccountDataEntry, AccountStorage},
+	error::{StfError, StfResult},
+	types::AccountId,
+};
+use itp_storage::storage_value_key;
+use itp_types::{OpaqueCall, SidechainBlockNumber};
+use log::*;
+use sp_core::H256;
+use sp_io::hashing::blake2_256;
+use sp_runtime::MultiAddress;
+use std::{collections::BTreeMap, format, prelude::v1::*};
+
+pub const UNSHIELDING_STORAGE_PREFIX: &str = "Unshielding";
+pub const PENDING_PAYOUTS_KEY: &str = "PendingPayouts";
+pub const LAST_PAYOUT_KEY: &str = "LastPayout";
+pub const NEXT_PAYOUT_ID_KEY: &str = "NextPayoutId";
+pub const PAYOUT_NONCE_KEY: &str = "PayoutNonce";
+pub const OUTSTANDING_BATCH_KEY: &str = "OutstandingBatch";
+pub const OUTSTANDING_BATCH_CALL_KEY: &str = "OutstandingBatchCall";
+
+/// Prefix of the remarks stored with the payouts, see [PayoutRemark].
+pub const PAYOUT_REMARK_PREFIX: &[u8] = b"unshield-payout";
+
+/// Number of sidechain blocks between two payout batches.
+pub const PAYOUT_PERIOD: SidechainBlockNumber = 100;
+
+/// Number of sidechain blocks after which an unconfirmed batch is sent again.
+pub const PAYOUT_CONFIRMATION_TIMEOUT: SidechainBlockNumber = 10 * PAYOUT_PERIOD;
+
+/// Maximum number of payouts that can be queued, which bounds the size of the payout extrinsic.
+pub const MAX_PENDING_PAYOUTS: usize = 256;
+
+/// Unique identifier of a payout within the shard.
+pub type PayoutId = u64;
+
+/// Parentchain account nonce of the payout signer.
+pub type PayoutNonce = u32;
+
+/// Unshielding request waiting for the next payout batch.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct PendingPayout {
+	pub id: PayoutId,
+	pub beneficiary: AccountId,
+	pub amount: Balance,
+}
+
+/// Batch that has been sent to the parentchain, but whose remarks haven't been observed yet.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct OutstandingBatch {
+	/// Nonce with which the batch is signed, also when it is sent again.
+	pub nonce: PayoutNonce,
+	/// Payouts of the batch that haven't been confirmed yet.
+	pub payouts: Vec<PendingPayout>,
+	/// Sidechain block in which the batch has been sent last.
+	pub sent_at: SidechainBlockNumber,
+}
+
+/// Remarks stored by the shard vault along with a payout batch.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub enum PayoutRemark {
+	/// Stored once the batch signed with the nonce has been executed.
+	Batch(PayoutNonce),
+	/// Stored in the same `batch_all` as the transfer of the payout.
+	Payout(PayoutId),
+}
+
+impl PayoutRemark {
+	pub fn remark(&self) -> Vec<u8> {
+		(PAYOUT_REMARK_PREFIX, self).encode()
+	}
+
+	/// Hash of the remark as deposited in the `System::Remarked` event.
+	pub fn hash(&self) -> H256 {
+		blake2_256(&self.remark()).into()
+	}
+}
+
+fn pending_payouts_key() -> Vec<u8> {
+	storage_value_key(UNSHIELDING_STORAGE_PREFIX, PENDING_PAYOUTS_KEY)
+}
+
+fn outstanding_batch_key() -> Vec<u8> {
+	storage_value_key(UNSHIELDING_STORAGE_PREFIX, OUTSTANDING_BATCH_KEY)
+}
+
+fn outstanding_batch_call_key() -> Vec<u8> {
+	storage_value_key(UNSHIELDING_STORAGE_PREFIX, OUTSTANDING_BATCH_CALL_KEY)
+}
+
+pub fn pending_payouts() -> Vec<PendingPayout> {
+	get_storage_value(UNSHIELDING_STORAGE_PREFIX, PENDING_PAYOUTS_KEY).unwrap_or_default()
+}
+
+pub fn outstanding_batch() -> Option<OutstandingBatch> {
+	get_storage_value(UNSHIELDING_STORAGE_PREFIX, OUTSTANDING_BATCH_KEY)
+}
+
+fn payout_nonce() -> PayoutNonce {
+	get_storage_value(UNSHIELDING_STORAGE_PREFIX, PAYOUT_NONCE_KEY).unwrap_or_default()
+}
+
+pub fn ensure_payout_capacity() -> StfResult<()> {
+	if pending_payouts().len() >= MAX_PENDING_PAYOUTS {
+		return Err(StfError::Dispatch("too many pending unshield payouts".into()))
+	}
+	Ok(())
+}
+
+/// Queues a payout for the next batch. The caller is responsible for burning the funds.
+pub fn queue_payout(beneficiary: AccountId, amount: Balance) -> StfResult<()> {
+	ensure_payout_capacity()?;
+	let id: PayoutId =
+		get_storage_value(UNSHIELDING_STORAGE_PREFIX, NEXT_PAYOUT_ID_KEY).unwrap_or_default();
+	sp_io::storage::set(
+		&storage_value_key(UNSHIELDING_STORAGE_PREFIX, NEXT_PAYOUT_ID_KEY),
+		&(id + 1).encode(),
+	);
+	let mut payouts = pending_payouts();
+	payouts.push(PendingPayout { id, beneficiary, amount });
+	sp_io::storage::set(&pending_payouts_key(), &payouts.encode());
+	Ok(())
+}
+
+/// Returns the batch to be sent to the parentchain, if any.
+///
+/// If a batch is outstanding, it is returned again with the same nonce once it hasn't been
+/// confirmed within [PAYOUT_CONFIRMATION_TIMEOUT] blocks. Otherwise all pending payouts are taken
+/// in randomized order as the next batch.
+///
+/// Returns nothing if the last batch is less than [PAYOUT_PERIOD] blocks ago, so that payout
+/// calls submitted by several validateers don't empty the queue in consecutive blocks.
+pub fn take_payout_batch() -> Option<OutstandingBatch> {
+	let now = current_block_number();
+	let last_payout: Option<SidechainBlockNumber> =
+		get_storage_value(UNSHIELDING_STORAGE_PREFIX, LAST_PAYOUT_KEY);
+	if last_payout.map_or(false, |last| now < last.saturating_add(PAYOUT_PERIOD)) {
+		debug!("Skipping unshield payout, last batch was at block {:?}", last_payout);
+		return None
+	}
+
+	let batch = match outstanding_batch() {
+		Some(batch) if now < batch.sent_at.saturating_add(PAYOUT_CONFIRMATION_TIMEOUT) => {
+			debug!("Skipping unshield payout, batch {} is still outstanding", batch.nonce);
+			return None
+		},
+		Some(batch) => {
+			warn!("Sending unconfirmed unshield payout batch {} again", batch.nonce);
+			OutstandingBatch { sent_at: now, ..batch }
+		},
+		None => {
+			let mut payouts = pending_payouts();
+			if payouts.is_empty() {
+				return None
+			}
+			sp_io::storage::clear(&pending_payouts_key());
+			// Only the author executes the call, the other validateers import the resulting state.
+			shuffle(&mut payouts, sp_io::offchain::random_seed());
+			OutstandingBatch { nonce: payout_nonce(), payouts, sent_at: now }
+		},
+	};
+	sp_io::storage::set(
+		&storage_value_key(UNSHIELDING_STORAGE_PREFIX, LAST_PAYOUT_KEY),
+		&now.encode(),
+	);
+	sp_io::storage::set(&outstanding_batch_key(), &batch.encode());
+	Some(batch)
+}
+
+/// Stores the parentchain call of the outstanding batch, to be signed by the enclave.
+pub fn set_outstanding_batch_call(call: &OpaqueCall) {
+	sp_io::storage::set(&outstanding_batch_call_key(), &call.0.encode());
+}
+
+/// The nonce and parentchain call of the outstanding batch, if it has been sent in the current
+/// block.
+pub fn batch_to_send() -> Option<(PayoutNonce, OpaqueCall)> {
+	let now = current_block_number();
+	let batch = outstanding_batch().filter(|batch| batch.sent_at == now)?;
+	let call: Vec<u8> = get_storage_value(UNSHIELDING_STORAGE_PREFIX, OUTSTANDING_BATCH_CALL_KEY)?;
+	Some((batch.nonce, OpaqueCall(call)))
+}
+
+/// Confirms the payouts of the outstanding batch whose remarks have been observed on the
+/// parentchain. Returns the number of confirmed payouts.
+///
+/// Once the remark of the batch has been observed, the payouts without a remark have failed and
+/// are queued again, and the next batch is signed with the next nonce.
+pub fn confirm_payouts(remarks: Vec<H256>) -> usize {
+	let mut batch = match outstanding_batch() {
+		Some(batch) => batch,
+		None => return 0,
+	};
+	let unconfirmed = batch.payouts.len();
+	batch
+		.payouts
+		.retain(|payout| !remarks.contains(&PayoutRemark::Payout(payout.id).hash()));
+	let confirmed = unconfirmed - batch.payouts.len();
+
+	if remarks.contains(&PayoutRemark::Batch(batch.nonce).hash()) {
+		if !batch.payouts.is_empty() {
+			warn!("Queueing {} failed unshield payout(s) again", batch.payouts.len());
+			let mut pending = batch.payouts;
+			pending.extend(pending_payouts());
+			sp_io::storage::set(&pending_payouts_key(), &pending.encode());
+		}
+		sp_io::storage::set(
+			&storage_value_key(UNSHIELDING_STORAGE_PREFIX, PAYOUT_NONCE_KEY),
+			&(batch.nonce + 1).encode(),
+		);
+		sp_io::storage::clear(&outstanding_batch_key());
+		sp_io::storage::clear(&outstanding_batch_call_key());
+	} else {
+		sp_io::storage::set(&outstanding_batch_key(), &batch.encode());
+	}
+	confirmed
+}
+
+/// Funds owed to their owners when the shard is retired: the free balances of all accounts but
+/// the enclave signer, and the queued payouts. The amounts of the same owner are summed up.
+pub fn retirement_payouts() -> Vec<(AccountId, Balance)> {
+	let enclave_account: AccountId = enclave_signer_account();
+	let mut owed: BTreeMap<AccountId, Balance> = BTreeMap::new();
+	for (who, info) in frame_system::Account::<Runtime>::iter() {
+		if who != enclave_account && info.data.free > 0 {
+			let amount = owed.entry(who).or_default();
+			*amount = amount.saturating_add(info.data.free);
+		}
+	}
+	for payout in pending_payouts() {
+		let amount = owed.entry(payout.beneficiary).or_default();
+		*amount = amount.saturating_add(payout.amount);
+	}
+	owed.into_iter().collect()
+}
+
+/// Takes the [retirement_payouts] of a retired shard: burns the free balances and clears the
+/// queue, such that the funds can't be paid out a second time.
+///
+/// The outstanding batch is kept, it may still be executed by the vault.
+pub fn take_retirement_payouts() -> StfResult<Vec<(AccountId, Balance)>> {
+	let payouts = retirement_payouts();
+	for (who, _) in payouts.iter() {
+		if System::account(who).data.free > 0 {
+			ita_sgx_runtime::BalancesCall::<Runtime>::force_set_balance {
+				who: MultiAddress::Id(who.clone()),
+				new_free: 0,
+			}
+			.dispatch_bypass_filter(ita_sgx_runtime::RuntimeOrigin::root())
+			.map_err(|e| {
+				StfError::Dispatch(format!("Burn retirement payout error: {:?}", e.error))
+			})?;
+		}
+	}
+	sp_io::storage::clear(&pending_payouts_key());
+	Ok(payouts)
+}
+
+/// Fisher-Yates shuffle, drawing the random numbers from a hash chain of the seed.
+fn shuffle<T>(items: &mut [T], seed: [u8; 32]) {
+	let mut randomness = seed;
+	for i in (1..items.len()).rev() {
+		randomness = blake2_256(&randomness);
+		let mut random_bytes = [0u8; 8];
+		random_bytes.copy_from_slice(&randomness[..8]);
+		let random = u64::from_le_bytes(random_bytes);
+		items.swap(i, (random % (i as u64 + 1)) as usize);
+	}
+}
+
+/// Exports the queued payouts to the account.
+pub struct UnshieldingStorage;
+
+impl AccountStorage for UnshieldingStorage {
+	const PALLET: &'static str = UNSHIELDING_STORAGE_PREFIX;
+
+	fn account_entries(who: &AccountId) -> Vec<AccountDataEntry> {
+		let payouts: Vec<PendingPayout> = pending_payouts()
+			.into_iter()
+			.filter(|payout| &payout.beneficiary == who)
+			.collect();
+		if payouts.is_empty() {
+			return Vec::new()
+		}
+		vec![AccountDataEntry::new(PENDING_PAYOUTS_KEY, &payouts)]
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::helpers::set_block_number;
+	use itp_sgx_externalities::{SgxExternalities, SgxExternalitiesTrait};
+	use sp_keyring::AccountKeyring;
+
+	fn remarks_of(batch: &OutstandingBatch) -> Vec<H256> {
+		batch
+			.payouts
+			.iter()
+			.map(|payout| PayoutRemark::Payout(payout.id).hash())
+			.chain([PayoutRemark::Batch(batch.nonce).hash()])
+			.collect()
+	}
+
+	#[test]
+	fn shuffle_is_a_deterministic_permutation() {
+		let mut items: Vec<u32> = (0..50).collect();
+		let mut same_seed = items.clone();
+
+		shuffle(&mut items, [7u8; 32]);
+		shuffle(&mut same_seed, [7u8; 32]);
+
+		assert_eq!(items, same_seed);
+		assert_ne!(items, (0..50).collect::<Vec<_>>());
+		items.sort();
+		assert_eq!(items, (0..50).collect::<Vec<_>>());
+	}
+
+	#[test]
+	fn payout_batch_is_taken_once_per_period() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+		let bob: AccountId = AccountKeyring::Bob.public().into();
+
+		state.execute_with(|| {
+			set_block_number(10);
+			queue_payout(alice.clone(), 5).unwrap();
+			queue_payout(bob, 7).unwrap();
+
+			let batch = take_payout_batch().unwrap();
+			assert_eq!(batch.payouts.len(), 2);
+			assert!(pending_payouts().is_empty());
+			confirm_payouts(remarks_of(&batch));
+
+			queue_payout(alice, 3).unwrap();
+			set_block_number(10 + PAYOUT_PERIOD - 1);
+			assert!(take_payout_batch().is_none());
+			assert_eq!(pending_payouts().len(), 1);
+
+			set_block_number(10 + PAYOUT_PERIOD);
+			assert_eq!(take_payout_batch().unwrap().payouts.len(), 1);
+		});
+	}
+
+	#[test]
+	fn identical_payouts_are_confirmed_by_their_id() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+
+		state.execute_with(|| {
+			set_block_number(10);
+			queue_payout(alice.clone(), 5).unwrap();
+			queue_payout(alice.clone(), 5).unwrap();
+			let batch = take_payout_batch().unwrap();
+
+			let confirmed = confirm_payouts(vec![PayoutRemark::Payout(1).hash()]);
+
+			assert_eq!(confirmed, 1);
+			assert_eq!(
+				outstanding_batch().unwrap().payouts,
+				vec![PendingPayout { id: 0, beneficiary: alice, amount: 5 }]
+			);
+			assert_eq!(outstanding_batch().unwrap().nonce, batch.nonce);
+		});
+	}
+
+	#[test]
+	fn failed_payouts_of_an_executed_batch_are_queued_again() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+		let bob: AccountId = AccountKeyring::Bob.public().into();
+
+		state.execute_with(|| {
+			set_block_number(10);
+			queue_payout(alice, 5).unwrap();
+			queue_payout(bob.clone(), 7).unwrap();
+			let batch = take_payout_batch().unwrap();
+
+			let confirmed = confirm_payouts(vec![
+				PayoutRemark::Payout(0).hash(),
+				PayoutRemark::Batch(batch.nonce).hash(),
+			]);
+
+			assert_eq!(confirmed, 1);
+			assert!(outstanding_batch().is_none());
+			assert_eq!(
+				pending_payouts(),
+				vec![PendingPayout { id: 1, beneficiary: bob, amount: 7 }]
+			);
+
+			set_block_number(10 + PAYOUT_PERIOD);
+			assert_eq!(take_payout_batch().unwrap().nonce, batch.nonce + 1);
+		});
+	}
+
+	#[test]
+	fn unconfirmed_batch_is_sent_again_with_the_same_nonce_after_the_timeout() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+
+		state.execute_with(|| {
+			set_block_number(10);
+			queue_payout(alice.clone(), 5).unwrap();
+			let batch = take_payout_batch().unwrap();
+			set_outstanding_batch_call(&OpaqueCall(vec![1, 2, 3]));
+			assert_eq!(batch_to_send(), Some((batch.nonce, OpaqueCall(vec![1, 2, 3]))));
+
+			set_block_number(10 + PAYOUT_CONFIRMATION_TIMEOUT - PAYOUT_PERIOD);
+			queue_payout(alice, 3).unwrap();
+			assert!(take_payout_batch().is_none());
+			assert!(batch_to_send().is_none());
+
+			set_block_number(10 + PAYOUT_CONFIRMATION_TIMEOUT);
+			let resent = take_payout_batch().unwrap();
+
+			assert_eq!(resent.nonce, batch.nonce);
+			assert_eq!(resent.payouts, batch.payouts);
+			assert_eq!(resent.sent_at, 10 + PAYOUT_CONFIRMATION_TIMEOUT);
+			assert_eq!(batch_to_send(), Some((batch.nonce, OpaqueCall(vec![1, 2, 3]))));
+			// The payout queued in the meantime waits for the outstanding batch.
+			assert_eq!(pending_payouts().len(), 1);
+		});
+	}
+
+	#[test]
+	fn late_confirmation_after_a_resend_confirms_the_payouts_once() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+		let bob: AccountId = AccountKeyring::Bob.public().into();
+
+		state.execute_with(|| {
+			set_block_number(10);
+			queue_payout(alice, 5).unwrap();
+			queue_payout(bob, 7).unwrap();
+			let batch = take_payout_batch().unwrap();
+			set_block_number(10 + PAYOUT_CONFIRMATION_TIMEOUT);
+			assert_eq!(take_payout_batch().unwrap().nonce, batch.nonce);
+
+			// Either the first or the re-sent extrinsic is included, as both use the same nonce.
+			assert_eq!(confirm_payouts(remarks_of(&batch)), 2);
+			assert!(outstanding_batch().is_none());
+			assert!(pending_payouts().is_empty());
+
+			// Observing the remarks again doesn't confirm or queue anything.
+			assert_eq!(confirm_payouts(remarks_of(&batch)), 0);
+			assert!(pending_payouts().is_empty());
+			assert_eq!(payout_nonce(), batch.nonce + 1);
+		});
+	}
+
+	#[test]
+	fn retirement_payouts_include_balances_and_queued_payouts() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+		let bob: AccountId = AccountKeyring::Bob.public().into();
+		let enclave: AccountId = AccountKeyring::Eve.public().into();
+
+		state.execute_with(|| {
+			sp_io::storage::set(
+				&storage_value_key("Sudo", crate::ENCLAVE_ACCOUNT_KEY),
+				&enclave.encode(),
+			);
+			for (who, free) in [(&alice, 10), (&bob, 0), (&enclave, 100)] {
+				frame_system::Account::<Runtime>::mutate(who, |info| info.data.free = free);
+			}
+			queue_payout(alice.clone(), 5).unwrap();
+			queue_payout(bob.clone(), 7).unwrap();
+
+			let mut payouts = retirement_payouts();
+			payouts.sort_by_key(|(_, amount)| *amount);
+
+			assert_eq!(payouts, vec![(bob, 7), (alice, 15)]);
+		});
+	}
+
+	#[test]
+	fn retirement_payouts_are_burnt_and_the_outstanding_batch_is_kept() {
+		let mut state = SgxExternalities::default();
+		let alice: AccountId = AccountKeyring::Alice.public().into();
+		let bob: AccountId = AccountKeyring::Bob.public().into();
+		let enclave: AccountId = AccountKeyring::Eve.public().into();
+
+		state.execute_with(|| {
+			set_block_number(10);
+			sp_io::storage::set(
+				&storage_value_key("Sudo", crate::ENCLAVE_ACCOUNT_KEY),
+				&enclave.encode(),
+			);
+			frame_system::Account::<Runtime>::mutate(&alice, |info| info.data.free = 10);
+			queue_payout(bob, 7).unwrap();
+			let batch = take_payout_batch().unwrap();
+			queue_payout(alice.clone(), 5).unwrap();
+
+			let payouts = take_retirement_payouts().unwrap();
+
+			assert_eq!(payouts, vec![(alice.clone(), 15)]);
+			assert_eq!(System::account(&alice).data.free, 0);
+			assert!(pending_payouts().is_empty());
+			assert!(retirement_payouts().is_empty());
+			// The batch sent before the retirement is neither paid out again nor dropped.
+			assert_eq!(outstanding_batch(), Some(batch));
+		});
+	}
+}
@@ -0,0 +1,62 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Weights of the trusted calls, to be generated by the trusted call benchmarks with:
+//! `cd benchmarks && cargo run --release -- --output ../app-libs/stf/src/weights.rs`
+//!
+//! Source: estimates, the benchmarks have not been run on validateer hardware yet. Replace this
+//! module with their output instead of adjusting single weights.
+
+use core::time::Duration;
+
+/// Weight of the calls that have not been benchmarked, e.g. the EVM calls.
+pub const DEFAULT_WEIGHT: Duration = Duration::from_micros(5_000);
+
+/// Weight of a trusted call variant, by [name](crate::TrustedCall::name).
+pub fn weight_of(call_name: &str) -> Duration {
+	let micros: u64 = match call_name {
+		"noop" => 100,
+		"balance_set_balance" => 200,
+		"balance_transfer" => 300,
+		"balance_unshield" => 400,
+		"balance_shield" => 300,
+		"schedule_call" => 500,
+		"reap_accounts" => 50_000,
+		"balance_unshield_batched" => 400,
+		"payout_unshield_batch" => 5_000,
+		"confirm_unshield_payouts" => 5_000,
+		"assets_shield" => 300,
+		"assets_transfer" => 300,
+		"assets_unshield" => 400,
+		"shard_acl_set_policy" => 150,
+		"shard_acl_set_member" => 200,
+		"shard_acl_set_maintenance_mode" => 150,
+		"session_key_register" => 200,
+		"session_key_revoke" => 150,
+		"session_call" => 100,
+		"sponsored_call" => 150,
+		"fees_set_config" => 150,
+		"fees_set_multiplier" => 150,
+		"cross_shard_transfer" => 400,
+		"cross_shard_deliver" => 300,
+		"cross_shard_commit_outbox" => 800,
+		"cross_shard_anchor_commitment" => 100,
+		"reserves_attest" => 50_000,
+		_ => return DEFAULT_WEIGHT,
+	};
+	Duration::from_micros(micros)
+}
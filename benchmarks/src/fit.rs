@@ -0,0 +1,116 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Fitting of the measured execution times.
+
+use std::time::Duration;
+
+/// Linear model `base + slope * accounts` of the execution time of a call, in nanoseconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearFit {
+	pub base: f64,
+	pub slope: f64,
+}
+
+impl LinearFit {
+	/// Least squares fit of the (state size, execution time) samples, `None` if there are none.
+	///
+	/// The slope is 0 if all samples have the same state size.
+	pub fn from_samples(samples: &[(u32, Duration)]) -> Option<Self> {
+		if samples.is_empty() {
+			return None
+		}
+		let n = samples.len() as f64;
+		let points = samples.iter().map(|(x, y)| (*x as f64, y.as_nanos() as f64));
+		let (sum_x, sum_y) = points.clone().fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x, sy + y));
+		let (mean_x, mean_y) = (sum_x / n, sum_y / n);
+
+		let (covariance, variance) = points.fold((0.0, 0.0), |(cov, var), (x, y)| {
+			(cov + (x - mean_x) * (y - mean_y), var + (x - mean_x) * (x - mean_x))
+		});
+		let slope = if variance > 0.0 { covariance / variance } else { 0.0 };
+		Some(LinearFit { base: mean_y - slope * mean_x, slope })
+	}
+
+	/// Execution time at the given state size, never negative.
+	pub fn at(&self, state_size: u32) -> Duration {
+		let nanos = self.base + self.slope * state_size as f64;
+		Duration::from_nanos(nanos.max(0.0).round() as u64)
+	}
+}
+
+/// Median of the samples, the mean of the two middle ones for an even number of samples.
+pub fn median(samples: &mut [Duration]) -> Duration {
+	if samples.is_empty() {
+		return Duration::ZERO
+	}
+	samples.sort_unstable();
+	let middle = samples.len() / 2;
+	if samples.len() % 2 == 0 {
+		(samples[middle - 1] + samples[middle]) / 2
+	} else {
+		samples[middle]
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn micros(samples: &[(u32, u64)]) -> Vec<(u32, Duration)> {
+		samples.iter().map(|(x, y)| (*x, Duration::from_micros(*y))).collect()
+	}
+
+	#[test]
+	fn fit_of_exact_line_recovers_it() {
+		let fit = LinearFit::from_samples(&micros(&[(1, 102), (100, 300), (1000, 2100)])).unwrap();
+
+		assert!((fit.base - 100_000.0).abs() < 1e-6);
+		assert!((fit.slope - 2_000.0).abs() < 1e-6);
+		assert_eq!(fit.at(10_000), Duration::from_micros(20_100));
+	}
+
+	#[test]
+	fn fit_of_single_state_size_is_constant_mean() {
+		let fit = LinearFit::from_samples(&micros(&[(100, 10), (100, 30)])).unwrap();
+
+		assert_eq!(fit.slope, 0.0);
+		assert_eq!(fit.at(10_000), Duration::from_micros(20));
+	}
+
+	#[test]
+	fn fit_never_returns_negative_times() {
+		let fit = LinearFit::from_samples(&micros(&[(1, 100), (100, 1)])).unwrap();
+
+		assert_eq!(fit.at(10_000), Duration::ZERO);
+	}
+
+	#[test]
+	fn fit_of_no_samples_is_none() {
+		assert!(LinearFit::from_samples(&[]).is_none());
+	}
+
+	#[test]
+	fn median_works() {
+		let mut odd = [3, 1, 2].map(Duration::from_micros);
+		let mut even = [4, 1, 3, 2].map(Duration::from_micros);
+
+		assert_eq!(median(&mut odd), Duration::from_micros(2));
+		assert_eq!(median(&mut even), Duration::from_nanos(2_500));
+		assert_eq!(median(&mut []), Duration::ZERO);
+	}
+}
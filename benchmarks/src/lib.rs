@@ -0,0 +1,166 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Benchmarks of the trusted calls, from which the weights in `app-libs/stf/src/weights.rs`
+//! are generated.
+//!
+//! Every call variant is executed repeatedly in the `std` STF against states of increasing size,
+//! each prepared with the worst-case preconditions of the call (see [setups]). The median
+//! execution time per state size is fitted linearly over the number of accounts and evaluated
+//! at the largest state size. The time of the signature verification, which the executor does
+//! before the STF, is added to every weight.
+
+pub mod fit;
+pub mod setups;
+pub mod weights_file;
+
+use crate::{fit::LinearFit, setups::BenchmarkAccounts};
+use ita_sgx_runtime::Runtime;
+use ita_stf::{Getter, State, Stf, TrustedCall, TrustedCallSigned};
+use itp_node_api::metadata::{metadata_mocks::NodeMetadataMock, provider::NodeMetadataRepository};
+use itp_stf_interface::{system_pallet::SystemPalletAccountInterface, StateCallInterface};
+use itp_stf_primitives::{
+	traits::{TrustedCallSigning, TrustedCallVerification},
+	types::{KeyPair, Signature},
+};
+use itp_types::ShardIdentifier;
+use sp_core::{ed25519, Pair};
+use std::{
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+pub type StfState = Stf<TrustedCallSigned, Getter, State, Runtime>;
+
+/// Enclave and shard the benchmarked calls are signed for.
+const MRENCLAVE: [u8; 32] = [1u8; 32];
+const SHARD: ShardIdentifier = ShardIdentifier::zero();
+
+/// Prepares the worst-case state for a call and returns the call to be measured.
+pub type SetupFn = fn(&mut State, &BenchmarkAccounts) -> TrustedCall;
+
+/// Benchmark of one trusted call variant.
+pub struct CallBenchmark {
+	/// [Name](TrustedCall::name) of the benchmarked variant.
+	pub name: &'static str,
+	pub setup: SetupFn,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchmarkConfig {
+	/// Number of endowed accounts in the benchmarked states.
+	pub state_sizes: Vec<u32>,
+	/// Executions per call and state size, of which the median is taken.
+	pub repetitions: u32,
+}
+
+impl Default for BenchmarkConfig {
+	fn default() -> Self {
+		BenchmarkConfig { state_sizes: vec![1, 100, 1000, 10_000], repetitions: 20 }
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkResult {
+	pub name: &'static str,
+	/// Median execution time per state size.
+	pub medians: Vec<(u32, Duration)>,
+	/// Fitted weight at the largest state size, incl. the signature verification.
+	pub weight: Duration,
+}
+
+/// Runs all [setups::benchmarks] and returns their results, together with the time of the
+/// signature verification that is included in every weight.
+pub fn run(config: &BenchmarkConfig) -> Result<(Vec<BenchmarkResult>, Duration), String> {
+	if config.state_sizes.is_empty() || config.repetitions == 0 {
+		return Err("at least one state size and one repetition are required".into())
+	}
+	let verification = benchmark_signature_verification(config.repetitions);
+
+	let benchmarks = setups::benchmarks();
+	let mut medians: Vec<Vec<(u32, Duration)>> = vec![Vec::new(); benchmarks.len()];
+	for &state_size in &config.state_sizes {
+		let accounts = BenchmarkAccounts::new(state_size);
+		let genesis = setups::genesis_state(&accounts);
+
+		for (benchmark, medians) in benchmarks.iter().zip(medians.iter_mut()) {
+			let mut prepared = genesis.clone();
+			let call = (benchmark.setup)(&mut prepared, &accounts);
+			if call.name() != benchmark.name {
+				return Err(format!("setup of {} returned a {} call", benchmark.name, call.name()))
+			}
+
+			let mut samples = (0..config.repetitions)
+				.map(|_| execute_measured(&mut prepared.clone(), call.clone()))
+				.collect::<Result<Vec<_>, _>>()
+				.map_err(|e| format!("{} with {} accounts: {}", benchmark.name, state_size, e))?;
+			medians.push((state_size, fit::median(&mut samples)));
+		}
+	}
+
+	let max_state_size = config.state_sizes.iter().copied().max().unwrap_or_default();
+	let results = benchmarks
+		.iter()
+		.zip(medians)
+		.map(|(benchmark, medians)| {
+			let execution = LinearFit::from_samples(&medians)
+				.map(|fit| fit.at(max_state_size))
+				.unwrap_or_default()
+				// A noisy fit must not undercut what has actually been measured.
+				.max(medians.iter().map(|(_, median)| *median).max().unwrap_or_default());
+			BenchmarkResult { name: benchmark.name, medians, weight: execution + verification }
+		})
+		.collect();
+	Ok((results, verification))
+}
+
+/// Executes the call with the correct nonce of its sender and returns the execution time.
+///
+/// The signature of the call is not checked by the STF (but by the executor before), hence a
+/// dummy one is used. Fails if the call is not executed successfully, since the benchmark would
+/// not measure the intended code path otherwise.
+pub fn execute_measured(state: &mut State, call: TrustedCall) -> Result<Duration, String> {
+	let nonce = StfState::get_account_nonce(state, call.sender_account());
+	let signed =
+		TrustedCallSigned::new(call, nonce, Signature::Ed25519(ed25519::Signature([0u8; 64])));
+	let repo = Arc::new(NodeMetadataRepository::new(NodeMetadataMock::new()));
+
+	let started = Instant::now();
+	let result = StfState::execute_call(state, signed, &MRENCLAVE, &SHARD, &mut Vec::new(), repo);
+	let elapsed = started.elapsed();
+
+	result.map(|_| elapsed).map_err(|e| format!("{:?}", e))
+}
+
+/// Median time of the signature verification of a (typical sized) trusted call.
+pub fn benchmark_signature_verification(repetitions: u32) -> Duration {
+	let pair = ed25519::Pair::from_seed(b"benchmark-signature-verification");
+	let call =
+		TrustedCall::balance_transfer(pair.public().into(), setups::fresh_account(0), u128::MAX);
+	let signed = call.sign(&KeyPair::Ed25519(Box::new(pair)), 0, &MRENCLAVE, &SHARD);
+
+	let mut samples: Vec<Duration> = (0..repetitions.max(1))
+		.map(|_| {
+			let started = Instant::now();
+			let verified = signed.verify_signature(&MRENCLAVE, &SHARD);
+			let elapsed = started.elapsed();
+			assert!(verified, "benchmarked signature must be valid");
+			elapsed
+		})
+		.collect();
+	fit::median(&mut samples)
+}
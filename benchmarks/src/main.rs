@@ -0,0 +1,79 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Runs the trusted call benchmarks and prints or writes the generated weights module.
+//!
+//! Usage: `cargo run --release -- [--output <file>] [--repetitions <n>] [--state-sizes <a,b,..>]`
+
+use integritee_worker_benchmarks::{run, weights_file, BenchmarkConfig};
+use std::{env, fs, process};
+
+fn main() {
+	let (config, output) = match parse_args(env::args().skip(1)) {
+		Ok(args) => args,
+		Err(e) => {
+			eprintln!("{}", e);
+			eprintln!(
+				"Usage: integritee-worker-benchmarks [--output <file>] [--repetitions <n>] [--state-sizes <a,b,..>]"
+			);
+			process::exit(2)
+		},
+	};
+
+	let (results, signature_verification) = run(&config).unwrap_or_else(|e| {
+		eprintln!("Benchmark failed: {}", e);
+		process::exit(1)
+	});
+
+	eprintln!("signature verification: {:?}", signature_verification);
+	for result in &results {
+		eprintln!("{}: {:?} (medians: {:?})", result.name, result.weight, result.medians);
+	}
+
+	let rendered = weights_file::render(&results, signature_verification, &config);
+	match output {
+		Some(path) => fs::write(&path, rendered).unwrap_or_else(|e| {
+			eprintln!("Failed to write {}: {}", path, e);
+			process::exit(1)
+		}),
+		None => print!("{}", rendered),
+	}
+}
+
+fn parse_args(
+	mut args: impl Iterator<Item = String>,
+) -> Result<(BenchmarkConfig, Option<String>), String> {
+	let mut config = BenchmarkConfig::default();
+	let mut output = None;
+	while let Some(arg) = args.next() {
+		let mut value = || args.next().ok_or_else(|| format!("Missing value of {}", arg));
+		match arg.as_str() {
+			"--output" => output = Some(value()?),
+			"--repetitions" =>
+				config.repetitions =
+					value()?.parse().map_err(|e| format!("Invalid repetitions: {}", e))?,
+			"--state-sizes" =>
+				config.state_sizes = value()?
+					.split(',')
+					.map(|s| s.trim().parse())
+					.collect::<Result<_, _>>()
+					.map_err(|e| format!("Invalid state sizes: {}", e))?,
+			_ => return Err(format!("Unknown argument {}", arg)),
+		}
+	}
+	Ok((config, output))
+}
@@ -0,0 +1,390 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Worst-case setups of the trusted call benchmarks.
+//!
+//! Each setup prepares the state such that the benchmarked call takes its most expensive path,
+//! e.g. creates a new account, or operates on a full queue. The preparation itself is not
+//! measured.
+
+use crate::{execute_measured, CallBenchmark, StfState, MRENCLAVE, SHARD};
+use codec::Encode;
+use ita_stf::{
+	cross_shard::COMMITMENT_PERIOD,
+	helpers::set_block_number,
+	rent::REAPING_PERIOD,
+	scheduler::{ScheduledAt, MAX_SCHEDULED_CALLS},
+	unshielding::{PayoutRemark, MAX_PENDING_PAYOUTS},
+	State, TrustedCall,
+};
+use itp_sgx_externalities::SgxExternalitiesTrait;
+use itp_stf_interface::{sudo_pallet::SudoPalletInterface, InitState, SHARD_VAULT_KEY};
+use itp_stf_primitives::{
+	cross_shard::{AnchoredCommitment, CrossShardMessage, CrossShardPayload, OutboxCommitment},
+	fees::{FeeConfig, FeeDestination, FeeMultiplier},
+	session_keys::{SessionKey, MAX_SCOPE_LEN},
+	shard_acl::AccessPolicy,
+	traits::TrustedCallSigning,
+	types::{AccountId, KeyPair},
+};
+use itp_types::{parentchain::ParentchainAssetId, ShardIdentifier, SidechainBlockNumber};
+use sp_core::{ed25519, Pair};
+
+/// Balance every endowed account of the benchmarked states holds.
+pub const ENDOWMENT: u128 = 1_000_000_000_000_000;
+
+/// Amount that is transferred, shielded or unshielded by the benchmarked calls.
+pub const AMOUNT: u128 = 1_000_000;
+
+/// Asset that is used by the asset benchmarks.
+pub const ASSET: ParentchainAssetId = ParentchainAssetId::Assets(1);
+
+/// Sidechain block at which the calls are benchmarked.
+const BLOCK_NUMBER: SidechainBlockNumber = 1;
+
+/// Accounts of a benchmarked state.
+pub struct BenchmarkAccounts {
+	pub enclave: AccountId,
+	/// Endowed with [ENDOWMENT], the first one is the sender of the user calls.
+	pub endowed: Vec<AccountId>,
+}
+
+impl BenchmarkAccounts {
+	pub fn new(state_size: u32) -> Self {
+		BenchmarkAccounts {
+			enclave: account(b'e', 0),
+			endowed: (0..state_size.max(1)).map(|i| account(b'a', i)).collect(),
+		}
+	}
+
+	pub fn caller(&self) -> AccountId {
+		self.endowed[0].clone()
+	}
+}
+
+/// Account that does not exist in any benchmarked state.
+pub fn fresh_account(index: u32) -> AccountId {
+	account(b'f', index)
+}
+
+fn account(tag: u8, index: u32) -> AccountId {
+	let mut raw = [tag; 32];
+	raw[..4].copy_from_slice(&index.to_le_bytes());
+	AccountId::from(raw)
+}
+
+fn vault_account() -> AccountId {
+	account(b'v', 0)
+}
+
+/// State at [BLOCK_NUMBER] with the endowed accounts and the shard vault.
+pub fn genesis_state(accounts: &BenchmarkAccounts) -> State {
+	let mut state = StfState::init_state(accounts.enclave.clone());
+	let root = StfState::get_root(&mut state);
+	state.insert(SHARD_VAULT_KEY.into(), vault_account().encode());
+	state.execute_with(|| set_block_number(BLOCK_NUMBER));
+
+	prepare(
+		&mut state,
+		accounts
+			.endowed
+			.iter()
+			.map(|who| TrustedCall::balance_set_balance(root.clone(), who.clone(), ENDOWMENT, 0)),
+	);
+	state
+}
+
+/// Executes the calls one after the other, panics if one of them fails.
+fn prepare(state: &mut State, calls: impl IntoIterator<Item = TrustedCall>) {
+	for call in calls {
+		let name = call.name();
+		if let Err(e) = execute_measured(state, call) {
+			panic!("benchmark preparation failed, {} returned: {}", name, e)
+		}
+	}
+}
+
+pub fn benchmarks() -> Vec<CallBenchmark> {
+	vec![
+		CallBenchmark { name: "noop", setup: noop },
+		CallBenchmark { name: "balance_set_balance", setup: balance_set_balance },
+		CallBenchmark { name: "balance_transfer", setup: balance_transfer },
+		CallBenchmark { name: "balance_unshield", setup: balance_unshield },
+		CallBenchmark { name: "balance_shield", setup: balance_shield },
+		CallBenchmark { name: "schedule_call", setup: schedule_call },
+		CallBenchmark { name: "reap_accounts", setup: reap_accounts },
+		CallBenchmark { name: "balance_unshield_batched", setup: balance_unshield_batched },
+		CallBenchmark { name: "payout_unshield_batch", setup: payout_unshield_batch },
+		CallBenchmark { name: "confirm_unshield_payouts", setup: confirm_unshield_payouts },
+		CallBenchmark { name: "assets_shield", setup: assets_shield },
+		CallBenchmark { name: "assets_transfer", setup: assets_transfer },
+		CallBenchmark { name: "assets_unshield", setup: assets_unshield },
+		CallBenchmark { name: "shard_acl_set_policy", setup: shard_acl_set_policy },
+		CallBenchmark { name: "shard_acl_set_member", setup: shard_acl_set_member },
+		CallBenchmark {
+			name: "shard_acl_set_maintenance_mode",
+			setup: shard_acl_set_maintenance_mode,
+		},
+		CallBenchmark { name: "session_key_register", setup: session_key_register },
+		CallBenchmark { name: "session_key_revoke", setup: session_key_revoke },
+		CallBenchmark { name: "session_call", setup: session_call },
+		CallBenchmark { name: "sponsored_call", setup: sponsored_call },
+		CallBenchmark { name: "fees_set_config", setup: fees_set_config },
+		CallBenchmark { name: "fees_set_multiplier", setup: fees_set_multiplier },
+		CallBenchmark { name: "cross_shard_transfer", setup: cross_shard_transfer },
+		CallBenchmark { name: "cross_shard_deliver", setup: cross_shard_deliver },
+		CallBenchmark { name: "cross_shard_commit_outbox", setup: cross_shard_commit_outbox },
+		CallBenchmark {
+			name: "cross_shard_anchor_commitment",
+			setup: cross_shard_anchor_commitment,
+		},
+		CallBenchmark { name: "reserves_attest", setup: reserves_attest },
+	]
+}
+
+fn noop(_: &mut State, accounts: &BenchmarkAccounts) -> TrustedCall {
+	TrustedCall::noop(accounts.caller())
+}
+
+/// Creates a new account.
+fn balance_set_balance(state: &mut State, _: &BenchmarkAccounts) -> TrustedCall {
+	TrustedCall::balance_set_balance(StfState::get_root(state), fresh_account(0), ENDOWMENT, 0)
+}
+
+/// Transfers to a new account.
+fn balance_transfer(_: &mut State, accounts: &BenchmarkAccounts) -> TrustedCall {
+	TrustedCall::balance_transfer(accounts.caller(), fresh_account(0), AMOUNT)
+}
+
+fn balance_unshield(_: &mut State, accounts: &BenchmarkAccounts) -> TrustedCall {
+	TrustedCall::balance_unshield(
+		accounts.caller(),
+		fresh_account(0),
+		AMOUNT,
+		ShardIdentifier::default(),
+	)
+}
+
+/// Shields into a new account.
+fn balance_shield(_: &mut State, accounts: &BenchmarkAccounts) -> TrustedCall {
+	TrustedCall::balance_shield(accounts.enclave.clone(), fresh_account(0), AMOUNT)
+}
+
+/// Appends to a scheduler that is one call short of [MAX_SCHEDULED_CALLS].
+fn schedule_call(state: &mut State, accounts: &BenchmarkAccounts) -> TrustedCall {
+	prepare(state, (1..MAX_SCHEDULED_CALLS as u32).map(|i| scheduled_transfer(accounts, i)));
+	scheduled_transfer(accounts, 0)
+}
+
+fn scheduled_transfer(accounts: &BenchmarkAccounts, index: u32) -> TrustedCall {
+	TrustedCall::schedule_call(
+		accounts.caller(),
+		ScheduledAt::SidechainBlock(BLOCK_NUMBER + 1),
+		Box::new(TrustedCall::balance_transfer(accounts.caller(), fresh_account(index), AMOUNT)),
+	)
+}
+
+/// Charges the rent of the first batch of accounts, one full period after they started paying rent.
+///
+/// A call visits at most [ita_stf::rent::MAX_ACCOUNTS_PER_REAPING_BATCH] accounts, which bounds
+/// its weight once the state is larger than a batch.
+fn reap_accounts(state: &mut State, accounts: &BenchmarkAccounts) -> TrustedCall {
+	// Accounts only pay rent once they have sent a call.
+	prepare(state, accounts.endowed.iter().cloned().map(TrustedCall::noop));
+	state.execute_with(|| set_block_number(BLOCK_NUMBER + REAPING_PERIOD));
+	TrustedCall::reap_accounts(accounts.enclave.clone())
+}
+
+/// Queues into a payout queue that is one payout short of [MAX_PENDING_PAYOUTS].
+fn balance_unshield_batched(state: &mut State, accounts: &BenchmarkAccounts) -> TrustedCall {
+	prepare(state, (1..MAX_PENDING_PAYOUTS as u32).map(|i| unshield_batched(accounts, i)));
+	unshield_batched(accounts, 0)
+}
+
+/// Pays out a full queue of [MAX_PENDING_PAYOUTS].
+fn payout_unshield_batch(state: &mut State, accounts: &BenchmarkAccounts) -> TrustedCall {
+	prepare(state, (0..MAX_PENDING_PAYOUTS as u32).map(|i| unshield_batched(accounts, i)));
+	TrustedCall::payout_unshield_batch(accounts.enclave.clone())
+}
+
+/// Confirms a full batch of [MAX_PENDING_PAYOUTS] outstanding payouts.
+fn confirm_unshield_payouts(state: &mut State, accounts: &BenchmarkAccounts) -> TrustedCall {
+	let remarks = (0..MAX_PENDING_PAYOUTS as u64)
+		.map(|id| PayoutRemark::Payout(id).hash())
+		.chain([PayoutRemark::Batch(0).hash()])
+		.collect();
+	let setup_calls = (0..MAX_PENDING_PAYOUTS as u32).map(|i| unshield_batched(accounts, i));
+	prepare(
+		state,
+		setup_calls.chain([TrustedCall::payout_unshield_batch(accounts.enclave.clone())]),
+	);
+	TrustedCall::confirm_unshield_payouts(accounts.enclave.clone(), remarks)
+}
+
+fn unshield_batched(accounts: &BenchmarkAccounts, index: u32) -> TrustedCall {
+	TrustedCall::balance_unshield_batched(accounts.caller(), fresh_account(index), AMOUNT)
+}
+
+/// Mints to a new holder.
+fn assets_shield(_: &mut State, accounts: &BenchmarkAccounts) -> TrustedCall {
+	TrustedCall::assets_shield(accounts.enclave.clone(), fresh_account(0), ASSET, AMOUNT)
+}
+
+/// Transfers to a new holder.
+fn assets_transfer(state: &mut State, accounts: &BenchmarkAccounts) -> TrustedCall {
+	mint_to_caller(state, accounts);
+	TrustedCall::assets_transfer(accounts.caller(), fresh_account(0), ASSET, AMOUNT)
+}
+
+fn assets_unshield(state: &mut State, accounts: &BenchmarkAccounts) -> TrustedCall {
+	mint_to_caller(state, accounts);
+	TrustedCall::assets_unshield(accounts.caller(), fresh_account(0), ASSET, AMOUNT)
+}
+
+fn mint_to_caller(state: &mut State, accounts: &BenchmarkAccounts) {
+	prepare(
+		state,
+		[TrustedCall::assets_shield(accounts.enclave.clone(), accounts.caller(), ASSET, AMOUNT)],
+	);
+}
+
+fn shard_acl_set_policy(state: &mut State, _: &BenchmarkAccounts) -> TrustedCall {
+	TrustedCall::shard_acl_set_policy(StfState::get_root(state), AccessPolicy::RequireRole(1))
+}
+
+fn shard_acl_set_member(state: &mut State, _: &BenchmarkAccounts) -> TrustedCall {
+	TrustedCall::shard_acl_set_member(StfState::get_root(state), fresh_account(0), Some(vec![1]))
+}
+
+fn shard_acl_set_maintenance_mode(state: &mut State, _: &BenchmarkAccounts) -> TrustedCall {
+	TrustedCall::shard_acl_set_maintenance_mode(StfState::get_root(state), true)
+}
+
+/// Session key with the largest scope, the permitted call is checked last.
+fn session_key() -> SessionKey {
+	let mut scope = vec![b"balance_transfer".to_vec(); MAX_SCOPE_LEN - 1];
+	scope.push(b"noop".to_vec());
+	SessionKey::new(u64::MAX, scope)
+}
+
+fn session_key_register(_: &mut State, accounts: &BenchmarkAccounts) -> TrustedCall {
+	TrustedCall::session_key_register(accounts.caller(), fresh_account(0), session_key())
+}
+
+fn session_key_revoke(state: &mut State, accounts: &BenchmarkAccounts) -> TrustedCall {
+	let register = session_key_register(state, accounts);
+	prepare(state, [register]);
+	TrustedCall::session_key_revoke(accounts.caller(), fresh_account(0))
+}
+
+/// Measures the overhead of the session key checks, the wrapped call is a `noop`.
+fn session_call(state: &mut State, accounts: &BenchmarkAccounts) -> TrustedCall {
+	let register = session_key_register(state, accounts);
+	prepare(state, [register]);
+	TrustedCall::session_call(fresh_account(0), Box::new(TrustedCall::noop(accounts.caller())))
+}
+
+/// Measures the overhead of the sponsoring including the verification of the user signature,
+/// the sponsored call is a `noop` of a new account.
+fn sponsored_call(_: &mut State, accounts: &BenchmarkAccounts) -> TrustedCall {
+	let user = ed25519::Pair::from_seed(b"benchmark-sponsored-call-user-ke");
+	let noop = TrustedCall::noop(user.public().into()).sign(
+		&KeyPair::Ed25519(Box::new(user)),
+		0,
+		&MRENCLAVE,
+		&SHARD,
+	);
+	TrustedCall::sponsored_call(accounts.caller(), Box::new(noop))
+}
+
+fn fees_set_config(state: &mut State, _: &BenchmarkAccounts) -> TrustedCall {
+	let config = FeeConfig {
+		base_fee: 1,
+		fee_per_byte: 1,
+		fee_per_micros: 1,
+		destination: FeeDestination::Treasury(fresh_account(0)),
+	};
+	TrustedCall::fees_set_config(StfState::get_root(state), config)
+}
+
+fn fees_set_multiplier(state: &mut State, _: &BenchmarkAccounts) -> TrustedCall {
+	TrustedCall::fees_set_multiplier(StfState::get_root(state), FeeMultiplier::from_u32(2))
+}
+
+/// Sends the first message to the destination shard.
+fn cross_shard_transfer(_: &mut State, accounts: &BenchmarkAccounts) -> TrustedCall {
+	TrustedCall::cross_shard_transfer(
+		accounts.caller(),
+		ShardIdentifier::repeat_byte(1),
+		fresh_account(0),
+		AMOUNT,
+	)
+}
+
+/// Delivers the first message of a shard, crediting a new account.
+fn cross_shard_deliver(_: &mut State, accounts: &BenchmarkAccounts) -> TrustedCall {
+	let message = CrossShardMessage {
+		destination: ShardIdentifier::default(),
+		nonce: 0,
+		sender: accounts.caller(),
+		payload: CrossShardPayload::Transfer { to: fresh_account(0), amount: AMOUNT },
+	};
+	TrustedCall::cross_shard_deliver(
+		accounts.enclave.clone(),
+		ShardIdentifier::repeat_byte(1),
+		message,
+	)
+}
+
+/// Commits to one message per sidechain block of a [COMMITMENT_PERIOD].
+fn cross_shard_commit_outbox(state: &mut State, accounts: &BenchmarkAccounts) -> TrustedCall {
+	prepare(
+		state,
+		(0..COMMITMENT_PERIOD as u32).map(|i| {
+			TrustedCall::cross_shard_transfer(
+				accounts.caller(),
+				ShardIdentifier::repeat_byte(1),
+				fresh_account(i),
+				AMOUNT,
+			)
+		}),
+	);
+	TrustedCall::cross_shard_commit_outbox(accounts.enclave.clone(), ShardIdentifier::default())
+}
+
+fn cross_shard_anchor_commitment(_: &mut State, accounts: &BenchmarkAccounts) -> TrustedCall {
+	let commitment = OutboxCommitment {
+		shard: ShardIdentifier::repeat_byte(1),
+		index: 0,
+		root: Default::default(),
+		message_count: 1,
+	};
+	TrustedCall::cross_shard_anchor_commitment(
+		accounts.enclave.clone(),
+		AnchoredCommitment { commitment, enclave_fingerprint: Default::default() },
+	)
+}
+
+/// Starts a reserves report with a full payout queue of [MAX_PENDING_PAYOUTS].
+///
+/// A call visits at most [ita_stf::reserves::MAX_ACCOUNTS_PER_ATTESTATION_BATCH] accounts, which
+/// bounds its weight once the state is larger than a batch. The queued payouts are added by the
+/// last batch, which is the first one for states of up to a batch.
+fn reserves_attest(state: &mut State, accounts: &BenchmarkAccounts) -> TrustedCall {
+	prepare(state, (0..MAX_PENDING_PAYOUTS as u32).map(|i| unshield_batched(accounts, i)));
+	TrustedCall::reserves_attest(accounts.enclave.clone(), ShardIdentifier::default())
+}
@@ -0,0 +1,163 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Rendering of the generated `app-libs/stf/src/weights.rs`.
+
+use crate::{BenchmarkConfig, BenchmarkResult};
+use std::{fmt::Write, time::Duration};
+
+/// Weight of the calls that have not been benchmarked, e.g. the EVM calls.
+pub const DEFAULT_WEIGHT: Duration = Duration::from_micros(5_000);
+
+const LICENSE_HEADER: &str = "/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the \"License\");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an \"AS IS\" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+";
+
+/// Renders the weights module, with the weights rounded up to whole microseconds.
+pub fn render(
+	results: &[BenchmarkResult],
+	signature_verification: Duration,
+	config: &BenchmarkConfig,
+) -> String {
+	let state_sizes: Vec<String> = config.state_sizes.iter().map(|s| s.to_string()).collect();
+
+	let mut out = String::from(LICENSE_HEADER);
+	// Writing to a `String` is infallible.
+	let _ = write!(
+		out,
+		"
+//! Weights of the trusted calls, generated by the trusted call benchmarks.
+//!
+//! DO NOT EDIT. Regenerate with:
+//! `cd benchmarks && cargo run --release -- --output ../app-libs/stf/src/weights.rs`
+//!
+//! Source: measured, incl. {} us signature verification
+//! State sizes (accounts): {}
+//! Repetitions: {}
+
+use core::time::Duration;
+
+/// Weight of the calls that have not been benchmarked, e.g. the EVM calls.
+pub const DEFAULT_WEIGHT: Duration = Duration::from_micros({});
+
+/// Weight of a trusted call variant, by [name](crate::TrustedCall::name).
+pub fn weight_of(call_name: &str) -> Duration {{
+	let micros: u64 = match call_name {{
+",
+		ceil_micros(signature_verification),
+		state_sizes.join(", "),
+		config.repetitions,
+		literal(ceil_micros(DEFAULT_WEIGHT)),
+	);
+	for result in results {
+		let _ =
+			writeln!(out, "\t\t\"{}\" => {},", result.name, literal(ceil_micros(result.weight)));
+	}
+	out.push_str(
+		"\t\t_ => return DEFAULT_WEIGHT,
+	};
+	Duration::from_micros(micros)
+}
+",
+	);
+	out
+}
+
+fn ceil_micros(duration: Duration) -> u64 {
+	(duration.as_nanos() as u64 + 999) / 1000
+}
+
+/// Integer literal with `_` separated thousands, e.g. `10_000`.
+fn literal(value: u64) -> String {
+	let digits = value.to_string();
+	let mut literal = String::new();
+	for (i, digit) in digits.chars().enumerate() {
+		if i > 0 && (digits.len() - i) % 3 == 0 {
+			literal.push('_');
+		}
+		literal.push(digit);
+	}
+	literal
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn literal_separates_thousands() {
+		assert_eq!(literal(0), "0");
+		assert_eq!(literal(999), "999");
+		assert_eq!(literal(5_000), "5_000");
+		assert_eq!(literal(1_234_567), "1_234_567");
+	}
+
+	#[test]
+	fn render_matches_weights_module_format() {
+		let results = vec![
+			BenchmarkResult { name: "noop", medians: vec![], weight: Duration::from_nanos(99_001) },
+			BenchmarkResult {
+				name: "reap_accounts",
+				medians: vec![],
+				weight: Duration::from_micros(50_000),
+			},
+		];
+		let config = BenchmarkConfig { state_sizes: vec![1, 100], repetitions: 5 };
+
+		let rendered = render(&results, Duration::from_micros(60), &config);
+
+		assert!(rendered.starts_with(LICENSE_HEADER));
+		assert!(rendered.contains("//! Source: measured, incl. 60 us signature verification\n"));
+		assert!(rendered.contains("//! State sizes (accounts): 1, 100\n"));
+		assert!(rendered.contains("//! Repetitions: 5\n"));
+		assert!(rendered.contains("Duration::from_micros(5_000);\n"));
+		assert!(rendered.contains("\t\t\"noop\" => 100,\n\t\t\"reap_accounts\" => 50_000,\n"));
+		assert!(rendered.ends_with(
+			"\t\t_ => return DEFAULT_WEIGHT,\n\t};\n\tDuration::from_micros(micros)\n}\n"
+		));
+	}
+
+	#[test]
+	fn weights_module_has_a_weight_for_every_benchmark() {
+		let weights_module = include_str!("../../app-libs/stf/src/weights.rs");
+		let weighted_calls = weights_module.matches("\" => ").count();
+		let benchmarks = crate::setups::benchmarks();
+
+		for benchmark in &benchmarks {
+			assert!(
+				weights_module.contains(&format!("\t\t\"{}\" => ", benchmark.name)),
+				"no weight for {}, regenerate the weights module",
+				benchmark.name
+			);
+		}
+		assert_eq!(weighted_calls, benchmarks.len(), "weights module has unbenchmarked calls");
+	}
+}
@@ -27,7 +27,9 @@ use crate::{
 };
 use codec::Decode;
 use hdrhistogram::Histogram;
-use ita_stf::{Getter, Index, TrustedCall, TrustedCallSigned, TrustedGetter};
+use ita_stf::{
+	default_getter_expiry, Getter, Index, TrustedCall, TrustedCallSigned, TrustedGetter,
+};
 use itc_rpc_client::direct_client::{DirectApi, DirectClient};
 use itp_stf_primitives::{
 	traits::TrustedCallSigning,
@@ -265,7 +267,7 @@ fn get_balance(
 ) -> Option<u128> {
 	let getter = Getter::trusted(
 		TrustedGetter::free_balance(account.public().into())
-			.sign(&KeyPair::Sr25519(Box::new(account.clone()))),
+			.sign(&KeyPair::Sr25519(Box::new(account.clone())), default_getter_expiry()),
 	);
 
 	let getter_start_timer = Instant::now();
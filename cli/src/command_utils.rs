@@ -15,7 +15,7 @@
 
 */
 
-use crate::Cli;
+use crate::{worker_directory::resolve_worker_url, Cli};
 use base58::FromBase58;
 use itc_rpc_client::direct_client::{DirectApi, DirectClient as DirectWorkerApi};
 use itp_node_api::api_client::{ParentchainApi, TungsteniteRpcClient};
@@ -52,7 +52,11 @@ pub(crate) fn get_accountid_from_str(account: &str) -> AccountId {
 }
 
 pub(crate) fn get_worker_api_direct(cli: &Cli) -> DirectWorkerApi {
-	let url = format!("{}:{}", cli.worker_url, cli.trusted_worker_port);
+	let url = if cli.resolve_worker {
+		resolve_worker_url(cli).unwrap_or_else(|e| panic!("could not resolve a worker: {}", e))
+	} else {
+		format!("{}:{}", cli.worker_url, cli.trusted_worker_port)
+	};
 	info!("Connecting to integritee-service-direct-port on '{}'", url);
 	DirectWorkerApi::new(url)
 }
@@ -18,11 +18,11 @@
 #[macro_export]
 macro_rules! get_layer_two_evm_nonce {
 	($signer_pair:ident, $cli:ident, $trusted_args:ident ) => {{
-		use ita_stf::{Getter, TrustedCallSigned};
+		use ita_stf::{default_getter_expiry, Getter, TrustedCallSigned};
 
 		let top = TrustedOperation::<TrustedCallSigned, Getter>::get(Getter::trusted(
 			TrustedGetter::evm_nonce($signer_pair.public().into())
-				.sign(&KeyPair::Sr25519(Box::new($signer_pair.clone()))),
+				.sign(&KeyPair::Sr25519(Box::new($signer_pair.clone())), default_getter_expiry()),
 		));
 		let res = perform_trusted_operation($cli, $trusted_args, &top).unwrap_or_default();
 		let nonce = match res {
@@ -20,7 +20,7 @@ use crate::{
 	trusted_operation::perform_trusted_operation, Cli, CliError, CliResult, CliResultOk,
 };
 use codec::Decode;
-use ita_stf::{Getter, TrustedCallSigned, TrustedGetter};
+use ita_stf::{default_getter_expiry, Getter, TrustedCallSigned, TrustedGetter};
 use itp_stf_primitives::types::{KeyPair, TrustedOperation};
 use itp_types::AccountId;
 use log::*;
@@ -54,7 +54,7 @@ impl EvmReadCommands {
 
 		let top = TrustedOperation::<TrustedCallSigned, Getter>::get(Getter::trusted(
 			TrustedGetter::evm_account_storages(sender_acc, execution_address, H256::zero())
-				.sign(&KeyPair::Sr25519(Box::new(sender))),
+				.sign(&KeyPair::Sr25519(Box::new(sender)), default_getter_expiry()),
 		));
 		let res = perform_trusted_operation(cli, trusted_args, &top)?;
 
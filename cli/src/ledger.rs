@@ -0,0 +1,139 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Signing of trusted calls with the substrate app on a Ledger device.
+//!
+//! The device is accessed via USB HID. The account is derived on the device with the
+//! BIP44 path `m/44'/<coin type>'/<account>'/0'/<index>'` and the signing payload is shown
+//! for confirmation before it is signed. Only ed25519 keys are supported.
+
+use codec::Decode;
+use itp_stf_primitives::{
+	traits::ExternalSigner,
+	types::{AccountId, Signature},
+};
+use ledger_apdu::APDUCommand;
+use ledger_transport_hid::{hidapi::HidApi, TransportNativeHID};
+use sp_core::ed25519;
+use std::{future::Future, pin::Pin};
+
+/// Class of the polkadot substrate app.
+pub(crate) const DEFAULT_LEDGER_APP_CLA: u8 = 0x90;
+/// SLIP-0044 coin type of polkadot.
+pub(crate) const DEFAULT_LEDGER_COIN_TYPE: u32 = 354;
+
+const INS_GET_ADDRESS: u8 = 0x01;
+const INS_SIGN: u8 = 0x02;
+
+const PAYLOAD_INIT: u8 = 0x00;
+const PAYLOAD_ADD: u8 = 0x01;
+const PAYLOAD_LAST: u8 = 0x02;
+
+const KEY_TYPE_ED25519: u8 = 0x00;
+const CHUNK_SIZE: usize = 250;
+const RETURN_CODE_OK: u16 = 0x9000;
+const HARDENED: u32 = 0x8000_0000;
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum LedgerError {
+	#[error("could not connect to the Ledger device: {0}")]
+	Transport(String),
+	#[error("Ledger app returned error code {0:#06x}")]
+	App(u16),
+	#[error("invalid response from the Ledger app: {0}")]
+	InvalidResponse(String),
+}
+
+pub(crate) struct LedgerSigner {
+	transport: TransportNativeHID,
+	cla: u8,
+	bip44_path: [u32; 5],
+}
+
+impl LedgerSigner {
+	pub fn new(cla: u8, coin_type: u32, account: u32, index: u32) -> Result<Self, LedgerError> {
+		let hid_api = HidApi::new().map_err(|e| LedgerError::Transport(e.to_string()))?;
+		let transport =
+			TransportNativeHID::new(&hid_api).map_err(|e| LedgerError::Transport(e.to_string()))?;
+		Ok(LedgerSigner {
+			transport,
+			cla,
+			bip44_path: [
+				44 | HARDENED,
+				coin_type | HARDENED,
+				account | HARDENED,
+				HARDENED,
+				index | HARDENED,
+			],
+		})
+	}
+
+	fn serialized_path(&self) -> Vec<u8> {
+		self.bip44_path.iter().flat_map(|i| i.to_le_bytes()).collect()
+	}
+
+	fn exchange(&self, ins: u8, p1: u8, data: Vec<u8>) -> Result<Vec<u8>, LedgerError> {
+		let command = APDUCommand { cla: self.cla, ins, p1, p2: KEY_TYPE_ED25519, data };
+		let answer = self
+			.transport
+			.exchange(&command)
+			.map_err(|e| LedgerError::Transport(e.to_string()))?;
+		match answer.retcode() {
+			RETURN_CODE_OK => Ok(answer.data().to_vec()),
+			code => Err(LedgerError::App(code)),
+		}
+	}
+
+	fn sign_blocking(&self, payload: &[u8]) -> Result<Signature, LedgerError> {
+		// The path is sent in a separate first chunk, followed by the chunked payload.
+		self.exchange(INS_SIGN, PAYLOAD_INIT, self.serialized_path())?;
+
+		let chunks: Vec<&[u8]> = payload.chunks(CHUNK_SIZE).collect();
+		let mut response = Vec::new();
+		for (i, chunk) in chunks.iter().enumerate() {
+			let p1 = if i + 1 == chunks.len() { PAYLOAD_LAST } else { PAYLOAD_ADD };
+			response = self.exchange(INS_SIGN, p1, chunk.to_vec())?;
+		}
+
+		// The app returns the signature prefixed by its type, which is the encoding of a
+		// `MultiSignature`.
+		Signature::decode(&mut response.as_slice())
+			.map_err(|e| LedgerError::InvalidResponse(e.to_string()))
+	}
+}
+
+impl ExternalSigner for LedgerSigner {
+	type Error = LedgerError;
+
+	fn account_id(&self) -> Result<AccountId, Self::Error> {
+		let response = self.exchange(INS_GET_ADDRESS, 0, self.serialized_path())?;
+		let public: [u8; 32] = response
+			.get(..32)
+			.and_then(|p| p.try_into().ok())
+			.ok_or_else(|| LedgerError::InvalidResponse("public key too short".to_string()))?;
+		Ok(ed25519::Public::from_raw(public).into())
+	}
+
+	fn sign<'a>(
+		&'a self,
+		payload: &'a [u8],
+	) -> Pin<Box<dyn Future<Output = Result<Signature, Self::Error>> + 'a>> {
+		// The HID transport is blocking, the future completes as soon as the user has
+		// confirmed (or rejected) the payload on the device.
+		Box::pin(async move { self.sign_blocking(payload) })
+	}
+}
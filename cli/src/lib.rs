@@ -35,18 +35,23 @@ mod command_utils;
 mod error;
 #[cfg(feature = "evm")]
 mod evm;
+#[cfg(feature = "ledger")]
+mod ledger;
+mod offline_signing;
 #[cfg(feature = "teeracle")]
 mod oracle;
 mod trusted_base_cli;
 mod trusted_cli;
 mod trusted_command_utils;
 mod trusted_operation;
+mod worker_directory;
 
 pub mod commands;
 
 use crate::commands::Commands;
 use clap::Parser;
 use itp_node_api::api_client::Metadata;
+use itp_types::ShardInfo;
 use sp_application_crypto::KeyTypeId;
 use sp_core::{H160, H256};
 use thiserror::Error;
@@ -81,6 +86,15 @@ pub struct Cli {
 	#[clap(short = 'P', long, default_value_t = String::from("2000"))]
 	trusted_worker_port: String,
 
+	/// pick a healthy worker of the shard from the worker directory on the parentchain,
+	/// instead of connecting to `--worker-url`
+	#[clap(long)]
+	resolve_worker: bool,
+
+	/// number of most recent parentchain blocks searched for worker directory records
+	#[clap(long, default_value_t = 300)]
+	directory_blocks: u32,
+
 	#[clap(subcommand)]
 	command: Commands,
 }
@@ -106,6 +120,10 @@ pub enum CliResultOk {
 	H160 {
 		hash: H160,
 	},
+	/// Result of "ListShardsCommand" and "InspectShardCommand"
+	ShardInfos {
+		shard_infos: Vec<ShardInfo>,
+	},
 	// TODO should ideally be removed; or at least drastically less used
 	// We WANT all commands exposed by the cli to return something useful for the caller(ie instead of printing)
 	None,
@@ -121,6 +139,10 @@ pub enum CliError {
 	EvmRead { msg: String },
 	#[error("worker rpc api error: {:?}", msg)]
 	WorkerRpcApi { msg: String },
+	#[error("offline signing error: {:?}", msg)]
+	OfflineSigning { msg: String },
+	#[error("worker directory error: {:?}", msg)]
+	WorkerDirectory { msg: String },
 }
 
 pub type CliResult = Result<CliResultOk, CliError>;
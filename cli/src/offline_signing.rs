@@ -0,0 +1,144 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! File formats of the offline signing workflow.
+//!
+//! 1. `trusted compose-call` exports an [UnsignedCallFile] on the online machine.
+//! 2. `trusted sign` signs it on an (air-gapped) machine holding the key and writes a [SignedCallFile].
+//! 3. `trusted submit-signed` encrypts the signed call and submits it to the worker.
+
+use crate::CliError;
+use base58::{FromBase58, ToBase58};
+use codec::Encode;
+use ita_stf::{Index, TrustedCall, TrustedCallSigned};
+use itp_stf_primitives::{traits::TrustedCallSigning, types::ShardIdentifier};
+use itp_utils::{FromHexPrefixed, ToHexPrefixed};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sp_core::crypto::Ss58Codec;
+use std::{fs, path::Path};
+
+/// Unsigned trusted call, including everything needed to sign it without access to a worker.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct UnsignedCallFile {
+	/// Account that has to sign the call (ss58).
+	pub signer: String,
+	/// Targeted worker MRENCLAVE (base58).
+	pub mrenclave: String,
+	/// Targeted shard (base58).
+	pub shard: String,
+	pub nonce: Index,
+	/// SCALE encoded `TrustedCall` (hex).
+	pub call: String,
+	/// Payload that has to be signed by `signer` (hex).
+	pub signing_payload: String,
+}
+
+impl UnsignedCallFile {
+	pub fn new(
+		call: &TrustedCall,
+		nonce: Index,
+		mrenclave: &[u8; 32],
+		shard: &ShardIdentifier,
+	) -> Self {
+		UnsignedCallFile {
+			signer: call.sender_account().to_ss58check(),
+			mrenclave: mrenclave.to_base58(),
+			shard: shard.encode().to_base58(),
+			nonce,
+			call: call.to_hex(),
+			signing_payload: call.signing_payload(nonce, mrenclave, shard).to_hex(),
+		}
+	}
+
+	/// Decodes the call and checks that the exported signing payload matches it.
+	///
+	/// The signer must never sign a payload that doesn't correspond to the call it is shown.
+	pub fn decode_and_verify(&self) -> Result<(TrustedCall, [u8; 32], ShardIdentifier), CliError> {
+		let call = TrustedCall::from_hex(&self.call)
+			.map_err(|e| offline_signing_error(format!("{:?}", e)))?;
+		let mrenclave = decode_mrenclave(&self.mrenclave)?;
+		let shard = decode_shard(&self.shard)?;
+
+		let expected_payload = call.signing_payload(self.nonce, &mrenclave, &shard).to_hex();
+		if expected_payload != self.signing_payload {
+			return Err(CliError::OfflineSigning {
+				msg: "signing payload does not match the exported call".to_string(),
+			})
+		}
+		Ok((call, mrenclave, shard))
+	}
+}
+
+/// Signed trusted call, ready to be encrypted and submitted by an online machine.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct SignedCallFile {
+	/// Targeted worker MRENCLAVE (base58).
+	pub mrenclave: String,
+	/// Targeted shard (base58).
+	pub shard: String,
+	/// SCALE encoded `TrustedCallSigned` (hex).
+	pub signed_call: String,
+}
+
+impl SignedCallFile {
+	pub fn new(signed_call: &TrustedCallSigned, mrenclave: &str, shard: &str) -> Self {
+		SignedCallFile {
+			mrenclave: mrenclave.to_string(),
+			shard: shard.to_string(),
+			signed_call: signed_call.to_hex(),
+		}
+	}
+
+	pub fn decode(&self) -> Result<(TrustedCallSigned, [u8; 32], ShardIdentifier), CliError> {
+		let signed_call = TrustedCallSigned::from_hex(&self.signed_call)
+			.map_err(|e| offline_signing_error(format!("{:?}", e)))?;
+		Ok((signed_call, decode_mrenclave(&self.mrenclave)?, decode_shard(&self.shard)?))
+	}
+}
+
+pub(crate) fn read_json_file<T: DeserializeOwned>(path: &Path) -> Result<T, CliError> {
+	let content = fs::read_to_string(path).map_err(offline_signing_error)?;
+	serde_json::from_str(&content).map_err(offline_signing_error)
+}
+
+pub(crate) fn write_json_file<T: Serialize>(path: &Path, value: &T) -> Result<(), CliError> {
+	let content = serde_json::to_string_pretty(value).map_err(offline_signing_error)?;
+	fs::write(path, content).map_err(offline_signing_error)
+}
+
+fn decode_mrenclave(mrenclave_base58: &str) -> Result<[u8; 32], CliError> {
+	let bytes = mrenclave_base58
+		.from_base58()
+		.map_err(|e| offline_signing_error(format!("{:?}", e)))?;
+	bytes
+		.try_into()
+		.map_err(|_| CliError::OfflineSigning { msg: "mrenclave has to be 32 bytes".to_string() })
+}
+
+fn decode_shard(shard_base58: &str) -> Result<ShardIdentifier, CliError> {
+	let bytes = shard_base58
+		.from_base58()
+		.map_err(|e| offline_signing_error(format!("{:?}", e)))?;
+	if bytes.len() != 32 {
+		return Err(CliError::OfflineSigning { msg: "shard has to be 32 bytes".to_string() })
+	}
+	Ok(ShardIdentifier::from_slice(&bytes))
+}
+
+fn offline_signing_error<E: ToString>(e: E) -> CliError {
+	CliError::OfflineSigning { msg: e.to_string() }
+}
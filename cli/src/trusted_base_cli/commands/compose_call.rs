@@ -0,0 +1,102 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	offline_signing::{write_json_file, UnsignedCallFile},
+	trusted_cli::TrustedCli,
+	trusted_command_utils::{get_accountid_from_str, get_identifiers},
+	Cli, CliResult, CliResultOk,
+};
+use ita_stf::{Index, TrustedCall};
+use log::*;
+use my_node_runtime::Balance;
+use std::path::PathBuf;
+
+/// Composes an unsigned trusted call and exports it, such that it can be signed
+/// offline with `trusted sign` and later be submitted with `trusted submit-signed`.
+///
+/// Nothing is sent to the worker, hence the nonce has to be supplied explicitly
+/// (it can be queried with `trusted nonce`).
+#[derive(Parser)]
+pub struct ComposeCallCommand {
+	/// nonce of the sender account in the layer two state
+	#[clap(long)]
+	nonce: Index,
+
+	/// file the unsigned call is exported to
+	#[clap(long, default_value = "call.json")]
+	export: PathBuf,
+
+	#[clap(subcommand)]
+	call: ComposableCall,
+}
+
+#[derive(Subcommand)]
+pub enum ComposableCall {
+	/// send funds from one incognito account to another
+	Transfer {
+		/// sender's AccountId in ss58check format
+		from: String,
+
+		/// recipient's AccountId in ss58check format
+		to: String,
+
+		/// amount to be transferred
+		amount: Balance,
+	},
+
+	/// transfer funds from an incognito account to a parentchain account
+	UnshieldFunds {
+		/// sender's incognito AccountId in ss58check format
+		from: String,
+
+		/// recipient's parentchain AccountId in ss58check format
+		to: String,
+
+		/// amount to be transferred
+		amount: Balance,
+	},
+}
+
+impl ComposeCallCommand {
+	pub(crate) fn run(&self, _cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
+		let (mrenclave, shard) = get_identifiers(trusted_args);
+
+		let call = match &self.call {
+			ComposableCall::Transfer { from, to, amount } => TrustedCall::balance_transfer(
+				get_accountid_from_str(from),
+				get_accountid_from_str(to),
+				*amount,
+			),
+			ComposableCall::UnshieldFunds { from, to, amount } => TrustedCall::balance_unshield(
+				get_accountid_from_str(from),
+				get_accountid_from_str(to),
+				*amount,
+				shard,
+			),
+		};
+		info!("composed trusted call: {:?}", call);
+
+		let unsigned_call = UnsignedCallFile::new(&call, self.nonce, &mrenclave, &shard);
+		write_json_file(&self.export, &unsigned_call)?;
+		println!("exported unsigned call to {}", self.export.display());
+		println!("signer: {}", unsigned_call.signer);
+		println!("signing payload: {}", unsigned_call.signing_payload);
+
+		Ok(CliResultOk::None)
+	}
+}
@@ -0,0 +1,93 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	trusted_cli::TrustedCli, trusted_command_utils::get_pair_from_str,
+	trusted_operation::perform_trusted_operation, Cli, CliError, CliResult, CliResultOk,
+};
+use codec::Decode;
+use ita_stf::{
+	confidential_events::{ConfidentialEventRecord, EncryptedEventRecord},
+	default_getter_expiry, Getter, TrustedCallSigned, TrustedGetter,
+};
+use itp_sgx_crypto::ecies::{secret_scalar, AccountKeyScheme};
+use itp_stf_primitives::{
+	pagination::{Page, PageRequest},
+	types::{KeyPair, TrustedOperation},
+};
+use sp_core::Pair;
+use std::boxed::Box;
+
+#[derive(Parser)]
+pub struct ConfidentialEventsCommand {
+	/// AccountId in ss58check format
+	account: String,
+
+	/// Only return this many events, continue with the printed cursor
+	#[clap(long)]
+	limit: Option<u32>,
+
+	/// Cursor returned with the previous page
+	#[clap(long, requires = "limit")]
+	cursor: Option<u32>,
+}
+
+impl ConfidentialEventsCommand {
+	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
+		let who = get_pair_from_str(trusted_args, &self.account);
+		// The events are encrypted to the account, the CLI uses sr25519 accounts only.
+		let public = who.public().0;
+		let secret = secret_scalar(AccountKeyScheme::Sr25519, &who.to_raw_vec())
+			.map_err(|e| CliError::TrustedOp { msg: format!("invalid account secret: {:?}", e) })?;
+		let getter = match self.limit {
+			Some(limit) => TrustedGetter::confidential_events_page(
+				who.public().into(),
+				PageRequest::new(self.cursor, limit),
+			),
+			None => TrustedGetter::confidential_events(who.public().into()),
+		};
+		let top = TrustedOperation::<TrustedCallSigned, Getter>::get(Getter::trusted(
+			getter.sign(&KeyPair::Sr25519(Box::new(who)), default_getter_expiry()),
+		));
+		let encoded = perform_trusted_operation(cli, trusted_args, &top)?.unwrap_or_default();
+		let page = match self.limit {
+			Some(_) => Page::<EncryptedEventRecord>::decode(&mut encoded.as_slice()).ok(),
+			None => Vec::<EncryptedEventRecord>::decode(&mut encoded.as_slice())
+				.ok()
+				.map(|items| Page { next: None, items }),
+		}
+		.ok_or_else(|| CliError::TrustedOp {
+			msg: "could not decode confidential events".into(),
+		})?;
+
+		for encrypted in page.items {
+			match ConfidentialEventRecord::decrypt(
+				AccountKeyScheme::Sr25519,
+				secret,
+				&public,
+				&encrypted,
+			) {
+				Some(record) => println!("block {}: {:?}", record.block_number, record.event),
+				None => println!("event that could not be decrypted"),
+			}
+		}
+		if let Some(cursor) = page.next {
+			println!("more events available, continue with --cursor {}", cursor);
+		}
+		Ok(CliResultOk::None)
+	}
+}
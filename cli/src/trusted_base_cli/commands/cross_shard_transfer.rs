@@ -0,0 +1,84 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	get_layer_two_nonce,
+	trusted_cli::TrustedCli,
+	trusted_command_utils::{get_accountid_from_str, get_identifiers, get_pair_from_str},
+	trusted_operation::perform_trusted_operation,
+	Cli, CliResult, CliResultOk,
+};
+use base58::FromBase58;
+use codec::Decode;
+use ita_stf::{Getter, Index, TrustedCall, TrustedCallSigned};
+use itp_stf_primitives::{
+	traits::TrustedCallSigning,
+	types::{KeyPair, ShardIdentifier, TrustedOperation},
+};
+use log::*;
+use my_node_runtime::Balance;
+use sp_core::{crypto::Ss58Codec, Pair};
+use std::boxed::Box;
+
+#[derive(Parser)]
+pub struct CrossShardTransferCommand {
+	/// sender's AccountId in ss58check format
+	from: String,
+
+	/// recipient's AccountId in ss58check format, on the destination shard
+	to: String,
+
+	/// amount to be transferred
+	amount: Balance,
+
+	/// destination shard, base58 encoded. Messages to shards of other workers have to be relayed
+	/// with `relay-cross-shard-message`
+	destination_shard: String,
+}
+
+impl CrossShardTransferCommand {
+	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
+		let from = get_pair_from_str(trusted_args, &self.from);
+		let to = get_accountid_from_str(&self.to);
+		let destination = ShardIdentifier::from_slice(
+			&self
+				.destination_shard
+				.from_base58()
+				.expect("destination shard has to be base58 encoded"),
+		);
+		info!("from ss58 is {}", from.public().to_ss58check());
+		info!("to ss58 is {}", to.to_ss58check());
+
+		let (mrenclave, shard) = get_identifiers(trusted_args);
+		let nonce = get_layer_two_nonce!(from, cli, trusted_args);
+		println!(
+			"send trusted call cross-shard transfer from {} to {} on shard {}: {}, nonce: {}",
+			from.public(),
+			to,
+			self.destination_shard,
+			self.amount,
+			nonce
+		);
+		let top: TrustedOperation<TrustedCallSigned, Getter> =
+			TrustedCall::cross_shard_transfer(from.public().into(), destination, to, self.amount)
+				.sign(&KeyPair::Sr25519(Box::new(from)), nonce, &mrenclave, &shard)
+				.into_trusted_operation(trusted_args.direct);
+		let res = perform_trusted_operation(cli, trusted_args, &top).map(|_| CliResultOk::None)?;
+		info!("trusted call cross-shard transfer executed");
+		Ok(res)
+	}
+}
@@ -0,0 +1,86 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	trusted_cli::TrustedCli, trusted_command_utils::get_pair_from_str,
+	trusted_operation::perform_trusted_operation, Cli, CliError, CliResult, CliResultOk,
+};
+use codec::{Decode, Encode};
+use ita_stf::{default_getter_expiry, Getter, TrustedCallSigned, TrustedGetter};
+use itp_stf_primitives::{
+	account_export::AccountDataExport,
+	types::{KeyPair, TrustedOperation},
+};
+use sgx_crypto_helper::{rsa3072::Rsa3072KeyPair, RsaKeyPair};
+use sp_core::Pair;
+use std::{boxed::Box, fs};
+
+/// Exports all data the shard stores for the account. The worker encrypts the export to a
+/// freshly generated key, which never leaves this command.
+#[derive(Parser)]
+pub struct ExportAccountDataCommand {
+	/// AccountId in ss58check format
+	account: String,
+
+	/// write the SCALE encoded export to this file instead of printing it
+	#[clap(long)]
+	output: Option<String>,
+}
+
+impl ExportAccountDataCommand {
+	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
+		let key_pair = Rsa3072KeyPair::new().map_err(|e| CliError::TrustedOp {
+			msg: format!("could not generate the export key: {:?}", e),
+		})?;
+		let pubkey = key_pair
+			.export_pubkey()
+			.ok()
+			.and_then(|pubkey| serde_json::to_vec(&pubkey).ok())
+			.ok_or_else(|| CliError::TrustedOp { msg: "could not encode the export key".into() })?;
+
+		let who = get_pair_from_str(trusted_args, &self.account);
+		let top = TrustedOperation::<TrustedCallSigned, Getter>::get(Getter::trusted(
+			TrustedGetter::account_data_export(who.public().into(), pubkey)
+				.sign(&KeyPair::Sr25519(Box::new(who)), default_getter_expiry()),
+		));
+		let encrypted = perform_trusted_operation(cli, trusted_args, &top)?
+			.ok_or_else(|| CliError::TrustedOp { msg: "no account data returned".into() })?;
+
+		let mut decrypted = Vec::new();
+		key_pair
+			.decrypt_buffer(&encrypted, &mut decrypted)
+			.map_err(|e| CliError::TrustedOp {
+				msg: format!("could not decrypt the export: {:?}", e),
+			})?;
+		let export = AccountDataExport::decode(&mut decrypted.as_slice()).map_err(|e| {
+			CliError::TrustedOp { msg: format!("could not decode the export: {:?}", e) }
+		})?;
+
+		if let Some(output) = &self.output {
+			fs::write(output, export.encode()).map_err(|e| CliError::TrustedOp {
+				msg: format!("could not write {}: {:?}", output, e),
+			})?;
+			return Ok(CliResultOk::None)
+		}
+		for pallet in export.pallets {
+			for entry in pallet.entries {
+				println!("{}::{}: 0x{}", pallet.pallet, entry.name, hex::encode(entry.value));
+			}
+		}
+		Ok(CliResultOk::None)
+	}
+}
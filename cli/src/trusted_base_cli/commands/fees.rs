@@ -0,0 +1,71 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	offline_signing::{read_json_file, UnsignedCallFile},
+	trusted_cli::TrustedCli,
+	trusted_operation::perform_trusted_operation,
+	Cli, CliError, CliResult, CliResultOk,
+};
+use codec::Decode;
+use ita_stf::{Getter, PublicGetter, TrustedCallSigned};
+use itp_stf_primitives::{
+	fees::{FeeBalance, FeeConfig, FeeMultiplier},
+	types::TrustedOperation,
+};
+use std::path::PathBuf;
+
+/// Queries the fee config of the shard and estimates the fee of a call before it's submitted.
+#[derive(Parser)]
+pub struct FeesCommand {
+	/// estimate the fee of the call exported by `trusted compose-call` to this file
+	#[clap(long)]
+	call: Option<PathBuf>,
+}
+
+impl FeesCommand {
+	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
+		let top = TrustedOperation::<TrustedCallSigned, Getter>::get(Getter::public(
+			PublicGetter::fee_config,
+		));
+		let (config, multiplier) = perform_trusted_operation(cli, trusted_args, &top)?
+			.and_then(|encoded| <(FeeConfig, FeeMultiplier)>::decode(&mut encoded.as_slice()).ok())
+			.ok_or_else(|| CliError::TrustedOp { msg: "could not decode fee config".into() })?;
+
+		println!("base fee:       {}", config.base_fee);
+		println!("fee per byte:   {}", config.fee_per_byte);
+		println!("fee per micros: {}", config.fee_per_micros);
+		println!("multiplier:     {}", multiplier);
+		println!("destination:    {:?}", config.destination);
+
+		let path = match &self.call {
+			Some(path) => path,
+			None => return Ok(CliResultOk::None),
+		};
+		let unsigned_call_file: UnsignedCallFile = read_json_file(path)?;
+		let (call, ..) = unsigned_call_file.decode_and_verify()?;
+		let top = TrustedOperation::<TrustedCallSigned, Getter>::get(Getter::public(
+			PublicGetter::fee_estimate(call),
+		));
+		let fee = perform_trusted_operation(cli, trusted_args, &top)?
+			.and_then(|encoded| FeeBalance::decode(&mut encoded.as_slice()).ok())
+			.ok_or_else(|| CliError::TrustedOp { msg: "could not decode fee estimate".into() })?;
+
+		println!("estimated fee:  {}", fee);
+		Ok(CliResultOk::Balance { balance: fee })
+	}
+}
@@ -23,7 +23,7 @@ use crate::{
 use codec::Decode;
 
 use itc_rpc_client::direct_client::DirectApi;
-use itp_rpc::{RpcRequest, RpcResponse, RpcReturnValue};
+use itp_rpc::{RpcErrorResponse, RpcRequest, RpcResponse, RpcReturnValue};
 
 use itp_types::{AccountId, DirectRequestStatus};
 use itp_utils::FromHexPrefixed;
@@ -41,6 +41,10 @@ impl GetShardVaultCommand {
 		let jsonrpc_call: String = RpcRequest::compose_jsonrpc_call(rpc_method, vec![]).unwrap();
 		let rpc_response_str = direct_api.get(&jsonrpc_call).unwrap();
 		// Decode RPC response.
+		if let Ok(error_response) = serde_json::from_str::<RpcErrorResponse>(&rpc_response_str) {
+			println!("[Error {}] {}", error_response.error.code, error_response.error.message);
+			return Err(CliError::WorkerRpcApi { msg: error_response.error.message })
+		}
 		let rpc_response: RpcResponse = serde_json::from_str(&rpc_response_str)
 			.map_err(|err| CliError::WorkerRpcApi { msg: err.to_string() })?;
 		let rpc_return_value = RpcReturnValue::from_hex(&rpc_response.result)
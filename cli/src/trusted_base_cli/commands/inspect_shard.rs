@@ -0,0 +1,105 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	command_utils::get_worker_api_direct, trusted_cli::TrustedCli, Cli, CliError, CliResult,
+	CliResultOk,
+};
+use base58::{FromBase58, ToBase58};
+use codec::{Decode, Encode};
+use itc_rpc_client::direct_client::{DirectApi, DirectClient};
+use itp_rpc::{RpcRequest, RpcResponse, RpcReturnValue};
+use itp_types::{DirectRequestStatus, ShardIdentifier, ShardInfo};
+use itp_utils::FromHexPrefixed;
+use log::*;
+use sp_core::crypto::Ss58Codec;
+
+#[derive(Parser)]
+pub struct InspectShardCommand {
+	/// shard identifier (base58 encoded)
+	shard: String,
+}
+
+impl InspectShardCommand {
+	pub(crate) fn run(&self, cli: &Cli, _trusted_args: &TrustedCli) -> CliResult {
+		let shard_vec = self
+			.shard
+			.from_base58()
+			.map_err(|_| CliError::WorkerRpcApi { msg: "shard has to be base58 encoded".into() })?;
+		let shard = ShardIdentifier::decode(&mut shard_vec.as_slice())
+			.map_err(|err| CliError::WorkerRpcApi { msg: err.to_string() })?;
+
+		let direct_api = get_worker_api_direct(cli);
+		let shard_info = get_shard_info(&direct_api, &shard)?;
+		direct_api.close().unwrap();
+
+		print_shard_info(&shard_info);
+		Ok(CliResultOk::ShardInfos { shard_infos: vec![shard_info] })
+	}
+}
+
+pub(crate) fn get_shard_info(
+	direct_api: &DirectClient,
+	shard: &ShardIdentifier,
+) -> Result<ShardInfo, CliError> {
+	let rpc_method = "author_getShardInfo".to_owned();
+	let jsonrpc_call: String =
+		RpcRequest::compose_jsonrpc_call(rpc_method, vec![shard.encode().to_base58()])
+			.map_err(|err| CliError::WorkerRpcApi { msg: err.to_string() })?;
+	let rpc_response_str = direct_api
+		.get(&jsonrpc_call)
+		.map_err(|err| CliError::WorkerRpcApi { msg: err.to_string() })?;
+	// Decode RPC response.
+	let rpc_response: RpcResponse = serde_json::from_str(&rpc_response_str)
+		.map_err(|err| CliError::WorkerRpcApi { msg: err.to_string() })?;
+	let rpc_return_value = RpcReturnValue::from_hex(&rpc_response.result)
+		// Replace with `inspect_err` once it's stable.
+		.map_err(|err| {
+			error!("Failed to decode RpcReturnValue: {:?}", err);
+			CliError::WorkerRpcApi { msg: "failed to decode RpcReturnValue".to_string() }
+		})?;
+
+	if rpc_return_value.status == DirectRequestStatus::Error {
+		let msg = String::decode(&mut rpc_return_value.value.as_slice())
+			.unwrap_or_else(|_| "rpc error".to_string());
+		println!("[Error] {}", msg);
+		return Err(CliError::WorkerRpcApi { msg })
+	}
+
+	ShardInfo::decode(&mut rpc_return_value.value.as_slice())
+		// Replace with `inspect_err` once it's stable.
+		.map_err(|err| {
+			error!("Failed to decode shard info: {:?}", err);
+			CliError::WorkerRpcApi { msg: err.to_string() }
+		})
+}
+
+pub(crate) fn print_shard_info(shard_info: &ShardInfo) {
+	println!("shard:                       {}", shard_info.shard.encode().to_base58());
+	println!("state hash:                  {:?}", shard_info.state_hash);
+	match (shard_info.last_sidechain_block_number, shard_info.last_sidechain_block_hash) {
+		(Some(number), Some(hash)) =>
+			println!("last sidechain block:        #{} ({:?})", number, hash),
+		_ => println!("last sidechain block:        none"),
+	}
+	println!("enclave account nonce:       {}", shard_info.enclave_account_nonce);
+	match &shard_info.shard_vault {
+		Some(vault) => println!("shard vault:                 {}", vault.to_ss58check()),
+		None => println!("shard vault:                 none"),
+	}
+	println!("pending unshield payouts:    {}", shard_info.pending_unshield_payouts);
+}
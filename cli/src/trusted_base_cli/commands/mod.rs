@@ -1,7 +1,30 @@
+pub mod asset_balance;
 pub mod balance;
+pub mod compose_call;
+pub mod confidential_events;
+pub mod cross_shard_transfer;
+pub mod export_account_data;
+pub mod fees;
 pub mod get_shard;
 pub mod get_shard_vault;
+pub mod inspect_shard;
+pub mod list_shards;
 pub mod nonce;
+pub mod register_session_key;
+pub mod relay_cross_shard_message;
+pub mod rent_status;
+pub mod reserves;
+pub mod revoke_session_key;
+pub mod schedule_transfer;
 pub mod set_balance;
+pub mod set_fee_config;
+pub mod set_fee_multiplier;
+pub mod set_shard_access_policy;
+pub mod set_shard_maintenance_mode;
+pub mod set_shard_member;
+pub mod sign_call;
+pub mod submit_signed_call;
 pub mod transfer;
+pub mod transfer_asset;
+pub mod unshield_asset;
 pub mod unshield_funds;
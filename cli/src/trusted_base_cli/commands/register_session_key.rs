@@ -0,0 +1,86 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	get_layer_two_nonce,
+	trusted_cli::TrustedCli,
+	trusted_command_utils::{get_accountid_from_str, get_identifiers, get_pair_from_str},
+	trusted_operation::perform_trusted_operation,
+	Cli, CliResult, CliResultOk,
+};
+use codec::Decode;
+use ita_stf::{Getter, Index, TrustedCall, TrustedCallSigned};
+use itp_stf_primitives::{
+	session_keys::SessionKey,
+	traits::TrustedCallSigning,
+	types::{KeyPair, TrustedOperation},
+};
+use log::*;
+use sp_core::{crypto::Ss58Codec, Pair};
+use std::{
+	boxed::Box,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Parser)]
+pub struct RegisterSessionKeyCommand {
+	/// owner's AccountId in ss58check format
+	owner: String,
+
+	/// delegate's AccountId in ss58check format
+	delegate: String,
+
+	/// trusted calls the delegate may submit on behalf of the owner, comma separated,
+	/// e.g. balance_transfer
+	#[clap(long, use_value_delimiter = true, required = true)]
+	calls: Vec<String>,
+
+	/// seconds from now until the session key expires
+	#[clap(long, default_value = "3600")]
+	valid_for: u64,
+}
+
+impl RegisterSessionKeyCommand {
+	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
+		let owner = get_pair_from_str(trusted_args, &self.owner);
+		let delegate = get_accountid_from_str(&self.delegate);
+		info!("owner ss58 is {}", owner.public().to_ss58check());
+		info!("delegate ss58 is {}", delegate.to_ss58check());
+
+		let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+		let session_key = SessionKey::new(
+			now + self.valid_for * 1000,
+			self.calls.iter().map(|name| name.as_bytes().to_vec()).collect(),
+		);
+
+		let (mrenclave, shard) = get_identifiers(trusted_args);
+		let nonce = get_layer_two_nonce!(owner, cli, trusted_args);
+		println!(
+			"send trusted call session-key-register({}, {}, {:?}) until {}, nonce: {}",
+			owner.public(),
+			delegate,
+			self.calls,
+			session_key.valid_until,
+			nonce
+		);
+		let top: TrustedOperation<TrustedCallSigned, Getter> =
+			TrustedCall::session_key_register(owner.public().into(), delegate, session_key)
+				.sign(&KeyPair::Sr25519(Box::new(owner)), nonce, &mrenclave, &shard)
+				.into_trusted_operation(trusted_args.direct);
+		Ok(perform_trusted_operation(cli, trusted_args, &top).map(|_| CliResultOk::None)?)
+	}
+}
@@ -0,0 +1,98 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	command_utils::get_worker_api_direct, trusted_cli::TrustedCli, Cli, CliError, CliResult,
+	CliResultOk,
+};
+use codec::Decode;
+use itc_rpc_client::direct_client::{DirectApi, DirectClient};
+use itp_rpc::{RpcRequest, RpcResponse, RpcReturnValue};
+use itp_stf_primitives::cross_shard::{CrossShardProof, MessageNonce};
+use itp_types::DirectRequestStatus;
+use itp_utils::{FromHexPrefixed, ToHexPrefixed};
+use log::*;
+
+/// Fetches the proof of a cross-shard message from the worker of the source shard (`-u`, `-P`)
+/// and submits it to the worker of the destination shard.
+///
+/// The message can only be relayed once the source shard committed to it and the destination
+/// worker anchored that commitment from the parentchain.
+#[derive(Parser)]
+pub struct RelayCrossShardMessageCommand {
+	/// source shard (base58 encoded)
+	source_shard: String,
+
+	/// destination shard (base58 encoded)
+	destination_shard: String,
+
+	/// nonce of the message from the source to the destination shard
+	nonce: MessageNonce,
+
+	/// trusted websocket url of the worker of the destination shard, including the port,
+	/// e.g. wss://127.0.0.1:2500
+	#[clap(long)]
+	destination_worker: String,
+}
+
+impl RelayCrossShardMessageCommand {
+	pub(crate) fn run(&self, cli: &Cli, _trusted_args: &TrustedCli) -> CliResult {
+		let source_api = get_worker_api_direct(cli);
+		let encoded_proof = request(
+			&source_api,
+			"author_getCrossShardMessageProof",
+			vec![self.source_shard.clone(), self.destination_shard.clone(), self.nonce.to_string()],
+		)?;
+		source_api.close().unwrap();
+		let proof = CrossShardProof::decode(&mut encoded_proof.as_slice())
+			.map_err(|err| CliError::WorkerRpcApi { msg: err.to_string() })?;
+		info!("relaying cross-shard message with commitment {:?}", proof.commitment);
+
+		let destination_api = DirectClient::new(self.destination_worker.clone());
+		request(&destination_api, "author_submitCrossShardMessageProof", vec![proof.to_hex()])?;
+		destination_api.close().unwrap();
+
+		println!(
+			"relayed cross-shard message {} of commitment {} from shard {}",
+			self.nonce, proof.commitment.index, self.source_shard
+		);
+		Ok(CliResultOk::None)
+	}
+}
+
+fn request(
+	direct_api: &DirectClient,
+	method: &str,
+	params: Vec<String>,
+) -> Result<Vec<u8>, CliError> {
+	let jsonrpc_call: String = RpcRequest::compose_jsonrpc_call(method.to_owned(), params).unwrap();
+	let rpc_response_str = direct_api.get(&jsonrpc_call).unwrap();
+	let rpc_response: RpcResponse = serde_json::from_str(&rpc_response_str)
+		.map_err(|err| CliError::WorkerRpcApi { msg: err.to_string() })?;
+	let rpc_return_value = RpcReturnValue::from_hex(&rpc_response.result)
+		// Replace with `inspect_err` once it's stable.
+		.map_err(|err| {
+			error!("Failed to decode RpcReturnValue: {:?}", err);
+			CliError::WorkerRpcApi { msg: "failed to decode RpcReturnValue".to_string() }
+		})?;
+
+	if rpc_return_value.status == DirectRequestStatus::Error {
+		println!("[Error] {}", String::decode(&mut rpc_return_value.value.as_slice()).unwrap());
+		return Err(CliError::WorkerRpcApi { msg: "rpc error".to_string() })
+	}
+	Ok(rpc_return_value.value)
+}
@@ -0,0 +1,53 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	trusted_cli::TrustedCli, trusted_command_utils::get_pair_from_str,
+	trusted_operation::perform_trusted_operation, Cli, CliError, CliResult, CliResultOk,
+};
+use codec::Decode;
+use ita_stf::{default_getter_expiry, rent::RentStatus, Getter, TrustedCallSigned, TrustedGetter};
+use itp_stf_primitives::types::{KeyPair, TrustedOperation};
+use sp_core::Pair;
+use std::boxed::Box;
+
+#[derive(Parser)]
+pub struct RentStatusCommand {
+	/// AccountId in ss58check format
+	account: String,
+}
+
+impl RentStatusCommand {
+	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
+		let who = get_pair_from_str(trusted_args, &self.account);
+		let top = TrustedOperation::<TrustedCallSigned, Getter>::get(Getter::trusted(
+			TrustedGetter::rent_status(who.public().into())
+				.sign(&KeyPair::Sr25519(Box::new(who)), default_getter_expiry()),
+		));
+		let status = perform_trusted_operation(cli, trusted_args, &top)?
+			.and_then(|encoded| RentStatus::decode(&mut encoded.as_slice()).ok())
+			.ok_or_else(|| CliError::TrustedOp { msg: "could not decode rent status".into() })?;
+
+		println!("storage bytes:   {}", status.storage_bytes);
+		println!("storage deposit: {}", status.storage_deposit);
+		println!("rent paid until: {}", status.rent_paid_until);
+		println!("rent due:        {}", status.rent_due);
+		println!("free balance:    {}", status.free_balance);
+		println!("reapable:        {}", status.reapable);
+		Ok(CliResultOk::None)
+	}
+}
@@ -0,0 +1,69 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	command_utils::get_chain_api, trusted_cli::TrustedCli,
+	trusted_operation::perform_trusted_operation, Cli, CliError, CliResult, CliResultOk,
+};
+use codec::Decode;
+use ita_stf::{Getter, PublicGetter, TrustedCallSigned};
+use itp_stf_primitives::{reserves::ReservesReport, types::TrustedOperation};
+use itp_types::AccountInfo;
+use sp_core::crypto::Ss58Codec;
+use substrate_api_client::GetStorage;
+
+/// Queries the latest reserves report of the shard and checks it against the balance the vault
+/// held on the parentchain at the reported block.
+#[derive(Parser)]
+pub struct ReservesCommand {}
+
+impl ReservesCommand {
+	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
+		let top = TrustedOperation::<TrustedCallSigned, Getter>::get(Getter::public(
+			PublicGetter::reserves_report,
+		));
+		let report = perform_trusted_operation(cli, trusted_args, &top)?
+			.and_then(|encoded| ReservesReport::decode(&mut encoded.as_slice()).ok())
+			.ok_or_else(|| CliError::TrustedOp { msg: "no reserves report available".into() })?;
+
+		println!("report:            {} ({:?})", report.index, report.hash());
+		println!("sidechain block:   {}", report.sidechain_block_number);
+		println!(
+			"parentchain block: {} ({:?})",
+			report.parentchain_block_number, report.parentchain_block_hash
+		);
+		println!("vault:             {}", report.vault.to_ss58check());
+		println!("accounts:          {}", report.account_count);
+		println!("liabilities:       {}", report.total_liabilities());
+
+		let api = get_chain_api(cli);
+		let vault_balance = api
+			.get_storage_map::<_, AccountInfo>(
+				"System",
+				"Account",
+				report.vault.clone(),
+				Some(report.parentchain_block_hash),
+			)
+			.map_err(|e| CliError::TrustedOp {
+				msg: format!("could not query the vault balance: {:?}", e),
+			})?
+			.map_or(0, |info| info.data.free);
+		println!("vault balance:     {}", vault_balance);
+		println!("fully backed:      {}", report.is_backed_by(vault_balance));
+		Ok(CliResultOk::Balance { balance: report.total_liabilities() })
+	}
+}
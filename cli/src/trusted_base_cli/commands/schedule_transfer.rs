@@ -0,0 +1,88 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	get_layer_two_nonce,
+	trusted_cli::TrustedCli,
+	trusted_command_utils::{get_accountid_from_str, get_identifiers, get_pair_from_str},
+	trusted_operation::perform_trusted_operation,
+	Cli, CliResult, CliResultOk,
+};
+use codec::Decode;
+use ita_stf::{scheduler::ScheduledAt, Getter, Index, TrustedCall, TrustedCallSigned};
+use itp_stf_primitives::{
+	traits::TrustedCallSigning,
+	types::{KeyPair, TrustedOperation},
+};
+use log::*;
+use my_node_runtime::Balance;
+use sp_core::{crypto::Ss58Codec, Pair};
+use std::boxed::Box;
+
+#[derive(Parser)]
+pub struct ScheduleTransferCommand {
+	/// sender's AccountId in ss58check format
+	from: String,
+
+	/// recipient's AccountId in ss58check format
+	to: String,
+
+	/// amount to be transferred
+	amount: Balance,
+
+	/// execute the transfer with the sidechain block of this number
+	#[clap(long, conflicts_with = "at_timestamp", required_unless_present = "at_timestamp")]
+	at_block: Option<u64>,
+
+	/// execute the transfer with the first sidechain block at or after this unix timestamp (millis)
+	#[clap(long)]
+	at_timestamp: Option<u64>,
+}
+
+impl ScheduleTransferCommand {
+	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
+		let from = get_pair_from_str(trusted_args, &self.from);
+		let to = get_accountid_from_str(&self.to);
+		info!("from ss58 is {}", from.public().to_ss58check());
+		info!("to ss58 is {}", to.to_ss58check());
+
+		let when = match (self.at_block, self.at_timestamp) {
+			(Some(block_number), _) => ScheduledAt::SidechainBlock(block_number),
+			(None, Some(timestamp)) => ScheduledAt::Timestamp(timestamp),
+			(None, None) => unreachable!("enforced by clap"),
+		};
+
+		let (mrenclave, shard) = get_identifiers(trusted_args);
+		let nonce = get_layer_two_nonce!(from, cli, trusted_args);
+		println!(
+			"schedule trusted call transfer from {} to {}: {} at {:?}, nonce: {}",
+			from.public(),
+			to,
+			self.amount,
+			when,
+			nonce
+		);
+		let transfer = TrustedCall::balance_transfer(from.public().into(), to, self.amount);
+		let top: TrustedOperation<TrustedCallSigned, Getter> =
+			TrustedCall::schedule_call(from.public().into(), when, Box::new(transfer))
+				.sign(&KeyPair::Sr25519(Box::new(from)), nonce, &mrenclave, &shard)
+				.into_trusted_operation(trusted_args.direct);
+		let res = perform_trusted_operation(cli, trusted_args, &top).map(|_| CliResultOk::None)?;
+		info!("trusted call schedule transfer executed");
+		Ok(res)
+	}
+}
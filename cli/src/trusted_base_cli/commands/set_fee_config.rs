@@ -0,0 +1,78 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	get_layer_two_nonce,
+	trusted_cli::TrustedCli,
+	trusted_command_utils::{get_accountid_from_str, get_identifiers, get_pair_from_str},
+	trusted_operation::perform_trusted_operation,
+	Cli, CliResult, CliResultOk,
+};
+use codec::Decode;
+use ita_stf::{Getter, Index, TrustedCall, TrustedCallSigned};
+use itp_stf_primitives::{
+	fees::{FeeBalance, FeeConfig, FeeDestination},
+	traits::TrustedCallSigning,
+	types::{KeyPair, TrustedOperation},
+};
+use sp_core::Pair;
+use std::boxed::Box;
+
+#[derive(Parser)]
+pub struct SetFeeConfigCommand {
+	/// fee every call pays
+	#[clap(long, default_value = "0")]
+	base_fee: FeeBalance,
+
+	/// fee per byte of the encoded call
+	#[clap(long, default_value = "0")]
+	fee_per_byte: FeeBalance,
+
+	/// fee per microsecond of the call's weight
+	#[clap(long, default_value = "0")]
+	fee_per_micros: FeeBalance,
+
+	/// pay the fees to this treasury AccountId in ss58check format, instead of burning them
+	#[clap(long)]
+	treasury: Option<String>,
+}
+
+impl SetFeeConfigCommand {
+	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
+		let signer = get_pair_from_str(trusted_args, "//Alice");
+		let destination = match &self.treasury {
+			Some(treasury) => FeeDestination::Treasury(get_accountid_from_str(treasury)),
+			None => FeeDestination::Burn,
+		};
+		let config = FeeConfig {
+			base_fee: self.base_fee,
+			fee_per_byte: self.fee_per_byte,
+			fee_per_micros: self.fee_per_micros,
+			destination,
+		};
+
+		println!("send trusted call fees-set-config({:?})", config);
+
+		let (mrenclave, shard) = get_identifiers(trusted_args);
+		let nonce = get_layer_two_nonce!(signer, cli, trusted_args);
+		let top: TrustedOperation<TrustedCallSigned, Getter> =
+			TrustedCall::fees_set_config(signer.public().into(), config)
+				.sign(&KeyPair::Sr25519(Box::new(signer)), nonce, &mrenclave, &shard)
+				.into_trusted_operation(trusted_args.direct);
+		Ok(perform_trusted_operation(cli, trusted_args, &top).map(|_| CliResultOk::None)?)
+	}
+}
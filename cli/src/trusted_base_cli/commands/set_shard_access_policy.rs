@@ -0,0 +1,57 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	get_layer_two_nonce,
+	trusted_cli::TrustedCli,
+	trusted_command_utils::{get_identifiers, get_pair_from_str, parse_access_policy},
+	trusted_operation::perform_trusted_operation,
+	Cli, CliResult, CliResultOk,
+};
+use codec::Decode;
+use ita_stf::{Getter, Index, TrustedCall, TrustedCallSigned};
+use itp_stf_primitives::{
+	shard_acl::AccessPolicy,
+	traits::TrustedCallSigning,
+	types::{KeyPair, TrustedOperation},
+};
+use sp_core::Pair;
+use std::boxed::Box;
+
+#[derive(Parser)]
+pub struct SetShardAccessPolicyCommand {
+	/// who may submit trusted calls to the shard: `open`, `allow-list` (members only)
+	/// or `role:<role id>` (members with this role only)
+	#[clap(parse(try_from_str = parse_access_policy))]
+	policy: AccessPolicy,
+}
+
+impl SetShardAccessPolicyCommand {
+	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
+		let signer = get_pair_from_str(trusted_args, "//Alice");
+
+		println!("send trusted call shard-acl-set-policy({:?})", self.policy);
+
+		let (mrenclave, shard) = get_identifiers(trusted_args);
+		let nonce = get_layer_two_nonce!(signer, cli, trusted_args);
+		let top: TrustedOperation<TrustedCallSigned, Getter> =
+			TrustedCall::shard_acl_set_policy(signer.public().into(), self.policy.clone())
+				.sign(&KeyPair::Sr25519(Box::new(signer)), nonce, &mrenclave, &shard)
+				.into_trusted_operation(trusted_args.direct);
+		Ok(perform_trusted_operation(cli, trusted_args, &top).map(|_| CliResultOk::None)?)
+	}
+}
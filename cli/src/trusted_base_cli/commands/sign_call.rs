@@ -0,0 +1,158 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+#[cfg(feature = "ledger")]
+use crate::ledger::{LedgerError, LedgerSigner, DEFAULT_LEDGER_APP_CLA, DEFAULT_LEDGER_COIN_TYPE};
+use crate::{
+	offline_signing::{read_json_file, write_json_file, SignedCallFile, UnsignedCallFile},
+	trusted_cli::TrustedCli,
+	Cli, CliError, CliResult, CliResultOk,
+};
+#[cfg(feature = "ledger")]
+use futures::executor::block_on;
+use ita_stf::{Index, TrustedCall, TrustedCallSigned};
+#[cfg(feature = "ledger")]
+use itp_stf_primitives::traits::{sign_with_external_signer, ExternalSigner};
+use itp_stf_primitives::{
+	traits::TrustedCallSigning,
+	types::{AccountId, KeyPair, ShardIdentifier},
+};
+use log::*;
+use sp_core::{crypto::Ss58Codec, sr25519, Pair};
+use std::{boxed::Box, path::PathBuf};
+
+/// Signs an unsigned call that was exported with `trusted compose-call`.
+///
+/// Does not connect to the worker or the parentchain, so it can be run on an air-gapped machine.
+/// With the `ledger` feature, the call can alternatively be signed on a Ledger device.
+#[derive(Parser)]
+pub struct SignCallCommand {
+	/// file containing the unsigned call
+	file: PathBuf,
+
+	/// secret URI (mnemonic, seed or dev account like //Alice) of the signer's sr25519 key
+	#[clap(long)]
+	suri: Option<String>,
+
+	/// sign with the substrate app on a connected Ledger device instead of a secret URI
+	#[cfg(feature = "ledger")]
+	#[clap(long, conflicts_with = "suri")]
+	ledger: bool,
+
+	/// BIP44 account of the Ledger key
+	#[cfg(feature = "ledger")]
+	#[clap(long, default_value_t = 0)]
+	ledger_account: u32,
+
+	/// BIP44 address index of the Ledger key
+	#[cfg(feature = "ledger")]
+	#[clap(long, default_value_t = 0)]
+	ledger_index: u32,
+
+	/// SLIP-0044 coin type used by the Ledger app
+	#[cfg(feature = "ledger")]
+	#[clap(long, default_value_t = DEFAULT_LEDGER_COIN_TYPE)]
+	ledger_coin_type: u32,
+
+	/// APDU class of the Ledger app
+	#[cfg(feature = "ledger")]
+	#[clap(long, default_value_t = DEFAULT_LEDGER_APP_CLA)]
+	ledger_cla: u8,
+
+	/// file the signed call is written to
+	#[clap(long, default_value = "call.signed.json")]
+	output: PathBuf,
+}
+
+impl SignCallCommand {
+	pub(crate) fn run(&self, _cli: &Cli, _trusted_args: &TrustedCli) -> CliResult {
+		let unsigned_call: UnsignedCallFile = read_json_file(&self.file)?;
+		let (call, mrenclave, shard) = unsigned_call.decode_and_verify()?;
+
+		#[cfg(feature = "ledger")]
+		let signed_call = if self.ledger {
+			self.sign_with_ledger(&call, unsigned_call.nonce, &mrenclave, &shard)?
+		} else {
+			self.sign_with_suri(&call, unsigned_call.nonce, &mrenclave, &shard)?
+		};
+		#[cfg(not(feature = "ledger"))]
+		let signed_call = self.sign_with_suri(&call, unsigned_call.nonce, &mrenclave, &shard)?;
+
+		let signed_call_file =
+			SignedCallFile::new(&signed_call, &unsigned_call.mrenclave, &unsigned_call.shard);
+		write_json_file(&self.output, &signed_call_file)?;
+		println!("wrote signed call to {}", self.output.display());
+
+		Ok(CliResultOk::None)
+	}
+
+	fn sign_with_suri(
+		&self,
+		call: &TrustedCall,
+		nonce: Index,
+		mrenclave: &[u8; 32],
+		shard: &ShardIdentifier,
+	) -> Result<TrustedCallSigned, CliError> {
+		let suri = self.suri.as_ref().ok_or_else(|| CliError::OfflineSigning {
+			msg: "either '--suri' or '--ledger' must be provided".to_string(),
+		})?;
+		let pair = sr25519::Pair::from_string(suri, None)
+			.map_err(|e| CliError::OfflineSigning { msg: format!("invalid suri: {:?}", e) })?;
+		ensure_signer(&pair.public().into(), call)?;
+
+		println!("signing trusted call: {:?}, nonce: {}", call, nonce);
+		Ok(call.sign(&KeyPair::Sr25519(Box::new(pair)), nonce, mrenclave, shard))
+	}
+
+	#[cfg(feature = "ledger")]
+	fn sign_with_ledger(
+		&self,
+		call: &TrustedCall,
+		nonce: Index,
+		mrenclave: &[u8; 32],
+		shard: &ShardIdentifier,
+	) -> Result<TrustedCallSigned, CliError> {
+		let ledger_error = |e: LedgerError| CliError::OfflineSigning { msg: e.to_string() };
+		let signer = LedgerSigner::new(
+			self.ledger_cla,
+			self.ledger_coin_type,
+			self.ledger_account,
+			self.ledger_index,
+		)
+		.map_err(ledger_error)?;
+		ensure_signer(&signer.account_id().map_err(ledger_error)?, call)?;
+
+		println!("signing trusted call: {:?}, nonce: {}", call, nonce);
+		println!("please confirm the payload on your Ledger device");
+		block_on(sign_with_external_signer(call, &signer, nonce, mrenclave, shard))
+			.map_err(ledger_error)
+	}
+}
+
+fn ensure_signer(signer: &AccountId, call: &TrustedCall) -> Result<(), CliError> {
+	if signer != call.sender_account() {
+		return Err(CliError::OfflineSigning {
+			msg: format!(
+				"key {} does not belong to the signer of the call: {}",
+				signer.to_ss58check(),
+				call.sender_account().to_ss58check()
+			),
+		})
+	}
+	info!("signer ss58 is {}", signer.to_ss58check());
+	Ok(())
+}
@@ -0,0 +1,88 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	get_layer_two_nonce,
+	offline_signing::{read_json_file, SignedCallFile},
+	trusted_cli::TrustedCli,
+	trusted_command_utils::get_pair_from_str,
+	trusted_operation::{perform_trusted_operation, read_shard},
+	Cli, CliError, CliResult, CliResultOk,
+};
+use codec::Decode;
+use ita_stf::{Getter, Index, TrustedCall, TrustedCallSigned};
+use itp_stf_primitives::{
+	traits::{TrustedCallSigning, TrustedCallVerification},
+	types::{KeyPair, TrustedOperation},
+};
+use log::*;
+use sp_core::{crypto::Ss58Codec, Pair};
+use std::{boxed::Box, path::PathBuf};
+
+/// Submits a call that was signed offline with `trusted sign` to the worker.
+#[derive(Parser)]
+pub struct SubmitSignedCallCommand {
+	/// file containing the signed call
+	file: PathBuf,
+
+	/// sponsor the call with this account, which signs it too, so that the sender of the call
+	/// doesn't need to hold any balance
+	#[clap(long)]
+	sponsor: Option<String>,
+}
+
+impl SubmitSignedCallCommand {
+	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
+		let signed_call_file: SignedCallFile = read_json_file(&self.file)?;
+		let (signed_call, mrenclave, shard) = signed_call_file.decode()?;
+
+		// The call is submitted to the shard given by the trusted args, so make sure it has
+		// been signed for that one.
+		let target_shard = read_shard(trusted_args)
+			.map_err(|e| CliError::OfflineSigning { msg: format!("invalid shard: {:?}", e) })?;
+		if target_shard != shard {
+			return Err(CliError::OfflineSigning {
+				msg: format!(
+					"call has been signed for shard {}, but is submitted to another one",
+					signed_call_file.shard
+				),
+			})
+		}
+		if !signed_call.verify_signature(&mrenclave, &shard) {
+			return Err(CliError::OfflineSigning { msg: "invalid signature".to_string() })
+		}
+
+		let signed_call =
+			match &self.sponsor {
+				Some(sponsor) => {
+					let sponsor = get_pair_from_str(trusted_args, sponsor);
+					info!("sponsor ss58 is {}", sponsor.public().to_ss58check());
+					let nonce = get_layer_two_nonce!(sponsor, cli, trusted_args);
+					TrustedCall::sponsored_call(sponsor.public().into(), Box::new(signed_call))
+						.sign(&KeyPair::Sr25519(Box::new(sponsor)), nonce, &mrenclave, &shard)
+				},
+				None => signed_call,
+			};
+
+		println!("submitting signed trusted call: {:?}", signed_call.call);
+		let top: TrustedOperation<TrustedCallSigned, Getter> =
+			signed_call.into_trusted_operation(trusted_args.direct);
+		let res = perform_trusted_operation(cli, trusted_args, &top).map(|_| CliResultOk::None)?;
+		info!("signed trusted call submitted");
+		Ok(res)
+	}
+}
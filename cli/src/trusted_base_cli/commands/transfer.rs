@@ -43,10 +43,18 @@ pub struct TransferCommand {
 
 	/// amount to be transferred
 	amount: Balance,
+
+	/// sign with this session key of the sender instead, the sender doesn't need to be in the
+	/// keystore
+	#[clap(long)]
+	session_key: Option<String>,
 }
 
 impl TransferCommand {
 	pub(crate) fn run(&self, cli: &Cli, trusted_args: &TrustedCli) -> CliResult {
+		if let Some(session_key) = &self.session_key {
+			return self.run_with_session_key(cli, trusted_args, session_key)
+		}
 		let from = get_pair_from_str(trusted_args, &self.from);
 		let to = get_accountid_from_str(&self.to);
 		info!("from ss58 is {}", from.public().to_ss58check());
@@ -69,4 +77,36 @@ impl TransferCommand {
 		info!("trusted call transfer executed");
 		Ok(res)
 	}
+	fn run_with_session_key(
+		&self,
+		cli: &Cli,
+		trusted_args: &TrustedCli,
+		session_key: &str,
+	) -> CliResult {
+		let delegate = get_pair_from_str(trusted_args, session_key);
+		let from = get_accountid_from_str(&self.from);
+		let to = get_accountid_from_str(&self.to);
+		info!("session key ss58 is {}", delegate.public().to_ss58check());
+		info!("from ss58 is {}", from.to_ss58check());
+		info!("to ss58 is {}", to.to_ss58check());
+
+		let (mrenclave, shard) = get_identifiers(trusted_args);
+		let nonce = get_layer_two_nonce!(delegate, cli, trusted_args);
+		println!(
+			"send trusted call transfer from {} to {}: {}, signed by session key {}, nonce: {}",
+			from,
+			to,
+			self.amount,
+			delegate.public(),
+			nonce
+		);
+		let transfer = TrustedCall::balance_transfer(from, to, self.amount);
+		let top: TrustedOperation<TrustedCallSigned, Getter> =
+			TrustedCall::session_call(delegate.public().into(), Box::new(transfer))
+				.sign(&KeyPair::Sr25519(Box::new(delegate)), nonce, &mrenclave, &shard)
+				.into_trusted_operation(trusted_args.direct);
+		let res = perform_trusted_operation(cli, trusted_args, &top).map(|_| CliResultOk::None)?;
+		info!("trusted call session transfer executed");
+		Ok(res)
+	}
 }
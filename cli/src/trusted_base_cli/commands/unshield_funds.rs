@@ -42,6 +42,11 @@ pub struct UnshieldFundsCommand {
 
 	/// amount to be transferred
 	amount: Balance,
+
+	/// Queue the payout for the next batch paid out by the shard vault, instead of paying out
+	/// right away. Hides the link between the trusted call and the parentchain transfer.
+	#[clap(long)]
+	batched: bool,
 }
 
 impl UnshieldFundsCommand {
@@ -60,10 +65,14 @@ impl UnshieldFundsCommand {
 
 		let (mrenclave, shard) = get_identifiers(trusted_args);
 		let nonce = get_layer_two_nonce!(from, cli, trusted_args);
-		let top: TrustedOperation<TrustedCallSigned, Getter> =
+		let call = if self.batched {
+			TrustedCall::balance_unshield_batched(from.public().into(), to, self.amount)
+		} else {
 			TrustedCall::balance_unshield(from.public().into(), to, self.amount, shard)
-				.sign(&KeyPair::Sr25519(Box::new(from)), nonce, &mrenclave, &shard)
-				.into_trusted_operation(trusted_args.direct);
+		};
+		let top: TrustedOperation<TrustedCallSigned, Getter> = call
+			.sign(&KeyPair::Sr25519(Box::new(from)), nonce, &mrenclave, &shard)
+			.into_trusted_operation(trusted_args.direct);
 		Ok(perform_trusted_operation(cli, trusted_args, &top).map(|_| CliResultOk::None)?)
 	}
 }
@@ -17,8 +17,22 @@
 
 use crate::{
 	trusted_base_cli::commands::{
-		balance::BalanceCommand, get_shard::GetShardCommand, get_shard_vault::GetShardVaultCommand,
-		nonce::NonceCommand, set_balance::SetBalanceCommand, transfer::TransferCommand,
+		asset_balance::AssetBalanceCommand, balance::BalanceCommand,
+		compose_call::ComposeCallCommand, confidential_events::ConfidentialEventsCommand,
+		cross_shard_transfer::CrossShardTransferCommand,
+		export_account_data::ExportAccountDataCommand, fees::FeesCommand,
+		get_shard::GetShardCommand, get_shard_vault::GetShardVaultCommand,
+		inspect_shard::InspectShardCommand, list_shards::ListShardsCommand, nonce::NonceCommand,
+		register_session_key::RegisterSessionKeyCommand,
+		relay_cross_shard_message::RelayCrossShardMessageCommand, rent_status::RentStatusCommand,
+		reserves::ReservesCommand, revoke_session_key::RevokeSessionKeyCommand,
+		schedule_transfer::ScheduleTransferCommand, set_balance::SetBalanceCommand,
+		set_fee_config::SetFeeConfigCommand, set_fee_multiplier::SetFeeMultiplierCommand,
+		set_shard_access_policy::SetShardAccessPolicyCommand,
+		set_shard_maintenance_mode::SetShardMaintenanceModeCommand,
+		set_shard_member::SetShardMemberCommand, sign_call::SignCallCommand,
+		submit_signed_call::SubmitSignedCallCommand, transfer::TransferCommand,
+		transfer_asset::TransferAssetCommand, unshield_asset::UnshieldAssetCommand,
 		unshield_funds::UnshieldFundsCommand,
 	},
 	trusted_cli::TrustedCli,
@@ -40,18 +54,76 @@ pub enum TrustedBaseCommand {
 	/// lists all incognito accounts in a given shard
 	ListAccounts,
 
-	/// send funds from one incognito account to another
+	/// send funds from one incognito account to another, optionally signed by a session key
 	Transfer(TransferCommand),
 
+	/// schedule a transfer between incognito accounts for a future sidechain block or timestamp
+	ScheduleTransfer(ScheduleTransferCommand),
+
+	/// send funds from an incognito account to an account on another shard
+	CrossShardTransfer(CrossShardTransferCommand),
+
+	/// relay a cross-shard message to the worker of a destination shard on another worker
+	RelayCrossShardMessage(RelayCrossShardMessageCommand),
+
 	/// ROOT call to set some account balance to an arbitrary number
 	SetBalance(SetBalanceCommand),
 
+	/// ROOT call to set who may submit trusted calls to the shard
+	SetShardAccessPolicy(SetShardAccessPolicyCommand),
+
+	/// ROOT call to add, update or remove a member of a permissioned shard
+	SetShardMember(SetShardMemberCommand),
+
+	/// ROOT call to put the shard into maintenance mode or lift it, in which no new trusted
+	/// calls are accepted except the ones of root. Send it indirectly via the parentchain if
+	/// the direct invocation is not available.
+	SetShardMaintenanceMode(SetShardMaintenanceModeCommand),
+
+	/// ROOT call to set the execution fees of the shard
+	SetFeeConfig(SetFeeConfigCommand),
+
+	/// ROOT call to set the factor all execution fees of the shard are scaled with
+	SetFeeMultiplier(SetFeeMultiplierCommand),
+
+	/// query the execution fees of the shard and estimate the fee of a composed call
+	Fees(FeesCommand),
+
+	/// register a time-limited session key that may submit the given calls on behalf of an
+	/// incognito account
+	RegisterSessionKey(RegisterSessionKeyCommand),
+
+	/// revoke a session key of an incognito account
+	RevokeSessionKey(RevokeSessionKeyCommand),
+
 	/// query balance for incognito account in keystore
 	Balance(BalanceCommand),
 
 	/// Transfer funds from an incognito account to an parentchain account
 	UnshieldFunds(UnshieldFundsCommand),
 
+	/// send non-native parentchain assets from one incognito account to another
+	TransferAsset(TransferAssetCommand),
+
+	/// query the balance of a non-native parentchain asset for incognito account in keystore
+	AssetBalance(AssetBalanceCommand),
+
+	/// Transfer non-native assets from an incognito account to an parentchain account
+	UnshieldAsset(UnshieldAssetCommand),
+
+	/// query the state rent status of an incognito account in keystore
+	RentStatus(RentStatusCommand),
+
+	/// check the latest reserves report of the shard against the vault balance on the
+	/// parentchain
+	Reserves(ReservesCommand),
+
+	/// list the latest confidential events of an incognito account in keystore, e.g. why a call failed
+	ConfidentialEvents(ConfidentialEventsCommand),
+
+	/// export all data the shard stores for an incognito account in keystore, decrypted locally
+	ExportAccountData(ExportAccountDataCommand),
+
 	/// gets the nonce of a given account, taking the pending trusted calls
 	/// in top pool in consideration
 	Nonce(NonceCommand),
@@ -61,6 +133,22 @@ pub enum TrustedBaseCommand {
 
 	/// get shard vault for shielding (if defined for this worker)
 	GetShardVault(GetShardVaultCommand),
+
+	/// list all shards maintained by this worker, including state hash, last sidechain block,
+	/// enclave account nonce and shard vault
+	ListShards(ListShardsCommand),
+
+	/// inspect a shard maintained by this worker
+	InspectShard(InspectShardCommand),
+
+	/// compose an unsigned trusted call and export it for offline signing
+	ComposeCall(ComposeCallCommand),
+
+	/// sign an exported trusted call offline
+	Sign(SignCallCommand),
+
+	/// submit a trusted call that has been signed offline, optionally sponsored by another account
+	SubmitSigned(SubmitSignedCallCommand),
 }
 
 impl TrustedBaseCommand {
@@ -69,12 +157,35 @@ impl TrustedBaseCommand {
 			TrustedBaseCommand::NewAccount => new_account(trusted_cli),
 			TrustedBaseCommand::ListAccounts => list_accounts(trusted_cli),
 			TrustedBaseCommand::Transfer(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::ScheduleTransfer(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::CrossShardTransfer(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::RelayCrossShardMessage(cmd) => cmd.run(cli, trusted_cli),
 			TrustedBaseCommand::SetBalance(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::SetShardAccessPolicy(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::SetShardMember(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::SetShardMaintenanceMode(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::SetFeeConfig(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::SetFeeMultiplier(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::Fees(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::RegisterSessionKey(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::RevokeSessionKey(cmd) => cmd.run(cli, trusted_cli),
 			TrustedBaseCommand::Balance(cmd) => cmd.run(cli, trusted_cli),
 			TrustedBaseCommand::UnshieldFunds(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::TransferAsset(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::AssetBalance(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::UnshieldAsset(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::RentStatus(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::Reserves(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::ExportAccountData(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::ConfidentialEvents(cmd) => cmd.run(cli, trusted_cli),
 			TrustedBaseCommand::Nonce(cmd) => cmd.run(cli, trusted_cli),
 			TrustedBaseCommand::GetShard(cmd) => cmd.run(cli, trusted_cli),
 			TrustedBaseCommand::GetShardVault(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::ListShards(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::InspectShard(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::ComposeCall(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::Sign(cmd) => cmd.run(cli, trusted_cli),
+			TrustedBaseCommand::SubmitSigned(cmd) => cmd.run(cli, trusted_cli),
 		}
 	}
 }
@@ -23,11 +23,14 @@ use crate::{
 };
 use base58::{FromBase58, ToBase58};
 use codec::{Decode, Encode};
-use ita_stf::{Getter, TrustedCallSigned, TrustedGetter};
+use ita_stf::{default_getter_expiry, Getter, TrustedCallSigned, TrustedGetter};
 use itc_rpc_client::direct_client::DirectApi;
 use itp_rpc::{RpcRequest, RpcResponse, RpcReturnValue};
-use itp_stf_primitives::types::{AccountId, KeyPair, ShardIdentifier, TrustedOperation};
-use itp_types::DirectRequestStatus;
+use itp_stf_primitives::{
+	shard_acl::{AccessPolicy, ShardRole},
+	types::{AccountId, KeyPair, ShardIdentifier, TrustedOperation},
+};
+use itp_types::{parentchain::ParentchainAssetId, DirectRequestStatus};
 use itp_utils::{FromHexPrefixed, ToHexPrefixed};
 use log::*;
 use my_node_runtime::Balance;
@@ -40,11 +43,11 @@ use substrate_client_keystore::LocalKeystore;
 #[macro_export]
 macro_rules! get_layer_two_nonce {
 	($signer_pair:ident, $cli: ident, $trusted_args:ident ) => {{
-		use ita_stf::{Getter, TrustedCallSigned, TrustedGetter};
+		use ita_stf::{default_getter_expiry, Getter, TrustedCallSigned, TrustedGetter};
 		use $crate::trusted_command_utils::get_pending_trusted_calls_for;
 		let top = TrustedOperation::<TrustedCallSigned, Getter>::get(Getter::trusted(
 			TrustedGetter::nonce($signer_pair.public().into())
-				.sign(&KeyPair::Sr25519(Box::new($signer_pair.clone()))),
+				.sign(&KeyPair::Sr25519(Box::new($signer_pair.clone())), default_getter_expiry()),
 		));
 		// final nonce = current system nonce + pending tx count, panic early
 		let res = perform_trusted_operation($cli, $trusted_args, &top).unwrap_or_default();
@@ -67,7 +70,8 @@ pub(crate) fn get_balance(cli: &Cli, trusted_args: &TrustedCli, arg_who: &str) -
 	debug!("arg_who = {:?}", arg_who);
 	let who = get_pair_from_str(trusted_args, arg_who);
 	let top = TrustedOperation::<TrustedCallSigned, Getter>::get(Getter::trusted(
-		TrustedGetter::free_balance(who.public().into()).sign(&KeyPair::Sr25519(Box::new(who))),
+		TrustedGetter::free_balance(who.public().into())
+			.sign(&KeyPair::Sr25519(Box::new(who)), default_getter_expiry()),
 	));
 	let res = perform_trusted_operation(cli, trusted_args, &top).unwrap_or(None);
 	debug!("received result for balance");
@@ -117,6 +121,32 @@ pub(crate) fn get_accountid_from_str(account: &str) -> AccountId {
 	}
 }
 
+/// Parses a parentchain asset in the format `assets:<asset id>` or `tokens:<currency id>`.
+pub(crate) fn parse_asset_id(asset: &str) -> Result<ParentchainAssetId, String> {
+	let (pallet, id) = asset
+		.split_once(':')
+		.ok_or_else(|| format!("expected `assets:<id>` or `tokens:<id>`, got {}", asset))?;
+	let id = id.parse::<u32>().map_err(|e| format!("invalid asset id {}: {}", id, e))?;
+	match pallet {
+		"assets" => Ok(ParentchainAssetId::Assets(id)),
+		"tokens" => Ok(ParentchainAssetId::Tokens(id)),
+		_ => Err(format!("unknown asset pallet {}, expected `assets` or `tokens`", pallet)),
+	}
+}
+
+/// Parses a shard access policy in the format `open`, `allow-list` or `role:<role id>`.
+pub(crate) fn parse_access_policy(policy: &str) -> Result<AccessPolicy, String> {
+	match policy.split_once(':') {
+		None if policy == "open" => Ok(AccessPolicy::Open),
+		None if policy == "allow-list" => Ok(AccessPolicy::AllowList),
+		Some(("role", role)) => role
+			.parse::<ShardRole>()
+			.map(AccessPolicy::RequireRole)
+			.map_err(|e| format!("invalid role {}: {}", role, e)),
+		_ => Err(format!("expected `open`, `allow-list` or `role:<id>`, got {}", policy)),
+	}
+}
+
 // TODO this function is ALMOST redundant with client::main
 // get a pair either form keyring (well known keys) or from the store
 pub(crate) fn get_pair_from_str(trusted_args: &TrustedCli, account: &str) -> sr25519_core::Pair {
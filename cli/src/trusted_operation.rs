@@ -27,7 +27,7 @@ use enclave_bridge_primitives::Request;
 use ita_stf::{Getter, TrustedCallSigned};
 use itc_rpc_client::direct_client::{DirectApi, DirectClient};
 use itp_node_api::api_client::{ParentchainApi, ENCLAVE_BRIDGE};
-use itp_rpc::{RpcRequest, RpcResponse, RpcReturnValue};
+use itp_rpc::{RpcErrorResponse, RpcRequest, RpcResponse, RpcReturnValue};
 use itp_sgx_crypto::ShieldingCryptoEncrypt;
 use itp_stf_primitives::types::{ShardIdentifier, TrustedOperation};
 use itp_types::{BlockNumber, DirectRequestStatus, TrustedOperationStatus};
@@ -92,6 +92,10 @@ pub(crate) fn get_state(
 	let rpc_response_str = direct_api.get(&jsonrpc_call).unwrap();
 
 	// Decode RPC response.
+	if let Ok(error_response) = serde_json::from_str::<RpcErrorResponse>(&rpc_response_str) {
+		println!("[Error {}] {}", error_response.error.code, error_response.error.message);
+		return Err(TrustedOperationError::Default { msg: error_response.error.message })
+	}
 	let rpc_response: RpcResponse = serde_json::from_str(&rpc_response_str)
 		.map_err(|err| TrustedOperationError::Default { msg: err.to_string() })?;
 	let rpc_return_value = RpcReturnValue::from_hex(&rpc_response.result)
@@ -0,0 +1,154 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Resolution of the worker to connect to from the enclave-signed records of the worker
+//! directory, which the workers publish on the parentchain.
+
+use crate::{command_utils::get_chain_api, commands::Commands, Cli, CliError};
+use base58::FromBase58;
+use ita_client_sdk::directory::{decode_published_record, select_workers};
+use itc_rpc_client::direct_client::{DirectApi, DirectClient as DirectWorkerApi};
+use itp_node_api::api_client::{PalletTeerexApi, ParentchainApi};
+use itp_settings::worker_directory::MAX_RECORD_AGE;
+use itp_time_utils::now_as_millis;
+use itp_types::{worker_directory::SignedWorkerRecord, AccountId, ShardIdentifier};
+use log::*;
+use my_node_runtime::{Hash, RuntimeEvent};
+use sp_core::ed25519;
+use std::sync::Mutex;
+use substrate_api_client::{GetChainInfo, GetStorage};
+
+type EventRecord = frame_system::EventRecord<RuntimeEvent, Hash>;
+
+/// Resolved once per invocation, as some commands connect to the worker several times.
+static RESOLVED_WORKER_URL: Mutex<Option<String>> = Mutex::new(None);
+
+/// Trusted RPC URL of the most recently attested, registered worker of the shard, which
+/// answers the shielding key request.
+pub(crate) fn resolve_worker_url(cli: &Cli) -> Result<String, CliError> {
+	let mut resolved_url = RESOLVED_WORKER_URL.lock().unwrap();
+	if let Some(url) = resolved_url.as_ref() {
+		return Ok(url.clone())
+	}
+
+	let shard = shard_of_command(cli)
+		.ok_or_else(|| directory_error("'--shard' or '--mrenclave' of a trusted command needed"))?;
+	let api = get_chain_api(cli);
+	let records = fetch_worker_records(&api, cli.directory_blocks)?;
+	info!("Found {} worker records in the last {} blocks", records.len(), cli.directory_blocks);
+
+	let candidates = select_workers(
+		records,
+		&shard,
+		now_as_millis(),
+		MAX_RECORD_AGE.as_millis() as u64,
+		|signer, mrenclave| registry_attested_at(&api, signer, mrenclave),
+	);
+	for candidate in candidates {
+		let url = match candidate.record.trusted_rpc_url_str() {
+			Some(url) => url.to_string(),
+			None => continue,
+		};
+		match DirectWorkerApi::new(url.clone()).get_rsa_pubkey() {
+			Ok(_) => {
+				info!("Resolved worker {:?} at {}", candidate.signer, url);
+				*resolved_url = Some(url.clone());
+				return Ok(url)
+			},
+			Err(e) => warn!("Worker at {} is not healthy: {:?}", url, e),
+		}
+	}
+	Err(directory_error(&format!("no healthy worker found for shard {:?}", shard)))
+}
+
+fn shard_of_command(cli: &Cli) -> Option<ShardIdentifier> {
+	let trusted_args = match &cli.command {
+		Commands::Trusted(trusted_args) => trusted_args,
+		_ => return None,
+	};
+	let shard = trusted_args.shard.as_ref().or(trusted_args.mrenclave.as_ref())?;
+	Some(ShardIdentifier::from_slice(&shard.from_base58().expect("shard has to be base58 encoded")))
+}
+
+/// Collects the valid worker records published in the last `blocks` finalized blocks.
+fn fetch_worker_records(
+	api: &ParentchainApi,
+	blocks: u32,
+) -> Result<Vec<SignedWorkerRecord>, CliError> {
+	let mut block_hash = api
+		.get_finalized_head()
+		.map_err(|e| directory_error(&format!("{:?}", e)))?
+		.ok_or_else(|| directory_error("no finalized block"))?;
+
+	let mut records = Vec::new();
+	for _ in 0..blocks {
+		let events: Vec<EventRecord> = api
+			.get_storage_value("System", "Events", Some(block_hash))
+			.map_err(|e| directory_error(&format!("{:?}", e)))?
+			.unwrap_or_default();
+		for event_record in events {
+			if let RuntimeEvent::EnclaveBridge(
+				my_node_runtime::pallet_enclave_bridge::Event::PublishedHash {
+					enclave_fingerprint,
+					hash,
+					data,
+				},
+			) = event_record.event
+			{
+				// Other kinds of published data are expected, so decoding failures are no error.
+				match decode_published_record(&hash.0, &data) {
+					Ok(signed_record)
+						if signed_record.record.mrenclave == enclave_fingerprint.0 =>
+						records.push(signed_record),
+					Ok(signed_record) => warn!(
+						"Ignoring worker record of {:?}, published by another enclave",
+						signed_record.signer
+					),
+					Err(e) => trace!("Published hash {:?} is no worker record: {:?}", hash, e),
+				}
+			}
+		}
+
+		let header = api
+			.get_header(Some(block_hash))
+			.map_err(|e| directory_error(&format!("{:?}", e)))?
+			.ok_or_else(|| directory_error("missing parentchain block"))?;
+		if header.number == 0 {
+			break
+		}
+		block_hash = header.parent_hash;
+	}
+	Ok(records)
+}
+
+/// Attestation timestamp of the signer's registration in teerex, `None` if it isn't registered
+/// with `mrenclave`.
+fn registry_attested_at(
+	api: &ParentchainApi,
+	signer: &ed25519::Public,
+	mrenclave: &[u8; 32],
+) -> Option<u64> {
+	match api.enclave(&AccountId::from(signer.0), None) {
+		Ok(Some(enclave)) if enclave.fingerprint().0 == *mrenclave =>
+			Some(enclave.attestation_timestamp()),
+		_ => None,
+	}
+}
+
+fn directory_error(msg: &str) -> CliError {
+	CliError::WorkerDirectory { msg: msg.to_string() }
+}
@@ -0,0 +1,179 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Hash chain of the audit entries of a single shard.
+
+use crate::{
+	error::{Error, Result},
+	AuditEntry, DropReason,
+};
+use codec::{Decode, Encode};
+use itp_stf_primitives::pagination::{Page, PageRequest};
+use sp_core::H256;
+use std::vec::Vec;
+
+/// The retained entries of a shard's audit log, oldest first.
+///
+/// Pruning removes entries from the front. The first retained entry still commits to the hash
+/// of its pruned predecessor, so the remaining chain stays verifiable.
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AuditChain {
+	entries: Vec<AuditEntry>,
+}
+
+impl AuditChain {
+	/// Appends a new entry that commits to the current head of the chain.
+	pub fn append(
+		&mut self,
+		operation_hash: H256,
+		reason: DropReason,
+		timestamp: u64,
+	) -> &AuditEntry {
+		let (index, previous_hash) = match self.entries.last() {
+			Some(head) => (head.index + 1, head.hash()),
+			None => (0, H256::zero()),
+		};
+		self.entries
+			.push(AuditEntry { index, operation_hash, reason, timestamp, previous_hash });
+		&self.entries[self.entries.len() - 1]
+	}
+
+	/// Removes the oldest entries, so at most `entries_to_keep` (but at least one) remain.
+	pub fn prune(&mut self, entries_to_keep: usize) {
+		let excess = self.entries.len().saturating_sub(entries_to_keep.max(1));
+		self.entries.drain(..excess);
+	}
+
+	/// Returns the entries starting at the index given by the request's cursor. A cursor that
+	/// points to a pruned entry starts at the oldest retained entry.
+	pub fn page(&self, request: &PageRequest) -> Page<AuditEntry> {
+		let first_index = match self.entries.first() {
+			Some(first) => first.index,
+			None => return Page::default(),
+		};
+		let start = request.cursor.unwrap_or_default().saturating_sub(first_index) as usize;
+		let entries: Vec<_> = self
+			.entries
+			.iter()
+			.skip(start)
+			.take(request.effective_limit() as usize + 1)
+			.cloned()
+			.collect();
+
+		let mut page = Page { next: None, items: entries };
+		if page.items.len() > request.effective_limit() as usize {
+			page.next = page.items.pop().map(|e| e.index);
+		}
+		page
+	}
+
+	pub fn entries(&self) -> &[AuditEntry] {
+		&self.entries
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	pub fn verify(&self) -> Result<()> {
+		verify_entries(&self.entries)
+	}
+}
+
+/// Verifies that consecutive `entries` are linked by their hashes and have no gaps, e.g. the
+/// entries of one or several consecutive pages.
+pub fn verify_entries(entries: &[AuditEntry]) -> Result<()> {
+	for pair in entries.windows(2) {
+		let (previous, entry) = (&pair[0], &pair[1]);
+		if entry.index != previous.index + 1 || entry.previous_hash != previous.hash() {
+			return Err(Error::BrokenChain(entry.index))
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itp_stf_primitives::pagination::PageCursor;
+
+	fn chain_with_entries(count: u8) -> AuditChain {
+		let mut chain = AuditChain::default();
+		for i in 0..count {
+			chain.append(H256::repeat_byte(i), DropReason::Expired, i as u64);
+		}
+		chain
+	}
+
+	#[test]
+	fn appended_entries_are_linked() {
+		let chain = chain_with_entries(3);
+
+		assert_eq!(chain.entries()[0].previous_hash, H256::zero());
+		assert_eq!(chain.entries()[2].previous_hash, chain.entries()[1].hash());
+		assert!(chain.verify().is_ok());
+	}
+
+	#[test]
+	fn altered_or_removed_entry_breaks_the_chain() {
+		let mut altered = chain_with_entries(3);
+		altered.entries[1].reason = DropReason::Banned;
+		assert!(matches!(altered.verify(), Err(Error::BrokenChain(2))));
+
+		let mut removed = chain_with_entries(3);
+		removed.entries.remove(1);
+		assert!(matches!(removed.verify(), Err(Error::BrokenChain(2))));
+	}
+
+	#[test]
+	fn pruned_chain_stays_verifiable_and_continues_indices() {
+		let mut chain = chain_with_entries(5);
+		chain.prune(2);
+
+		assert_eq!(chain.len(), 2);
+		assert_eq!(chain.entries()[0].index, 3);
+		assert_eq!(chain.append(H256::zero(), DropReason::Evicted, 5).index, 5);
+		assert!(chain.verify().is_ok());
+	}
+
+	#[test]
+	fn walking_all_pages_returns_every_retained_entry() {
+		let mut chain = chain_with_entries(10);
+		chain.prune(7);
+		let mut request = PageRequest::new(None, 3);
+		let mut collected = vec![];
+
+		loop {
+			let page = chain.page(&request);
+			collected.extend(page.items);
+			match page.next {
+				Some(cursor) => request.cursor = Some(cursor),
+				None => break,
+			}
+		}
+
+		assert_eq!(
+			collected.iter().map(|e| e.index).collect::<Vec<PageCursor>>(),
+			(3..10).collect::<Vec<_>>()
+		);
+		assert!(verify_entries(&collected).is_ok());
+	}
+}
@@ -0,0 +1,113 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Anti-censorship audit log of trusted operations that were received but never executed.
+//!
+//! Every operation that leaves the top pool without being executed (expired, banned, evicted
+//! because of the pool limits) or that is skipped because the slot time ran out is recorded as
+//! an [AuditEntry]. The entries of a shard are hash-chained, each entry commits to the hash of
+//! its predecessor, and sealed to disk. Shard governance can query the log through a privileged
+//! getter and verify with [chain::verify_entries] that no entry was removed or altered.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(all(feature = "std", feature = "sgx"))]
+compile_error!("feature \"std\" and feature \"sgx\" cannot be enabled at the same time");
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+#[macro_use]
+extern crate sgx_tstd as std;
+
+// re-export module to properly feature gate sgx and regular std environment
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+pub mod sgx_reexport_prelude {
+	pub use thiserror_sgx as thiserror;
+}
+
+pub use chain::AuditChain;
+pub use error::{Error, Result};
+pub use store::AuditLogStore;
+
+use codec::{Decode, Encode};
+use itp_stf_primitives::{
+	pagination::{Page, PageCursor, PageRequest},
+	types::ShardIdentifier,
+};
+use sp_core::{blake2_256, H256};
+
+pub mod chain;
+pub mod error;
+pub mod store;
+
+#[cfg(feature = "mocks")]
+pub mod mocks;
+
+/// Why a trusted operation was not executed.
+#[derive(Encode, Decode, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DropReason {
+	/// The operation outlived its longevity in the pool.
+	Expired,
+	/// The operation was submitted while being temporarily banned.
+	Banned,
+	/// The operation was evicted to enforce the pool limits.
+	Evicted,
+	/// No longer recorded: operations that do not fit into a slot stay in the pool. Kept such
+	/// that existing entries can still be decoded.
+	SlotExhausted,
+	/// The execution of the operation failed, it was removed from the pool.
+	ExecutionFailed,
+}
+
+/// A single record of the audit log.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct AuditEntry {
+	/// Position of the entry in the log of its shard, starting at 0. Used as page cursor.
+	pub index: PageCursor,
+	pub operation_hash: H256,
+	pub reason: DropReason,
+	/// Unix time in milliseconds when the entry was recorded.
+	pub timestamp: u64,
+	/// Hash of the preceding entry, [H256::zero] for the first entry of a shard.
+	pub previous_hash: H256,
+}
+
+impl AuditEntry {
+	/// Hash of the encoded entry, which the next entry commits to.
+	pub fn hash(&self) -> H256 {
+		blake2_256(&self.encode()).into()
+	}
+}
+
+/// Records trusted operations that were dropped or skipped without execution.
+pub trait RecordDroppedOperation: Send + Sync {
+	fn record_dropped(
+		&self,
+		shard: &ShardIdentifier,
+		operation_hash: &H256,
+		reason: DropReason,
+	) -> Result<()>;
+}
+
+/// Read access to the audit log of a shard.
+pub trait QueryAuditLog {
+	/// Returns the entries starting at the index given by the request's cursor, oldest first.
+	fn audit_log_page(
+		&self,
+		shard: &ShardIdentifier,
+		request: &PageRequest,
+	) -> Result<Page<AuditEntry>>;
+}
@@ -0,0 +1,51 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use std::sync::SgxRwLock as RwLock;
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+use crate::{error::Result, DropReason, RecordDroppedOperation};
+use itp_stf_primitives::types::ShardIdentifier;
+use sp_core::H256;
+use std::vec::Vec;
+
+/// Keeps the recorded operations in memory, in the order they were recorded.
+#[derive(Default)]
+pub struct RecordDroppedOperationMock {
+	recorded: RwLock<Vec<(ShardIdentifier, H256, DropReason)>>,
+}
+
+impl RecordDroppedOperationMock {
+	pub fn recorded(&self) -> Vec<(ShardIdentifier, H256, DropReason)> {
+		self.recorded.read().unwrap().clone()
+	}
+}
+
+impl RecordDroppedOperation for RecordDroppedOperationMock {
+	fn record_dropped(
+		&self,
+		shard: &ShardIdentifier,
+		operation_hash: &H256,
+		reason: DropReason,
+	) -> Result<()> {
+		self.recorded.write().unwrap().push((*shard, *operation_hash, reason));
+		Ok(())
+	}
+}
@@ -0,0 +1,176 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use std::sync::SgxRwLock as RwLock;
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+use crate::{
+	chain::AuditChain,
+	error::{Error, Result},
+	AuditEntry, DropReason, QueryAuditLog, RecordDroppedOperation,
+};
+use codec::{Decode, Encode};
+use itp_stf_primitives::{
+	pagination::{Page, PageRequest},
+	types::ShardIdentifier,
+};
+use itp_time_utils::now_as_millis;
+use log::*;
+use sp_core::H256;
+use std::{collections::BTreeMap, format, path::PathBuf};
+
+#[cfg(feature = "std")]
+use itp_sgx_io::{read as read_file, write as write_file};
+#[cfg(feature = "sgx")]
+use itp_sgx_io::{seal as write_file, unseal as read_file};
+
+/// Directory (relative to the worker's base directory) containing the sealed audit logs.
+pub const AUDIT_LOG_DIR: &str = "audit_log";
+
+/// Number of audit entries kept per shard, older entries are pruned.
+pub const AUDIT_ENTRIES_TO_KEEP: usize = 10_000;
+
+/// Keeps the audit log of every shard in memory and seals it to disk, one file per shard,
+/// whenever an entry is appended.
+pub struct AuditLogStore {
+	base_path: PathBuf,
+	entries_to_keep: usize,
+	chains: RwLock<BTreeMap<ShardIdentifier, AuditChain>>,
+}
+
+impl AuditLogStore {
+	pub fn new(base_path: PathBuf) -> Self {
+		Self::with_retention(base_path, AUDIT_ENTRIES_TO_KEEP)
+	}
+
+	pub fn with_retention(base_path: PathBuf, entries_to_keep: usize) -> Self {
+		Self {
+			base_path: base_path.join(AUDIT_LOG_DIR),
+			entries_to_keep,
+			chains: Default::default(),
+		}
+	}
+
+	fn chain_path(&self, shard: &ShardIdentifier) -> PathBuf {
+		self.base_path.join(format!("{}.bin", hex::encode(shard.encode())))
+	}
+
+	/// Loads the sealed chain of `shard`, which must verify. A missing file is an empty chain.
+	fn load_chain(&self, shard: &ShardIdentifier) -> Result<AuditChain> {
+		let path = self.chain_path(shard);
+		if !path.exists() {
+			return Ok(AuditChain::default())
+		}
+		let chain = AuditChain::decode(&mut read_file(path)?.as_slice())?;
+		chain.verify()?;
+		Ok(chain)
+	}
+
+	fn seal_chain(&self, shard: &ShardIdentifier, chain: &AuditChain) -> Result<()> {
+		std::fs::create_dir_all(&self.base_path)?;
+		write_file(&chain.encode(), self.chain_path(shard))?;
+		Ok(())
+	}
+
+	/// Runs `f` on the cached chain of `shard`, loading it from disk first if necessary.
+	fn with_chain<R>(
+		&self,
+		shard: &ShardIdentifier,
+		f: impl FnOnce(&mut AuditChain) -> Result<R>,
+	) -> Result<R> {
+		let mut chains = self.chains.write().map_err(|_| Error::LockPoisoning)?;
+		if !chains.contains_key(shard) {
+			let chain = self.load_chain(shard)?;
+			chains.insert(*shard, chain);
+		}
+		let chain = chains.get_mut(shard).expect("Chain was inserted above; qed");
+		f(chain)
+	}
+}
+
+impl RecordDroppedOperation for AuditLogStore {
+	fn record_dropped(
+		&self,
+		shard: &ShardIdentifier,
+		operation_hash: &H256,
+		reason: DropReason,
+	) -> Result<()> {
+		self.with_chain(shard, |chain| {
+			let entry: AuditEntry = chain.append(*operation_hash, reason, now_as_millis()).clone();
+			chain.prune(self.entries_to_keep);
+			debug!("Recorded audit entry {} of shard {:?}: {:?}", entry.index, shard, entry);
+			self.seal_chain(shard, chain)
+		})
+	}
+}
+
+impl QueryAuditLog for AuditLogStore {
+	fn audit_log_page(
+		&self,
+		shard: &ShardIdentifier,
+		request: &PageRequest,
+	) -> Result<Page<AuditEntry>> {
+		self.with_chain(shard, |chain| Ok(chain.page(request)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itp_sgx_temp_dir::TempDir;
+
+	#[test]
+	fn recorded_entries_are_sealed_and_reloaded() {
+		let temp_dir = TempDir::with_prefix("recorded_entries_are_sealed_and_reloaded").unwrap();
+		let shard = ShardIdentifier::repeat_byte(1);
+		let store = AuditLogStore::new(temp_dir.path().to_path_buf());
+
+		store.record_dropped(&shard, &H256::repeat_byte(2), DropReason::Banned).unwrap();
+		store
+			.record_dropped(&shard, &H256::repeat_byte(3), DropReason::ExecutionFailed)
+			.unwrap();
+
+		let reloaded = AuditLogStore::new(temp_dir.path().to_path_buf());
+		let page = reloaded.audit_log_page(&shard, &PageRequest::default()).unwrap();
+		assert_eq!(page.items.len(), 2);
+		assert_eq!(page.items[1].operation_hash, H256::repeat_byte(3));
+		assert_eq!(page.items[1].previous_hash, page.items[0].hash());
+	}
+
+	#[test]
+	fn shards_have_separate_logs_with_retention() {
+		let temp_dir = TempDir::with_prefix("shards_have_separate_logs_with_retention").unwrap();
+		let (shard_a, shard_b) = (ShardIdentifier::repeat_byte(1), ShardIdentifier::repeat_byte(2));
+		let store = AuditLogStore::with_retention(temp_dir.path().to_path_buf(), 2);
+
+		for i in 0..3 {
+			store
+				.record_dropped(&shard_a, &H256::repeat_byte(i), DropReason::Expired)
+				.unwrap();
+		}
+		store.record_dropped(&shard_b, &H256::zero(), DropReason::Evicted).unwrap();
+
+		let page_a = store.audit_log_page(&shard_a, &PageRequest::default()).unwrap();
+		let page_b = store.audit_log_page(&shard_b, &PageRequest::default()).unwrap();
+		assert_eq!(page_a.items.iter().map(|e| e.index).collect::<Vec<_>>(), vec![1, 2]);
+		assert_eq!(page_b.items.len(), 1);
+		assert_eq!(page_b.items[0].index, 0);
+	}
+}
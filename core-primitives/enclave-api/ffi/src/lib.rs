@@ -73,6 +73,19 @@ extern "C" {
 
 	pub fn execute_trusted_calls(eid: sgx_enclave_id_t, retval: *mut sgx_status_t) -> sgx_status_t;
 
+	pub fn refresh_reference_time(eid: sgx_enclave_id_t, retval: *mut sgx_status_t)
+		-> sgx_status_t;
+
+	pub fn replay_sidechain_block(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		shard: *const u8,
+		shard_size: u32,
+		block_number: u64,
+		report: *mut u8,
+		report_size: u32,
+	) -> sgx_status_t;
+
 	pub fn sync_parentchain(
 		eid: sgx_enclave_id_t,
 		retval: *mut sgx_status_t,
@@ -126,6 +139,101 @@ extern "C" {
 		pubkey_size: u32,
 	) -> sgx_status_t;
 
+	pub fn dump_state(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		shard: *const u8,
+		shard_size: u32,
+		dump: *mut u8,
+		dump_size: u32,
+	) -> sgx_status_t;
+
+	pub fn publish_state_snapshot(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		shard: *const u8,
+		shard_size: u32,
+		commitment: *mut u8,
+		commitment_size: u32,
+	) -> sgx_status_t;
+
+	pub fn publish_worker_record(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		trusted_url: *const u8,
+		trusted_url_size: u32,
+		untrusted_url: *const u8,
+		untrusted_url_size: u32,
+		record: *mut u8,
+		record_size: u32,
+	) -> sgx_status_t;
+
+	pub fn bootstrap_shard_from_state_snapshot(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		commitment: *const u8,
+		commitment_size: u32,
+		publication_header: *const u8,
+		publication_header_size: u32,
+		snapshot: *const u8,
+		snapshot_size: u32,
+	) -> sgx_status_t;
+
+	pub fn init_shard_with_genesis(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		shard: *const u8,
+		shard_size: u32,
+		genesis_config: *const u8,
+		genesis_config_size: u32,
+	) -> sgx_status_t;
+
+	pub fn get_shard_vault_mode(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		shard: *const u8,
+		shard_size: u32,
+		vault_mode: *mut u8,
+		vault_mode_size: u32,
+	) -> sgx_status_t;
+
+	pub fn request_key_handover(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		shard: *const u8,
+		shard_size: u32,
+		target_info: *const u8,
+		target_info_size: u32,
+		request: *mut u8,
+		request_size: u32,
+	) -> sgx_status_t;
+
+	pub fn import_handed_over_keys(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		shard: *const u8,
+		shard_size: u32,
+		response: *const u8,
+		response_size: u32,
+	) -> sgx_status_t;
+
+	pub fn retire_shard(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		shard: *const u8,
+		shard_size: u32,
+		unshield_vault_funds: c_int,
+		report: *mut u8,
+		report_size: u32,
+	) -> sgx_status_t;
+
+	pub fn set_memory_limits(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		limits: *const u8,
+		limits_size: u32,
+	) -> sgx_status_t;
+
 	pub fn get_mrenclave(
 		eid: sgx_enclave_id_t,
 		retval: *mut sgx_status_t,
@@ -221,6 +329,15 @@ extern "C" {
 		unchecked_extrinsic_size: u32,
 	) -> sgx_status_t;
 
+	pub fn update_oracle_feed_xt(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		feed_config: *const u8,
+		feed_config_size: u32,
+		unchecked_extrinsic: *mut u8,
+		unchecked_extrinsic_size: u32,
+	) -> sgx_status_t;
+
 	pub fn update_weather_data_xt(
 		eid: sgx_enclave_id_t,
 		retval: *mut sgx_status_t,
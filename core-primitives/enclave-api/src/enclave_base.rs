@@ -17,10 +17,18 @@
 */
 
 use crate::EnclaveResult;
-use codec::Decode;
+use codec::{Decode, Encode};
 use core::fmt::Debug;
 use itc_parentchain::primitives::{ParentchainId, ParentchainInitParams};
-use itp_types::ShardIdentifier;
+use itp_memory_accounting::MemoryLimits;
+use itp_types::{
+	enclave_upgrade::{KeyHandoverRequest, KeyHandoverResponse},
+	shard_lifecycle::{ShardGenesisConfig, ShardRetirementReport, VaultMode},
+	state_dump::StateDump,
+	state_snapshot::StateSnapshotCommitment,
+	worker_directory::SignedWorkerRecord,
+	ShardIdentifier,
+};
 use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
 use sp_core::ed25519;
 use teerex_primitives::EnclaveFingerprint;
@@ -74,6 +82,77 @@ pub trait EnclaveBase: Send + Sync + 'static {
 	fn get_ecc_vault_pubkey(&self, shard: &ShardIdentifier) -> EnclaveResult<ed25519::Public>;
 
 	fn get_fingerprint(&self) -> EnclaveResult<EnclaveFingerprint>;
+
+	/// Dumps all storage entries of the shard state.
+	/// Requires an enclave built with the `state-dump` feature.
+	fn dump_state(&self, shard: &ShardIdentifier) -> EnclaveResult<StateDump>;
+
+	/// Publishes the encrypted current state of the shard on IPFS and commits
+	/// its CID and state hash on the parentchain.
+	fn publish_state_snapshot(
+		&self,
+		shard: &ShardIdentifier,
+	) -> EnclaveResult<StateSnapshotCommitment>;
+
+	/// Replaces the state of the shard with a snapshot fetched from IPFS, after verifying
+	/// it against the commitment. The commitment must be published in the parentchain block
+	/// of `publication_header`, which must be recent enough to be proven by the light client.
+	/// Requires the state key of the shard to be provisioned.
+	fn bootstrap_shard_from_state_snapshot<Header: Encode>(
+		&self,
+		commitment: &StateSnapshotCommitment,
+		publication_header: &Header,
+		snapshot: &[u8],
+	) -> EnclaveResult<()>;
+
+	/// Signs the directory record of this worker, reachable under the given external URLs,
+	/// and publishes it on the parentchain.
+	fn publish_worker_record(
+		&self,
+		trusted_url: &str,
+		untrusted_url: &str,
+	) -> EnclaveResult<SignedWorkerRecord>;
+
+	/// Creates the state of a new shard with the genesis config and registers the shard config
+	/// on the parentchain. Requires the parentchain components to be initialized.
+	fn init_shard_with_genesis(
+		&self,
+		shard: &ShardIdentifier,
+		genesis_config: &ShardGenesisConfig,
+	) -> EnclaveResult<()>;
+
+	/// Vault mode the shard has been created with.
+	fn get_shard_vault_mode(&self, shard: &ShardIdentifier) -> EnclaveResult<VaultMode>;
+
+	/// Stops the authoring of the shard by putting it into maintenance mode on the parentchain,
+	/// publishes a snapshot of its final state and optionally pays out the funds of all accounts
+	/// from the shard vault. Requires the parentchain and sidechain components to be initialized.
+	///
+	/// Returns `None` while the retirement waits for the maintenance mode to be finalized, the
+	/// parentchain has to be synced before calling it again.
+	fn retire_shard(
+		&self,
+		shard: &ShardIdentifier,
+		unshield_vault_funds: bool,
+	) -> EnclaveResult<Option<ShardRetirementReport>>;
+
+	/// Requests the keys of the enclave with `target_info`, which is upgraded to this one.
+	/// Both enclaves have to run on the same platform.
+	fn request_key_handover(
+		&self,
+		shard: &ShardIdentifier,
+		target_info: &[u8],
+	) -> EnclaveResult<KeyHandoverRequest>;
+
+	/// Seals the keys and the shard state handed over in response to the last request.
+	fn import_handed_over_keys(
+		&self,
+		shard: &ShardIdentifier,
+		response: &KeyHandoverResponse,
+	) -> EnclaveResult<()>;
+
+	/// Sets the heap limits above which the enclave rejects new trusted operations and getters.
+	fn set_memory_limits(&self, limits: &MemoryLimits) -> EnclaveResult<()>;
 }
 
 /// EnclaveApi implementation for Enclave struct
@@ -86,10 +165,19 @@ mod impl_ffi {
 	use frame_support::ensure;
 	use itc_parentchain::primitives::{ParentchainId, ParentchainInitParams};
 	use itp_enclave_api_ffi as ffi;
+	use itp_memory_accounting::MemoryLimits;
 	use itp_settings::worker::{
 		HEADER_MAX_SIZE, MR_ENCLAVE_SIZE, SHIELDING_KEY_SIZE, SIGNING_KEY_SIZE,
+		STATE_DUMP_MAX_SIZE, WORKER_RECORD_MAX_SIZE,
+	};
+	use itp_types::{
+		enclave_upgrade::{KeyHandoverRequest, KeyHandoverResponse},
+		shard_lifecycle::{ShardGenesisConfig, ShardRetirementReport, VaultMode},
+		state_dump::StateDump,
+		state_snapshot::StateSnapshotCommitment,
+		worker_directory::SignedWorkerRecord,
+		ShardIdentifier,
 	};
-	use itp_types::ShardIdentifier;
 	use log::*;
 	use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
 	use sgx_types::*;
@@ -354,6 +442,257 @@ mod impl_ffi {
 
 			Ok(mr_enclave.into())
 		}
+
+		fn dump_state(&self, shard: &ShardIdentifier) -> EnclaveResult<StateDump> {
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+			let shard_bytes = shard.encode();
+			let mut dump = vec![0u8; STATE_DUMP_MAX_SIZE];
+
+			let result = unsafe {
+				ffi::dump_state(
+					self.eid,
+					&mut retval,
+					shard_bytes.as_ptr(),
+					shard_bytes.len() as u32,
+					dump.as_mut_ptr(),
+					dump.len() as u32,
+				)
+			};
+
+			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			Ok(Decode::decode(&mut dump.as_slice())?)
+		}
+
+		fn publish_state_snapshot(
+			&self,
+			shard: &ShardIdentifier,
+		) -> EnclaveResult<StateSnapshotCommitment> {
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+			let shard_bytes = shard.encode();
+			let mut commitment = vec![0u8; StateSnapshotCommitment::ENCODED_SIZE];
+
+			let result = unsafe {
+				ffi::publish_state_snapshot(
+					self.eid,
+					&mut retval,
+					shard_bytes.as_ptr(),
+					shard_bytes.len() as u32,
+					commitment.as_mut_ptr(),
+					commitment.len() as u32,
+				)
+			};
+
+			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			Ok(Decode::decode(&mut commitment.as_slice())?)
+		}
+
+		fn bootstrap_shard_from_state_snapshot<Header: Encode>(
+			&self,
+			commitment: &StateSnapshotCommitment,
+			publication_header: &Header,
+			snapshot: &[u8],
+		) -> EnclaveResult<()> {
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+			let commitment_bytes = commitment.encode();
+			let header_bytes = publication_header.encode();
+
+			let result = unsafe {
+				ffi::bootstrap_shard_from_state_snapshot(
+					self.eid,
+					&mut retval,
+					commitment_bytes.as_ptr(),
+					commitment_bytes.len() as u32,
+					header_bytes.as_ptr(),
+					header_bytes.len() as u32,
+					snapshot.as_ptr(),
+					snapshot.len() as u32,
+				)
+			};
+
+			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			Ok(())
+		}
+
+		fn publish_worker_record(
+			&self,
+			trusted_url: &str,
+			untrusted_url: &str,
+		) -> EnclaveResult<SignedWorkerRecord> {
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+			let mut record = vec![0u8; WORKER_RECORD_MAX_SIZE];
+
+			let result = unsafe {
+				ffi::publish_worker_record(
+					self.eid,
+					&mut retval,
+					trusted_url.as_ptr(),
+					trusted_url.len() as u32,
+					untrusted_url.as_ptr(),
+					untrusted_url.len() as u32,
+					record.as_mut_ptr(),
+					record.len() as u32,
+				)
+			};
+
+			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			Ok(Decode::decode(&mut record.as_slice())?)
+		}
+
+		fn init_shard_with_genesis(
+			&self,
+			shard: &ShardIdentifier,
+			genesis_config: &ShardGenesisConfig,
+		) -> EnclaveResult<()> {
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+			let shard_bytes = shard.encode();
+			let genesis_config_bytes = genesis_config.encode();
+
+			let result = unsafe {
+				ffi::init_shard_with_genesis(
+					self.eid,
+					&mut retval,
+					shard_bytes.as_ptr(),
+					shard_bytes.len() as u32,
+					genesis_config_bytes.as_ptr(),
+					genesis_config_bytes.len() as u32,
+				)
+			};
+
+			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			Ok(())
+		}
+
+		fn get_shard_vault_mode(&self, shard: &ShardIdentifier) -> EnclaveResult<VaultMode> {
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+			let shard_bytes = shard.encode();
+			let mut vault_mode = [0u8; 1];
+
+			let result = unsafe {
+				ffi::get_shard_vault_mode(
+					self.eid,
+					&mut retval,
+					shard_bytes.as_ptr(),
+					shard_bytes.len() as u32,
+					vault_mode.as_mut_ptr(),
+					vault_mode.len() as u32,
+				)
+			};
+
+			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			Ok(Decode::decode(&mut vault_mode.as_slice())?)
+		}
+
+		fn retire_shard(
+			&self,
+			shard: &ShardIdentifier,
+			unshield_vault_funds: bool,
+		) -> EnclaveResult<Option<ShardRetirementReport>> {
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+			let shard_bytes = shard.encode();
+			let mut report = vec![0u8; 1 + ShardRetirementReport::ENCODED_SIZE];
+
+			let result = unsafe {
+				ffi::retire_shard(
+					self.eid,
+					&mut retval,
+					shard_bytes.as_ptr(),
+					shard_bytes.len() as u32,
+					unshield_vault_funds.into(),
+					report.as_mut_ptr(),
+					report.len() as u32,
+				)
+			};
+
+			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			Ok(Decode::decode(&mut report.as_slice())?)
+		}
+
+		fn request_key_handover(
+			&self,
+			shard: &ShardIdentifier,
+			target_info: &[u8],
+		) -> EnclaveResult<KeyHandoverRequest> {
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+			let shard_bytes = shard.encode();
+			let mut request = vec![0u8; KeyHandoverRequest::ENCODED_SIZE];
+
+			let result = unsafe {
+				ffi::request_key_handover(
+					self.eid,
+					&mut retval,
+					shard_bytes.as_ptr(),
+					shard_bytes.len() as u32,
+					target_info.as_ptr(),
+					target_info.len() as u32,
+					request.as_mut_ptr(),
+					request.len() as u32,
+				)
+			};
+
+			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			Ok(Decode::decode(&mut request.as_slice())?)
+		}
+
+		fn import_handed_over_keys(
+			&self,
+			shard: &ShardIdentifier,
+			response: &KeyHandoverResponse,
+		) -> EnclaveResult<()> {
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+			let shard_bytes = shard.encode();
+			let response_bytes = response.encode();
+
+			let result = unsafe {
+				ffi::import_handed_over_keys(
+					self.eid,
+					&mut retval,
+					shard_bytes.as_ptr(),
+					shard_bytes.len() as u32,
+					response_bytes.as_ptr(),
+					response_bytes.len() as u32,
+				)
+			};
+
+			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			Ok(())
+		}
+
+		fn set_memory_limits(&self, limits: &MemoryLimits) -> EnclaveResult<()> {
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+			let limits_bytes = limits.encode();
+
+			let result = unsafe {
+				ffi::set_memory_limits(
+					self.eid,
+					&mut retval,
+					limits_bytes.as_ptr(),
+					limits_bytes.len() as u32,
+				)
+			};
+
+			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			Ok(())
+		}
 	}
 
 	fn init_parentchain_components_ffi(
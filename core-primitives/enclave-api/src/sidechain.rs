@@ -19,7 +19,7 @@
 use crate::EnclaveResult;
 use codec::Encode;
 use itp_storage::StorageProof;
-use itp_types::parentchain::ParentchainId;
+use itp_types::{parentchain::ParentchainId, replay::BlockReplayReport, ShardIdentifier};
 use sp_runtime::generic::SignedBlock;
 
 /// trait for handling blocks on the side chain
@@ -35,17 +35,30 @@ pub trait Sidechain: Send + Sync + 'static {
 	) -> EnclaveResult<()>;
 
 	fn execute_trusted_calls(&self) -> EnclaveResult<()>;
+
+	/// Cross-checks the host clock against the reference time source, if one is configured
+	/// and its refresh interval has elapsed. Blocks for the HTTPS round trip, so it must not
+	/// be called from the slot worker.
+	fn refresh_reference_time(&self) -> EnclaveResult<()>;
+
+	/// Re-executes a recorded sidechain block and compares the result with the recorded one.
+	fn replay_block(
+		&self,
+		shard: &ShardIdentifier,
+		block_number: u64,
+	) -> EnclaveResult<BlockReplayReport>;
 }
 
 #[cfg(feature = "implement-ffi")]
 mod impl_ffi {
 	use super::Sidechain;
 	use crate::{error::Error, Enclave, EnclaveResult};
-	use codec::Encode;
+	use codec::{Decode, Encode};
 	use frame_support::ensure;
 	use itp_enclave_api_ffi as ffi;
+	use itp_settings::worker::BLOCK_REPLAY_REPORT_MAX_SIZE;
 	use itp_storage::StorageProof;
-	use itp_types::parentchain::ParentchainId;
+	use itp_types::{parentchain::ParentchainId, replay::BlockReplayReport, ShardIdentifier};
 	use sgx_types::sgx_status_t;
 	use sp_runtime::generic::SignedBlock;
 
@@ -94,5 +107,43 @@ mod impl_ffi {
 
 			Ok(())
 		}
+
+		fn refresh_reference_time(&self) -> EnclaveResult<()> {
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+
+			let result = unsafe { ffi::refresh_reference_time(self.eid, &mut retval) };
+
+			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			Ok(())
+		}
+
+		fn replay_block(
+			&self,
+			shard: &ShardIdentifier,
+			block_number: u64,
+		) -> EnclaveResult<BlockReplayReport> {
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+			let shard_bytes = shard.encode();
+			let mut report = vec![0u8; BLOCK_REPLAY_REPORT_MAX_SIZE];
+
+			let result = unsafe {
+				ffi::replay_sidechain_block(
+					self.eid,
+					&mut retval,
+					shard_bytes.as_ptr(),
+					shard_bytes.len() as u32,
+					block_number,
+					report.as_mut_ptr(),
+					report.len() as u32,
+				)
+			};
+
+			ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			Ok(Decode::decode(&mut report.as_slice())?)
+		}
 	}
 }
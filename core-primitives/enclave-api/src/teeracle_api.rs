@@ -16,6 +16,7 @@
 */
 
 use crate::EnclaveResult;
+use itp_types::oracle::OracleFeedConfig;
 
 pub trait TeeracleApi: Send + Sync + 'static {
 	/// Update the currency market data for the token oracle.
@@ -25,6 +26,9 @@ pub trait TeeracleApi: Send + Sync + 'static {
 		fiat_currency: &str,
 	) -> EnclaveResult<Vec<u8>>;
 
+	/// Update the exchange rate of the feed with the median over its data sources.
+	fn update_oracle_feed_xt(&self, feed_config: &OracleFeedConfig) -> EnclaveResult<Vec<u8>>;
+
 	/// Update weather data for the corresponding coordinates.
 	fn update_weather_data_xt(&self, longitude: &str, latitude: &str) -> EnclaveResult<Vec<u8>>;
 }
@@ -36,6 +40,7 @@ mod impl_ffi {
 	use codec::Encode;
 	use frame_support::ensure;
 	use itp_enclave_api_ffi as ffi;
+	use itp_types::oracle::OracleFeedConfig;
 	use log::*;
 	use sgx_types::*;
 	impl TeeracleApi for Enclave {
@@ -73,6 +78,32 @@ mod impl_ffi {
 
 			Ok(response)
 		}
+
+		fn update_oracle_feed_xt(&self, feed_config: &OracleFeedConfig) -> EnclaveResult<Vec<u8>> {
+			info!("TeeracleApi update_oracle_feed_xt in with {:?}", feed_config);
+			let mut retval = sgx_status_t::SGX_SUCCESS;
+			let response_len = 8192;
+			let mut response: Vec<u8> = vec![0u8; response_len as usize];
+
+			let feed_config = feed_config.encode();
+
+			let res = unsafe {
+				ffi::update_oracle_feed_xt(
+					self.eid,
+					&mut retval,
+					feed_config.as_ptr(),
+					feed_config.len() as u32,
+					response.as_mut_ptr(),
+					response_len,
+				)
+			};
+
+			ensure!(res == sgx_status_t::SGX_SUCCESS, Error::Sgx(res));
+			ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+			Ok(response)
+		}
+
 		fn update_weather_data_xt(
 			&self,
 			longitude: &str,
@@ -24,7 +24,8 @@ compile_error!("feature \"std\" and feature \"sgx\" cannot be enabled at the sam
 extern crate sgx_tstd as std;
 
 use codec::{Decode, Encode};
-use std::string::String;
+use core::fmt::Debug;
+use std::{format, string::String};
 use substrate_fixed::types::U32F32;
 
 // FIXME: Copied from ita-oracle because of cyclic deps. Should be removed after integritee-network/pallets#71
@@ -37,9 +38,24 @@ pub enum EnclaveMetric {
 	TopPoolSizeIncrement,
 	TopPoolSizeDecrement,
 	ExchangeRateOracle(ExchangeRateOracleMetric),
+	/// Enclave heap usage in bytes - (Subsystem, Usage)
+	HeapUsage(String, u64),
+	/// Liveness signal of a long-running enclave thread - (Thread)
+	Heartbeat(String),
 	// OracleMetric(OracleMetric<MetricsInfo>),
 }
 
+/// Thread executing the sidechain slots.
+pub const SLOT_WORKER_THREAD: &str = "slot_worker";
+
+/// Thread running the direct invocation (trusted RPC) server.
+pub const DIRECT_RPC_SERVER_THREAD: &str = "direct_rpc_server";
+
+/// Thread syncing the parentchain with the given id.
+pub fn parentchain_sync_thread<Id: Debug>(parentchain_id: &Id) -> String {
+	format!("{:?}_parentchain_sync", parentchain_id)
+}
+
 #[derive(Encode, Decode, Debug)]
 pub enum ExchangeRateOracleMetric {
 	/// Exchange Rate from CoinGecko - (Source, TradingPair, ExchangeRate)
@@ -0,0 +1,157 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Per-subsystem usage counters and limits.
+
+use crate::{Error, MemoryLimits, Result, Subsystem};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Accounting used by the enclave's global allocator.
+pub static GLOBAL_MEMORY_ACCOUNTING: MemoryAccounting = MemoryAccounting::new();
+
+/// Value of a limit that is not set.
+const UNLIMITED: usize = 0;
+
+/// Heap usage and limits per [`Subsystem`].
+///
+/// Only uses atomics, so it can be updated from within the allocator without allocating
+/// or locking.
+pub struct MemoryAccounting {
+	usage: [AtomicUsize; Subsystem::COUNT],
+	limits: [AtomicUsize; Subsystem::COUNT],
+	total_limit: AtomicUsize,
+}
+
+impl MemoryAccounting {
+	#[allow(clippy::declare_interior_mutable_const)]
+	const ZERO: AtomicUsize = AtomicUsize::new(0);
+
+	pub const fn new() -> Self {
+		MemoryAccounting {
+			usage: [Self::ZERO; Subsystem::COUNT],
+			limits: [Self::ZERO; Subsystem::COUNT],
+			total_limit: AtomicUsize::new(UNLIMITED),
+		}
+	}
+
+	pub fn usage(&self, subsystem: Subsystem) -> usize {
+		self.usage[subsystem.index()].load(Ordering::Relaxed)
+	}
+
+	pub fn total_usage(&self) -> usize {
+		self.usage.iter().map(|u| u.load(Ordering::Relaxed)).sum()
+	}
+
+	pub fn set_limit(&self, subsystem: Subsystem, limit: Option<usize>) {
+		self.limits[subsystem.index()].store(limit.unwrap_or(UNLIMITED), Ordering::Relaxed)
+	}
+
+	pub fn set_total_limit(&self, limit: Option<usize>) {
+		self.total_limit.store(limit.unwrap_or(UNLIMITED), Ordering::Relaxed)
+	}
+
+	/// Replaces all limits with `limits`.
+	pub fn set_limits(&self, limits: &MemoryLimits) {
+		self.set_total_limit(limits.total.map(saturating_usize));
+		for subsystem in Subsystem::ALL {
+			let limit = limits
+				.subsystems
+				.iter()
+				.find(|(s, _)| *s == subsystem)
+				.map(|(_, l)| saturating_usize(*l));
+			self.set_limit(subsystem, limit);
+		}
+	}
+
+	/// Fails if either the total usage or the usage of `subsystem` has reached its limit.
+	pub fn check_admission(&self, subsystem: Subsystem) -> Result<()> {
+		let total_limit = self.total_limit.load(Ordering::Relaxed);
+		let total_usage = self.total_usage();
+		if total_limit != UNLIMITED && total_usage >= total_limit {
+			return Err(Error::ResourceExhausted {
+				subsystem,
+				usage: total_usage,
+				limit: total_limit,
+			})
+		}
+
+		let limit = self.limits[subsystem.index()].load(Ordering::Relaxed);
+		let usage = self.usage(subsystem);
+		if limit != UNLIMITED && usage >= limit {
+			return Err(Error::ResourceExhausted { subsystem, usage, limit })
+		}
+		Ok(())
+	}
+
+	pub(crate) fn add(&self, subsystem: Subsystem, size: usize) {
+		self.usage[subsystem.index()].fetch_add(size, Ordering::Relaxed);
+	}
+
+	pub(crate) fn sub(&self, subsystem: Subsystem, size: usize) {
+		self.usage[subsystem.index()].fetch_sub(size, Ordering::Relaxed);
+	}
+}
+
+impl Default for MemoryAccounting {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+fn saturating_usize(value: u64) -> usize {
+	usize::try_from(value).unwrap_or(usize::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn admission_is_granted_without_limits() {
+		let accounting = MemoryAccounting::new();
+		accounting.add(Subsystem::TopPool, 1 << 20);
+
+		assert!(accounting.check_admission(Subsystem::TopPool).is_ok());
+	}
+
+	#[test]
+	fn subsystem_limit_only_affects_its_subsystem() {
+		let accounting = MemoryAccounting::new();
+		accounting.set_limit(Subsystem::TopPool, Some(100));
+		accounting.add(Subsystem::TopPool, 100);
+
+		assert_eq!(
+			accounting.check_admission(Subsystem::TopPool),
+			Err(Error::ResourceExhausted { subsystem: Subsystem::TopPool, usage: 100, limit: 100 })
+		);
+		assert!(accounting.check_admission(Subsystem::GetterExecution).is_ok());
+
+		accounting.sub(Subsystem::TopPool, 1);
+		assert!(accounting.check_admission(Subsystem::TopPool).is_ok());
+	}
+
+	#[test]
+	fn total_limit_affects_all_subsystems() {
+		let accounting = MemoryAccounting::new();
+		accounting.set_limits(&MemoryLimits { total: Some(100), subsystems: vec![] });
+		accounting.add(Subsystem::StateCache, 60);
+		accounting.add(Subsystem::Untracked, 40);
+
+		assert!(accounting.check_admission(Subsystem::TopPool).is_err());
+		assert!(accounting.check_admission(Subsystem::GetterExecution).is_err());
+	}
+}
@@ -0,0 +1,146 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Allocator wrapper that keeps the [`MemoryAccounting`] up to date.
+
+use crate::{current_subsystem, MemoryAccounting, Subsystem};
+use core::{
+	alloc::{GlobalAlloc, Layout},
+	cmp::max,
+	mem::{align_of, size_of},
+};
+
+/// Wraps an allocator and accounts every allocation to the subsystem of the allocating thread.
+///
+/// The subsystem is stored in a header in front of each allocation, so memory that is freed
+/// from a different subsystem scope (e.g. a trusted operation that is removed from the pool
+/// by the block producer) is still deducted from the subsystem it was accounted to.
+pub struct AccountingAllocator<A> {
+	inner: A,
+	accounting: &'static MemoryAccounting,
+}
+
+impl<A> AccountingAllocator<A> {
+	pub const fn new(inner: A, accounting: &'static MemoryAccounting) -> Self {
+		AccountingAllocator { inner, accounting }
+	}
+}
+
+/// Offset of the user data from the start of the underlying allocation.
+///
+/// Is a multiple of the requested alignment and leaves room for the header word.
+fn header_offset(layout: &Layout) -> usize {
+	max(layout.align(), size_of::<usize>())
+}
+
+/// Layout of the underlying allocation, `None` if it would overflow.
+fn outer_layout(layout: &Layout, size: usize) -> Option<Layout> {
+	let size = size.checked_add(header_offset(layout))?;
+	Layout::from_size_align(size, max(layout.align(), align_of::<usize>())).ok()
+}
+
+impl<A> AccountingAllocator<A> {
+	unsafe fn finish_alloc(&self, outer: *mut u8, layout: &Layout) -> *mut u8 {
+		if outer.is_null() {
+			return outer
+		}
+		let subsystem = current_subsystem();
+		let ptr = outer.add(header_offset(layout));
+		(ptr.sub(size_of::<usize>()) as *mut usize).write(subsystem.index());
+		self.accounting.add(subsystem, layout.size());
+		ptr
+	}
+
+	unsafe fn subsystem_of(ptr: *mut u8) -> Subsystem {
+		Subsystem::from_index((ptr.sub(size_of::<usize>()) as *const usize).read())
+	}
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for AccountingAllocator<A> {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		match outer_layout(&layout, layout.size()) {
+			Some(outer) => self.finish_alloc(self.inner.alloc(outer), &layout),
+			None => core::ptr::null_mut(),
+		}
+	}
+
+	unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+		match outer_layout(&layout, layout.size()) {
+			Some(outer) => self.finish_alloc(self.inner.alloc_zeroed(outer), &layout),
+			None => core::ptr::null_mut(),
+		}
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		let subsystem = Self::subsystem_of(ptr);
+		self.accounting.sub(subsystem, layout.size());
+		let outer =
+			outer_layout(&layout, layout.size()).expect("Layout was valid when allocating; qed");
+		self.inner.dealloc(ptr.sub(header_offset(&layout)), outer);
+	}
+
+	unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		let outer =
+			outer_layout(&layout, layout.size()).expect("Layout was valid when allocating; qed");
+		let new_outer_size = match outer_layout(&layout, new_size) {
+			Some(new_outer) => new_outer.size(),
+			None => return core::ptr::null_mut(),
+		};
+		// The allocation stays with the subsystem it was originally accounted to.
+		let subsystem = Self::subsystem_of(ptr);
+		let offset = header_offset(&layout);
+		let new_outer_ptr = self.inner.realloc(ptr.sub(offset), outer, new_outer_size);
+		if new_outer_ptr.is_null() {
+			return new_outer_ptr
+		}
+		self.accounting.sub(subsystem, layout.size());
+		self.accounting.add(subsystem, new_size);
+		new_outer_ptr.add(offset)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::enter;
+	use std::alloc::System;
+
+	static TEST_ACCOUNTING: MemoryAccounting = MemoryAccounting::new();
+
+	#[test]
+	fn allocations_are_accounted_to_the_allocating_subsystem() {
+		let allocator = AccountingAllocator::new(System, &TEST_ACCOUNTING);
+		let layout = Layout::from_size_align(100, 32).unwrap();
+
+		let ptr = {
+			let _scope = enter(Subsystem::LightClient);
+			unsafe { allocator.alloc(layout) }
+		};
+		assert!(!ptr.is_null());
+		assert_eq!(ptr as usize % 32, 0);
+		assert_eq!(TEST_ACCOUNTING.usage(Subsystem::LightClient), 100);
+
+		let ptr = unsafe { allocator.realloc(ptr, layout, 300) };
+		assert!(!ptr.is_null());
+		assert_eq!(TEST_ACCOUNTING.usage(Subsystem::LightClient), 300);
+
+		// Freed outside of the subsystem scope, but still deducted from it.
+		unsafe { allocator.dealloc(ptr, Layout::from_size_align(300, 32).unwrap()) };
+		assert_eq!(TEST_ACCOUNTING.usage(Subsystem::LightClient), 0);
+		assert_eq!(TEST_ACCOUNTING.usage(Subsystem::Untracked), 0);
+	}
+}
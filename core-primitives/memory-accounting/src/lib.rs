@@ -0,0 +1,119 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Heap accounting and admission control for the enclave.
+//!
+//! The enclave has a fixed heap (see `HeapMaxSize` in the enclave config) and running out of it
+//! aborts the enclave. [`AccountingAllocator`] wraps the global allocator and attributes every
+//! allocation to the [`Subsystem`] that is active on the allocating thread, see [`enter`].
+//! Components that accept untrusted work call [`check_admission`] first, so they can reject a
+//! request with [`Error::ResourceExhausted`] before the heap is exhausted.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(all(feature = "std", feature = "sgx"))]
+compile_error!("feature \"std\" and feature \"sgx\" cannot be enabled at the same time");
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+#[macro_use]
+extern crate sgx_tstd as std;
+
+// re-export module to properly feature gate sgx and regular std environment
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+pub mod sgx_reexport_prelude {
+	pub use thiserror_sgx as thiserror;
+}
+
+pub use accounting::{MemoryAccounting, GLOBAL_MEMORY_ACCOUNTING};
+pub use allocator::AccountingAllocator;
+pub use error::{Error, Result};
+pub use scope::{current_subsystem, enter, Scoped, SubsystemScope};
+
+use codec::{Decode, Encode};
+use std::vec::Vec;
+
+pub mod accounting;
+pub mod allocator;
+pub mod error;
+pub mod scope;
+
+/// Enclave component that heap allocations are attributed to.
+#[derive(Encode, Decode, Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Subsystem {
+	/// Everything that is not allocated within a subsystem scope.
+	Untracked = 0,
+	/// Trusted operations submitted to the top pool.
+	TopPool = 1,
+	/// Cached shard states of the state handler.
+	StateCache = 2,
+	/// Parentchain light client validation.
+	LightClient = 3,
+	/// Execution of trusted getters.
+	GetterExecution = 4,
+}
+
+impl Subsystem {
+	pub const COUNT: usize = 5;
+
+	pub const ALL: [Subsystem; Self::COUNT] = [
+		Subsystem::Untracked,
+		Subsystem::TopPool,
+		Subsystem::StateCache,
+		Subsystem::LightClient,
+		Subsystem::GetterExecution,
+	];
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			Subsystem::Untracked => "untracked",
+			Subsystem::TopPool => "top_pool",
+			Subsystem::StateCache => "state_cache",
+			Subsystem::LightClient => "light_client",
+			Subsystem::GetterExecution => "getter_execution",
+		}
+	}
+
+	pub(crate) fn index(&self) -> usize {
+		*self as usize
+	}
+
+	pub(crate) fn from_index(index: usize) -> Self {
+		Self::ALL.get(index).copied().unwrap_or(Subsystem::Untracked)
+	}
+}
+
+/// Heap limits in bytes, passed from the untrusted worker configuration into the enclave.
+///
+/// A subsystem without an entry is only bounded by the `total` limit.
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryLimits {
+	/// Limit on the overall tracked heap usage, `None` means unlimited.
+	pub total: Option<u64>,
+	/// Limits of individual subsystems.
+	pub subsystems: Vec<(Subsystem, u64)>,
+}
+
+/// Checks whether `subsystem` may take on more work, using the global accounting.
+pub fn check_admission(subsystem: Subsystem) -> Result<()> {
+	GLOBAL_MEMORY_ACCOUNTING.check_admission(subsystem)
+}
+
+/// Applies `limits` to the global accounting.
+pub fn set_limits(limits: &MemoryLimits) {
+	GLOBAL_MEMORY_ACCOUNTING.set_limits(limits)
+}
@@ -0,0 +1,102 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Thread-local selection of the subsystem that allocations are attributed to.
+
+use crate::Subsystem;
+use core::{
+	cell::Cell,
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+};
+
+thread_local! {
+	static CURRENT_SUBSYSTEM: Cell<Subsystem> = Cell::new(Subsystem::Untracked);
+}
+
+/// Attributes all allocations of the current thread to `subsystem` until the returned
+/// scope is dropped. Scopes can be nested, dropping restores the previous subsystem.
+#[must_use]
+pub fn enter(subsystem: Subsystem) -> SubsystemScope {
+	let previous = CURRENT_SUBSYSTEM
+		.try_with(|current| current.replace(subsystem))
+		.unwrap_or(Subsystem::Untracked);
+	SubsystemScope { previous }
+}
+
+/// Subsystem that allocations of the current thread are currently attributed to.
+pub fn current_subsystem() -> Subsystem {
+	// Fails only while the thread is being torn down.
+	CURRENT_SUBSYSTEM
+		.try_with(|current| current.get())
+		.unwrap_or(Subsystem::Untracked)
+}
+
+/// Guard returned by [`enter`].
+pub struct SubsystemScope {
+	previous: Subsystem,
+}
+
+impl Drop for SubsystemScope {
+	fn drop(&mut self) {
+		let _ = CURRENT_SUBSYSTEM.try_with(|current| current.set(self.previous));
+	}
+}
+
+/// Polls the wrapped future within the scope of a subsystem.
+///
+/// Needed for futures that allocate when polled by an executor outside of the scope, like
+/// the futures returned by the top pool.
+pub struct Scoped<F> {
+	inner: F,
+	subsystem: Subsystem,
+}
+
+impl<F> Scoped<F> {
+	pub fn new(subsystem: Subsystem, inner: F) -> Self {
+		Scoped { inner, subsystem }
+	}
+}
+
+impl<F: Future + Unpin> Future for Scoped<F> {
+	type Output = F::Output;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let _scope = enter(self.subsystem);
+		Pin::new(&mut self.inner).poll(cx)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn nested_scopes_restore_previous_subsystem() {
+		assert_eq!(current_subsystem(), Subsystem::Untracked);
+		{
+			let _outer = enter(Subsystem::TopPool);
+			{
+				let _inner = enter(Subsystem::StateCache);
+				assert_eq!(current_subsystem(), Subsystem::StateCache);
+			}
+			assert_eq!(current_subsystem(), Subsystem::TopPool);
+		}
+		assert_eq!(current_subsystem(), Subsystem::Untracked);
+	}
+}
@@ -17,7 +17,10 @@
 
 use crate::ApiResult;
 use itp_api_client_types::{traits::GetStorage, Api, Config, Request};
-use itp_types::{AccountId, IpfsHash, MultiEnclave, ShardIdentifier, ShardStatus};
+use itp_types::{
+	enclave_upgrade::UpgradableShardConfig, AccountId, IpfsHash, MultiEnclave, ShardIdentifier,
+	ShardStatus,
+};
 use log::error;
 
 pub const TEEREX: &str = "Teerex";
@@ -45,6 +48,11 @@ pub trait PalletTeerexApi {
 		shard: &ShardIdentifier,
 		at_block: Option<Self::Hash>,
 	) -> ApiResult<Option<IpfsHash>>;
+	fn shard_config(
+		&self,
+		shard: &ShardIdentifier,
+		at_block: Option<Self::Hash>,
+	) -> ApiResult<Option<UpgradableShardConfig>>;
 }
 
 impl<RuntimeConfig, Client> PalletTeerexApi for Api<RuntimeConfig, Client>
@@ -107,4 +115,12 @@ where
 	) -> ApiResult<Option<IpfsHash>> {
 		self.get_storage_map(TEEREX, "LatestIPFSHash", shard, at_block)
 	}
+
+	fn shard_config(
+		&self,
+		shard: &ShardIdentifier,
+		at_block: Option<Self::Hash>,
+	) -> ApiResult<Option<UpgradableShardConfig>> {
+		self.get_storage_map(ENCLAVE_BRIDGE, "ShardConfigRegistry", shard, at_block)
+	}
 }
@@ -16,7 +16,10 @@
 */
 
 use crate::{pallet_teerex::PalletTeerexApi, ApiResult};
-use itp_types::{parentchain::Hash, AccountId, IpfsHash, MultiEnclave, ShardIdentifier};
+use itp_types::{
+	enclave_upgrade::UpgradableShardConfig, parentchain::Hash, AccountId, IpfsHash, MultiEnclave,
+	ShardIdentifier,
+};
 use std::collections::HashMap;
 
 #[derive(Default)]
@@ -63,4 +66,12 @@ impl PalletTeerexApi for PalletTeerexApiMock {
 	) -> ApiResult<Option<IpfsHash>> {
 		todo!()
 	}
+
+	fn shard_config(
+		&self,
+		_shard: &ShardIdentifier,
+		_at_block: Option<Hash>,
+	) -> ApiResult<Option<UpgradableShardConfig>> {
+		todo!()
+	}
 }
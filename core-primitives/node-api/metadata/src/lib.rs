@@ -20,9 +20,12 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use crate::{
-	error::Result, pallet_balances::BalancesCallIndexes,
-	pallet_enclave_bridge::EnclaveBridgeCallIndexes, pallet_proxy::ProxyCallIndexes,
-	pallet_sidechain::SidechainCallIndexes, pallet_teerex::TeerexCallIndexes,
+	error::Result, pallet_assets::AssetsCallIndexes, pallet_balances::BalancesCallIndexes,
+	pallet_enclave_bridge::EnclaveBridgeCallIndexes,
+	pallet_parachain_system::ParachainSystemCallIndexes, pallet_proxy::ProxyCallIndexes,
+	pallet_sidechain::SidechainCallIndexes, pallet_system::SystemCallIndexes,
+	pallet_teerex::TeerexCallIndexes, pallet_tokens::TokensCallIndexes,
+	pallet_utility::UtilityCallIndexes,
 };
 use codec::{Decode, Encode};
 use sp_core::storage::StorageKey;
@@ -31,12 +34,17 @@ pub use crate::error::Error;
 pub use itp_api_client_types::{Metadata, MetadataError};
 
 pub mod error;
+pub mod pallet_assets;
 pub mod pallet_balances;
 pub mod pallet_enclave_bridge;
+pub mod pallet_parachain_system;
 pub mod pallet_proxy;
 pub mod pallet_sidechain;
+pub mod pallet_system;
 pub mod pallet_teeracle;
 pub mod pallet_teerex;
+pub mod pallet_tokens;
+pub mod pallet_utility;
 
 #[cfg(feature = "mocks")]
 pub mod metadata_mocks;
@@ -47,6 +55,11 @@ pub trait NodeMetadataTrait:
 	+ SidechainCallIndexes
 	+ ProxyCallIndexes
 	+ BalancesCallIndexes
+	+ UtilityCallIndexes
+	+ AssetsCallIndexes
+	+ TokensCallIndexes
+	+ ParachainSystemCallIndexes
+	+ SystemCallIndexes
 {
 }
 impl<
@@ -54,7 +67,12 @@ impl<
 			+ EnclaveBridgeCallIndexes
 			+ SidechainCallIndexes
 			+ ProxyCallIndexes
-			+ BalancesCallIndexes,
+			+ BalancesCallIndexes
+			+ UtilityCallIndexes
+			+ AssetsCallIndexes
+			+ TokensCallIndexes
+			+ ParachainSystemCallIndexes
+			+ SystemCallIndexes,
 	> NodeMetadataTrait for T
 {
 }
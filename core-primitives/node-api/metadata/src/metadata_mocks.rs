@@ -16,9 +16,12 @@
 */
 
 use crate::{
-	error::Result, pallet_balances::BalancesCallIndexes,
-	pallet_enclave_bridge::EnclaveBridgeCallIndexes, pallet_proxy::ProxyCallIndexes,
-	pallet_sidechain::SidechainCallIndexes, pallet_teerex::TeerexCallIndexes,
+	error::Result, pallet_assets::AssetsCallIndexes, pallet_balances::BalancesCallIndexes,
+	pallet_enclave_bridge::EnclaveBridgeCallIndexes,
+	pallet_parachain_system::ParachainSystemCallIndexes, pallet_proxy::ProxyCallIndexes,
+	pallet_sidechain::SidechainCallIndexes, pallet_system::SystemCallIndexes,
+	pallet_teerex::TeerexCallIndexes, pallet_tokens::TokensCallIndexes,
+	pallet_utility::UtilityCallIndexes,
 };
 use codec::{Decode, Encode};
 
@@ -56,6 +59,18 @@ pub struct NodeMetadataMock {
 	transfer: u8,
 	transfer_keep_alive: u8,
 	transfer_allow_death: u8,
+	utility_module: u8,
+	batch: u8,
+	force_batch: u8,
+	batch_all: u8,
+	assets_module: u8,
+	assets_transfer_keep_alive: u8,
+	tokens_module: u8,
+	tokens_transfer_keep_alive: u8,
+	parachain_system_module: u8,
+	set_validation_data: u8,
+	system_module: u8,
+	remark_with_event: u8,
 	runtime_spec_version: u32,
 	runtime_transaction_version: u32,
 }
@@ -85,6 +100,18 @@ impl NodeMetadataMock {
 			transfer: 7u8,
 			transfer_keep_alive: 3u8,
 			transfer_allow_death: 0u8,
+			utility_module: 8u8,
+			batch: 0u8,
+			force_batch: 4u8,
+			batch_all: 2u8,
+			assets_module: 12u8,
+			assets_transfer_keep_alive: 9u8,
+			tokens_module: 13u8,
+			tokens_transfer_keep_alive: 2u8,
+			parachain_system_module: 1u8,
+			set_validation_data: 0u8,
+			system_module: 0u8,
+			remark_with_event: 7u8,
 			runtime_spec_version: 25,
 			runtime_transaction_version: 4,
 		}
@@ -168,3 +195,41 @@ impl BalancesCallIndexes for NodeMetadataMock {
 		Ok([self.balances_module, self.transfer_allow_death])
 	}
 }
+
+impl UtilityCallIndexes for NodeMetadataMock {
+	fn batch_call_indexes(&self) -> Result<[u8; 2]> {
+		Ok([self.utility_module, self.batch])
+	}
+
+	fn force_batch_call_indexes(&self) -> Result<[u8; 2]> {
+		Ok([self.utility_module, self.force_batch])
+	}
+
+	fn batch_all_call_indexes(&self) -> Result<[u8; 2]> {
+		Ok([self.utility_module, self.batch_all])
+	}
+}
+
+impl AssetsCallIndexes for NodeMetadataMock {
+	fn assets_transfer_keep_alive_call_indexes(&self) -> Result<[u8; 2]> {
+		Ok([self.assets_module, self.assets_transfer_keep_alive])
+	}
+}
+
+impl TokensCallIndexes for NodeMetadataMock {
+	fn tokens_transfer_keep_alive_call_indexes(&self) -> Result<[u8; 2]> {
+		Ok([self.tokens_module, self.tokens_transfer_keep_alive])
+	}
+}
+
+impl ParachainSystemCallIndexes for NodeMetadataMock {
+	fn set_validation_data_call_indexes(&self) -> Result<[u8; 2]> {
+		Ok([self.parachain_system_module, self.set_validation_data])
+	}
+}
+
+impl SystemCallIndexes for NodeMetadataMock {
+	fn remark_with_event_call_indexes(&self) -> Result<[u8; 2]> {
+		Ok([self.system_module, self.remark_with_event])
+	}
+}
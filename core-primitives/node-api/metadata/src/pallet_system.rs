@@ -16,18 +16,28 @@ use sp_core::storage::StorageKey;
 /// Pallet' name:
 const SYSTEM: &str = "System";
 
+pub trait SystemCallIndexes {
+	fn remark_with_event_call_indexes(&self) -> Result<[u8; 2]>;
+}
+
+impl SystemCallIndexes for NodeMetadata {
+	fn remark_with_event_call_indexes(&self) -> Result<[u8; 2]> {
+		self.call_indexes(SYSTEM, "remark_with_event")
+	}
+}
+
 pub trait SystemStorageIndexes {
-    fn system_account_storage_key(&self) -> Result<StorageKey>;
+	fn system_account_storage_key(&self) -> Result<StorageKey>;
 
-    fn system_account_storage_map_key(&self, index: u64) -> Result<StorageKey>;
+	fn system_account_storage_map_key(&self, index: u64) -> Result<StorageKey>;
 }
 
 impl SystemStorageIndexes for NodeMetadata {
-    fn system_account_storage_key(&self) -> Result<StorageKey> {
-        self.storage_value_key(SYSTEM, "Account")
-    }
+	fn system_account_storage_key(&self) -> Result<StorageKey> {
+		self.storage_value_key(SYSTEM, "Account")
+	}
 
-    fn system_account_storage_map_key(&self, index: u64) -> Result<StorageKey> {
-        self.storage_map_key(SYSTEM, "Account", index)
-    }
-}
\ No newline at end of file
+	fn system_account_storage_map_key(&self, index: u64) -> Result<StorageKey> {
+		self.storage_map_key(SYSTEM, "Account", index)
+	}
+}
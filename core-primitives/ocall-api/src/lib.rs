@@ -25,8 +25,9 @@ use core::result::Result as StdResult;
 use derive_more::{Display, From};
 use itp_storage::Error as StorageError;
 use itp_types::{
-	parentchain::ParentchainId, storage::StorageEntryVerified, BlockHash, ShardIdentifier,
-	TrustedOperationStatus, WorkerRequest, WorkerResponse,
+	parentchain::ParentchainId, state_confirmation::StateConfirmation,
+	storage::StorageEntryVerified, BlockHash, ShardIdentifier, TrustedOperationStatus,
+	WorkerRequest, WorkerResponse,
 };
 use sgx_types::*;
 use sp_core::H256;
@@ -41,6 +42,36 @@ pub enum Error {
 }
 
 pub type Result<T> = StdResult<T, Error>;
+
+/// Typed status of an o-call that was rejected by the untrusted o-call bridge,
+/// without being executed or before its result was available.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq)]
+pub enum OCallBridgeStatus {
+	/// Too many o-calls of the same type are pending, try again later.
+	QueueSaturated,
+	/// The o-call did not finish within its time limit.
+	TimedOut,
+}
+
+impl OCallBridgeStatus {
+	/// Returns the bridge status, if the sgx status of an o-call stems from the o-call bridge.
+	pub fn from_status(status: sgx_status_t) -> Option<Self> {
+		match status {
+			sgx_status_t::SGX_ERROR_BUSY => Some(OCallBridgeStatus::QueueSaturated),
+			sgx_status_t::SGX_ERROR_SERVICE_TIMEOUT => Some(OCallBridgeStatus::TimedOut),
+			_ => None,
+		}
+	}
+}
+
+impl From<OCallBridgeStatus> for sgx_status_t {
+	fn from(status: OCallBridgeStatus) -> Self {
+		match status {
+			OCallBridgeStatus::QueueSaturated => sgx_status_t::SGX_ERROR_BUSY,
+			OCallBridgeStatus::TimedOut => sgx_status_t::SGX_ERROR_SERVICE_TIMEOUT,
+		}
+	}
+}
 /// Trait for the enclave to make o-calls related to remote attestation
 pub trait EnclaveAttestationOCallApi: Clone + Send + Sync {
 	fn sgx_init_quote(&self) -> SgxResult<(sgx_target_info_t, sgx_epid_group_id_t)>;
@@ -89,6 +120,10 @@ pub trait EnclaveRpcOCallApi: Clone + Send + Sync + Default {
 
 /// trait for o-calls related to on-chain interactions
 pub trait EnclaveOnChainOCallApi: Clone + Send + Sync {
+	/// Sends to the same parentchain are submitted in the order they were made.
+	///
+	/// [`OCallBridgeStatus::TimedOut`] only means that the inclusion was not awaited to the
+	/// end: the extrinsics are submitted nonetheless and must not be sent again.
 	fn send_to_parentchain(
 		&self,
 		extrinsics: Vec<OpaqueExtrinsic>,
@@ -141,6 +176,18 @@ pub trait EnclaveSidechainOCallApi: Clone + Send + Sync {
 	) -> SgxResult<Vec<SignedSidechainBlock>>;
 }
 
+/// Trait for the pessimistic execution mode, collecting the peer validateers' confirmations of
+/// a proposed state update.
+pub trait EnclaveStateConfirmationOCallApi: Clone + Send + Sync {
+	/// Sends the (state key encrypted) confirmation request to the peers of `shard` and
+	/// returns the confirmations received. Peers that failed to confirm are left out.
+	fn request_state_confirmations(
+		&self,
+		shard: ShardIdentifier,
+		encrypted_request: Vec<u8>,
+	) -> SgxResult<Vec<StateConfirmation>>;
+}
+
 /// Newtype for IPFS CID
 pub struct IpfsCid(pub [u8; 46]);
 
@@ -150,3 +197,21 @@ pub trait EnclaveIpfsOCallApi: Clone + Send + Sync {
 
 	fn read_ipfs(&self, cid: &IpfsCid) -> SgxResult<()>;
 }
+
+/// Identifier of a TCP connection the untrusted worker opened on behalf of the enclave.
+pub type TcpConnectionId = u32;
+
+/// Trait for o-calls relaying the TCP bytes of HTTPS connections.
+///
+/// The TLS session is terminated inside the enclave, the untrusted worker only opens the TCP
+/// connection and forwards the encrypted TLS records.
+pub trait HttpsClientOCallApi: Clone + Send + Sync {
+	fn tcp_connect(&self, host: &str, port: u16) -> SgxResult<TcpConnectionId>;
+
+	fn tcp_send(&self, connection: TcpConnectionId, data: &[u8]) -> SgxResult<()>;
+
+	/// Receives at most `max_len` bytes. No bytes are returned once the peer closed the connection.
+	fn tcp_receive(&self, connection: TcpConnectionId, max_len: u32) -> SgxResult<Vec<u8>>;
+
+	fn tcp_close(&self, connection: TcpConnectionId) -> SgxResult<()>;
+}
@@ -0,0 +1,101 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Stable JSON-RPC error codes of the worker.
+//!
+//! Client SDKs match on these codes, so existing values must never be changed or reused.
+
+/// Base code for all authorship errors.
+pub const AUTHOR_BASE_ERROR: i64 = 1000;
+/// Extrinsic has an invalid format.
+pub const BAD_FORMAT: i64 = AUTHOR_BASE_ERROR + 1;
+/// Error during operation verification in runtime.
+pub const VERIFICATION_ERROR: i64 = AUTHOR_BASE_ERROR + 2;
+/// Sender is not permitted to submit trusted calls to the shard.
+pub const ACCESS_DENIED: i64 = AUTHOR_BASE_ERROR + 3;
+/// Enclave memory limits are reached, the operation can be retried later.
+pub const RESOURCE_EXHAUSTED: i64 = AUTHOR_BASE_ERROR + 4;
+/// Parentchain sync of the enclave stalled, trusted calls are accepted again once it recovers.
+pub const PARENTCHAIN_SYNC_STALLED: i64 = AUTHOR_BASE_ERROR + 5;
+/// Shard is in maintenance mode, trusted calls are accepted again once it is lifted.
+pub const SHARD_IN_MAINTENANCE: i64 = AUTHOR_BASE_ERROR + 6;
+/// Trusted operation exceeds the maximum size.
+pub const OPERATION_TOO_LARGE: i64 = AUTHOR_BASE_ERROR + 7;
+/// Signature of the trusted call is not valid for this enclave and shard.
+pub const INVALID_SIGNATURE: i64 = AUTHOR_BASE_ERROR + 8;
+/// Nonce of the trusted call is already used or too far ahead of the account nonce.
+pub const NONCE_OUT_OF_WINDOW: i64 = AUTHOR_BASE_ERROR + 9;
+
+/// Pool rejected the operation as invalid
+pub const POOL_INVALID_TX: i64 = AUTHOR_BASE_ERROR + 10;
+/// Cannot determine operation validity.
+pub const POOL_UNKNOWN_VALIDITY: i64 = POOL_INVALID_TX + 1;
+/// The operation is temporarily banned.
+pub const POOL_TEMPORARILY_BANNED: i64 = POOL_INVALID_TX + 2;
+/// The operation is already in the pool
+pub const POOL_ALREADY_IMPORTED: i64 = POOL_INVALID_TX + 3;
+/// TrustedOperation has too low priority to replace existing one in the pool.
+pub const POOL_TOO_LOW_PRIORITY: i64 = POOL_INVALID_TX + 4;
+/// Including this operation would cause a dependency cycle.
+pub const POOL_CYCLE_DETECTED: i64 = POOL_INVALID_TX + 5;
+/// The operation was not included to the pool because of the limits.
+pub const POOL_IMMEDIATELY_DROPPED: i64 = POOL_INVALID_TX + 6;
+/// The key type crypto is not known.
+pub const UNSUPPORTED_KEY_TYPE: i64 = POOL_INVALID_TX + 7;
+
+/// Base code for all state RPC errors.
+pub const STATE_BASE_ERROR: i64 = 4000;
+/// Provided block range couldn't be resolved to a list of blocks.
+pub const INVALID_BLOCK_RANGE: i64 = STATE_BASE_ERROR + 1;
+/// Provided count exceeds maximum value.
+pub const INVALID_COUNT: i64 = STATE_BASE_ERROR + 2;
+/// Any other state RPC error.
+pub const STATE_INTERNAL_ERROR: i64 = STATE_BASE_ERROR + 4;
+
+/// Base code for all STF executor errors, also used for errors without a dedicated code.
+pub const STF_EXECUTOR_BASE_ERROR: i64 = 2000;
+/// The signature of the getter does not authorize the access.
+pub const GETTER_NOT_AUTHORIZED: i64 = STF_EXECUTOR_BASE_ERROR + 1;
+/// The trusted call type is invalid or not supported.
+pub const INVALID_TRUSTED_CALL_TYPE: i64 = STF_EXECUTOR_BASE_ERROR + 2;
+/// The shard has no vault account assigned yet.
+pub const SHARD_VAULT_NOT_SET: i64 = STF_EXECUTOR_BASE_ERROR + 3;
+/// The nonce of the enclave account does not fit into the nonce type.
+pub const NONCE_OVERFLOW: i64 = STF_EXECUTOR_BASE_ERROR + 4;
+/// The state of the shard could not be observed, e.g. because the shard does not exist.
+pub const STATE_OBSERVATION_FAILED: i64 = STF_EXECUTOR_BASE_ERROR + 5;
+/// Enclave memory limits are reached, the getter can be retried later.
+pub const STF_RESOURCE_EXHAUSTED: i64 = STF_EXECUTOR_BASE_ERROR + 6;
+/// The state was written by a newer STF version than the enclave supports.
+pub const UNSUPPORTED_STF_VERSION: i64 = STF_EXECUTOR_BASE_ERROR + 7;
+/// The request could not be decoded.
+pub const STF_DECODING_ERROR: i64 = STF_EXECUTOR_BASE_ERROR + 8;
+/// The getter stream is unknown or expired, or the chunk index is out of range. The stream has
+/// to be restarted by requesting chunk 0.
+pub const GETTER_STREAM_NOT_FOUND: i64 = STF_EXECUTOR_BASE_ERROR + 9;
+/// The getter has expired, it may be a replay. The getter has to be signed again.
+pub const GETTER_EXPIRED: i64 = STF_EXECUTOR_BASE_ERROR + 10;
+/// The cross-shard message is unknown or has not been committed yet, retry after the next
+/// outbox commitment.
+pub const CROSS_SHARD_MESSAGE_NOT_COMMITTED: i64 = STF_EXECUTOR_BASE_ERROR + 11;
+/// The commitment of the cross-shard proof has not been anchored (yet) from the parentchain,
+/// or was published by an untrusted enclave.
+pub const CROSS_SHARD_COMMITMENT_NOT_ANCHORED: i64 = STF_EXECUTOR_BASE_ERROR + 12;
+/// The Merkle proof of the cross-shard message does not match the commitment.
+pub const INVALID_CROSS_SHARD_PROOF: i64 = STF_EXECUTOR_BASE_ERROR + 13;
+/// The key a getter response should be encrypted to is not a valid RSA3072 public key.
+pub const INVALID_RESPONSE_KEY: i64 = STF_EXECUTOR_BASE_ERROR + 14;
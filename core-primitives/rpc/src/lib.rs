@@ -23,10 +23,14 @@ compile_error!("feature \"std\" and feature \"sgx\" cannot be enabled at the sam
 #[cfg(all(not(feature = "std"), feature = "sgx"))]
 extern crate sgx_tstd as std;
 
+extern crate alloc;
+
+use alloc::{borrow::ToOwned, string::String, vec::Vec};
 use codec::{Decode, Encode};
 use itp_types::DirectRequestStatus;
 use serde::{Deserialize, Serialize};
-use std::{borrow::ToOwned, string::String, vec::Vec};
+
+pub mod error_codes;
 
 #[derive(Encode, Decode, Debug)]
 pub struct RpcReturnValue {
@@ -55,6 +59,22 @@ pub struct RpcResponse {
 	pub id: u32,
 }
 
+/// Error object of a JSON-RPC response, see [error_codes] for the codes used by the worker.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RpcErrorObject {
+	pub code: i64,
+	pub message: String,
+	#[serde(default)]
+	pub data: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RpcErrorResponse {
+	pub jsonrpc: String,
+	pub error: RpcErrorObject,
+	pub id: u32,
+}
+
 #[derive(Clone, Encode, Decode, Serialize, Deserialize)]
 pub struct RpcRequest {
 	pub jsonrpc: String,
@@ -82,6 +82,12 @@ pub mod worker {
 	pub const SIGNING_KEY_SIZE: usize = 32;
 	// size of the MR enclave
 	pub const MR_ENCLAVE_SIZE: usize = 32;
+	// maximum size of the report of a sidechain block replay
+	pub const BLOCK_REPLAY_REPORT_MAX_SIZE: usize = 1_000_000;
+	// maximum size of a SCALE encoded dump of a shard state
+	pub const STATE_DUMP_MAX_SIZE: usize = 10_000_000;
+	// maximum size of a SCALE encoded, signed worker directory record
+	pub const WORKER_RECORD_MAX_SIZE: usize = 4096;
 	// Factors to tune the initial amount of enclave funding:
 	// Should be set to a value that ensures that the enclave can register itself
 	// and the worker can run for a certain time. Only for development.
@@ -92,17 +98,62 @@ pub mod worker {
 	// Should be set to a value that ensures that at least 2 sidechain blocks are finalized per
 	// parentchain block.
 	pub const BLOCK_NUMBER_FINALIZATION_DIFF: u64 = 20;
+	// Heap usage in MiB above which the enclave rejects new trusted operations and getters.
+	// Leaves headroom below the `HeapMaxSize` of 512 MiB for block production and import.
+	pub const ENCLAVE_MEMORY_LIMIT_MB: u64 = 448;
 }
 
 pub mod sidechain {
 	use core::time::Duration;
 
 	pub static SLOT_DURATION: Duration = Duration::from_millis(1000);
+
+	/// Share of the shard's validateers (in percent, including the proposer) that must confirm
+	/// a state update before it is applied, in the pessimistic execution mode.
+	pub const STATE_CONFIRMATION_QUORUM_PERCENT: u64 = 67;
 }
 
 /// Settings concerning the enclave
 pub mod enclave {}
 
+/// Settings for the cross-checking of the host clock
+pub mod secure_time {
+	use core::time::Duration;
+
+	/// Maximal deviation of the host clock from a trusted time source, block production halts
+	/// beyond it.
+	pub static MAX_CLOCK_DRIFT: Duration = Duration::from_secs(30);
+	/// Delay with which finalized parentchain blocks are observed, the host clock may be ahead
+	/// of their timestamp by this in addition to the drift.
+	pub static MAX_PARENTCHAIN_TIMESTAMP_LAG: Duration = Duration::from_secs(120);
+	/// HTTPS endpoint whose `Date` header serves as additional reference time, none by default.
+	pub static REFERENCE_TIME_URL: Option<&str> = None;
+	/// PEM encoded root certificates the TLS certificate of the reference time endpoint is
+	/// pinned to.
+	pub static REFERENCE_TIME_ROOT_CERTIFICATES: &[&str] = &[];
+	/// Interval in which the reference time is fetched.
+	pub static REFERENCE_TIME_REFRESH_INTERVAL: Duration = Duration::from_secs(600);
+}
+
+/// Settings for the dead man's switch on the parentchain sync
+pub mod parentchain_sync {
+	use core::time::Duration;
+
+	/// Duration without a newly imported parentchain block after which block production and
+	/// the submission of trusted calls are frozen, until the sync recovers. Zero disables it.
+	pub static MAX_PARENTCHAIN_SYNC_STALL: Duration = Duration::from_secs(600);
+}
+
+/// Settings for the worker directory
+pub mod worker_directory {
+	use core::time::Duration;
+
+	/// Interval in which a worker publishes its signed directory record, if not configured.
+	pub static DEFAULT_PUBLISH_INTERVAL: Duration = Duration::from_secs(600);
+	/// Records older than this are considered stale by clients resolving a worker.
+	pub static MAX_RECORD_AGE: Duration = Duration::from_secs(1800);
+}
+
 /// Settings for the Teeracle
 pub mod teeracle {
 	use core::time::Duration;
@@ -112,4 +163,11 @@ pub mod teeracle {
 	pub static ONE_DAY: Duration = Duration::from_secs(86400);
 
 	pub static THIRTY_MINUTES: Duration = Duration::from_secs(1800);
+
+	/// Exchange rate published if no oracle feed is configured, as `<crypto>/<fiat>`.
+	pub static DEFAULT_ORACLE_FEED: &str = "TEER/USD";
+	/// Data sources the median of an oracle feed is computed over, if none are configured.
+	pub static DEFAULT_ORACLE_SOURCES: &[&str] = &["coin_gecko", "coin_market_cap"];
+	/// Minimal number of data sources that must respond to publish an oracle feed.
+	pub static DEFAULT_ORACLE_MIN_SOURCES: u32 = 1;
 }
@@ -17,17 +17,23 @@
 
 use crate::{
 	error::{Error, Result},
-	traits::StateCrypto,
+	traits::{StateAead, StateCrypto},
 };
 use aes::Aes128;
+use aes_gcm::{
+	aead::{generic_array::GenericArray, Aead, NewAead},
+	Aes256Gcm,
+};
 use codec::{Decode, Encode};
 use ofb::{
 	cipher::{NewStreamCipher, SyncStreamCipher},
 	Ofb,
 };
+use sp_core::blake2_256;
 use std::{
 	convert::{TryFrom, TryInto},
 	path::PathBuf,
+	vec::Vec,
 };
 
 type AesOfb = Ofb<Aes128>;
@@ -74,6 +80,39 @@ impl StateCrypto for Aes {
 	}
 }
 
+impl StateAead for Aes {
+	type Error = Error;
+
+	fn seal(&self, plaintext: &[u8], nonce: [u8; 12]) -> Result<Vec<u8>> {
+		let mut message = nonce.to_vec();
+		message.extend(
+			self.aead_cipher()
+				.encrypt(GenericArray::from_slice(&nonce), plaintext)
+				.map_err(|_| Error::Aead)?,
+		);
+		Ok(message)
+	}
+
+	fn open(&self, message: &[u8]) -> Result<Vec<u8>> {
+		if message.len() < 12 {
+			return Err(Error::Aead)
+		}
+		let (nonce, ciphertext) = message.split_at(12);
+		self.aead_cipher()
+			.decrypt(GenericArray::from_slice(nonce), ciphertext)
+			.map_err(|_| Error::Aead)
+	}
+}
+
+impl Aes {
+	/// AES-256-GCM with a key derived from the state key, such that the key is not used in
+	/// two modes.
+	fn aead_cipher(&self) -> Aes256Gcm {
+		let key = blake2_256(&(b"state-aead", self.key, self.init_vec).encode());
+		Aes256Gcm::new(GenericArray::from_slice(&key))
+	}
+}
+
 impl TryFrom<&Aes> for AesOfb {
 	type Error = Error;
 
@@ -161,6 +200,40 @@ pub mod sgx {
 	}
 }
 
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn aes() -> Aes {
+		Aes::new([3u8; 16], [0u8; 16])
+	}
+
+	#[test]
+	fn sealed_message_can_be_opened() {
+		let message = aes().seal(b"hello", [1u8; 12]).unwrap();
+
+		assert_eq!(aes().open(&message).unwrap(), b"hello".to_vec());
+	}
+
+	#[test]
+	fn same_plaintext_with_other_nonce_gives_other_ciphertext() {
+		assert_ne!(
+			aes().seal(b"hello", [1u8; 12]).unwrap(),
+			aes().seal(b"hello", [2u8; 12]).unwrap()
+		);
+	}
+
+	#[test]
+	fn tampered_message_is_rejected() {
+		let mut message = aes().seal(b"hello", [1u8; 12]).unwrap();
+		let last = message.len() - 1;
+		message[last] ^= 1;
+
+		assert!(aes().open(&message).is_err());
+		assert!(aes().open(&message[..5]).is_err());
+	}
+}
+
 #[cfg(feature = "test")]
 pub mod sgx_tests {
 	use super::sgx::*;
@@ -0,0 +1,191 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Encryption to the public key of an account, for data that only the account may read.
+//!
+//! ECIES on the curve of the account key: every message is encrypted with a fresh ephemeral
+//! key pair, the shared secret of the ephemeral secret and the account's public key is hashed
+//! into an AES-256-GCM key. As a key is never used twice, the nonce is fixed.
+
+use crate::error::{Error, Result};
+use aes_gcm::{
+	aead::{generic_array::GenericArray, Aead, NewAead},
+	Aes256Gcm,
+};
+use codec::{Decode, Encode};
+use curve25519_dalek::{
+	constants::{ED25519_BASEPOINT_POINT, RISTRETTO_BASEPOINT_POINT},
+	edwards::CompressedEdwardsY,
+	ristretto::CompressedRistretto,
+	scalar::Scalar,
+};
+use sha2::{Digest, Sha512};
+use sp_core::blake2_256;
+use std::vec::Vec;
+
+const NONCE: [u8; 12] = [0u8; 12];
+
+/// Signature scheme of an account, which determines the curve its public key is on.
+#[derive(Encode, Decode, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AccountKeyScheme {
+	Sr25519,
+	Ed25519,
+}
+
+impl Default for AccountKeyScheme {
+	fn default() -> Self {
+		AccountKeyScheme::Sr25519
+	}
+}
+
+/// Message encrypted to the public key of an account.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct EciesCiphertext {
+	pub ephemeral_public: [u8; 32],
+	pub ciphertext: Vec<u8>,
+}
+
+/// Encrypts `plaintext` to `public`, `randomness` has to be fresh for every message.
+pub fn encrypt(
+	scheme: AccountKeyScheme,
+	public: &[u8; 32],
+	plaintext: &[u8],
+	randomness: [u8; 32],
+) -> Result<EciesCiphertext> {
+	let ephemeral_secret = Scalar::from_bytes_mod_order(randomness);
+	let (ephemeral_public, shared_secret) = match scheme {
+		AccountKeyScheme::Sr25519 => {
+			let point = CompressedRistretto(*public).decompress().ok_or(Error::InvalidPublicKey)?;
+			(
+				(ephemeral_secret * RISTRETTO_BASEPOINT_POINT).compress().to_bytes(),
+				(ephemeral_secret * point).compress().to_bytes(),
+			)
+		},
+		AccountKeyScheme::Ed25519 => {
+			let point = CompressedEdwardsY(*public).decompress().ok_or(Error::InvalidPublicKey)?;
+			(
+				(ephemeral_secret * ED25519_BASEPOINT_POINT).compress().to_bytes(),
+				(ephemeral_secret * point).compress().to_bytes(),
+			)
+		},
+	};
+	let ciphertext = cipher(&shared_secret, &ephemeral_public, public)
+		.encrypt(GenericArray::from_slice(&NONCE), plaintext)
+		.map_err(|_| Error::Aead)?;
+	Ok(EciesCiphertext { ephemeral_public, ciphertext })
+}
+
+/// Scalar of the account's secret key, as needed by [decrypt].
+///
+/// `raw_secret` is the raw secret of the key pair as returned by `Pair::to_raw_vec`, the
+/// secret key of an sr25519 pair or the seed of an ed25519 pair.
+pub fn secret_scalar(scheme: AccountKeyScheme, raw_secret: &[u8]) -> Result<[u8; 32]> {
+	let mut scalar = [0u8; 32];
+	match scheme {
+		AccountKeyScheme::Sr25519 => {
+			let key = raw_secret.get(..32).ok_or(Error::InvalidSecretKey)?;
+			scalar.copy_from_slice(key);
+		},
+		AccountKeyScheme::Ed25519 => {
+			if raw_secret.len() != 32 {
+				return Err(Error::InvalidSecretKey)
+			}
+			scalar.copy_from_slice(&Sha512::digest(raw_secret)[..32]);
+			scalar[0] &= 248;
+			scalar[31] &= 127;
+			scalar[31] |= 64;
+		},
+	}
+	Ok(scalar)
+}
+
+/// Decrypts a message that has been encrypted to `public`, with the [secret_scalar] of the
+/// account.
+pub fn decrypt(
+	scheme: AccountKeyScheme,
+	secret: [u8; 32],
+	public: &[u8; 32],
+	message: &EciesCiphertext,
+) -> Result<Vec<u8>> {
+	let secret = Scalar::from_bits(secret);
+	let shared_secret = match scheme {
+		AccountKeyScheme::Sr25519 => CompressedRistretto(message.ephemeral_public)
+			.decompress()
+			.map(|point| (secret * point).compress().to_bytes()),
+		AccountKeyScheme::Ed25519 => CompressedEdwardsY(message.ephemeral_public)
+			.decompress()
+			.map(|point| (secret * point).compress().to_bytes()),
+	}
+	.ok_or(Error::InvalidPublicKey)?;
+	cipher(&shared_secret, &message.ephemeral_public, public)
+		.decrypt(GenericArray::from_slice(&NONCE), message.ciphertext.as_slice())
+		.map_err(|_| Error::Aead)
+}
+
+fn cipher(shared_secret: &[u8; 32], ephemeral_public: &[u8; 32], public: &[u8; 32]) -> Aes256Gcm {
+	let key = blake2_256(&(b"ecies", shared_secret, ephemeral_public, public).encode());
+	Aes256Gcm::new(GenericArray::from_slice(&key))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::{ed25519, sr25519, Pair};
+
+	#[test]
+	fn sr25519_roundtrip_works() {
+		let (pair, _) = sr25519::Pair::generate();
+		let scalar = secret_scalar(AccountKeyScheme::Sr25519, &pair.to_raw_vec()).unwrap();
+
+		let message =
+			encrypt(AccountKeyScheme::Sr25519, &pair.public().0, b"hello", [3u8; 32]).unwrap();
+
+		assert_ne!(message.ciphertext[..5], b"hello"[..]);
+		assert_eq!(
+			decrypt(AccountKeyScheme::Sr25519, scalar, &pair.public().0, &message).unwrap(),
+			b"hello".to_vec()
+		);
+	}
+
+	#[test]
+	fn ed25519_roundtrip_works() {
+		let (pair, _) = ed25519::Pair::generate();
+		let scalar = secret_scalar(AccountKeyScheme::Ed25519, &pair.to_raw_vec()).unwrap();
+
+		let message =
+			encrypt(AccountKeyScheme::Ed25519, &pair.public().0, b"hello", [3u8; 32]).unwrap();
+
+		assert_eq!(
+			decrypt(AccountKeyScheme::Ed25519, scalar, &pair.public().0, &message).unwrap(),
+			b"hello".to_vec()
+		);
+	}
+
+	#[test]
+	fn decrypting_with_another_key_fails() {
+		let (pair, _) = ed25519::Pair::generate();
+		let (other, _) = ed25519::Pair::generate();
+		let other_scalar = secret_scalar(AccountKeyScheme::Ed25519, &other.to_raw_vec()).unwrap();
+
+		let message =
+			encrypt(AccountKeyScheme::Ed25519, &pair.public().0, b"hello", [3u8; 32]).unwrap();
+
+		assert!(
+			decrypt(AccountKeyScheme::Ed25519, other_scalar, &other.public().0, &message).is_err()
+		);
+	}
+}
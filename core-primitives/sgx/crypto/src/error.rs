@@ -26,6 +26,10 @@ use std::prelude::v1::Box;
 pub enum Error {
 	IO(std::io::Error),
 	InvalidNonceKeyLength,
+	InvalidPublicKey,
+	InvalidSecretKey,
+	/// Authenticated encryption or decryption failed.
+	Aead,
 	Codec(codec::Error),
 	Serialization(serde_json::Error),
 	LockPoisoning,
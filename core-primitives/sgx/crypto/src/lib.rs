@@ -34,6 +34,7 @@ pub mod sgx_reexport_prelude {
 }
 
 pub mod aes;
+pub mod ecies;
 pub mod ed25519;
 pub mod ed25519_derivation;
 pub mod error;
@@ -68,6 +68,12 @@ impl ShieldingCryptoEncrypt for Rsa3072PubKey {
 	}
 }
 
+/// Parses a public key from its JSON representation, the format in which the shielding key is
+/// served by `author_getShieldingKey`.
+pub fn rsa3072_pubkey_from_json(json: &[u8]) -> Result<Rsa3072PubKey> {
+	serde_json::from_slice(json).map_err(Error::Serialization)
+}
+
 impl ToPubkey for Rsa3072KeyPair {
 	type Error = Error;
 	type Pubkey = Rsa3072PubKey;
@@ -24,6 +24,16 @@ pub trait StateCrypto {
 	fn decrypt(&self, data: &mut [u8]) -> Result<(), Self::Error>;
 }
 
+/// Authenticated encryption with the state key, for messages that leave the enclave.
+///
+/// Unlike [StateCrypto], every message is encrypted with its own nonce, which is prepended to
+/// the ciphertext. The nonce has to be fresh for every message.
+pub trait StateAead {
+	type Error: Debug;
+	fn seal(&self, plaintext: &[u8], nonce: [u8; 12]) -> Result<Vec<u8>, Self::Error>;
+	fn open(&self, message: &[u8]) -> Result<Vec<u8>, Self::Error>;
+}
+
 pub trait ShieldingCryptoEncrypt {
 	type Error: Debug;
 	fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>, Self::Error>;
@@ -0,0 +1,379 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Delivery of cross-shard messages, see [itp_stf_primitives::cross_shard].
+//!
+//! For every message that the execution of a block emits on the source shard, the
+//! [CrossShardRouter] signs the delivery call of the STF with the enclave account and submits
+//! it to the top pool of the destination shard. The destination shard rejects a message that
+//! is delivered twice, so it's safe to route a message again. A message that can't be delivered
+//! stays in the outbox of the source shard.
+//!
+//! Messages from shards of other workers are submitted by relayers with a proof against the
+//! commitment of the source shard. The router only delivers them if the commitment has been
+//! anchored in the destination shard and was published by an enclave with our MRENCLAVE, i.e.
+//! an enclave that runs the same STF.
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use crate::sgx_reexport_prelude::*;
+
+use crate::{
+	error::{Error, Result},
+	traits::{RelayCrossShardMessages, RouteCrossShardMessages, StfEnclaveSigning},
+};
+use codec::{Decode, Encode};
+use core::{fmt::Debug, marker::PhantomData};
+use itp_sgx_crypto::{key_repository::AccessKey, ShieldingCryptoEncrypt};
+use itp_stf_interface::CrossShardMessagingInterface;
+use itp_stf_primitives::{
+	cross_shard::{CrossShardMessage, CrossShardProof, MessageNonce},
+	traits::{TrustedCallSigning, TrustedCallVerification},
+	types::{ShardIdentifier, TrustedOperation},
+};
+use itp_stf_state_handler::{handle_state::HandleState, query_shard_state::QueryShardState};
+use itp_top_pool_author::traits::AuthorApi;
+use itp_types::H256;
+use log::*;
+use std::{format, sync::Arc};
+
+pub struct CrossShardRouter<
+	Stf,
+	EnclaveSigner,
+	TopPoolAuthor,
+	ShieldingKeyRepository,
+	StateHandler,
+	TCS,
+	G,
+> {
+	enclave_signer: Arc<EnclaveSigner>,
+	top_pool_author: Arc<TopPoolAuthor>,
+	shielding_key_repo: Arc<ShieldingKeyRepository>,
+	state_handler: Arc<StateHandler>,
+	_phantom: PhantomData<(Stf, TCS, G)>,
+}
+
+impl<Stf, EnclaveSigner, TopPoolAuthor, ShieldingKeyRepository, StateHandler, TCS, G>
+	CrossShardRouter<Stf, EnclaveSigner, TopPoolAuthor, ShieldingKeyRepository, StateHandler, TCS, G>
+where
+	Stf: CrossShardMessagingInterface<StateHandler::StateT>,
+	Stf::Call: Encode + Debug + TrustedCallSigning<TCS>,
+	EnclaveSigner: StfEnclaveSigning<TCS>,
+	TopPoolAuthor: AuthorApi<H256, H256, TCS, G> + Send + Sync + 'static,
+	ShieldingKeyRepository: AccessKey,
+	<ShieldingKeyRepository as AccessKey>::KeyType: ShieldingCryptoEncrypt,
+	StateHandler: QueryShardState + HandleState,
+	TCS: PartialEq + Encode + Decode + Debug + Clone + Send + Sync + TrustedCallVerification,
+	G: PartialEq + Encode + Decode + Debug + Clone + Send + Sync,
+{
+	pub fn new(
+		enclave_signer: Arc<EnclaveSigner>,
+		top_pool_author: Arc<TopPoolAuthor>,
+		shielding_key_repo: Arc<ShieldingKeyRepository>,
+		state_handler: Arc<StateHandler>,
+	) -> Self {
+		Self {
+			enclave_signer,
+			top_pool_author,
+			shielding_key_repo,
+			state_handler,
+			_phantom: PhantomData,
+		}
+	}
+
+	fn deliver(&self, source: &ShardIdentifier, message: &CrossShardMessage) -> Result<()> {
+		let destination = message.destination;
+		if !self.state_handler.shard_exists(&destination)? {
+			return Err(Error::UnknownDestinationShard(destination))
+		}
+
+		let delivery_call = Stf::delivery_call(
+			self.enclave_signer.get_enclave_account()?,
+			*source,
+			message.clone(),
+		);
+		let signed_call = self.enclave_signer.sign_call_with_self(&delivery_call, &destination)?;
+		let trusted_operation = TrustedOperation::<TCS, G>::indirect_call(signed_call);
+
+		let encrypted_operation = self
+			.shielding_key_repo
+			.retrieve_key()?
+			.encrypt(&trusted_operation.encode())
+			.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+		futures::executor::block_on(
+			self.top_pool_author.submit_top(encrypted_operation, destination),
+		)
+		.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+		Ok(())
+	}
+}
+
+impl<Stf, EnclaveSigner, TopPoolAuthor, ShieldingKeyRepository, StateHandler, TCS, G>
+	RouteCrossShardMessages
+	for CrossShardRouter<
+		Stf,
+		EnclaveSigner,
+		TopPoolAuthor,
+		ShieldingKeyRepository,
+		StateHandler,
+		TCS,
+		G,
+	>
+where
+	Stf: CrossShardMessagingInterface<StateHandler::StateT> + Send + Sync,
+	Stf::Call: Encode + Debug + TrustedCallSigning<TCS>,
+	EnclaveSigner: StfEnclaveSigning<TCS> + Send + Sync,
+	TopPoolAuthor: AuthorApi<H256, H256, TCS, G> + Send + Sync + 'static,
+	ShieldingKeyRepository: AccessKey + Send + Sync,
+	<ShieldingKeyRepository as AccessKey>::KeyType: ShieldingCryptoEncrypt,
+	StateHandler: QueryShardState + HandleState + Send + Sync,
+	TCS: PartialEq + Encode + Decode + Debug + Clone + Send + Sync + TrustedCallVerification,
+	G: PartialEq + Encode + Decode + Debug + Clone + Send + Sync,
+{
+	fn route(&self, source: &ShardIdentifier, messages: &[CrossShardMessage]) -> Result<()> {
+		for message in messages {
+			debug!(
+				"Routing cross-shard message {} from shard {:?} to shard {:?}",
+				message.nonce, source, message.destination
+			);
+			self.deliver(source, message)?;
+		}
+		Ok(())
+	}
+}
+
+impl<Stf, EnclaveSigner, TopPoolAuthor, ShieldingKeyRepository, StateHandler, TCS, G>
+	RelayCrossShardMessages
+	for CrossShardRouter<
+		Stf,
+		EnclaveSigner,
+		TopPoolAuthor,
+		ShieldingKeyRepository,
+		StateHandler,
+		TCS,
+		G,
+	>
+where
+	Stf: CrossShardMessagingInterface<StateHandler::StateT> + Send + Sync,
+	Stf::Call: Encode + Debug + TrustedCallSigning<TCS>,
+	EnclaveSigner: StfEnclaveSigning<TCS> + Send + Sync,
+	TopPoolAuthor: AuthorApi<H256, H256, TCS, G> + Send + Sync + 'static,
+	ShieldingKeyRepository: AccessKey + Send + Sync,
+	<ShieldingKeyRepository as AccessKey>::KeyType: ShieldingCryptoEncrypt,
+	StateHandler: QueryShardState + HandleState + Send + Sync,
+	TCS: PartialEq + Encode + Decode + Debug + Clone + Send + Sync + TrustedCallVerification,
+	G: PartialEq + Encode + Decode + Debug + Clone + Send + Sync,
+{
+	fn message_proof(
+		&self,
+		source: &ShardIdentifier,
+		destination: &ShardIdentifier,
+		nonce: MessageNonce,
+	) -> Result<CrossShardProof> {
+		let (mut state, _) = self.state_handler.load_cloned(source)?;
+		Stf::message_proof(&mut state, destination, nonce)
+			.ok_or(Error::CrossShardMessageNotCommitted(*destination, nonce))
+	}
+
+	fn submit_message_proof(&self, proof: &CrossShardProof) -> Result<()> {
+		let destination = proof.message.destination;
+		if !self.state_handler.shard_exists(&destination)? {
+			return Err(Error::UnknownDestinationShard(destination))
+		}
+		let source = proof.source();
+		let not_anchored =
+			|| Error::CrossShardCommitmentNotAnchored(source, proof.commitment.index);
+
+		let (mut state, _) = self.state_handler.load_cloned(&destination)?;
+		let anchored = Stf::anchored_commitment(&mut state, &source, proof.commitment.index)
+			.ok_or_else(not_anchored)?;
+		if anchored.commitment != proof.commitment
+			|| anchored.enclave_fingerprint != H256::from(self.enclave_signer.get_mrenclave()?)
+		{
+			return Err(not_anchored())
+		}
+		if !proof.verify() {
+			return Err(Error::InvalidCrossShardProof)
+		}
+
+		debug!(
+			"Relaying cross-shard message {} from shard {:?} to shard {:?}",
+			proof.message.nonce, source, destination
+		);
+		self.deliver(&source, &proof.message)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mocks::StfEnclaveSignerMock;
+	use itp_sgx_crypto::mocks::KeyRepositoryMock;
+	use itp_sgx_externalities::SgxExternalitiesTrait;
+	use itp_stf_primitives::{
+		cross_shard::{message_leaf, AnchoredCommitment, CrossShardPayload, OutboxCommitment},
+		merkle::{merkle_proof, merkle_root},
+		types::AccountId,
+	};
+	use itp_test::mock::{
+		handle_state_mock::HandleStateMock,
+		shielding_crypto_mock::ShieldingCryptoMock,
+		stf_mock::{anchored_commitment_mock_key, GetterMock, StfMock, TrustedCallSignedMock},
+	};
+	use itp_top_pool_author::mocks::AuthorApiMock;
+
+	type TestTopPoolAuthor = AuthorApiMock<H256, H256, TrustedCallSignedMock, GetterMock>;
+	type TestRouter = CrossShardRouter<
+		StfMock,
+		StfEnclaveSignerMock,
+		TestTopPoolAuthor,
+		KeyRepositoryMock<ShieldingCryptoMock>,
+		HandleStateMock,
+		TrustedCallSignedMock,
+		GetterMock,
+	>;
+
+	fn source() -> ShardIdentifier {
+		ShardIdentifier::repeat_byte(1)
+	}
+
+	fn destination() -> ShardIdentifier {
+		ShardIdentifier::repeat_byte(2)
+	}
+
+	fn message(destination: ShardIdentifier, nonce: u64) -> CrossShardMessage {
+		CrossShardMessage {
+			destination,
+			nonce,
+			sender: AccountId::new([1u8; 32]),
+			payload: CrossShardPayload::Transfer { to: AccountId::new([2u8; 32]), amount: 1 },
+		}
+	}
+
+	fn test_router() -> (TestRouter, Arc<TestTopPoolAuthor>) {
+		test_router_with_state(HandleStateMock::from_shard(destination()).unwrap())
+	}
+
+	fn test_router_with_state(
+		state_handler: HandleStateMock,
+	) -> (TestRouter, Arc<TestTopPoolAuthor>) {
+		let top_pool_author = Arc::new(TestTopPoolAuthor::default());
+		let router = TestRouter::new(
+			Arc::new(StfEnclaveSignerMock::default()),
+			top_pool_author.clone(),
+			Arc::new(KeyRepositoryMock::new(ShieldingCryptoMock::default())),
+			Arc::new(state_handler),
+		);
+		(router, top_pool_author)
+	}
+
+	/// Proof of the second of three messages, the commitment is anchored in the destination
+	/// shard as published by an enclave with the given fingerprint.
+	fn anchored_proof(enclave_fingerprint: H256) -> (CrossShardProof, HandleStateMock) {
+		let messages: Vec<_> = (0..3).map(|nonce| message(destination(), nonce)).collect();
+		let leaves: Vec<H256> = messages.iter().map(message_leaf).collect();
+		let commitment = OutboxCommitment {
+			shard: source(),
+			index: 4,
+			root: merkle_root(&leaves),
+			message_count: 3,
+		};
+
+		let state_handler = HandleStateMock::from_shard(destination()).unwrap();
+		let (mut state, _) = state_handler.load_cloned(&destination()).unwrap();
+		state.insert(
+			anchored_commitment_mock_key(&source(), commitment.index),
+			AnchoredCommitment { commitment, enclave_fingerprint }.encode(),
+		);
+		state_handler.reset(state, &destination()).unwrap();
+
+		let proof = CrossShardProof {
+			commitment,
+			message: messages[1].clone(),
+			leaf_index: 1,
+			proof: merkle_proof(&leaves, 1).unwrap(),
+		};
+		(proof, state_handler)
+	}
+
+	#[test]
+	fn messages_are_submitted_to_the_destination_shard() {
+		let (router, top_pool_author) = test_router();
+
+		router
+			.route(&source(), &[message(destination(), 0), message(destination(), 1)])
+			.unwrap();
+
+		assert_eq!(2, top_pool_author.pending_tops(destination()).unwrap().len());
+		assert!(top_pool_author.pending_tops(source()).unwrap().is_empty());
+	}
+
+	#[test]
+	fn routing_to_unknown_shard_fails() {
+		let (router, top_pool_author) = test_router();
+		let unknown = ShardIdentifier::repeat_byte(3);
+
+		let result = router.route(&source(), &[message(unknown, 0), message(destination(), 0)]);
+
+		assert!(matches!(result, Err(Error::UnknownDestinationShard(s)) if s == unknown));
+		assert!(top_pool_author.pending_tops(destination()).unwrap().is_empty());
+	}
+
+	#[test]
+	fn proven_message_is_submitted_to_the_destination_shard() {
+		// The enclave signer mock has a zero MRENCLAVE.
+		let (proof, state_handler) = anchored_proof(H256::zero());
+		let (router, top_pool_author) = test_router_with_state(state_handler);
+
+		router.submit_message_proof(&proof).unwrap();
+
+		assert_eq!(1, top_pool_author.pending_tops(destination()).unwrap().len());
+	}
+
+	#[test]
+	fn commitment_published_by_other_enclave_is_rejected() {
+		let (proof, state_handler) = anchored_proof(H256::repeat_byte(9));
+		let (router, top_pool_author) = test_router_with_state(state_handler);
+
+		let result = router.submit_message_proof(&proof);
+
+		assert!(
+			matches!(result, Err(Error::CrossShardCommitmentNotAnchored(s, 4)) if s == source())
+		);
+		assert!(top_pool_author.pending_tops(destination()).unwrap().is_empty());
+	}
+
+	#[test]
+	fn proof_of_other_message_is_rejected() {
+		let (mut proof, state_handler) = anchored_proof(H256::zero());
+		let (router, top_pool_author) = test_router_with_state(state_handler);
+		proof.message = message(destination(), 7);
+
+		assert!(matches!(router.submit_message_proof(&proof), Err(Error::InvalidCrossShardProof)));
+		assert!(top_pool_author.pending_tops(destination()).unwrap().is_empty());
+	}
+
+	#[test]
+	fn proof_of_unanchored_commitment_is_rejected() {
+		let (mut proof, state_handler) = anchored_proof(H256::zero());
+		let (router, _) = test_router_with_state(state_handler);
+		proof.commitment.index = 5;
+
+		assert!(matches!(
+			router.submit_message_proof(&proof),
+			Err(Error::CrossShardCommitmentNotAnchored(_, 5))
+		));
+	}
+}
@@ -86,7 +86,8 @@ where
 		let enclave_account = self.get_enclave_account()?;
 		let nonce = self
 			.state_observer
-			.observe_state(shard, move |state| Stf::get_account_nonce(state, &enclave_account))?;
+			.observe_state(shard, move |state| Stf::get_account_nonce(state, &enclave_account))
+			.map_err(|source| Error::StateObservationFailed { shard: *shard, source })?;
 
 		Ok(nonce)
 	}
@@ -118,6 +119,10 @@ where
 		Ok(enclave_call_signing_key.public().into())
 	}
 
+	fn get_mrenclave(&self) -> Result<[u8; 32]> {
+		Ok(self.ocall_api.get_mrenclave_of_self()?.m)
+	}
+
 	fn sign_call_with_self<TC: Encode + Debug + TrustedCallSigning<TCS>>(
 		&self,
 		trusted_call: &TC,
@@ -127,14 +132,15 @@ where
 		let enclave_account = self.get_enclave_account()?;
 		let enclave_call_signing_key = self.get_enclave_call_signing_key()?;
 
-		let current_nonce = self.get_enclave_account_nonce(shard)?;
+		let current_nonce: Index = self.get_enclave_account_nonce(shard)?.into();
 		let pending_tx_count = self
 			.top_pool_author
 			.get_pending_trusted_calls_for(*shard, &enclave_account)
 			.len();
 		let pending_tx_count =
-			Index::try_from(pending_tx_count).map_err(|e| Error::Other(e.into()))?;
-		let adjusted_nonce: Index = current_nonce.into() + pending_tx_count;
+			Index::try_from(pending_tx_count).map_err(|_| Error::NonceOverflow)?;
+		let adjusted_nonce =
+			current_nonce.checked_add(pending_tx_count).ok_or(Error::NonceOverflow)?;
 
 		Ok(trusted_call.sign(
 			&KeyPair::Ed25519(Box::new(enclave_call_signing_key)),
@@ -161,8 +167,11 @@ where
 	G: PartialEq + Encode + Decode + Debug + Send + Sync,
 {
 	fn get_shard_vault(&self, shard: &ShardIdentifier) -> Result<AccountId> {
-		let vault = self.state_observer.observe_state(shard, move |state| Stf::get_vault(state))?;
+		let vault = self
+			.state_observer
+			.observe_state(shard, move |state| Stf::get_vault(state))
+			.map_err(|source| Error::StateObservationFailed { shard: *shard, source })?;
 
-		vault.ok_or_else(|| Error::Other("shard vault undefined".into()))
+		vault.ok_or(Error::ShardVaultNotSet)
 	}
 }
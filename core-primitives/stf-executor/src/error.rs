@@ -18,7 +18,19 @@
 #[cfg(all(not(feature = "std"), feature = "sgx"))]
 use crate::sgx_reexport_prelude::*;
 
-use itp_stf_primitives::error::StfError;
+use itp_rpc::error_codes::{
+	CROSS_SHARD_COMMITMENT_NOT_ANCHORED, CROSS_SHARD_MESSAGE_NOT_COMMITTED, GETTER_EXPIRED,
+	GETTER_NOT_AUTHORIZED, GETTER_STREAM_NOT_FOUND, INVALID_CROSS_SHARD_PROOF,
+	INVALID_RESPONSE_KEY, INVALID_TRUSTED_CALL_TYPE, NONCE_OVERFLOW, SHARD_VAULT_NOT_SET,
+	STATE_OBSERVATION_FAILED, STF_DECODING_ERROR, STF_EXECUTOR_BASE_ERROR, STF_RESOURCE_EXHAUSTED,
+	UNSUPPORTED_STF_VERSION,
+};
+use itp_stf_interface::StfVersion;
+use itp_stf_primitives::{
+	cross_shard::{CommitmentIndex, MessageNonce},
+	error::StfError,
+};
+use itp_types::ShardIdentifier;
 use sgx_types::sgx_status_t;
 use std::{boxed::Box, format};
 
@@ -29,8 +41,22 @@ pub type Result<T> = core::result::Result<T, Error>;
 pub enum Error {
 	#[error("Trusted operation has invalid signature")]
 	GetterIsNotAuthorized,
+	#[error("Trusted getter has expired, it has to be signed again with a later expiry")]
+	GetterHasExpired,
 	#[error("Invalid or unsupported trusted call type")]
 	InvalidTrustedCallType,
+	#[error("Shard vault undefined")]
+	ShardVaultNotSet,
+	#[error("Enclave account nonce overflow")]
+	NonceOverflow,
+	#[error("Failed to observe the state of shard {shard:?}: {source}")]
+	StateObservationFailed { shard: ShardIdentifier, source: itp_stf_state_observer::error::Error },
+	#[error("Decoding error: {0}")]
+	Decode(codec::Error),
+	#[error("Getter stream not found or expired, or chunk {0} is out of range")]
+	GetterStreamNotFound(u32),
+	#[error("Getter stream lock is poisoned")]
+	GetterStreamLockPoisoning,
 	#[error("SGX error, status: {0}")]
 	Sgx(sgx_status_t),
 	#[error("State handling error: {0}")]
@@ -47,6 +73,32 @@ pub enum Error {
 	OcallApi(itp_ocall_api::Error),
 	#[error("Crypto error: {0}")]
 	Crypto(itp_sgx_crypto::error::Error),
+	#[error("Invalid key to encrypt the getter response to: {0}")]
+	InvalidResponseKey(itp_sgx_crypto::error::Error),
+	#[error("Periodic tasks lock is poisoned")]
+	PeriodicTasksLockPoisoning,
+	#[error("Destination shard {0:?} of a cross-shard message is not hosted by this worker")]
+	UnknownDestinationShard(ShardIdentifier),
+	#[error("Cross-shard message {1} to shard {0:?} has not been committed")]
+	CrossShardMessageNotCommitted(ShardIdentifier, MessageNonce),
+	#[error("Commitment {1} of shard {0:?} has not been anchored by a trusted enclave")]
+	CrossShardCommitmentNotAnchored(ShardIdentifier, CommitmentIndex),
+	#[error("Merkle proof of the cross-shard message does not match the commitment")]
+	InvalidCrossShardProof,
+	#[error("No replay record found for sidechain block {0}")]
+	ReplayRecordNotFound(u64),
+	#[error("Reconstructed state does not match the recorded state of sidechain block {0}")]
+	ReplayStateMismatch(u64),
+	#[error("Sidechain block {0} can't be replayed, {1} of its operations are unknown")]
+	ReplayOperationsMissing(u64, usize),
+	#[error("Replay recorder lock is poisoned")]
+	ReplayRecorderLockPoisoning,
+	#[error("State was written by STF version {state_version}, this enclave only supports up to version {enclave_version}")]
+	UnsupportedStfVersion { state_version: StfVersion, enclave_version: StfVersion },
+	#[error("No state migration registered from STF version {0}")]
+	MissingStateMigration(StfVersion),
+	#[error("Enclave resources exhausted: {0}")]
+	ResourceExhausted(#[from] itp_memory_accounting::Error),
 	#[error(transparent)]
 	Other(#[from] Box<dyn std::error::Error + Sync + Send + 'static>),
 }
@@ -59,7 +111,7 @@ impl From<sgx_status_t> for Error {
 
 impl From<codec::Error> for Error {
 	fn from(e: codec::Error) -> Self {
-		Self::Other(format!("{:?}", e).into())
+		Self::Decode(e)
 	}
 }
 
@@ -86,3 +138,81 @@ impl From<itp_node_api::metadata::Error> for Error {
 		Self::NodeMetadata(e)
 	}
 }
+
+impl Error {
+	/// Stable JSON-RPC error code of the error, see [itp_rpc::error_codes].
+	pub fn code(&self) -> i64 {
+		match self {
+			Error::GetterIsNotAuthorized => GETTER_NOT_AUTHORIZED,
+			Error::GetterHasExpired => GETTER_EXPIRED,
+			Error::InvalidTrustedCallType => INVALID_TRUSTED_CALL_TYPE,
+			Error::ShardVaultNotSet => SHARD_VAULT_NOT_SET,
+			Error::NonceOverflow => NONCE_OVERFLOW,
+			Error::StateObservationFailed { .. } => STATE_OBSERVATION_FAILED,
+			Error::ResourceExhausted(_) => STF_RESOURCE_EXHAUSTED,
+			Error::UnsupportedStfVersion { .. } => UNSUPPORTED_STF_VERSION,
+			Error::Decode(_) => STF_DECODING_ERROR,
+			Error::GetterStreamNotFound(_) => GETTER_STREAM_NOT_FOUND,
+			Error::CrossShardMessageNotCommitted(..) => CROSS_SHARD_MESSAGE_NOT_COMMITTED,
+			Error::CrossShardCommitmentNotAnchored(..) => CROSS_SHARD_COMMITMENT_NOT_ANCHORED,
+			Error::InvalidCrossShardProof => INVALID_CROSS_SHARD_PROOF,
+			Error::InvalidResponseKey(_) => INVALID_RESPONSE_KEY,
+			// Internal errors, a client can't do anything about them. New variants have to be
+			// added here explicitly, such that it is a conscious decision to give them no code.
+			Error::GetterStreamLockPoisoning
+			| Error::Sgx(_)
+			| Error::StateHandler(_)
+			| Error::StateObserver(_)
+			| Error::NodeMetadata(_)
+			| Error::NodeMetadataProvider(_)
+			| Error::Stf(_)
+			| Error::OcallApi(_)
+			| Error::Crypto(_)
+			| Error::PeriodicTasksLockPoisoning
+			| Error::UnknownDestinationShard(_)
+			| Error::ReplayRecordNotFound(_)
+			| Error::ReplayStateMismatch(_)
+			| Error::ReplayOperationsMissing(..)
+			| Error::ReplayRecorderLockPoisoning
+			| Error::MissingStateMigration(_)
+			| Error::Other(_) => STF_EXECUTOR_BASE_ERROR,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn typed_errors_have_distinct_codes() {
+		let errors = [
+			Error::GetterIsNotAuthorized,
+			Error::GetterHasExpired,
+			Error::InvalidTrustedCallType,
+			Error::ShardVaultNotSet,
+			Error::NonceOverflow,
+			Error::StateObservationFailed {
+				shard: ShardIdentifier::default(),
+				source: itp_stf_state_observer::error::Error::LockPoisoning,
+			},
+			Error::Decode(codec::Error::from("bad input")),
+			Error::GetterStreamNotFound(1),
+			Error::CrossShardMessageNotCommitted(ShardIdentifier::default(), 0),
+			Error::CrossShardCommitmentNotAnchored(ShardIdentifier::default(), 0),
+			Error::InvalidCrossShardProof,
+			Error::InvalidResponseKey(itp_sgx_crypto::error::Error::LockPoisoning),
+		];
+		let mut codes: Vec<i64> = errors.iter().map(|e| e.code()).collect();
+		codes.sort();
+		codes.dedup();
+
+		assert_eq!(codes.len(), errors.len());
+		assert!(!codes.contains(&STF_EXECUTOR_BASE_ERROR));
+	}
+
+	#[test]
+	fn untyped_errors_fall_back_to_base_code() {
+		assert_eq!(Error::Other("other".into()).code(), STF_EXECUTOR_BASE_ERROR);
+	}
+}
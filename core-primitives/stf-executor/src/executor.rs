@@ -17,6 +17,7 @@
 
 use crate::{
 	error::{Error, Result},
+	migration::migrate_state,
 	traits::{StatePostProcessing, StateUpdateProposer, StfUpdateState},
 	BatchExecutionResult, ExecutedOperation,
 };
@@ -25,11 +26,12 @@ use itp_node_api::metadata::{provider::AccessNodeMetadata, NodeMetadataTrait};
 use itp_ocall_api::{EnclaveAttestationOCallApi, EnclaveOnChainOCallApi};
 use itp_sgx_externalities::{SgxExternalitiesTrait, StateHash};
 use itp_stf_interface::{
-	parentchain_pallet::ParentchainPalletInterface, StateCallInterface, UpdateState,
+	parentchain_pallet::ParentchainPalletInterface, CrossShardMessagingInterface,
+	ScheduledCallsInterface, StateCallInterface, StfVersioning, UpdateState,
 };
 use itp_stf_primitives::{
-	traits::TrustedCallVerification,
-	types::{ShardIdentifier, TrustedOperation, TrustedOperationOrHash},
+	traits::{TrustedCallVerification, TrustedCallWeight},
+	types::{AccountId, ShardIdentifier, TrustedOperation, TrustedOperationOrHash},
 };
 use itp_stf_state_handler::{handle_state::HandleState, query_shard_state::QueryShardState};
 use itp_time_utils::duration_now;
@@ -121,6 +123,8 @@ where
 		if let Err(e) = Stf::execute_call(
 			state,
 			trusted_call.clone(),
+			&mrenclave.m,
+			shard,
 			&mut extrinsic_call_backs,
 			self.node_metadata_repo.clone(),
 		) {
@@ -275,13 +279,24 @@ where
 	Stf: UpdateState<
 			StateHandler::StateT,
 			<StateHandler::StateT as SgxExternalitiesTrait>::SgxExternalitiesDiffType,
-		> + StateCallInterface<TCS, StateHandler::StateT, NodeMetadataRepository>,
+		> + StateCallInterface<TCS, StateHandler::StateT, NodeMetadataRepository>
+		+ ScheduledCallsInterface<StateHandler::StateT, NodeMetadataRepository>
+		+ CrossShardMessagingInterface<StateHandler::StateT>
+		+ StfVersioning<StateHandler::StateT>,
 	<StateHandler::StateT as SgxExternalitiesTrait>::SgxExternalitiesDiffType:
 		IntoIterator<Item = (Vec<u8>, Option<Vec<u8>>)>,
 	<StateHandler::StateT as SgxExternalitiesTrait>::SgxExternalitiesDiffType:
 		From<BTreeMap<Vec<u8>, Option<Vec<u8>>>>,
 	<Stf as StateCallInterface<TCS, StateHandler::StateT, NodeMetadataRepository>>::Error: Debug,
-	TCS: PartialEq + Encode + Decode + Debug + Clone + Send + Sync + TrustedCallVerification,
+	TCS: PartialEq
+		+ Encode
+		+ Decode
+		+ Debug
+		+ Clone
+		+ Send
+		+ Sync
+		+ TrustedCallVerification
+		+ TrustedCallWeight,
 	G: PartialEq + Encode + Decode + Debug + Clone + Send + Sync,
 {
 	type Externalities = StateHandler::StateT;
@@ -304,16 +319,43 @@ where
 
 		// Execute any pre-processing steps.
 		let mut state = prepare_state_function(state);
+
+		// A state written by an older STF is migrated with the first block of this enclave.
+		// The migration is part of the state diff of the block.
+		if let Some(previous_version) = migrate_state::<Stf, _>(&mut state)? {
+			info!(
+				"Migrated state of shard {:?} from STF version {} to {}",
+				shard,
+				previous_version,
+				Stf::stf_version()
+			);
+		}
 		let mut executed_and_failed_calls = Vec::<ExecutedOperation<TCS, G>>::new();
+		let mut postponed_senders = Vec::<AccountId>::new();
 
 		// Iterate through all calls until time is over.
 		for trusted_call_signed in trusted_calls.into_iter() {
 			// Break if allowed time window is over.
-			if ends_at < duration_now() {
+			let now = duration_now();
+			if ends_at < now {
 				info!("Aborting execution of trusted calls because slot time is up");
 				break
 			}
 
+			// A call whose weight exceeds the remaining slot time stays in the pool for the next
+			// block, and so do the later calls of its sender, as their nonces would not match.
+			if let Some(call) = trusted_call_signed.to_call() {
+				let sender = call.sender_account();
+				if postponed_senders.contains(sender) {
+					continue
+				}
+				if now + call.weight() > ends_at {
+					trace!("Postponing call of weight {:?} to the next block", call.weight());
+					postponed_senders.push(sender.clone());
+					continue
+				}
+			}
+
 			match self.execute_trusted_call_on_stf(
 				&mut state,
 				&trusted_call_signed,
@@ -330,10 +372,34 @@ where
 			};
 		}
 
+		// Execute the scheduled calls that are due, if there is time left. They are not part of
+		// the top pool, hence don't race with the nonces of the pool calls.
+		let mut scheduled_calls_callbacks = Vec::new();
+		if ends_at < duration_now() {
+			info!("Skipping execution of scheduled calls because slot time is up");
+		} else {
+			let mrenclave = self.ocall_api.get_mrenclave_of_self()?;
+			let executed = Stf::execute_due_scheduled_calls(
+				&mut state,
+				&mrenclave.m,
+				shard,
+				&mut scheduled_calls_callbacks,
+				self.node_metadata_repo.clone(),
+			);
+			if executed > 0 {
+				debug!("Executed {} scheduled calls", executed);
+			}
+		}
+
+		// Collected after the scheduled calls, as these can emit messages too.
+		let outgoing_messages = Stf::take_outgoing_messages(&mut state);
+
 		Ok(BatchExecutionResult {
 			executed_operations: executed_and_failed_calls,
 			state_hash_before_execution,
 			state_after_execution: state,
+			outgoing_messages,
+			scheduled_calls_callbacks,
 		})
 	}
 }
@@ -26,9 +26,11 @@ use itp_stf_state_handler::handle_state::HandleState;
 use itp_test::mock::{
 	handle_state_mock::HandleStateMock,
 	onchain_mock::OnchainMock,
-	stf_mock::{GetterMock, StfMock, TrustedCallMock, TrustedCallSignedMock},
+	stf_mock::{
+		scheduled_calls_mock_key, GetterMock, StfMock, TrustedCallMock, TrustedCallSignedMock,
+	},
 };
-use itp_types::H256;
+use itp_types::{OpaqueCall, H256};
 use sp_core::{ed25519, Pair};
 use sp_runtime::app_crypto::sp_core::blake2_256;
 use std::{sync::Arc, time::Duration, vec};
@@ -127,6 +129,43 @@ pub fn propose_state_update_executes_only_one_trusted_call_given_not_enough_time
 	);
 }
 
+pub fn propose_state_update_postpones_calls_exceeding_remaining_time() {
+	// given
+	let (stf_executor, ocall_api, state_handler) = stf_executor();
+	let mrenclave = ocall_api.get_mrenclave_of_self().unwrap().m;
+	let (_, shard) = init_state_and_shard_with_state_handler(state_handler.as_ref());
+	let sender = endowed_account();
+	let other_sender = ed25519::Pair::from_seed(&[2u8; 32]);
+
+	let heavy_call = TrustedCallMock::weighted_noop(sender.public().into(), 1_000)
+		.sign(&sender.clone().into(), 0, &mrenclave, &shard)
+		.into_trusted_operation(true);
+	let next_call_of_sender = TrustedCallMock::noop(sender.public().into())
+		.sign(&sender.clone().into(), 1, &mrenclave, &shard)
+		.into_trusted_operation(true);
+	let call_of_other_sender = TrustedCallMock::noop(other_sender.public().into())
+		.sign(&other_sender.clone().into(), 0, &mrenclave, &shard)
+		.into_trusted_operation(true);
+	let call_of_other_sender_hash: H256 = blake2_256(&call_of_other_sender.encode()).into();
+
+	// when
+	let batch_execution_result = stf_executor
+		.propose_state_update(
+			&vec![heavy_call, next_call_of_sender, call_of_other_sender],
+			&ParentchainHeaderBuilder::default().build(),
+			&shard,
+			Duration::from_millis(100),
+			|state| state,
+		)
+		.unwrap();
+
+	// then
+	assert_eq!(
+		batch_execution_result.get_executed_operation_hashes(),
+		vec![call_of_other_sender_hash]
+	);
+}
+
 pub fn propose_state_update_executes_noop_leaving_state_untouched() {
 	// given
 	let (stf_executor, ocall_api, state_handler) = stf_executor();
@@ -235,6 +274,72 @@ pub fn propose_state_update_always_executes_preprocessing_step() {
 	assert_ne!(old_state, batch_execution_result.state_after_execution);
 }
 
+pub fn propose_state_update_executes_due_scheduled_calls_after_trusted_calls() {
+	// given
+	let (stf_executor, ocall_api, state_handler) = stf_executor();
+	let mrenclave = ocall_api.get_mrenclave_of_self().unwrap().m;
+	let (_, shard) = init_state_and_shard_with_state_handler(state_handler.as_ref());
+	let sender = endowed_account();
+	let trusted_operation =
+		TrustedCallMock::balance_transfer(sender.public().into(), sender.public().into(), 42)
+			.sign(&sender.clone().into(), 0, &mrenclave, &shard)
+			.into_trusted_operation(true);
+	let operation_hash: H256 = blake2_256(&trusted_operation.encode()).into();
+	let due_calls = vec![vec![7u8; 10]];
+
+	// when
+	let batch_execution_result = stf_executor
+		.propose_state_update(
+			&vec![trusted_operation],
+			&ParentchainHeaderBuilder::default().build(),
+			&shard,
+			Duration::from_secs(1000),
+			|mut state| {
+				state.insert(scheduled_calls_mock_key(), due_calls.encode());
+				state
+			},
+		)
+		.unwrap();
+
+	// then
+	// No enclave-signed operation is added for the scheduled calls.
+	assert_eq!(batch_execution_result.executed_operations.len(), 1);
+	assert_eq!(batch_execution_result.get_executed_operation_hashes(), vec![operation_hash]);
+	assert_eq!(batch_execution_result.get_extrinsic_callbacks(), vec![OpaqueCall(vec![7u8; 10])]);
+	assert!(batch_execution_result
+		.state_after_execution
+		.get(&scheduled_calls_mock_key())
+		.is_none());
+}
+
+pub fn propose_state_update_keeps_scheduled_calls_given_no_time() {
+	// given
+	let (stf_executor, _, state_handler) = stf_executor();
+	let (_, shard) = init_state_and_shard_with_state_handler(state_handler.as_ref());
+	let due_calls = vec![vec![7u8; 10]];
+
+	// when
+	let batch_execution_result = stf_executor
+		.propose_state_update(
+			&vec![],
+			&ParentchainHeaderBuilder::default().build(),
+			&shard,
+			Duration::ZERO,
+			|mut state| {
+				state.insert(scheduled_calls_mock_key(), due_calls.encode());
+				state
+			},
+		)
+		.unwrap();
+
+	// then
+	assert!(batch_execution_result.get_extrinsic_callbacks().is_empty());
+	assert_eq!(
+		batch_execution_result.state_after_execution.get(&scheduled_calls_mock_key()),
+		Some(&due_calls.encode())
+	);
+}
+
 // Helper Functions
 fn stf_executor() -> (
 	StfExecutor<
@@ -18,8 +18,12 @@
 //! Getter executor uses the state observer to get the most recent state and runs the getter on it.
 //! The getter is verified (signature verfification) inside the `GetState` implementation.
 
-use crate::{error::Result, state_getter::GetState};
+use crate::{
+	error::{Error, Result},
+	state_getter::GetState,
+};
 use codec::Decode;
+use itp_memory_accounting::Subsystem;
 use itp_stf_primitives::traits::GetterAuthorization;
 use itp_stf_state_observer::traits::ObserveState;
 use itp_types::ShardIdentifier;
@@ -64,13 +68,17 @@ where
 		shard: &ShardIdentifier,
 		encoded_signed_getter: Vec<u8>,
 	) -> Result<Option<Vec<u8>>> {
+		itp_memory_accounting::check_admission(Subsystem::GetterExecution)?;
+		let _scope = itp_memory_accounting::enter(Subsystem::GetterExecution);
+
 		let getter = G::decode(&mut encoded_signed_getter.as_slice())?;
 		trace!("Successfully decoded trusted getter");
 
 		let getter_timer_start = Instant::now();
 		let state_result = self
 			.state_observer
-			.observe_state(shard, |state| StateGetter::get_state(getter, state))??;
+			.observe_state(shard, |state| StateGetter::get_state(getter, state))
+			.map_err(|source| Error::StateObservationFailed { shard: *shard, source })??;
 
 		debug!("Getter executed in {} ms", getter_timer_start.elapsed().as_millis());
 
@@ -130,7 +138,11 @@ mod tests {
 		assert_eq!(decoded_state, test_state);
 	}
 	fn dummy_trusted_getter() -> TrustedGetterSignedMock {
-		TrustedGetterSignedMock { getter: TrustedGetterMock::some_value, signature: true }
+		TrustedGetterSignedMock {
+			getter: TrustedGetterMock::some_value,
+			valid_until: u64::MAX,
+			signature: true,
+		}
 		//			TrustedGetter::nonce(AccountId::new([0u8; 32])),
 		//			MultiSignature::Ed25519(Signature::from_raw([0u8; 64])),
 	}
@@ -0,0 +1,286 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Streams large getter results in chunks over the direct RPC.
+//!
+//! Requesting chunk 0 executes the getter. If the encoded result does not fit into a single
+//! chunk, it is kept in the enclave, keyed by the hash of the shard and the signed getter, and
+//! the remaining chunks are served from there. Hence, only a client that holds the signed getter
+//! can fetch the remaining chunks, and all chunks are from the same state. A stream is closed
+//! when its last chunk was fetched, when it expired, or when it is evicted to make room for a
+//! new stream.
+
+#[cfg(feature = "sgx")]
+use std::sync::SgxRwLock as RwLock;
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+use crate::{
+	error::{Error, Result},
+	getter_executor::ExecuteGetter,
+};
+use codec::Encode;
+use core::time::Duration;
+use itp_time_utils::duration_now;
+use itp_types::{getter_stream::GetterStreamChunk, ShardIdentifier, H256};
+use log::*;
+use sp_core::blake2_256;
+use std::{collections::BTreeMap, sync::Arc, vec::Vec};
+
+/// Size of the data in a single chunk.
+pub const GETTER_STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Maximum number of streams that are kept open, the oldest one is evicted beyond that.
+pub const MAX_OPEN_GETTER_STREAMS: usize = 32;
+
+/// Time after which an open stream is dropped.
+pub const GETTER_STREAM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Executes a getter and returns its result in chunks.
+pub trait StreamGetter {
+	fn stream_getter(
+		&self,
+		shard: &ShardIdentifier,
+		encoded_signed_getter: Vec<u8>,
+		chunk: u32,
+	) -> Result<GetterStreamChunk>;
+}
+
+struct OpenStream {
+	result: Vec<u8>,
+	opened_at: Duration,
+}
+
+pub struct GetterStreamer<GetterExecutor> {
+	getter_executor: Arc<GetterExecutor>,
+	streams: RwLock<BTreeMap<H256, OpenStream>>,
+	chunk_size: usize,
+	max_open_streams: usize,
+	timeout: Duration,
+}
+
+impl<GetterExecutor> GetterStreamer<GetterExecutor> {
+	pub fn new(getter_executor: Arc<GetterExecutor>) -> Self {
+		Self::with_limits(
+			getter_executor,
+			GETTER_STREAM_CHUNK_SIZE,
+			MAX_OPEN_GETTER_STREAMS,
+			GETTER_STREAM_TIMEOUT,
+		)
+	}
+
+	pub fn with_limits(
+		getter_executor: Arc<GetterExecutor>,
+		chunk_size: usize,
+		max_open_streams: usize,
+		timeout: Duration,
+	) -> Self {
+		GetterStreamer {
+			getter_executor,
+			streams: Default::default(),
+			chunk_size: chunk_size.max(1),
+			max_open_streams,
+			timeout,
+		}
+	}
+
+	pub fn open_streams(&self) -> usize {
+		self.streams.read().map(|streams| streams.len()).unwrap_or_default()
+	}
+
+	fn total_chunks(&self, result_len: usize) -> u32 {
+		// An empty result is still returned as a single (empty) chunk.
+		((result_len.max(1) + self.chunk_size - 1) / self.chunk_size) as u32
+	}
+
+	fn chunk_of(&self, result: &[u8], chunk: u32) -> GetterStreamChunk {
+		let start = (chunk as usize).saturating_mul(self.chunk_size).min(result.len());
+		let end = start.saturating_add(self.chunk_size).min(result.len());
+		GetterStreamChunk {
+			chunk,
+			total_chunks: self.total_chunks(result.len()),
+			data: result[start..end].to_vec(),
+		}
+	}
+
+	fn open_stream(&self, id: H256, result: Vec<u8>, now: Duration) -> Result<()> {
+		let mut streams = self.streams.write().map_err(|_| Error::GetterStreamLockPoisoning)?;
+		let timeout = self.timeout;
+		streams.retain(|_, stream| now.saturating_sub(stream.opened_at) <= timeout);
+
+		while streams.len() >= self.max_open_streams && !streams.contains_key(&id) {
+			let oldest =
+				streams.iter().min_by_key(|(_, stream)| stream.opened_at).map(|(id, _)| *id);
+			match oldest {
+				Some(oldest) => {
+					debug!("Evicting getter stream {:?}, too many open streams", oldest);
+					streams.remove(&oldest);
+				},
+				None => break,
+			}
+		}
+		if self.max_open_streams > 0 {
+			streams.insert(id, OpenStream { result, opened_at: now });
+		}
+		Ok(())
+	}
+
+	fn next_chunk(&self, id: &H256, chunk: u32, now: Duration) -> Result<GetterStreamChunk> {
+		let mut streams = self.streams.write().map_err(|_| Error::GetterStreamLockPoisoning)?;
+		let stream = streams
+			.get(id)
+			.filter(|stream| now.saturating_sub(stream.opened_at) <= self.timeout)
+			.ok_or(Error::GetterStreamNotFound(chunk))?;
+
+		if chunk >= self.total_chunks(stream.result.len()) {
+			return Err(Error::GetterStreamNotFound(chunk))
+		}
+		let next = self.chunk_of(&stream.result, chunk);
+		if next.is_last() {
+			streams.remove(id);
+		}
+		Ok(next)
+	}
+}
+
+impl<GetterExecutor> StreamGetter for GetterStreamer<GetterExecutor>
+where
+	GetterExecutor: ExecuteGetter,
+{
+	fn stream_getter(
+		&self,
+		shard: &ShardIdentifier,
+		encoded_signed_getter: Vec<u8>,
+		chunk: u32,
+	) -> Result<GetterStreamChunk> {
+		let id: H256 = blake2_256(&(shard, &encoded_signed_getter).encode()).into();
+		let now = duration_now();
+
+		if chunk > 0 {
+			return self.next_chunk(&id, chunk, now)
+		}
+
+		let result = self.getter_executor.execute_getter(shard, encoded_signed_getter)?.encode();
+		let first = self.chunk_of(&result, 0);
+		if !first.is_last() {
+			trace!("Opening getter stream {:?} with {} chunks", id, first.total_chunks);
+			self.open_stream(id, result, now)?;
+		}
+		Ok(first)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Decode;
+
+	/// Returns the encoded getter itself as result.
+	struct EchoGetterExecutor;
+
+	impl ExecuteGetter for EchoGetterExecutor {
+		fn execute_getter(
+			&self,
+			_shard: &ShardIdentifier,
+			encoded_signed_getter: Vec<u8>,
+		) -> Result<Option<Vec<u8>>> {
+			Ok(Some(encoded_signed_getter))
+		}
+	}
+
+	fn streamer(chunk_size: usize, max_open_streams: usize) -> GetterStreamer<EchoGetterExecutor> {
+		GetterStreamer::with_limits(
+			Arc::new(EchoGetterExecutor),
+			chunk_size,
+			max_open_streams,
+			GETTER_STREAM_TIMEOUT,
+		)
+	}
+
+	fn fetch_all(
+		streamer: &GetterStreamer<EchoGetterExecutor>,
+		getter: &[u8],
+	) -> Result<Option<Vec<u8>>> {
+		let shard = ShardIdentifier::default();
+		let mut result = Vec::new();
+		let mut chunk = 0;
+		loop {
+			let next = streamer.stream_getter(&shard, getter.to_vec(), chunk)?;
+			assert_eq!(next.chunk, chunk);
+			result.extend(next.data.iter());
+			if next.is_last() {
+				break
+			}
+			chunk += 1;
+		}
+		Ok(Decode::decode(&mut result.as_slice()).unwrap())
+	}
+
+	#[test]
+	fn concatenated_chunks_are_the_getter_result() {
+		let streamer = streamer(10, 4);
+		let getter: Vec<u8> = (0..=255).collect();
+
+		assert_eq!(fetch_all(&streamer, &getter).unwrap(), Some(getter));
+		assert_eq!(streamer.open_streams(), 0);
+	}
+
+	#[test]
+	fn small_result_is_returned_in_a_single_chunk_without_opening_a_stream() {
+		let streamer = streamer(1024, 4);
+
+		let chunk = streamer.stream_getter(&Default::default(), vec![1, 2, 3], 0).unwrap();
+
+		assert_eq!(chunk.total_chunks, 1);
+		assert!(chunk.is_last());
+		assert_eq!(streamer.open_streams(), 0);
+	}
+
+	#[test]
+	fn chunks_of_unknown_stream_or_out_of_range_are_rejected() {
+		let streamer = streamer(10, 4);
+		let shard = ShardIdentifier::default();
+		let getter: Vec<u8> = (0..50).collect();
+
+		assert!(matches!(
+			streamer.stream_getter(&shard, getter.clone(), 1),
+			Err(Error::GetterStreamNotFound(1))
+		));
+
+		let first = streamer.stream_getter(&shard, getter.clone(), 0).unwrap();
+		assert!(matches!(
+			streamer.stream_getter(&shard, getter, first.total_chunks),
+			Err(Error::GetterStreamNotFound(_))
+		));
+	}
+
+	#[test]
+	fn oldest_stream_is_evicted_when_limit_is_reached() {
+		let streamer = streamer(10, 1);
+		let shard = ShardIdentifier::default();
+		let first_getter: Vec<u8> = (0..50).collect();
+		let second_getter: Vec<u8> = (50..100).collect();
+
+		streamer.stream_getter(&shard, first_getter.clone(), 0).unwrap();
+		streamer.stream_getter(&shard, second_getter.clone(), 0).unwrap();
+
+		assert_eq!(streamer.open_streams(), 1);
+		assert!(streamer.stream_getter(&shard, first_getter, 1).is_err());
+		assert!(streamer.stream_getter(&shard, second_getter, 1).is_ok());
+	}
+}
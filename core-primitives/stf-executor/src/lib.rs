@@ -27,31 +27,34 @@ extern crate sgx_tstd as std;
 use codec::{Decode, Encode};
 use core::fmt::Debug;
 use itp_sgx_externalities::SgxExternalitiesTrait;
-use itp_stf_primitives::types::TrustedOperationOrHash;
+use itp_stf_primitives::{cross_shard::CrossShardMessage, types::TrustedOperationOrHash};
 use itp_types::{OpaqueCall, H256};
 use std::vec::Vec;
 
 // re-export module to properly feature gate sgx and regular std environment
 #[cfg(all(not(feature = "std"), feature = "sgx"))]
 pub mod sgx_reexport_prelude {
+	pub use futures_sgx as futures;
 	pub use thiserror_sgx as thiserror;
 }
 
+pub mod cross_shard_router;
+pub mod enclave_signer;
 pub mod error;
+pub mod executor;
 pub mod getter_executor;
+pub mod getter_stream;
+pub mod migration;
+pub mod replay;
 pub mod state_getter;
 pub mod traits;
 
-#[cfg(feature = "sgx")]
-pub mod executor;
-
-#[cfg(feature = "sgx")]
-pub mod enclave_signer;
-
 #[cfg(all(feature = "sgx", feature = "test"))]
 pub mod executor_tests;
 
-#[cfg(feature = "mocks")]
+pub mod periodic_tasks;
+
+#[cfg(any(test, feature = "mocks"))]
 pub mod mocks;
 
 /// Execution status of a trusted operation
@@ -133,6 +136,10 @@ where
 	pub state_hash_before_execution: H256,
 	pub executed_operations: Vec<ExecutedOperation<TCS, G>>,
 	pub state_after_execution: Externalities,
+	/// Messages to other shards emitted by the executed operations, in order.
+	pub outgoing_messages: Vec<CrossShardMessage>,
+	/// Extrinsic callbacks of the scheduled calls executed after the trusted operations.
+	pub scheduled_calls_callbacks: Vec<OpaqueCall>,
 }
 
 impl<Externalities, TCS, G> BatchExecutionResult<Externalities, TCS, G>
@@ -145,6 +152,7 @@ where
 		self.executed_operations
 			.iter()
 			.flat_map(|e| e.status.get_extrinsic_callbacks())
+			.chain(self.scheduled_calls_callbacks.iter().cloned())
 			.collect()
 	}
 
@@ -218,6 +226,8 @@ mod tests {
 			executed_operations: executed_calls,
 			state_hash_before_execution: H256::default(),
 			state_after_execution: SgxExternalities::default(),
+			outgoing_messages: Vec::new(),
+			scheduled_calls_callbacks: Vec::new(),
 		}
 	}
 
@@ -0,0 +1,147 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Migration of shard states written by an older STF version.
+
+use crate::error::{Error, Result};
+use itp_stf_interface::{StfVersion, StfVersioning};
+use log::*;
+
+/// Migrates `state` to the STF version of this enclave, by running all registered
+/// migrations from the version of the state onwards.
+///
+/// Returns the previous version of the state if it was migrated. Fails if the state
+/// was written by a newer STF, which this enclave can't interpret.
+pub fn migrate_state<Stf, State>(state: &mut State) -> Result<Option<StfVersion>>
+where
+	Stf: StfVersioning<State>,
+{
+	let enclave_version = Stf::stf_version();
+	let state_version = Stf::get_state_stf_version(state);
+
+	if state_version > enclave_version {
+		return Err(Error::UnsupportedStfVersion { state_version, enclave_version })
+	}
+	if state_version == enclave_version {
+		return Ok(None)
+	}
+
+	let migrations = Stf::state_migrations();
+	for version in state_version..enclave_version {
+		let migration = migrations
+			.iter()
+			.find(|m| m.from_version == version)
+			.ok_or(Error::MissingStateMigration(version))?;
+
+		debug!("Migrating state from STF version {} to {}", version, version + 1);
+		(migration.migrate)(state);
+		Stf::set_state_stf_version(state, version + 1);
+	}
+
+	Ok(Some(state_version))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::{Decode, Encode};
+	use itp_sgx_externalities::{SgxExternalities, SgxExternalitiesTrait};
+	use itp_stf_interface::{StateMigration, STF_VERSION_KEY};
+
+	const RENAMED_KEY: &[u8] = b"renamed";
+
+	/// STF with version 2, which renamed a storage key in version 1.
+	struct VersionedStf;
+
+	impl StfVersioning<SgxExternalities> for VersionedStf {
+		fn stf_version() -> StfVersion {
+			2
+		}
+
+		fn get_state_stf_version(state: &mut SgxExternalities) -> StfVersion {
+			state
+				.get(STF_VERSION_KEY.as_bytes())
+				.and_then(|v| Decode::decode(&mut v.as_slice()).ok())
+				.unwrap_or_default()
+		}
+
+		fn set_state_stf_version(state: &mut SgxExternalities, version: StfVersion) {
+			state.insert(STF_VERSION_KEY.into(), version.encode());
+		}
+
+		fn state_migrations() -> Vec<StateMigration<SgxExternalities>> {
+			vec![
+				StateMigration { from_version: 0, migrate: |_| {} },
+				StateMigration {
+					from_version: 1,
+					migrate: |state| {
+						if let Some(value) = state.remove(b"original") {
+							state.insert(RENAMED_KEY.to_vec(), value);
+						}
+					},
+				},
+			]
+		}
+	}
+
+	fn state_with_version(version: StfVersion) -> SgxExternalities {
+		let mut state = SgxExternalities::default();
+		VersionedStf::set_state_stf_version(&mut state, version);
+		state.insert(b"original".to_vec(), vec![42]);
+		state
+	}
+
+	#[test]
+	fn unversioned_state_runs_all_migrations() {
+		let mut state = SgxExternalities::default();
+		state.insert(b"original".to_vec(), vec![42]);
+
+		assert_eq!(Some(0), migrate_state::<VersionedStf, _>(&mut state).unwrap());
+
+		assert_eq!(2, VersionedStf::get_state_stf_version(&mut state));
+		assert_eq!(Some(&vec![42]), state.get(RENAMED_KEY));
+		assert!(state.get(b"original").is_none());
+	}
+
+	#[test]
+	fn only_newer_migrations_are_run() {
+		let mut state = state_with_version(1);
+
+		assert_eq!(Some(1), migrate_state::<VersionedStf, _>(&mut state).unwrap());
+
+		assert_eq!(2, VersionedStf::get_state_stf_version(&mut state));
+		assert_eq!(Some(&vec![42]), state.get(RENAMED_KEY));
+	}
+
+	#[test]
+	fn current_state_is_not_migrated() {
+		let mut state = state_with_version(2);
+
+		assert_eq!(None, migrate_state::<VersionedStf, _>(&mut state).unwrap());
+		assert!(state.get(RENAMED_KEY).is_none());
+	}
+
+	#[test]
+	fn state_of_newer_stf_is_refused() {
+		let mut state = state_with_version(3);
+
+		assert!(matches!(
+			migrate_state::<VersionedStf, _>(&mut state),
+			Err(Error::UnsupportedStfVersion { state_version: 3, enclave_version: 2 })
+		));
+	}
+}
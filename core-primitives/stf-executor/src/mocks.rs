@@ -98,6 +98,8 @@ where
 			executed_operations,
 			state_hash_before_execution: H256::default(),
 			state_after_execution: updated_state,
+			outgoing_messages: Vec::new(),
+			scheduled_calls_callbacks: Vec::new(),
 		})
 	}
 }
@@ -128,6 +130,10 @@ impl<TCS: PartialEq + Encode + Debug> StfEnclaveSigning<TCS> for StfEnclaveSigne
 		Ok(self.signer.public().into())
 	}
 
+	fn get_mrenclave(&self) -> Result<[u8; 32]> {
+		Ok(self.mr_enclave)
+	}
+
 	fn sign_call_with_self<TC: Encode + Debug + TrustedCallSigning<TCS>>(
 		&self,
 		trusted_call: &TC,
@@ -139,7 +145,7 @@ impl<TCS: PartialEq + Encode + Debug> StfEnclaveSigning<TCS> for StfEnclaveSigne
 
 impl StfShardVaultQuery for StfEnclaveSignerMock {
 	fn get_shard_vault(&self, _shard: &ShardIdentifier) -> Result<AccountId> {
-		Err(crate::error::Error::Other("shard vault undefined".into()))
+		Err(crate::error::Error::ShardVaultNotSet)
 	}
 }
 
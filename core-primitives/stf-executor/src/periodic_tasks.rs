@@ -0,0 +1,340 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Periodic maintenance tasks that are executed by the enclave itself.
+//!
+//! A [PeriodicTask] composes trusted calls, which are signed with the enclave account and
+//! submitted to the top pool every `period` sidechain slots. The slot worker drives the
+//! execution by calling [ExecutePeriodicTasks::on_slot].
+
+#[cfg(feature = "sgx")]
+use std::sync::SgxRwLock as RwLock;
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use crate::sgx_reexport_prelude::*;
+
+use crate::{
+	error::{Error, Result},
+	traits::{ExecutePeriodicTasks, StfEnclaveSigning},
+};
+use codec::{Decode, Encode};
+use core::{fmt::Debug, marker::PhantomData};
+use itp_sgx_crypto::{key_repository::AccessKey, ShieldingCryptoEncrypt};
+use itp_stf_primitives::{
+	traits::{TrustedCallSigning, TrustedCallVerification},
+	types::{AccountId, ShardIdentifier, TrustedOperation},
+};
+use itp_top_pool_author::traits::AuthorApi;
+use itp_types::H256;
+use log::*;
+use std::{boxed::Box, collections::BTreeMap, format, sync::Arc, vec::Vec};
+
+/// A task that is periodically executed by the enclave.
+pub trait PeriodicTask<TC>: Send + Sync {
+	/// Name of the task, used for logging.
+	fn name(&self) -> &'static str;
+
+	/// Composes the trusted calls that should be executed on `shard` in this period.
+	///
+	/// The calls are signed by the enclave, hence `enclave_account` has to be their sender.
+	fn compose_calls(&self, enclave_account: &AccountId, shard: &ShardIdentifier) -> Vec<TC>;
+}
+
+struct RegisteredTask<TC> {
+	task: Box<dyn PeriodicTask<TC>>,
+	/// Cadence of the task in sidechain slots.
+	period: u64,
+	/// Index of the last period the task has been executed in, per shard.
+	last_executed_period: BTreeMap<ShardIdentifier, u64>,
+	/// Hashes of the operations submitted in the last execution, per shard.
+	submitted: BTreeMap<ShardIdentifier, Vec<H256>>,
+}
+
+pub struct PeriodicTaskScheduler<EnclaveSigner, TopPoolAuthor, ShieldingKeyRepository, TC, TCS, G> {
+	enclave_signer: Arc<EnclaveSigner>,
+	top_pool_author: Arc<TopPoolAuthor>,
+	shielding_key_repo: Arc<ShieldingKeyRepository>,
+	tasks: RwLock<Vec<RegisteredTask<TC>>>,
+	_phantom: PhantomData<(TCS, G)>,
+}
+
+impl<EnclaveSigner, TopPoolAuthor, ShieldingKeyRepository, TC, TCS, G>
+	PeriodicTaskScheduler<EnclaveSigner, TopPoolAuthor, ShieldingKeyRepository, TC, TCS, G>
+where
+	EnclaveSigner: StfEnclaveSigning<TCS>,
+	TopPoolAuthor: AuthorApi<H256, H256, TCS, G> + Send + Sync + 'static,
+	ShieldingKeyRepository: AccessKey,
+	<ShieldingKeyRepository as AccessKey>::KeyType: ShieldingCryptoEncrypt,
+	TC: Encode + Debug + TrustedCallSigning<TCS>,
+	TCS: PartialEq + Encode + Decode + Debug + Clone + Send + Sync + TrustedCallVerification,
+	G: PartialEq + Encode + Decode + Debug + Clone + Send + Sync,
+{
+	pub fn new(
+		enclave_signer: Arc<EnclaveSigner>,
+		top_pool_author: Arc<TopPoolAuthor>,
+		shielding_key_repo: Arc<ShieldingKeyRepository>,
+	) -> Self {
+		Self {
+			enclave_signer,
+			top_pool_author,
+			shielding_key_repo,
+			tasks: RwLock::new(Vec::new()),
+			_phantom: PhantomData,
+		}
+	}
+
+	/// Registers a task that is executed every `period` sidechain slots.
+	pub fn register(&self, task: Box<dyn PeriodicTask<TC>>, period: u64) -> Result<()> {
+		if period == 0 {
+			return Err(Error::Other(format!("Period of task {} must not be 0", task.name()).into()))
+		}
+		let mut tasks = self.tasks.write().map_err(|_| Error::PeriodicTasksLockPoisoning)?;
+		info!("Registering periodic task {} with a period of {} slots", task.name(), period);
+		tasks.push(RegisteredTask {
+			task,
+			period,
+			last_executed_period: Default::default(),
+			submitted: Default::default(),
+		});
+		Ok(())
+	}
+
+	/// Returns true if an operation submitted by the last execution of the task is still
+	/// pending in the top pool.
+	fn has_pending_operations(
+		&self,
+		task: &RegisteredTask<TC>,
+		enclave_account: &AccountId,
+		shard: &ShardIdentifier,
+	) -> bool {
+		let submitted = match task.submitted.get(shard) {
+			Some(hashes) if !hashes.is_empty() => hashes,
+			_ => return false,
+		};
+		self.top_pool_author
+			.get_pending_trusted_calls_for(*shard, enclave_account)
+			.iter()
+			.any(|top| submitted.contains(&self.top_pool_author.hash_of(top)))
+	}
+
+	fn submit(&self, call: &TC, shard: &ShardIdentifier) -> Result<H256> {
+		let signed_call = self.enclave_signer.sign_call_with_self(call, shard)?;
+		let trusted_operation = TrustedOperation::<TCS, G>::indirect_call(signed_call);
+		let hash = self.top_pool_author.hash_of(&trusted_operation);
+
+		let encrypted_operation = self
+			.shielding_key_repo
+			.retrieve_key()?
+			.encrypt(&trusted_operation.encode())
+			.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+		futures::executor::block_on(self.top_pool_author.submit_top(encrypted_operation, *shard))
+			.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+		Ok(hash)
+	}
+}
+
+impl<EnclaveSigner, TopPoolAuthor, ShieldingKeyRepository, TC, TCS, G> ExecutePeriodicTasks
+	for PeriodicTaskScheduler<EnclaveSigner, TopPoolAuthor, ShieldingKeyRepository, TC, TCS, G>
+where
+	EnclaveSigner: StfEnclaveSigning<TCS>,
+	TopPoolAuthor: AuthorApi<H256, H256, TCS, G> + Send + Sync + 'static,
+	ShieldingKeyRepository: AccessKey,
+	<ShieldingKeyRepository as AccessKey>::KeyType: ShieldingCryptoEncrypt,
+	TC: Encode + Debug + TrustedCallSigning<TCS>,
+	TCS: PartialEq + Encode + Decode + Debug + Clone + Send + Sync + TrustedCallVerification,
+	G: PartialEq + Encode + Decode + Debug + Clone + Send + Sync,
+{
+	fn on_slot(&self, slot: u64, shards: &[ShardIdentifier]) -> Result<()> {
+		let mut tasks = self.tasks.write().map_err(|_| Error::PeriodicTasksLockPoisoning)?;
+		if tasks.is_empty() {
+			return Ok(())
+		}
+		let enclave_account = self.enclave_signer.get_enclave_account()?;
+
+		for task in tasks.iter_mut() {
+			// Slots that were missed are not caught up on, a task runs at most once per period.
+			let period = slot / task.period;
+
+			for shard in shards {
+				if task.last_executed_period.get(shard).map_or(false, |last| *last >= period) {
+					continue
+				}
+				if self.has_pending_operations(task, &enclave_account, shard) {
+					debug!(
+						"Skipping periodic task {} on shard {:?}, previous execution is still pending",
+						task.task.name(),
+						shard
+					);
+					continue
+				}
+
+				let calls = task.task.compose_calls(&enclave_account, shard);
+				debug!(
+					"Executing periodic task {} on shard {:?} with {} call(s)",
+					task.task.name(),
+					shard,
+					calls.len()
+				);
+				let mut submitted = Vec::with_capacity(calls.len());
+				for call in calls.iter() {
+					match self.submit(call, shard) {
+						Ok(hash) => submitted.push(hash),
+						Err(e) => error!(
+							"Failed to submit call {:?} of periodic task {}: {:?}",
+							call,
+							task.task.name(),
+							e
+						),
+					}
+				}
+				task.submitted.insert(*shard, submitted);
+				task.last_executed_period.insert(*shard, period);
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mocks::StfEnclaveSignerMock;
+	use itp_sgx_crypto::mocks::KeyRepositoryMock;
+	use itp_stf_primitives::types::TrustedOperationOrHash;
+	use itp_test::mock::{
+		shielding_crypto_mock::ShieldingCryptoMock,
+		stf_mock::{GetterMock, TrustedCallMock, TrustedCallSignedMock},
+	};
+	use itp_top_pool_author::mocks::AuthorApiMock;
+
+	type TestTopPoolAuthor = AuthorApiMock<H256, H256, TrustedCallSignedMock, GetterMock>;
+	type TestScheduler<ShieldingCrypto = ShieldingCryptoMock> = PeriodicTaskScheduler<
+		StfEnclaveSignerMock,
+		TestTopPoolAuthor,
+		KeyRepositoryMock<ShieldingCrypto>,
+		TrustedCallMock,
+		TrustedCallSignedMock,
+		GetterMock,
+	>;
+
+	/// Leaves the operations unencrypted, so that the author mock can decode them and the
+	/// submitted operations are found among the pending ones.
+	#[derive(Clone, Default)]
+	struct PlainTextCrypto;
+
+	impl ShieldingCryptoEncrypt for PlainTextCrypto {
+		type Error = ();
+
+		fn encrypt(&self, data: &[u8]) -> core::result::Result<Vec<u8>, ()> {
+			Ok(data.to_vec())
+		}
+	}
+
+	struct NoopTask;
+
+	impl PeriodicTask<TrustedCallMock> for NoopTask {
+		fn name(&self) -> &'static str {
+			"noop"
+		}
+
+		fn compose_calls(
+			&self,
+			enclave_account: &AccountId,
+			_shard: &ShardIdentifier,
+		) -> Vec<TrustedCallMock> {
+			vec![TrustedCallMock::noop(enclave_account.clone())]
+		}
+	}
+
+	fn test_scheduler() -> (TestScheduler, Arc<TestTopPoolAuthor>) {
+		test_scheduler_with_crypto(ShieldingCryptoMock::default())
+	}
+
+	fn test_scheduler_with_crypto<ShieldingCrypto>(
+		shielding_crypto: ShieldingCrypto,
+	) -> (TestScheduler<ShieldingCrypto>, Arc<TestTopPoolAuthor>)
+	where
+		ShieldingCrypto: ShieldingCryptoEncrypt + Clone,
+	{
+		let top_pool_author = Arc::new(TestTopPoolAuthor::default());
+		let scheduler = TestScheduler::new(
+			Arc::new(StfEnclaveSignerMock::default()),
+			top_pool_author.clone(),
+			Arc::new(KeyRepositoryMock::new(shielding_crypto)),
+		);
+		scheduler.register(Box::new(NoopTask), 5).unwrap();
+		(scheduler, top_pool_author)
+	}
+
+	#[test]
+	fn task_is_executed_once_per_period() {
+		let (scheduler, top_pool_author) = test_scheduler();
+		let shard = ShardIdentifier::default();
+
+		scheduler.on_slot(10, &[shard]).unwrap();
+		scheduler.on_slot(11, &[shard]).unwrap();
+		scheduler.on_slot(14, &[shard]).unwrap();
+		assert_eq!(1, top_pool_author.pending_tops(shard).unwrap().len());
+
+		scheduler.on_slot(15, &[shard]).unwrap();
+		assert_eq!(2, top_pool_author.pending_tops(shard).unwrap().len());
+	}
+
+	#[test]
+	fn missed_slots_are_not_caught_up_on() {
+		let (scheduler, top_pool_author) = test_scheduler();
+		let shard = ShardIdentifier::default();
+
+		scheduler.on_slot(10, &[shard]).unwrap();
+		scheduler.on_slot(42, &[shard]).unwrap();
+		scheduler.on_slot(43, &[shard]).unwrap();
+
+		assert_eq!(2, top_pool_author.pending_tops(shard).unwrap().len());
+	}
+
+	#[test]
+	fn task_is_skipped_while_previous_execution_is_pending() {
+		let (scheduler, top_pool_author) = test_scheduler_with_crypto(PlainTextCrypto);
+		let shard = ShardIdentifier::default();
+
+		scheduler.on_slot(10, &[shard]).unwrap();
+		scheduler.on_slot(15, &[shard]).unwrap();
+		assert_eq!(1, top_pool_author.pending_tops(shard).unwrap().len());
+
+		// Once the previous operation left the pool, the task is executed in the same period.
+		let executed = top_pool_author
+			.get_pending_trusted_calls(shard)
+			.into_iter()
+			.map(|top| (TrustedOperationOrHash::from_top(top), true))
+			.collect();
+		top_pool_author.remove_calls_from_pool(shard, executed);
+		scheduler.on_slot(16, &[shard]).unwrap();
+		assert_eq!(1, top_pool_author.pending_tops(shard).unwrap().len());
+
+		scheduler.on_slot(17, &[shard]).unwrap();
+		assert_eq!(1, top_pool_author.pending_tops(shard).unwrap().len());
+	}
+
+	#[test]
+	fn registering_task_with_zero_period_fails() {
+		let (scheduler, _) = test_scheduler();
+		assert!(scheduler.register(Box::new(NoopTask), 0).is_err());
+	}
+}
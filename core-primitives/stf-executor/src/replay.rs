@@ -0,0 +1,620 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Deterministic replay of the sidechain block execution, for debugging.
+//!
+//! When recording is enabled, every imported sidechain block is recorded, the ones proposed by
+//! this validateer as well as the ones imported from peers: the trusted operations, the
+//! parentchain header, the timestamp and the state the block was executed on. Re-executing these
+//! inputs must result in exactly the same state changes as the block, any difference points to
+//! non-determinism in the STF or to a diverging state of the proposer.
+//!
+//! The state is not stored in full for every block. A record contains the changes relative to
+//! the state of the previous record, only every [`REPLAY_CHECKPOINT_INTERVAL`]-th record (or the
+//! first one after a restart) carries the full state. The records are kept in memory while the
+//! blocks are imported and only written to disk by [`RecordBlockReplay::flush`], after the
+//! block production of the slot.
+
+use crate::{
+	error::{Error, Result},
+	traits::StateUpdateProposer,
+};
+use codec::{Decode, Encode};
+use core::fmt::Debug;
+use itp_sgx_externalities::{
+	SgxExternalities, SgxExternalitiesTrait, SgxExternalitiesType, StateHash,
+};
+use itp_stf_primitives::types::{ShardIdentifier, TrustedOperation};
+use itp_types::{
+	replay::{BlockReplayReport, StateMismatch},
+	H256,
+};
+use log::*;
+use sp_runtime::traits::Header as HeaderTrait;
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	format,
+	path::PathBuf,
+	time::Duration,
+	vec::Vec,
+};
+
+#[cfg(feature = "sgx")]
+use std::sync::SgxMutex as Mutex;
+
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+#[cfg(feature = "std")]
+use itp_sgx_io::{read as read_file, write as write_file};
+#[cfg(feature = "sgx")]
+use itp_sgx_io::{seal as write_file, unseal as read_file};
+
+/// Directory (relative to the worker's base directory) containing the replay records.
+pub const REPLAY_RECORDS_DIR: &str = "replay";
+
+/// Number of replay records kept per shard, older records are deleted.
+pub const REPLAY_RECORDS_TO_KEEP: u64 = 1000;
+
+/// Every n-th block of a shard is recorded with its full state, the others only with the
+/// changes relative to the previous record.
+pub const REPLAY_CHECKPOINT_INTERVAL: u64 = 100;
+
+/// Execution time granted to a replay. Unlike the block production, the replay is not bound to
+/// a slot and must execute all the recorded operations.
+pub const REPLAY_MAX_EXEC_DURATION: Duration = Duration::from_secs(60);
+
+/// Storage changes of a block execution, `None` meaning the key was removed.
+pub type StateChanges = BTreeMap<Vec<u8>, Option<Vec<u8>>>;
+
+/// The state a recorded block was executed on.
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+pub enum RecordedState {
+	/// The full encoded state.
+	Checkpoint(Vec<u8>),
+	/// The changes relative to the state of the record of `previous_block_number`. Besides the
+	/// changes of that block, they contain the changes of the parentchain block import.
+	Changes { previous_block_number: u64, changes: StateChanges },
+}
+
+/// Everything needed to re-execute the block with number `block_number`.
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+pub struct BlockReplayRecord<PH, TCS, G>
+where
+	TCS: PartialEq + Encode + Decode + Debug + Send + Sync,
+	G: PartialEq + Encode + Decode + Debug + Send + Sync,
+{
+	pub shard: ShardIdentifier,
+	pub block_number: u64,
+	pub timestamp: u64,
+	pub parentchain_header: PH,
+	pub trusted_operations: Vec<TrustedOperation<TCS, G>>,
+	/// Hashes of the block's operations that were unknown to this validateer, i.e. neither
+	/// proposed by it nor submitted to its pool. A block with missing operations can't be
+	/// replayed.
+	pub missing_operations: Vec<H256>,
+	pub state_hash_before_execution: H256,
+	pub state_before_execution: RecordedState,
+	pub state_hash_after_execution: H256,
+	pub state_changes: StateChanges,
+}
+
+/// An imported block, together with the operations it executed as far as they are known.
+pub struct ImportedBlock<PH, TCS, G> {
+	pub shard: ShardIdentifier,
+	pub block_number: u64,
+	pub timestamp: u64,
+	pub parentchain_header: PH,
+	pub trusted_operations: Vec<TrustedOperation<TCS, G>>,
+	pub missing_operations: Vec<H256>,
+	pub state_hash_after_execution: H256,
+	pub state_changes: StateChanges,
+}
+
+/// Records the imported blocks and loads the records for the replay.
+pub trait RecordBlockReplay<PH, TCS, G>: Send + Sync
+where
+	TCS: PartialEq + Encode + Decode + Debug + Send + Sync,
+	G: PartialEq + Encode + Decode + Debug + Send + Sync,
+{
+	/// Keeps the operations attempted for a proposed block until the block is imported. Unlike
+	/// the block, they include the operations that failed.
+	fn note_proposed_operations(
+		&self,
+		shard: &ShardIdentifier,
+		block_number: u64,
+		trusted_operations: Vec<TrustedOperation<TCS, G>>,
+	) -> Result<()>;
+
+	/// Operations noted for a proposed block, if this validateer proposed it.
+	fn take_proposed_operations(
+		&self,
+		shard: &ShardIdentifier,
+		block_number: u64,
+	) -> Result<Option<Vec<TrustedOperation<TCS, G>>>>;
+
+	/// Records an imported block that was executed on `state_before_execution`. The record is
+	/// kept in memory until the next [`flush`](Self::flush).
+	fn record(
+		&self,
+		block: ImportedBlock<PH, TCS, G>,
+		state_before_execution: SgxExternalities,
+	) -> Result<()>;
+
+	/// Writes the pending records to disk.
+	fn flush(&self) -> Result<()>;
+
+	fn load(
+		&self,
+		shard: &ShardIdentifier,
+		block_number: u64,
+	) -> Result<BlockReplayRecord<PH, TCS, G>>;
+
+	/// Reconstructs the state the block was executed on, from the last checkpoint on.
+	fn load_state_before_execution(
+		&self,
+		record: &BlockReplayRecord<PH, TCS, G>,
+	) -> Result<SgxExternalities>;
+}
+
+/// Stores the replay records as sealed files, one file per block.
+pub struct BlockReplayStore<PH, TCS, G>
+where
+	TCS: PartialEq + Encode + Decode + Debug + Send + Sync,
+	G: PartialEq + Encode + Decode + Debug + Send + Sync,
+{
+	base_path: PathBuf,
+	proposed_operations: Mutex<BTreeMap<(ShardIdentifier, u64), Vec<TrustedOperation<TCS, G>>>>,
+	/// Block number and state of the last record of each shard, the base of the next record.
+	last_recorded_states: Mutex<BTreeMap<ShardIdentifier, (u64, SgxExternalitiesType)>>,
+	pending_records: Mutex<Vec<BlockReplayRecord<PH, TCS, G>>>,
+}
+
+impl<PH, TCS, G> BlockReplayStore<PH, TCS, G>
+where
+	TCS: PartialEq + Encode + Decode + Debug + Send + Sync,
+	G: PartialEq + Encode + Decode + Debug + Send + Sync,
+{
+	pub fn new(base_path: PathBuf) -> Self {
+		Self {
+			base_path: base_path.join(REPLAY_RECORDS_DIR),
+			proposed_operations: Default::default(),
+			last_recorded_states: Default::default(),
+			pending_records: Default::default(),
+		}
+	}
+
+	fn shard_path(&self, shard: &ShardIdentifier) -> PathBuf {
+		self.base_path.join(hex::encode(shard.encode()))
+	}
+
+	fn record_path(&self, shard: &ShardIdentifier, block_number: u64) -> PathBuf {
+		self.shard_path(shard).join(format!("{}.bin", block_number))
+	}
+}
+
+impl<PH, TCS, G> BlockReplayStore<PH, TCS, G>
+where
+	PH: Encode + Decode,
+	TCS: PartialEq + Encode + Decode + Debug + Send + Sync,
+	G: PartialEq + Encode + Decode + Debug + Send + Sync,
+{
+	fn write_record(&self, record: &BlockReplayRecord<PH, TCS, G>) -> Result<()> {
+		std::fs::create_dir_all(self.shard_path(&record.shard))
+			.map_err(|e| Error::Other(e.into()))?;
+		write_file(&record.encode(), self.record_path(&record.shard, record.block_number))
+			.map_err(|e| Error::Other(e.into()))?;
+
+		if let Some(outdated) = record.block_number.checked_sub(REPLAY_RECORDS_TO_KEEP) {
+			let outdated_path = self.record_path(&record.shard, outdated);
+			if outdated_path.exists() {
+				if let Err(e) = std::fs::remove_file(&outdated_path) {
+					warn!("Failed to remove outdated replay record {:?}: {:?}", outdated_path, e);
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+impl<PH, TCS, G> RecordBlockReplay<PH, TCS, G> for BlockReplayStore<PH, TCS, G>
+where
+	PH: Encode + Decode + Send + Sync,
+	TCS: PartialEq + Encode + Decode + Debug + Send + Sync,
+	G: PartialEq + Encode + Decode + Debug + Send + Sync,
+{
+	fn note_proposed_operations(
+		&self,
+		shard: &ShardIdentifier,
+		block_number: u64,
+		trusted_operations: Vec<TrustedOperation<TCS, G>>,
+	) -> Result<()> {
+		self.proposed_operations
+			.lock()
+			.map_err(|_| Error::ReplayRecorderLockPoisoning)?
+			.insert((*shard, block_number), trusted_operations);
+		Ok(())
+	}
+
+	fn take_proposed_operations(
+		&self,
+		shard: &ShardIdentifier,
+		block_number: u64,
+	) -> Result<Option<Vec<TrustedOperation<TCS, G>>>> {
+		let mut proposed_operations = self
+			.proposed_operations
+			.lock()
+			.map_err(|_| Error::ReplayRecorderLockPoisoning)?;
+		let operations = proposed_operations.remove(&(*shard, block_number));
+		// Proposed blocks that lost a fork are never imported.
+		proposed_operations.retain(|(s, n), _| s != shard || *n > block_number);
+		Ok(operations)
+	}
+
+	fn record(
+		&self,
+		block: ImportedBlock<PH, TCS, G>,
+		state_before_execution: SgxExternalities,
+	) -> Result<()> {
+		let state_hash_before_execution = state_before_execution.hash();
+		let state = state_before_execution.state;
+
+		let mut last_recorded_states = self
+			.last_recorded_states
+			.lock()
+			.map_err(|_| Error::ReplayRecorderLockPoisoning)?;
+		let state_before_execution = match last_recorded_states.get(&block.shard) {
+			Some((previous_block_number, previous_state))
+				if block.block_number % REPLAY_CHECKPOINT_INTERVAL != 0 =>
+				RecordedState::Changes {
+					previous_block_number: *previous_block_number,
+					changes: state_changes_between(previous_state, &state),
+				},
+			_ => RecordedState::Checkpoint(state.encode()),
+		};
+		last_recorded_states.insert(block.shard, (block.block_number, state));
+		drop(last_recorded_states);
+
+		self.pending_records
+			.lock()
+			.map_err(|_| Error::ReplayRecorderLockPoisoning)?
+			.push(BlockReplayRecord {
+				shard: block.shard,
+				block_number: block.block_number,
+				timestamp: block.timestamp,
+				parentchain_header: block.parentchain_header,
+				trusted_operations: block.trusted_operations,
+				missing_operations: block.missing_operations,
+				state_hash_before_execution,
+				state_before_execution,
+				state_hash_after_execution: block.state_hash_after_execution,
+				state_changes: block.state_changes,
+			});
+		Ok(())
+	}
+
+	fn flush(&self) -> Result<()> {
+		let pending_records = core::mem::take(
+			&mut *self.pending_records.lock().map_err(|_| Error::ReplayRecorderLockPoisoning)?,
+		);
+
+		for record in pending_records {
+			if let Err(e) = self.write_record(&record) {
+				warn!(
+					"Failed to write the replay record of sidechain block {}: {:?}",
+					record.block_number, e
+				);
+				// The following records must not depend on the missing one.
+				self.last_recorded_states
+					.lock()
+					.map_err(|_| Error::ReplayRecorderLockPoisoning)?
+					.remove(&record.shard);
+			}
+		}
+		Ok(())
+	}
+
+	fn load(
+		&self,
+		shard: &ShardIdentifier,
+		block_number: u64,
+	) -> Result<BlockReplayRecord<PH, TCS, G>> {
+		let path = self.record_path(shard, block_number);
+		if !path.exists() {
+			return Err(Error::ReplayRecordNotFound(block_number))
+		}
+		let encoded = read_file(path).map_err(|e| Error::Other(e.into()))?;
+		Ok(Decode::decode(&mut encoded.as_slice())?)
+	}
+
+	fn load_state_before_execution(
+		&self,
+		record: &BlockReplayRecord<PH, TCS, G>,
+	) -> Result<SgxExternalities> {
+		// Walk back to the last checkpoint, then apply the changes forward.
+		let mut changes_to_apply = Vec::new();
+		let mut current =
+			(record.state_hash_before_execution, record.state_before_execution.clone());
+		let mut state = loop {
+			match current.1 {
+				RecordedState::Checkpoint(encoded) => {
+					let state = SgxExternalities::new(Decode::decode(&mut encoded.as_slice())?);
+					break verified_state(state, current.0, record.block_number)?
+				},
+				RecordedState::Changes { previous_block_number, changes } => {
+					changes_to_apply.push((current.0, changes));
+					let previous = self.load(&record.shard, previous_block_number)?;
+					current =
+						(previous.state_hash_before_execution, previous.state_before_execution);
+				},
+			}
+		};
+
+		for (expected_hash, changes) in changes_to_apply.into_iter().rev() {
+			apply_state_changes(&mut state, changes);
+			state = verified_state(state, expected_hash, record.block_number)?;
+		}
+		Ok(state)
+	}
+}
+
+fn verified_state(
+	state: SgxExternalities,
+	expected_hash: H256,
+	block_number: u64,
+) -> Result<SgxExternalities> {
+	if state.hash() != expected_hash {
+		return Err(Error::ReplayStateMismatch(block_number))
+	}
+	Ok(state)
+}
+
+/// The changes that turn `previous` into `current`.
+pub fn state_changes_between(
+	previous: &SgxExternalitiesType,
+	current: &SgxExternalitiesType,
+) -> StateChanges {
+	let mut changes: StateChanges = current
+		.iter()
+		.filter(|(key, value)| previous.get(*key) != Some(*value))
+		.map(|(key, value)| (key.clone(), Some(value.clone())))
+		.collect();
+	changes.extend(
+		previous
+			.keys()
+			.filter(|key| !current.contains_key(*key))
+			.map(|key| (key.clone(), None)),
+	);
+	changes
+}
+
+fn apply_state_changes(state: &mut SgxExternalities, changes: StateChanges) {
+	for (key, value) in changes {
+		match value {
+			Some(value) => {
+				state.state.insert(key, value);
+			},
+			None => {
+				state.state.remove(&key);
+			},
+		}
+	}
+}
+
+/// Re-executes a recorded block on top of the state it was executed on and compares the
+/// resulting state changes with the recorded ones. The shard state is not modified.
+///
+/// `prepare_state_function` must do the same block preparation as the block production.
+pub fn replay_block<Executor, PH, TCS, G, F>(
+	executor: &Executor,
+	record: BlockReplayRecord<PH, TCS, G>,
+	state_before_execution: Executor::Externalities,
+	prepare_state_function: F,
+) -> Result<BlockReplayReport>
+where
+	Executor: StateUpdateProposer<TCS, G>,
+	Executor::Externalities: StateHash,
+	<Executor::Externalities as SgxExternalitiesTrait>::SgxExternalitiesDiffType:
+		Clone + IntoIterator<Item = (Vec<u8>, Option<Vec<u8>>)>,
+	PH: HeaderTrait<Hash = H256>,
+	TCS: PartialEq + Encode + Decode + Debug + Send + Sync,
+	G: PartialEq + Encode + Decode + Debug + Send + Sync,
+	F: FnOnce(Executor::Externalities) -> Executor::Externalities,
+{
+	if !record.missing_operations.is_empty() {
+		return Err(Error::ReplayOperationsMissing(
+			record.block_number,
+			record.missing_operations.len(),
+		))
+	}
+
+	let result = executor.propose_state_update(
+		&record.trusted_operations,
+		&record.parentchain_header,
+		&record.shard,
+		REPLAY_MAX_EXEC_DURATION,
+		|_| prepare_state_function(state_before_execution),
+	)?;
+
+	let replayed_changes: StateChanges =
+		result.state_after_execution.state_diff().clone().into_iter().collect();
+
+	Ok(BlockReplayReport {
+		shard: record.shard,
+		block_number: record.block_number,
+		recorded_state_hash: record.state_hash_after_execution,
+		replayed_state_hash: result.state_after_execution.hash(),
+		mismatches: diff_state_changes(&record.state_changes, &replayed_changes),
+	})
+}
+
+/// Returns all keys whose change differs between `recorded` and `replayed`, ordered by key.
+pub fn diff_state_changes(recorded: &StateChanges, replayed: &StateChanges) -> Vec<StateMismatch> {
+	let keys: BTreeSet<&Vec<u8>> = recorded.keys().chain(replayed.keys()).collect();
+
+	keys.into_iter()
+		.filter_map(|key| {
+			let recorded_value = recorded.get(key);
+			let replayed_value = replayed.get(key);
+			(recorded_value != replayed_value).then(|| StateMismatch {
+				key: key.clone(),
+				recorded: recorded_value.cloned(),
+				replayed: replayed_value.cloned(),
+			})
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itp_sgx_temp_dir::TempDir;
+	use itp_test::mock::stf_mock::{GetterMock, TrustedCallSignedMock};
+
+	type TestReplayStore = BlockReplayStore<(), TrustedCallSignedMock, GetterMock>;
+
+	fn changes(entries: &[(u8, Option<u8>)]) -> StateChanges {
+		entries.iter().map(|(k, v)| (vec![*k], v.map(|v| vec![v]))).collect()
+	}
+
+	fn state(entries: &[(u8, u8)]) -> SgxExternalities {
+		let map: BTreeMap<Vec<u8>, Vec<u8>> =
+			entries.iter().map(|(k, v)| (vec![*k], vec![*v])).collect();
+		SgxExternalities::new(map.into())
+	}
+
+	fn imported_block(block_number: u64) -> ImportedBlock<(), TrustedCallSignedMock, GetterMock> {
+		ImportedBlock {
+			shard: ShardIdentifier::default(),
+			block_number,
+			timestamp: block_number * 1000,
+			parentchain_header: (),
+			trusted_operations: Vec::new(),
+			missing_operations: Vec::new(),
+			state_hash_after_execution: H256::default(),
+			state_changes: StateChanges::new(),
+		}
+	}
+
+	#[test]
+	fn state_changes_turn_the_previous_into_the_current_state() {
+		let previous = state(&[(1, 10), (2, 20), (3, 30)]);
+		let current = state(&[(1, 10), (2, 21), (4, 40)]);
+
+		let changes = state_changes_between(&previous.state, &current.state);
+		assert_eq!(changes, self::changes(&[(2, Some(21)), (3, None), (4, Some(40))]));
+
+		let mut reconstructed = previous;
+		apply_state_changes(&mut reconstructed, changes);
+		assert_eq!(reconstructed.state, current.state);
+	}
+
+	#[test]
+	fn state_before_execution_is_reconstructed_from_the_last_checkpoint() {
+		let temp_dir = TempDir::with_prefix("state_is_reconstructed_from_checkpoint").unwrap();
+		let store = TestReplayStore::new(temp_dir.path().to_path_buf());
+		let states = [state(&[(1, 1)]), state(&[(1, 2), (2, 2)]), state(&[(2, 3)])];
+
+		for (i, state) in states.iter().enumerate() {
+			store.record(imported_block(i as u64 + 1), state.clone()).unwrap();
+		}
+		store.flush().unwrap();
+
+		let first = store.load(&ShardIdentifier::default(), 1).unwrap();
+		assert!(matches!(first.state_before_execution, RecordedState::Checkpoint(_)));
+		let last = store.load(&ShardIdentifier::default(), 3).unwrap();
+		assert_eq!(
+			last.state_before_execution,
+			RecordedState::Changes {
+				previous_block_number: 2,
+				changes: changes(&[(1, None), (2, Some(3))])
+			}
+		);
+		assert_eq!(last.timestamp, 3000);
+		assert_eq!(store.load_state_before_execution(&last).unwrap().state, states[2].state);
+	}
+
+	#[test]
+	fn records_are_only_written_on_flush() {
+		let temp_dir = TempDir::with_prefix("records_are_only_written_on_flush").unwrap();
+		let store = TestReplayStore::new(temp_dir.path().to_path_buf());
+
+		store.record(imported_block(1), state(&[(1, 1)])).unwrap();
+
+		assert!(matches!(
+			store.load(&ShardIdentifier::default(), 1),
+			Err(Error::ReplayRecordNotFound(1))
+		));
+		store.flush().unwrap();
+		assert!(store.load(&ShardIdentifier::default(), 1).is_ok());
+	}
+
+	#[test]
+	fn proposed_operations_of_blocks_that_lost_a_fork_are_discarded() {
+		let store = TestReplayStore::new(PathBuf::new());
+		let shard = ShardIdentifier::default();
+
+		store.note_proposed_operations(&shard, 4, Vec::new()).unwrap();
+		store.note_proposed_operations(&shard, 5, Vec::new()).unwrap();
+
+		assert_eq!(store.take_proposed_operations(&shard, 5).unwrap(), Some(Vec::new()));
+		assert_eq!(store.take_proposed_operations(&shard, 4).unwrap(), None);
+	}
+
+	#[test]
+	fn diff_of_equal_changes_is_empty() {
+		let recorded = changes(&[(1, Some(10)), (2, None)]);
+
+		assert!(diff_state_changes(&recorded, &recorded.clone()).is_empty());
+	}
+
+	#[test]
+	fn diff_reports_differing_missing_and_additional_keys() {
+		let recorded = changes(&[(1, Some(10)), (2, None), (3, Some(30))]);
+		let replayed = changes(&[(1, Some(11)), (2, None), (4, None)]);
+
+		let mismatches = diff_state_changes(&recorded, &replayed);
+
+		assert_eq!(
+			mismatches,
+			vec![
+				StateMismatch {
+					key: vec![1],
+					recorded: Some(Some(vec![10])),
+					replayed: Some(Some(vec![11]))
+				},
+				StateMismatch { key: vec![3], recorded: Some(Some(vec![30])), replayed: None },
+				StateMismatch { key: vec![4], recorded: None, replayed: Some(None) },
+			]
+		);
+	}
+
+	#[test]
+	fn report_is_deterministic_only_without_mismatches_and_equal_hashes() {
+		let mut report = BlockReplayReport {
+			shard: ShardIdentifier::default(),
+			block_number: 1,
+			recorded_state_hash: H256::repeat_byte(1),
+			replayed_state_hash: H256::repeat_byte(1),
+			mismatches: Vec::new(),
+		};
+		assert!(report.is_deterministic());
+
+		report.replayed_state_hash = H256::repeat_byte(2);
+		assert!(!report.is_deterministic());
+	}
+}
@@ -18,9 +18,10 @@
 use crate::error::{Error, Result};
 use codec::Decode;
 use core::marker::PhantomData;
+use itp_sgx_crypto::{rsa3072_pubkey_from_json, ShieldingCryptoEncrypt};
 use itp_sgx_externalities::SgxExternalities;
 use itp_stf_interface::StateGetterInterface;
-use itp_stf_primitives::traits::GetterAuthorization;
+use itp_stf_primitives::traits::{GetterAuthorization, GetterExpiry, GetterResponseEncryption};
 use log::*;
 use std::vec::Vec;
 
@@ -29,7 +30,8 @@ pub trait GetState<StateType, G: PartialEq + Decode + GetterAuthorization> {
 	/// Executes a trusted getter on a state and return its value, if available.
 	///
 	/// Also verifies the signature of the trusted getter and returns an error
-	/// if it's invalid.
+	/// if it's invalid or if the getter has expired. If the getter names a key
+	/// to encrypt the response to, the value is only ever returned encrypted.
 	fn get_state(getter: G, state: &mut StateType) -> Result<Option<Vec<u8>>>;
 }
 
@@ -40,15 +42,31 @@ pub struct StfStateGetter<Stf> {
 impl<Stf, G> GetState<SgxExternalities, G> for StfStateGetter<Stf>
 where
 	Stf: StateGetterInterface<G, SgxExternalities>,
-	G: PartialEq + Decode + GetterAuthorization,
+	G: PartialEq + Decode + GetterAuthorization + GetterExpiry + GetterResponseEncryption,
 {
 	fn get_state(getter: G, state: &mut SgxExternalities) -> Result<Option<Vec<u8>>> {
 		if !getter.is_authorized() {
 			error!("getter authorization failed");
 			return Err(Error::GetterIsNotAuthorized)
 		}
+		if getter.is_expired(Stf::state_timestamp(state)) {
+			warn!("getter has expired, it may be replayed");
+			return Err(Error::GetterHasExpired)
+		}
+		// Parse the key before executing the getter, so that we never compute a value we can't
+		// hand out encrypted.
+		let response_key = getter
+			.response_key()
+			.map(|key| rsa3072_pubkey_from_json(key).map_err(Error::InvalidResponseKey))
+			.transpose()?;
+
 		debug!("getter authorized. calling into STF to get state");
-		Ok(Stf::execute_getter(state, getter))
+		let value = Stf::execute_getter(state, getter);
+
+		match (value, response_key) {
+			(Some(value), Some(key)) => Ok(Some(key.encrypt(&value)?)),
+			(value, _) => Ok(value),
+		}
 	}
 }
 
@@ -58,28 +76,60 @@ mod tests {
 	use core::assert_matches::assert_matches;
 
 	use itp_test::mock::stf_mock::{
-		GetterMock, StfMock, TrustedGetterMock, TrustedGetterSignedMock,
+		GetterMock, StfMock, TrustedGetterMock, TrustedGetterSignedMock, MOCK_STATE_TIMESTAMP,
 	};
 
 	type TestStateGetter = StfStateGetter<StfMock>;
 
+	fn trusted_getter(valid_until: u64, signature: bool) -> GetterMock {
+		GetterMock::trusted(TrustedGetterSignedMock {
+			getter: TrustedGetterMock::some_value,
+			valid_until,
+			signature,
+		})
+	}
+
 	#[test]
 	fn upon_false_signature_get_stf_state_errs() {
-		let getter =
-			TrustedGetterSignedMock { getter: TrustedGetterMock::some_value, signature: false };
+		let getter = trusted_getter(MOCK_STATE_TIMESTAMP, false);
 		let mut state = SgxExternalities::default();
 
 		assert_matches!(
-			TestStateGetter::get_state(GetterMock::trusted(getter), &mut state),
+			TestStateGetter::get_state(getter, &mut state),
 			Err(Error::GetterIsNotAuthorized)
 		);
 	}
 
 	#[test]
 	fn state_getter_is_executed_if_signature_is_correct() {
-		let getter =
-			TrustedGetterSignedMock { getter: TrustedGetterMock::some_value, signature: true };
+		let getter = trusted_getter(MOCK_STATE_TIMESTAMP, true);
+		let mut state = SgxExternalities::default();
+		assert!(TestStateGetter::get_state(getter, &mut state).is_ok());
+	}
+
+	#[test]
+	fn getter_with_invalid_response_key_errs_instead_of_returning_plaintext() {
+		let getter = GetterMock::trusted(TrustedGetterSignedMock {
+			getter: TrustedGetterMock::encrypted_value(b"invalid".to_vec()),
+			valid_until: MOCK_STATE_TIMESTAMP,
+			signature: true,
+		});
 		let mut state = SgxExternalities::default();
-		assert!(TestStateGetter::get_state(GetterMock::trusted(getter), &mut state).is_ok());
+
+		assert_matches!(
+			TestStateGetter::get_state(getter, &mut state),
+			Err(Error::InvalidResponseKey(_))
+		);
+	}
+
+	#[test]
+	fn expired_getter_is_rejected() {
+		let getter = trusted_getter(MOCK_STATE_TIMESTAMP - 1, true);
+		let mut state = SgxExternalities::default();
+
+		assert_matches!(
+			TestStateGetter::get_state(getter, &mut state),
+			Err(Error::GetterHasExpired)
+		);
 	}
 }
@@ -20,6 +20,7 @@ use codec::{Decode, Encode};
 use core::fmt::Debug;
 use itp_sgx_externalities::SgxExternalitiesTrait;
 use itp_stf_primitives::{
+	cross_shard::{CrossShardMessage, CrossShardProof, MessageNonce},
 	traits::TrustedCallSigning,
 	types::{AccountId, ShardIdentifier, TrustedOperation},
 };
@@ -42,6 +43,9 @@ where
 {
 	fn get_enclave_account(&self) -> Result<AccountId>;
 
+	/// MRENCLAVE of this enclave, which the signatures of the calls commit to.
+	fn get_mrenclave(&self) -> Result<[u8; 32]>;
+
 	fn sign_call_with_self<TC: Encode + Debug + TrustedCallSigning<TCS>>(
 		&self,
 		trusted_call: &TC,
@@ -78,6 +82,32 @@ where
 		F: FnOnce(Self::Externalities) -> Self::Externalities;
 }
 
+/// Executes the periodic tasks of the enclave that are due in a sidechain slot.
+pub trait ExecutePeriodicTasks {
+	fn on_slot(&self, slot: u64, shards: &[ShardIdentifier]) -> Result<()>;
+}
+
+/// Delivers the cross-shard messages emitted on a shard to their destination shards.
+pub trait RouteCrossShardMessages: Send + Sync {
+	/// Delivers the messages in order, stops at the first one that can't be delivered.
+	fn route(&self, source: &ShardIdentifier, messages: &[CrossShardMessage]) -> Result<()>;
+}
+
+/// Relays cross-shard messages between workers, see [itp_stf_primitives::cross_shard].
+pub trait RelayCrossShardMessages: Send + Sync {
+	/// Proof of a message sent by one of our shards, against the commitment it is part of.
+	fn message_proof(
+		&self,
+		source: &ShardIdentifier,
+		destination: &ShardIdentifier,
+		nonce: MessageNonce,
+	) -> Result<CrossShardProof>;
+
+	/// Verifies the proof of a message to one of our shards against the anchored commitment of
+	/// its source shard and submits the delivery of the message.
+	fn submit_message_proof(&self, proof: &CrossShardProof) -> Result<()>;
+}
+
 /// Updates the STF state for a specific header.
 ///
 /// Cannot be implemented for a generic header currently, because the runtime expects a ParentchainHeader.
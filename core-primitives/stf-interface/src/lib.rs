@@ -27,10 +27,18 @@ use codec::{Decode, Encode};
 use core::fmt::Debug;
 use itp_node_api_metadata::NodeMetadataTrait;
 use itp_node_api_metadata_provider::AccessNodeMetadata;
-use itp_stf_primitives::traits::TrustedCallVerification;
+use itp_stf_primitives::{
+	cross_shard::{
+		AnchoredCommitment, CommitmentIndex, CrossShardMessage, CrossShardProof, MessageNonce,
+	},
+	error::StfResult,
+	traits::TrustedCallVerification,
+	types::ShardIdentifier,
+};
 use itp_types::{
 	parentchain::{AccountId, ParentchainId},
-	OpaqueCall,
+	shard_lifecycle::ShardGenesisConfig,
+	Balance, OpaqueCall,
 };
 
 #[cfg(feature = "mocks")]
@@ -41,15 +49,75 @@ pub mod system_pallet;
 
 pub const SHARD_VAULT_KEY: &str = "ShardVaultPubKey";
 
+/// Storage key of the `ShardGenesisConfig` the shard has been created with.
+pub const SHARD_GENESIS_CONFIG_KEY: &str = "ShardGenesisConfig";
+
+/// Storage key of the STF version that last wrote the shard state.
+pub const STF_VERSION_KEY: &str = "StfVersion";
+
+/// Version of the state semantics of an STF.
+pub type StfVersion = u32;
+
+/// Migrates a shard state from `from_version` to `from_version + 1`.
+pub struct StateMigration<State> {
+	pub from_version: StfVersion,
+	pub migrate: fn(&mut State),
+}
+
 /// Interface to initialize a new state.
 pub trait InitState<State, AccountId> {
 	/// Initialize a new state for a given enclave account.
 	fn init_state(enclave_account: AccountId) -> State;
 }
 
+/// Interface to set up the initial state of a new shard from its genesis config.
+pub trait InitShardGenesis<State> {
+	type Error;
+
+	/// Applies the genesis config to a newly initialized state and stores it in the state,
+	/// under the [`SHARD_GENESIS_CONFIG_KEY`].
+	fn apply_shard_genesis(
+		state: &mut State,
+		genesis_config: &ShardGenesisConfig,
+	) -> Result<(), Self::Error>;
+}
+
 /// Interface to query shard vault account for shard
 pub trait ShardVaultQuery<S> {
 	fn get_vault(state: &mut S) -> Option<AccountId>;
+
+	/// Number of unshielding requests waiting to be paid out from the vault.
+	fn get_pending_unshield_payouts(state: &mut S) -> u32;
+
+	/// Payouts that have been sent from the vault, but whose transfers haven't been confirmed.
+	fn get_unconfirmed_payouts(state: &mut S) -> Vec<(AccountId, Balance)>;
+
+	/// Payout batch that has been (re-)sent in the current block, as the account nonce of the
+	/// payout signer and the call to be signed with it.
+	fn get_payout_batch_to_send(state: &mut S) -> Option<(u32, OpaqueCall)>;
+
+	/// Funds to be paid out from the vault to their owners when the shard is retired. They are
+	/// burnt in the state, so they can't be paid out twice.
+	fn take_retirement_payouts(state: &mut S) -> StfResult<Vec<(AccountId, Balance)>>;
+}
+
+/// Interface to the version of an STF and the registry of its state migrations.
+pub trait StfVersioning<State> {
+	/// Version of the STF compiled into this enclave.
+	///
+	/// Must be increased whenever the state semantics change, together with
+	/// a migration from the previous version in `state_migrations`.
+	fn stf_version() -> StfVersion;
+
+	/// Version of the STF that last wrote the state.
+	///
+	/// States written before the STF was versioned have version 0.
+	fn get_state_stf_version(state: &mut State) -> StfVersion;
+
+	fn set_state_stf_version(state: &mut State, version: StfVersion);
+
+	/// All migrations, one for every version before `stf_version`.
+	fn state_migrations() -> Vec<StateMigration<State>>;
 }
 
 /// Interface for all functions calls necessary to update an already
@@ -70,18 +138,75 @@ where
 	type Error;
 
 	/// Execute a call on a specific state. Callbacks are added as an `OpaqueCall`.
+	///
+	/// The signature of `call` has been verified by the caller, `mrenclave` and `shard` are needed
+	/// to verify the signatures of the calls it carries.
 	fn execute_call(
 		state: &mut State,
 		call: TCS,
+		mrenclave: &[u8; 32],
+		shard: &ShardIdentifier,
 		calls: &mut Vec<OpaqueCall>,
 		node_metadata_repo: Arc<NodeMetadataRepository>,
 	) -> Result<(), Self::Error>;
 }
 
+/// Interface to the scheduler of the STF, which stores trusted calls that are executed at a
+/// later point in time.
+pub trait ScheduledCallsInterface<State, NodeMetadataRepository> {
+	/// Executes the scheduled calls that are due within the given state, on behalf of the
+	/// accounts that scheduled them. Callbacks are added as an `OpaqueCall`.
+	///
+	/// Returns the number of executed calls, failed ones included.
+	fn execute_due_scheduled_calls(
+		state: &mut State,
+		mrenclave: &[u8; 32],
+		shard: &ShardIdentifier,
+		calls: &mut Vec<OpaqueCall>,
+		node_metadata_repo: Arc<NodeMetadataRepository>,
+	) -> usize;
+}
+
+/// Interface to the cross-shard messaging of the STF, see [itp_stf_primitives::cross_shard].
+pub trait CrossShardMessagingInterface<State> {
+	type Call;
+
+	/// Removes and returns the messages emitted by the calls executed on the source shard since
+	/// the last call.
+	fn take_outgoing_messages(state: &mut State) -> Vec<CrossShardMessage>;
+
+	/// Returns the call that delivers `message` from the `source` shard on its destination.
+	///
+	/// The returned call has the enclave account as sender and needs to be signed by the enclave.
+	fn delivery_call(
+		enclave_account: AccountId,
+		source: ShardIdentifier,
+		message: CrossShardMessage,
+	) -> Self::Call;
+
+	/// Proof of a message sent by the shard of `state`, `None` if it hasn't been committed yet.
+	fn message_proof(
+		state: &mut State,
+		destination: &ShardIdentifier,
+		nonce: MessageNonce,
+	) -> Option<CrossShardProof>;
+
+	/// A commitment of the `source` shard, if it has been anchored in the shard of `state`.
+	fn anchored_commitment(
+		state: &mut State,
+		source: &ShardIdentifier,
+		index: CommitmentIndex,
+	) -> Option<AnchoredCommitment>;
+}
+
 /// Interface to execute state reading getters on a state.
 pub trait StateGetterInterface<G, S> {
 	/// Execute a getter on a specific state.
 	fn execute_getter(state: &mut S, getter: G) -> Option<Vec<u8>>;
+
+	/// Timestamp (unix millis) of the sidechain block that produced the state, the expiry of
+	/// signed getters is checked against it.
+	fn state_timestamp(state: &mut S) -> u64;
 }
 
 /// Trait used to abstract the call execution.
@@ -95,6 +220,8 @@ where
 	/// Execute a call. Callbacks are added as an `OpaqueCall`.
 	fn execute(
 		self,
+		mrenclave: &[u8; 32],
+		shard: &ShardIdentifier,
 		calls: &mut Vec<OpaqueCall>,
 		node_metadata_repo: Arc<NodeMetadataRepository>,
 	) -> Result<(), Self::Error>;
@@ -19,16 +19,25 @@
 
 extern crate alloc;
 use crate::{
-	system_pallet::SystemPalletAccountInterface, ExecuteCall, ExecuteGetter, InitState,
-	StateCallInterface, StateGetterInterface, UpdateState,
+	system_pallet::SystemPalletAccountInterface, CrossShardMessagingInterface, ExecuteCall,
+	ExecuteGetter, InitShardGenesis, InitState, ScheduledCallsInterface, StateCallInterface,
+	StateGetterInterface, StateMigration, StfVersion, StfVersioning, UpdateState,
 };
 use alloc::{string::String, sync::Arc, vec::Vec};
 use codec::{Decode, Encode};
 use core::{fmt::Debug, marker::PhantomData};
 use itp_node_api_metadata::metadata_mocks::NodeMetadataMock;
 use itp_node_api_metadata_provider::NodeMetadataRepository;
-use itp_stf_primitives::traits::TrustedCallVerification;
-use itp_types::{parentchain::ParentchainId, AccountId, Index, OpaqueCall};
+use itp_stf_primitives::{
+	cross_shard::{
+		AnchoredCommitment, CommitmentIndex, CrossShardMessage, CrossShardProof, MessageNonce,
+	},
+	traits::TrustedCallVerification,
+	types::ShardIdentifier,
+};
+use itp_types::{
+	parentchain::ParentchainId, shard_lifecycle::ShardGenesisConfig, AccountId, Index, OpaqueCall,
+};
 
 #[derive(Default)]
 pub struct StateInterfaceMock<State, StateDiff> {
@@ -43,6 +52,17 @@ impl<State, StateDiff, AccountId> InitState<State, AccountId>
 	}
 }
 
+impl<State, StateDiff> InitShardGenesis<State> for StateInterfaceMock<State, StateDiff> {
+	type Error = String;
+
+	fn apply_shard_genesis(
+		_state: &mut State,
+		_genesis_config: &ShardGenesisConfig,
+	) -> Result<(), Self::Error> {
+		unimplemented!()
+	}
+}
+
 impl<State, StateDiff> UpdateState<State, StateDiff> for StateInterfaceMock<State, StateDiff> {
 	fn apply_state_diff(_state: &mut State, _state_diff: StateDiff) {
 		unimplemented!()
@@ -63,6 +83,8 @@ where
 	fn execute_call(
 		_state: &mut State,
 		_call: TCS,
+		_mrenclave: &[u8; 32],
+		_shard: &ShardIdentifier,
 		_calls: &mut Vec<OpaqueCall>,
 		_node_metadata_repo: Arc<NodeMetadataRepository<NodeMetadataMock>>,
 	) -> Result<(), Self::Error> {
@@ -76,6 +98,73 @@ impl<Getter, State, StateDiff> StateGetterInterface<Getter, State>
 	fn execute_getter(_state: &mut State, _getter: Getter) -> Option<Vec<u8>> {
 		None
 	}
+
+	fn state_timestamp(_state: &mut State) -> u64 {
+		0
+	}
+}
+
+impl<State, StateDiff, NodeMetadataRepository>
+	ScheduledCallsInterface<State, NodeMetadataRepository> for StateInterfaceMock<State, StateDiff>
+{
+	fn execute_due_scheduled_calls(
+		_state: &mut State,
+		_mrenclave: &[u8; 32],
+		_shard: &ShardIdentifier,
+		_calls: &mut Vec<OpaqueCall>,
+		_node_metadata_repo: Arc<NodeMetadataRepository>,
+	) -> usize {
+		0
+	}
+}
+
+impl<State, StateDiff> CrossShardMessagingInterface<State>
+	for StateInterfaceMock<State, StateDiff>
+{
+	type Call = ();
+
+	fn take_outgoing_messages(_state: &mut State) -> Vec<CrossShardMessage> {
+		Vec::new()
+	}
+
+	fn delivery_call(
+		_enclave_account: AccountId,
+		_source: ShardIdentifier,
+		_message: CrossShardMessage,
+	) {
+	}
+
+	fn message_proof(
+		_state: &mut State,
+		_destination: &ShardIdentifier,
+		_nonce: MessageNonce,
+	) -> Option<CrossShardProof> {
+		None
+	}
+
+	fn anchored_commitment(
+		_state: &mut State,
+		_source: &ShardIdentifier,
+		_index: CommitmentIndex,
+	) -> Option<AnchoredCommitment> {
+		None
+	}
+}
+
+impl<State, StateDiff> StfVersioning<State> for StateInterfaceMock<State, StateDiff> {
+	fn stf_version() -> StfVersion {
+		0
+	}
+
+	fn get_state_stf_version(_state: &mut State) -> StfVersion {
+		0
+	}
+
+	fn set_state_stf_version(_state: &mut State, _version: StfVersion) {}
+
+	fn state_migrations() -> Vec<StateMigration<State>> {
+		Vec::new()
+	}
 }
 
 impl<State, StateDiff> SystemPalletAccountInterface<State, AccountId>
@@ -99,6 +188,8 @@ impl ExecuteCall<NodeMetadataRepository<NodeMetadataMock>> for CallExecutorMock
 
 	fn execute(
 		self,
+		_mrenclave: &[u8; 32],
+		_shard: &ShardIdentifier,
 		_calls: &mut Vec<OpaqueCall>,
 		_node_metadata_repo: Arc<NodeMetadataRepository<NodeMetadataMock>>,
 	) -> Result<(), Self::Error> {
@@ -0,0 +1,79 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Export of the state associated with an account.
+//!
+//! The owner of an account can request all of its state with a signed getter, which carries a
+//! public key of the owner. The pallets of the STF contribute their entries through the
+//! [AccountStorage] registry, and the state getter encrypts the export to the given key before
+//! it leaves the enclave, see [crate::traits::GetterResponseEncryption]. Hence only the owner
+//! can decrypt it, even if the response is relayed by an untrusted party.
+
+use crate::types::AccountId;
+use alloc::{string::String, vec::Vec};
+use codec::{Decode, Encode};
+
+/// RSA-3072 public key the export is encrypted to, JSON encoded like the shielding key served
+/// by `author_getShieldingKey`.
+pub type ExportKey = Vec<u8>;
+
+/// A storage item of a pallet, as far as it concerns the exported account.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct AccountDataEntry {
+	/// Name of the storage item.
+	pub name: String,
+	/// SCALE encoded value. Items that are shared with other accounts only contain the parts of
+	/// the exported account.
+	pub value: Vec<u8>,
+}
+
+impl AccountDataEntry {
+	pub fn new<V: Encode>(name: &str, value: &V) -> Self {
+		AccountDataEntry { name: name.into(), value: value.encode() }
+	}
+}
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct PalletAccountData {
+	pub pallet: String,
+	pub entries: Vec<AccountDataEntry>,
+}
+
+/// All state associated with an account, by pallet.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct AccountDataExport {
+	pub account: AccountId,
+	pub pallets: Vec<PalletAccountData>,
+}
+
+/// Implemented by the pallets of the STF that keep state associated with accounts, such that
+/// the state can be included in an [AccountDataExport].
+pub trait AccountStorage {
+	/// Name of the pallet in the export.
+	const PALLET: &'static str;
+
+	/// Entries of the pallet that concern `who`, empty if there are none.
+	fn account_entries(who: &AccountId) -> Vec<AccountDataEntry>;
+
+	/// Entries of the pallet, `None` if there are none.
+	fn export(who: &AccountId) -> Option<PalletAccountData> {
+		let entries = Self::account_entries(who);
+		if entries.is_empty() {
+			return None
+		}
+		Some(PalletAccountData { pallet: Self::PALLET.into(), entries })
+	}
+}
@@ -0,0 +1,190 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Messages between shards.
+//!
+//! A trusted call executed on the source shard emits a [CrossShardMessage], which the executor
+//! collects and the cross-shard router delivers to the destination shard with an
+//! enclave-signed call. Both shards track the messages in their state, under the
+//! [CROSS_SHARD_STORAGE_PREFIX]: the source shard numbers the messages per destination and keeps
+//! them in its outbox, the destination shard only accepts the next expected nonce per source.
+//! Hence every message is applied exactly once and in order.
+//!
+//! Shards hosted by other workers can't be reached by the router. Instead, the source shard
+//! periodically commits to the messages sent since its previous [OutboxCommitment] with a
+//! Merkle root, which is kept in its state and published on the parentchain. The destination
+//! worker anchors the published commitments in its shard state, and a relayer submits each
+//! message with a [CrossShardProof] against such an anchored commitment.
+
+use crate::{
+	merkle::{leaf_hash, root_from_proof},
+	types::{AccountId, ShardIdentifier},
+};
+use codec::{Decode, Encode};
+use itp_sgx_runtime_primitives::types::Balance;
+use sp_core::H256;
+use sp_std::vec::Vec;
+
+pub const CROSS_SHARD_STORAGE_PREFIX: &str = "CrossShard";
+/// Storage map of the nonce of the next message to a destination shard (`Blake2_128Concat`).
+pub const OUTBOX_NONCE_KEY: &str = "OutboxNonce";
+/// Storage double map of the sent messages, by destination shard and nonce (both
+/// `Blake2_128Concat`).
+pub const OUTBOX_KEY: &str = "Outbox";
+/// Storage map of the nonce of the next message expected from a source shard
+/// (`Blake2_128Concat`).
+pub const INBOX_NONCE_KEY: &str = "InboxNonce";
+/// Storage value of the messages emitted within the current batch, drained by the executor.
+pub const OUTGOING_KEY: &str = "Outgoing";
+/// Storage value of the `(destination, nonce)` of the messages sent since the last commitment.
+pub const UNCOMMITTED_KEY: &str = "Uncommitted";
+/// Storage value of the index of the next outbox commitment.
+pub const COMMITMENT_INDEX_KEY: &str = "CommitmentIndex";
+/// Storage map of the outbox commitments, by index (`Blake2_128Concat`).
+pub const COMMITMENT_KEY: &str = "Commitment";
+/// Storage map of the `(destination, nonce)` of the committed messages, in leaf order, by
+/// commitment index (`Blake2_128Concat`).
+pub const COMMITTED_MESSAGES_KEY: &str = "CommittedMessages";
+/// Storage double map of the commitment index of a sent message, by destination shard and nonce
+/// (both `Blake2_128Concat`).
+pub const MESSAGE_COMMITMENT_KEY: &str = "MessageCommitment";
+/// Storage double map of the commitments of other shards that were published on the
+/// parentchain, by source shard and commitment index (both `Blake2_128Concat`).
+pub const ANCHORED_COMMITMENT_KEY: &str = "AnchoredCommitment";
+
+/// Sequence number of the messages from one shard to another, starting at 0.
+pub type MessageNonce = u64;
+
+/// Effect of a message on the destination shard.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub enum CrossShardPayload {
+	/// Funds that were burnt on the source shard, to be minted to `to` on the destination shard.
+	Transfer { to: AccountId, amount: Balance },
+}
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct CrossShardMessage {
+	pub destination: ShardIdentifier,
+	pub nonce: MessageNonce,
+	/// Account on the source shard that sent the message.
+	pub sender: AccountId,
+	pub payload: CrossShardPayload,
+}
+
+/// Sequence number of the outbox commitments of a shard, starting at 0.
+pub type CommitmentIndex = u64;
+
+/// Commitment of a shard to the messages it sent since its previous commitment.
+///
+/// The commitment is published on the parentchain with `EnclaveBridge::publish_hash`: the root
+/// is the published hash, the shard the topic and the encoded commitment the data.
+#[derive(Encode, Decode, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutboxCommitment {
+	pub shard: ShardIdentifier,
+	pub index: CommitmentIndex,
+	/// Merkle root over the [message_leaf]s of the committed messages.
+	pub root: H256,
+	pub message_count: u32,
+}
+
+/// A commitment of another shard, as published on the parentchain.
+#[derive(Encode, Decode, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AnchoredCommitment {
+	pub commitment: OutboxCommitment,
+	/// MRENCLAVE of the enclave that published the commitment.
+	pub enclave_fingerprint: H256,
+}
+
+/// Leaf of a message in the Merkle tree of an [OutboxCommitment].
+pub fn message_leaf(message: &CrossShardMessage) -> H256 {
+	leaf_hash(message)
+}
+
+/// Proof that a message is part of an [OutboxCommitment], submitted by a relayer to the worker
+/// of the destination shard.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct CrossShardProof {
+	pub commitment: OutboxCommitment,
+	pub message: CrossShardMessage,
+	/// Position of the message among the committed messages.
+	pub leaf_index: u32,
+	/// Merkle path from the message to the root of the commitment.
+	pub proof: Vec<H256>,
+}
+
+impl CrossShardProof {
+	/// Source shard of the message.
+	pub fn source(&self) -> ShardIdentifier {
+		self.commitment.shard
+	}
+
+	/// Checks the Merkle path of the message against the root of the commitment. Whether the
+	/// commitment itself has been published is up to the caller.
+	pub fn verify(&self) -> bool {
+		root_from_proof(
+			message_leaf(&self.message),
+			self.leaf_index as usize,
+			self.commitment.message_count as usize,
+			&self.proof,
+		) == Some(self.commitment.root)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::merkle::{merkle_proof, merkle_root};
+
+	fn message(nonce: MessageNonce) -> CrossShardMessage {
+		CrossShardMessage {
+			destination: ShardIdentifier::repeat_byte(2),
+			nonce,
+			sender: AccountId::new([1u8; 32]),
+			payload: CrossShardPayload::Transfer { to: AccountId::new([3u8; 32]), amount: 10 },
+		}
+	}
+
+	fn proof_of(messages: &[CrossShardMessage], index: usize) -> CrossShardProof {
+		let leaves: Vec<H256> = messages.iter().map(message_leaf).collect();
+		CrossShardProof {
+			commitment: OutboxCommitment {
+				shard: ShardIdentifier::repeat_byte(1),
+				index: 0,
+				root: merkle_root(&leaves),
+				message_count: leaves.len() as u32,
+			},
+			message: messages[index].clone(),
+			leaf_index: index as u32,
+			proof: merkle_proof(&leaves, index).unwrap(),
+		}
+	}
+
+	#[test]
+	fn proof_of_committed_message_verifies() {
+		let messages: Vec<_> = (0..3).map(message).collect();
+		assert!(proof_of(&messages, 1).verify());
+	}
+
+	#[test]
+	fn proof_with_altered_message_does_not_verify() {
+		let messages: Vec<_> = (0..3).map(message).collect();
+		let mut proof = proof_of(&messages, 1);
+		proof.message.payload =
+			CrossShardPayload::Transfer { to: AccountId::new([3u8; 32]), amount: 1_000 };
+
+		assert!(!proof.verify());
+	}
+}
@@ -14,7 +14,11 @@
 	limitations under the License.
 
 */
-use crate::types::{AccountId, Nonce};
+use crate::{
+	cross_shard::MessageNonce,
+	fees::FeeBalance,
+	types::{AccountId, Nonce},
+};
 use derive_more::Display;
 
 use alloc::string::String;
@@ -33,6 +37,18 @@ pub enum StfError {
 	MissingFunds,
 	#[display(fmt = "Invalid Nonce {:?} != {:?}", _0, _1)]
 	InvalidNonce(Nonce, Nonce),
+	#[display(fmt = "Account {:?} is not permitted to submit calls to this shard", _0)]
+	ShardAccessDenied(AccountId),
+	#[display(fmt = "Genesis config is for STF version {}, but the STF has version {}", _0, _1)]
+	IncompatibleStfVersion(u32, u32),
+	#[display(fmt = "Session key {:?} of {:?} doesn't permit the call or has expired", _0, _1)]
+	SessionKeyNotPermitted(AccountId, AccountId),
+	#[display(fmt = "Account {:?} can't pay the fee of {}", _0, _1)]
+	CannotPayFee(AccountId, FeeBalance),
+	#[display(fmt = "Cross-shard message with nonce {} received, but expected {}", _0, _1)]
+	UnexpectedMessageNonce(MessageNonce, MessageNonce),
+	#[display(fmt = "Invalid signature of the call of {:?}", _0)]
+	InvalidSignature(AccountId),
 	StorageHashMismatch,
 	InvalidStorageDiff,
 	InvalidMetadata,
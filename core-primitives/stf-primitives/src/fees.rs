@@ -0,0 +1,112 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Execution fees of trusted calls.
+//!
+//! The fee of a call is proportional to its encoded length and its weight, scaled by the fee
+//! multiplier of the shard. Both the [FeeConfig] and the multiplier are stored in the shard
+//! state under the [FEES_STORAGE_PREFIX] and managed by root. Shards without a fee config
+//! execute calls for free.
+
+use crate::types::AccountId;
+use codec::{Decode, Encode};
+use core::time::Duration;
+use sp_runtime::{traits::One, FixedPointNumber, FixedU128};
+
+pub const FEES_STORAGE_PREFIX: &str = "Fees";
+/// Storage value of the [FeeConfig].
+pub const FEE_CONFIG_KEY: &str = "Config";
+/// Storage value of the [FeeMultiplier].
+pub const FEE_MULTIPLIER_KEY: &str = "Multiplier";
+
+pub type FeeBalance = u128;
+
+/// Factor all fees are scaled with, e.g. raised by root while the shard is under load.
+pub type FeeMultiplier = FixedU128;
+
+/// Where the fees go.
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FeeDestination {
+	/// Fees are burnt, reducing the total issuance of the shard.
+	#[default]
+	Burn,
+	/// Fees are paid to the treasury account of the shard.
+	Treasury(AccountId),
+}
+
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+pub struct FeeConfig {
+	/// Fee every call pays, covers the signature and nonce checks.
+	pub base_fee: FeeBalance,
+	/// Fee per byte of the encoded call.
+	pub fee_per_byte: FeeBalance,
+	/// Fee per microsecond of the call's weight.
+	pub fee_per_micros: FeeBalance,
+	pub destination: FeeDestination,
+}
+
+impl FeeConfig {
+	/// Fee of a call with the given encoded length and weight.
+	pub fn fee(&self, length: usize, weight: Duration, multiplier: FeeMultiplier) -> FeeBalance {
+		let unscaled = self
+			.base_fee
+			.saturating_add(self.fee_per_byte.saturating_mul(length as FeeBalance))
+			.saturating_add(self.fee_per_micros.saturating_mul(weight.as_micros()));
+		multiplier.saturating_mul_int(unscaled)
+	}
+}
+
+pub fn default_fee_multiplier() -> FeeMultiplier {
+	FeeMultiplier::one()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn fee_config() -> FeeConfig {
+		FeeConfig { base_fee: 1_000, fee_per_byte: 10, fee_per_micros: 2, ..Default::default() }
+	}
+
+	#[test]
+	fn fee_is_proportional_to_length_and_weight() {
+		let one = default_fee_multiplier();
+
+		assert_eq!(fee_config().fee(0, Duration::ZERO, one), 1_000);
+		assert_eq!(fee_config().fee(100, Duration::from_micros(300), one), 1_000 + 1_000 + 600);
+	}
+
+	#[test]
+	fn fee_is_scaled_by_multiplier() {
+		let fee =
+			fee_config().fee(100, Duration::ZERO, FeeMultiplier::saturating_from_rational(3, 2));
+
+		assert_eq!(fee, 3_000);
+	}
+
+	#[test]
+	fn default_config_is_free() {
+		assert_eq!(
+			FeeConfig::default().fee(
+				1_000,
+				Duration::from_secs(1),
+				FeeMultiplier::saturating_from_integer(10)
+			),
+			0
+		);
+	}
+}
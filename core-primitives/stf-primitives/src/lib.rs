@@ -17,6 +17,14 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 extern crate alloc;
 
+pub mod account_export;
+pub mod cross_shard;
 pub mod error;
+pub mod fees;
+pub mod merkle;
+pub mod pagination;
+pub mod reserves;
+pub mod session_keys;
+pub mod shard_acl;
 pub mod traits;
 pub mod types;
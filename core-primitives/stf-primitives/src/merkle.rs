@@ -0,0 +1,140 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Binary Merkle tree over blake2 hashes.
+//!
+//! Leaves and inner nodes are hashed with distinct prefixes, such that an inner node can't be
+//! passed off as a leaf. A node without a sibling is promoted to the next level unchanged,
+//! hence the verifier needs to know the number of leaves.
+
+use codec::Encode;
+use sp_core::{blake2_256, H256};
+use sp_std::vec::Vec;
+
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+/// Hash of a leaf of the tree.
+pub fn leaf_hash<T: Encode>(leaf: &T) -> H256 {
+	blake2_256(&(LEAF_PREFIX, leaf).encode()).into()
+}
+
+fn node_hash(left: &H256, right: &H256) -> H256 {
+	blake2_256(&(NODE_PREFIX, left, right).encode()).into()
+}
+
+fn next_level(level: &[H256]) -> Vec<H256> {
+	level
+		.chunks(2)
+		.map(|pair| pair.get(1).map_or(pair[0], |right| node_hash(&pair[0], right)))
+		.collect()
+}
+
+/// Root of the tree over the leaf hashes, zero if there are none.
+pub fn merkle_root(leaves: &[H256]) -> H256 {
+	let mut level = leaves.to_vec();
+	while level.len() > 1 {
+		level = next_level(&level);
+	}
+	level.first().copied().unwrap_or_default()
+}
+
+/// Sibling hashes on the path from the leaf at `index` to the root, bottom up.
+pub fn merkle_proof(leaves: &[H256], mut index: usize) -> Option<Vec<H256>> {
+	if index >= leaves.len() {
+		return None
+	}
+	let mut proof = Vec::new();
+	let mut level = leaves.to_vec();
+	while level.len() > 1 {
+		if let Some(sibling) = level.get(index ^ 1) {
+			proof.push(*sibling);
+		}
+		level = next_level(&level);
+		index /= 2;
+	}
+	Some(proof)
+}
+
+/// Root of a tree with `leaf_count` leaves that contains `leaf` at `index`, according to the
+/// `proof`. `None` if the proof doesn't fit the shape of the tree.
+pub fn root_from_proof(
+	leaf: H256,
+	mut index: usize,
+	mut leaf_count: usize,
+	proof: &[H256],
+) -> Option<H256> {
+	if index >= leaf_count {
+		return None
+	}
+	let mut hash = leaf;
+	let mut siblings = proof.iter();
+	while leaf_count > 1 {
+		if index ^ 1 < leaf_count {
+			let sibling = siblings.next()?;
+			hash =
+				if index % 2 == 0 { node_hash(&hash, sibling) } else { node_hash(sibling, &hash) };
+		}
+		index /= 2;
+		leaf_count = (leaf_count + 1) / 2;
+	}
+	if siblings.next().is_some() {
+		return None
+	}
+	Some(hash)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn leaves(count: u32) -> Vec<H256> {
+		(0..count).map(|i| leaf_hash(&i)).collect()
+	}
+
+	#[test]
+	fn proofs_of_all_leaves_verify() {
+		for count in 1..=9 {
+			let leaves = leaves(count);
+			let root = merkle_root(&leaves);
+			for (index, leaf) in leaves.iter().enumerate() {
+				let proof = merkle_proof(&leaves, index).unwrap();
+				assert_eq!(root_from_proof(*leaf, index, leaves.len(), &proof), Some(root));
+			}
+		}
+	}
+
+	#[test]
+	fn proof_does_not_verify_for_other_leaf_or_position() {
+		let leaves = leaves(5);
+		let root = merkle_root(&leaves);
+		let proof = merkle_proof(&leaves, 2).unwrap();
+
+		assert_ne!(root_from_proof(leaves[3], 2, 5, &proof), Some(root));
+		assert_ne!(root_from_proof(leaves[2], 3, 5, &proof), Some(root));
+		assert_eq!(root_from_proof(leaves[2], 2, 3, &proof), None);
+		assert_eq!(root_from_proof(leaves[2], 5, 5, &proof), None);
+	}
+
+	#[test]
+	fn single_leaf_is_its_own_root() {
+		let leaves = leaves(1);
+		assert_eq!(merkle_root(&leaves), leaves[0]);
+		assert_eq!(merkle_proof(&leaves, 0), Some(Vec::new()));
+		assert_eq!(merkle_root(&[]), H256::zero());
+	}
+}
@@ -0,0 +1,123 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Cursor based pagination of getter results.
+//!
+//! A getter that returns a potentially large list takes a [PageRequest] and returns a [Page]. The
+//! cursor is the position of the first item in the full list, the [Page::next] cursor has to be
+//! passed with the next request to continue. Pages are limited to [MAX_PAGE_LIMIT] items,
+//! regardless of the requested limit.
+
+use codec::{Decode, Encode};
+use sp_std::vec::Vec;
+
+/// Position of an item in the full result list.
+pub type PageCursor = u32;
+
+/// Upper bound on the number of items returned in a single page.
+pub const MAX_PAGE_LIMIT: u32 = 256;
+
+#[derive(Encode, Decode, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PageRequest {
+	/// Cursor returned with the previous page, `None` to start at the beginning.
+	pub cursor: Option<PageCursor>,
+	/// Maximum number of items to return, capped at [MAX_PAGE_LIMIT]. 0 means [MAX_PAGE_LIMIT].
+	pub limit: u32,
+}
+
+impl PageRequest {
+	pub fn new(cursor: Option<PageCursor>, limit: u32) -> Self {
+		PageRequest { cursor, limit }
+	}
+
+	pub fn effective_limit(&self) -> u32 {
+		match self.limit {
+			0 => MAX_PAGE_LIMIT,
+			limit => limit.min(MAX_PAGE_LIMIT),
+		}
+	}
+}
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct Page<T> {
+	/// Continuation token, `None` if this is the last page.
+	pub next: Option<PageCursor>,
+	pub items: Vec<T>,
+}
+
+impl<T> Default for Page<T> {
+	fn default() -> Self {
+		Page { next: None, items: Vec::new() }
+	}
+}
+
+/// Returns the page of `items` described by `request`.
+pub fn paginate<T>(items: Vec<T>, request: &PageRequest) -> Page<T> {
+	let start = request.cursor.unwrap_or_default() as usize;
+	if start >= items.len() {
+		return Page::default()
+	}
+	let end = start.saturating_add(request.effective_limit() as usize).min(items.len());
+	let next = (end < items.len()).then_some(end as PageCursor);
+	let items = items.into_iter().skip(start).take(end - start).collect();
+	Page { next, items }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_std::vec;
+
+	#[test]
+	fn walking_all_pages_returns_every_item_once() {
+		let items: Vec<u32> = (0..10).collect();
+		let mut request = PageRequest::new(None, 4);
+		let mut collected = vec![];
+		let mut pages = 0;
+
+		loop {
+			let page = paginate(items.clone(), &request);
+			collected.extend(page.items);
+			pages += 1;
+			match page.next {
+				Some(cursor) => request.cursor = Some(cursor),
+				None => break,
+			}
+		}
+
+		assert_eq!(collected, items);
+		assert_eq!(pages, 3);
+	}
+
+	#[test]
+	fn limit_is_capped_and_zero_means_maximum() {
+		let items: Vec<u32> = (0..MAX_PAGE_LIMIT + 1).collect();
+
+		let page = paginate(items.clone(), &PageRequest::new(None, u32::MAX));
+		assert_eq!(page.items.len(), MAX_PAGE_LIMIT as usize);
+		assert_eq!(page.next, Some(MAX_PAGE_LIMIT));
+
+		let page = paginate(items, &PageRequest::new(None, 0));
+		assert_eq!(page.items.len(), MAX_PAGE_LIMIT as usize);
+	}
+
+	#[test]
+	fn cursor_past_the_end_returns_empty_last_page() {
+		let page = paginate(vec![1u8, 2, 3], &PageRequest::new(Some(7), 2));
+		assert_eq!(page, Page::default());
+	}
+}
@@ -0,0 +1,255 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Proof of reserves of the shard vault.
+//!
+//! The funds shielded into a shard are held by its vault account on the parentchain. The
+//! enclave periodically publishes a [ReservesReport] with `EnclaveBridge::publish_hash`, which
+//! commits to the liabilities of the shard, i.e. the shielded balances of all accounts, with a
+//! Merkle-sum tree. Every node of the tree carries the sum of the balances below it, hence the
+//! root carries the total, and no balance can be left out or counted negatively without
+//! breaking the path of some account.
+//!
+//! The report names the parentchain block the shard state was synced to, so anyone can check
+//! that the vault held at least the total liabilities at that block. Only the root is kept,
+//! the tree is built leaf by leaf with a [SumTreeBuilder]. The leaves are salted with enclave
+//! randomness, such that the root doesn't reveal any balances.
+
+use crate::types::{AccountId, ShardIdentifier};
+use codec::{Decode, Encode};
+use itp_sgx_runtime_primitives::types::{Balance, BlockNumber, SidechainBlockNumber};
+use sp_core::{blake2_256, H256};
+use sp_std::vec::Vec;
+
+pub const RESERVES_STORAGE_PREFIX: &str = "Reserves";
+/// Storage value of the latest [ReservesReport].
+pub const LATEST_REPORT_KEY: &str = "LatestReport";
+/// Storage value of the report being built, while not all accounts have been visited.
+pub const PENDING_REPORT_KEY: &str = "PendingReport";
+/// Storage value of the key of the next account to visit for the pending report.
+pub const CURSOR_KEY: &str = "Cursor";
+
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+/// Sequence number of the reserves reports of a shard, starting at 0.
+pub type ReportIndex = u64;
+
+/// Node of a Merkle-sum tree.
+#[derive(Encode, Decode, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SumNode {
+	pub hash: H256,
+	/// Sum of the balances of the leaves below the node.
+	pub sum: Balance,
+}
+
+/// Balance of an account that is owed to its owner, as committed to in a report.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct Liability {
+	pub account: AccountId,
+	pub balance: Balance,
+	pub salt: H256,
+}
+
+impl Liability {
+	pub fn leaf(&self) -> SumNode {
+		SumNode {
+			hash: blake2_256(&(LEAF_PREFIX, &self.account, self.balance, self.salt).encode())
+				.into(),
+			sum: self.balance,
+		}
+	}
+}
+
+/// Parent of two nodes, `None` if the sum overflows.
+fn parent(left: &SumNode, right: &SumNode) -> Option<SumNode> {
+	Some(SumNode {
+		hash: blake2_256(&(NODE_PREFIX, left, right).encode()).into(),
+		sum: left.sum.checked_add(right.sum)?,
+	})
+}
+
+/// Builds the root of a Merkle-sum tree leaf by leaf, keeping only one node per level.
+///
+/// Pairs of leaves are combined level by level, a node without a sibling is promoted unchanged,
+/// like in [crate::merkle].
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SumTreeBuilder {
+	/// Left node waiting for its sibling, per level from the leaves up.
+	pending: Vec<Option<SumNode>>,
+	leaf_count: u32,
+}
+
+impl SumTreeBuilder {
+	/// Appends the leaf, `None` if the sum overflows.
+	pub fn push(&mut self, leaf: SumNode) -> Option<()> {
+		let mut node = leaf;
+		for slot in self.pending.iter_mut() {
+			match slot.take() {
+				Some(left) => node = parent(&left, &node)?,
+				None => {
+					*slot = Some(node);
+					self.leaf_count += 1;
+					return Some(())
+				},
+			}
+		}
+		self.pending.push(Some(node));
+		self.leaf_count += 1;
+		Some(())
+	}
+
+	pub fn leaf_count(&self) -> u32 {
+		self.leaf_count
+	}
+
+	/// Root of the tree over all leaves, the default node if there are none. `None` if the sum
+	/// overflows.
+	pub fn root(&self) -> Option<SumNode> {
+		let mut root: Option<SumNode> = None;
+		for left in self.pending.iter().flatten() {
+			root = Some(match root {
+				Some(right) => parent(left, &right)?,
+				None => *left,
+			});
+		}
+		Some(root.unwrap_or_default())
+	}
+}
+
+/// Statement of the liabilities of a shard, published on the parentchain by the enclave.
+///
+/// The hash of the encoded report is the published hash, the shard the topic and the encoded
+/// report the data.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct ReservesReport {
+	pub shard: ShardIdentifier,
+	pub index: ReportIndex,
+	pub sidechain_block_number: SidechainBlockNumber,
+	/// Latest parentchain block the shard state has been synced to. The balance of the vault at
+	/// this block has to cover the total liabilities.
+	pub parentchain_block_number: BlockNumber,
+	pub parentchain_block_hash: H256,
+	pub vault: AccountId,
+	/// Root of the Merkle-sum tree over the [Liability] leaves, its sum is the total liabilities.
+	pub liabilities: SumNode,
+	pub account_count: u32,
+}
+
+impl ReservesReport {
+	/// Hash under which the report is published.
+	pub fn hash(&self) -> H256 {
+		blake2_256(&self.encode()).into()
+	}
+
+	pub fn total_liabilities(&self) -> Balance {
+		self.liabilities.sum
+	}
+
+	/// Whether the `vault_balance` at the reported parentchain block covers the liabilities.
+	pub fn is_backed_by(&self, vault_balance: Balance) -> bool {
+		vault_balance >= self.total_liabilities()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn leaves(count: u8) -> Vec<SumNode> {
+		(0..count)
+			.map(|i| {
+				Liability {
+					account: AccountId::new([i; 32]),
+					balance: 10 * i as Balance,
+					salt: H256::repeat_byte(i),
+				}
+				.leaf()
+			})
+			.collect()
+	}
+
+	fn build(leaves: &[SumNode]) -> Option<SumNode> {
+		let mut builder = SumTreeBuilder::default();
+		for leaf in leaves {
+			builder.push(*leaf)?;
+		}
+		builder.root()
+	}
+
+	/// Combines the nodes level by level, promoting the last one of an odd level.
+	fn reference_root(leaves: &[SumNode]) -> SumNode {
+		let mut level = leaves.to_vec();
+		while level.len() > 1 {
+			level = level
+				.chunks(2)
+				.map(|pair| match pair.get(1) {
+					Some(right) => parent(&pair[0], right).unwrap(),
+					None => pair[0],
+				})
+				.collect();
+		}
+		level.first().copied().unwrap_or_default()
+	}
+
+	#[test]
+	fn builder_root_matches_the_level_by_level_root() {
+		for count in 0..=9 {
+			let leaves = leaves(count);
+			let total: Balance = leaves.iter().map(|leaf| leaf.sum).sum();
+			let root = build(&leaves).unwrap();
+			assert_eq!(root, reference_root(&leaves));
+			assert_eq!(root.sum, total);
+		}
+	}
+
+	#[test]
+	fn root_changes_with_any_balance() {
+		let mut leaves = leaves(5);
+		let root = build(&leaves).unwrap();
+		leaves[3].sum -= 1;
+		assert_ne!(build(&leaves).unwrap().hash, root.hash);
+	}
+
+	#[test]
+	fn overflowing_sums_are_rejected() {
+		let leaves = [
+			SumNode { hash: H256::repeat_byte(1), sum: Balance::MAX },
+			SumNode { hash: H256::repeat_byte(2), sum: 1 },
+			SumNode { hash: H256::repeat_byte(3), sum: 1 },
+		];
+		assert_eq!(build(&leaves[..2]), None);
+		// The unpaired leaf is only added up for the root.
+		assert_eq!(build(&[leaves[1], leaves[2], leaves[0]]), None);
+	}
+
+	#[test]
+	fn report_is_backed_by_a_sufficient_vault_balance() {
+		let report = ReservesReport {
+			shard: ShardIdentifier::repeat_byte(1),
+			index: 0,
+			sidechain_block_number: 100,
+			parentchain_block_number: 50,
+			parentchain_block_hash: H256::repeat_byte(5),
+			vault: AccountId::new([9u8; 32]),
+			liabilities: build(&leaves(4)).unwrap(),
+			account_count: 4,
+		};
+		assert_eq!(report.total_liabilities(), 60);
+		assert!(report.is_backed_by(60));
+		assert!(!report.is_backed_by(59));
+	}
+}
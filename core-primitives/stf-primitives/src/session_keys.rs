@@ -0,0 +1,81 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Session keys, time-limited delegates that may sign trusted calls on behalf of an account.
+//!
+//! A session key is registered by its owner with the [SessionKey] scope and expiry, and stored
+//! in the shard state under the [SESSION_KEYS_STORAGE_PREFIX]. The delegate wraps the calls it
+//! submits for the owner in a `session_call`, which the STF only executes if the session key
+//! is still valid and its scope includes the wrapped call.
+
+use codec::{Decode, Encode};
+use sp_std::vec::Vec;
+
+pub const SESSION_KEYS_STORAGE_PREFIX: &str = "SessionKeys";
+/// Storage double map of (owner, delegate) to the [SessionKey] (`Blake2_128Concat`).
+pub const DELEGATES_KEY: &str = "Delegates";
+
+/// Maximum number of call names in the scope of a session key.
+pub const MAX_SCOPE_LEN: usize = 32;
+
+/// A delegate key registered by an account.
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SessionKey {
+	/// Sidechain timestamp (ms) after which the session key is no longer accepted.
+	pub valid_until: u64,
+	/// Names of the trusted call variants the delegate may submit, see `TrustedCall::name`.
+	pub scope: Vec<Vec<u8>>,
+}
+
+impl SessionKey {
+	pub fn new(valid_until: u64, scope: Vec<Vec<u8>>) -> Self {
+		SessionKey { valid_until, scope }
+	}
+
+	pub fn is_expired(&self, now: u64) -> bool {
+		now > self.valid_until
+	}
+
+	/// Whether the delegate may submit the call with the given name at `now`.
+	pub fn permits(&self, call_name: &str, now: u64) -> bool {
+		!self.is_expired(now)
+			&& self.scope.iter().any(|name| name.as_slice() == call_name.as_bytes())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_std::vec;
+
+	fn session_key() -> SessionKey {
+		SessionKey::new(1_000, vec![b"balance_transfer".to_vec()])
+	}
+
+	#[test]
+	fn scoped_call_is_permitted_until_expiry() {
+		assert!(session_key().permits("balance_transfer", 999));
+		assert!(session_key().permits("balance_transfer", 1_000));
+		assert!(!session_key().permits("balance_transfer", 1_001));
+	}
+
+	#[test]
+	fn call_outside_of_scope_is_not_permitted() {
+		assert!(!session_key().permits("balance_unshield", 0));
+		assert!(!SessionKey::default().permits("noop", 0));
+	}
+}
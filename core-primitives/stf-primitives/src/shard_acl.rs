@@ -0,0 +1,112 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Access control of permissioned shards.
+//!
+//! The access policy and the members of a shard are stored in the shard state, under the
+//! [SHARD_ACL_STORAGE_PREFIX]. The rules are implemented by the STF only. The top pool author
+//! enforces them on submission of a trusted call through [ShardAccessControl], and the STF
+//! re-checks them at execution.
+//!
+//! A shard in maintenance mode (see [MAINTENANCE_MODE_KEY]) accepts no new trusted calls, except
+//! from root and the enclave signer. Calls that are already in the pool are still executed.
+
+use crate::types::AccountId;
+use codec::{Decode, Encode};
+use sp_std::vec::Vec;
+
+pub const SHARD_ACL_STORAGE_PREFIX: &str = "ShardAcl";
+/// Storage value of the [AccessPolicy].
+pub const ACCESS_POLICY_KEY: &str = "Policy";
+/// Storage map of the members of a shard to their [ShardRole]s (`Blake2_128Concat`).
+pub const MEMBERS_KEY: &str = "Members";
+/// Storage value whether the shard is in maintenance mode, `false` if not set.
+pub const MAINTENANCE_MODE_KEY: &str = "MaintenanceMode";
+
+/// Role that can be required to submit trusted calls to a shard.
+pub type ShardRole = u32;
+
+/// Who is allowed to submit trusted calls to a shard.
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+pub enum AccessPolicy {
+	/// Everyone, the default of a shard.
+	#[default]
+	Open,
+	/// Members of the shard only.
+	AllowList,
+	/// Members of the shard that have been granted the role.
+	RequireRole(ShardRole),
+}
+
+impl AccessPolicy {
+	/// Whether an account with the given member roles is permitted, `None` if it's no member.
+	pub fn permits(&self, member_roles: Option<&[ShardRole]>) -> bool {
+		match self {
+			AccessPolicy::Open => true,
+			AccessPolicy::AllowList => member_roles.is_some(),
+			AccessPolicy::RequireRole(role) =>
+				member_roles.map_or(false, |roles| roles.contains(role)),
+		}
+	}
+}
+
+/// Roles of a member of a shard, as stored in the [MEMBERS_KEY] map.
+pub type MemberRoles = Vec<ShardRole>;
+
+/// Why a trusted call is not accepted for a shard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubmissionDenied {
+	ShardInMaintenance,
+	AccessDenied,
+}
+
+/// Access control of the STF, evaluated on the plain storage of a shard state.
+pub trait ShardAccessControl {
+	/// Ensures the account may submit trusted calls to the shard whose state is read
+	/// with `storage`.
+	fn ensure_may_submit(
+		&self,
+		storage: &dyn Fn(&[u8]) -> Option<Vec<u8>>,
+		who: &AccountId,
+	) -> Result<(), SubmissionDenied>;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn open_policy_permits_everyone() {
+		assert!(AccessPolicy::Open.permits(None));
+		assert!(AccessPolicy::default().permits(None));
+	}
+
+	#[test]
+	fn allow_list_permits_members_only() {
+		assert!(AccessPolicy::AllowList.permits(Some(&[])));
+		assert!(!AccessPolicy::AllowList.permits(None));
+	}
+
+	#[test]
+	fn required_role_has_to_be_granted() {
+		let policy = AccessPolicy::RequireRole(2);
+
+		assert!(policy.permits(Some(&[1, 2])));
+		assert!(!policy.permits(Some(&[1])));
+		assert!(!policy.permits(None));
+	}
+}
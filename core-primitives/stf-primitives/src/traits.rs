@@ -14,10 +14,13 @@
 	limitations under the License.
 
 */
-use crate::types::{AccountId, KeyPair, ShardIdentifier};
-use alloc::vec::Vec;
+use crate::{
+	account_export::ExportKey,
+	types::{AccountId, KeyPair, ShardIdentifier, Signature},
+};
+use alloc::{boxed::Box, vec::Vec};
 use codec::{Decode, Encode};
-use core::fmt::Debug;
+use core::{fmt::Debug, future::Future, pin::Pin, time::Duration};
 use itp_sgx_runtime_primitives::types::Index;
 use sp_runtime::transaction_validity::{TransactionValidityError, ValidTransaction};
 /// checks authorization of stf getters
@@ -25,15 +28,75 @@ pub trait GetterAuthorization {
 	fn is_authorized(&self) -> bool;
 }
 
+/// Expiry of signed getters, so that an intercepted getter can't be replayed to obtain fresh
+/// state later on.
+pub trait GetterExpiry {
+	/// Whether the getter has expired at the given sidechain timestamp (unix millis).
+	fn is_expired(&self, now: u64) -> bool;
+}
+
+/// Getters whose value is returned encrypted to a key of the requester, instead of in the clear.
+pub trait GetterResponseEncryption {
+	/// Key the value has to be encrypted to, `None` if the value is returned in the clear.
+	fn response_key(&self) -> Option<&ExportKey>;
+}
+
 /// knows how to sign a trusted call input and provides a signed output
 pub trait TrustedCallSigning<TCS> {
+	/// The payload that has to be signed by the sender account.
+	fn signing_payload(
+		&self,
+		nonce: Index,
+		mrenclave: &[u8; 32],
+		shard: &ShardIdentifier,
+	) -> Vec<u8>;
+
+	/// Assembles the signed output from a signature over the `signing_payload`.
+	fn with_signature(&self, nonce: Index, signature: Signature) -> TCS;
+
 	fn sign(
 		&self,
 		pair: &KeyPair,
 		nonce: Index,
 		mrenclave: &[u8; 32],
 		shard: &ShardIdentifier,
-	) -> TCS;
+	) -> TCS {
+		let payload = self.signing_payload(nonce, mrenclave, shard);
+		self.with_signature(nonce, pair.sign(payload.as_slice()))
+	}
+}
+
+/// Signer that does not expose its secret key, e.g. a hardware wallet.
+///
+/// Creating the signature may involve I/O or a confirmation by the user,
+/// so it is returned asynchronously.
+pub trait ExternalSigner {
+	type Error;
+
+	/// The account that signatures of this signer can be verified against.
+	fn account_id(&self) -> Result<AccountId, Self::Error>;
+
+	fn sign<'a>(
+		&'a self,
+		payload: &'a [u8],
+	) -> Pin<Box<dyn Future<Output = Result<Signature, Self::Error>> + 'a>>;
+}
+
+/// Signs a trusted call with an [ExternalSigner].
+pub async fn sign_with_external_signer<TC, TCS, S>(
+	call: &TC,
+	signer: &S,
+	nonce: Index,
+	mrenclave: &[u8; 32],
+	shard: &ShardIdentifier,
+) -> Result<TCS, S::Error>
+where
+	TC: TrustedCallSigning<TCS>,
+	S: ExternalSigner,
+{
+	let payload = call.signing_payload(nonce, mrenclave, shard);
+	let signature = signer.sign(payload.as_slice()).await?;
+	Ok(call.with_signature(nonce, signature))
 }
 
 /// enables TrustedCallSigned verification
@@ -42,9 +105,26 @@ pub trait TrustedCallVerification {
 
 	fn nonce(&self) -> Index;
 
+	/// Nonces the call consumes, the one of the sender and e.g. the one of the user of a
+	/// sponsored call. Two calls consuming the same nonce exclude each other in the top pool.
+	fn nonces(&self) -> Vec<(AccountId, Index)> {
+		alloc::vec![(self.sender_account().clone(), self.nonce())]
+	}
+
+	/// Implementations may accept other signature schemes than the substrate ones as
+	/// alternative, e.g. signatures of ethereum wallets.
 	fn verify_signature(&self, mrenclave: &[u8; 32], shard: &ShardIdentifier) -> bool;
 }
 
+/// Execution time of a trusted call, as measured by the trusted call benchmarks.
+///
+/// Used to pack the sidechain blocks: a call is only executed if its weight fits into the
+/// remaining slot time.
+pub trait TrustedCallWeight {
+	/// Upper bound of the time it takes to execute the call, incl. the signature verification.
+	fn weight(&self) -> Duration;
+}
+
 /// validation for top pool
 pub trait PoolTransactionValidation {
 	fn validate(&self) -> Result<ValidTransaction, TransactionValidityError>;
@@ -66,6 +146,9 @@ where
 
 	fn get_default_shard(&self) -> ShardIdentifier;
 
+	/// All shards maintained by this worker.
+	fn list_handled_shards(&self) -> Vec<ShardIdentifier>;
+
 	fn sign_call_with_self<TC: Encode + Debug + TrustedCallSigning<TCS>>(
 		&self,
 		trusted_call: &TC,
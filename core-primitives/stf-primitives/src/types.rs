@@ -111,9 +111,8 @@ where
 	}
 
 	fn validate_trusted_call(trusted_call_signed: &TCS) -> ValidTransaction {
-		let from = trusted_call_signed.sender_account();
 		let requires = vec![];
-		let provides = vec![(from, trusted_call_signed.nonce()).encode()];
+		let provides = trusted_call_signed.nonces().iter().map(|nonce| nonce.encode()).collect();
 
 		ValidTransaction { priority: 1 << 20, requires, provides, longevity: 64, propagate: true }
 	}
@@ -36,6 +36,7 @@ pub mod file_io;
 pub mod handle_state;
 pub mod in_memory_state_file_io;
 pub mod query_shard_state;
+pub mod shard_genesis_builder;
 pub mod state_handler;
 pub mod state_initializer;
 mod state_snapshot_primitives;
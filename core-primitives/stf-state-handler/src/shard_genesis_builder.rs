@@ -0,0 +1,114 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Builds the initial state of a new shard from its genesis config, instead of the default state.
+
+use crate::{
+	error::{Error, Result},
+	state_initializer::InitializeState,
+};
+use core::{fmt::Debug, marker::PhantomData};
+use itp_stf_interface::InitShardGenesis;
+use itp_types::shard_lifecycle::ShardGenesisConfig;
+use std::{format, sync::Arc};
+
+/// Build the initial state of a shard from its genesis config.
+pub trait BuildShardGenesis {
+	type StateType;
+
+	fn build(&self, genesis_config: &ShardGenesisConfig) -> Result<Self::StateType>;
+}
+
+pub struct ShardGenesisBuilder<Stf, StateInitializer> {
+	state_initializer: Arc<StateInitializer>,
+	_phantom: PhantomData<Stf>,
+}
+
+impl<Stf, StateInitializer> ShardGenesisBuilder<Stf, StateInitializer>
+where
+	Stf: InitShardGenesis<StateInitializer::StateType>,
+	Stf::Error: Debug,
+	StateInitializer: InitializeState,
+{
+	pub fn new(state_initializer: Arc<StateInitializer>) -> Self {
+		Self { state_initializer, _phantom: Default::default() }
+	}
+}
+
+impl<Stf, StateInitializer> BuildShardGenesis for ShardGenesisBuilder<Stf, StateInitializer>
+where
+	Stf: InitShardGenesis<StateInitializer::StateType>,
+	Stf::Error: Debug,
+	StateInitializer: InitializeState,
+{
+	type StateType = StateInitializer::StateType;
+
+	fn build(&self, genesis_config: &ShardGenesisConfig) -> Result<Self::StateType> {
+		let mut state = self.state_initializer.initialize()?;
+		Stf::apply_shard_genesis(&mut state, genesis_config)
+			.map_err(|e| Error::Other(format!("Invalid shard genesis config: {:?}", e).into()))?;
+		Ok(state)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test::mocks::initialize_state_mock::InitializeStateMock;
+	use itp_types::AccountId;
+
+	type TestState = u64;
+
+	/// Adds the sum of the endowed balances to the state.
+	struct EndowingStfMock;
+
+	impl InitShardGenesis<TestState> for EndowingStfMock {
+		type Error = ();
+
+		fn apply_shard_genesis(
+			state: &mut TestState,
+			genesis_config: &ShardGenesisConfig,
+		) -> core::result::Result<(), Self::Error> {
+			if genesis_config.stf_version.is_some() {
+				return Err(())
+			}
+			*state += genesis_config.endowed_accounts.iter().map(|(_, b)| *b as u64).sum::<u64>();
+			Ok(())
+		}
+	}
+
+	fn builder() -> ShardGenesisBuilder<EndowingStfMock, InitializeStateMock<TestState>> {
+		ShardGenesisBuilder::new(Arc::new(InitializeStateMock::new(3)))
+	}
+
+	#[test]
+	fn genesis_is_applied_on_top_of_initialized_state() {
+		let genesis_config = ShardGenesisConfig {
+			endowed_accounts: vec![(AccountId::new([1u8; 32]), 5), (AccountId::new([2u8; 32]), 7)],
+			..Default::default()
+		};
+
+		assert_eq!(builder().build(&genesis_config).unwrap(), 15);
+	}
+
+	#[test]
+	fn rejected_genesis_config_returns_error() {
+		let genesis_config = ShardGenesisConfig { stf_version: Some(2), ..Default::default() };
+
+		assert!(builder().build(&genesis_config).is_err());
+	}
+}
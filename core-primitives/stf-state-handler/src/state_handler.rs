@@ -30,6 +30,7 @@ use crate::{
 };
 use core::fmt::Debug;
 use itp_hashing::Hash;
+use itp_memory_accounting::Subsystem;
 use itp_sgx_externalities::SgxExternalitiesTrait;
 use itp_stf_state_observer::traits::UpdateState;
 use itp_types::ShardIdentifier;
@@ -109,6 +110,7 @@ where
 		state_snapshot_repository: &Repository,
 	) -> Result<StatesMap<Repository::StateType, Repository::HashType>> {
 		let shards = state_snapshot_repository.list_shards()?;
+		let _scope = itp_memory_accounting::enter(Subsystem::StateCache);
 
 		let r = shards
 			.into_iter()
@@ -212,7 +214,11 @@ where
 		let state_hash = state.hash();
 		// We create a state copy here, in order to serve the state observer. This does not scale
 		// well and we will want a better solution in the future, maybe with #459.
-		state_lock.insert(*shard, (state.clone(), state_hash));
+		let cached_state = {
+			let _scope = itp_memory_accounting::enter(Subsystem::StateCache);
+			state.clone()
+		};
+		state_lock.insert(*shard, (cached_state, state_hash));
 		drop(state_lock); // Drop the write lock as early as possible.
 
 		self.update_state_snapshot(shard, &state, state_hash)?;
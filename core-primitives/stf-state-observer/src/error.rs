@@ -20,6 +20,7 @@ use crate::sgx_reexport_prelude::*;
 
 pub type Result<T> = core::result::Result<T, Error>;
 
+use itp_types::ShardIdentifier;
 use std::boxed::Box;
 
 /// State Observer Error.
@@ -27,6 +28,8 @@ use std::boxed::Box;
 pub enum Error {
 	#[error("Current state is empty (not set)")]
 	CurrentStateEmpty,
+	#[error("No state recorded for shard {0:?}")]
+	NoRecordedState(ShardIdentifier),
 	#[error("Could not acquire lock, lock is poisoned")]
 	LockPoisoning,
 	#[error(transparent)]
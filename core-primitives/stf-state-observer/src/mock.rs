@@ -25,10 +25,15 @@ use crate::{
 	error::{Error, Result},
 	traits::{ObserveState, UpdateState},
 };
+use codec::Decode;
 use core::fmt::Debug;
-use itp_types::ShardIdentifier;
+use itp_sgx_externalities::SgxExternalitiesTrait;
+use itp_types::{state_dump::StateDump, ShardIdentifier};
 use log::*;
-use std::vec::Vec;
+use std::{collections::BTreeMap, vec::Vec};
+
+#[cfg(feature = "std")]
+use std::{boxed::Box, fs, path::Path};
 
 /// Observe state mock.
 #[derive(Default)]
@@ -64,6 +69,66 @@ where
 	}
 }
 
+/// Observe state mock that serves states recorded with the `dump-state` command of the worker.
+///
+/// Each shard is served from its own dump, so tests can run against realistic
+/// production data instead of synthetic states.
+#[derive(Default)]
+pub struct RecordedStateObserver<StateType> {
+	states: RwLock<BTreeMap<ShardIdentifier, StateType>>,
+}
+
+impl<StateType> RecordedStateObserver<StateType>
+where
+	StateType: SgxExternalitiesTrait + Default,
+{
+	pub fn from_dump(dump: StateDump) -> Self {
+		let observer = Self { states: Default::default() };
+		observer.insert_dump(dump);
+		observer
+	}
+
+	/// Decodes a SCALE encoded [`StateDump`].
+	pub fn from_encoded_dump(mut encoded_dump: &[u8]) -> Result<Self> {
+		let dump = StateDump::decode(&mut encoded_dump).map_err(|e| Error::Other(e.into()))?;
+		Ok(Self::from_dump(dump))
+	}
+
+	/// Loads a state dump file, as written by the `dump-state` command of the worker.
+	#[cfg(feature = "std")]
+	pub fn from_dump_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+		let encoded_dump = fs::read(path).map_err(|e| Error::Other(Box::new(e)))?;
+		Self::from_encoded_dump(&encoded_dump)
+	}
+
+	/// Adds the state of another shard, replaces the state if the shard is already known.
+	pub fn insert_dump(&self, dump: StateDump) {
+		let mut state = StateType::default();
+		for (key, value) in dump.entries {
+			state.insert(key, value);
+		}
+		state.prune_state_diff();
+
+		self.states.write().unwrap().insert(dump.shard, state);
+	}
+}
+
+impl<StateType> ObserveState for RecordedStateObserver<StateType> {
+	type StateType = StateType;
+
+	fn observe_state<F, R>(&self, shard: &ShardIdentifier, observation_func: F) -> Result<R>
+	where
+		F: FnOnce(&mut Self::StateType) -> R,
+	{
+		let mut states_lock = self.states.write().map_err(|_| Error::LockPoisoning)?;
+
+		states_lock
+			.get_mut(shard)
+			.map(observation_func)
+			.ok_or(Error::NoRecordedState(*shard))
+	}
+}
+
 /// Update state mock.
 #[derive(Default)]
 pub struct UpdateStateMock<StateType> {
@@ -802,6 +802,24 @@ pub mod offchain {
 		warn!("offchain::sleep_until unimplemented");
 	}
 
+	/// Random seed drawn from the enclave's random number generator.
+	#[cfg(feature = "sgx")]
+	pub fn random_seed() -> [u8; 32] {
+		let mut seed = [0u8; 32];
+		let ret = unsafe { sgx_types::sgx_read_rand(seed.as_mut_ptr(), seed.len()) };
+		if ret != sgx_types::sgx_status_t::SGX_SUCCESS {
+			panic!("sgx_read_rand failed: {:?}", ret);
+		}
+		seed
+	}
+
+	/// Random seed drawn from the OS random number generator.
+	#[cfg(feature = "std")]
+	pub fn random_seed() -> [u8; 32] {
+		ed25519::Pair::generate().1
+	}
+
+	#[cfg(not(any(feature = "std", feature = "sgx")))]
 	pub fn random_seed() -> [u8; 32] {
 		warn!("offchain::random_seed unimplemented");
 		[0; 32]
@@ -0,0 +1,67 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Dead man's switch for the parentchain sync of the enclave.
+//!
+//! The enclave must not keep producing sidechain blocks on an outdated view of the parentchain,
+//! e.g. when the untrusted host withholds parentchain blocks. The slot worker reports the number
+//! of the latest imported and finalized parentchain block to the [`SyncWatchdog`] every slot.
+//! If that number did not advance for longer than the configured maximal stall, the shard is
+//! frozen: block production halts and the author rejects new trusted calls with
+//! [`Error::ParentchainSyncStalled`], while trusted getters are still served. The freeze is
+//! lifted as soon as a new parentchain block is imported.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(all(feature = "std", feature = "sgx"))]
+compile_error!("feature \"std\" and feature \"sgx\" cannot be enabled at the same time");
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+#[macro_use]
+extern crate sgx_tstd as std;
+
+// re-export module to properly feature gate sgx and regular std environment
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+pub mod sgx_reexport_prelude {
+	pub use thiserror_sgx as thiserror;
+}
+
+pub use error::{Error, Result};
+pub use watchdog::{SyncWatchdog, GLOBAL_SYNC_WATCHDOG};
+
+use core::time::Duration;
+
+pub mod error;
+pub mod watchdog;
+
+/// Milliseconds since the unix epoch.
+pub type Moment = u64;
+
+/// Reports the latest parentchain block number to the global watchdog, see
+/// [`SyncWatchdog::observe`].
+pub fn observe(block_number: u64, now: Moment) -> Result<()> {
+	GLOBAL_SYNC_WATCHDOG.observe(block_number, now)
+}
+
+/// Fails if the global watchdog froze the enclave, see [`SyncWatchdog::ensure_synced`].
+pub fn ensure_synced() -> Result<()> {
+	GLOBAL_SYNC_WATCHDOG.ensure_synced()
+}
+
+/// Sets the maximal stall of the global watchdog.
+pub fn set_max_stall(max_stall: Duration) {
+	GLOBAL_SYNC_WATCHDOG.set_max_stall(max_stall)
+}
@@ -0,0 +1,185 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Tracks the progress of the parentchain sync.
+
+use crate::{Error, Moment, Result};
+use core::{
+	sync::atomic::{AtomicBool, AtomicU64, Ordering},
+	time::Duration,
+};
+use log::*;
+
+/// Watchdog consulted by the slot worker and the author.
+pub static GLOBAL_SYNC_WATCHDOG: SyncWatchdog = SyncWatchdog::new();
+
+/// Value of the maximal stall if the watchdog is disabled.
+const DISABLED: u64 = 0;
+
+/// Value of the progress timestamp before the first observation.
+const NOT_OBSERVED: Moment = 0;
+
+/// Freezes the enclave if the parentchain block number did not advance for longer than the
+/// maximal stall.
+///
+/// Only uses atomics, so the author can query it without locking. Observations are expected to
+/// come from a single thread, i.e. the slot worker.
+pub struct SyncWatchdog {
+	max_stall_millis: AtomicU64,
+	last_block_number: AtomicU64,
+	last_progress: AtomicU64,
+	last_observation: AtomicU64,
+	frozen: AtomicBool,
+}
+
+impl SyncWatchdog {
+	/// Creates a disabled watchdog, see [`Self::set_max_stall`].
+	pub const fn new() -> Self {
+		SyncWatchdog {
+			max_stall_millis: AtomicU64::new(DISABLED),
+			last_block_number: AtomicU64::new(0),
+			last_progress: AtomicU64::new(NOT_OBSERVED),
+			last_observation: AtomicU64::new(NOT_OBSERVED),
+			frozen: AtomicBool::new(false),
+		}
+	}
+
+	/// Sets the duration without parentchain progress after which the enclave is frozen. A zero
+	/// duration disables the watchdog.
+	pub fn set_max_stall(&self, max_stall: Duration) {
+		let millis = u64::try_from(max_stall.as_millis()).unwrap_or(u64::MAX);
+		self.max_stall_millis.store(millis, Ordering::Relaxed)
+	}
+
+	/// Records the number of the latest imported parentchain block at `now`.
+	///
+	/// Fails if the block number did not advance for longer than the maximal stall. The first
+	/// observation after the start of the enclave counts as progress.
+	pub fn observe(&self, block_number: u64, now: Moment) -> Result<()> {
+		if self.last_progress.load(Ordering::Relaxed) == NOT_OBSERVED
+			|| block_number > self.last_block_number.load(Ordering::Relaxed)
+		{
+			self.last_block_number.store(block_number, Ordering::Relaxed);
+			self.last_progress.store(now, Ordering::Relaxed);
+		}
+		self.last_observation.store(now, Ordering::Relaxed);
+
+		let stalled_for_millis = self.stalled_for_millis();
+		let max_stall_millis = self.max_stall_millis.load(Ordering::Relaxed);
+		let frozen = max_stall_millis != DISABLED && stalled_for_millis > max_stall_millis;
+
+		let was_frozen = self.frozen.swap(frozen, Ordering::Relaxed);
+		if frozen && !was_frozen {
+			error!(
+				"No parentchain progress since block {} for {} ms, freezing block production and trusted call submission",
+				block_number, stalled_for_millis
+			);
+		} else if !frozen && was_frozen {
+			info!("Parentchain sync recovered at block {}, resuming", block_number);
+		}
+
+		self.ensure_synced()
+	}
+
+	/// Fails if the enclave is frozen because of a stalled parentchain sync.
+	pub fn ensure_synced(&self) -> Result<()> {
+		if self.is_frozen() {
+			return Err(Error::ParentchainSyncStalled {
+				block_number: self.last_block_number.load(Ordering::Relaxed),
+				stalled_for_millis: self.stalled_for_millis(),
+			})
+		}
+		Ok(())
+	}
+
+	pub fn is_frozen(&self) -> bool {
+		self.frozen.load(Ordering::Relaxed)
+	}
+
+	fn stalled_for_millis(&self) -> Moment {
+		self.last_observation
+			.load(Ordering::Relaxed)
+			.saturating_sub(self.last_progress.load(Ordering::Relaxed))
+	}
+}
+
+impl Default for SyncWatchdog {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const START: Moment = 1_700_000_000_000;
+
+	fn watchdog(max_stall_millis: u64) -> SyncWatchdog {
+		let watchdog = SyncWatchdog::new();
+		watchdog.set_max_stall(Duration::from_millis(max_stall_millis));
+		watchdog
+	}
+
+	#[test]
+	fn first_observation_does_not_freeze() {
+		let watchdog = watchdog(1_000);
+
+		assert!(watchdog.observe(5, START).is_ok());
+		assert!(watchdog.ensure_synced().is_ok());
+	}
+
+	#[test]
+	fn freezes_once_the_block_number_stalls_beyond_the_limit() {
+		let watchdog = watchdog(1_000);
+		watchdog.observe(5, START).unwrap();
+
+		assert!(watchdog.observe(5, START + 1_000).is_ok());
+		assert_eq!(
+			watchdog.observe(5, START + 1_001),
+			Err(Error::ParentchainSyncStalled { block_number: 5, stalled_for_millis: 1_001 })
+		);
+		assert!(watchdog.is_frozen());
+		assert!(watchdog.ensure_synced().is_err());
+	}
+
+	#[test]
+	fn resumes_once_the_block_number_advances() {
+		let watchdog = watchdog(1_000);
+		watchdog.observe(5, START).unwrap();
+		watchdog.observe(5, START + 2_000).unwrap_err();
+
+		assert!(watchdog.observe(6, START + 3_000).is_ok());
+		assert!(!watchdog.is_frozen());
+		assert!(watchdog.ensure_synced().is_ok());
+	}
+
+	#[test]
+	fn reverting_block_number_is_no_progress() {
+		let watchdog = watchdog(1_000);
+		watchdog.observe(5, START).unwrap();
+
+		assert!(watchdog.observe(4, START + 2_000).is_err());
+	}
+
+	#[test]
+	fn disabled_watchdog_never_freezes() {
+		let watchdog = SyncWatchdog::new();
+		watchdog.observe(5, START).unwrap();
+
+		assert!(watchdog.observe(5, START + 1_000_000).is_ok());
+	}
+}
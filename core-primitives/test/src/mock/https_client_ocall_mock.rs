@@ -0,0 +1,124 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+#[cfg(feature = "sgx")]
+use std::sync::SgxRwLock as RwLock;
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+use itp_ocall_api::{HttpsClientOCallApi, TcpConnectionId};
+use sgx_types::{sgx_status_t, SgxResult};
+use std::{
+	collections::BTreeMap,
+	string::{String, ToString},
+	sync::Arc,
+	vec::Vec,
+};
+
+#[derive(Default)]
+struct MockConnection {
+	sent: Vec<u8>,
+	to_receive: Vec<u8>,
+	closed: bool,
+}
+
+#[derive(Default)]
+struct MockState {
+	connected: Vec<(String, u16)>,
+	connections: BTreeMap<TcpConnectionId, MockConnection>,
+}
+
+/// HTTPS client o-call mock. Every connection receives the same bytes, the connections share
+/// the mock's state when it is cloned.
+#[derive(Clone, Default)]
+pub struct HttpsClientOCallMock {
+	response: Vec<u8>,
+	state: Arc<RwLock<MockState>>,
+}
+
+impl HttpsClientOCallMock {
+	pub fn new(response: Vec<u8>) -> Self {
+		HttpsClientOCallMock { response, state: Default::default() }
+	}
+
+	/// Host and port of every connection, in the order they were opened.
+	pub fn connected(&self) -> Vec<(String, u16)> {
+		self.state.read().unwrap().connected.clone()
+	}
+
+	pub fn sent(&self, connection: TcpConnectionId) -> Vec<u8> {
+		self.state
+			.read()
+			.unwrap()
+			.connections
+			.get(&connection)
+			.map(|c| c.sent.clone())
+			.unwrap_or_default()
+	}
+
+	pub fn is_closed(&self, connection: TcpConnectionId) -> bool {
+		self.state
+			.read()
+			.unwrap()
+			.connections
+			.get(&connection)
+			.map_or(false, |c| c.closed)
+	}
+}
+
+impl HttpsClientOCallApi for HttpsClientOCallMock {
+	fn tcp_connect(&self, host: &str, port: u16) -> SgxResult<TcpConnectionId> {
+		let mut state = self.state.write().unwrap();
+		let connection = state.connected.len() as TcpConnectionId;
+		state.connected.push((host.to_string(), port));
+		state.connections.insert(
+			connection,
+			MockConnection { to_receive: self.response.clone(), ..Default::default() },
+		);
+		Ok(connection)
+	}
+
+	fn tcp_send(&self, connection: TcpConnectionId, data: &[u8]) -> SgxResult<()> {
+		let mut state = self.state.write().unwrap();
+		let connection = state
+			.connections
+			.get_mut(&connection)
+			.ok_or(sgx_status_t::SGX_ERROR_UNEXPECTED)?;
+		connection.sent.extend_from_slice(data);
+		Ok(())
+	}
+
+	fn tcp_receive(&self, connection: TcpConnectionId, max_len: u32) -> SgxResult<Vec<u8>> {
+		let mut state = self.state.write().unwrap();
+		let connection = state
+			.connections
+			.get_mut(&connection)
+			.ok_or(sgx_status_t::SGX_ERROR_UNEXPECTED)?;
+		let len = connection.to_receive.len().min(max_len as usize);
+		Ok(connection.to_receive.drain(..len).collect())
+	}
+
+	fn tcp_close(&self, connection: TcpConnectionId) -> SgxResult<()> {
+		let mut state = self.state.write().unwrap();
+		let connection = state
+			.connections
+			.get_mut(&connection)
+			.ok_or(sgx_status_t::SGX_ERROR_UNEXPECTED)?;
+		connection.closed = true;
+		Ok(())
+	}
+}
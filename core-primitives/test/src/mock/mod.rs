@@ -16,6 +16,7 @@
 */
 
 pub mod handle_state_mock;
+pub mod https_client_ocall_mock;
 pub mod metrics_ocall_mock;
 pub mod onchain_mock;
 pub mod shielding_crypto_mock;
@@ -21,12 +21,13 @@ use core::fmt::Debug;
 use itp_enclave_bridge_storage::{EnclaveBridgeStorage, EnclaveBridgeStorageKeys};
 use itp_ocall_api::{
 	EnclaveAttestationOCallApi, EnclaveMetricsOCallApi, EnclaveOnChainOCallApi,
-	EnclaveSidechainOCallApi,
+	EnclaveSidechainOCallApi, EnclaveStateConfirmationOCallApi,
 };
 use itp_storage::Error::StorageValueUnavailable;
 use itp_types::{
-	parentchain::ParentchainId, storage::StorageEntryVerified, AccountId, BlockHash,
-	EnclaveFingerprint, ShardIdentifier, ShardSignerStatus, WorkerRequest, WorkerResponse,
+	parentchain::ParentchainId, state_confirmation::StateConfirmation,
+	storage::StorageEntryVerified, AccountId, BlockHash, EnclaveFingerprint, ShardIdentifier,
+	ShardSignerStatus, WorkerRequest, WorkerResponse,
 };
 use sgx_types::*;
 use sp_core::H256;
@@ -38,6 +39,7 @@ use std::collections::HashMap;
 pub struct OnchainMock {
 	inner: HashMap<Vec<u8>, Vec<u8>>,
 	mr_enclave: [u8; SGX_HASH_SIZE],
+	state_confirmations: Vec<StateConfirmation>,
 }
 
 impl OnchainMock {
@@ -76,6 +78,11 @@ impl OnchainMock {
 		self
 	}
 
+	pub fn with_state_confirmations(mut self, confirmations: Vec<StateConfirmation>) -> Self {
+		self.state_confirmations = confirmations;
+		self
+	}
+
 	pub fn insert_at_header<Header: HeaderTrait<Hash = H256>>(
 		&mut self,
 		header: &Header,
@@ -169,6 +176,16 @@ impl EnclaveSidechainOCallApi for OnchainMock {
 	}
 }
 
+impl EnclaveStateConfirmationOCallApi for OnchainMock {
+	fn request_state_confirmations(
+		&self,
+		_shard: ShardIdentifier,
+		_encrypted_request: Vec<u8>,
+	) -> SgxResult<Vec<StateConfirmation>> {
+		Ok(self.state_confirmations.clone())
+	}
+}
+
 impl EnclaveMetricsOCallApi for OnchainMock {
 	fn update_metric<Metric: Encode>(&self, _metric: Metric) -> SgxResult<()> {
 		Ok(())
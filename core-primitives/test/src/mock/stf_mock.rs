@@ -21,11 +21,18 @@ use itp_node_api::metadata::metadata_mocks::NodeMetadataMock;
 use itp_node_api_metadata_provider::NodeMetadataRepository;
 use itp_sgx_externalities::{SgxExternalities, SgxExternalitiesDiffType, SgxExternalitiesTrait};
 use itp_stf_interface::{
-	ExecuteCall, InitState, StateCallInterface, StateGetterInterface, UpdateState,
+	CrossShardMessagingInterface, ExecuteCall, InitState, ScheduledCallsInterface,
+	StateCallInterface, StateGetterInterface, StateMigration, StfVersion, StfVersioning,
+	UpdateState, STF_VERSION_KEY,
 };
 use itp_stf_primitives::{
+	account_export::ExportKey,
+	cross_shard::{
+		AnchoredCommitment, CommitmentIndex, CrossShardMessage, CrossShardProof, MessageNonce,
+	},
 	traits::{
-		GetterAuthorization, PoolTransactionValidation, TrustedCallSigning, TrustedCallVerification,
+		GetterAuthorization, GetterExpiry, GetterResponseEncryption, PoolTransactionValidation,
+		TrustedCallSigning, TrustedCallVerification, TrustedCallWeight,
 	},
 	types::{KeyPair, Nonce, TrustedOperation},
 };
@@ -67,23 +74,117 @@ impl StateCallInterface<TrustedCallSignedMock, SgxExternalities, NodeMetadataRep
 	fn execute_call(
 		state: &mut SgxExternalities,
 		call: TrustedCallSignedMock,
+		mrenclave: &[u8; 32],
+		shard: &ShardIdentifier,
 		calls: &mut Vec<OpaqueCall>,
 		node_metadata_repo: Arc<NodeMetadataRepositoryMock>,
 	) -> Result<(), Self::Error> {
-		state.execute_with(|| call.execute(calls, node_metadata_repo))
+		state.execute_with(|| call.execute(mrenclave, shard, calls, node_metadata_repo))
+	}
+}
+
+impl ScheduledCallsInterface<SgxExternalities, NodeMetadataRepositoryMock> for StfMock {
+	/// Takes the encoded callbacks stored with [scheduled_calls_mock_key], one per due call.
+	fn execute_due_scheduled_calls(
+		state: &mut SgxExternalities,
+		_mrenclave: &[u8; 32],
+		_shard: &ShardIdentifier,
+		calls: &mut Vec<OpaqueCall>,
+		_node_metadata_repo: Arc<NodeMetadataRepositoryMock>,
+	) -> usize {
+		let due: Vec<Vec<u8>> = state
+			.remove(&scheduled_calls_mock_key())
+			.and_then(|v| Decode::decode(&mut v.as_slice()).ok())
+			.unwrap_or_default();
+		let executed = due.len();
+		calls.extend(due.into_iter().map(OpaqueCall));
+		executed
+	}
+}
+
+/// Plain storage key of the due scheduled calls in the state of the [StfMock].
+pub fn scheduled_calls_mock_key() -> Vec<u8> {
+	b"ScheduledCalls".encode()
+}
+
+impl CrossShardMessagingInterface<SgxExternalities> for StfMock {
+	type Call = TrustedCallMock;
+
+	fn take_outgoing_messages(_state: &mut SgxExternalities) -> Vec<CrossShardMessage> {
+		Vec::new()
+	}
+
+	fn delivery_call(
+		enclave_account: AccountId,
+		_source: ShardIdentifier,
+		_message: CrossShardMessage,
+	) -> TrustedCallMock {
+		TrustedCallMock::noop(enclave_account)
+	}
+
+	fn message_proof(
+		_state: &mut SgxExternalities,
+		_destination: &ShardIdentifier,
+		_nonce: MessageNonce,
+	) -> Option<CrossShardProof> {
+		None
+	}
+
+	/// Reads the commitments stored with [anchored_commitment_mock_key].
+	fn anchored_commitment(
+		state: &mut SgxExternalities,
+		source: &ShardIdentifier,
+		index: CommitmentIndex,
+	) -> Option<AnchoredCommitment> {
+		state
+			.get(&anchored_commitment_mock_key(source, index))
+			.and_then(|v| AnchoredCommitment::decode(&mut v.as_slice()).ok())
 	}
 }
 
+/// Plain storage key of an anchored commitment in the state of the [StfMock].
+pub fn anchored_commitment_mock_key(source: &ShardIdentifier, index: CommitmentIndex) -> Vec<u8> {
+	(b"AnchoredCommitment", source, index).encode()
+}
+
 impl InitState<SgxExternalities, AccountId> for StfMock {
 	fn init_state(_enclave_account: AccountId) -> SgxExternalities {
 		SgxExternalities::new(Default::default())
 	}
 }
 
+impl StfVersioning<SgxExternalities> for StfMock {
+	fn stf_version() -> StfVersion {
+		0
+	}
+
+	fn get_state_stf_version(state: &mut SgxExternalities) -> StfVersion {
+		state
+			.get(STF_VERSION_KEY.as_bytes())
+			.and_then(|v| Decode::decode(&mut v.as_slice()).ok())
+			.unwrap_or_default()
+	}
+
+	fn set_state_stf_version(state: &mut SgxExternalities, version: StfVersion) {
+		state.insert(STF_VERSION_KEY.into(), version.encode());
+	}
+
+	fn state_migrations() -> Vec<StateMigration<SgxExternalities>> {
+		Vec::new()
+	}
+}
+
+/// Timestamp of every state of the [StfMock].
+pub const MOCK_STATE_TIMESTAMP: u64 = 1_000;
+
 impl StateGetterInterface<GetterMock, SgxExternalities> for StfMock {
 	fn execute_getter(_state: &mut SgxExternalities, _getter: GetterMock) -> Option<Vec<u8>> {
 		Some(vec![42])
 	}
+
+	fn state_timestamp(_state: &mut SgxExternalities) -> u64 {
+		MOCK_STATE_TIMESTAMP
+	}
 }
 
 pub type TrustedOperationMock = TrustedOperation<TrustedCallSignedMock, GetterMock>;
@@ -94,6 +195,8 @@ pub enum TrustedCallMock {
 	noop(AccountId),
 	balance_transfer(AccountId, AccountId, Balance),
 	waste_time_ms(AccountId, u64),
+	/// Noop with the given weight in ms.
+	weighted_noop(AccountId, u64),
 }
 
 impl TrustedCallMock {
@@ -102,28 +205,27 @@ impl TrustedCallMock {
 			Self::noop(sender_account) => sender_account,
 			Self::balance_transfer(sender_account, ..) => sender_account,
 			Self::waste_time_ms(sender_account, ..) => sender_account,
+			Self::weighted_noop(sender_account, ..) => sender_account,
 		}
 	}
 }
 
 impl TrustedCallSigning<TrustedCallSignedMock> for TrustedCallMock {
-	fn sign(
+	fn signing_payload(
 		&self,
-		pair: &KeyPair,
 		nonce: Index,
 		mrenclave: &[u8; 32],
 		shard: &ShardIdentifier,
-	) -> TrustedCallSignedMock {
+	) -> Vec<u8> {
 		let mut payload = self.encode();
 		payload.append(&mut nonce.encode());
 		payload.append(&mut mrenclave.encode());
 		payload.append(&mut shard.encode());
+		payload
+	}
 
-		TrustedCallSignedMock {
-			call: self.clone(),
-			nonce,
-			signature: pair.sign(payload.as_slice()),
-		}
+	fn with_signature(&self, nonce: Index, signature: Signature) -> TrustedCallSignedMock {
+		TrustedCallSignedMock { call: self.clone(), nonce, signature }
 	}
 }
 
@@ -161,6 +263,8 @@ impl ExecuteCall<NodeMetadataRepositoryMock> for TrustedCallSignedMock {
 
 	fn execute(
 		self,
+		_mrenclave: &[u8; 32],
+		_shard: &ShardIdentifier,
 		_calls: &mut Vec<OpaqueCall>,
 		_node_metadata_repo: Arc<NodeMetadataRepositoryMock>,
 	) -> Result<(), Self::Error> {
@@ -177,6 +281,7 @@ impl ExecuteCall<NodeMetadataRepositoryMock> for TrustedCallSignedMock {
 				sleep(Duration::from_millis(ms));
 				Ok(())
 			},
+			TrustedCallMock::weighted_noop(..) => Ok(()),
 		}
 	}
 
@@ -199,6 +304,15 @@ impl TrustedCallVerification for TrustedCallSignedMock {
 	}
 }
 
+impl TrustedCallWeight for TrustedCallSignedMock {
+	fn weight(&self) -> Duration {
+		match self.call {
+			TrustedCallMock::weighted_noop(_, ms) => Duration::from_millis(ms),
+			_ => Duration::ZERO,
+		}
+	}
+}
+
 #[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
 pub enum GetterMock {
@@ -227,6 +341,27 @@ impl GetterAuthorization for GetterMock {
 	}
 }
 
+impl GetterResponseEncryption for GetterMock {
+	fn response_key(&self) -> Option<&ExportKey> {
+		match self {
+			Self::trusted(TrustedGetterSignedMock {
+				getter: TrustedGetterMock::encrypted_value(key),
+				..
+			}) => Some(key),
+			_ => None,
+		}
+	}
+}
+
+impl GetterExpiry for GetterMock {
+	fn is_expired(&self, now: u64) -> bool {
+		match self {
+			Self::trusted(tgs) => tgs.valid_until < now,
+			Self::public(_) => false,
+		}
+	}
+}
+
 #[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
 pub enum PublicGetterMock {
@@ -237,11 +372,14 @@ pub enum PublicGetterMock {
 #[allow(non_camel_case_types)]
 pub enum TrustedGetterMock {
 	some_value,
+	/// Like `some_value`, but the value is encrypted to the given key.
+	encrypted_value(ExportKey),
 }
 
 #[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
 pub struct TrustedGetterSignedMock {
 	pub getter: TrustedGetterMock,
+	pub valid_until: u64,
 	pub signature: bool,
 }
 
@@ -271,6 +409,7 @@ pub fn mock_top_indirect_trusted_call_signed() -> TrustedOperationMock {
 pub fn mock_top_trusted_getter_signed() -> TrustedOperationMock {
 	TrustedOperationMock::get(GetterMock::trusted(TrustedGetterSignedMock {
 		getter: TrustedGetterMock::some_value,
+		valid_until: u64::MAX,
 		signature: true,
 	}))
 }
@@ -24,16 +24,20 @@ use crate::{
 	error::{Error as StateRpcError, Result},
 	top_filter::Filter,
 	traits::{AuthorApi, OnBlockImported},
+	validation::ValidationConfig,
 };
-use codec::{Decode, Encode};
+use codec::{Decode, DecodeAll, Encode};
 use itp_enclave_metrics::EnclaveMetric;
+use itp_memory_accounting::{Scoped, Subsystem};
 use itp_ocall_api::EnclaveMetricsOCallApi;
 use itp_sgx_crypto::{key_repository::AccessKey, ShieldingCryptoDecrypt};
+use itp_sgx_externalities::SgxExternalitiesTrait;
 use itp_stf_primitives::{
+	shard_acl::ShardAccessControl,
 	traits::{PoolTransactionValidation, TrustedCallVerification},
 	types::{AccountId, TrustedOperation as StfTrustedOperation, TrustedOperationOrHash},
 };
-use itp_stf_state_handler::query_shard_state::QueryShardState;
+use itp_stf_state_handler::{handle_state::HandleState, query_shard_state::QueryShardState};
 use itp_top_pool::{
 	error::{Error as PoolError, IntoPoolError},
 	primitives::{
@@ -86,6 +90,8 @@ where
 	state_facade: Arc<StateFacade>,
 	shielding_key_repo: Arc<ShieldingKeyRepository>,
 	ocall_api: Arc<OCallApi>,
+	validation: ValidationConfig,
+	access_control: Option<Arc<dyn ShardAccessControl + Send + Sync>>,
 }
 
 impl<TopPool, TopFilter, StateFacade, ShieldingKeyRepository, OCallApi, TCS, G>
@@ -108,7 +114,33 @@ where
 		encryption_key: Arc<ShieldingKeyRepository>,
 		ocall_api: Arc<OCallApi>,
 	) -> Self {
-		Author { top_pool, top_filter, state_facade, shielding_key_repo: encryption_key, ocall_api }
+		Author {
+			top_pool,
+			top_filter,
+			state_facade,
+			shielding_key_repo: encryption_key,
+			ocall_api,
+			validation: ValidationConfig::default(),
+			access_control: None,
+		}
+	}
+
+	/// Validate submitted trusted operations with the given config instead of the default,
+	/// which does neither verify signatures nor the STF version of the shard state.
+	pub fn with_validation(mut self, validation: ValidationConfig) -> Self {
+		self.validation = validation;
+		self
+	}
+
+	/// Enforce the access control and the maintenance mode of the shards with the rules of the
+	/// STF on submission of trusted calls. Without it, all calls are accepted and only checked
+	/// on execution.
+	pub fn with_access_control(
+		mut self,
+		access_control: Arc<dyn ShardAccessControl + Send + Sync>,
+	) -> Self {
+		self.access_control = Some(access_control);
+		self
 	}
 }
 
@@ -122,7 +154,8 @@ impl<TopPool, TopFilter, StateFacade, ShieldingKeyRepository, OCallApi, TCS, G>
 where
 	TopPool: TrustedOperationPool<StfTrustedOperation<TCS, G>> + Sync + Send + 'static,
 	TopFilter: Filter<Value = StfTrustedOperation<TCS, G>>,
-	StateFacade: QueryShardState,
+	StateFacade: QueryShardState + HandleState,
+	StateFacade::StateT: SgxExternalitiesTrait,
 	ShieldingKeyRepository: AccessKey,
 	<ShieldingKeyRepository as AccessKey>::KeyType: ShieldingCryptoDecrypt,
 	OCallApi: EnclaveMetricsOCallApi + Send + Sync + 'static,
@@ -151,6 +184,13 @@ where
 		shard: ShardIdentifier,
 		submission_mode: TopSubmissionMode,
 	) -> PoolFuture<TxHash, RpcError> {
+		// reject the operation before the enclave runs out of memory
+		if let Err(e) = itp_memory_accounting::check_admission(Subsystem::TopPool) {
+			warn!("Rejecting trusted operation: {}", e);
+			return Box::pin(ready(Err(ClientError::ResourceExhausted(e).into())))
+		}
+		let _scope = itp_memory_accounting::enter(Subsystem::TopPool);
+
 		// check if shard exists
 		match self.state_facade.shard_exists(&shard) {
 			Err(_) => return Box::pin(ready(Err(ClientError::InvalidShard.into()))),
@@ -160,6 +200,12 @@ where
 				},
 		};
 
+		// reject oversized blobs before spending any effort on them
+		if let Err(e) = self.validation.ensure_size(&ext) {
+			warn!("Rejecting trusted operation: {}", e);
+			return Box::pin(ready(Err(e.into())))
+		}
+
 		// decrypt call
 		let shielding_key = match self.shielding_key_repo.retrieve_key() {
 			Ok(k) => k,
@@ -169,9 +215,9 @@ where
 			Ok(req) => req,
 			Err(_) => return Box::pin(ready(Err(ClientError::BadFormatDecipher.into()))),
 		};
-		// decode call
+		// decode call, trailing bytes are not accepted either
 		let trusted_operation =
-			match StfTrustedOperation::<TCS, G>::decode(&mut request_vec.as_slice()) {
+			match StfTrustedOperation::<TCS, G>::decode_all(&mut request_vec.as_slice()) {
 				Ok(op) => op,
 				Err(_) => return Box::pin(ready(Err(ClientError::BadFormat.into()))),
 			};
@@ -185,6 +231,36 @@ where
 			return Box::pin(ready(Err(ClientError::UnsupportedOperation.into())))
 		}
 
+		if let Some(call) = trusted_operation.to_call() {
+			// trusted calls are frozen while the parentchain sync stalls, getters are still served
+			if let Err(e) = itp_sync_watchdog::ensure_synced() {
+				warn!("Rejecting trusted call: {}", e);
+				return Box::pin(ready(Err(ClientError::ParentchainSyncStalled(e).into())))
+			}
+
+			let sender = call.sender_account();
+			if let Err(e) = self.validation.ensure_valid_signature(call, &shard) {
+				warn!("Rejecting trusted call of {:?}: {}", sender, e);
+				return Box::pin(ready(Err(e.into())))
+			}
+
+			// enforce the access control of permissioned shards and the maintenance mode,
+			// and reject calls that can't be executed on the current state
+			match self.state_facade.execute_on_current(&shard, |state, _| {
+				if let Some(access_control) = &self.access_control {
+					access_control.ensure_may_submit(&|key| state.get(key).cloned(), sender)?;
+				}
+				self.validation.ensure_executable(state, call)
+			}) {
+				Ok(Ok(())) => {},
+				Ok(Err(e)) => {
+					warn!("Rejecting trusted call of {:?} to shard {:?}: {}", sender, shard, e);
+					return Box::pin(ready(Err(e.into())))
+				},
+				Err(_) => return Box::pin(ready(Err(ClientError::InvalidShard.into()))),
+			}
+		}
+
 		//let best_block_hash = self.client.info().best_hash;
 		// dummy block hash
 		let best_block_hash = Default::default();
@@ -209,7 +285,8 @@ where
 		}
 
 		match submission_mode {
-			TopSubmissionMode::Submit => Box::pin(
+			TopSubmissionMode::Submit => Box::pin(Scoped::new(
+				Subsystem::TopPool,
 				self.top_pool
 					.submit_one(
 						&generic::BlockId::hash(best_block_hash),
@@ -218,9 +295,10 @@ where
 						shard,
 					)
 					.map_err(map_top_error::<TopPool, TCS, G>),
-			),
+			)),
 
-			TopSubmissionMode::SubmitWatch => Box::pin(
+			TopSubmissionMode::SubmitWatch => Box::pin(Scoped::new(
+				Subsystem::TopPool,
 				self.top_pool
 					.submit_and_watch(
 						&generic::BlockId::hash(best_block_hash),
@@ -229,7 +307,7 @@ where
 						shard,
 					)
 					.map_err(map_top_error::<TopPool, TCS, G>),
-			),
+			)),
 		}
 	}
 
@@ -294,7 +372,8 @@ impl<TopPool, TopFilter, StateFacade, ShieldingKeyRepository, OCallApi, TCS, G>
 where
 	TopPool: TrustedOperationPool<StfTrustedOperation<TCS, G>> + Sync + Send + 'static,
 	TopFilter: Filter<Value = StfTrustedOperation<TCS, G>>,
-	StateFacade: QueryShardState,
+	StateFacade: QueryShardState + HandleState,
+	StateFacade::StateT: SgxExternalitiesTrait,
 	ShieldingKeyRepository: AccessKey,
 	<ShieldingKeyRepository as AccessKey>::KeyType: ShieldingCryptoDecrypt,
 	OCallApi: EnclaveMetricsOCallApi + Send + Sync + 'static,
@@ -365,7 +444,10 @@ where
 	) -> Vec<StfTrustedOperation<TCS, G>> {
 		self.get_pending_trusted_calls(shard)
 			.into_iter()
-			.filter(|o| o.signed_caller_account() == Some(account))
+			.filter(|o| {
+				o.to_call()
+					.map_or(false, |call| call.nonces().iter().any(|(who, _)| who == account))
+			})
 			.collect()
 	}
 
@@ -21,10 +21,15 @@ use crate::{
 	test_utils::submit_operation_to_top_pool,
 	top_filter::{AllowAllTopsFilter, Filter, GettersOnlyFilter},
 	traits::AuthorApi,
+	validation::NONCE_WINDOW,
 };
 use codec::{Decode, Encode};
+use itp_rpc::error_codes::{ACCESS_DENIED, BAD_FORMAT, NONCE_OUT_OF_WINDOW, SHARD_IN_MAINTENANCE};
 use itp_sgx_crypto::{mocks::KeyRepositoryMock, ShieldingCryptoDecrypt, ShieldingCryptoEncrypt};
-
+use itp_stf_primitives::{
+	shard_acl::{ShardAccessControl, SubmissionDenied},
+	types::AccountId,
+};
 use itp_stf_state_handler::handle_state::HandleState;
 use itp_test::mock::{
 	handle_state_mock::HandleStateMock,
@@ -32,10 +37,12 @@ use itp_test::mock::{
 	shielding_crypto_mock::ShieldingCryptoMock,
 	stf_mock::{
 		mock_top_direct_trusted_call_signed, mock_top_indirect_trusted_call_signed,
-		mock_top_trusted_getter_signed, GetterMock, TrustedCallSignedMock, TrustedOperationMock,
+		mock_top_trusted_getter_signed, mock_trusted_call_signed, GetterMock,
+		TrustedCallSignedMock, TrustedOperationMock,
 	},
 };
 use itp_top_pool::mocks::trusted_operation_pool_mock::TrustedOperationPoolMock;
+use jsonrpc_core::{futures::executor, ErrorCode};
 
 use sgx_crypto_helper::{rsa3072::Rsa3072KeyPair, RsaKeyPair};
 use sp_core::H256;
@@ -125,6 +132,81 @@ fn submitting_indirect_call_works() {
 	assert_eq!(1, author.get_pending_trusted_calls(shard_id()).len());
 }
 
+/// Denies all trusted calls for the given reason.
+struct DenyAllAccessControl(SubmissionDenied);
+
+impl ShardAccessControl for DenyAllAccessControl {
+	fn ensure_may_submit(
+		&self,
+		_storage: &dyn Fn(&[u8]) -> Option<Vec<u8>>,
+		_who: &AccountId,
+	) -> Result<(), SubmissionDenied> {
+		Err(self.0)
+	}
+}
+
+#[test]
+fn submitting_call_to_permissioned_shard_by_non_member_returns_error() {
+	let (author, top_pool, shielding_key) = create_author_with_filter(AllowAllTopsFilter::new());
+	let author =
+		author.with_access_control(Arc::new(DenyAllAccessControl(SubmissionDenied::AccessDenied)));
+
+	let top_call = mock_top_direct_trusted_call_signed();
+	let submit_response =
+		submit_operation_to_top_pool(&author, &top_call, &shielding_key, shard_id());
+
+	assert_eq!(submit_response.unwrap_err().code, ErrorCode::ServerError(ACCESS_DENIED));
+	assert!(top_pool.get_last_submitted_transactions().is_empty());
+
+	let top_getter = mock_top_trusted_getter_signed();
+	submit_operation_to_top_pool(&author, &top_getter, &shielding_key, shard_id()).unwrap();
+	assert_eq!(1, top_pool.get_last_submitted_transactions().len());
+}
+
+#[test]
+fn submitting_call_to_shard_in_maintenance_returns_error() {
+	let (author, top_pool, shielding_key) = create_author_with_filter(AllowAllTopsFilter::new());
+	let author = author
+		.with_access_control(Arc::new(DenyAllAccessControl(SubmissionDenied::ShardInMaintenance)));
+
+	let top_call = mock_top_direct_trusted_call_signed();
+	let submit_response =
+		submit_operation_to_top_pool(&author, &top_call, &shielding_key, shard_id());
+
+	assert_eq!(submit_response.unwrap_err().code, ErrorCode::ServerError(SHARD_IN_MAINTENANCE));
+	assert!(top_pool.get_last_submitted_transactions().is_empty());
+
+	let top_getter = mock_top_trusted_getter_signed();
+	submit_operation_to_top_pool(&author, &top_getter, &shielding_key, shard_id()).unwrap();
+	assert_eq!(1, top_pool.get_last_submitted_transactions().len());
+}
+
+#[test]
+fn submitting_operation_with_trailing_bytes_returns_error() {
+	let (author, top_pool, shielding_key) = create_author_with_filter(AllowAllTopsFilter::new());
+	let mut encoded_call = mock_top_direct_trusted_call_signed().encode();
+	encoded_call.push(0);
+
+	let submit_response = executor::block_on(
+		author.watch_top(shielding_key.encrypt(&encoded_call).unwrap(), shard_id()),
+	);
+
+	assert_eq!(submit_response.unwrap_err().code, ErrorCode::ServerError(BAD_FORMAT));
+	assert!(top_pool.get_last_submitted_transactions().is_empty());
+}
+
+#[test]
+fn submitting_call_with_nonce_out_of_window_returns_error() {
+	let (author, top_pool, shielding_key) = create_author_with_filter(AllowAllTopsFilter::new());
+	let top_call = TrustedOperationMock::direct_call(mock_trusted_call_signed(NONCE_WINDOW + 1));
+
+	let submit_response =
+		submit_operation_to_top_pool(&author, &top_call, &shielding_key, shard_id());
+
+	assert_eq!(submit_response.unwrap_err().code, ErrorCode::ServerError(NONCE_OUT_OF_WINDOW));
+	assert!(top_pool.get_last_submitted_transactions().is_empty());
+}
+
 fn create_author_with_filter<F: Filter<Value = TrustedOperationMock>>(
 	filter: F,
 ) -> (TestAuthor<F>, Arc<TrustedOperationPoolMock<TrustedOperationMock>>, ShieldingCryptoMock) {
@@ -21,6 +21,16 @@
 use crate::sgx_reexport_prelude::*;
 
 use derive_more::{Display, From};
+use itp_rpc::error_codes::{
+	ACCESS_DENIED, BAD_FORMAT, INVALID_SIGNATURE, NONCE_OUT_OF_WINDOW, OPERATION_TOO_LARGE,
+	PARENTCHAIN_SYNC_STALLED, POOL_ALREADY_IMPORTED, POOL_CYCLE_DETECTED, POOL_IMMEDIATELY_DROPPED,
+	POOL_INVALID_TX, POOL_TEMPORARILY_BANNED, POOL_TOO_LOW_PRIORITY, POOL_UNKNOWN_VALIDITY,
+	RESOURCE_EXHAUSTED, SHARD_IN_MAINTENANCE, UNSUPPORTED_KEY_TYPE, UNSUPPORTED_STF_VERSION,
+	VERIFICATION_ERROR,
+};
+use itp_sgx_runtime_primitives::types::Index;
+use itp_stf_interface::StfVersion;
+use itp_stf_primitives::shard_acl::SubmissionDenied;
 use jsonrpc_core as rpc_core;
 use std::{boxed::Box, format};
 
@@ -68,6 +78,39 @@ pub enum Error {
 	/// Unsupported trusted operation (in case we allow only certain types of operations, using filters)
 	#[display(fmt = "Unsupported operation type")]
 	UnsupportedOperation,
+	/// Sender is not permitted to submit trusted calls to a permissioned shard.
+	#[display(fmt = "Access to shard denied")]
+	AccessDenied,
+	/// The shard is in maintenance mode and accepts no new trusted calls.
+	#[display(fmt = "Shard is in maintenance mode")]
+	ShardInMaintenance,
+	/// The enclave does not have enough memory left to accept the operation.
+	#[display(fmt = "Enclave resources exhausted: {}", _0)]
+	#[from(ignore)]
+	ResourceExhausted(itp_memory_accounting::Error),
+	/// The parentchain sync stalled, no trusted calls are accepted until it recovers.
+	#[display(fmt = "Shard frozen: {}", _0)]
+	#[from(ignore)]
+	ParentchainSyncStalled(itp_sync_watchdog::Error),
+	/// The encrypted trusted operation exceeds the maximum size.
+	#[display(fmt = "Trusted operation too large: {} bytes, maximum is {}", size, max)]
+	#[from(ignore)]
+	OperationTooLarge { size: usize, max: usize },
+	/// The signature of the trusted call is not valid for this enclave and shard.
+	#[display(fmt = "Invalid trusted call signature")]
+	InvalidSignature,
+	/// The shard state was written by a newer STF than the one of this enclave.
+	#[display(
+		fmt = "Unsupported STF version of the shard state: {}, enclave supports up to {}",
+		state_version,
+		enclave_version
+	)]
+	#[from(ignore)]
+	UnsupportedStfVersion { state_version: StfVersion, enclave_version: StfVersion },
+	/// The nonce is already used or too far ahead of the account nonce.
+	#[display(fmt = "Nonce {} out of window, account nonce is {}", nonce, account_nonce)]
+	#[from(ignore)]
+	NonceOutOfWindow { nonce: Index, account_nonce: Index },
 }
 
 impl std::error::Error for Error {
@@ -81,29 +124,14 @@ impl std::error::Error for Error {
 	}
 }
 
-/// Base code for all authorship errors.
-const BASE_ERROR: i64 = 1000;
-/// Extrinsic has an invalid format.
-const BAD_FORMAT: i64 = BASE_ERROR + 1;
-/// Error during operation verification in runtime.
-const VERIFICATION_ERROR: i64 = BASE_ERROR + 2;
-
-/// Pool rejected the operation as invalid
-const POOL_INVALID_TX: i64 = BASE_ERROR + 10;
-/// Cannot determine operation validity.
-const POOL_UNKNOWN_VALIDITY: i64 = POOL_INVALID_TX + 1;
-/// The operation is temporarily banned.
-const POOL_TEMPORARILY_BANNED: i64 = POOL_INVALID_TX + 2;
-/// The operation is already in the pool
-const POOL_ALREADY_IMPORTED: i64 = POOL_INVALID_TX + 3;
-/// TrustedOperation has too low priority to replace existing one in the pool.
-const POOL_TOO_LOW_PRIORITY: i64 = POOL_INVALID_TX + 4;
-/// Including this operation would cause a dependency cycle.
-const POOL_CYCLE_DETECTED: i64 = POOL_INVALID_TX + 5;
-/// The operation was not included to the pool because of the limits.
-const POOL_IMMEDIATELY_DROPPED: i64 = POOL_INVALID_TX + 6;
-/// The key type crypto is not known.
-const UNSUPPORTED_KEY_TYPE: i64 = POOL_INVALID_TX + 7;
+impl From<SubmissionDenied> for Error {
+	fn from(denied: SubmissionDenied) -> Self {
+		match denied {
+			SubmissionDenied::ShardInMaintenance => Error::ShardInMaintenance,
+			SubmissionDenied::AccessDenied => Error::AccessDenied,
+		}
+	}
+}
 
 impl From<Error> for rpc_core::Error {
 	fn from(e: Error) -> Self {
@@ -130,6 +158,46 @@ impl From<Error> for rpc_core::Error {
 				message: "Shard does not exist".into(),
 				data: Some(format!("{:?}", e).into()),
 			},
+			Error::AccessDenied => rpc_core::Error {
+				code: rpc_core::ErrorCode::ServerError(ACCESS_DENIED),
+				message: "Access to shard denied".into(),
+				data: Some("The sender is not permitted to submit trusted calls to this shard".into()),
+			},
+			Error::ShardInMaintenance => rpc_core::Error {
+				code: rpc_core::ErrorCode::ServerError(SHARD_IN_MAINTENANCE),
+				message: "Shard is in maintenance mode".into(),
+				data: Some("No trusted calls are accepted until the maintenance is over".into()),
+			},
+			Error::ResourceExhausted(e) => rpc_core::Error {
+				code: rpc_core::ErrorCode::ServerError(RESOURCE_EXHAUSTED),
+				message: "Enclave resources exhausted".into(),
+				data: Some(format!("{}", e).into()),
+			},
+			Error::ParentchainSyncStalled(e) => rpc_core::Error {
+				code: rpc_core::ErrorCode::ServerError(PARENTCHAIN_SYNC_STALLED),
+				message: "Shard frozen, parentchain sync stalled".into(),
+				data: Some(format!("{}", e).into()),
+			},
+			Error::OperationTooLarge { .. } => rpc_core::Error {
+				code: rpc_core::ErrorCode::ServerError(OPERATION_TOO_LARGE),
+				message: "Trusted operation too large".into(),
+				data: Some(format!("{}", e).into()),
+			},
+			Error::InvalidSignature => rpc_core::Error {
+				code: rpc_core::ErrorCode::ServerError(INVALID_SIGNATURE),
+				message: "Invalid trusted call signature".into(),
+				data: Some("The call has to be signed for the MRENCLAVE and shard it is submitted to".into()),
+			},
+			Error::UnsupportedStfVersion { .. } => rpc_core::Error {
+				code: rpc_core::ErrorCode::ServerError(UNSUPPORTED_STF_VERSION),
+				message: "Unsupported STF version".into(),
+				data: Some(format!("{}", e).into()),
+			},
+			Error::NonceOutOfWindow { .. } => rpc_core::Error {
+				code: rpc_core::ErrorCode::ServerError(NONCE_OUT_OF_WINDOW),
+				message: "Nonce out of window".into(),
+				data: Some(format!("{}", e).into()),
+			},
 			Error::Pool(PoolError::InvalidTrustedOperation) => rpc_core::Error {
 				code: rpc_core::ErrorCode::ServerError(POOL_INVALID_TX),
 				message: "Invalid Trusted Operation".into(),
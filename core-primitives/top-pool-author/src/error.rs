@@ -21,6 +21,7 @@ use crate::sgx_reexport_prelude::*;
 use crate::client_error::Error as ClientError;
 use core::pin::Pin;
 use derive_more::{Display, From};
+use itp_rpc::error_codes::{INVALID_BLOCK_RANGE, INVALID_COUNT, STATE_INTERNAL_ERROR};
 use itp_top_pool::error::{Error as PoolError, IntoPoolError};
 use jsonrpc_core as rpc;
 use std::{boxed::Box, error, format, string::String};
@@ -85,27 +86,42 @@ impl IntoPoolError for Error {
 	}
 }
 
-/// Base code for all state errors.
-const BASE_ERROR: i64 = 4000;
-
 impl From<Error> for rpc::Error {
 	fn from(e: Error) -> Self {
 		match e {
 			Error::InvalidBlockRange { .. } => rpc::Error {
-				code: rpc::ErrorCode::ServerError(BASE_ERROR + 1),
+				code: rpc::ErrorCode::ServerError(INVALID_BLOCK_RANGE),
 				message: format!("{}", e),
 				data: None,
 			},
 			Error::InvalidCount { .. } => rpc::Error {
-				code: rpc::ErrorCode::ServerError(BASE_ERROR + 2),
+				code: rpc::ErrorCode::ServerError(INVALID_COUNT),
 				message: format!("{}", e),
 				data: None,
 			},
 			e => rpc::Error {
-				code: rpc::ErrorCode::ServerError(BASE_ERROR + 4),
+				code: rpc::ErrorCode::ServerError(STATE_INTERNAL_ERROR),
 				message: format!("{}", e),
 				data: None,
 			},
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn wrapped_errors_keep_the_state_internal_error_code() {
+		let errors = [
+			Error::PoolError(PoolError::InvalidTrustedOperation),
+			Error::ClientError(ClientError::AccessDenied),
+		];
+
+		for error in errors {
+			let rpc_error: rpc::Error = error.into();
+			assert_eq!(rpc_error.code, rpc::ErrorCode::ServerError(STATE_INTERNAL_ERROR));
+		}
+	}
+}
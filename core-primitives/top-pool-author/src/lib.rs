@@ -38,6 +38,7 @@ pub mod client_error;
 pub mod error;
 pub mod top_filter;
 pub mod traits;
+pub mod validation;
 
 #[cfg(test)]
 mod author_tests;
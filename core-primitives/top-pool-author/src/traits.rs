@@ -60,7 +60,7 @@ where
 	/// Returns pool status
 	fn get_status(&self, shard: ShardIdentifier) -> PoolStatus;
 
-	/// Returns all pending trusted calls for a given `account`
+	/// Returns all pending trusted calls for a given `account`, incl. the calls sponsored for it
 	fn get_pending_trusted_calls_for(
 		&self,
 		shard: ShardIdentifier,
@@ -0,0 +1,218 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Sanity checks of submitted trusted operations, so that operations which can never be
+//! executed are rejected right away instead of occupying a pool slot until execution.
+
+use crate::client_error::{Error as ClientError, Result};
+use codec::Decode;
+use itp_sgx_externalities::SgxExternalitiesTrait;
+use itp_sgx_runtime_primitives::types::Index;
+use itp_stf_interface::{StfVersion, STF_VERSION_KEY};
+use itp_stf_primitives::{traits::TrustedCallVerification, types::AccountId};
+use itp_storage::{storage_map_key, StorageHasher};
+use itp_types::ShardIdentifier;
+
+/// Maximum size of an encrypted trusted operation.
+pub const MAX_ENCRYPTED_OPERATION_SIZE: usize = 64 * 1024;
+
+/// How far ahead of the current account nonce the nonce of a submitted trusted call may be.
+pub const NONCE_WINDOW: Index = 128;
+
+/// Storage of the account nonces, see `frame_system::Account`.
+const SYSTEM_STORAGE_PREFIX: &str = "System";
+const ACCOUNT_KEY: &str = "Account";
+
+/// Configuration of the validation of submitted trusted operations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationConfig {
+	pub max_encrypted_size: usize,
+	pub nonce_window: Index,
+	/// MRENCLAVE the trusted calls have to be signed for. Signatures are only verified
+	/// on execution if it is not set.
+	pub mrenclave: Option<[u8; 32]>,
+	/// STF version of this enclave. Shard states written by a newer STF may contain calls
+	/// this enclave does not know, so no calls are accepted for them.
+	pub stf_version: Option<StfVersion>,
+}
+
+impl Default for ValidationConfig {
+	fn default() -> Self {
+		ValidationConfig {
+			max_encrypted_size: MAX_ENCRYPTED_OPERATION_SIZE,
+			nonce_window: NONCE_WINDOW,
+			mrenclave: None,
+			stf_version: None,
+		}
+	}
+}
+
+impl ValidationConfig {
+	pub fn new(mrenclave: [u8; 32], stf_version: StfVersion) -> Self {
+		ValidationConfig {
+			mrenclave: Some(mrenclave),
+			stf_version: Some(stf_version),
+			..Default::default()
+		}
+	}
+
+	/// Rejects operations that are too large before spending any effort on decrypting them.
+	pub fn ensure_size(&self, encrypted_operation: &[u8]) -> Result<()> {
+		if encrypted_operation.len() > self.max_encrypted_size {
+			return Err(ClientError::OperationTooLarge {
+				size: encrypted_operation.len(),
+				max: self.max_encrypted_size,
+			})
+		}
+		Ok(())
+	}
+
+	/// Verifies the signature of the trusted call, if the MRENCLAVE is known.
+	pub fn ensure_valid_signature<TCS: TrustedCallVerification>(
+		&self,
+		call: &TCS,
+		shard: &ShardIdentifier,
+	) -> Result<()> {
+		match self.mrenclave {
+			Some(ref mrenclave) if !call.verify_signature(mrenclave, shard) =>
+				Err(ClientError::InvalidSignature),
+			_ => Ok(()),
+		}
+	}
+
+	/// Checks the trusted call against the shard state it is going to be executed on.
+	pub fn ensure_executable<TCS: TrustedCallVerification, State: SgxExternalitiesTrait>(
+		&self,
+		state: &State,
+		call: &TCS,
+	) -> Result<()> {
+		if let Some(enclave_version) = self.stf_version {
+			let state_version = state_stf_version(state);
+			if state_version > enclave_version {
+				return Err(ClientError::UnsupportedStfVersion { state_version, enclave_version })
+			}
+		}
+
+		for (account, nonce) in call.nonces() {
+			let account_nonce = account_nonce(state, &account);
+			if nonce < account_nonce || nonce - account_nonce > self.nonce_window {
+				return Err(ClientError::NonceOutOfWindow { nonce, account_nonce })
+			}
+		}
+		Ok(())
+	}
+}
+
+fn state_stf_version<State: SgxExternalitiesTrait>(state: &State) -> StfVersion {
+	state
+		.get(STF_VERSION_KEY.as_bytes())
+		.and_then(|v| Decode::decode(&mut v.as_slice()).ok())
+		.unwrap_or_default()
+}
+
+/// The nonce is the first field of the `AccountInfo`, so the rest does not need to be decoded.
+fn account_nonce<State: SgxExternalitiesTrait>(state: &State, who: &AccountId) -> Index {
+	state
+		.get(&storage_map_key(
+			SYSTEM_STORAGE_PREFIX,
+			ACCOUNT_KEY,
+			who,
+			&StorageHasher::Blake2_128Concat,
+		))
+		.and_then(|v| Index::decode(&mut v.as_slice()).ok())
+		.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use itp_sgx_externalities::SgxExternalities;
+	use itp_test::mock::stf_mock::mock_trusted_call_signed;
+	use sp_core::{ed25519, Pair};
+
+	fn account(seed: u8) -> AccountId {
+		ed25519::Pair::from_seed(&[seed; 32]).public().into()
+	}
+
+	fn state_with_nonce(who: &AccountId, nonce: Index) -> SgxExternalities {
+		let mut state = SgxExternalities::default();
+		state.insert(
+			storage_map_key(
+				SYSTEM_STORAGE_PREFIX,
+				ACCOUNT_KEY,
+				who,
+				&StorageHasher::Blake2_128Concat,
+			),
+			// nonce, consumers, providers, sufficients, followed by the account data
+			(nonce, 0u32, 1u32, 0u32, 100u128).encode(),
+		);
+		state
+	}
+
+	#[test]
+	fn oversized_operation_is_rejected() {
+		let config = ValidationConfig { max_encrypted_size: 4, ..Default::default() };
+
+		assert!(config.ensure_size(&[0u8; 4]).is_ok());
+		assert!(matches!(
+			config.ensure_size(&[0u8; 5]),
+			Err(ClientError::OperationTooLarge { size: 5, max: 4 })
+		));
+	}
+
+	#[test]
+	fn nonce_is_read_from_account_info() {
+		let state = state_with_nonce(&account(1), 7);
+
+		assert_eq!(account_nonce(&state, &account(1)), 7);
+		assert_eq!(account_nonce(&state, &account(2)), 0);
+	}
+
+	#[test]
+	fn nonce_outside_of_window_is_rejected() {
+		let call = mock_trusted_call_signed(5);
+		let config = ValidationConfig { nonce_window: 2, ..Default::default() };
+
+		for account_nonce in [3, 4, 5] {
+			let state = state_with_nonce(call.sender_account(), account_nonce);
+			assert!(config.ensure_executable(&state, &call).is_ok());
+		}
+		for account_nonce in [2, 6] {
+			let state = state_with_nonce(call.sender_account(), account_nonce);
+			assert!(matches!(
+				config.ensure_executable(&state, &call),
+				Err(ClientError::NonceOutOfWindow { nonce: 5, .. })
+			));
+		}
+	}
+
+	#[test]
+	fn state_of_newer_stf_is_rejected() {
+		let call = mock_trusted_call_signed(0);
+		let config = ValidationConfig::new([0u8; 32], 1);
+
+		let mut state = SgxExternalities::default();
+		assert!(config.ensure_executable(&state, &call).is_ok());
+
+		state.insert(STF_VERSION_KEY.as_bytes().to_vec(), 2u32.encode());
+		assert!(matches!(
+			config.ensure_executable(&state, &call),
+			Err(ClientError::UnsupportedStfVersion { state_version: 2, enclave_version: 1 })
+		));
+	}
+}
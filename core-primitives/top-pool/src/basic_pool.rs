@@ -39,6 +39,7 @@ use alloc::{boxed::Box, string::String, sync::Arc};
 use codec::Encode;
 use core::{marker::PhantomData, pin::Pin};
 use itc_direct_rpc_server::SendRpcResponse;
+use itp_audit_log::RecordDroppedOperation;
 use itp_stf_primitives::{traits::PoolTransactionValidation, types::ShardIdentifier};
 use its_primitives::types::BlockHash as SidechainBlockHash;
 use jsonrpc_core::futures::{
@@ -128,10 +129,33 @@ where
 	where
 		<PoolApi as ChainApi>::Error: IntoPoolError,
 	{
-		let pool = Arc::new(Pool::new(options, pool_api.clone(), rpc_response_sender));
+		let pool = Pool::new(options, pool_api.clone(), rpc_response_sender);
+		Self::from_pool(pool, pool_api)
+	}
+
+	/// Create new basic operation pool that records the operations it drops without
+	/// execution in the audit log.
+	pub fn create_with_audit_log(
+		options: PoolOptions,
+		pool_api: Arc<PoolApi>,
+		rpc_response_sender: Arc<RpcResponse>,
+		audit_log: Arc<dyn RecordDroppedOperation>,
+	) -> Self
+	where
+		<PoolApi as ChainApi>::Error: IntoPoolError,
+	{
+		let pool =
+			Pool::new_with_audit_log(options, pool_api.clone(), rpc_response_sender, audit_log);
+		Self::from_pool(pool, pool_api)
+	}
+
+	fn from_pool(pool: Pool<PoolApi, RpcResponse, TOP>, pool_api: Arc<PoolApi>) -> Self
+	where
+		<PoolApi as ChainApi>::Error: IntoPoolError,
+	{
 		BasicPool {
 			_api: pool_api,
-			pool,
+			pool: Arc::new(pool),
 			ready_poll: Default::default(),
 			_phantom: Default::default(),
 		}
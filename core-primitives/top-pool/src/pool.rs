@@ -28,6 +28,7 @@ use crate::{
 use codec::Encode;
 use core::matches;
 use itc_direct_rpc_server::SendRpcResponse;
+use itp_audit_log::RecordDroppedOperation;
 use itp_stf_primitives::{traits::PoolTransactionValidation, types::ShardIdentifier};
 use itp_types::BlockHash as SidechainBlockHash;
 use jsonrpc_core::futures::{channel::mpsc::Receiver, future, Future};
@@ -138,10 +139,23 @@ where
 {
 	/// Create a new operation pool.
 	pub fn new(options: Options, api: Arc<B>, rpc_response_sender: Arc<R>) -> Self {
-		Pool {
-			validated_pool: Arc::new(ValidatedPool::new(options, api, rpc_response_sender)),
-			_phantom: Default::default(),
-		}
+		Self::from_validated_pool(ValidatedPool::new(options, api, rpc_response_sender))
+	}
+
+	/// Create a new operation pool that records dropped operations in the audit log.
+	pub fn new_with_audit_log(
+		options: Options,
+		api: Arc<B>,
+		rpc_response_sender: Arc<R>,
+		audit_log: Arc<dyn RecordDroppedOperation>,
+	) -> Self {
+		Self::from_validated_pool(
+			ValidatedPool::new(options, api, rpc_response_sender).with_audit_log(audit_log),
+		)
+	}
+
+	fn from_validated_pool(validated_pool: ValidatedPool<B, R, TOP>) -> Self {
+		Pool { validated_pool: Arc::new(validated_pool), _phantom: Default::default() }
 	}
 
 	/// Imports a bunch of unverified extrinsics to the pool
@@ -478,6 +492,7 @@ pub mod tests {
 		primitives::from_low_u64_to_be_h256,
 	};
 	use codec::{Decode, Encode};
+	use itp_audit_log::{mocks::RecordDroppedOperationMock, DropReason};
 	use itp_stf_primitives::types::Nonce;
 	use itp_test::mock::stf_mock::{
 		mock_top_direct_trusted_call_signed, mock_trusted_call_signed, TrustedOperationMock,
@@ -811,4 +826,31 @@ pub mod tests {
 		assert_eq!(pool.validated_pool().status(shard).ready, 0);
 		assert_eq!(pool.validated_pool().status(shard).future, 0);
 	}
+
+	#[test]
+	pub fn test_should_record_evicted_and_banned_operations_in_audit_log() {
+		// given
+		let shard = ShardIdentifier::default();
+		let limit = Limit { count: 100, total_bytes: 10 };
+		let audit_log = Arc::new(RecordDroppedOperationMock::default());
+		let pool = Pool::new_with_audit_log(
+			Options { ready: limit.clone(), future: limit, ..Default::default() },
+			TestApi::default().into(),
+			Arc::new(TestRpcResponder::new()),
+			audit_log.clone(),
+		);
+		let operation = TrustedOperationMock::direct_call(mock_trusted_call_signed(1));
+		let hash = pool.hash_of(&operation);
+
+		// when
+		block_on(pool.submit_one(&BlockId::Number(0), SOURCE, operation.clone(), shard))
+			.unwrap_err();
+		block_on(pool.submit_one(&BlockId::Number(0), SOURCE, operation, shard)).unwrap_err();
+
+		// then
+		assert_eq!(
+			audit_log.recorded(),
+			vec![(shard, hash, DropReason::Evicted), (shard, hash, DropReason::Banned)]
+		);
+	}
 }
@@ -40,6 +40,7 @@ use crate::{
 };
 use core::{marker::PhantomData, result::Result};
 use itc_direct_rpc_server::SendRpcResponse;
+use itp_audit_log::{DropReason, RecordDroppedOperation};
 use itp_stf_primitives::types::ShardIdentifier;
 use itp_types::BlockHash as SidechainBlockHash;
 use jsonrpc_core::futures::channel::mpsc::{channel, Sender};
@@ -109,6 +110,7 @@ where
 	pool: RwLock<base::BasePool<TOP>>,
 	import_notification_sinks: Mutex<Vec<Sender<TxHash>>>,
 	rotator: PoolRotator,
+	audit_log: Option<Arc<dyn RecordDroppedOperation>>,
 	_phantom: PhantomData<R>,
 }
 
@@ -127,10 +129,32 @@ where
 			pool: RwLock::new(base_pool),
 			import_notification_sinks: Default::default(),
 			rotator: Default::default(),
+			audit_log: None,
 			_phantom: Default::default(),
 		}
 	}
 
+	/// Records the operations that are dropped without being executed in the audit log.
+	pub fn with_audit_log(mut self, audit_log: Arc<dyn RecordDroppedOperation>) -> Self {
+		self.audit_log = Some(audit_log);
+		self
+	}
+
+	fn record_dropped<'a>(
+		&self,
+		shard: &ShardIdentifier,
+		hashes: impl IntoIterator<Item = &'a TxHash>,
+		reason: DropReason,
+	) {
+		if let Some(audit_log) = &self.audit_log {
+			for hash in hashes {
+				if let Err(e) = audit_log.record_dropped(shard, hash, reason) {
+					log::warn!(target: "txpool", "[{:?}] Failed to record {:?} in the audit log: {:?}", hash, reason, e);
+				}
+			}
+		}
+	}
+
 	/// Bans given set of hashes.
 	pub fn ban(&self, now: &Instant, hashes: impl IntoIterator<Item = TxHash>) {
 		self.rotator.ban(now, hashes)
@@ -153,6 +177,7 @@ where
 		shard: ShardIdentifier,
 	) -> Result<(), B::Error> {
 		if !ignore_banned && self.is_banned(tx_hash) {
+			self.record_dropped(&shard, core::iter::once(tx_hash), DropReason::Banned);
 			Err(error::Error::TemporarilyBanned.into())
 		} else if self.pool.read().unwrap().is_imported(tx_hash, shard) {
 			Err(error::Error::AlreadyImported.into())
@@ -263,6 +288,8 @@ where
 				log::debug!(target: "txpool", "Enforcing limits: {} dropped", removed.len());
 			}
 
+			self.record_dropped(&shard, &removed, DropReason::Evicted);
+
 			// run notifications
 			let mut listener = self.listener.write().unwrap();
 			for h in &removed {
@@ -573,6 +600,11 @@ where
 			}
 			hashes
 		};
+		self.record_dropped(
+			&shard,
+			to_remove.iter().chain(&futures_to_remove),
+			DropReason::Expired,
+		);
 		// removing old operations
 		self.remove_invalid(&to_remove, shard, false);
 		self.remove_invalid(&futures_to_remove, shard, false);
@@ -0,0 +1,245 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Enclave upgrades announced on the parentchain.
+//!
+//! A new enclave fingerprint (MRENCLAVE) is announced for a shard as pending upgrade of the
+//! shard config in the `EnclaveBridge` pallet, together with the parentchain block at which
+//! it becomes active. The old enclave authors sidechain blocks until the activation block,
+//! the new one from the activation block on. Before that, the new enclave obtains the keys of
+//! the old one by local attestation, see [KeyHandoverRequest].
+
+use crate::{AccountId, BlockNumber, EnclaveFingerprint, ShardIdentifier};
+use codec::{Decode, Encode};
+use sp_std::vec::Vec;
+
+/// Shard config as stored in the `ShardConfigRegistry` of the `EnclaveBridge` pallet.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct ShardConfig {
+	pub enclave_fingerprint: EnclaveFingerprint,
+	pub max_instances: Option<u32>,
+	pub authorities: Option<Vec<AccountId>>,
+	pub maintenance_mode: bool,
+}
+
+/// Active shard config with an optionally pending upgrade.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct UpgradableShardConfig {
+	pub active_config: ShardConfig,
+	pub pending_upgrade: Option<ShardConfig>,
+	pub upgrade_at: Option<BlockNumber>,
+}
+
+impl UpgradableShardConfig {
+	/// The config in effect at the given parentchain block.
+	pub fn config_at(&self, block_number: BlockNumber) -> &ShardConfig {
+		match (&self.pending_upgrade, self.upgrade_at) {
+			(Some(pending), Some(upgrade_at)) if upgrade_at <= block_number => pending,
+			_ => &self.active_config,
+		}
+	}
+
+	/// The pending upgrade, if it changes the enclave fingerprint.
+	pub fn pending_enclave_upgrade(&self) -> Option<EnclaveUpgrade> {
+		let pending = self.pending_upgrade.as_ref()?;
+		let activation_block = self.upgrade_at?;
+
+		if pending.enclave_fingerprint == self.active_config.enclave_fingerprint {
+			return None
+		}
+
+		Some(EnclaveUpgrade {
+			old_fingerprint: self.active_config.enclave_fingerprint,
+			new_fingerprint: pending.enclave_fingerprint,
+			activation_block,
+		})
+	}
+}
+
+/// Hand over of the sidechain authorship from one enclave to another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct EnclaveUpgrade {
+	pub old_fingerprint: EnclaveFingerprint,
+	pub new_fingerprint: EnclaveFingerprint,
+	pub activation_block: BlockNumber,
+}
+
+impl EnclaveUpgrade {
+	/// Whether an enclave may author sidechain blocks at the given parentchain block.
+	///
+	/// Enclaves that are part of neither side of the upgrade are not affected.
+	pub fn may_author(&self, fingerprint: &EnclaveFingerprint, block_number: BlockNumber) -> bool {
+		if fingerprint == &self.new_fingerprint {
+			block_number >= self.activation_block
+		} else if fingerprint == &self.old_fingerprint {
+			block_number < self.activation_block
+		} else {
+			true
+		}
+	}
+}
+
+/// Whether the enclave with `fingerprint` may author sidechain blocks of a shard at the given
+/// parentchain block.
+///
+/// Shards without a config on the parentchain are not restricted, shards in maintenance mode
+/// (e.g. retired ones) are not authored at all.
+pub fn may_author(
+	config: Option<&UpgradableShardConfig>,
+	fingerprint: &EnclaveFingerprint,
+	block_number: BlockNumber,
+) -> bool {
+	let config = match config {
+		Some(c) => c,
+		None => return true,
+	};
+	if config.config_at(block_number).maintenance_mode {
+		return false
+	}
+	match config.pending_enclave_upgrade() {
+		Some(upgrade) => upgrade.may_author(fingerprint, block_number),
+		None => &config.active_config.enclave_fingerprint == fingerprint,
+	}
+}
+
+/// Request of the new enclave of an announced upgrade for the keys of the old enclave.
+///
+/// `report` is a local attestation report of the new enclave targeted at the old one, its
+/// report data commits to the shard and the ephemeral public key the keys are encrypted to.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct KeyHandoverRequest {
+	pub shard: ShardIdentifier,
+	pub ephemeral_public: [u8; 32],
+	pub report: Vec<u8>,
+	/// Target info of the new enclave, for the report of the response.
+	pub target_info: Vec<u8>,
+}
+
+impl KeyHandoverRequest {
+	/// Size of the SCALE encoded request: shard, public key, an `sgx_report_t` of 432 and an
+	/// `sgx_target_info_t` of 512 bytes, each with a two byte length prefix.
+	pub const ENCODED_SIZE: usize = 32 + 32 + 2 + 432 + 2 + 512;
+}
+
+/// Keys of the old enclave, encrypted to the ephemeral public key of the request.
+///
+/// `report` is a local attestation report of the old enclave targeted at the new one, its
+/// report data commits to the encrypted keys.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct KeyHandoverResponse {
+	pub encrypted_keys: Vec<u8>,
+	pub report: Vec<u8>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn shard_config(fingerprint: u8) -> ShardConfig {
+		ShardConfig {
+			enclave_fingerprint: [fingerprint; 32].into(),
+			max_instances: None,
+			authorities: None,
+			maintenance_mode: false,
+		}
+	}
+
+	fn upgrade() -> EnclaveUpgrade {
+		UpgradableShardConfig {
+			active_config: shard_config(1),
+			pending_upgrade: Some(shard_config(2)),
+			upgrade_at: Some(100),
+		}
+		.pending_enclave_upgrade()
+		.unwrap()
+	}
+
+	#[test]
+	fn config_without_new_fingerprint_has_no_enclave_upgrade() {
+		let config = UpgradableShardConfig {
+			active_config: shard_config(1),
+			pending_upgrade: Some(shard_config(1)),
+			upgrade_at: Some(100),
+		};
+
+		assert_eq!(None, config.pending_enclave_upgrade());
+	}
+
+	#[test]
+	fn pending_config_is_in_effect_from_upgrade_block_on() {
+		let config = UpgradableShardConfig {
+			active_config: shard_config(1),
+			pending_upgrade: Some(shard_config(2)),
+			upgrade_at: Some(100),
+		};
+
+		assert_eq!(config.config_at(99), &shard_config(1));
+		assert_eq!(config.config_at(100), &shard_config(2));
+	}
+
+	#[test]
+	fn authorship_is_handed_over_at_activation_block() {
+		let upgrade = upgrade();
+		let old = [1u8; 32].into();
+		let new = [2u8; 32].into();
+
+		assert!(upgrade.may_author(&old, 99));
+		assert!(!upgrade.may_author(&new, 99));
+		assert!(!upgrade.may_author(&old, 100));
+		assert!(upgrade.may_author(&new, 100));
+	}
+
+	fn config(active: u8, pending: Option<(u8, BlockNumber)>) -> UpgradableShardConfig {
+		UpgradableShardConfig {
+			active_config: shard_config(active),
+			pending_upgrade: pending.map(|(f, _)| shard_config(f)),
+			upgrade_at: pending.map(|(_, at)| at),
+		}
+	}
+
+	#[test]
+	fn shard_without_config_is_not_restricted() {
+		assert!(may_author(None, &[1u8; 32].into(), 10));
+	}
+
+	#[test]
+	fn only_active_enclave_authors_without_pending_upgrade() {
+		let config = config(1, None);
+		assert!(may_author(Some(&config), &[1u8; 32].into(), 10));
+		assert!(!may_author(Some(&config), &[2u8; 32].into(), 10));
+	}
+
+	#[test]
+	fn authorship_of_shard_is_handed_over_at_activation_block() {
+		let config = config(1, Some((2, 10)));
+
+		assert!(may_author(Some(&config), &[1u8; 32].into(), 9));
+		assert!(!may_author(Some(&config), &[2u8; 32].into(), 9));
+		assert!(!may_author(Some(&config), &[1u8; 32].into(), 10));
+		assert!(may_author(Some(&config), &[2u8; 32].into(), 10));
+	}
+
+	#[test]
+	fn no_enclave_authors_in_maintenance_mode() {
+		let mut config = config(1, Some((1, 10)));
+		config.pending_upgrade.as_mut().unwrap().maintenance_mode = true;
+
+		assert!(may_author(Some(&config), &[1u8; 32].into(), 9));
+		assert!(!may_author(Some(&config), &[1u8; 32].into(), 10));
+		assert!(!may_author(Some(&config), &[2u8; 32].into(), 10));
+	}
+}
@@ -0,0 +1,48 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Types of the `state_executeGetterStream` RPC method, which returns large getter results in
+//! chunks.
+
+use crate::Request;
+use codec::{Decode, Encode};
+use sp_std::vec::Vec;
+
+/// Requests the chunk with index `chunk` of the result of the getter in `request`.
+///
+/// The getter is executed when chunk 0 is requested, the result is then kept in the enclave
+/// until the last chunk was fetched with the same (signed) getter, or until the stream expires.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct GetterStreamRequest {
+	pub request: Request,
+	pub chunk: u32,
+}
+
+/// Chunk of the encoded `Option<Vec<u8>>` getter result, i.e. the concatenated `data` of all
+/// chunks is what `state_executeGetter` returns for the same getter.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Encode, Decode)]
+pub struct GetterStreamChunk {
+	pub chunk: u32,
+	pub total_chunks: u32,
+	pub data: Vec<u8>,
+}
+
+impl GetterStreamChunk {
+	pub fn is_last(&self) -> bool {
+		self.chunk.saturating_add(1) >= self.total_chunks
+	}
+}
@@ -22,8 +22,18 @@ use crate::storage::StorageEntry;
 use codec::{Decode, Encode};
 use sp_std::vec::Vec;
 
+pub mod enclave_upgrade;
+pub mod getter_stream;
+pub mod oracle;
 pub mod parentchain;
+pub mod replay;
+pub mod shard_lifecycle;
+pub mod state_confirmation;
+pub mod state_dump;
+pub mod state_snapshot;
 pub mod storage;
+pub mod submission_receipt;
+pub mod worker_directory;
 
 /// Substrate runtimes provide no string type. Hence, for arbitrary data of varying length the
 /// `Vec<u8>` is used. In the polkadot-js the typedef `Text` is used to automatically
@@ -110,6 +120,24 @@ pub enum TrustedOperationStatus {
 	Invalid,
 }
 
+/// Summary of a shard maintained by a worker, as reported to operators.
+#[derive(Encode, Decode, Clone, Debug, PartialEq)]
+pub struct ShardInfo {
+	pub shard: ShardIdentifier,
+	/// Hash of the current (latest written) state of the shard.
+	pub state_hash: H256,
+	/// Number of the last sidechain block that was imported or produced on this shard.
+	pub last_sidechain_block_number: Option<SidechainBlockNumber>,
+	/// Hash of the last sidechain block that was imported or produced on this shard.
+	pub last_sidechain_block_hash: Option<BlockHash>,
+	/// Nonce of the enclave signer account within the shard state.
+	pub enclave_account_nonce: Index,
+	/// Shard vault account, if it has been initialized.
+	pub shard_vault: Option<AccountId>,
+	/// Number of batched unshielding requests waiting to be paid out from the shard vault.
+	pub pending_unshield_payouts: u32,
+}
+
 #[derive(Encode, Decode, Clone, Debug, PartialEq)]
 pub enum WorkerRequest {
 	ChainStorage(Vec<u8>, Option<BlockHash>), // (storage_key, at_block)
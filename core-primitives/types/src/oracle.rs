@@ -0,0 +1,69 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Deployment configuration of the values a teeracle publishes on the parentchain.
+
+use alloc::{format, string::String, vec::Vec};
+use codec::{Decode, Encode};
+use sp_core::bounded::alloc;
+
+/// An exchange rate that is fetched from several data sources and published as their median.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct OracleFeedConfig {
+	/// Symbol of the crypto currency, e.g. `TEER`.
+	pub crypto_currency: String,
+	/// Symbol of the fiat currency, e.g. `USD`.
+	pub fiat_currency: String,
+	/// Ids of the data sources the median is computed over, e.g. `coin_gecko`.
+	pub sources: Vec<String>,
+	/// Minimal number of sources that must respond, otherwise no value is published.
+	pub min_sources: u32,
+}
+
+impl OracleFeedConfig {
+	pub fn new(
+		crypto_currency: String,
+		fiat_currency: String,
+		sources: Vec<String>,
+		min_sources: u32,
+	) -> Self {
+		Self { crypto_currency, fiat_currency, sources, min_sources }
+	}
+
+	/// The data source the aggregated value is published for, e.g.
+	/// `median(coin_gecko,coin_market_cap)`. It must be whitelisted for the enclave in the
+	/// teeracle pallet.
+	pub fn data_source(&self) -> String {
+		format!("median({})", self.sources.join(","))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn data_source_lists_all_sources() {
+		let config = OracleFeedConfig::new(
+			"TEER".into(),
+			"USD".into(),
+			vec!["coin_gecko".into(), "coin_market_cap".into()],
+			1,
+		);
+
+		assert_eq!(config.data_source(), "median(coin_gecko,coin_market_cap)");
+	}
+}
@@ -73,6 +73,15 @@ pub trait FilterEvents {
 	fn get_extrinsic_statuses(&self) -> core::result::Result<Vec<ExtrinsicStatus>, Self::Error>;
 
 	fn get_transfer_events(&self) -> core::result::Result<Vec<BalanceTransfer>, Self::Error>;
+
+	/// Transfers of non-native assets, from both the `Assets` and the `Tokens` pallet.
+	fn get_asset_transfer_events(&self) -> core::result::Result<Vec<AssetTransfer>, Self::Error>;
+
+	/// Hashes published by enclaves with `EnclaveBridge::publish_hash`.
+	fn get_published_hash_events(&self) -> core::result::Result<Vec<PublishedHash>, Self::Error>;
+
+	/// Remarks stored with `System::remark_with_event`.
+	fn get_remark_events(&self) -> core::result::Result<Vec<Remarked>, Self::Error>;
 }
 
 #[derive(Encode, Decode, Debug)]
@@ -121,6 +130,120 @@ impl StaticEvent for BalanceTransfer {
 	const EVENT: &'static str = "Transfer";
 }
 
+/// Identifier of a non-native parentchain asset, which can be shielded into the shard.
+///
+/// Both the asset ids of the `Assets` pallet and the currency ids of the ORML `Tokens` pallet
+/// are expected to be `u32`.
+#[derive(Encode, Decode, Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ParentchainAssetId {
+	Assets(u32),
+	Tokens(u32),
+}
+
+impl core::fmt::Display for ParentchainAssetId {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		match self {
+			ParentchainAssetId::Assets(id) => write!(f, "assets:{}", id),
+			ParentchainAssetId::Tokens(id) => write!(f, "tokens:{}", id),
+		}
+	}
+}
+
+#[derive(Encode, Decode, Debug)]
+pub struct AssetsTransferred {
+	pub asset_id: u32,
+	pub from: AccountId,
+	pub to: AccountId,
+	pub amount: Balance,
+}
+
+impl StaticEvent for AssetsTransferred {
+	const PALLET: &'static str = "Assets";
+	const EVENT: &'static str = "Transferred";
+}
+
+#[derive(Encode, Decode, Debug)]
+pub struct TokensTransfer {
+	pub currency_id: u32,
+	pub from: AccountId,
+	pub to: AccountId,
+	pub amount: Balance,
+}
+
+impl StaticEvent for TokensTransfer {
+	const PALLET: &'static str = "Tokens";
+	const EVENT: &'static str = "Transfer";
+}
+
+/// Transfer of a non-native asset, see [ParentchainAssetId].
+#[derive(Encode, Decode, Debug)]
+pub struct AssetTransfer {
+	pub asset_id: ParentchainAssetId,
+	pub from: AccountId,
+	pub to: AccountId,
+	pub amount: Balance,
+}
+
+impl From<AssetsTransferred> for AssetTransfer {
+	fn from(event: AssetsTransferred) -> Self {
+		AssetTransfer {
+			asset_id: ParentchainAssetId::Assets(event.asset_id),
+			from: event.from,
+			to: event.to,
+			amount: event.amount,
+		}
+	}
+}
+
+impl From<TokensTransfer> for AssetTransfer {
+	fn from(event: TokensTransfer) -> Self {
+		AssetTransfer {
+			asset_id: ParentchainAssetId::Tokens(event.currency_id),
+			from: event.from,
+			to: event.to,
+			amount: event.amount,
+		}
+	}
+}
+
+impl core::fmt::Display for AssetTransfer {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		let message = format!(
+			"AssetTransfer :: asset: {}, from: {}, to: {}, amount: {}",
+			self.asset_id,
+			account_id_to_string::<AccountId>(&self.from),
+			account_id_to_string::<AccountId>(&self.to),
+			self.amount
+		);
+		write!(f, "{}", message)
+	}
+}
+
+#[derive(Encode, Decode, Debug)]
+pub struct PublishedHash {
+	/// MRENCLAVE of the publishing enclave.
+	pub enclave_fingerprint: Hash,
+	pub hash: Hash,
+	pub data: Vec<u8>,
+}
+
+impl StaticEvent for PublishedHash {
+	const PALLET: &'static str = "EnclaveBridge";
+	const EVENT: &'static str = "PublishedHash";
+}
+
+#[derive(Encode, Decode, Debug)]
+pub struct Remarked {
+	pub sender: AccountId,
+	/// Hash of the remark, the remark itself is not part of the event.
+	pub hash: Hash,
+}
+
+impl StaticEvent for Remarked {
+	const PALLET: &'static str = "System";
+	const EVENT: &'static str = "Remarked";
+}
+
 pub trait HandleParentchainEvents<Executor, TCS, Error>
 where
 	Executor: IndirectExecutor<TCS, Error>,
@@ -0,0 +1,49 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Result types of the sidechain block replay, shared between the enclave and the service.
+
+use crate::{ShardIdentifier, H256};
+use codec::{Decode, Encode};
+use sp_std::vec::Vec;
+
+/// A storage key whose value differs between the recorded and the replayed execution.
+///
+/// The outer `None` means the key was not touched by the respective execution,
+/// the inner `None` that it was removed.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct StateMismatch {
+	pub key: Vec<u8>,
+	pub recorded: Option<Option<Vec<u8>>>,
+	pub replayed: Option<Option<Vec<u8>>>,
+}
+
+/// Outcome of a sidechain block replay.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct BlockReplayReport {
+	pub shard: ShardIdentifier,
+	pub block_number: u64,
+	pub recorded_state_hash: H256,
+	pub replayed_state_hash: H256,
+	pub mismatches: Vec<StateMismatch>,
+}
+
+impl BlockReplayReport {
+	pub fn is_deterministic(&self) -> bool {
+		self.recorded_state_hash == self.replayed_state_hash && self.mismatches.is_empty()
+	}
+}
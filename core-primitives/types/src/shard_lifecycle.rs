@@ -0,0 +1,133 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Creation and retirement of shards, see the `init-shard` and `retire-shard` worker commands.
+
+use crate::{
+	enclave_upgrade::ShardConfig, state_snapshot::StateSnapshotCommitment, AccountId, Balance,
+	EnclaveFingerprint,
+};
+use codec::{Decode, Encode};
+use itp_stf_primitives::shard_acl::{AccessPolicy, MemberRoles};
+use sp_std::vec::Vec;
+
+/// How the funds shielded into a shard are held on the parentchain.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Encode, Decode)]
+pub enum VaultMode {
+	/// Vault account derived from the enclave signer, with the enclave signer as its proxy.
+	#[default]
+	Proxied,
+	/// No vault account is created, the shard holds no parentchain funds.
+	NoVault,
+}
+
+/// Genesis config of a shard, stored in its state when the shard is created.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Encode, Decode)]
+pub struct ShardGenesisConfig {
+	/// Enclave accounts that may author sidechain blocks of the shard.
+	/// Empty to not restrict the authorship.
+	pub authorities: Vec<AccountId>,
+	pub vault_mode: VaultMode,
+	/// Accounts endowed with a free balance in the initial state.
+	pub endowed_accounts: Vec<(AccountId, Balance)>,
+	/// Access policy of the shard, open by default.
+	pub access_policy: AccessPolicy,
+	/// Initial members of the shard and their roles.
+	pub members: Vec<(AccountId, MemberRoles)>,
+	/// STF version the genesis config has been written for, checked against the version of
+	/// the enclave's STF. `None` for any version.
+	pub stf_version: Option<u32>,
+}
+
+impl ShardGenesisConfig {
+	/// Shard config to register on the parentchain, for a shard run by the given enclave.
+	pub fn shard_config(&self, enclave_fingerprint: EnclaveFingerprint) -> ShardConfig {
+		ShardConfig {
+			enclave_fingerprint,
+			max_instances: None,
+			authorities: (!self.authorities.is_empty()).then(|| self.authorities.clone()),
+			maintenance_mode: false,
+		}
+	}
+}
+
+/// Outcome of the retirement of a shard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct ShardRetirementReport {
+	/// Commitment to the final state of the shard.
+	pub final_state: StateSnapshotCommitment,
+	/// Number of accounts whose funds have been unshielded from the vault.
+	pub unshielded_accounts: u32,
+	/// Total amount that has been unshielded from the vault.
+	pub unshielded_amount: Balance,
+	/// Number of payouts that had been sent before the retirement, but whose transfers were
+	/// not confirmed yet. They are not paid out again and remain listed in the final state.
+	pub unconfirmed_payouts: u32,
+	/// Total amount of the unconfirmed payouts.
+	pub unconfirmed_amount: Balance,
+}
+
+impl ShardRetirementReport {
+	/// Size of the SCALE encoded report, all fields have a fixed size.
+	pub const ENCODED_SIZE: usize = StateSnapshotCommitment::ENCODED_SIZE + 4 + 16 + 4 + 16;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{state_snapshot::IPFS_CID_LENGTH, ShardIdentifier, H256};
+
+	#[test]
+	fn genesis_config_without_authorities_does_not_restrict_authorship() {
+		let config = ShardGenesisConfig::default().shard_config([1u8; 32].into());
+
+		assert_eq!(config.authorities, None);
+		assert!(!config.maintenance_mode);
+	}
+
+	#[test]
+	fn genesis_config_authorities_are_registered() {
+		let authorities = vec![AccountId::new([2u8; 32]), AccountId::new([3u8; 32])];
+		let genesis = ShardGenesisConfig {
+			authorities: authorities.clone(),
+			vault_mode: VaultMode::NoVault,
+			..Default::default()
+		};
+
+		let config = genesis.shard_config([1u8; 32].into());
+
+		assert_eq!(config.enclave_fingerprint, [1u8; 32].into());
+		assert_eq!(config.authorities, Some(authorities));
+	}
+
+	#[test]
+	fn encoded_size_of_retirement_report_matches_encoding() {
+		let report = ShardRetirementReport {
+			final_state: StateSnapshotCommitment::new(
+				ShardIdentifier::repeat_byte(1),
+				H256::repeat_byte(2),
+				[b'Q'; IPFS_CID_LENGTH],
+			),
+			unshielded_accounts: 3,
+			unshielded_amount: 4,
+			unconfirmed_payouts: 5,
+			unconfirmed_amount: 6,
+		};
+
+		assert_eq!(report.encode().len(), ShardRetirementReport::ENCODED_SIZE);
+	}
+}
@@ -0,0 +1,140 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Confirmations of a proposed sidechain state update, for the pessimistic execution mode.
+//!
+//! Before a proposed block is published, its state transition is re-executed by the peer
+//! validateers of the shard. Each peer that arrives at the same state signs the update, and
+//! only with a quorum of such confirmations is the update applied.
+
+use crate::{AccountId, ShardIdentifier, H256};
+use codec::{Decode, Encode};
+use sp_core::{ed25519, Pair};
+use sp_runtime::traits::Verify;
+use sp_std::{collections::btree_set::BTreeSet, vec::Vec};
+
+/// The state transition of a proposed sidechain block.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct ConfirmedStateUpdate {
+	pub shard: ShardIdentifier,
+	pub block_number: u64,
+	pub state_hash_before_execution: H256,
+	pub state_hash_after_execution: H256,
+}
+
+/// A validateer's signature over a [`ConfirmedStateUpdate`] it re-executed itself.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct StateConfirmation {
+	pub update: ConfirmedStateUpdate,
+	pub signer: ed25519::Public,
+	pub signature: ed25519::Signature,
+}
+
+impl StateConfirmation {
+	pub fn new(update: ConfirmedStateUpdate, signer: &ed25519::Pair) -> Self {
+		let signature = signer.sign(update.encode().as_slice());
+		Self { update, signer: signer.public(), signature }
+	}
+
+	pub fn verify(&self) -> bool {
+		self.signature.verify(self.update.encode().as_slice(), &self.signer)
+	}
+}
+
+/// Number of confirmations required out of `validateer_count`, with the quorum given in percent.
+pub fn required_confirmations(validateer_count: u64, quorum_percent: u64) -> u64 {
+	let required = (validateer_count * quorum_percent + 99) / 100;
+	required.clamp(1, validateer_count.max(1))
+}
+
+/// Number of distinct `authorities` that validly confirmed exactly `update`.
+pub fn count_confirmations(
+	update: &ConfirmedStateUpdate,
+	confirmations: &[StateConfirmation],
+	authorities: &[AccountId],
+) -> u64 {
+	confirmations
+		.iter()
+		.filter(|c| &c.update == update && c.verify())
+		.filter(|c| authorities.contains(&AccountId::from(c.signer)))
+		.map(|c| c.signer)
+		.collect::<BTreeSet<_>>()
+		.len() as u64
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn update(state_hash_after_execution: u8) -> ConfirmedStateUpdate {
+		ConfirmedStateUpdate {
+			shard: ShardIdentifier::default(),
+			block_number: 3,
+			state_hash_before_execution: H256::repeat_byte(1),
+			state_hash_after_execution: H256::repeat_byte(state_hash_after_execution),
+		}
+	}
+
+	fn pair(seed: u8) -> ed25519::Pair {
+		ed25519::Pair::from_seed(&[seed; 32])
+	}
+
+	fn authorities(pairs: &[&ed25519::Pair]) -> Vec<AccountId> {
+		pairs.iter().map(|p| AccountId::from(p.public())).collect()
+	}
+
+	#[test]
+	fn required_confirmations_rounds_up_and_is_at_least_one() {
+		assert_eq!(1, required_confirmations(0, 67));
+		assert_eq!(1, required_confirmations(1, 67));
+		assert_eq!(2, required_confirmations(2, 67));
+		assert_eq!(3, required_confirmations(4, 67));
+		assert_eq!(4, required_confirmations(4, 100));
+	}
+
+	#[test]
+	fn tampered_confirmation_does_not_verify() {
+		let mut confirmation = StateConfirmation::new(update(2), &pair(1));
+		assert!(confirmation.verify());
+
+		confirmation.update = update(3);
+		assert!(!confirmation.verify());
+	}
+
+	#[test]
+	fn only_distinct_authorities_confirming_the_same_update_are_counted() {
+		let (alice, bob, charlie) = (pair(1), pair(2), pair(3));
+		let confirmations = vec![
+			StateConfirmation::new(update(2), &alice),
+			StateConfirmation::new(update(2), &alice),
+			StateConfirmation::new(update(3), &bob),
+			StateConfirmation::new(update(2), &charlie),
+		];
+
+		assert_eq!(
+			1,
+			count_confirmations(&update(2), &confirmations, &authorities(&[&alice, &bob]))
+		);
+		assert_eq!(
+			2,
+			count_confirmations(
+				&update(2),
+				&confirmations,
+				&authorities(&[&alice, &bob, &charlie])
+			)
+		);
+	}
+}
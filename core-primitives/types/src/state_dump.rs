@@ -0,0 +1,41 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Plain key/value dump of a shard state, as written by the `dump-state` command of the worker.
+
+use crate::ShardIdentifier;
+use codec::{Decode, Encode};
+use sp_std::vec::Vec;
+
+/// All storage entries of a shard state at the time of the dump.
+///
+/// The state diff is not part of the dump, only the committed storage.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Encode, Decode)]
+pub struct StateDump {
+	pub shard: ShardIdentifier,
+	pub entries: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl StateDump {
+	pub fn new(shard: ShardIdentifier, entries: Vec<(Vec<u8>, Vec<u8>)>) -> Self {
+		Self { shard, entries }
+	}
+
+	pub fn get(&self, key: &[u8]) -> Option<&Vec<u8>> {
+		self.entries.iter().find(|(k, _)| k.as_slice() == key).map(|(_, v)| v)
+	}
+}
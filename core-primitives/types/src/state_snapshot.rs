@@ -0,0 +1,66 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Commitment to an encrypted shard state snapshot that was published on IPFS.
+
+use crate::{ShardIdentifier, H256};
+use codec::{Decode, Encode};
+
+/// Length of a base58 encoded IPFS CIDv0.
+pub const IPFS_CID_LENGTH: usize = 46;
+
+/// The commitment is published on the parentchain with `EnclaveBridge::publish_hash`:
+/// the state hash is the published hash, the shard the topic and the CID the data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct StateSnapshotCommitment {
+	pub shard: ShardIdentifier,
+	/// Hash of the (unencrypted) state, as computed by the state handler.
+	pub state_hash: H256,
+	/// Base58 encoded IPFS CID of the snapshot, encrypted with the state key.
+	pub cid: [u8; IPFS_CID_LENGTH],
+}
+
+impl StateSnapshotCommitment {
+	/// Size of the SCALE encoded commitment, all fields have a fixed size.
+	pub const ENCODED_SIZE: usize = 32 + 32 + IPFS_CID_LENGTH;
+
+	pub fn new(shard: ShardIdentifier, state_hash: H256, cid: [u8; IPFS_CID_LENGTH]) -> Self {
+		Self { shard, state_hash, cid }
+	}
+
+	/// The CID as string, `None` if it is not valid UTF-8.
+	pub fn cid_str(&self) -> Option<&str> {
+		core::str::from_utf8(&self.cid).ok()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn encoded_size_matches_encoding() {
+		let commitment = StateSnapshotCommitment::new(
+			ShardIdentifier::repeat_byte(1),
+			H256::repeat_byte(2),
+			[b'Q'; IPFS_CID_LENGTH],
+		);
+
+		assert_eq!(commitment.encode().len(), StateSnapshotCommitment::ENCODED_SIZE);
+		assert_eq!(commitment.cid_str(), Some("Q".repeat(IPFS_CID_LENGTH).as_str()));
+	}
+}
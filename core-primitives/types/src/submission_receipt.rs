@@ -0,0 +1,111 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Receipts for submitted trusted operations.
+//!
+//! With `author_submitExtrinsicWithReceipt` the enclave signs a [SubmissionReceipt] as soon as
+//! the operation was accepted into the top pool. The receipt commits to the hash of the
+//! (decrypted) trusted operation, which the client can compute itself. Together with the
+//! signature of a registered enclave it proves that the operation was submitted to the shard at
+//! the given time, e.g. to dispute censorship if it never makes it into a block.
+
+use crate::{ShardIdentifier, H256};
+use codec::{Decode, Encode};
+use sp_core::{ed25519, Pair};
+use sp_runtime::traits::Verify;
+use sp_std::vec::Vec;
+
+/// Prefix of the signed payload, such that a receipt signature can't be mistaken for a
+/// signature of the enclave over anything else.
+pub const SUBMISSION_RECEIPT_CONTEXT: &[u8] = b"integritee/submission-receipt";
+
+/// Statement that the operation with `operation_hash` was accepted into the top pool of `shard`.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct SubmissionReceipt {
+	pub shard: ShardIdentifier,
+	pub operation_hash: H256,
+	/// Unix timestamp in milliseconds, at which the operation was accepted into the pool.
+	pub accepted_at: u64,
+}
+
+impl SubmissionReceipt {
+	pub fn signing_payload(&self) -> Vec<u8> {
+		(SUBMISSION_RECEIPT_CONTEXT, self).encode()
+	}
+}
+
+/// A [SubmissionReceipt] signed by the enclave's signing key, i.e. the enclave account.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct SignedSubmissionReceipt {
+	pub receipt: SubmissionReceipt,
+	pub signer: ed25519::Public,
+	pub signature: ed25519::Signature,
+}
+
+impl SignedSubmissionReceipt {
+	pub fn new(receipt: SubmissionReceipt, signer: &ed25519::Pair) -> Self {
+		let signature = signer.sign(receipt.signing_payload().as_slice());
+		Self { receipt, signer: signer.public(), signature }
+	}
+
+	/// Verifies the signature. Whether the signer is a registered enclave of the shard has to be
+	/// checked separately.
+	pub fn verify(&self) -> bool {
+		self.signature.verify(self.receipt.signing_payload().as_slice(), &self.signer)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn receipt() -> SubmissionReceipt {
+		SubmissionReceipt {
+			shard: ShardIdentifier::repeat_byte(1),
+			operation_hash: H256::repeat_byte(2),
+			accepted_at: 1_700_000_000_000,
+		}
+	}
+
+	#[test]
+	fn signed_receipt_verifies() {
+		let signed = SignedSubmissionReceipt::new(receipt(), &ed25519::Pair::from_seed(&[3; 32]));
+
+		assert!(signed.verify());
+	}
+
+	#[test]
+	fn tampered_receipt_does_not_verify() {
+		let mut signed =
+			SignedSubmissionReceipt::new(receipt(), &ed25519::Pair::from_seed(&[3; 32]));
+		signed.receipt.operation_hash = H256::repeat_byte(9);
+
+		assert!(!signed.verify());
+	}
+
+	#[test]
+	fn signature_over_plain_receipt_is_rejected() {
+		let pair = ed25519::Pair::from_seed(&[3; 32]);
+		let signed = SignedSubmissionReceipt {
+			receipt: receipt(),
+			signer: pair.public(),
+			signature: pair.sign(receipt().encode().as_slice()),
+		};
+
+		assert!(!signed.verify());
+	}
+}
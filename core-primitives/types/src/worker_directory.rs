@@ -0,0 +1,144 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Records of the worker directory.
+//!
+//! Each worker periodically publishes a [WorkerRecord] signed by its enclave signing key with the
+//! enclave bridge's `publish_hash` call. The record tells clients where the worker can be reached
+//! and which shards it serves, such that they can pick a healthy worker for a shard instead of
+//! hard-coding a worker URL. When the worker was attested is taken from its registration in
+//! teerex, not from the record.
+//!
+//! The published hash is [SignedWorkerRecord::hash], the published data the encoded
+//! [SignedWorkerRecord] and the topics the shards of the record.
+
+use crate::ShardIdentifier;
+use codec::{Decode, Encode};
+use sp_core::{ed25519, hashing::blake2_256, Pair};
+use sp_runtime::traits::Verify;
+use sp_std::vec::Vec;
+
+/// Prefix of the signed payload, such that a record signature can't be mistaken for a
+/// signature of the enclave over anything else.
+pub const WORKER_RECORD_CONTEXT: &[u8] = b"integritee/worker-record";
+
+/// Endpoints and status of a worker, as seen by its enclave.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct WorkerRecord {
+	pub mrenclave: [u8; 32],
+	/// External URL of the trusted (direct invocation) RPC server.
+	pub trusted_rpc_url: Vec<u8>,
+	/// External URL of the untrusted RPC server.
+	pub untrusted_rpc_url: Vec<u8>,
+	/// Shards the worker has a state of.
+	pub shards: Vec<ShardIdentifier>,
+	pub stf_version: u32,
+	/// Unix timestamp in milliseconds, at which the record was created.
+	pub published_at: u64,
+}
+
+impl WorkerRecord {
+	pub fn signing_payload(&self) -> Vec<u8> {
+		(WORKER_RECORD_CONTEXT, self).encode()
+	}
+
+	pub fn serves(&self, shard: &ShardIdentifier) -> bool {
+		self.shards.contains(shard)
+	}
+
+	/// Whether the record was published at most `max_age_millis` before `now`.
+	pub fn is_fresh(&self, now: u64, max_age_millis: u64) -> bool {
+		self.published_at <= now && now - self.published_at <= max_age_millis
+	}
+
+	pub fn trusted_rpc_url_str(&self) -> Option<&str> {
+		core::str::from_utf8(&self.trusted_rpc_url).ok()
+	}
+
+	pub fn untrusted_rpc_url_str(&self) -> Option<&str> {
+		core::str::from_utf8(&self.untrusted_rpc_url).ok()
+	}
+}
+
+/// A [WorkerRecord] signed by the enclave's signing key, i.e. the enclave account.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct SignedWorkerRecord {
+	pub record: WorkerRecord,
+	pub signer: ed25519::Public,
+	pub signature: ed25519::Signature,
+}
+
+impl SignedWorkerRecord {
+	pub fn new(record: WorkerRecord, signer: &ed25519::Pair) -> Self {
+		let signature = signer.sign(record.signing_payload().as_slice());
+		Self { record, signer: signer.public(), signature }
+	}
+
+	/// Verifies the signature. Whether the signer is a registered enclave has to be checked
+	/// separately.
+	pub fn verify(&self) -> bool {
+		self.signature.verify(self.record.signing_payload().as_slice(), &self.signer)
+	}
+
+	/// Hash under which the record is published on the parentchain.
+	pub fn hash(&self) -> [u8; 32] {
+		blake2_256(&self.encode())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn record() -> WorkerRecord {
+		WorkerRecord {
+			mrenclave: [1; 32],
+			trusted_rpc_url: b"wss://worker.example:2000".to_vec(),
+			untrusted_rpc_url: b"ws://worker.example:2001".to_vec(),
+			shards: vec![ShardIdentifier::repeat_byte(2)],
+			stf_version: 1,
+			published_at: 1_700_000_600_000,
+		}
+	}
+
+	#[test]
+	fn signed_record_verifies() {
+		let signed = SignedWorkerRecord::new(record(), &ed25519::Pair::from_seed(&[3; 32]));
+
+		assert!(signed.verify());
+		assert_eq!(signed.record.trusted_rpc_url_str(), Some("wss://worker.example:2000"));
+	}
+
+	#[test]
+	fn tampered_record_does_not_verify() {
+		let mut signed = SignedWorkerRecord::new(record(), &ed25519::Pair::from_seed(&[3; 32]));
+		signed.record.trusted_rpc_url = b"wss://attacker.example:2000".to_vec();
+
+		assert!(!signed.verify());
+	}
+
+	#[test]
+	fn freshness_and_shards_are_checked() {
+		let record = record();
+
+		assert!(record.serves(&ShardIdentifier::repeat_byte(2)));
+		assert!(!record.serves(&ShardIdentifier::repeat_byte(3)));
+		assert!(record.is_fresh(record.published_at + 1000, 1000));
+		assert!(!record.is_fresh(record.published_at + 1001, 1000));
+		assert!(!record.is_fresh(record.published_at - 1, 1000));
+	}
+}
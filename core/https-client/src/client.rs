@@ -0,0 +1,162 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use crate::sgx_reexport_prelude::*;
+
+use crate::{
+	error::{Error, Result},
+	http::{HttpsRequest, HttpsResponse},
+	stream::OCallTcpStream,
+};
+use itp_ocall_api::HttpsClientOCallApi;
+use log::*;
+use std::{
+	io::{ErrorKind, Read, Write},
+	string::{String, ToString},
+	sync::Arc,
+	vec::Vec,
+};
+
+/// Maximal size of a response in bytes, larger responses are rejected.
+pub const MAX_RESPONSE_SIZE: usize = 4 * 1024 * 1024;
+
+pub trait SendHttpsRequest {
+	fn send(&self, request: &HttpsRequest) -> Result<HttpsResponse>;
+}
+
+/// HTTPS client that terminates the TLS session inside the enclave.
+///
+/// Only servers whose certificate chain ends in one of the pinned root certificates are
+/// accepted.
+pub struct HttpsClient<OCallApi> {
+	ocall_api: Arc<OCallApi>,
+	tls_config: Arc<rustls::ClientConfig>,
+}
+
+impl<OCallApi: HttpsClientOCallApi> HttpsClient<OCallApi> {
+	/// Creates a client trusting only the given (PEM encoded) root certificates.
+	pub fn new(ocall_api: Arc<OCallApi>, root_certificates: &[String]) -> Result<Self> {
+		let mut tls_config = rustls::ClientConfig::new();
+		for certificate in root_certificates.iter() {
+			if tls_config.root_store.add_pem_file(&mut certificate.as_bytes()).is_err() {
+				warn!("Ignoring root certificate that could not be parsed");
+			}
+		}
+		if tls_config.root_store.is_empty() {
+			return Err(Error::InvalidRootCertificates)
+		}
+
+		Ok(HttpsClient { ocall_api, tls_config: Arc::new(tls_config) })
+	}
+}
+
+impl<OCallApi: HttpsClientOCallApi> SendHttpsRequest for HttpsClient<OCallApi> {
+	fn send(&self, request: &HttpsRequest) -> Result<HttpsResponse> {
+		let encoded_request = request.to_bytes()?;
+		let (host, port) = request.host_and_port()?;
+		let dns_name = webpki::DNSNameRef::try_from_ascii_str(host)
+			.map_err(|_| Error::InvalidDnsName(host.to_string()))?;
+
+		let mut session = rustls::ClientSession::new(&self.tls_config, dns_name);
+		let mut socket = OCallTcpStream::connect(self.ocall_api.clone(), host, port)?;
+		let mut tls = rustls::Stream::new(&mut session, &mut socket);
+
+		tls.write_all(&encoded_request)?;
+		debug!("Sent {} {} over relayed TLS connection", request.method.as_str(), request.url);
+
+		let raw_response = read_to_end_limited(&mut tls, MAX_RESPONSE_SIZE)?;
+		HttpsResponse::parse(&raw_response)
+	}
+}
+
+/// Reads until the server closed the TLS session, at most `limit` bytes.
+///
+/// Without a close_notify alert, an attacker relaying the TCP connection could truncate
+/// the response by closing the socket, so a plain EOF is an error.
+fn read_to_end_limited(reader: &mut impl Read, limit: usize) -> Result<Vec<u8>> {
+	let mut response = Vec::new();
+	let mut buffer = [0u8; 4096];
+	loop {
+		match reader.read(&mut buffer) {
+			// Returned by rustls if the socket was closed without a close_notify alert.
+			Ok(0) => return Err(Error::TruncatedResponse),
+			Ok(n) => {
+				if response.len() + n > limit {
+					return Err(Error::ResponseTooLarge(limit))
+				}
+				response.extend_from_slice(&buffer[..n]);
+			},
+			// Returned by rustls if the server closed the connection with a close_notify alert.
+			Err(e) if e.kind() == ErrorKind::ConnectionAborted => return Ok(response),
+			Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+			Err(e) => return Err(e.into()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itp_test::mock::https_client_ocall_mock::HttpsClientOCallMock;
+
+	#[test]
+	fn client_without_valid_root_certificate_cannot_be_created() {
+		let ocall_api = Arc::new(HttpsClientOCallMock::default());
+
+		let result = HttpsClient::new(ocall_api, &["not a certificate".to_string()]);
+
+		assert!(matches!(result, Err(Error::InvalidRootCertificates)));
+	}
+
+	/// Reader that behaves like a rustls stream, ending with a close_notify or a plain EOF.
+	struct TlsReader {
+		data: Vec<u8>,
+		close_notify: bool,
+	}
+
+	impl Read for TlsReader {
+		fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+			if self.data.is_empty() {
+				return if self.close_notify {
+					Err(ErrorKind::ConnectionAborted.into())
+				} else {
+					Ok(0)
+				}
+			}
+			let n = buf.len().min(self.data.len());
+			buf[..n].copy_from_slice(&self.data[..n]);
+			self.data.drain(..n);
+			Ok(n)
+		}
+	}
+
+	#[test]
+	fn reading_stops_at_limit() {
+		let mut reader = TlsReader { data: vec![1u8; 10], close_notify: true };
+		assert!(matches!(read_to_end_limited(&mut reader, 9), Err(Error::ResponseTooLarge(9))));
+
+		let mut reader = TlsReader { data: vec![1u8; 10], close_notify: true };
+		assert_eq!(read_to_end_limited(&mut reader, 10).unwrap().len(), 10);
+	}
+
+	#[test]
+	fn eof_without_close_notify_is_a_truncated_response() {
+		let mut reader = TlsReader { data: vec![1u8; 10], close_notify: false };
+
+		assert!(matches!(read_to_end_limited(&mut reader, 10), Err(Error::TruncatedResponse)));
+	}
+}
@@ -0,0 +1,45 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use crate::sgx_reexport_prelude::*;
+
+use sgx_types::sgx_status_t;
+use std::string::String;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	#[error("Invalid URL: {0}")]
+	InvalidUrl(String),
+	#[error("Only https URLs are supported, got: {0}")]
+	UnsupportedScheme(String),
+	#[error("Invalid DNS name: {0}")]
+	InvalidDnsName(String),
+	#[error("None of the root certificates could be parsed")]
+	InvalidRootCertificates,
+	#[error("TCP relay o-call failed: {0}")]
+	OCall(sgx_status_t),
+	#[error("Invalid HTTP response: {0}")]
+	InvalidResponse(String),
+	#[error("Response exceeds the maximal size of {0} bytes")]
+	ResponseTooLarge(usize),
+	#[error("Connection was closed without a TLS close_notify, the response may be truncated")]
+	TruncatedResponse,
+	#[error("IO error: {0}")]
+	Io(#[from] std::io::Error),
+}
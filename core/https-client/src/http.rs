@@ -0,0 +1,272 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Minimal HTTP/1.1 encoding of requests and parsing of responses.
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use crate::sgx_reexport_prelude::*;
+
+use crate::error::{Error, Result};
+use std::{
+	format,
+	string::{String, ToString},
+	vec::Vec,
+};
+use url::Url;
+
+/// Maximal number of headers of a response.
+const MAX_RESPONSE_HEADERS: usize = 64;
+
+const HTTPS_DEFAULT_PORT: u16 = 443;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+	Get,
+	Post,
+	Put,
+	Delete,
+}
+
+impl Method {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Method::Get => "GET",
+			Method::Post => "POST",
+			Method::Put => "PUT",
+			Method::Delete => "DELETE",
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpsRequest {
+	pub method: Method,
+	pub url: Url,
+	pub headers: Vec<(String, String)>,
+	pub body: Vec<u8>,
+}
+
+impl HttpsRequest {
+	pub fn new(method: Method, url: Url) -> Self {
+		HttpsRequest { method, url, headers: Vec::new(), body: Vec::new() }
+	}
+
+	pub fn get(url: Url) -> Self {
+		Self::new(Method::Get, url)
+	}
+
+	pub fn post(url: Url, body: Vec<u8>) -> Self {
+		Self::new(Method::Post, url).with_body(body)
+	}
+
+	pub fn with_header(mut self, name: &str, value: &str) -> Self {
+		self.headers.push((name.to_string(), value.to_string()));
+		self
+	}
+
+	pub fn with_body(mut self, body: Vec<u8>) -> Self {
+		self.body = body;
+		self
+	}
+
+	/// Host and port to connect to, only `https` URLs are accepted.
+	pub fn host_and_port(&self) -> Result<(&str, u16)> {
+		if self.url.scheme() != "https" {
+			return Err(Error::UnsupportedScheme(self.url.scheme().to_string()))
+		}
+		let host = self.url.host_str().ok_or_else(|| Error::InvalidUrl(self.url.to_string()))?;
+		Ok((host, self.url.port().unwrap_or(HTTPS_DEFAULT_PORT)))
+	}
+
+	/// HTTP/1.1 encoding of the request. The server is asked to close the connection after
+	/// the response, so the response ends with the connection.
+	pub fn to_bytes(&self) -> Result<Vec<u8>> {
+		let (host, port) = self.host_and_port()?;
+		let host_header = match self.url.port() {
+			Some(_) => format!("{}:{}", host, port),
+			None => host.to_string(),
+		};
+		let target = match self.url.query() {
+			Some(query) => format!("{}?{}", self.url.path(), query),
+			None => self.url.path().to_string(),
+		};
+
+		let mut head = format!(
+			"{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Length: {}\r\n",
+			self.method.as_str(),
+			target,
+			host_header,
+			self.body.len()
+		);
+		for (name, value) in self.headers.iter() {
+			head.push_str(&format!("{}: {}\r\n", name, value));
+		}
+		head.push_str("\r\n");
+
+		let mut bytes = head.into_bytes();
+		bytes.extend_from_slice(&self.body);
+		Ok(bytes)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpsResponse {
+	pub status: u16,
+	pub headers: Vec<(String, String)>,
+	pub body: Vec<u8>,
+}
+
+impl HttpsResponse {
+	pub fn is_success(&self) -> bool {
+		(200..300).contains(&self.status)
+	}
+
+	/// Value of the first header with the given name, compared case-insensitively.
+	pub fn header(&self, name: &str) -> Option<&str> {
+		self.headers
+			.iter()
+			.find(|(n, _)| n.eq_ignore_ascii_case(name))
+			.map(|(_, v)| v.as_str())
+	}
+
+	/// Parses a complete response, as read until the server closed the connection.
+	pub fn parse(raw: &[u8]) -> Result<Self> {
+		let mut headers = [httparse::EMPTY_HEADER; MAX_RESPONSE_HEADERS];
+		let mut response = httparse::Response::new(&mut headers);
+		let header_len = match response.parse(raw) {
+			Ok(httparse::Status::Complete(len)) => len,
+			Ok(httparse::Status::Partial) =>
+				return Err(Error::InvalidResponse("incomplete header".to_string())),
+			Err(e) => return Err(Error::InvalidResponse(format!("{:?}", e))),
+		};
+
+		let status = response
+			.code
+			.ok_or_else(|| Error::InvalidResponse("missing status code".to_string()))?;
+		let headers: Vec<(String, String)> = response
+			.headers
+			.iter()
+			.map(|h| (h.name.to_string(), String::from_utf8_lossy(h.value).to_string()))
+			.collect();
+
+		let mut parsed = HttpsResponse { status, headers, body: Vec::new() };
+		let payload = &raw[header_len..];
+
+		parsed.body = if parsed
+			.header("Transfer-Encoding")
+			.map_or(false, |v| v.to_ascii_lowercase().contains("chunked"))
+		{
+			decode_chunked(payload)?
+		} else if let Some(length) = parsed.header("Content-Length") {
+			let length: usize = length.trim().parse().map_err(|_| {
+				Error::InvalidResponse(format!("invalid content length {}", length))
+			})?;
+			payload
+				.get(..length)
+				.ok_or_else(|| Error::InvalidResponse("body shorter than content length".into()))?
+				.to_vec()
+		} else {
+			payload.to_vec()
+		};
+
+		Ok(parsed)
+	}
+}
+
+/// Decodes a body with chunked transfer encoding. Chunk extensions and trailers are ignored.
+fn decode_chunked(mut payload: &[u8]) -> Result<Vec<u8>> {
+	let invalid = |reason: &str| Error::InvalidResponse(format!("invalid chunk: {}", reason));
+	let mut body = Vec::new();
+
+	loop {
+		let line_end = find_crlf(payload).ok_or_else(|| invalid("missing size"))?;
+		let size_line = core::str::from_utf8(&payload[..line_end]).map_err(|_| invalid("size"))?;
+		let size_hex = size_line.split(';').next().unwrap_or_default().trim();
+		let size = usize::from_str_radix(size_hex, 16).map_err(|_| invalid("size"))?;
+		payload = &payload[line_end + 2..];
+
+		if size == 0 {
+			return Ok(body)
+		}
+
+		let chunk = payload.get(..size).ok_or_else(|| invalid("truncated"))?;
+		body.extend_from_slice(chunk);
+		payload = payload.get(size + 2..).ok_or_else(|| invalid("truncated"))?;
+	}
+}
+
+fn find_crlf(bytes: &[u8]) -> Option<usize> {
+	bytes.windows(2).position(|w| w == b"\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn url(url: &str) -> Url {
+		Url::parse(url).unwrap()
+	}
+
+	#[test]
+	fn request_is_encoded_with_host_and_body() {
+		let request = HttpsRequest::post(url("https://example.com:8443/api?x=1"), b"{}".to_vec())
+			.with_header("Content-Type", "application/json");
+
+		let encoded = String::from_utf8(request.to_bytes().unwrap()).unwrap();
+
+		assert_eq!(
+			encoded,
+			"POST /api?x=1 HTTP/1.1\r\nHost: example.com:8443\r\nConnection: close\r\n\
+			Content-Length: 2\r\nContent-Type: application/json\r\n\r\n{}"
+		);
+		assert_eq!(request.host_and_port().unwrap(), ("example.com", 8443));
+	}
+
+	#[test]
+	fn plain_http_is_rejected() {
+		let request = HttpsRequest::get(url("http://example.com"));
+
+		assert!(matches!(request.to_bytes(), Err(Error::UnsupportedScheme(_))));
+	}
+
+	#[test]
+	fn response_with_content_length_is_parsed() {
+		let raw = b"HTTP/1.1 200 OK\r\ncontent-length: 5\r\nX-Test: a\r\n\r\nhello";
+
+		let response = HttpsResponse::parse(raw).unwrap();
+
+		assert!(response.is_success());
+		assert_eq!(response.header("x-test"), Some("a"));
+		assert_eq!(response.body, b"hello".to_vec());
+	}
+
+	#[test]
+	fn chunked_response_is_decoded() {
+		let raw =
+			b"HTTP/1.1 404 Not Found\r\nTransfer-Encoding: chunked\r\n\r\n4\r\nWiki\r\n6;x=y\r\npedia!\r\n0\r\n\r\n";
+
+		let response = HttpsResponse::parse(raw).unwrap();
+
+		assert_eq!(response.status, 404);
+		assert_eq!(response.body, b"Wikipedia!".to_vec());
+	}
+
+	#[test]
+	fn truncated_response_is_rejected() {
+		assert!(HttpsResponse::parse(b"HTTP/1.1 200 OK\r\nContent-Le").is_err());
+		assert!(HttpsResponse::parse(b"HTTP/1.1 200 OK\r\nContent-Length: 9\r\n\r\nhi").is_err());
+	}
+}
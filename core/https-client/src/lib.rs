@@ -0,0 +1,49 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! HTTPS client for the enclave, with the TLS session terminated inside the enclave.
+//!
+//! The untrusted worker only opens the TCP connection and relays the encrypted TLS records
+//! through the [HttpsClientOCallApi](itp_ocall_api::HttpsClientOCallApi) o-calls. The server
+//! certificate is validated in the enclave against the root certificates pinned by the caller,
+//! so STF extensions and oracles can fetch external data without trusting the worker.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(all(feature = "std", feature = "sgx"))]
+compile_error!("feature \"std\" and feature \"sgx\" cannot be enabled at the same time");
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+#[macro_use]
+extern crate sgx_tstd as std;
+
+// re-export module to properly feature gate sgx and regular std environment
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+pub mod sgx_reexport_prelude {
+	pub use rustls_sgx as rustls;
+	pub use thiserror_sgx as thiserror;
+	pub use url_sgx as url;
+	pub use webpki_sgx as webpki;
+}
+
+pub use client::{HttpsClient, SendHttpsRequest};
+pub use error::{Error, Result};
+pub use http::{HttpsRequest, HttpsResponse, Method};
+
+pub mod client;
+pub mod error;
+pub mod http;
+pub mod stream;
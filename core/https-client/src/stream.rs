@@ -0,0 +1,111 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Byte stream over a TCP connection that the untrusted worker relays through o-calls.
+
+use crate::error::{Error, Result};
+use itp_ocall_api::{HttpsClientOCallApi, TcpConnectionId};
+use log::*;
+use std::{
+	format,
+	io::{self, Read, Write},
+	sync::Arc,
+};
+
+/// Maximal number of bytes received with a single o-call.
+pub const MAX_RECEIVE_CHUNK: usize = 16 * 1024;
+
+/// TCP connection opened by the untrusted worker, the connection is closed when dropped.
+///
+/// Only encrypted TLS records should be written to it, it is not confidential by itself.
+pub struct OCallTcpStream<OCallApi: HttpsClientOCallApi> {
+	ocall_api: Arc<OCallApi>,
+	connection: TcpConnectionId,
+}
+
+impl<OCallApi: HttpsClientOCallApi> OCallTcpStream<OCallApi> {
+	pub fn connect(ocall_api: Arc<OCallApi>, host: &str, port: u16) -> Result<Self> {
+		let connection = ocall_api.tcp_connect(host, port).map_err(Error::OCall)?;
+		debug!("Opened relayed TCP connection {} to {}:{}", connection, host, port);
+		Ok(OCallTcpStream { ocall_api, connection })
+	}
+
+	pub fn connection(&self) -> TcpConnectionId {
+		self.connection
+	}
+}
+
+impl<OCallApi: HttpsClientOCallApi> Read for OCallTcpStream<OCallApi> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let max_len = buf.len().min(MAX_RECEIVE_CHUNK);
+		let received = self
+			.ocall_api
+			.tcp_receive(self.connection, max_len as u32)
+			.map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{:?}", e)))?;
+		if received.len() > max_len {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "received too many bytes"))
+		}
+		buf[..received.len()].copy_from_slice(&received);
+		Ok(received.len())
+	}
+}
+
+impl<OCallApi: HttpsClientOCallApi> Write for OCallTcpStream<OCallApi> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.ocall_api
+			.tcp_send(self.connection, buf)
+			.map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{:?}", e)))?;
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+impl<OCallApi: HttpsClientOCallApi> Drop for OCallTcpStream<OCallApi> {
+	fn drop(&mut self) {
+		if let Err(e) = self.ocall_api.tcp_close(self.connection) {
+			warn!("Failed to close relayed TCP connection {}: {:?}", self.connection, e);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itp_test::mock::https_client_ocall_mock::HttpsClientOCallMock;
+
+	#[test]
+	fn bytes_are_relayed_and_connection_is_closed_on_drop() {
+		let ocall_api = Arc::new(HttpsClientOCallMock::new(b"response".to_vec()));
+
+		let connection = {
+			let mut stream =
+				OCallTcpStream::connect(ocall_api.clone(), "example.com", 443).unwrap();
+			stream.write_all(b"request").unwrap();
+
+			let mut received = Vec::new();
+			stream.read_to_end(&mut received).unwrap();
+			assert_eq!(received, b"response".to_vec());
+			stream.connection()
+		};
+
+		assert_eq!(ocall_api.connected(), vec![("example.com".to_string(), 443)]);
+		assert_eq!(ocall_api.sent(connection), b"request".to_vec());
+		assert!(ocall_api.is_closed(connection));
+	}
+}
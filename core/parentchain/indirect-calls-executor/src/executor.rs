@@ -125,7 +125,8 @@ impl<
 		ParentchainEventHandler,
 		TCS,
 		G,
-	> where
+	>
+where
 	ShieldingKeyRepository: AccessKey,
 	<ShieldingKeyRepository as AccessKey>::KeyType: ShieldingCryptoDecrypt<Error = itp_sgx_crypto::Error>
 		+ ShieldingCryptoEncrypt<Error = itp_sgx_crypto::Error>,
@@ -252,7 +253,8 @@ impl<
 		PrivacySidechain,
 		TCS,
 		G,
-	> where
+	>
+where
 	ShieldingKeyRepository: AccessKey,
 	<ShieldingKeyRepository as AccessKey>::KeyType: ShieldingCryptoDecrypt<Error = itp_sgx_crypto::Error>
 		+ ShieldingCryptoEncrypt<Error = itp_sgx_crypto::Error>,
@@ -287,6 +289,10 @@ impl<
 		self.top_pool_author.list_handled_shards().first().copied().unwrap_or_default()
 	}
 
+	fn list_handled_shards(&self) -> Vec<ShardIdentifier> {
+		self.top_pool_author.list_handled_shards()
+	}
+
 	fn sign_call_with_self<TC: Encode + Debug + TrustedCallSigning<TCS>>(
 		&self,
 		trusted_call: &TC,
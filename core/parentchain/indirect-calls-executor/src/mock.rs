@@ -14,7 +14,10 @@ use itp_sgx_runtime_primitives::types::{AccountId, Balance};
 use itp_stf_primitives::{traits::IndirectExecutor, types::Signature};
 use itp_test::mock::stf_mock::{GetterMock, TrustedCallMock, TrustedCallSignedMock};
 use itp_types::{
-	parentchain::{BalanceTransfer, ExtrinsicStatus, FilterEvents, HandleParentchainEvents},
+	parentchain::{
+		AssetTransfer, BalanceTransfer, ExtrinsicStatus, FilterEvents, HandleParentchainEvents,
+		PublishedHash, Remarked,
+	},
 	Address, Request, ShardIdentifier, H256,
 };
 use log::*;
@@ -175,6 +178,18 @@ impl FilterEvents for MockEvents {
 		};
 		Ok(Vec::from([transfer]))
 	}
+
+	fn get_asset_transfer_events(&self) -> core::result::Result<Vec<AssetTransfer>, Self::Error> {
+		Ok(Vec::new())
+	}
+
+	fn get_published_hash_events(&self) -> core::result::Result<Vec<PublishedHash>, Self::Error> {
+		Ok(Vec::new())
+	}
+
+	fn get_remark_events(&self) -> core::result::Result<Vec<Remarked>, Self::Error> {
+		Ok(Vec::new())
+	}
 }
 
 pub struct MockParentchainEventHandler {}
@@ -23,6 +23,7 @@ use crate::{
 };
 use codec::Encode;
 use core::iter::Iterator;
+use itp_memory_accounting::Subsystem;
 use itp_ocall_api::EnclaveOnChainOCallApi;
 use itp_storage::{Error as StorageError, StorageProof, StorageProofChecker};
 use itp_types::parentchain::{IdentifyParentchain, ParentchainId};
@@ -139,6 +140,7 @@ where
 	OCallApi: EnclaveOnChainOCallApi,
 {
 	fn submit_block(&mut self, signed_block: &SignedBlock<Block>) -> Result<(), Error> {
+		let _scope = itp_memory_accounting::enter(Subsystem::LightClient);
 		let header = signed_block.block.header();
 		let justifications = signed_block.justifications.clone();
 
@@ -22,8 +22,11 @@ use codec::Decode;
 use frame_metadata::RuntimeMetadataPrefixed;
 use itp_api_client_types::Metadata;
 use itp_rpc::{RpcRequest, RpcResponse, RpcReturnValue};
-use itp_types::DirectRequestStatus;
-use itp_utils::FromHexPrefixed;
+use itp_types::{
+	enclave_upgrade::{KeyHandoverRequest, KeyHandoverResponse},
+	DirectRequestStatus,
+};
+use itp_utils::{FromHexPrefixed, ToHexPrefixed};
 use log::*;
 use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
 use std::{
@@ -51,6 +54,10 @@ pub trait DirectApi {
 	fn get_mu_ra_url(&self) -> Result<String>;
 	fn get_untrusted_worker_url(&self) -> Result<String>;
 	fn get_state_metadata(&self) -> Result<Metadata>;
+	/// Target info of the worker's enclave, for local attestation on the same platform.
+	fn get_enclave_target_info(&self) -> Result<Vec<u8>>;
+	/// Requests the keys of the worker's enclave for the new enclave of an enclave upgrade.
+	fn hand_over_keys(&self, request: &KeyHandoverRequest) -> Result<KeyHandoverResponse>;
 
 	fn send(&self, request: &str) -> Result<()>;
 	/// Close any open websocket connection.
@@ -155,6 +162,30 @@ impl DirectApi for DirectClient {
 		Metadata::try_from(metadata).map_err(|e| e.into())
 	}
 
+	fn get_enclave_target_info(&self) -> Result<Vec<u8>> {
+		let jsonrpc_call: String = RpcRequest::compose_jsonrpc_call(
+			"author_getEnclaveTargetInfo".to_string(),
+			Default::default(),
+		)?;
+
+		let response_str = self.get(&jsonrpc_call)?;
+
+		decode_value_from_rpc_response(&response_str)
+	}
+
+	fn hand_over_keys(&self, request: &KeyHandoverRequest) -> Result<KeyHandoverResponse> {
+		let jsonrpc_call: String = RpcRequest::compose_jsonrpc_call(
+			"author_handOverKeys".to_string(),
+			vec![request.to_hex()],
+		)?;
+
+		let response_str = self.get(&jsonrpc_call)?;
+
+		let response = decode_value_from_rpc_response(&response_str)?;
+		info!("[+] Got the keys handed over by the upgraded enclave");
+		Ok(response)
+	}
+
 	fn send(&self, request: &str) -> Result<()> {
 		self.web_socket_control.send(request)
 	}
@@ -176,6 +207,17 @@ fn decode_from_rpc_response(json_rpc_response: &str) -> Result<String> {
 	}
 }
 
+fn decode_value_from_rpc_response<T: Decode>(json_rpc_response: &str) -> Result<T> {
+	let rpc_response: RpcResponse = serde_json::from_str(json_rpc_response)?;
+	let rpc_return_value = RpcReturnValue::from_hex(&rpc_response.result)
+		.map_err(|e| Error::Custom(format!("{:?}", e).into()))?;
+
+	match rpc_return_value.status {
+		DirectRequestStatus::Ok => Ok(T::decode(&mut rpc_return_value.value.as_slice())?),
+		_ => Err(Error::Status(String::decode(&mut rpc_return_value.value.as_slice())?)),
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -21,6 +21,7 @@ use crate::{direct_client::DirectApi, error::Result};
 use codec::Decode;
 use frame_metadata::RuntimeMetadataPrefixed;
 use itp_api_client_types::Metadata;
+use itp_types::enclave_upgrade::{KeyHandoverRequest, KeyHandoverResponse};
 use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
 use std::{sync::mpsc::Sender as MpscSender, thread::JoinHandle};
 
@@ -89,6 +90,14 @@ impl DirectApi for DirectClientMock {
 		Metadata::try_from(metadata).map_err(|e| e.into())
 	}
 
+	fn get_enclave_target_info(&self) -> Result<Vec<u8>> {
+		unimplemented!()
+	}
+
+	fn hand_over_keys(&self, _request: &KeyHandoverRequest) -> Result<KeyHandoverResponse> {
+		unimplemented!()
+	}
+
 	fn send(&self, _request: &str) -> Result<()> {
 		unimplemented!()
 	}
@@ -19,8 +19,10 @@ use itp_enclave_api::direct_request::DirectRequest;
 use itp_rpc::RpcRequest;
 use itp_utils::ToHexPrefixed;
 use its_peer_fetch::block_fetch_server::BlockFetchServerModuleBuilder;
-use its_primitives::types::block::SignedBlock;
-use its_rpc_handler::constants::RPC_METHOD_NAME_IMPORT_BLOCKS;
+use its_primitives::types::{block::SignedBlock, ShardIdentifier};
+use its_rpc_handler::constants::{
+	RPC_METHOD_NAME_CONFIRM_STATE_UPDATE, RPC_METHOD_NAME_IMPORT_BLOCKS,
+};
 use its_storage::interface::FetchBlocks;
 use jsonrpsee::{
 	types::error::CallError,
@@ -28,8 +30,11 @@ use jsonrpsee::{
 };
 use log::debug;
 use std::{net::SocketAddr, sync::Arc};
+use substrate_compat::SubstrateCompatModuleBuilder;
 use tokio::net::ToSocketAddrs;
 
+pub mod substrate_compat;
+
 #[cfg(test)]
 mod mock;
 #[cfg(test)]
@@ -39,6 +44,7 @@ pub async fn run_server<Enclave, FetchSidechainBlocks>(
 	addr: impl ToSocketAddrs,
 	enclave: Arc<Enclave>,
 	sidechain_block_fetcher: Arc<FetchSidechainBlocks>,
+	shard: ShardIdentifier,
 ) -> anyhow::Result<SocketAddr>
 where
 	Enclave: DirectRequest,
@@ -47,7 +53,7 @@ where
 	let mut server = WsServerBuilder::default().build(addr).await?;
 
 	// FIXME: import block should be moved to trusted side.
-	let mut import_sidechain_block_module = RpcModule::new(enclave);
+	let mut import_sidechain_block_module = RpcModule::new(enclave.clone());
 	import_sidechain_block_module.register_method(
 		RPC_METHOD_NAME_IMPORT_BLOCKS,
 		|params, enclave| {
@@ -64,13 +70,35 @@ where
 				.map_err(|e| CallError::Failed(e.into()))
 		},
 	)?;
+	import_sidechain_block_module.register_method(
+		RPC_METHOD_NAME_CONFIRM_STATE_UPDATE,
+		|params, enclave| {
+			debug!("{} params: {:?}", RPC_METHOD_NAME_CONFIRM_STATE_UPDATE, params);
+
+			// The request is encrypted with the state key, it is forwarded as it is.
+			let enclave_req = RpcRequest::compose_jsonrpc_call(
+				RPC_METHOD_NAME_CONFIRM_STATE_UPDATE.into(),
+				vec![params.one::<String>()?],
+			)
+			.unwrap();
+
+			enclave
+				.rpc(enclave_req.as_bytes().to_vec())
+				.map_err(|e| CallError::Failed(e.into()))
+		},
+	)?;
 	server.register_module(import_sidechain_block_module).unwrap();
 
-	let fetch_sidechain_blocks_module = BlockFetchServerModuleBuilder::new(sidechain_block_fetcher)
-		.build()
-		.map_err(|e| CallError::Failed(e.to_string().into()))?; // `to_string` necessary due to no all errors implementing Send + Sync.
+	let fetch_sidechain_blocks_module =
+		BlockFetchServerModuleBuilder::new(sidechain_block_fetcher.clone())
+			.build()
+			.map_err(|e| CallError::Failed(e.to_string().into()))?; // `to_string` necessary due to no all errors implementing Send + Sync.
 	server.register_module(fetch_sidechain_blocks_module).unwrap();
 
+	let substrate_compat_module =
+		SubstrateCompatModuleBuilder::new(enclave, sidechain_block_fetcher, shard).build()?;
+	server.register_module(substrate_compat_module).unwrap();
+
 	let socket_addr = server.local_addr()?;
 	tokio::spawn(async move { server.start().await });
 
@@ -20,7 +20,7 @@ use itp_rpc::RpcResponse;
 use itp_utils::ToHexPrefixed;
 use its_primitives::{
 	traits::ShardIdentifierFor,
-	types::{BlockHash, SignedBlock, SignedBlock as SignedSidechainBlock},
+	types::{BlockHash, BlockNumber, SignedBlock, SignedBlock as SignedSidechainBlock},
 };
 use its_storage::interface::FetchBlocks;
 use parity_scale_codec::Encode;
@@ -52,4 +52,25 @@ impl FetchBlocks<SignedSidechainBlock> for MockSidechainBlockFetcher {
 	) -> its_storage::Result<Vec<SignedBlock>> {
 		Ok(Vec::new())
 	}
+	fn fetch_block_by_hash(
+		&self,
+		_block_hash: &BlockHash,
+	) -> its_storage::Result<Option<SignedBlock>> {
+		Ok(None)
+	}
+
+	fn fetch_block_by_number(
+		&self,
+		_shard_identifier: &ShardIdentifierFor<SignedBlock>,
+		_block_number: BlockNumber,
+	) -> its_storage::Result<Option<SignedBlock>> {
+		Ok(None)
+	}
+
+	fn fetch_latest_block(
+		&self,
+		_shard_identifier: &ShardIdentifierFor<SignedBlock>,
+	) -> its_storage::Result<Option<SignedBlock>> {
+		Ok(None)
+	}
 }
@@ -0,0 +1,265 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Subset of the standard Substrate RPC methods, served with sidechain blocks.
+//!
+//! Allows clients like polkadot-js apps or the substrate-api-client to follow the sidechain
+//! of the shard this worker is handling, without any sidechain specific RPC code.
+
+use itp_enclave_api::direct_request::DirectRequest;
+use itp_rpc::{RpcRequest, RpcResponse, RpcReturnValue};
+use itp_utils::FromHexPrefixed;
+use its_primitives::{
+	traits::{Block as BlockT, Header as HeaderT, SignedBlock as SignedBlockT},
+	types::{BlockHash, BlockNumber, ShardIdentifier, SignedBlock},
+};
+use its_rpc_handler::constants::{
+	RPC_METHOD_NAME_CHAIN_GET_BLOCK, RPC_METHOD_NAME_CHAIN_GET_BLOCK_HASH,
+	RPC_METHOD_NAME_CHAIN_GET_FINALIZED_HEAD, RPC_METHOD_NAME_CHAIN_GET_HEADER,
+	RPC_METHOD_NAME_CHAIN_SUBSCRIBE_NEW_HEADS, RPC_METHOD_NAME_CHAIN_UNSUBSCRIBE_NEW_HEADS,
+	RPC_METHOD_NAME_STATE_GET_RUNTIME_VERSION,
+};
+use its_storage::interface::FetchBlocks;
+use jsonrpsee::{types::error::CallError, ws_server::RpcModule};
+use log::*;
+use parity_scale_codec::Decode;
+use serde_json::{json, Value};
+use sp_version::RuntimeVersion;
+use std::{sync::Arc, thread, time::Duration};
+
+/// Interval in which the block storage is polled for new heads of a `chain_subscribeNewHeads`
+/// subscription. Should be well below the sidechain slot duration.
+const NEW_HEADS_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+pub struct SubstrateCompatContext<Enclave, FetchBlocksFromStorage> {
+	enclave: Arc<Enclave>,
+	sidechain_block_fetcher: Arc<FetchBlocksFromStorage>,
+	shard: ShardIdentifier,
+}
+
+impl<Enclave, FetchBlocksFromStorage> SubstrateCompatContext<Enclave, FetchBlocksFromStorage>
+where
+	FetchBlocksFromStorage: FetchBlocks<SignedBlock>,
+{
+	/// Fetches the block with the given hash, or the latest block if no hash is given.
+	fn block_or_latest(
+		&self,
+		maybe_block_hash: Option<BlockHash>,
+	) -> Result<Option<SignedBlock>, CallError> {
+		match maybe_block_hash {
+			Some(block_hash) => self.sidechain_block_fetcher.fetch_block_by_hash(&block_hash),
+			None => self.sidechain_block_fetcher.fetch_latest_block(&self.shard),
+		}
+		.map_err(|e| {
+			error!("Failed to fetch sidechain block from storage: {:?}", e);
+			CallError::Failed(e.into())
+		})
+	}
+}
+
+/// RPC server module builder for the Substrate compatible RPC methods.
+pub struct SubstrateCompatModuleBuilder<Enclave, FetchBlocksFromStorage> {
+	context: SubstrateCompatContext<Enclave, FetchBlocksFromStorage>,
+}
+
+impl<Enclave, FetchBlocksFromStorage> SubstrateCompatModuleBuilder<Enclave, FetchBlocksFromStorage>
+where
+	Enclave: DirectRequest,
+	FetchBlocksFromStorage: FetchBlocks<SignedBlock> + Send + Sync + 'static,
+{
+	pub fn new(
+		enclave: Arc<Enclave>,
+		sidechain_block_fetcher: Arc<FetchBlocksFromStorage>,
+		shard: ShardIdentifier,
+	) -> Self {
+		SubstrateCompatModuleBuilder {
+			context: SubstrateCompatContext { enclave, sidechain_block_fetcher, shard },
+		}
+	}
+
+	pub fn build(
+		self,
+	) -> anyhow::Result<RpcModule<SubstrateCompatContext<Enclave, FetchBlocksFromStorage>>> {
+		let mut module = RpcModule::new(self.context);
+
+		module.register_method(RPC_METHOD_NAME_CHAIN_GET_HEADER, |params, context| {
+			debug!("{}: {:?}", RPC_METHOD_NAME_CHAIN_GET_HEADER, params);
+
+			let maybe_block_hash = first_optional(params.parse()?);
+			Ok(context.block_or_latest(maybe_block_hash)?.map(|b| header_to_json(&b)))
+		})?;
+
+		module.register_method(RPC_METHOD_NAME_CHAIN_GET_BLOCK, |params, context| {
+			debug!("{}: {:?}", RPC_METHOD_NAME_CHAIN_GET_BLOCK, params);
+
+			let maybe_block_hash = first_optional(params.parse()?);
+			Ok(context.block_or_latest(maybe_block_hash)?.map(|b| block_to_json(&b)))
+		})?;
+
+		module.register_method(RPC_METHOD_NAME_CHAIN_GET_BLOCK_HASH, |params, context| {
+			debug!("{}: {:?}", RPC_METHOD_NAME_CHAIN_GET_BLOCK_HASH, params);
+
+			let maybe_block = match first_optional::<BlockNumber>(params.parse()?) {
+				Some(block_number) => context
+					.sidechain_block_fetcher
+					.fetch_block_by_number(&context.shard, block_number)
+					.map_err(|e| {
+						error!("Failed to fetch sidechain block from storage: {:?}", e);
+						CallError::Failed(e.into())
+					})?,
+				None => context.block_or_latest(None)?,
+			};
+			Ok(maybe_block.map(|b| b.hash()))
+		})?;
+
+		// Sidechain blocks are final once they are imported.
+		module.register_method(RPC_METHOD_NAME_CHAIN_GET_FINALIZED_HEAD, |_, context| {
+			debug!("{}", RPC_METHOD_NAME_CHAIN_GET_FINALIZED_HEAD);
+
+			Ok(context.block_or_latest(None)?.map(|b| b.hash()))
+		})?;
+
+		module.register_method(RPC_METHOD_NAME_STATE_GET_RUNTIME_VERSION, |_, context| {
+			debug!("{}", RPC_METHOD_NAME_STATE_GET_RUNTIME_VERSION);
+
+			let enclave_req = RpcRequest::compose_jsonrpc_call(
+				RPC_METHOD_NAME_STATE_GET_RUNTIME_VERSION.into(),
+				vec![],
+			)
+			.map_err(|e| CallError::Failed(e.into()))?;
+
+			let response = context
+				.enclave
+				.rpc(enclave_req.as_bytes().to_vec())
+				.map_err(|e| CallError::Failed(e.into()))?;
+
+			decode_runtime_version(&response).map_err(|e| CallError::Failed(e.into()))
+		})?;
+
+		module.register_subscription(
+			RPC_METHOD_NAME_CHAIN_SUBSCRIBE_NEW_HEADS,
+			RPC_METHOD_NAME_CHAIN_UNSUBSCRIBE_NEW_HEADS,
+			|_, mut sink, context| {
+				debug!("{}", RPC_METHOD_NAME_CHAIN_SUBSCRIBE_NEW_HEADS);
+
+				thread::spawn(move || {
+					let mut last_sent_hash = None;
+					loop {
+						match context.block_or_latest(None) {
+							Ok(Some(block)) if Some(block.hash()) != last_sent_hash => {
+								if sink.send(&header_to_json(&block)).is_err() {
+									debug!("New heads subscription closed");
+									return
+								}
+								last_sent_hash = Some(block.hash());
+							},
+							Err(e) => warn!("Failed to get the latest sidechain block: {:?}", e),
+							_ => {},
+						}
+						thread::sleep(NEW_HEADS_POLL_INTERVAL);
+					}
+				});
+				Ok(())
+			},
+		)?;
+
+		Ok(module)
+	}
+}
+
+/// Substrate clients pass optional parameters either as `[]`, `[null]` or not at all.
+fn first_optional<T>(params: Option<Vec<Option<T>>>) -> Option<T> {
+	params.and_then(|p| p.into_iter().next()).flatten()
+}
+
+/// Converts a sidechain block header into the JSON representation of a Substrate header.
+///
+/// The sidechain header does not contain the state root (the state is only known inside the
+/// enclave), it is therefore always zero.
+pub fn header_to_json(signed_block: &SignedBlock) -> Value {
+	let header = signed_block.block().header();
+	json!({
+		"parentHash": header.parent_hash(),
+		"number": format!("0x{:x}", header.block_number()),
+		"stateRoot": BlockHash::default(),
+		"extrinsicsRoot": header.block_data_hash(),
+		"digest": { "logs": [] },
+	})
+}
+
+/// Converts a sidechain block into the JSON representation of a Substrate signed block.
+///
+/// The extrinsics are the hashes of the trusted operations executed in the block, the
+/// operations themselves are not part of the sidechain block.
+pub fn block_to_json(signed_block: &SignedBlock) -> Value {
+	json!({
+		"block": {
+			"header": header_to_json(signed_block),
+			"extrinsics": signed_block.block().block_data().signed_top_hashes,
+		},
+		"justifications": Value::Null,
+	})
+}
+
+fn decode_runtime_version(enclave_response: &[u8]) -> Result<RuntimeVersion, String> {
+	let response_str = std::str::from_utf8(enclave_response).map_err(|e| e.to_string())?;
+	let rpc_response: RpcResponse =
+		serde_json::from_str(response_str.trim()).map_err(|e| e.to_string())?;
+	let return_value =
+		RpcReturnValue::from_hex(&rpc_response.result).map_err(|e| format!("{:?}", e))?;
+	RuntimeVersion::decode(&mut return_value.value.as_slice()).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use its_test::sidechain_block_builder::{SidechainBlockBuilder, SidechainBlockBuilderTrait};
+
+	#[test]
+	fn header_to_json_uses_substrate_field_names() {
+		let block = SidechainBlockBuilder::default().build_signed();
+		let header = header_to_json(&block);
+
+		assert_eq!(header["parentHash"], json!(block.block().header().parent_hash()));
+		assert_eq!(
+			header["number"],
+			json!(format!("0x{:x}", block.block().header().block_number()))
+		);
+		assert_eq!(header["extrinsicsRoot"], json!(block.block().header().block_data_hash()));
+		assert_eq!(header["digest"]["logs"], json!([]));
+	}
+
+	#[test]
+	fn block_to_json_lists_trusted_operation_hashes_as_extrinsics() {
+		let block = SidechainBlockBuilder::default().build_signed();
+		let json_block = block_to_json(&block);
+
+		assert_eq!(
+			json_block["block"]["extrinsics"],
+			json!(block.block().block_data().signed_top_hashes)
+		);
+		assert_eq!(json_block["block"]["header"], header_to_json(&block));
+	}
+
+	#[test]
+	fn first_optional_handles_all_optional_parameter_forms() {
+		assert_eq!(first_optional::<u64>(None), None);
+		assert_eq!(first_optional::<u64>(Some(vec![])), None);
+		assert_eq!(first_optional::<u64>(Some(vec![None])), None);
+		assert_eq!(first_optional(Some(vec![Some(3u64)])), Some(3));
+	}
+}
@@ -18,7 +18,11 @@
 use super::*;
 use crate::mock::MockSidechainBlockFetcher;
 use itp_rpc::RpcResponse;
-use its_rpc_handler::constants::RPC_METHOD_NAME_IMPORT_BLOCKS;
+use itp_utils::ToHexPrefixed;
+use its_rpc_handler::constants::{
+	RPC_METHOD_NAME_CHAIN_GET_HEADER, RPC_METHOD_NAME_CONFIRM_STATE_UPDATE,
+	RPC_METHOD_NAME_IMPORT_BLOCKS,
+};
 use its_test::sidechain_block_builder::{SidechainBlockBuilder, SidechainBlockBuilderTrait};
 use jsonrpsee::{
 	types::{to_json_value, traits::Client},
@@ -35,10 +39,14 @@ fn init() {
 #[tokio::test]
 async fn test_client_calls() {
 	init();
-	let addr =
-		run_server("127.0.0.1:0", Arc::new(TestEnclave), Arc::new(MockSidechainBlockFetcher))
-			.await
-			.unwrap();
+	let addr = run_server(
+		"127.0.0.1:0",
+		Arc::new(TestEnclave),
+		Arc::new(MockSidechainBlockFetcher),
+		ShardIdentifier::default(),
+	)
+	.await
+	.unwrap();
 	info!("ServerAddress: {:?}", addr);
 
 	let url = format!("ws://{}", addr);
@@ -54,3 +62,48 @@ async fn test_client_calls() {
 
 	assert!(RpcResponse::decode(&mut response.as_slice()).is_ok());
 }
+
+#[tokio::test]
+async fn confirm_state_update_is_forwarded_to_enclave() {
+	init();
+	let addr = run_server(
+		"127.0.0.1:0",
+		Arc::new(TestEnclave),
+		Arc::new(MockSidechainBlockFetcher),
+		ShardIdentifier::default(),
+	)
+	.await
+	.unwrap();
+
+	let url = format!("ws://{}", addr);
+	let client = WsClientBuilder::default().build(&url).await.unwrap();
+	let response: Vec<u8> = client
+		.request(
+			RPC_METHOD_NAME_CONFIRM_STATE_UPDATE,
+			vec![to_json_value(vec![1u8, 2, 3].to_hex()).unwrap()].into(),
+		)
+		.await
+		.unwrap();
+
+	assert!(RpcResponse::decode(&mut response.as_slice()).is_ok());
+}
+
+#[tokio::test]
+async fn chain_get_header_returns_none_without_sidechain_blocks() {
+	init();
+	let addr = run_server(
+		"127.0.0.1:0",
+		Arc::new(TestEnclave),
+		Arc::new(MockSidechainBlockFetcher),
+		ShardIdentifier::default(),
+	)
+	.await
+	.unwrap();
+
+	let url = format!("ws://{}", addr);
+	let client = WsClientBuilder::default().build(&url).await.unwrap();
+	let response: Option<serde_json::Value> =
+		client.request(RPC_METHOD_NAME_CHAIN_GET_HEADER, vec![].into()).await.unwrap();
+
+	assert!(response.is_none());
+}
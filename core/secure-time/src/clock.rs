@@ -0,0 +1,33 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::Moment;
+
+/// Clock of the untrusted host, the time the secure time service cross-checks.
+pub trait HostClock: Send + Sync {
+	fn now_millis(&self) -> Moment;
+}
+
+/// The host clock as seen through the `SystemTime` o-call.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct SystemHostClock;
+
+impl HostClock for SystemHostClock {
+	fn now_millis(&self) -> Moment {
+		itp_time_utils::now_as_millis()
+	}
+}
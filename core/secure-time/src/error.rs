@@ -0,0 +1,40 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use crate::sgx_reexport_prelude::*;
+
+use crate::secure_time_service::TimeSource;
+use std::string::String;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	#[error("No finalized parentchain block has been observed yet, the host clock is unchecked")]
+	NotAnchored,
+	#[error("Host clock drifted {drift} ms from the {reference:?} time, the limit is {limit} ms")]
+	DriftExceeded { reference: TimeSource, drift: i64, limit: u64 },
+	#[error("Host clock went backwards by {0} ms")]
+	ClockWentBackwards(u64),
+	#[error("Invalid reference time: {0}")]
+	InvalidReferenceTime(String),
+	#[error("HTTPS client error: {0}")]
+	HttpsClient(#[from] itc_https_client::Error),
+	#[error("Could not acquire lock on the secure time state")]
+	LockPoisoning,
+}
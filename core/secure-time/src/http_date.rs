@@ -0,0 +1,96 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Parsing of the HTTP `Date` header, in the IMF-fixdate format of RFC 7231.
+
+use crate::{
+	error::{Error, Result},
+	Moment,
+};
+use std::{format, vec::Vec};
+
+const MONTHS: [&str; 12] =
+	["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+/// Parses a date like `Sun, 06 Nov 1994 08:49:37 GMT` into millis since the unix epoch.
+pub fn parse_http_date(date: &str) -> Result<Moment> {
+	let invalid = || Error::InvalidReferenceTime(format!("Invalid HTTP date: {}", date));
+
+	let parts: Vec<&str> = date.trim().split(' ').collect();
+	if parts.len() != 6 || !parts[0].ends_with(',') || parts[5] != "GMT" {
+		return Err(invalid())
+	}
+
+	let day: u32 = parts[1].parse().map_err(|_| invalid())?;
+	let month = MONTHS.iter().position(|m| *m == parts[2]).ok_or_else(invalid)? as u32 + 1;
+	let year: i64 = parts[3].parse().map_err(|_| invalid())?;
+	let time = parts[4]
+		.split(':')
+		.map(|p| p.parse::<u64>())
+		.collect::<core::result::Result<Vec<_>, _>>()
+		.map_err(|_| invalid())?;
+
+	if time.len() != 3
+		|| !(1..=31).contains(&day)
+		|| year < 1970
+		|| time[0] > 23
+		|| time[1] > 59
+		|| time[2] > 60
+	{
+		return Err(invalid())
+	}
+
+	let days = days_since_epoch(year, month, day) as u64;
+	let seconds = days * 86_400 + time[0] * 3_600 + time[1] * 60 + time[2];
+	Ok(seconds * 1_000)
+}
+
+/// Days since the unix epoch of a date in the proleptic gregorian calendar.
+fn days_since_epoch(year: i64, month: u32, day: u32) -> i64 {
+	// Shift the year to start in March, so the leap day is the last day of the year.
+	let (year, month) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+	let era = year.div_euclid(400);
+	let year_of_era = year - era * 400;
+	let day_of_year = (153 * month as i64 + 2) / 5 + day as i64 - 1;
+	let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+	era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_rfc_7231_example() {
+		assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT").unwrap(), 784_111_777_000);
+	}
+
+	#[test]
+	fn parses_epoch_and_leap_day() {
+		assert_eq!(parse_http_date("Thu, 01 Jan 1970 00:00:00 GMT").unwrap(), 0);
+		assert_eq!(parse_http_date("Tue, 29 Feb 2000 12:00:00 GMT").unwrap(), 951_825_600_000);
+	}
+
+	#[test]
+	fn rejects_other_formats() {
+		assert!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT").is_err());
+		assert!(parse_http_date("Sun Nov  6 08:49:37 1994").is_err());
+		assert!(parse_http_date("Sun, 06 Nov 1994 08:49:37 CET").is_err());
+		assert!(parse_http_date("Sun, 06 Foo 1994 08:49:37 GMT").is_err());
+		assert!(parse_http_date("Sun, 06 Nov 1994 25:49:37 GMT").is_err());
+	}
+}
@@ -0,0 +1,55 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Secure time for the enclave, which must not blindly trust the clock of the untrusted host.
+//!
+//! The [SecureTimeService] cross-checks the host clock against the timestamps of the finalized
+//! parentchain blocks and, optionally, against a [reference time source](FetchReferenceTime)
+//! that is fetched over a TLS session terminated inside the enclave. The STF and the block
+//! production consume the time through the [TrustedTime] trait, which fails as soon as the host
+//! clock drifted beyond the configured [DriftLimits]. Sidechain blocks must not be produced
+//! in that case.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(all(feature = "std", feature = "sgx"))]
+compile_error!("feature \"std\" and feature \"sgx\" cannot be enabled at the same time");
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+#[macro_use]
+extern crate sgx_tstd as std;
+
+// re-export module to properly feature gate sgx and regular std environment
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+pub mod sgx_reexport_prelude {
+	pub use thiserror_sgx as thiserror;
+	pub use url_sgx as url;
+}
+
+pub use clock::{HostClock, SystemHostClock};
+pub use error::{Error, Result};
+pub use reference_time::{FetchReferenceTime, HttpsDateTimeSource, ReferenceTime};
+pub use secure_time_service::{DriftLimits, SecureTimeService, TimeSource, TrustedTime};
+
+pub mod clock;
+pub mod error;
+pub mod http_date;
+pub mod reference_time;
+pub mod secure_time_service;
+
+/// Milliseconds since the unix epoch.
+pub type Moment = u64;
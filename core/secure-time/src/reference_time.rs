@@ -0,0 +1,142 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use crate::sgx_reexport_prelude::*;
+
+use crate::{
+	clock::HostClock,
+	error::{Error, Result},
+	http_date::parse_http_date,
+	Moment,
+};
+use core::time::Duration;
+use itc_https_client::{HttpsRequest, SendHttpsRequest};
+use std::{format, string::ToString};
+use url::Url;
+
+/// Round trips above this duration are rejected, the host could otherwise widen the
+/// uncertainty of the reference time arbitrarily by delaying the response.
+pub const MAX_ROUND_TRIP: Duration = Duration::from_secs(5);
+
+/// Time reported by a source outside of the host's control.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReferenceTime {
+	pub timestamp: Moment,
+	/// Maximal error of `timestamp` in millis, in either direction.
+	pub uncertainty: u64,
+}
+
+/// Source of a reference time the host clock is cross-checked against.
+pub trait FetchReferenceTime: Send + Sync {
+	fn fetch_reference_time(&self) -> Result<ReferenceTime>;
+}
+
+/// Reference time from the `Date` header of an HTTPS endpoint.
+///
+/// The TLS session is terminated in the enclave and the server certificate is pinned by the
+/// [HttpsClient](itc_https_client::HttpsClient), so the host can delay the response, which is
+/// bounded by [MAX_ROUND_TRIP], but not forge it.
+pub struct HttpsDateTimeSource<Client, Clock> {
+	client: Client,
+	clock: Clock,
+	url: Url,
+}
+
+impl<Client, Clock> HttpsDateTimeSource<Client, Clock> {
+	pub fn new(client: Client, clock: Clock, url: &str) -> Result<Self> {
+		let url = Url::parse(url).map_err(|e| Error::InvalidReferenceTime(e.to_string()))?;
+		Ok(Self { client, clock, url })
+	}
+}
+
+impl<Client, Clock> FetchReferenceTime for HttpsDateTimeSource<Client, Clock>
+where
+	Client: SendHttpsRequest + Send + Sync,
+	Clock: HostClock,
+{
+	fn fetch_reference_time(&self) -> Result<ReferenceTime> {
+		let request = HttpsRequest::get(self.url.clone());
+
+		let sent_at = self.clock.now_millis();
+		let response = self.client.send(&request)?;
+		let round_trip = self.clock.now_millis().saturating_sub(sent_at);
+
+		if round_trip > MAX_ROUND_TRIP.as_millis() as u64 {
+			return Err(Error::InvalidReferenceTime(format!(
+				"Round trip of {} ms to {} is too long",
+				round_trip, self.url
+			)))
+		}
+
+		let date = response.header("Date").ok_or_else(|| {
+			Error::InvalidReferenceTime(format!("No Date header in the response of {}", self.url))
+		})?;
+
+		// The date header has a resolution of one second, we take the middle of that second.
+		Ok(ReferenceTime { timestamp: parse_http_date(date)? + 500, uncertainty: 500 + round_trip })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::clock::SystemHostClock;
+	use itc_https_client::HttpsResponse;
+	use std::{string::String, vec::Vec};
+
+	struct HttpsClientMock {
+		headers: Vec<(String, String)>,
+	}
+
+	impl SendHttpsRequest for HttpsClientMock {
+		fn send(&self, _request: &HttpsRequest) -> itc_https_client::Result<HttpsResponse> {
+			Ok(HttpsResponse { status: 200, headers: self.headers.clone(), body: Vec::new() })
+		}
+	}
+
+	fn source_with_headers(
+		headers: Vec<(String, String)>,
+	) -> HttpsDateTimeSource<HttpsClientMock, SystemHostClock> {
+		HttpsDateTimeSource::new(
+			HttpsClientMock { headers },
+			SystemHostClock,
+			"https://time.example.com",
+		)
+		.unwrap()
+	}
+
+	#[test]
+	fn reference_time_is_read_from_date_header() {
+		let source = source_with_headers(vec![(
+			"date".to_string(),
+			"Sun, 06 Nov 1994 08:49:37 GMT".to_string(),
+		)]);
+
+		let reference_time = source.fetch_reference_time().unwrap();
+
+		assert_eq!(reference_time.timestamp, 784_111_777_500);
+		assert!(reference_time.uncertainty >= 500);
+	}
+
+	#[test]
+	fn missing_date_header_fails() {
+		let source = source_with_headers(Vec::new());
+
+		assert!(matches!(source.fetch_reference_time(), Err(Error::InvalidReferenceTime(_))));
+	}
+}
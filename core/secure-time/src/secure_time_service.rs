@@ -0,0 +1,356 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+#[cfg(feature = "sgx")]
+use std::sync::SgxRwLock as RwLock;
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+use crate::{
+	clock::HostClock,
+	error::{Error, Result},
+	reference_time::FetchReferenceTime,
+	Moment,
+};
+use core::time::Duration;
+use log::*;
+use std::sync::Arc;
+
+/// Drift-checked time for the STF and the sidechain block production.
+pub trait TrustedTime: Send + Sync {
+	/// Current time in millis since the unix epoch.
+	///
+	/// Never goes backwards and fails if the host clock drifted beyond the configured limits.
+	fn now(&self) -> Result<Moment>;
+
+	/// Fails if the host clock drifted beyond the configured limits, no sidechain blocks must
+	/// be produced in that case.
+	fn ensure_within_drift(&self) -> Result<()>;
+}
+
+/// Limits the host clock must stay within.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DriftLimits {
+	/// Maximal deviation from a trusted time source.
+	pub max_drift: Duration,
+	/// Finalized parentchain blocks are observed with a delay, the host clock may be ahead of
+	/// their timestamp by this duration in addition to `max_drift`.
+	pub max_parentchain_lag: Duration,
+}
+
+/// Trusted time source the host clock is cross-checked against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeSource {
+	Parentchain,
+	Reference,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DriftViolation {
+	reference: TimeSource,
+	drift: i64,
+	limit: u64,
+}
+
+impl From<DriftViolation> for Error {
+	fn from(v: DriftViolation) -> Self {
+		Error::DriftExceeded { reference: v.reference, drift: v.drift, limit: v.limit }
+	}
+}
+
+#[derive(Default)]
+struct TimeState {
+	anchored_block: Option<u64>,
+	parentchain_violation: Option<DriftViolation>,
+	reference_violation: Option<DriftViolation>,
+	reference_fetched_at: Option<Moment>,
+	last_returned: Moment,
+}
+
+/// Cross-checks the host clock against the parentchain and an optional reference time source.
+///
+/// A parentchain timestamp is only checked when its block is observed for the first time, so a
+/// stalled parentchain does not halt the block production. A detected drift persists until the
+/// next observation of the same source is within the limits again.
+pub struct SecureTimeService<Clock> {
+	clock: Clock,
+	limits: DriftLimits,
+	reference_source: Option<Arc<dyn FetchReferenceTime>>,
+	reference_refresh_interval: Duration,
+	state: RwLock<TimeState>,
+}
+
+impl<Clock: HostClock> SecureTimeService<Clock> {
+	pub fn new(clock: Clock, limits: DriftLimits) -> Self {
+		Self {
+			clock,
+			limits,
+			reference_source: None,
+			reference_refresh_interval: Duration::ZERO,
+			state: RwLock::new(TimeState::default()),
+		}
+	}
+
+	/// Additionally cross-checks the host clock against `reference_source`, at most once per
+	/// `refresh_interval`.
+	pub fn with_reference_source(
+		mut self,
+		reference_source: Arc<dyn FetchReferenceTime>,
+		refresh_interval: Duration,
+	) -> Self {
+		self.reference_source = Some(reference_source);
+		self.reference_refresh_interval = refresh_interval;
+		self
+	}
+
+	/// Whether the timestamp of the parentchain block `block_number` (or a later one) has
+	/// already been observed. Allows to skip fetching the timestamp from the parentchain.
+	pub fn is_anchored_at(&self, block_number: u64) -> bool {
+		self.state
+			.read()
+			.map(|s| s.anchored_block.map_or(false, |anchored| anchored >= block_number))
+			.unwrap_or(false)
+	}
+
+	/// Cross-checks the host clock against the timestamp of a finalized parentchain block.
+	pub fn observe_parentchain_timestamp(
+		&self,
+		block_number: u64,
+		timestamp: Moment,
+	) -> Result<()> {
+		let mut state = self.state.write().map_err(|_| Error::LockPoisoning)?;
+		if state.anchored_block.map_or(false, |anchored| anchored >= block_number) {
+			return Ok(())
+		}
+
+		let drift = self.clock.now_millis() as i64 - timestamp as i64;
+		let max_drift = self.limits.max_drift.as_millis() as u64;
+		let max_lag = self.limits.max_parentchain_lag.as_millis() as u64;
+
+		// The host clock may never be behind the parentchain, but is ahead by the finality lag.
+		let violation = if drift < 0 && drift.unsigned_abs() > max_drift {
+			Some(DriftViolation { reference: TimeSource::Parentchain, drift, limit: max_drift })
+		} else if drift > 0 && drift as u64 > max_drift + max_lag {
+			Some(DriftViolation {
+				reference: TimeSource::Parentchain,
+				drift,
+				limit: max_drift + max_lag,
+			})
+		} else {
+			None
+		};
+
+		trace!("Host clock drift to parentchain block {}: {} ms", block_number, drift);
+		state.anchored_block = Some(block_number);
+		state.parentchain_violation = violation;
+		violation.map_or(Ok(()), |v| Err(v.into()))
+	}
+
+	/// Cross-checks the host clock against the reference time source, if one is configured and
+	/// the refresh interval has elapsed since the last check.
+	///
+	/// Blocks for the round trip to the reference source, so it is called from a dedicated
+	/// thread. [`TrustedTime::ensure_within_drift`] only reads the outcome of the last check.
+	pub fn refresh_reference_time(&self) -> Result<()> {
+		let reference_source = match &self.reference_source {
+			Some(s) => s,
+			None => return Ok(()),
+		};
+
+		let host_now = self.clock.now_millis();
+		let is_due = self
+			.state
+			.read()
+			.map_err(|_| Error::LockPoisoning)?
+			.reference_fetched_at
+			.map_or(true, |fetched_at| {
+				host_now.saturating_sub(fetched_at)
+					>= self.reference_refresh_interval.as_millis() as u64
+			});
+		if !is_due {
+			return Ok(())
+		}
+
+		// Fetch without holding the lock, the request is relayed through the host.
+		let reference_time = reference_source.fetch_reference_time()?;
+		let host_now = self.clock.now_millis();
+
+		let drift = host_now as i64 - reference_time.timestamp as i64;
+		let limit = self.limits.max_drift.as_millis() as u64 + reference_time.uncertainty;
+		let violation = (drift.unsigned_abs() > limit).then_some(DriftViolation {
+			reference: TimeSource::Reference,
+			drift,
+			limit,
+		});
+
+		trace!("Host clock drift to reference time: {} ms", drift);
+		let mut state = self.state.write().map_err(|_| Error::LockPoisoning)?;
+		state.reference_fetched_at = Some(host_now);
+		state.reference_violation = violation;
+		violation.map_or(Ok(()), |v| Err(v.into()))
+	}
+
+	fn check(&self, state: &TimeState, host_now: Moment) -> Result<()> {
+		if state.anchored_block.is_none() {
+			return Err(Error::NotAnchored)
+		}
+		if let Some(violation) = state.parentchain_violation.or(state.reference_violation) {
+			return Err(violation.into())
+		}
+		let went_backwards = state.last_returned.saturating_sub(host_now);
+		if went_backwards > self.limits.max_drift.as_millis() as u64 {
+			return Err(Error::ClockWentBackwards(went_backwards))
+		}
+		Ok(())
+	}
+}
+
+impl<Clock: HostClock> TrustedTime for SecureTimeService<Clock> {
+	fn now(&self) -> Result<Moment> {
+		let mut state = self.state.write().map_err(|_| Error::LockPoisoning)?;
+		let host_now = self.clock.now_millis();
+		self.check(&state, host_now)?;
+
+		// Small backward jumps of the host clock are absorbed, the trusted time stands still.
+		let now = host_now.max(state.last_returned);
+		state.last_returned = now;
+		Ok(now)
+	}
+
+	fn ensure_within_drift(&self) -> Result<()> {
+		let state = self.state.read().map_err(|_| Error::LockPoisoning)?;
+		self.check(&state, self.clock.now_millis())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::reference_time::ReferenceTime;
+	use core::sync::atomic::{AtomicU64, Ordering};
+
+	#[derive(Clone, Default)]
+	struct ClockMock(Arc<AtomicU64>);
+
+	impl ClockMock {
+		fn set(&self, millis: Moment) {
+			self.0.store(millis, Ordering::SeqCst);
+		}
+	}
+
+	impl HostClock for ClockMock {
+		fn now_millis(&self) -> Moment {
+			self.0.load(Ordering::SeqCst)
+		}
+	}
+
+	struct ReferenceMock(Moment);
+
+	impl FetchReferenceTime for ReferenceMock {
+		fn fetch_reference_time(&self) -> Result<ReferenceTime> {
+			Ok(ReferenceTime { timestamp: self.0, uncertainty: 1_000 })
+		}
+	}
+
+	const LIMITS: DriftLimits = DriftLimits {
+		max_drift: Duration::from_secs(10),
+		max_parentchain_lag: Duration::from_secs(60),
+	};
+
+	fn service_at(host_now: Moment) -> (SecureTimeService<ClockMock>, ClockMock) {
+		let clock = ClockMock::default();
+		clock.set(host_now);
+		(SecureTimeService::new(clock.clone(), LIMITS), clock)
+	}
+
+	#[test]
+	fn time_is_unchecked_before_first_parentchain_block() {
+		let (service, _) = service_at(100_000);
+
+		assert!(matches!(service.now(), Err(Error::NotAnchored)));
+	}
+
+	#[test]
+	fn host_time_is_trusted_within_parentchain_lag() {
+		let (service, _) = service_at(100_000);
+
+		service.observe_parentchain_timestamp(1, 50_000).unwrap();
+
+		assert!(service.is_anchored_at(1));
+		assert_eq!(service.now().unwrap(), 100_000);
+	}
+
+	#[test]
+	fn drift_halts_until_next_parentchain_block_is_within_limits() {
+		let (service, clock) = service_at(100_000);
+
+		// Host clock is behind the parentchain.
+		assert!(service.observe_parentchain_timestamp(1, 120_000).is_err());
+		assert!(matches!(
+			service.ensure_within_drift(),
+			Err(Error::DriftExceeded { reference: TimeSource::Parentchain, drift: -20_000, .. })
+		));
+
+		// Same block again is not re-evaluated.
+		clock.set(125_000);
+		service.observe_parentchain_timestamp(1, 120_000).unwrap();
+		assert!(service.ensure_within_drift().is_err());
+
+		service.observe_parentchain_timestamp(2, 120_000).unwrap();
+		assert_eq!(service.now().unwrap(), 125_000);
+
+		// Host clock is ahead by more than lag and drift.
+		clock.set(300_000);
+		assert!(service.observe_parentchain_timestamp(3, 126_000).is_err());
+		assert!(service.now().is_err());
+	}
+
+	#[test]
+	fn trusted_time_does_not_go_backwards() {
+		let (service, clock) = service_at(100_000);
+		service.observe_parentchain_timestamp(1, 100_000).unwrap();
+		assert_eq!(service.now().unwrap(), 100_000);
+
+		clock.set(95_000);
+		assert_eq!(service.now().unwrap(), 100_000);
+
+		clock.set(80_000);
+		assert!(matches!(service.now(), Err(Error::ClockWentBackwards(20_000))));
+	}
+
+	#[test]
+	fn reference_time_drift_halts() {
+		let (service, clock) = service_at(100_000);
+		let service = service
+			.with_reference_source(Arc::new(ReferenceMock(100_000)), Duration::from_secs(60));
+		service.observe_parentchain_timestamp(1, 100_000).unwrap();
+
+		// Host clock is ahead of the reference by more than drift and uncertainty.
+		clock.set(120_000);
+		assert!(service.refresh_reference_time().is_err());
+		assert!(matches!(
+			service.now(),
+			Err(Error::DriftExceeded { reference: TimeSource::Reference, limit: 11_000, .. })
+		));
+
+		// The reference is not fetched again before the refresh interval elapsed.
+		clock.set(100_000);
+		service.refresh_reference_time().unwrap();
+		assert!(service.now().is_err());
+	}
+}
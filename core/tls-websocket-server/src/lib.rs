@@ -41,7 +41,7 @@ use crate::{
 	config_provider::FromFileConfigProvider,
 	connection_id_generator::{ConnectionId, ConnectionIdGenerator},
 	error::{WebSocketError, WebSocketResult},
-	ws_server::TungsteniteWsServer,
+	ws_server::{Heartbeat, TungsteniteWsServer},
 };
 use mio::{event::Evented, Token};
 use std::{
@@ -166,6 +166,7 @@ pub fn create_ws_server<Handler>(
 	private_key: &str,
 	certificate: &str,
 	handler: Arc<Handler>,
+	heartbeat: Option<Heartbeat>,
 ) -> Arc<TungsteniteWsServer<Handler, FromFileConfigProvider>>
 where
 	Handler: WebSocketMessageHandler,
@@ -173,5 +174,9 @@ where
 	let config_provider =
 		Arc::new(FromFileConfigProvider::new(private_key.to_string(), certificate.to_string()));
 
-	Arc::new(TungsteniteWsServer::new(addr_plain.to_string(), config_provider, handler))
+	let server = TungsteniteWsServer::new(addr_plain.to_string(), config_provider, handler);
+	Arc::new(match heartbeat {
+		Some(heartbeat) => server.with_heartbeat(heartbeat),
+		None => server,
+	})
 }
@@ -41,12 +41,25 @@ use mio::{
 use mio_extras::channel::{channel, Receiver, Sender};
 use net::SocketAddr;
 use rustls::ServerConfig;
-use std::{collections::HashMap, format, net, string::String, sync::Arc};
+use std::{
+	boxed::Box,
+	collections::HashMap,
+	format, net,
+	string::String,
+	sync::Arc,
+	time::{Duration, Instant},
+};
 
 // Default tokens for the server.
 pub(crate) const NEW_CONNECTIONS_LISTENER: mio::Token = mio::Token(0);
 pub(crate) const SERVER_SIGNAL_TOKEN: mio::Token = mio::Token(1);
 
+/// Interval in which the event loop signals that it is alive, also when there are no events.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Called by the event loop of the server every [HEARTBEAT_INTERVAL].
+pub type Heartbeat = Box<dyn Fn() + Send + Sync>;
+
 /// Secure web-socket server implementation using the Tungstenite library.
 pub struct TungsteniteWsServer<Handler, ConfigProvider> {
 	ws_address: String,
@@ -56,6 +69,7 @@ pub struct TungsteniteWsServer<Handler, ConfigProvider> {
 	connections: RwLock<HashMap<mio::Token, TungsteniteWsConnection<Handler>>>,
 	is_running: RwLock<bool>,
 	signal_sender: Mutex<Option<Sender<ServerSignal>>>,
+	heartbeat: Option<Heartbeat>,
 }
 
 impl<Handler, ConfigProvider> TungsteniteWsServer<Handler, ConfigProvider>
@@ -76,6 +90,23 @@ where
 			connections: Default::default(),
 			is_running: Default::default(),
 			signal_sender: Default::default(),
+			heartbeat: None,
+		}
+	}
+
+	/// Lets the event loop call `heartbeat` periodically, e.g. for a watchdog to detect
+	/// that the server got stuck.
+	pub fn with_heartbeat(mut self, heartbeat: Heartbeat) -> Self {
+		self.heartbeat = Some(heartbeat);
+		self
+	}
+
+	fn beat(&self, last_heartbeat: &mut Instant) {
+		if let Some(heartbeat) = &self.heartbeat {
+			if last_heartbeat.elapsed() >= HEARTBEAT_INTERVAL {
+				heartbeat();
+				*last_heartbeat = Instant::now();
+			}
 		}
 	}
 
@@ -223,7 +254,9 @@ where
 		let (server_signal_sender, mut signal_receiver) = channel::<ServerSignal>();
 		self.register_server_signal_sender(server_signal_sender)?;
 
-		let tcp_listener = net::TcpListener::bind(socket_addr).expect("Could not listen on port");
+		// Fails if the server is restarted while the previous instance still holds the port.
+		let tcp_listener =
+			net::TcpListener::bind(socket_addr).map_err(WebSocketError::TcpBindError)?;
 		let tcp_listener =
 			mio::net::TcpListener::from_std(tcp_listener).map_err(WebSocketError::TcpBindError)?;
 		let mut poll = Poll::new()?;
@@ -245,10 +278,17 @@ where
 
 		*self.is_running.write().map_err(|_| WebSocketError::LockPoisoning)? = true;
 
+		let poll_timeout = self.heartbeat.as_ref().map(|_| HEARTBEAT_INTERVAL);
+		let mut last_heartbeat = Instant::now();
+		if let Some(heartbeat) = &self.heartbeat {
+			heartbeat();
+		}
+
 		// Run the event loop.
 		'outer_event_loop: loop {
-			let num_events = poll.poll(&mut events, None)?;
+			let num_events = poll.poll(&mut events, poll_timeout)?;
 			debug!("Number of readiness events: {}", num_events);
+			self.beat(&mut last_heartbeat);
 
 			for event in events.iter() {
 				match event.token() {
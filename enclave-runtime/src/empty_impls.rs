@@ -36,6 +36,18 @@ pub unsafe extern "C" fn update_market_data_xt(
 	unreachable!("Cannot update market data, teeracle feature is not enabled.")
 }
 
+/// Empty Teeracle oracle feed implementation.
+#[cfg(not(feature = "teeracle"))]
+#[no_mangle]
+pub unsafe extern "C" fn update_oracle_feed_xt(
+	_feed_config: *const u8,
+	_feed_config_size: u32,
+	_unchecked_extrinsic: *mut u8,
+	_unchecked_extrinsic_size: u32,
+) -> sgx_types::sgx_status_t {
+	unreachable!("Cannot update oracle feed, teeracle feature is not enabled.")
+}
+
 /// Empty Teeracle Weather data implementation.
 #[cfg(not(feature = "teeracle"))]
 #[no_mangle]
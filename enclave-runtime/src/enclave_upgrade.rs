@@ -0,0 +1,364 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Hand over of the shielding key, state key and shard state from the old to the new enclave
+//! of an enclave upgrade announced on the parentchain.
+//!
+//! Both enclaves run on the same platform, so they authenticate each other by local
+//! attestation:
+//! 1. The new enclave creates a report targeted at the old enclave, committing to the shard
+//!    and an ephemeral public key.
+//! 2. The old enclave verifies the report and checks its MRENCLAVE against the pending upgrade
+//!    of the shard config, as verified by its light client. It encrypts its keys and the shard
+//!    state to the ephemeral key and creates a report targeted at the new enclave, committing
+//!    to the ciphertext.
+//! 3. The new enclave verifies the report, checks that the old enclave has been signed by the
+//!    same signer, decrypts and seals the keys and the state.
+
+use crate::{
+	error::{Error, Result},
+	initialization::global_components::{
+		EnclaveSealHandler, GLOBAL_INTEGRITEE_PARENTCHAIN_LIGHT_CLIENT_SEAL,
+		GLOBAL_OCALL_API_COMPONENT, GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT,
+		GLOBAL_STATE_HANDLER_COMPONENT, GLOBAL_STATE_KEY_REPOSITORY_COMPONENT,
+	},
+	tls_ra::seal_handler::{SealStateAndKeys, UnsealStateAndKeys},
+	utils::get_validator_accessor_from_solo_or_parachain,
+};
+use codec::{Decode, Encode};
+use frame_support::ensure;
+use itc_parentchain::light_client::{concurrent_access::ValidatorAccess, LightClientState};
+use itp_component_container::ComponentGetter;
+use itp_ocall_api::EnclaveAttestationOCallApi;
+use itp_sgx_crypto::ecies::{self, AccountKeyScheme, EciesCiphertext};
+use itp_types::{
+	enclave_upgrade::{KeyHandoverRequest, KeyHandoverResponse},
+	EnclaveFingerprint, ShardIdentifier,
+};
+use itp_utils::write_slice_and_whitespace_pad;
+use its_primitives::types::block::SignedBlock as SignedSidechainBlock;
+use its_sidechain::validateer_fetch::ValidateerFetch;
+use lazy_static::lazy_static;
+use log::*;
+use sgx_tse::{rsgx_create_report, rsgx_self_report, rsgx_self_target, rsgx_verify_report};
+use sgx_types::{sgx_report_data_t, sgx_report_t, sgx_status_t, sgx_target_info_t};
+use sp_core::{blake2_256, ed25519, Pair};
+use std::{mem, ptr, slice, sync::SgxMutex as Mutex, vec::Vec};
+
+lazy_static! {
+	/// Ephemeral seed of the key handover requested last, the keys are encrypted to its public key.
+	static ref PENDING_KEY_HANDOVER: Mutex<Option<[u8; 32]>> = Mutex::new(None);
+}
+
+/// Keys and state handed over to the new enclave, as unsealed by the old one.
+#[derive(Encode, Decode)]
+struct HandedOverKeys {
+	shielding_key: Vec<u8>,
+	state_key: Vec<u8>,
+	state: Vec<u8>,
+}
+
+/// Writes the SCALE encoded [`KeyHandoverRequest`] for the keys of the enclave with
+/// `target_info` into `request`.
+#[no_mangle]
+pub unsafe extern "C" fn request_key_handover(
+	shard: *const u8,
+	shard_size: u32,
+	target_info: *const u8,
+	target_info_size: u32,
+	request: *mut u8,
+	request_size: u32,
+) -> sgx_status_t {
+	let shard_identifier =
+		ShardIdentifier::from_slice(slice::from_raw_parts(shard, shard_size as usize));
+	let target_info_slice = slice::from_raw_parts(target_info, target_info_size as usize);
+
+	let handover_request = match request_key_handover_internal(shard_identifier, target_info_slice)
+	{
+		Ok(r) => r,
+		Err(e) => {
+			error!("Failed to request the keys of the upgraded enclave: {:?}", e);
+			return e.into()
+		},
+	};
+
+	let request_slice = slice::from_raw_parts_mut(request, request_size as usize);
+	if let Err(e) = write_slice_and_whitespace_pad(request_slice, handover_request.encode()) {
+		return Error::BufferError(e).into()
+	};
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+/// Seals the keys and the shard state of the SCALE encoded [`KeyHandoverResponse`] to the
+/// last request.
+#[no_mangle]
+pub unsafe extern "C" fn import_handed_over_keys(
+	shard: *const u8,
+	shard_size: u32,
+	response: *const u8,
+	response_size: u32,
+) -> sgx_status_t {
+	let shard_identifier =
+		ShardIdentifier::from_slice(slice::from_raw_parts(shard, shard_size as usize));
+	let mut response_slice = slice::from_raw_parts(response, response_size as usize);
+	let handover_response = match KeyHandoverResponse::decode(&mut response_slice) {
+		Ok(r) => r,
+		Err(e) => {
+			error!("Failed to decode key handover response: {:?}", e);
+			return sgx_status_t::SGX_ERROR_UNEXPECTED
+		},
+	};
+
+	if let Err(e) = import_handed_over_keys_internal(&shard_identifier, &handover_response) {
+		error!("Failed to import the keys of the upgraded enclave: {:?}", e);
+		return e.into()
+	}
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+/// Target info of this enclave, for the report of a key handover request.
+pub(crate) fn enclave_target_info() -> Result<Vec<u8>> {
+	Ok(to_bytes(&rsgx_self_target()?))
+}
+
+/// Serves the key handover request of the new enclave of a pending upgrade of the shard.
+pub(crate) fn hand_over_keys(request: &KeyHandoverRequest) -> Result<KeyHandoverResponse> {
+	let report = verify_key_handover_request(request)?;
+
+	let validator_access = get_validator_accessor_from_solo_or_parachain()?;
+	let latest_header = validator_access.execute_on_validator(|v| {
+		let latest_header = v.latest_finalized_header()?;
+		Ok(latest_header)
+	})?;
+	let ocall_api = GLOBAL_OCALL_API_COMPONENT.get()?;
+	let upgrade = ocall_api
+		.shard_config::<_, SignedSidechainBlock>(&latest_header, request.shard)
+		.map_err(|e| Error::Other(format!("{:?}", e).into()))?
+		.and_then(|config| config.pending_enclave_upgrade())
+		.ok_or_else(|| Error::Other("No enclave upgrade is pending for the shard".into()))?;
+
+	let own_fingerprint: EnclaveFingerprint = ocall_api.get_mrenclave_of_self()?.m.into();
+	ensure!(
+		upgrade.old_fingerprint == own_fingerprint,
+		Error::Other("This enclave is not the one being upgraded".into())
+	);
+	ensure!(
+		upgrade.new_fingerprint == report.body.mr_enclave.m.into(),
+		Error::Other("Key handover requested by an enclave other than the announced one".into())
+	);
+
+	let seal_handler = seal_handler()?;
+	let keys = HandedOverKeys {
+		shielding_key: seal_handler.unseal_shielding_key()?,
+		state_key: seal_handler.unseal_state_key()?,
+		state: seal_handler.unseal_state(&request.shard)?,
+	};
+
+	info!(
+		"Handing over the keys and the state of shard {:?} to the upgraded enclave",
+		request.shard
+	);
+	create_key_handover_response(request, &keys, sp_io::offchain::random_seed())
+}
+
+fn request_key_handover_internal(
+	shard: ShardIdentifier,
+	target_info: &[u8],
+) -> Result<KeyHandoverRequest> {
+	let target_info: sgx_target_info_t = from_bytes(target_info)?;
+	let ephemeral_seed = sp_io::offchain::random_seed();
+
+	let request = create_key_handover_request(shard, &target_info, &ephemeral_seed)?;
+	*PENDING_KEY_HANDOVER.lock().map_err(|_| Error::MutexAccess)? = Some(ephemeral_seed);
+
+	Ok(request)
+}
+
+fn import_handed_over_keys_internal(
+	shard: &ShardIdentifier,
+	response: &KeyHandoverResponse,
+) -> Result<()> {
+	let ephemeral_seed = PENDING_KEY_HANDOVER
+		.lock()
+		.map_err(|_| Error::MutexAccess)?
+		.take()
+		.ok_or_else(|| Error::Other("No key handover has been requested".into()))?;
+
+	let keys = open_key_handover_response(response, &ephemeral_seed)?;
+
+	let seal_handler = seal_handler()?;
+	seal_handler.seal_shielding_key(&keys.shielding_key)?;
+	seal_handler.seal_state_key(&keys.state_key)?;
+	seal_handler.seal_state(&keys.state, shard)?;
+	Ok(())
+}
+
+fn create_key_handover_request(
+	shard: ShardIdentifier,
+	target_info: &sgx_target_info_t,
+	ephemeral_seed: &[u8; 32],
+) -> Result<KeyHandoverRequest> {
+	let ephemeral_public = ed25519::Pair::from_seed(ephemeral_seed).public().0;
+	let own_target_info = to_bytes(&rsgx_self_target()?);
+
+	let report_data = report_data(&(shard, ephemeral_public, &own_target_info).encode());
+	let report = rsgx_create_report(target_info, &report_data)?;
+
+	Ok(KeyHandoverRequest {
+		shard,
+		ephemeral_public,
+		report: to_bytes(&report),
+		target_info: own_target_info,
+	})
+}
+
+/// Verifies that the request has been created on this platform for this enclave, and returns
+/// the report of the requesting enclave.
+fn verify_key_handover_request(request: &KeyHandoverRequest) -> Result<sgx_report_t> {
+	let report: sgx_report_t = from_bytes(&request.report)?;
+	rsgx_verify_report(&report)?;
+
+	let expected_report_data =
+		report_data(&(request.shard, request.ephemeral_public, &request.target_info).encode());
+	ensure!(
+		report.body.report_data.d == expected_report_data.d,
+		Error::Other("Key handover request does not match its report".into())
+	);
+	Ok(report)
+}
+
+fn create_key_handover_response(
+	request: &KeyHandoverRequest,
+	keys: &HandedOverKeys,
+	randomness: [u8; 32],
+) -> Result<KeyHandoverResponse> {
+	let target_info: sgx_target_info_t = from_bytes(&request.target_info)?;
+	let encrypted_keys = ecies::encrypt(
+		AccountKeyScheme::Ed25519,
+		&request.ephemeral_public,
+		&keys.encode(),
+		randomness,
+	)?
+	.encode();
+
+	let report = rsgx_create_report(&target_info, &report_data(&encrypted_keys))?;
+
+	Ok(KeyHandoverResponse { encrypted_keys, report: to_bytes(&report) })
+}
+
+/// Verifies that the response has been created on this platform for this enclave, by an
+/// enclave of the same signer, and decrypts the keys.
+fn open_key_handover_response(
+	response: &KeyHandoverResponse,
+	ephemeral_seed: &[u8; 32],
+) -> Result<HandedOverKeys> {
+	let report: sgx_report_t = from_bytes(&response.report)?;
+	rsgx_verify_report(&report)?;
+
+	ensure!(
+		report.body.mr_signer.m == rsgx_self_report().body.mr_signer.m,
+		Error::Other("Keys have been handed over by an enclave of another signer".into())
+	);
+	ensure!(
+		report.body.report_data.d == report_data(&response.encrypted_keys).d,
+		Error::Other("Key handover response does not match its report".into())
+	);
+
+	let ephemeral_public = ed25519::Pair::from_seed(ephemeral_seed).public().0;
+	let secret = ecies::secret_scalar(AccountKeyScheme::Ed25519, ephemeral_seed)?;
+	let ciphertext = EciesCiphertext::decode(&mut response.encrypted_keys.as_slice())?;
+	let keys = ecies::decrypt(AccountKeyScheme::Ed25519, secret, &ephemeral_public, &ciphertext)?;
+
+	Ok(HandedOverKeys::decode(&mut keys.as_slice())?)
+}
+
+fn seal_handler() -> Result<EnclaveSealHandler> {
+	Ok(EnclaveSealHandler::new(
+		GLOBAL_STATE_HANDLER_COMPONENT.get()?,
+		GLOBAL_STATE_KEY_REPOSITORY_COMPONENT.get()?,
+		GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT.get()?,
+		GLOBAL_INTEGRITEE_PARENTCHAIN_LIGHT_CLIENT_SEAL.get()?,
+	))
+}
+
+fn report_data(payload: &[u8]) -> sgx_report_data_t {
+	let mut report_data = sgx_report_data_t::default();
+	report_data.d[..32].copy_from_slice(&blake2_256(payload));
+	report_data
+}
+
+/// Raw bytes of an SGX struct, as exchanged between the enclaves via their hosts.
+fn to_bytes<T: Copy>(value: &T) -> Vec<u8> {
+	// Safe, the SGX structs are plain C structs without padding or pointers.
+	unsafe { slice::from_raw_parts(value as *const T as *const u8, mem::size_of::<T>()) }.to_vec()
+}
+
+fn from_bytes<T: Copy>(bytes: &[u8]) -> Result<T> {
+	ensure!(bytes.len() == mem::size_of::<T>(), Error::Other("Unexpected SGX struct size".into()));
+	// Safe, the length has been checked and any bit pattern is a valid SGX struct.
+	Ok(unsafe { ptr::read_unaligned(bytes.as_ptr() as *const T) })
+}
+
+#[cfg(feature = "test")]
+pub mod tests {
+	use super::*;
+
+	fn keys() -> HandedOverKeys {
+		HandedOverKeys {
+			shielding_key: vec![1u8; 16],
+			state_key: vec![2u8; 16],
+			state: vec![3u8; 64],
+		}
+	}
+
+	pub fn key_handover_between_enclaves_of_the_same_signer_works() {
+		let shard = ShardIdentifier::from([1u8; 32]);
+		let ephemeral_seed = [7u8; 32];
+		let own_target_info = rsgx_self_target().unwrap();
+
+		let request =
+			create_key_handover_request(shard, &own_target_info, &ephemeral_seed).unwrap();
+		let report = verify_key_handover_request(&request).unwrap();
+		assert_eq!(report.body.mr_enclave.m, rsgx_self_report().body.mr_enclave.m);
+
+		let response = create_key_handover_response(&request, &keys(), [9u8; 32]).unwrap();
+		let handed_over = open_key_handover_response(&response, &ephemeral_seed).unwrap();
+
+		assert_eq!(handed_over.encode(), keys().encode());
+	}
+
+	pub fn key_handover_request_for_another_shard_is_rejected() {
+		let own_target_info = rsgx_self_target().unwrap();
+		let mut request =
+			create_key_handover_request([1u8; 32].into(), &own_target_info, &[7u8; 32]).unwrap();
+		request.shard = [2u8; 32].into();
+
+		assert!(verify_key_handover_request(&request).is_err());
+	}
+
+	pub fn key_handover_response_with_swapped_keys_is_rejected() {
+		let own_target_info = rsgx_self_target().unwrap();
+		let request =
+			create_key_handover_request([1u8; 32].into(), &own_target_info, &[7u8; 32]).unwrap();
+		let mut response = create_key_handover_response(&request, &keys(), [9u8; 32]).unwrap();
+		let other_response = create_key_handover_response(&request, &keys(), [8u8; 32]).unwrap();
+		response.encrypted_keys = other_response.encrypted_keys;
+
+		assert!(open_key_handover_response(&response, &[7u8; 32]).is_err());
+	}
+}
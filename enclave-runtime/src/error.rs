@@ -51,6 +51,10 @@ pub enum Error {
 	Attestation(itp_attestation_handler::error::Error),
 	Metadata(itp_node_api_metadata::error::Error),
 	BufferError(itp_utils::buffer::BufferError),
+	Ipfs(crate::ipfs::IpfsError),
+	StateSnapshotHashMismatch,
+	StateSnapshotNotPublished,
+	SecureTime(itc_secure_time::Error),
 	Other(Box<dyn std::error::Error>),
 }
 
@@ -0,0 +1,43 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Heartbeats of the long-running enclave threads, supervised by the watchdog of the
+//! untrusted worker, which restarts a thread once its heartbeats stop.
+
+use crate::{
+	error::{Error, Result},
+	initialization::global_components::GLOBAL_OCALL_API_COMPONENT,
+};
+use itp_component_container::ComponentGetter;
+use itp_enclave_metrics::EnclaveMetric;
+use itp_ocall_api::EnclaveMetricsOCallApi;
+use log::*;
+
+/// Signals the untrusted worker that `thread` is still making progress.
+pub(crate) fn send_heartbeat(thread: &str) {
+	if let Err(e) = try_send_heartbeat(thread) {
+		warn!("Failed to send heartbeat of {}: {:?}", thread, e);
+	}
+}
+
+fn try_send_heartbeat(thread: &str) -> Result<()> {
+	let ocall_api = GLOBAL_OCALL_API_COMPONENT.get()?;
+	ocall_api
+		.update_metric(EnclaveMetric::Heartbeat(thread.into()))
+		.map_err(Error::Sgx)?;
+	Ok(())
+}
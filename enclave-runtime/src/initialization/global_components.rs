@@ -32,11 +32,12 @@ use crate::{
 };
 use ita_parentchain_interface::{integritee, target_a, target_b};
 use ita_sgx_runtime::Runtime;
-use ita_stf::{Getter, State as StfState, Stf, TrustedCallSigned};
+use ita_stf::{Getter, State as StfState, Stf, TrustedCall, TrustedCallSigned};
 use itc_direct_rpc_server::{
 	rpc_connection_registry::ConnectionRegistry, rpc_responder::RpcResponder,
 	rpc_watch_extractor::RpcWatchExtractor, rpc_ws_handler::RpcWsHandler,
 };
+use itc_https_client::HttpsClient;
 use itc_parentchain::{
 	block_import_dispatcher::{
 		immediate_dispatcher::ImmediateDispatcher, triggered_dispatcher::TriggeredDispatcher,
@@ -49,10 +50,12 @@ use itc_parentchain::{
 		light_validation::LightValidation, light_validation_state::LightValidationState,
 	},
 };
+use itc_secure_time::{SecureTimeService, SystemHostClock};
 use itc_tls_websocket_server::{
 	config_provider::FromFileConfigProvider, ws_server::TungsteniteWsServer, ConnectionToken,
 };
 use itp_attestation_handler::IntelAttestationHandler;
+use itp_audit_log::AuditLogStore;
 use itp_component_container::ComponentContainer;
 use itp_extrinsics_factory::ExtrinsicsFactory;
 use itp_import_queue::ImportQueue;
@@ -63,13 +66,15 @@ use itp_node_api::{
 use itp_nonce_cache::NonceCache;
 use itp_sgx_crypto::{key_repository::KeyRepository, Aes, AesSeal, Ed25519Seal, Rsa3072Seal};
 use itp_stf_executor::{
-	enclave_signer::StfEnclaveSigner, executor::StfExecutor, getter_executor::GetterExecutor,
-	state_getter::StfStateGetter,
+	cross_shard_router::CrossShardRouter, enclave_signer::StfEnclaveSigner, executor::StfExecutor,
+	getter_executor::GetterExecutor, periodic_tasks::PeriodicTaskScheduler,
+	replay::BlockReplayStore, state_getter::StfStateGetter,
 };
 use itp_stf_primitives::types::{Hash, TrustedOperation};
 use itp_stf_state_handler::{
-	file_io::sgx::SgxStateFileIo, state_initializer::StateInitializer,
-	state_snapshot_repository::StateSnapshotRepository, StateHandler,
+	file_io::sgx::SgxStateFileIo, shard_genesis_builder::ShardGenesisBuilder,
+	state_initializer::StateInitializer, state_snapshot_repository::StateSnapshotRepository,
+	StateHandler,
 };
 use itp_stf_state_observer::state_observer::StateObserver;
 use itp_top_pool::basic_pool::BasicPool;
@@ -77,7 +82,10 @@ use itp_top_pool_author::{
 	api::SidechainApi,
 	author::{Author, AuthorTopFilter},
 };
-use itp_types::{Block as ParentchainBlock, SignedBlock as SignedParentchainBlock};
+use itp_types::{
+	parentchain::Header as ParentchainHeader, Block as ParentchainBlock,
+	SignedBlock as SignedParentchainBlock,
+};
 use its_primitives::{
 	traits::{Block as SidechainBlockTrait, SignedBlock as SignedSidechainBlockTrait},
 	types::block::SignedBlock as SignedSidechainBlock,
@@ -109,9 +117,15 @@ pub type EnclaveStateInitializer =
 	StateInitializer<StfState, EnclaveStf, EnclaveShieldingKeyRepository>;
 pub type EnclaveStateHandler =
 	StateHandler<EnclaveStateSnapshotRepository, EnclaveStateObserver, EnclaveStateInitializer>;
+pub type EnclaveShardGenesisBuilder = ShardGenesisBuilder<EnclaveStf, EnclaveStateInitializer>;
 pub type EnclaveGetterExecutor =
 	GetterExecutor<EnclaveStateObserver, StfStateGetter<EnclaveStf>, Getter>;
 pub type EnclaveOCallApi = OcallApi;
+pub type EnclaveHttpsClient = HttpsClient<EnclaveOCallApi>;
+pub type EnclaveAuditLog = AuditLogStore;
+pub type EnclaveBlockReplayStore =
+	BlockReplayStore<ParentchainHeader, EnclaveTrustedCallSigned, EnclaveGetter>;
+pub type EnclaveSecureTimeService = SecureTimeService<SystemHostClock>;
 pub type EnclaveNodeMetadataRepository = NodeMetadataRepository<NodeMetadata>;
 pub type EnclaveStfExecutor = StfExecutor<
 	EnclaveOCallApi,
@@ -130,6 +144,23 @@ pub type EnclaveStfEnclaveSigner = StfEnclaveSigner<
 	EnclaveTrustedCallSigned,
 	EnclaveGetter,
 >;
+pub type EnclavePeriodicTaskScheduler = PeriodicTaskScheduler<
+	EnclaveStfEnclaveSigner,
+	EnclaveTopPoolAuthor,
+	EnclaveShieldingKeyRepository,
+	TrustedCall,
+	EnclaveTrustedCallSigned,
+	EnclaveGetter,
+>;
+pub type EnclaveCrossShardRouter = CrossShardRouter<
+	EnclaveStf,
+	EnclaveStfEnclaveSigner,
+	EnclaveTopPoolAuthor,
+	EnclaveShieldingKeyRepository,
+	EnclaveStateHandler,
+	EnclaveTrustedCallSigned,
+	EnclaveGetter,
+>;
 pub type EnclaveAttestationHandler =
 	IntelAttestationHandler<EnclaveOCallApi, EnclaveSigningKeyRepository>;
 
@@ -398,6 +429,18 @@ pub static GLOBAL_STATE_HANDLER_COMPONENT: ComponentContainer<EnclaveStateHandle
 pub static GLOBAL_STATE_OBSERVER_COMPONENT: ComponentContainer<EnclaveStateObserver> =
 	ComponentContainer::new("state observer");
 
+/// Audit log of the trusted operations that were dropped without execution.
+pub static GLOBAL_AUDIT_LOG_COMPONENT: ComponentContainer<EnclaveAuditLog> =
+	ComponentContainer::new("audit log");
+
+/// Replay records of the imported sidechain blocks, only initialized with `replay-recording`.
+pub static GLOBAL_BLOCK_REPLAY_STORE_COMPONENT: ComponentContainer<EnclaveBlockReplayStore> =
+	ComponentContainer::new("block replay store");
+
+/// Secure time, the host clock cross-checked against the parentchain.
+pub static GLOBAL_SECURE_TIME_SERVICE_COMPONENT: ComponentContainer<EnclaveSecureTimeService> =
+	ComponentContainer::new("secure time service");
+
 /// TOP pool author.
 pub static GLOBAL_TOP_POOL_AUTHOR_COMPONENT: ComponentContainer<EnclaveTopPoolAuthor> =
 	ComponentContainer::new("top_pool_author");
@@ -467,6 +510,15 @@ pub static GLOBAL_SIDECHAIN_BLOCK_COMPOSER_COMPONENT: ComponentContainer<
 	EnclaveSidechainBlockComposer,
 > = ComponentContainer::new("sidechain_block_composer");
 
+/// Periodic enclave maintenance tasks, driven by the sidechain slot worker.
+pub static GLOBAL_PERIODIC_TASK_SCHEDULER_COMPONENT: ComponentContainer<
+	EnclavePeriodicTaskScheduler,
+> = ComponentContainer::new("periodic_task_scheduler");
+
+/// Delivers cross-shard messages between the shards of this worker.
+pub static GLOBAL_CROSS_SHARD_ROUTER_COMPONENT: ComponentContainer<EnclaveCrossShardRouter> =
+	ComponentContainer::new("cross_shard_router");
+
 /// Sidechain block syncer.
 pub static GLOBAL_SIDECHAIN_BLOCK_SYNCER_COMPONENT: ComponentContainer<
 	EnclaveSidechainBlockSyncer,
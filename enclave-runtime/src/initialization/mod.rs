@@ -20,15 +20,18 @@ pub mod parentchain;
 use crate::{
 	error::{Error, Result as EnclaveResult},
 	initialization::global_components::{
-		EnclaveBlockImportConfirmationHandler, EnclaveGetterExecutor, EnclaveLightClientSeal,
-		EnclaveOCallApi, EnclaveRpcConnectionRegistry, EnclaveRpcResponder,
-		EnclaveShieldingKeyRepository, EnclaveSidechainApi, EnclaveSidechainBlockImportQueue,
-		EnclaveSidechainBlockImportQueueWorker, EnclaveSidechainBlockImporter,
-		EnclaveSidechainBlockSyncer, EnclaveStateFileIo, EnclaveStateHandler,
-		EnclaveStateInitializer, EnclaveStateObserver, EnclaveStateSnapshotRepository,
-		EnclaveStfEnclaveSigner, EnclaveTopPool, EnclaveTopPoolAuthor,
-		GLOBAL_ATTESTATION_HANDLER_COMPONENT, GLOBAL_INTEGRITEE_PARENTCHAIN_LIGHT_CLIENT_SEAL,
-		GLOBAL_OCALL_API_COMPONENT, GLOBAL_RPC_WS_HANDLER_COMPONENT,
+		EnclaveAuditLog, EnclaveBlockImportConfirmationHandler, EnclaveCrossShardRouter,
+		EnclaveGetterExecutor, EnclaveHttpsClient, EnclaveLightClientSeal, EnclaveOCallApi,
+		EnclavePeriodicTaskScheduler, EnclaveRpcConnectionRegistry, EnclaveRpcResponder,
+		EnclaveSecureTimeService, EnclaveShieldingKeyRepository, EnclaveSidechainApi,
+		EnclaveSidechainBlockImportQueue, EnclaveSidechainBlockImportQueueWorker,
+		EnclaveSidechainBlockImporter, EnclaveSidechainBlockSyncer, EnclaveStateFileIo,
+		EnclaveStateHandler, EnclaveStateInitializer, EnclaveStateObserver,
+		EnclaveStateSnapshotRepository, EnclaveStfEnclaveSigner, EnclaveTopPool,
+		EnclaveTopPoolAuthor, GLOBAL_ATTESTATION_HANDLER_COMPONENT, GLOBAL_AUDIT_LOG_COMPONENT,
+		GLOBAL_CROSS_SHARD_ROUTER_COMPONENT, GLOBAL_INTEGRITEE_PARENTCHAIN_LIGHT_CLIENT_SEAL,
+		GLOBAL_OCALL_API_COMPONENT, GLOBAL_PERIODIC_TASK_SCHEDULER_COMPONENT,
+		GLOBAL_RPC_WS_HANDLER_COMPONENT, GLOBAL_SECURE_TIME_SERVICE_COMPONENT,
 		GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT, GLOBAL_SIDECHAIN_BLOCK_COMPOSER_COMPONENT,
 		GLOBAL_SIDECHAIN_BLOCK_SYNCER_COMPONENT, GLOBAL_SIDECHAIN_IMPORT_QUEUE_COMPONENT,
 		GLOBAL_SIDECHAIN_IMPORT_QUEUE_WORKER_COMPONENT, GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT,
@@ -37,11 +40,13 @@ use crate::{
 		GLOBAL_TARGET_B_PARENTCHAIN_LIGHT_CLIENT_SEAL, GLOBAL_TOP_POOL_AUTHOR_COMPONENT,
 		GLOBAL_WEB_SOCKET_SERVER_COMPONENT,
 	},
+	maintenance_tasks::register_maintenance_tasks,
 	ocall::OcallApi,
 	rpc::{rpc_response_channel::RpcResponseChannel, worker_api_direct::public_api_rpc_handler},
 	utils::{
 		get_extrinsic_factory_from_integritee_solo_or_parachain,
 		get_node_metadata_repository_from_integritee_solo_or_parachain,
+		get_stf_enclave_signer_from_solo_or_parachain,
 		get_triggered_dispatcher_from_solo_or_parachain,
 		get_validator_accessor_from_solo_or_parachain,
 	},
@@ -49,21 +54,33 @@ use crate::{
 };
 use base58::ToBase58;
 use codec::Encode;
-use ita_stf::{Getter, TrustedCallSigned};
+use ita_stf::{
+	migrations::STF_VERSION, shard_acl::SubmissionAccessControl, Getter, TrustedCallSigned,
+};
 use itc_direct_rpc_server::{
 	create_determine_watch, rpc_connection_registry::ConnectionRegistry,
 	rpc_ws_handler::RpcWsHandler,
 };
+use itc_secure_time::{DriftLimits, HttpsDateTimeSource, SystemHostClock};
 use itc_tls_websocket_server::{
 	certificate_generation::ed25519_self_signed_certificate, create_ws_server, ConnectionToken,
 	WebSocketServer,
 };
 use itp_attestation_handler::IntelAttestationHandler;
 use itp_component_container::{ComponentGetter, ComponentInitializer};
+use itp_enclave_metrics::DIRECT_RPC_SERVER_THREAD;
+use itp_ocall_api::EnclaveAttestationOCallApi;
 use itp_primitives_cache::GLOBAL_PRIMITIVES_CACHE;
-use itp_settings::files::{
-	INTEGRITEE_PARENTCHAIN_LIGHT_CLIENT_DB_PATH, STATE_SNAPSHOTS_CACHE_SIZE,
-	TARGET_A_PARENTCHAIN_LIGHT_CLIENT_DB_PATH, TARGET_B_PARENTCHAIN_LIGHT_CLIENT_DB_PATH,
+use itp_settings::{
+	files::{
+		INTEGRITEE_PARENTCHAIN_LIGHT_CLIENT_DB_PATH, STATE_SNAPSHOTS_CACHE_SIZE,
+		TARGET_A_PARENTCHAIN_LIGHT_CLIENT_DB_PATH, TARGET_B_PARENTCHAIN_LIGHT_CLIENT_DB_PATH,
+	},
+	parentchain_sync::MAX_PARENTCHAIN_SYNC_STALL,
+	secure_time::{
+		MAX_CLOCK_DRIFT, MAX_PARENTCHAIN_TIMESTAMP_LAG, REFERENCE_TIME_REFRESH_INTERVAL,
+		REFERENCE_TIME_ROOT_CERTIFICATES, REFERENCE_TIME_URL,
+	},
 };
 use itp_sgx_crypto::{
 	get_aes_repository, get_ed25519_repository, get_rsa3072_repository, key_repository::AccessKey,
@@ -74,12 +91,19 @@ use itp_stf_state_handler::{
 	state_snapshot_repository_loader::StateSnapshotRepositoryLoader, StateHandler,
 };
 use itp_top_pool::pool::Options as PoolOptions;
-use itp_top_pool_author::author::AuthorTopFilter;
+use itp_top_pool_author::{author::AuthorTopFilter, validation::ValidationConfig};
 use itp_types::{parentchain::ParentchainId, ShardIdentifier};
 use its_sidechain::block_composer::BlockComposer;
 use log::*;
 use sp_core::crypto::Pair;
-use std::{collections::HashMap, path::PathBuf, string::String, sync::Arc};
+use std::{
+	boxed::Box,
+	collections::HashMap,
+	path::PathBuf,
+	string::{String, ToString},
+	sync::Arc,
+	vec::Vec,
+};
 pub(crate) fn init_enclave(
 	mu_ra_url: String,
 	untrusted_worker_url: String,
@@ -117,7 +141,7 @@ pub(crate) fn init_enclave(
 	GLOBAL_TARGET_B_PARENTCHAIN_LIGHT_CLIENT_SEAL.initialize(target_b_light_client_seal);
 
 	let state_file_io =
-		Arc::new(EnclaveStateFileIo::new(state_key_repository, StateDir::new(base_dir)));
+		Arc::new(EnclaveStateFileIo::new(state_key_repository, StateDir::new(base_dir.clone())));
 	let state_initializer =
 		Arc::new(EnclaveStateInitializer::new(shielding_key_repository.clone()));
 	let state_snapshot_repository_loader = StateSnapshotRepositoryLoader::<
@@ -166,17 +190,25 @@ pub(crate) fn init_enclave(
 	// validateer completely breaking (IO PipeError).
 	// Corresponding GH issues are #545 and #600.
 
+	let audit_log = Arc::new(EnclaveAuditLog::new(base_dir));
+	GLOBAL_AUDIT_LOG_COMPONENT.initialize(audit_log.clone());
+
 	let top_pool_author = create_top_pool_author(
 		connection_registry.clone(),
 		state_handler,
 		ocall_api.clone(),
 		shielding_key_repository.clone(),
-	);
+		audit_log,
+	)?;
 	GLOBAL_TOP_POOL_AUTHOR_COMPONENT.initialize(top_pool_author.clone());
 
 	let getter_executor = Arc::new(EnclaveGetterExecutor::new(state_observer));
-	let io_handler =
-		public_api_rpc_handler(top_pool_author, getter_executor, shielding_key_repository);
+	let io_handler = public_api_rpc_handler(
+		top_pool_author,
+		getter_executor,
+		shielding_key_repository,
+		signing_key_repository.clone(),
+	);
 	let rpc_handler = Arc::new(RpcWsHandler::new(io_handler, watch_extractor, connection_registry));
 	GLOBAL_RPC_WS_HANDLER_COMPONENT.initialize(rpc_handler);
 
@@ -215,13 +247,27 @@ pub(crate) fn init_enclave_sidechain_components() -> EnclaveResult<()> {
 
 	let signer = GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT.get()?.retrieve_key()?;
 
-	let sidechain_block_importer = Arc::new(EnclaveSidechainBlockImporter::new(
+	GLOBAL_SECURE_TIME_SERVICE_COMPONENT.initialize(create_secure_time_service(ocall_api.clone())?);
+	itp_sync_watchdog::set_max_stall(MAX_PARENTCHAIN_SYNC_STALL);
+
+	let sidechain_block_importer = EnclaveSidechainBlockImporter::new(
 		state_handler,
 		state_key_repository.clone(),
 		top_pool_author,
 		parentchain_block_import_dispatcher,
 		ocall_api.clone(),
-	));
+	);
+	#[cfg(feature = "replay-recording")]
+	let sidechain_block_importer = {
+		let replay_store =
+			Arc::new(crate::initialization::global_components::EnclaveBlockReplayStore::new(
+				crate::get_base_path()?,
+			));
+		crate::initialization::global_components::GLOBAL_BLOCK_REPLAY_STORE_COMPONENT
+			.initialize(replay_store.clone());
+		sidechain_block_importer.with_replay_recorder(replay_store)
+	};
+	let sidechain_block_importer = Arc::new(sidechain_block_importer);
 
 	let sidechain_block_import_queue = GLOBAL_SIDECHAIN_IMPORT_QUEUE_COMPONENT.get()?;
 	let metadata_repository = get_node_metadata_repository_from_integritee_solo_or_parachain()?;
@@ -252,9 +298,54 @@ pub(crate) fn init_enclave_sidechain_components() -> EnclaveResult<()> {
 	let block_composer = Arc::new(BlockComposer::new(signer, state_key_repository));
 	GLOBAL_SIDECHAIN_BLOCK_COMPOSER_COMPONENT.initialize(block_composer);
 
+	let periodic_task_scheduler = Arc::new(EnclavePeriodicTaskScheduler::new(
+		get_stf_enclave_signer_from_solo_or_parachain()?,
+		GLOBAL_TOP_POOL_AUTHOR_COMPONENT.get()?,
+		GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT.get()?,
+	));
+	register_maintenance_tasks(&periodic_task_scheduler)?;
+	GLOBAL_PERIODIC_TASK_SCHEDULER_COMPONENT.initialize(periodic_task_scheduler);
+
+	let cross_shard_router = Arc::new(EnclaveCrossShardRouter::new(
+		get_stf_enclave_signer_from_solo_or_parachain()?,
+		GLOBAL_TOP_POOL_AUTHOR_COMPONENT.get()?,
+		GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT.get()?,
+		GLOBAL_STATE_HANDLER_COMPONENT.get()?,
+	));
+	GLOBAL_CROSS_SHARD_ROUTER_COMPONENT.initialize(cross_shard_router);
+
 	Ok(())
 }
 
+/// Creates the secure time service, with the HTTPS reference time source if one is configured.
+fn create_secure_time_service(
+	ocall_api: Arc<EnclaveOCallApi>,
+) -> EnclaveResult<Arc<EnclaveSecureTimeService>> {
+	let secure_time = EnclaveSecureTimeService::new(
+		SystemHostClock,
+		DriftLimits {
+			max_drift: MAX_CLOCK_DRIFT,
+			max_parentchain_lag: MAX_PARENTCHAIN_TIMESTAMP_LAG,
+		},
+	);
+
+	let secure_time = match REFERENCE_TIME_URL {
+		Some(url) => {
+			let root_certificates: Vec<String> =
+				REFERENCE_TIME_ROOT_CERTIFICATES.iter().map(|c| c.to_string()).collect();
+			let https_client = EnclaveHttpsClient::new(ocall_api, &root_certificates)
+				.map_err(|e| Error::Other(e.into()))?;
+			let reference_source = HttpsDateTimeSource::new(https_client, SystemHostClock, url)?;
+			info!("Cross-checking the host clock against the reference time of {}", url);
+			secure_time
+				.with_reference_source(Arc::new(reference_source), REFERENCE_TIME_REFRESH_INTERVAL)
+		},
+		None => secure_time,
+	};
+
+	Ok(Arc::new(secure_time))
+}
+
 pub(crate) fn init_direct_invocation_server(server_addr: String) -> EnclaveResult<()> {
 	let rpc_handler = GLOBAL_RPC_WS_HANDLER_COMPONENT.get()?;
 	let signer = GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT.get()?.retrieve_key()?;
@@ -267,8 +358,13 @@ pub(crate) fn init_direct_invocation_server(server_addr: String) -> EnclaveResul
 	let pem_serialized = cert.serialize_pem().map_err(|e| Error::Other(e.into()))?;
 	let private_key = cert.serialize_private_key_pem();
 
-	let web_socket_server =
-		create_ws_server(server_addr.as_str(), &private_key, &pem_serialized, rpc_handler);
+	let web_socket_server = create_ws_server(
+		server_addr.as_str(),
+		&private_key,
+		&pem_serialized,
+		rpc_handler,
+		Some(Box::new(|| crate::heartbeat::send_heartbeat(DIRECT_RPC_SERVER_THREAD))),
+	);
 
 	GLOBAL_WEB_SOCKET_SERVER_COMPONENT.initialize(web_socket_server.clone());
 
@@ -294,19 +390,30 @@ pub fn create_top_pool_author(
 	state_handler: Arc<EnclaveStateHandler>,
 	ocall_api: Arc<EnclaveOCallApi>,
 	shielding_key_repository: Arc<EnclaveShieldingKeyRepository>,
-) -> Arc<EnclaveTopPoolAuthor> {
+	audit_log: Arc<EnclaveAuditLog>,
+) -> EnclaveResult<Arc<EnclaveTopPoolAuthor>> {
+	let validation = ValidationConfig::new(ocall_api.get_mrenclave_of_self()?.m, STF_VERSION);
+
 	let response_channel = Arc::new(RpcResponseChannel::default());
 	let rpc_responder = Arc::new(EnclaveRpcResponder::new(connection_registry, response_channel));
 
 	let side_chain_api = Arc::new(EnclaveSidechainApi::new());
-	let top_pool =
-		Arc::new(EnclaveTopPool::create(PoolOptions::default(), side_chain_api, rpc_responder));
+	let top_pool = Arc::new(EnclaveTopPool::create_with_audit_log(
+		PoolOptions::default(),
+		side_chain_api,
+		rpc_responder,
+		audit_log,
+	));
 
-	Arc::new(EnclaveTopPoolAuthor::new(
-		top_pool,
-		AuthorTopFilter::<TrustedCallSigned, Getter>::new(),
-		state_handler,
-		shielding_key_repository,
-		ocall_api,
+	Ok(Arc::new(
+		EnclaveTopPoolAuthor::new(
+			top_pool,
+			AuthorTopFilter::<TrustedCallSigned, Getter>::new(),
+			state_handler,
+			shielding_key_repository,
+			ocall_api,
+		)
+		.with_validation(validation)
+		.with_access_control(Arc::new(SubmissionAccessControl)),
 	))
 }
@@ -55,6 +55,7 @@ use itc_parentchain::{
 	primitives::ParentchainId,
 };
 use itp_component_container::ComponentGetter;
+use itp_enclave_metrics::parentchain_sync_thread;
 use itp_import_queue::PushToQueue;
 use itp_node_api::metadata::NodeMetadata;
 use itp_nonce_cache::{MutateNonce, Nonce};
@@ -75,11 +76,21 @@ use std::{
 };
 mod attestation;
 mod empty_impls;
+mod enclave_upgrade;
+mod heartbeat;
 mod initialization;
 mod ipfs;
+mod maintenance_tasks;
+mod memory_accounting;
 mod ocall;
+mod replay;
+mod shard_lifecycle;
 mod shard_vault;
+mod state_confirmation;
+mod state_dump;
+mod state_snapshot;
 mod utils;
+mod worker_directory;
 
 pub mod error;
 pub mod rpc;
@@ -331,9 +342,10 @@ pub unsafe extern "C" fn call_rpc_methods(
 fn sidechain_rpc_int(request: &str) -> Result<String> {
 	let sidechain_block_import_queue = GLOBAL_SIDECHAIN_IMPORT_QUEUE_COMPONENT.get()?;
 
-	let io = sidechain_io_handler(move |signed_block| {
-		sidechain_block_import_queue.push_single(signed_block)
-	});
+	let io = sidechain_io_handler(
+		move |signed_block| sidechain_block_import_queue.push_single(signed_block),
+		state_confirmation::confirm_state_update,
+	);
 
 	// note: errors are still returned as Option<String>
 	Ok(io
@@ -470,6 +482,8 @@ unsafe fn sync_parentchain_internal(
 		Vec::<StorageProof>::decode_raw(events_proofs_to_sync, events_proofs_to_sync_size)?;
 	let parentchain_id = ParentchainId::decode_raw(parentchain_id, parentchain_id_size as usize)?;
 
+	heartbeat::send_heartbeat(&parentchain_sync_thread(&parentchain_id));
+
 	let blocks_to_sync_merkle_roots: Vec<sp_core::H256> =
 		blocks_to_sync.iter().map(|block| block.block.header.state_root).collect();
 
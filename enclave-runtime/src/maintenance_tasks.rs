@@ -0,0 +1,109 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Periodic maintenance tasks of the enclave, see [itp_stf_executor::periodic_tasks].
+
+use crate::{error::Result, initialization::global_components::EnclavePeriodicTaskScheduler};
+use ita_stf::{
+	cross_shard::COMMITMENT_PERIOD, rent::REAPING_BATCH_PERIOD, reserves::ATTESTATION_BATCH_PERIOD,
+	unshielding::PAYOUT_PERIOD, TrustedCall,
+};
+use itp_stf_executor::periodic_tasks::PeriodicTask;
+use itp_types::{AccountId, ShardIdentifier};
+use std::{boxed::Box, vec, vec::Vec};
+
+/// Charges the state rent and reaps the accounts that can't afford it anymore.
+///
+/// Runs every [REAPING_BATCH_PERIOD] slots, so that a reaping pass is continued batch by batch.
+/// The STF itself only starts a new pass once per reaping period.
+pub(crate) struct ReapAccountsTask;
+
+impl PeriodicTask<TrustedCall> for ReapAccountsTask {
+	fn name(&self) -> &'static str {
+		"reap_accounts"
+	}
+
+	fn compose_calls(
+		&self,
+		enclave_account: &AccountId,
+		_shard: &ShardIdentifier,
+	) -> Vec<TrustedCall> {
+		vec![TrustedCall::reap_accounts(enclave_account.clone())]
+	}
+}
+
+/// Pays out the queued unshielding requests from the shard vault.
+pub(crate) struct PayoutUnshieldBatchTask;
+
+impl PeriodicTask<TrustedCall> for PayoutUnshieldBatchTask {
+	fn name(&self) -> &'static str {
+		"payout_unshield_batch"
+	}
+
+	fn compose_calls(
+		&self,
+		enclave_account: &AccountId,
+		_shard: &ShardIdentifier,
+	) -> Vec<TrustedCall> {
+		vec![TrustedCall::payout_unshield_batch(enclave_account.clone())]
+	}
+}
+
+/// Commits to the sent cross-shard messages and publishes the commitment on the parentchain.
+pub(crate) struct CommitCrossShardOutboxTask;
+
+impl PeriodicTask<TrustedCall> for CommitCrossShardOutboxTask {
+	fn name(&self) -> &'static str {
+		"cross_shard_commit_outbox"
+	}
+
+	fn compose_calls(
+		&self,
+		enclave_account: &AccountId,
+		shard: &ShardIdentifier,
+	) -> Vec<TrustedCall> {
+		vec![TrustedCall::cross_shard_commit_outbox(enclave_account.clone(), *shard)]
+	}
+}
+
+/// Commits to the liabilities of the shard and publishes the reserves report on the parentchain.
+///
+/// Runs every [ATTESTATION_BATCH_PERIOD] slots, so that a report is continued batch by batch.
+/// The STF itself only starts a new report once per attestation period.
+pub(crate) struct AttestReservesTask;
+
+impl PeriodicTask<TrustedCall> for AttestReservesTask {
+	fn name(&self) -> &'static str {
+		"reserves_attest"
+	}
+
+	fn compose_calls(
+		&self,
+		enclave_account: &AccountId,
+		shard: &ShardIdentifier,
+	) -> Vec<TrustedCall> {
+		vec![TrustedCall::reserves_attest(enclave_account.clone(), *shard)]
+	}
+}
+
+pub(crate) fn register_maintenance_tasks(scheduler: &EnclavePeriodicTaskScheduler) -> Result<()> {
+	scheduler.register(Box::new(ReapAccountsTask), REAPING_BATCH_PERIOD)?;
+	scheduler.register(Box::new(PayoutUnshieldBatchTask), PAYOUT_PERIOD)?;
+	scheduler.register(Box::new(CommitCrossShardOutboxTask), COMMITMENT_PERIOD)?;
+	scheduler.register(Box::new(AttestReservesTask), ATTESTATION_BATCH_PERIOD)?;
+	Ok(())
+}
@@ -0,0 +1,62 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Heap accounting of the enclave, see [itp_memory_accounting].
+
+use crate::error::{Error, Result};
+use codec::Decode;
+use itp_enclave_metrics::EnclaveMetric;
+use itp_memory_accounting::{
+	AccountingAllocator, MemoryLimits, Subsystem, GLOBAL_MEMORY_ACCOUNTING,
+};
+use itp_ocall_api::EnclaveMetricsOCallApi;
+use log::*;
+use sgx_types::sgx_status_t;
+use std::{alloc::System, slice, string::ToString};
+
+#[global_allocator]
+static ALLOCATOR: AccountingAllocator<System> =
+	AccountingAllocator::new(System, &GLOBAL_MEMORY_ACCOUNTING);
+
+/// Sets the heap limits above which new trusted operations and getters are rejected.
+#[no_mangle]
+pub unsafe extern "C" fn set_memory_limits(limits: *const u8, limits_size: u32) -> sgx_status_t {
+	let mut limits_slice = slice::from_raw_parts(limits, limits_size as usize);
+
+	let limits = match MemoryLimits::decode(&mut limits_slice) {
+		Ok(l) => l,
+		Err(e) => return Error::Codec(e).into(),
+	};
+
+	info!("Setting enclave memory limits: {:?}", limits);
+	itp_memory_accounting::set_limits(&limits);
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+/// Reports the heap usage of each subsystem to the untrusted worker.
+pub(crate) fn report_memory_usage<OCallApi: EnclaveMetricsOCallApi>(
+	ocall_api: &OCallApi,
+) -> Result<()> {
+	for subsystem in Subsystem::ALL {
+		let usage = GLOBAL_MEMORY_ACCOUNTING.usage(subsystem) as u64;
+		ocall_api
+			.update_metric(EnclaveMetric::HeapUsage(subsystem.name().to_string(), usage))
+			.map_err(Error::Sgx)?;
+	}
+	Ok(())
+}
@@ -67,6 +67,31 @@ extern "C" {
 		update_info: *mut sgx_update_info_bit_t,
 	) -> sgx_status_t;
 
+	pub fn ocall_tcp_connect(
+		ret_val: *mut sgx_status_t,
+		host: *const u8,
+		host_size: u32,
+		port: u16,
+		connection: *mut u32,
+	) -> sgx_status_t;
+
+	pub fn ocall_tcp_send(
+		ret_val: *mut sgx_status_t,
+		connection: u32,
+		data: *const u8,
+		data_size: u32,
+	) -> sgx_status_t;
+
+	pub fn ocall_tcp_receive(
+		ret_val: *mut sgx_status_t,
+		connection: u32,
+		buffer: *mut u8,
+		buffer_size: u32,
+		received: *mut u32,
+	) -> sgx_status_t;
+
+	pub fn ocall_tcp_close(ret_val: *mut sgx_status_t, connection: u32) -> sgx_status_t;
+
 	pub fn ocall_worker_request(
 		ret_val: *mut sgx_status_t,
 		request: *const u8,
@@ -107,6 +132,16 @@ extern "C" {
 		sidechain_blocks_size: u32,
 	) -> sgx_status_t;
 
+	pub fn ocall_request_state_confirmations(
+		ret_val: *mut sgx_status_t,
+		shard_identifier: *const u8,
+		shard_identifier_size: u32,
+		request: *const u8,
+		request_size: u32,
+		confirmations: *mut u8,
+		confirmations_size: u32,
+	) -> sgx_status_t;
+
 	pub fn ocall_send_to_parentchain(
 		ret_val: *mut sgx_status_t,
 		extrinsics: *const u8,
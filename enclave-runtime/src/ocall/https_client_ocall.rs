@@ -0,0 +1,96 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+use crate::ocall::{ffi, OcallApi};
+use frame_support::ensure;
+use itp_ocall_api::{HttpsClientOCallApi, TcpConnectionId};
+use sgx_types::{sgx_status_t, SgxResult};
+use std::vec::Vec;
+
+impl HttpsClientOCallApi for OcallApi {
+	fn tcp_connect(&self, host: &str, port: u16) -> SgxResult<TcpConnectionId> {
+		let mut rt: sgx_status_t = sgx_status_t::SGX_ERROR_UNEXPECTED;
+		let mut connection: TcpConnectionId = 0;
+
+		let res = unsafe {
+			ffi::ocall_tcp_connect(
+				&mut rt as *mut sgx_status_t,
+				host.as_ptr(),
+				host.len() as u32,
+				port,
+				&mut connection as *mut u32,
+			)
+		};
+
+		ensure!(rt == sgx_status_t::SGX_SUCCESS, rt);
+		ensure!(res == sgx_status_t::SGX_SUCCESS, res);
+
+		Ok(connection)
+	}
+
+	fn tcp_send(&self, connection: TcpConnectionId, data: &[u8]) -> SgxResult<()> {
+		let mut rt: sgx_status_t = sgx_status_t::SGX_ERROR_UNEXPECTED;
+
+		let res = unsafe {
+			ffi::ocall_tcp_send(
+				&mut rt as *mut sgx_status_t,
+				connection,
+				data.as_ptr(),
+				data.len() as u32,
+			)
+		};
+
+		ensure!(rt == sgx_status_t::SGX_SUCCESS, rt);
+		ensure!(res == sgx_status_t::SGX_SUCCESS, res);
+
+		Ok(())
+	}
+
+	fn tcp_receive(&self, connection: TcpConnectionId, max_len: u32) -> SgxResult<Vec<u8>> {
+		let mut rt: sgx_status_t = sgx_status_t::SGX_ERROR_UNEXPECTED;
+		let mut buffer: Vec<u8> = vec![0; max_len as usize];
+		let mut received: u32 = 0;
+
+		let res = unsafe {
+			ffi::ocall_tcp_receive(
+				&mut rt as *mut sgx_status_t,
+				connection,
+				buffer.as_mut_ptr(),
+				buffer.len() as u32,
+				&mut received as *mut u32,
+			)
+		};
+
+		ensure!(rt == sgx_status_t::SGX_SUCCESS, rt);
+		ensure!(res == sgx_status_t::SGX_SUCCESS, res);
+		// Never trust the length reported by the untrusted side.
+		ensure!(received <= max_len, sgx_status_t::SGX_ERROR_UNEXPECTED);
+
+		buffer.truncate(received as usize);
+		Ok(buffer)
+	}
+
+	fn tcp_close(&self, connection: TcpConnectionId) -> SgxResult<()> {
+		let mut rt: sgx_status_t = sgx_status_t::SGX_ERROR_UNEXPECTED;
+
+		let res = unsafe { ffi::ocall_tcp_close(&mut rt as *mut sgx_status_t, connection) };
+
+		ensure!(rt == sgx_status_t::SGX_SUCCESS, rt);
+		ensure!(res == sgx_status_t::SGX_SUCCESS, res);
+
+		Ok(())
+	}
+}
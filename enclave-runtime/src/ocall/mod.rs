@@ -17,10 +17,12 @@
 
 mod attestation_ocall;
 mod ffi;
+mod https_client_ocall;
 mod ipfs_ocall;
 mod metrics_ocall;
 mod on_chain_ocall;
 mod sidechain_ocall;
+mod state_confirmation_ocall;
 
 #[derive(Clone, Debug, Default)]
 pub struct OcallApi;
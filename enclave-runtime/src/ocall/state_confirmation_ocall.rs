@@ -0,0 +1,59 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+use crate::ocall::{ffi, OcallApi};
+use codec::{Decode, Encode};
+use frame_support::ensure;
+use itp_ocall_api::EnclaveStateConfirmationOCallApi;
+use itp_types::{state_confirmation::StateConfirmation, ShardIdentifier};
+use log::*;
+use sgx_types::{sgx_status_t, SgxResult};
+use std::vec::Vec;
+
+impl EnclaveStateConfirmationOCallApi for OcallApi {
+	fn request_state_confirmations(
+		&self,
+		shard: ShardIdentifier,
+		encrypted_request: Vec<u8>,
+	) -> SgxResult<Vec<StateConfirmation>> {
+		const CONFIRMATIONS_BUFFER_SIZE: usize = 16384; // Buffer size for the confirmations in bytes (16KB).
+
+		let mut rt: sgx_status_t = sgx_status_t::SGX_ERROR_UNEXPECTED;
+		let shard_encoded = shard.encode();
+
+		let mut confirmations_encoded: Vec<u8> = vec![0; CONFIRMATIONS_BUFFER_SIZE];
+
+		let res = unsafe {
+			ffi::ocall_request_state_confirmations(
+				&mut rt as *mut sgx_status_t,
+				shard_encoded.as_ptr(),
+				shard_encoded.len() as u32,
+				encrypted_request.as_ptr(),
+				encrypted_request.len() as u32,
+				confirmations_encoded.as_mut_ptr(),
+				confirmations_encoded.len() as u32,
+			)
+		};
+
+		ensure!(rt == sgx_status_t::SGX_SUCCESS, rt);
+		ensure!(res == sgx_status_t::SGX_SUCCESS, res);
+
+		Decode::decode(&mut confirmations_encoded.as_slice()).map_err(|e| {
+			error!("Failed to decode state confirmations: {}", e);
+			sgx_status_t::SGX_ERROR_UNEXPECTED
+		})
+	}
+}
@@ -0,0 +1,90 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	error::{Error, Result},
+	get_base_path,
+	initialization::global_components::{EnclaveGetter, EnclaveTrustedCallSigned},
+	sync::{EnclaveLock, EnclaveStateRWLock},
+	utils::get_stf_executor_from_solo_or_parachain,
+};
+use codec::Encode;
+use itp_stf_executor::replay::{replay_block, BlockReplayStore, RecordBlockReplay};
+use itp_types::{
+	parentchain::Header as ParentchainHeader, replay::BlockReplayReport, ShardIdentifier,
+};
+use itp_utils::write_slice_and_whitespace_pad;
+use its_sidechain::aura::state_confirmation::prepare_sidechain_state;
+use log::*;
+use sgx_types::sgx_status_t;
+use std::slice;
+
+/// Re-executes a sidechain block from its replay record and writes the encoded
+/// [`BlockReplayReport`] into `report`.
+///
+/// Records only exist if the enclave was built with the `replay-recording` feature.
+#[no_mangle]
+pub unsafe extern "C" fn replay_sidechain_block(
+	shard: *const u8,
+	shard_size: u32,
+	block_number: u64,
+	report: *mut u8,
+	report_size: u32,
+) -> sgx_status_t {
+	let shard_identifier =
+		ShardIdentifier::from_slice(slice::from_raw_parts(shard, shard_size as usize));
+
+	let replay_report = match replay_sidechain_block_internal(shard_identifier, block_number) {
+		Ok(r) => r,
+		Err(e) => {
+			error!("Failed to replay sidechain block {}: {:?}", block_number, e);
+			return e.into()
+		},
+	};
+
+	let report_slice = slice::from_raw_parts_mut(report, report_size as usize);
+	if let Err(e) = write_slice_and_whitespace_pad(report_slice, replay_report.encode()) {
+		return Error::BufferError(e).into()
+	};
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+fn replay_sidechain_block_internal(
+	shard: ShardIdentifier,
+	block_number: u64,
+) -> Result<BlockReplayReport> {
+	let _enclave_read_lock = EnclaveLock::read_all()?;
+
+	let stf_executor = get_stf_executor_from_solo_or_parachain()?;
+	let replay_store: BlockReplayStore<ParentchainHeader, EnclaveTrustedCallSigned, EnclaveGetter> =
+		BlockReplayStore::new(get_base_path()?);
+	let record = replay_store.load(&shard, block_number)?;
+	let state_before_execution = replay_store.load_state_before_execution(&record)?;
+	let timestamp = record.timestamp;
+
+	info!(
+		"Replaying sidechain block {} of shard {:?} with {} trusted operations",
+		block_number,
+		shard,
+		record.trusted_operations.len()
+	);
+
+	Ok(replay_block(stf_executor.as_ref(), record, state_before_execution, |state| {
+		prepare_sidechain_state(state, block_number, timestamp)
+	})?)
+}
@@ -20,28 +20,61 @@ use crate::{
 		generate_dcap_ra_extrinsic_from_quote_internal,
 		generate_ias_ra_extrinsic_from_der_cert_internal,
 	},
+	enclave_upgrade,
+	initialization::global_components::{
+		EnclaveStf, GLOBAL_AUDIT_LOG_COMPONENT, GLOBAL_CROSS_SHARD_ROUTER_COMPONENT,
+		GLOBAL_STATE_HANDLER_COMPONENT,
+	},
 	utils::{
 		get_stf_enclave_signer_from_solo_or_parachain,
 		get_validator_accessor_from_solo_or_parachain,
 	},
 };
-use codec::Encode;
+use base58::FromBase58;
+use codec::{Decode, Encode};
 use core::result::Result;
-use ita_sgx_runtime::Runtime;
-use ita_stf::{Getter, TrustedCallSigned};
+use ita_sgx_runtime::{Runtime, VERSION};
+use ita_stf::{Getter, TrustedCallSigned, TrustedGetter};
 use itc_parentchain::light_client::{concurrent_access::ValidatorAccess, ExtrinsicSender};
+use itp_audit_log::{AuditEntry, QueryAuditLog};
+use itp_component_container::ComponentGetter;
 use itp_primitives_cache::{GetPrimitives, GLOBAL_PRIMITIVES_CACHE};
 use itp_rpc::RpcReturnValue;
-use itp_sgx_crypto::key_repository::AccessPubkey;
-use itp_stf_executor::{getter_executor::ExecuteGetter, traits::StfShardVaultQuery};
+use itp_sgx_crypto::key_repository::{AccessKey, AccessPubkey};
+use itp_sgx_externalities::SgxExternalitiesTrait;
+use itp_stf_executor::{
+	error::Error as StfExecutorError,
+	getter_executor::ExecuteGetter,
+	getter_stream::{GetterStreamer, StreamGetter},
+	traits::{RelayCrossShardMessages, StfEnclaveSigning, StfShardVaultQuery},
+};
+use itp_stf_interface::{system_pallet::SystemPalletAccountInterface, StateGetterInterface};
+use itp_stf_primitives::{
+	cross_shard::{CrossShardProof, MessageNonce},
+	pagination::Page,
+	traits::{GetterAuthorization, GetterExpiry},
+	types::AccountId,
+};
+use itp_stf_state_handler::{handle_state::HandleState, query_shard_state::QueryShardState};
+use itp_storage::storage_value_key;
 use itp_top_pool_author::traits::AuthorApi;
-use itp_types::{DirectRequestStatus, Request, ShardIdentifier, H256};
+use itp_types::{
+	enclave_upgrade::{KeyHandoverRequest, KeyHandoverResponse},
+	getter_stream::{GetterStreamChunk, GetterStreamRequest},
+	DirectRequestStatus, Request, ShardIdentifier, ShardInfo, H256,
+};
 use itp_utils::{FromHexPrefixed, ToHexPrefixed};
 use its_primitives::types::block::SignedBlock;
-use its_sidechain::rpc_handler::{direct_top_pool_api, import_block_api};
-use jsonrpc_core::{serde_json::json, IoHandler, Params, Value};
+use its_sidechain::{
+	rpc_handler::{
+		confirm_state_update_api, direct_top_pool_api, import_block_api, submission_receipt_api,
+	},
+	state::SidechainSystemExt,
+};
+use jsonrpc_core::{serde_json::json, Error as RpcError, ErrorCode, IoHandler, Params, Value};
 use log::debug;
 use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
+use sp_core::ed25519;
 use sp_runtime::OpaqueExtrinsic;
 use std::{borrow::ToOwned, format, str, string::String, sync::Arc, vec::Vec};
 
@@ -59,20 +92,27 @@ fn get_all_rpc_methods_string(io_handler: &IoHandler) -> String {
 	format!("methods: [{}]", method_string)
 }
 
-pub fn public_api_rpc_handler<Author, GetterExecutor, AccessShieldingKey>(
+pub fn public_api_rpc_handler<Author, GetterExecutor, AccessShieldingKey, AccessSigningKey>(
 	top_pool_author: Arc<Author>,
 	getter_executor: Arc<GetterExecutor>,
 	shielding_key: Arc<AccessShieldingKey>,
+	signing_key: Arc<AccessSigningKey>,
 ) -> IoHandler
 where
 	Author: AuthorApi<H256, H256, TrustedCallSigned, Getter> + Send + Sync + 'static,
 	GetterExecutor: ExecuteGetter + Send + Sync + 'static,
 	AccessShieldingKey: AccessPubkey<KeyType = Rsa3072PubKey> + Send + Sync + 'static,
+	AccessSigningKey: AccessKey<KeyType = ed25519::Pair> + Send + Sync + 'static,
 {
-	let mut io = direct_top_pool_api::add_top_pool_direct_rpc_methods(
+	let io = direct_top_pool_api::add_top_pool_direct_rpc_methods(
 		top_pool_author.clone(),
 		IoHandler::new(),
 	);
+	let mut io = submission_receipt_api::add_submission_receipt_rpc_method(
+		top_pool_author.clone(),
+		signing_key,
+		io,
+	);
 
 	io.add_sync_method("author_getShieldingKey", move |_: Params| {
 		debug!("worker_api_direct rpc was called: author_getShieldingKey");
@@ -103,13 +143,11 @@ where
 		let shard =
 			local_top_pool_author.list_handled_shards().first().copied().unwrap_or_default();
 		if let Ok(stf_enclave_signer) = get_stf_enclave_signer_from_solo_or_parachain() {
-			if let Ok(vault) = stf_enclave_signer.get_shard_vault(&shard) {
-				let json_value =
-					RpcReturnValue::new(vault.encode(), false, DirectRequestStatus::Ok);
-				Ok(json!(json_value.to_hex()))
-			} else {
-				Ok(json!(compute_hex_encoded_return_error("failed to get shard vault").to_hex()))
-			}
+			let vault = stf_enclave_signer
+				.get_shard_vault(&shard)
+				.map_err(|e| stf_executor_rpc_error(&e))?;
+			let json_value = RpcReturnValue::new(vault.encode(), false, DirectRequestStatus::Ok);
+			Ok(json!(json_value.to_hex()))
 		} else {
 			Ok(json!(compute_hex_encoded_return_error(
 				"failed to get stf_enclave_signer to get shard vault"
@@ -125,6 +163,40 @@ where
 		Ok(json!(json_value.to_hex()))
 	});
 
+	io.add_sync_method("author_getShards", move |_: Params| {
+		debug!("worker_api_direct rpc was called: author_getShards");
+		let json_value = match list_shards_inner() {
+			Ok(shards) =>
+				RpcReturnValue::new(shards.encode(), false, DirectRequestStatus::Ok).to_hex(),
+			Err(error) => compute_hex_encoded_return_error(error.as_str()),
+		};
+		Ok(json!(json_value))
+	});
+
+	io.add_sync_method("author_getShardInfo", move |params: Params| {
+		debug!("worker_api_direct rpc was called: author_getShardInfo");
+		let json_value = match get_shard_info_inner(params) {
+			Ok(shard_info) =>
+				RpcReturnValue::new(shard_info.encode(), false, DirectRequestStatus::Ok).to_hex(),
+			Err(error) => compute_hex_encoded_return_error(error.as_str()),
+		};
+		Ok(json!(json_value))
+	});
+
+	io.add_sync_method("author_getCrossShardMessageProof", move |params: Params| {
+		debug!("worker_api_direct rpc was called: author_getCrossShardMessageProof");
+		let proof = get_cross_shard_message_proof_inner(params)?;
+		let json_value = RpcReturnValue::new(proof.encode(), false, DirectRequestStatus::Ok);
+		Ok(json!(json_value.to_hex()))
+	});
+
+	io.add_sync_method("author_submitCrossShardMessageProof", move |params: Params| {
+		debug!("worker_api_direct rpc was called: author_submitCrossShardMessageProof");
+		submit_cross_shard_message_proof_inner(params)?;
+		let json_value = RpcReturnValue::new(Vec::new(), false, DirectRequestStatus::Ok);
+		Ok(json!(json_value.to_hex()))
+	});
+
 	io.add_sync_method("author_getMuRaUrl", move |_: Params| {
 		debug!("worker_api_direct rpc was called: author_getMuRaUrl");
 		let url = match GLOBAL_PRIMITIVES_CACHE.get_mu_ra_url() {
@@ -153,6 +225,26 @@ where
 		Ok(json!(json_value.to_hex()))
 	});
 
+	io.add_sync_method("author_getEnclaveTargetInfo", move |_: Params| {
+		debug!("worker_api_direct rpc was called: author_getEnclaveTargetInfo");
+		let json_value = match enclave_upgrade::enclave_target_info() {
+			Ok(target_info) =>
+				RpcReturnValue::new(target_info.encode(), false, DirectRequestStatus::Ok).to_hex(),
+			Err(e) => compute_hex_encoded_return_error(format!("{:?}", e).as_str()),
+		};
+		Ok(json!(json_value))
+	});
+
+	io.add_sync_method("author_handOverKeys", move |params: Params| {
+		debug!("worker_api_direct rpc was called: author_handOverKeys");
+		let json_value = match hand_over_keys_inner(params) {
+			Ok(response) =>
+				RpcReturnValue::new(response.encode(), false, DirectRequestStatus::Ok).to_hex(),
+			Err(error) => compute_hex_encoded_return_error(error.as_str()),
+		};
+		Ok(json!(json_value))
+	});
+
 	io.add_sync_method("chain_subscribeAllHeads", |_: Params| {
 		debug!("worker_api_direct rpc was called: chain_subscribeAllHeads");
 		let parsed = "world";
@@ -168,19 +260,33 @@ where
 
 	io.add_sync_method("state_getRuntimeVersion", |_: Params| {
 		debug!("worker_api_direct rpc was called: state_getRuntimeVersion");
-		let parsed = "world";
-		Ok(Value::String(format!("hello, {}", parsed)))
+		Ok(json!(runtime_version_return_value()))
+	});
+
+	let getter_streamer = GetterStreamer::new(getter_executor.clone());
+	io.add_sync_method("state_executeGetterStream", move |params: Params| {
+		debug!("worker_api_direct rpc was called: state_executeGetterStream");
+		let chunk = stream_getter_inner(&getter_streamer, params)?;
+		let json_value = RpcReturnValue::new(chunk.encode(), false, DirectRequestStatus::Ok);
+		Ok(json!(json_value.to_hex()))
 	});
 
 	io.add_sync_method("state_executeGetter", move |params: Params| {
 		debug!("worker_api_direct rpc was called: state_executeGetter");
-		let json_value = match execute_getter_inner(getter_executor.as_ref(), params) {
-			Ok(state_getter_value) => RpcReturnValue {
-				do_watch: false,
-				value: state_getter_value.encode(),
-				status: DirectRequestStatus::Ok,
-			}
-			.to_hex(),
+		let state_getter_value = execute_getter_inner(getter_executor.as_ref(), params)?;
+		let json_value = RpcReturnValue {
+			do_watch: false,
+			value: state_getter_value.encode(),
+			status: DirectRequestStatus::Ok,
+		}
+		.to_hex();
+		Ok(json!(json_value))
+	});
+
+	io.add_sync_method("state_getAuditLog", move |params: Params| {
+		debug!("worker_api_direct rpc was called: state_getAuditLog");
+		let json_value = match get_audit_log_inner(params) {
+			Ok(page) => RpcReturnValue::new(page.encode(), false, DirectRequestStatus::Ok).to_hex(),
 			Err(error) => compute_hex_encoded_return_error(error.as_str()),
 		};
 		Ok(json!(json_value))
@@ -243,25 +349,210 @@ where
 	io
 }
 
+/// Maps an STF executor error to a JSON-RPC error carrying its stable error code.
+fn stf_executor_rpc_error(error: &StfExecutorError) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(error.code()),
+		message: format!("{}", error),
+		data: None,
+	}
+}
+
 fn execute_getter_inner<GE: ExecuteGetter>(
 	getter_executor: &GE,
 	params: Params,
-) -> Result<Option<Vec<u8>>, String> {
-	let hex_encoded_params = params.parse::<Vec<String>>().map_err(|e| format!("{:?}", e))?;
+) -> Result<Option<Vec<u8>>, RpcError> {
+	let hex_encoded_params = params.parse::<Vec<String>>()?;
+	let hex_encoded_request = hex_encoded_params
+		.first()
+		.ok_or_else(|| RpcError::invalid_params("Missing request parameter"))?;
 
-	let request =
-		Request::from_hex(&hex_encoded_params[0].clone()).map_err(|e| format!("{:?}", e))?;
+	let request = Request::from_hex(hex_encoded_request)
+		.map_err(|e| RpcError::invalid_params(format!("{:?}", e)))?;
 
 	let shard: ShardIdentifier = request.shard;
 	let encoded_trusted_getter: Vec<u8> = request.cyphertext;
 
 	let getter_result = getter_executor
 		.execute_getter(&shard, encoded_trusted_getter)
-		.map_err(|e| format!("{:?}", e))?;
+		.map_err(|e| stf_executor_rpc_error(&e))?;
 
 	Ok(getter_result)
 }
 
+fn stream_getter_inner<SG: StreamGetter>(
+	getter_streamer: &SG,
+	params: Params,
+) -> Result<GetterStreamChunk, RpcError> {
+	let hex_encoded_params = params.parse::<Vec<String>>()?;
+	let hex_encoded_request = hex_encoded_params
+		.first()
+		.ok_or_else(|| RpcError::invalid_params("Missing request parameter"))?;
+
+	let stream_request = GetterStreamRequest::from_hex(hex_encoded_request)
+		.map_err(|e| RpcError::invalid_params(format!("{:?}", e)))?;
+	let request = stream_request.request;
+
+	getter_streamer
+		.stream_getter(&request.shard, request.cyphertext, stream_request.chunk)
+		.map_err(|e| stf_executor_rpc_error(&e))
+}
+
+fn runtime_version_return_value() -> String {
+	RpcReturnValue::new(VERSION.encode(), false, DirectRequestStatus::Ok).to_hex()
+}
+
+fn list_shards_inner() -> Result<Vec<ShardIdentifier>, String> {
+	let state_handler = GLOBAL_STATE_HANDLER_COMPONENT.get().map_err(|e| format!("{:?}", e))?;
+	state_handler.list_shards().map_err(|e| format!("{:?}", e))
+}
+
+fn get_shard_info_inner(params: Params) -> Result<ShardInfo, String> {
+	let base58_encoded_params = params.parse::<Vec<String>>().map_err(|e| format!("{:?}", e))?;
+
+	if base58_encoded_params.len() != 1 {
+		return Err(format!(
+			"Wrong number of arguments for shard info query: {}, expected: {}",
+			base58_encoded_params.len(),
+			1
+		))
+	}
+
+	let shard_vec = base58_encoded_params[0]
+		.from_base58()
+		.map_err(|_| "Invalid base58 format of shard id".to_owned())?;
+	let shard = ShardIdentifier::decode(&mut shard_vec.as_slice())
+		.map_err(|_| "Shard ID is not of type H256".to_owned())?;
+
+	let state_handler = GLOBAL_STATE_HANDLER_COMPONENT.get().map_err(|e| format!("{:?}", e))?;
+	if !state_handler.shard_exists(&shard).map_err(|e| format!("{:?}", e))? {
+		return Err(format!("Shard {:?} is not maintained by this worker", shard))
+	}
+
+	let stf_enclave_signer =
+		get_stf_enclave_signer_from_solo_or_parachain().map_err(|e| format!("{:?}", e))?;
+	let enclave_account =
+		stf_enclave_signer.get_enclave_account().map_err(|e| format!("{:?}", e))?;
+	// The vault is optional, not every shard has one initialized.
+	let shard_vault = match stf_enclave_signer.get_shard_vault(&shard) {
+		Ok(vault) => Some(vault),
+		Err(StfExecutorError::ShardVaultNotSet) => None,
+		Err(e) => return Err(format!("{:?}", e)),
+	};
+
+	let (mut state, state_hash) =
+		state_handler.load_cloned(&shard).map_err(|e| format!("{:?}", e))?;
+	let enclave_account_nonce = EnclaveStf::get_account_nonce(&mut state, &enclave_account);
+	let pending_unshield_payouts = EnclaveStf::get_pending_unshield_payouts(&mut state);
+
+	Ok(ShardInfo {
+		shard,
+		state_hash,
+		last_sidechain_block_number: state.get_block_number(),
+		last_sidechain_block_hash: state.get_last_block_hash(),
+		enclave_account_nonce,
+		shard_vault,
+		pending_unshield_payouts,
+	})
+}
+
+fn decode_base58_shard(encoded: &str) -> Result<ShardIdentifier, RpcError> {
+	let shard_vec = encoded
+		.from_base58()
+		.map_err(|_| RpcError::invalid_params("Invalid base58 format of shard id"))?;
+	ShardIdentifier::decode(&mut shard_vec.as_slice())
+		.map_err(|_| RpcError::invalid_params("Shard ID is not of type H256"))
+}
+
+/// Params: base58 encoded source and destination shard, and the message nonce.
+fn get_cross_shard_message_proof_inner(params: Params) -> Result<CrossShardProof, RpcError> {
+	let params = params.parse::<Vec<String>>()?;
+	if params.len() != 3 {
+		return Err(RpcError::invalid_params(format!(
+			"Wrong number of arguments for cross-shard message proof query: {}, expected: {}",
+			params.len(),
+			3
+		)))
+	}
+	let source = decode_base58_shard(&params[0])?;
+	let destination = decode_base58_shard(&params[1])?;
+	let nonce = params[2]
+		.parse::<MessageNonce>()
+		.map_err(|_| RpcError::invalid_params("Invalid message nonce"))?;
+	let router = GLOBAL_CROSS_SHARD_ROUTER_COMPONENT
+		.get()
+		.map_err(|e| RpcError::invalid_params(format!("{:?}", e)))?;
+	router
+		.message_proof(&source, &destination, nonce)
+		.map_err(|e| stf_executor_rpc_error(&e))
+}
+
+/// Params: the hex encoded [CrossShardProof].
+fn submit_cross_shard_message_proof_inner(params: Params) -> Result<(), RpcError> {
+	let hex_encoded_params = params.parse::<Vec<String>>()?;
+	let hex_encoded_proof = hex_encoded_params
+		.first()
+		.ok_or_else(|| RpcError::invalid_params("Missing proof parameter"))?;
+	let proof = CrossShardProof::from_hex(hex_encoded_proof)
+		.map_err(|e| RpcError::invalid_params(format!("Invalid cross-shard proof: {:?}", e)))?;
+	let router = GLOBAL_CROSS_SHARD_ROUTER_COMPONENT
+		.get()
+		.map_err(|e| RpcError::invalid_params(format!("{:?}", e)))?;
+	router.submit_message_proof(&proof).map_err(|e| stf_executor_rpc_error(&e))
+}
+
+/// Params: the hex encoded [KeyHandoverRequest] of the new enclave of an enclave upgrade.
+fn hand_over_keys_inner(params: Params) -> Result<KeyHandoverResponse, String> {
+	let hex_encoded_params = params.parse::<Vec<String>>().map_err(|e| format!("{:?}", e))?;
+	let hex_encoded_request = hex_encoded_params
+		.first()
+		.ok_or_else(|| "Missing request parameter".to_owned())?;
+	let request =
+		KeyHandoverRequest::from_hex(hex_encoded_request).map_err(|e| format!("{:?}", e))?;
+	enclave_upgrade::hand_over_keys(&request).map_err(|e| format!("{:?}", e))
+}
+
+/// Serves the privileged `audit_log` getter, which must be signed by the root of the shard.
+fn get_audit_log_inner(params: Params) -> Result<Page<AuditEntry>, String> {
+	let hex_encoded_params = params.parse::<Vec<String>>().map_err(|e| format!("{:?}", e))?;
+	let hex_encoded_request = hex_encoded_params
+		.first()
+		.ok_or_else(|| "Missing request parameter".to_owned())?;
+
+	let request = Request::from_hex(hex_encoded_request).map_err(|e| format!("{:?}", e))?;
+	let getter =
+		Getter::decode(&mut request.cyphertext.as_slice()).map_err(|e| format!("{:?}", e))?;
+	if !getter.is_authorized() {
+		return Err("Audit log getter has an invalid signature".to_owned())
+	}
+	let (who, page_request) = match &getter {
+		Getter::trusted(signed) => match signed.getter.clone() {
+			TrustedGetter::audit_log(who, page_request) => (who, page_request),
+			_ => return Err("Expected an audit_log getter".to_owned()),
+		},
+		_ => return Err("Expected an audit_log getter".to_owned()),
+	};
+
+	let state_handler = GLOBAL_STATE_HANDLER_COMPONENT.get().map_err(|e| format!("{:?}", e))?;
+	let (mut state, _) =
+		state_handler.load_cloned(&request.shard).map_err(|e| format!("{:?}", e))?;
+	if getter.is_expired(EnclaveStf::state_timestamp(&mut state)) {
+		return Err("Audit log getter has expired".to_owned())
+	}
+	let root: Option<AccountId> = state
+		.get(&storage_value_key("Sudo", "Key"))
+		.and_then(|v| Decode::decode(&mut v.as_slice()).ok());
+	if root.as_ref() != Some(&who) {
+		return Err("Only the root of the shard may query the audit log".to_owned())
+	}
+
+	GLOBAL_AUDIT_LOG_COMPONENT
+		.get()
+		.map_err(|e| format!("{:?}", e))?
+		.audit_log_page(&request.shard, &page_request)
+		.map_err(|e| format!("{:?}", e))
+}
+
 fn forward_dcap_quote_inner(params: Params) -> Result<OpaqueExtrinsic, String> {
 	let hex_encoded_params = params.parse::<Vec<String>>().map_err(|e| format!("{:?}", e))?;
 
@@ -316,13 +607,25 @@ fn attesteer_forward_ias_attestation_report_inner(
 	Ok(ext)
 }
 
-pub fn sidechain_io_handler<ImportFn, Error>(import_fn: ImportFn) -> IoHandler
+pub fn sidechain_io_handler<ImportFn, ConfirmFn, Error, ConfirmError>(
+	import_fn: ImportFn,
+	confirm_fn: ConfirmFn,
+) -> IoHandler
 where
 	ImportFn: Fn(SignedBlock) -> Result<(), Error> + Sync + Send + 'static,
+	ConfirmFn: Fn(Vec<u8>) -> Result<Vec<u8>, ConfirmError> + Sync + Send + 'static,
 	Error: std::fmt::Debug,
+	ConfirmError: std::fmt::Debug,
 {
-	let io = IoHandler::new();
-	import_block_api::add_import_block_rpc_method(import_fn, io)
+	let io = import_block_api::add_import_block_rpc_method(import_fn, IoHandler::new());
+	let mut io = confirm_state_update_api::add_confirm_state_update_rpc_method(confirm_fn, io);
+
+	// Queried by the Substrate compatible RPC methods of the untrusted RPC server.
+	io.add_sync_method("state_getRuntimeVersion", |_: Params| {
+		debug!("sidechain rpc was called: state_getRuntimeVersion");
+		Ok(json!(runtime_version_return_value()))
+	});
+	io
 }
 
 #[cfg(feature = "test")]
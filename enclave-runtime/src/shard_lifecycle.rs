@@ -0,0 +1,397 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Creation of shards with a genesis config and their registration on the parentchain, and
+//! retirement of shards.
+
+use crate::{
+	error::{Error, Result},
+	initialization::global_components::{
+		EnclaveShardGenesisBuilder, EnclaveStateInitializer, EnclaveStf,
+		GLOBAL_OCALL_API_COMPONENT, GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT,
+		GLOBAL_SIDECHAIN_BLOCK_SYNCER_COMPONENT, GLOBAL_STATE_HANDLER_COMPONENT,
+	},
+	shard_vault::get_shard_vault_account,
+	state_snapshot::publish_state_snapshot_internal,
+	utils::{
+		get_extrinsic_factory_from_integritee_solo_or_parachain,
+		get_node_metadata_repository_from_integritee_solo_or_parachain,
+		get_triggered_dispatcher_from_solo_or_parachain,
+		get_validator_accessor_from_solo_or_parachain,
+	},
+};
+use codec::{Compact, Decode, Encode};
+use frame_support::ensure;
+use ita_stf::unshielding::MAX_PENDING_PAYOUTS;
+use itc_parentchain::{
+	block_import_dispatcher::triggered_dispatcher::TriggerParentchainBlockImport,
+	light_client::{concurrent_access::ValidatorAccess, LightClientState},
+};
+use itp_component_container::ComponentGetter;
+use itp_extrinsics_factory::CreateExtrinsics;
+use itp_node_api::metadata::{
+	pallet_balances::BalancesCallIndexes, pallet_enclave_bridge::EnclaveBridgeCallIndexes,
+	pallet_proxy::ProxyCallIndexes, pallet_utility::UtilityCallIndexes,
+	provider::AccessNodeMetadata,
+};
+use itp_ocall_api::{EnclaveAttestationOCallApi, EnclaveOnChainOCallApi};
+use itp_stf_interface::{ShardVaultQuery, SHARD_GENESIS_CONFIG_KEY};
+use itp_stf_state_handler::{
+	handle_state::HandleState, query_shard_state::QueryShardState,
+	shard_genesis_builder::BuildShardGenesis,
+};
+use itp_types::{
+	enclave_upgrade::ShardConfig,
+	parentchain::{Address, ParentchainId, ProxyType},
+	shard_lifecycle::{ShardGenesisConfig, ShardRetirementReport, VaultMode},
+	Balance, BlockNumber, EnclaveFingerprint, OpaqueCall, ShardIdentifier,
+};
+use itp_utils::write_slice_and_whitespace_pad;
+use its_primitives::types::block::SignedBlock as SignedSidechainBlock;
+use its_sidechain::{state::SidechainSystemExt, validateer_fetch::ValidateerFetch};
+use lazy_static::lazy_static;
+use log::*;
+use sgx_types::{c_int, sgx_status_t};
+use std::{
+	collections::BTreeMap,
+	format, slice,
+	sync::{Arc, SgxMutex as Mutex},
+	vec::Vec,
+};
+
+/// Number of parentchain blocks to wait after the shard has entered maintenance mode before its
+/// final state is committed to, such that the last sidechain blocks have reached all peers.
+pub const RETIREMENT_SETTLEMENT_BLOCKS: BlockNumber = 10;
+
+lazy_static! {
+	/// Shards being retired, with the first parentchain block at which this worker has seen them
+	/// in maintenance mode.
+	static ref RETIREMENTS: Mutex<BTreeMap<ShardIdentifier, Option<BlockNumber>>> =
+		Mutex::new(BTreeMap::new());
+}
+
+/// Creates the state of a new shard with the SCALE encoded [`ShardGenesisConfig`] and registers
+/// the shard config on the parentchain.
+#[no_mangle]
+pub unsafe extern "C" fn init_shard_with_genesis(
+	shard: *const u8,
+	shard_size: u32,
+	genesis_config: *const u8,
+	genesis_config_size: u32,
+) -> sgx_status_t {
+	let shard_identifier =
+		ShardIdentifier::from_slice(slice::from_raw_parts(shard, shard_size as usize));
+	let mut genesis_config_slice =
+		slice::from_raw_parts(genesis_config, genesis_config_size as usize);
+	let genesis_config = match ShardGenesisConfig::decode(&mut genesis_config_slice) {
+		Ok(c) => c,
+		Err(e) => {
+			error!("Failed to decode shard genesis config: {:?}", e);
+			return sgx_status_t::SGX_ERROR_UNEXPECTED
+		},
+	};
+
+	if let Err(e) = init_shard_with_genesis_internal(shard_identifier, genesis_config) {
+		error!("Failed to initialize shard {:?} with genesis config: {:?}", shard_identifier, e);
+		return e.into()
+	}
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+/// Writes the SCALE encoded [`VaultMode`] of the shard into `vault_mode`.
+#[no_mangle]
+pub unsafe extern "C" fn get_shard_vault_mode(
+	shard: *const u8,
+	shard_size: u32,
+	vault_mode: *mut u8,
+	vault_mode_size: u32,
+) -> sgx_status_t {
+	let shard_identifier =
+		ShardIdentifier::from_slice(slice::from_raw_parts(shard, shard_size as usize));
+
+	let mode = match shard_genesis_config(&shard_identifier) {
+		Ok(config) => config.vault_mode,
+		Err(e) => {
+			error!("Failed to read genesis config of shard {:?}: {:?}", shard_identifier, e);
+			return e.into()
+		},
+	};
+
+	let vault_mode_slice = slice::from_raw_parts_mut(vault_mode, vault_mode_size as usize);
+	if let Err(e) = write_slice_and_whitespace_pad(vault_mode_slice, mode.encode()) {
+		return Error::BufferError(e).into()
+	};
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+/// Advances the retirement of the shard and writes the SCALE encoded
+/// `Option<ShardRetirementReport>` into `report`, `None` as long as the retirement is pending.
+#[no_mangle]
+pub unsafe extern "C" fn retire_shard(
+	shard: *const u8,
+	shard_size: u32,
+	unshield_vault_funds: c_int,
+	report: *mut u8,
+	report_size: u32,
+) -> sgx_status_t {
+	let shard_identifier =
+		ShardIdentifier::from_slice(slice::from_raw_parts(shard, shard_size as usize));
+
+	let retirement_report = match retire_shard_internal(shard_identifier, unshield_vault_funds == 1)
+	{
+		Ok(r) => r,
+		Err(e) => {
+			error!("Failed to retire shard {:?}: {:?}", shard_identifier, e);
+			return e.into()
+		},
+	};
+
+	let report_slice = slice::from_raw_parts_mut(report, report_size as usize);
+	if let Err(e) = write_slice_and_whitespace_pad(report_slice, retirement_report.encode()) {
+		return Error::BufferError(e).into()
+	};
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+fn init_shard_with_genesis_internal(
+	shard: ShardIdentifier,
+	genesis_config: ShardGenesisConfig,
+) -> Result<()> {
+	let state_handler = GLOBAL_STATE_HANDLER_COMPONENT.get()?;
+	ensure!(!state_handler.shard_exists(&shard)?, Error::Other("shard already exists".into()));
+
+	let shielding_key_repository = GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT.get()?;
+	let genesis_builder = EnclaveShardGenesisBuilder::new(Arc::new(EnclaveStateInitializer::new(
+		shielding_key_repository,
+	)));
+	let genesis_state = genesis_builder.build(&genesis_config)?;
+	state_handler.reset(genesis_state, &shard)?;
+
+	let shard_config = genesis_config.shard_config(own_fingerprint()?);
+	update_shard_config(&shard, shard_config)?;
+
+	info!(
+		"Initialized shard {:?} with {} authorities, {} endowed accounts and vault mode {:?}",
+		shard,
+		genesis_config.authorities.len(),
+		genesis_config.endowed_accounts.len(),
+		genesis_config.vault_mode
+	);
+	Ok(())
+}
+
+/// Genesis config of the shard, the default one for shards created without.
+pub(crate) fn shard_genesis_config(shard: &ShardIdentifier) -> Result<ShardGenesisConfig> {
+	let state_handler = GLOBAL_STATE_HANDLER_COMPONENT.get()?;
+	let encoded_config = state_handler.execute_on_current(shard, |state, _| {
+		state.state.get(SHARD_GENESIS_CONFIG_KEY.as_bytes()).cloned()
+	})?;
+
+	match encoded_config {
+		Some(config) => Ok(ShardGenesisConfig::decode(&mut config.as_slice())?),
+		None => Ok(ShardGenesisConfig::default()),
+	}
+}
+
+/// Stops the authoring of the shard by all validateers, commits to the final state and
+/// optionally pays out the funds of all accounts from the shard vault.
+///
+/// The retirement takes several calls, each after syncing the parentchain, and returns `None`
+/// until it is done:
+/// 1. The shard is put into maintenance mode, in which validateers refuse to author and import
+///    blocks of the shard.
+/// 2. Once the maintenance mode has been in place for [RETIREMENT_SETTLEMENT_BLOCKS], the
+///    last sidechain blocks are imported from a peer and the resulting state is committed to.
+fn retire_shard_internal(
+	shard: ShardIdentifier,
+	unshield_vault_funds: bool,
+) -> Result<Option<ShardRetirementReport>> {
+	let state_handler = GLOBAL_STATE_HANDLER_COMPONENT.get()?;
+	ensure!(state_handler.shard_exists(&shard)?, Error::Other("shard not initialized".into()));
+
+	let genesis_config = shard_genesis_config(&shard)?;
+	ensure!(
+		!unshield_vault_funds || genesis_config.vault_mode == VaultMode::Proxied,
+		Error::Other("shard has no vault to unshield from".into())
+	);
+
+	// No sidechain blocks are authored anymore to trigger the parentchain import, so we look at
+	// the latest synced header instead.
+	let latest_header = match get_triggered_dispatcher_from_solo_or_parachain()?.peek_latest()? {
+		Some(block) => block.block.header,
+		None => get_validator_accessor_from_solo_or_parachain()?.execute_on_validator(|v| {
+			let latest_header = v.latest_finalized_header()?;
+			Ok(latest_header)
+		})?,
+	};
+	let ocall_api = GLOBAL_OCALL_API_COMPONENT.get()?;
+	let onchain_config = ocall_api
+		.shard_config::<_, SignedSidechainBlock>(&latest_header, shard)
+		.map_err(|e| Error::Other(format!("{:?}", e).into()))?
+		.map(|config| config.config_at(latest_header.number).clone());
+
+	let mut retirements = RETIREMENTS.lock().map_err(|_| Error::MutexAccess)?;
+	match onchain_config {
+		Some(config) if config.maintenance_mode => {
+			let maintenance_since =
+				*retirements.entry(shard).or_insert(None).get_or_insert(latest_header.number);
+			if latest_header.number < maintenance_since + RETIREMENT_SETTLEMENT_BLOCKS {
+				info!(
+					"Shard {:?} is in maintenance mode since block {}, waiting for the last sidechain blocks to settle",
+					shard, maintenance_since
+				);
+				return Ok(None)
+			}
+		},
+		config => {
+			if !retirements.contains_key(&shard) {
+				let mut shard_config = match config {
+					Some(config) => config,
+					None => genesis_config.shard_config(own_fingerprint()?),
+				};
+				shard_config.maintenance_mode = true;
+				update_shard_config(&shard, shard_config)?;
+				retirements.insert(shard, None);
+			}
+			info!("Waiting for the maintenance mode of shard {:?} to be finalized", shard);
+			return Ok(None)
+		},
+	}
+
+	let last_block_hash = state_handler
+		.execute_on_current(&shard, |state, _| state.get_last_block_hash())?
+		.unwrap_or_default();
+	GLOBAL_SIDECHAIN_BLOCK_SYNCER_COMPONENT.get()?.import_latest_blocks_from_peer(
+		last_block_hash,
+		&latest_header,
+		shard,
+	)?;
+
+	let final_state = publish_state_snapshot_internal(shard)?;
+
+	// Payouts that have been sent before the retirement, but whose inclusion hasn't been
+	// observed yet. They are neither paid out again nor dropped.
+	let (mut state, _) = state_handler.load_cloned(&shard)?;
+	let unconfirmed_payouts = EnclaveStf::get_unconfirmed_payouts(&mut state);
+	let unconfirmed_amount = sum_of(&unconfirmed_payouts);
+
+	let (unshielded_accounts, unshielded_amount) =
+		if unshield_vault_funds { pay_out_vault(&shard)? } else { (0, 0) };
+	retirements.remove(&shard);
+
+	info!(
+		"Retired shard {:?}, unshielded {} to {} accounts, {} still unconfirmed",
+		shard, unshielded_amount, unshielded_accounts, unconfirmed_amount
+	);
+	Ok(Some(ShardRetirementReport {
+		final_state,
+		unshielded_accounts,
+		unshielded_amount,
+		unconfirmed_payouts: unconfirmed_payouts.len() as u32,
+		unconfirmed_amount,
+	}))
+}
+
+/// Transfers the funds of all accounts of the shard from the vault back to the same account on
+/// the parentchain, in batches of at most [MAX_PENDING_PAYOUTS] transfers.
+///
+/// The paid out balances are burnt in the same state update, so the funds can't be paid out
+/// twice.
+fn pay_out_vault(shard: &ShardIdentifier) -> Result<(u32, Balance)> {
+	let state_handler = GLOBAL_STATE_HANDLER_COMPONENT.get()?;
+	let (state_lock, mut state) = state_handler.load_for_mutation(shard)?;
+	let payouts = EnclaveStf::take_retirement_payouts(&mut state)
+		.map_err(|e| Error::Stf(format!("{:?}", e)))?;
+	if payouts.is_empty() {
+		return Ok((0, 0))
+	}
+
+	let vault = get_shard_vault_account(*shard)?;
+	let node_metadata_repo = get_node_metadata_repository_from_integritee_solo_or_parachain()?;
+	let transfer_call_indexes =
+		node_metadata_repo.get_from_metadata(|m| m.transfer_keep_alive_call_indexes())??;
+	let force_batch_call_indexes =
+		node_metadata_repo.get_from_metadata(|m| m.force_batch_call_indexes())??;
+	let proxy_call_indexes = node_metadata_repo.get_from_metadata(|m| m.proxy_call_indexes())??;
+
+	let calls: Vec<OpaqueCall> = payouts
+		.chunks(MAX_PENDING_PAYOUTS)
+		.map(|chunk| {
+			let transfer_calls: Vec<OpaqueCall> = chunk
+				.iter()
+				.map(|(owner, amount)| {
+					OpaqueCall::from_tuple(&(
+						transfer_call_indexes,
+						Address::from(owner.clone()),
+						Compact(*amount),
+					))
+				})
+				.collect();
+			// `force_batch`, such that a single failing transfer doesn't revert the others.
+			let batch_call = OpaqueCall::from_tuple(&(force_batch_call_indexes, transfer_calls));
+			OpaqueCall::from_tuple(&(
+				proxy_call_indexes,
+				Address::from(vault.clone()),
+				None::<ProxyType>,
+				batch_call,
+			))
+		})
+		.collect();
+
+	let extrinsics_factory = get_extrinsic_factory_from_integritee_solo_or_parachain()?;
+	let xts = extrinsics_factory.create_extrinsics(&calls, None)?;
+	state_handler.write_after_mutation(state, state_lock, shard)?;
+	GLOBAL_OCALL_API_COMPONENT.get()?.send_to_parentchain(
+		xts,
+		&ParentchainId::Integritee,
+		false,
+	)?;
+
+	Ok((payouts.len() as u32, sum_of(&payouts)))
+}
+
+fn sum_of<AccountId>(payouts: &[(AccountId, Balance)]) -> Balance {
+	payouts
+		.iter()
+		.fold(0 as Balance, |sum, (_, amount)| sum.saturating_add(*amount))
+}
+
+/// Sends `EnclaveBridge::update_shard_config` and waits until it has been included.
+fn update_shard_config(shard: &ShardIdentifier, shard_config: ShardConfig) -> Result<()> {
+	let node_metadata_repo = get_node_metadata_repository_from_integritee_solo_or_parachain()?;
+	let extrinsics_factory = get_extrinsic_factory_from_integritee_solo_or_parachain()?;
+	let enactment_delay: BlockNumber = 0;
+
+	let call = OpaqueCall::from_tuple(&(
+		node_metadata_repo.get_from_metadata(|m| m.update_shard_config_call_indexes())??,
+		shard,
+		shard_config,
+		enactment_delay,
+	));
+	let xts = extrinsics_factory.create_extrinsics(&[call], None)?;
+	GLOBAL_OCALL_API_COMPONENT
+		.get()?
+		.send_to_parentchain(xts, &ParentchainId::Integritee, true)?;
+	Ok(())
+}
+
+fn own_fingerprint() -> Result<EnclaveFingerprint> {
+	Ok(GLOBAL_OCALL_API_COMPONENT.get()?.get_mrenclave_of_self()?.m.into())
+}
@@ -17,9 +17,10 @@
 use crate::{
 	error::{Error, Result as EnclaveResult},
 	initialization::global_components::{
-		GLOBAL_OCALL_API_COMPONENT, GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT,
-		GLOBAL_STATE_HANDLER_COMPONENT,
+		EnclaveStf, GLOBAL_OCALL_API_COMPONENT, GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT,
+		GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT, GLOBAL_STATE_HANDLER_COMPONENT,
 	},
+	shard_lifecycle::shard_genesis_config,
 	utils::{
 		get_extrinsic_factory_from_integritee_solo_or_parachain,
 		get_node_metadata_repository_from_integritee_solo_or_parachain,
@@ -36,19 +37,27 @@ use itp_node_api::{
 	},
 };
 use itp_node_api_metadata::pallet_proxy::ProxyCallIndexes;
-use itp_nonce_cache::NonceCache;
+use itp_nonce_cache::{Nonce, NonceCache};
 use itp_ocall_api::EnclaveOnChainOCallApi;
-use itp_sgx_crypto::key_repository::AccessKey;
-use itp_stf_interface::SHARD_VAULT_KEY;
+use itp_sgx_crypto::{ed25519_derivation::DeriveEd25519, key_repository::AccessKey};
+use itp_stf_interface::{ShardVaultQuery, SHARD_VAULT_KEY};
 use itp_stf_state_handler::{handle_state::HandleState, query_shard_state::QueryShardState};
 use itp_types::{
 	parentchain::{AccountId, Address, ParentchainId, ProxyType},
+	shard_lifecycle::VaultMode,
 	OpaqueCall, ShardIdentifier,
 };
 use log::*;
 use sgx_types::sgx_status_t;
-use sp_core::crypto::{DeriveJunction, Pair};
-use std::{slice, sync::Arc, vec::Vec};
+use sp_core::{
+	crypto::{DeriveJunction, Pair},
+	ed25519,
+};
+use std::{
+	slice,
+	sync::{Arc, SgxRwLock as RwLock},
+	vec::Vec,
+};
 
 #[no_mangle]
 pub unsafe extern "C" fn init_proxied_shard_vault(
@@ -105,11 +114,56 @@ pub(crate) fn get_shard_vault_account(shard: ShardIdentifier) -> EnclaveResult<A
 		})
 }
 
+/// Proxy of the shard vault that signs the unshield payout batches of the shard and nothing
+/// else, such that its account nonce identifies the batches, see [ita_stf::unshielding]. It is
+/// derived from the shielding key, which is shared by all validateers of the shard. It pays the
+/// fees of the batches and is funded by the enclave account when the vault is initialized.
+pub(crate) fn payout_signer(shard: &ShardIdentifier) -> EnclaveResult<ed25519::Pair> {
+	let key = GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT
+		.get()?
+		.retrieve_key()?
+		.derive_ed25519()?;
+	let junctions =
+		vec![DeriveJunction::hard(b"unshield-payouts"), DeriveJunction::hard(shard.encode())];
+	key.derive(junctions.into_iter(), None)
+		.map(|(pair, _)| pair)
+		.map_err(|_| Error::Other("failed to derive payout signer keypair".into()))
+}
+
+/// Signs the unshield payout batch that has been sent in the last block of the shard with the
+/// payout signer, and sends it to the parentchain.
+pub(crate) fn send_unshield_payout_batch(shard: &ShardIdentifier) -> EnclaveResult<()> {
+	let (mut state, _) = GLOBAL_STATE_HANDLER_COMPONENT.get()?.load_cloned(shard)?;
+	let (nonce, call) = match EnclaveStf::get_payout_batch_to_send(&mut state) {
+		Some(batch) => batch,
+		None => return Ok(()),
+	};
+
+	let nonce_cache = Arc::new(NonceCache::new(RwLock::new(Nonce(nonce))));
+	let payout_extrinsics_factory = get_extrinsic_factory_from_integritee_solo_or_parachain()?
+		.with_signer(
+			StaticExtrinsicSigner::<_, PairSignature>::new(payout_signer(shard)?),
+			nonce_cache,
+		);
+
+	info!("sending unshield payout batch of shard {:?} with nonce {}", shard, nonce);
+	let xts = payout_extrinsics_factory.create_extrinsics(&[call], None)?;
+	GLOBAL_OCALL_API_COMPONENT.get()?.send_to_parentchain(
+		xts,
+		&ParentchainId::Integritee,
+		false,
+	)?;
+	Ok(())
+}
+
 pub(crate) fn init_proxied_shard_vault_internal(shard: ShardIdentifier) -> EnclaveResult<()> {
 	let state_handler = GLOBAL_STATE_HANDLER_COMPONENT.get()?;
 	if !state_handler.shard_exists(&shard).unwrap() {
 		return Err(Error::Other("shard not initialized".into()))
 	};
+	if shard_genesis_config(&shard)?.vault_mode != VaultMode::Proxied {
+		return Err(Error::Other("shard has been created without a vault".into()))
+	}
 
 	let ocall_api = GLOBAL_OCALL_API_COMPONENT.get()?;
 	let enclave_signer = GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT.get()?.retrieve_key()?;
@@ -120,25 +174,32 @@ pub(crate) fn init_proxied_shard_vault_internal(shard: ShardIdentifier) -> Encla
 		.map_err(|_| Error::Other("failed to derive shard vault keypair".into()))?
 		.0;
 
+	let payout_signer = payout_signer(&shard)?;
+
 	info!("shard vault account derived pubkey: 0x{}", hex::encode(vault.public().0));
 
 	let (state_lock, mut state) = state_handler.load_for_mutation(&shard)?;
 	state.state.insert(SHARD_VAULT_KEY.into(), vault.public().0.to_vec());
 	state_handler.write_after_mutation(state, state_lock, &shard)?;
 
-	info!("send existential funds from enclave account to vault account");
+	info!("send existential funds from enclave account to vault account and payout signer");
 	let call_ids = node_metadata_repo
 		.get_from_metadata(|m| m.call_indexes("Balances", "transfer_keep_alive"))?
 		.map_err(MetadataProviderError::MetadataError)?;
 
-	let call = OpaqueCall::from_tuple(&(
-		call_ids,
-		Address::from(AccountId::from(vault.public().0)),
-		Compact(PROXY_DEPOSIT),
-	));
+	let calls: Vec<OpaqueCall> = [vault.public().0, payout_signer.public().0]
+		.into_iter()
+		.map(|account| {
+			OpaqueCall::from_tuple(&(
+				call_ids,
+				Address::from(AccountId::from(account)),
+				Compact(PROXY_DEPOSIT),
+			))
+		})
+		.collect();
 
-	info!("vault funding call: 0x{}", hex::encode(call.0.clone()));
-	let xts = enclave_extrinsics_factory.create_extrinsics(&[call], None)?;
+	info!("vault funding call: 0x{}", hex::encode(calls[0].0.clone()));
+	let xts = enclave_extrinsics_factory.create_extrinsics(&calls, None)?;
 
 	//this extrinsic must be included in a block before we can move on. otherwise the next will fail
 	ocall_api.send_to_parentchain(xts, &ParentchainId::Integritee, true)?;
@@ -148,20 +209,25 @@ pub(crate) fn init_proxied_shard_vault_internal(shard: ShardIdentifier) -> Encla
 	let vault_extrinsics_factory = enclave_extrinsics_factory
 		.with_signer(StaticExtrinsicSigner::<_, PairSignature>::new(vault), nonce_cache);
 
-	info!("register enclave signer as proxy for shard vault");
+	info!("register enclave signer and payout signer as proxies for shard vault");
 	let call_ids = node_metadata_repo
 		.get_from_metadata(|m| m.call_indexes("Proxy", "add_proxy"))?
 		.map_err(MetadataProviderError::MetadataError)?;
 
-	let call = OpaqueCall::from_tuple(&(
-		call_ids,
-		Address::from(AccountId::from(enclave_signer.public().0)),
-		ProxyType::Any,
-		0u32, // delay
-	));
+	let calls: Vec<OpaqueCall> = [enclave_signer.public().0, payout_signer.public().0]
+		.into_iter()
+		.map(|proxy| {
+			OpaqueCall::from_tuple(&(
+				call_ids,
+				Address::from(AccountId::from(proxy)),
+				ProxyType::Any,
+				0u32, // delay
+			))
+		})
+		.collect();
 
-	info!("add proxy call: 0x{}", hex::encode(call.0.clone()));
-	let xts = vault_extrinsics_factory.create_extrinsics(&[call], None)?;
+	info!("add proxy call: 0x{}", hex::encode(calls[0].0.clone()));
+	let xts = vault_extrinsics_factory.create_extrinsics(&calls, None)?;
 
 	ocall_api.send_to_parentchain(xts, &ParentchainId::Integritee, false)?;
 	Ok(())
@@ -0,0 +1,63 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Peer side of the pessimistic execution mode: re-executes a proposed state update and
+//! signs it, if it results in the proposed state.
+
+use crate::{
+	error::Result,
+	initialization::global_components::{
+		EnclaveGetter, EnclaveTrustedCallSigned, GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT,
+		GLOBAL_STATE_KEY_REPOSITORY_COMPONENT,
+	},
+	sync::{EnclaveLock, EnclaveStateRWLock},
+	utils::get_stf_executor_from_solo_or_parachain,
+};
+use codec::{Decode, Encode};
+use itp_component_container::ComponentGetter;
+use itp_sgx_crypto::{key_repository::AccessKey, StateAead};
+use itp_types::{parentchain::Header as ParentchainHeader, state_confirmation::StateConfirmation};
+use its_sidechain::aura::state_confirmation::{reexecute_state_update, StateConfirmationRequest};
+use log::*;
+use std::vec::Vec;
+
+/// Decrypts the confirmation request of a peer, re-executes it and returns the encoded
+/// [`StateConfirmation`].
+pub(crate) fn confirm_state_update(encrypted_request: Vec<u8>) -> Result<Vec<u8>> {
+	let request_bytes = GLOBAL_STATE_KEY_REPOSITORY_COMPONENT
+		.get()?
+		.retrieve_key()?
+		.open(&encrypted_request)?;
+	let request: StateConfirmationRequest<
+		ParentchainHeader,
+		EnclaveTrustedCallSigned,
+		EnclaveGetter,
+	> = Decode::decode(&mut request_bytes.as_slice())?;
+
+	info!(
+		"Re-executing state update of sidechain block {} of shard {:?} for confirmation",
+		request.block_number, request.shard
+	);
+
+	let update = {
+		let _enclave_read_lock = EnclaveLock::read_all()?;
+		let stf_executor = get_stf_executor_from_solo_or_parachain()?;
+		reexecute_state_update(stf_executor.as_ref(), &request)?
+	};
+
+	let signer = GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT.get()?.retrieve_key()?;
+	Ok(StateConfirmation::new(update, &signer).encode())
+}
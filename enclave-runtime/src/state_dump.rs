@@ -0,0 +1,76 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	error::{Error, Result},
+	initialization::global_components::GLOBAL_STATE_HANDLER_COMPONENT,
+};
+use codec::Encode;
+use itp_component_container::ComponentGetter;
+use itp_stf_state_handler::handle_state::HandleState;
+use itp_types::{state_dump::StateDump, ShardIdentifier};
+use itp_utils::write_slice_and_whitespace_pad;
+use log::*;
+use sgx_types::sgx_status_t;
+use std::slice;
+
+/// Writes the SCALE encoded [`StateDump`] of the current state of `shard` into `dump`.
+///
+/// Only available if the enclave was built with the `state-dump` feature.
+#[no_mangle]
+pub unsafe extern "C" fn dump_state(
+	shard: *const u8,
+	shard_size: u32,
+	dump: *mut u8,
+	dump_size: u32,
+) -> sgx_status_t {
+	if !cfg!(feature = "state-dump") {
+		error!("Dumping the state requires an enclave built with the state-dump feature");
+		return sgx_status_t::SGX_ERROR_FEATURE_NOT_SUPPORTED
+	}
+
+	let shard_identifier =
+		ShardIdentifier::from_slice(slice::from_raw_parts(shard, shard_size as usize));
+
+	let state_dump = match dump_state_internal(shard_identifier) {
+		Ok(d) => d,
+		Err(e) => {
+			error!("Failed to dump state of shard {:?}: {:?}", shard_identifier, e);
+			return e.into()
+		},
+	};
+
+	let dump_slice = slice::from_raw_parts_mut(dump, dump_size as usize);
+	if let Err(e) = write_slice_and_whitespace_pad(dump_slice, state_dump.encode()) {
+		return Error::BufferError(e).into()
+	};
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+fn dump_state_internal(shard: ShardIdentifier) -> Result<StateDump> {
+	let state_handler = GLOBAL_STATE_HANDLER_COMPONENT.get()?;
+
+	let entries = state_handler.execute_on_current(&shard, |state, _| {
+		state.state.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+	})?;
+
+	let state_dump = StateDump::new(shard, entries);
+	info!("Dumping {} storage entries of shard {:?}", state_dump.entries.len(), shard);
+
+	Ok(state_dump)
+}
@@ -0,0 +1,291 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Publication of encrypted shard state snapshots on IPFS, committed on the parentchain,
+//! and bootstrapping of a shard from such a snapshot.
+//!
+//! A snapshot is sealed with the state AEAD under a fresh random nonce, so snapshots never
+//! share a keystream. A shard is only bootstrapped from a snapshot whose commitment is found
+//! in the `PublishedHash` events of a parentchain block proven by the light client.
+
+use crate::{
+	error::{Error, Result},
+	initialization::global_components::{
+		GLOBAL_OCALL_API_COMPONENT, GLOBAL_STATE_HANDLER_COMPONENT,
+		GLOBAL_STATE_KEY_REPOSITORY_COMPONENT,
+	},
+	ipfs::IpfsContent,
+	utils::{
+		get_extrinsic_factory_from_integritee_solo_or_parachain,
+		get_node_metadata_repository_from_integritee_solo_or_parachain,
+		get_validator_accessor_from_solo_or_parachain,
+	},
+};
+use codec::{Decode, Encode, Input};
+use frame_support::ensure;
+use ita_parentchain_interface::integritee::FilterableEvents;
+use itc_parentchain::{
+	indirect_calls_executor::filter_metadata::{EventCreator, EventsFromMetadata},
+	light_client::{concurrent_access::ValidatorAccess, LightClientState},
+};
+use itp_component_container::ComponentGetter;
+use itp_extrinsics_factory::CreateExtrinsics;
+use itp_node_api::metadata::{
+	pallet_enclave_bridge::EnclaveBridgeCallIndexes, provider::AccessNodeMetadata,
+};
+use itp_ocall_api::{EnclaveIpfsOCallApi, EnclaveOnChainOCallApi};
+use itp_sgx_crypto::{key_repository::AccessKey, StateAead};
+use itp_sgx_externalities::{SgxExternalities, SgxExternalitiesTrait, StateHash};
+use itp_stf_state_handler::{handle_state::HandleState, query_shard_state::QueryShardState};
+use itp_storage::{storage_map_key, storage_value_key, StorageHasher};
+use itp_types::{
+	parentchain::{FilterEvents, Header as ParentchainHeader, ParentchainId},
+	state_snapshot::StateSnapshotCommitment,
+	OpaqueCall, ShardIdentifier, H256,
+};
+use itp_utils::write_slice_and_whitespace_pad;
+use log::*;
+use sgx_types::sgx_status_t;
+use sp_runtime::traits::Header as HeaderT;
+use std::{slice, vec::Vec};
+
+/// Publishes the current state of `shard` and writes the SCALE encoded
+/// [`StateSnapshotCommitment`] into `commitment`.
+#[no_mangle]
+pub unsafe extern "C" fn publish_state_snapshot(
+	shard: *const u8,
+	shard_size: u32,
+	commitment: *mut u8,
+	commitment_size: u32,
+) -> sgx_status_t {
+	let shard_identifier =
+		ShardIdentifier::from_slice(slice::from_raw_parts(shard, shard_size as usize));
+
+	let state_commitment = match publish_state_snapshot_internal(shard_identifier) {
+		Ok(c) => c,
+		Err(e) => {
+			error!("Failed to publish state snapshot of shard {:?}: {:?}", shard_identifier, e);
+			return e.into()
+		},
+	};
+
+	let commitment_slice = slice::from_raw_parts_mut(commitment, commitment_size as usize);
+	if let Err(e) = write_slice_and_whitespace_pad(commitment_slice, state_commitment.encode()) {
+		return Error::BufferError(e).into()
+	};
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+/// Replaces the state of the committed shard with the encrypted `snapshot`. The commitment
+/// must have been published in the parentchain block with the SCALE encoded
+/// `publication_header`.
+#[no_mangle]
+pub unsafe extern "C" fn bootstrap_shard_from_state_snapshot(
+	commitment: *const u8,
+	commitment_size: u32,
+	publication_header: *const u8,
+	publication_header_size: u32,
+	snapshot: *const u8,
+	snapshot_size: u32,
+) -> sgx_status_t {
+	let mut commitment_slice = slice::from_raw_parts(commitment, commitment_size as usize);
+	let state_commitment = match StateSnapshotCommitment::decode(&mut commitment_slice) {
+		Ok(c) => c,
+		Err(e) => {
+			error!("Failed to decode state snapshot commitment: {:?}", e);
+			return sgx_status_t::SGX_ERROR_UNEXPECTED
+		},
+	};
+	let mut header_slice =
+		slice::from_raw_parts(publication_header, publication_header_size as usize);
+	let publication_header = match ParentchainHeader::decode(&mut header_slice) {
+		Ok(h) => h,
+		Err(e) => {
+			error!("Failed to decode publication header: {:?}", e);
+			return sgx_status_t::SGX_ERROR_UNEXPECTED
+		},
+	};
+	let snapshot = slice::from_raw_parts(snapshot, snapshot_size as usize).to_vec();
+
+	if let Err(e) = bootstrap_shard_from_state_snapshot_internal(
+		&state_commitment,
+		&publication_header,
+		snapshot,
+	) {
+		error!(
+			"Failed to bootstrap shard {:?} from state snapshot: {:?}",
+			state_commitment.shard, e
+		);
+		return e.into()
+	}
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+pub(crate) fn publish_state_snapshot_internal(
+	shard: ShardIdentifier,
+) -> Result<StateSnapshotCommitment> {
+	let state_handler = GLOBAL_STATE_HANDLER_COMPONENT.get()?;
+	let (state, state_hash) = state_handler.load_cloned(&shard)?;
+
+	// The shard is sealed together with the state, such that a snapshot can not be installed
+	// into another shard.
+	let mut nonce = [0u8; 12];
+	nonce.copy_from_slice(&sp_io::offchain::random_seed()[..12]);
+	let snapshot = GLOBAL_STATE_KEY_REPOSITORY_COMPONENT
+		.get()?
+		.retrieve_key()?
+		.seal(&(shard, state.state()).encode(), nonce)?;
+
+	let ocall_api = GLOBAL_OCALL_API_COMPONENT.get()?;
+	let cid = ocall_api.write_ipfs(&snapshot)?;
+	let commitment = StateSnapshotCommitment::new(shard, state_hash, cid.0);
+
+	// The CID is reported by the untrusted IPFS node, make sure we commit to our snapshot.
+	verify_snapshot_cid(&commitment, snapshot)?;
+
+	let node_metadata_repo = get_node_metadata_repository_from_integritee_solo_or_parachain()?;
+	let extrinsics_factory = get_extrinsic_factory_from_integritee_solo_or_parachain()?;
+	let publish_hash_call = OpaqueCall::from_tuple(&(
+		node_metadata_repo.get_from_metadata(|m| m.publish_hash_call_indexes())??,
+		commitment.state_hash,
+		vec![commitment.shard],
+		commitment.cid.to_vec(),
+	));
+	let xts = extrinsics_factory.create_extrinsics(&[publish_hash_call], None)?;
+	ocall_api.send_to_parentchain(xts, &ParentchainId::Integritee, false)?;
+
+	info!(
+		"Published state snapshot of shard {:?} with state hash {:?}, CID {:?}",
+		shard,
+		commitment.state_hash,
+		commitment.cid_str()
+	);
+	Ok(commitment)
+}
+
+fn bootstrap_shard_from_state_snapshot_internal(
+	commitment: &StateSnapshotCommitment,
+	publication_header: &ParentchainHeader,
+	snapshot: Vec<u8>,
+) -> Result<()> {
+	verify_commitment_published(commitment, publication_header)?;
+	verify_snapshot_cid(commitment, snapshot.clone())?;
+
+	let plaintext = GLOBAL_STATE_KEY_REPOSITORY_COMPONENT.get()?.retrieve_key()?.open(&snapshot)?;
+	let (shard, state): (ShardIdentifier, _) = Decode::decode(&mut plaintext.as_slice())?;
+	ensure!(shard == commitment.shard, Error::StateSnapshotHashMismatch);
+	let state = SgxExternalities::new(state);
+	ensure!(
+		<SgxExternalities as StateHash>::hash(&state) == commitment.state_hash,
+		Error::StateSnapshotHashMismatch
+	);
+
+	let state_handler = GLOBAL_STATE_HANDLER_COMPONENT.get()?;
+	if !state_handler.shard_exists(&commitment.shard)? {
+		state_handler.initialize_shard(commitment.shard)?;
+	}
+	state_handler.reset(state, &commitment.shard)?;
+
+	info!(
+		"Bootstrapped shard {:?} from state snapshot with state hash {:?}",
+		commitment.shard, commitment.state_hash
+	);
+	Ok(())
+}
+
+/// Verifies that `commitment` was published in the block of `publication_header`.
+///
+/// The light client only vouches for the latest finalized header. The publication header is
+/// proven by its hash in `System::BlockHash` at that header, which the parentchain keeps for
+/// its most recent blocks only. Its events are then read with a proof against its state root.
+fn verify_commitment_published(
+	commitment: &StateSnapshotCommitment,
+	publication_header: &ParentchainHeader,
+) -> Result<()> {
+	let latest_header =
+		get_validator_accessor_from_solo_or_parachain()?.execute_on_validator(|v| {
+			let latest_header = v.latest_finalized_header()?;
+			Ok(latest_header)
+		})?;
+	let ocall_api = GLOBAL_OCALL_API_COMPONENT.get()?;
+
+	let block_hash_key = storage_map_key(
+		"System",
+		"BlockHash",
+		publication_header.number(),
+		&StorageHasher::Twox64Concat,
+	);
+	let block_hash: Option<H256> = ocall_api
+		.get_storage_verified(block_hash_key, &latest_header, &ParentchainId::Integritee)?
+		.into_tuple()
+		.1;
+	ensure!(
+		block_hash == Some(publication_header.hash()),
+		Error::Other(
+			"Publication block is not a recent ancestor of the latest finalized block".into()
+		)
+	);
+
+	let events: Option<EncodedEvents> = ocall_api
+		.get_storage_verified(
+			storage_value_key("System", "Events"),
+			publication_header,
+			&ParentchainId::Integritee,
+		)?
+		.into_tuple()
+		.1;
+	let events = events.ok_or_else(|| Error::Other("Publication block has no events".into()))?;
+	let events = get_node_metadata_repository_from_integritee_solo_or_parachain()?
+		.get_from_metadata(|m| {
+			EventCreator::<FilterableEvents>::create_from_metadata(
+				m.clone(),
+				publication_header.hash(),
+				&events.0,
+			)
+		})?
+		.ok_or_else(|| Error::Other("Could not decode events of the publication block".into()))?;
+
+	let published = events
+		.get_published_hash_events()
+		.map_err(|e| Error::Other(format!("{:?}", e).into()))?
+		.iter()
+		.any(|e| e.hash == commitment.state_hash && e.data == commitment.cid.to_vec());
+	ensure!(published, Error::StateSnapshotNotPublished);
+	Ok(())
+}
+
+/// Undecoded `System::Events` storage value, decoded with the node metadata afterwards.
+struct EncodedEvents(Vec<u8>);
+
+impl Decode for EncodedEvents {
+	fn decode<I: Input>(input: &mut I) -> core::result::Result<Self, codec::Error> {
+		let len = input.remaining_len()?.ok_or("Length of the encoded events is unknown")?;
+		let mut events = vec![0u8; len];
+		input.read(&mut events)?;
+		Ok(EncodedEvents(events))
+	}
+}
+
+/// Verifies that the encrypted snapshot is the content addressed by the committed CID.
+fn verify_snapshot_cid(commitment: &StateSnapshotCommitment, snapshot: Vec<u8>) -> Result<()> {
+	let cid = commitment
+		.cid_str()
+		.ok_or_else(|| Error::Other("CID is not valid UTF-8".into()))?;
+	Ok(IpfsContent::new(cid, snapshot).verify()?)
+}
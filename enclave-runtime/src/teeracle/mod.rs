@@ -26,10 +26,12 @@ use crate::{
 use codec::{Decode, Encode};
 use core::slice;
 use ita_oracle::{
-	create_coin_gecko_oracle, create_coin_market_cap_oracle, create_open_meteo_weather_oracle,
+	create_coin_gecko_oracle, create_coin_market_cap_oracle, create_median_exchange_rate_oracle,
+	create_open_meteo_weather_oracle,
 	metrics_exporter::ExportMetrics,
 	oracles::{
 		exchange_rate_oracle::{ExchangeRateOracle, GetExchangeRate},
+		median_oracle::GetAggregatedValue,
 		weather_oracle::{GetLongitude, WeatherOracle},
 	},
 	traits::OracleSource,
@@ -38,7 +40,7 @@ use ita_oracle::{
 use itp_component_container::ComponentGetter;
 use itp_extrinsics_factory::CreateExtrinsics;
 use itp_node_api::metadata::{pallet_teeracle::TeeracleCallIndexes, provider::AccessNodeMetadata};
-use itp_types::OpaqueCall;
+use itp_types::{oracle::OracleFeedConfig, OpaqueCall};
 use itp_utils::write_slice_and_whitespace_pad;
 use log::*;
 use sgx_types::sgx_status_t;
@@ -265,3 +267,88 @@ where
 
 	Ok(call)
 }
+
+/// Fetches the exchange rate of the configured feed from all its data sources and creates the
+/// extrinsic publishing their median.
+#[no_mangle]
+pub unsafe extern "C" fn update_oracle_feed_xt(
+	feed_config: *const u8,
+	feed_config_size: u32,
+	unchecked_extrinsic: *mut u8,
+	unchecked_extrinsic_size: u32,
+) -> sgx_status_t {
+	let mut feed_config_slice = slice::from_raw_parts(feed_config, feed_config_size as usize);
+	let feed_config = match OracleFeedConfig::decode(&mut feed_config_slice) {
+		Ok(config) => config,
+		Err(e) => {
+			error!("Could not decode oracle feed config: {:?}", e);
+			return sgx_status_t::SGX_ERROR_UNEXPECTED
+		},
+	};
+
+	let extrinsics = match update_oracle_feed_internal(feed_config) {
+		Ok(xts) => xts,
+		Err(e) => {
+			error!("Update oracle feed failed: {:?}", e);
+			return sgx_status_t::SGX_ERROR_UNEXPECTED
+		},
+	};
+
+	let extrinsic_slice =
+		slice::from_raw_parts_mut(unchecked_extrinsic, unchecked_extrinsic_size as usize);
+
+	// Save created extrinsic as slice in the return value unchecked_extrinsic.
+	if let Err(e) = write_slice_and_whitespace_pad(extrinsic_slice, extrinsics.encode()) {
+		error!("Copying encoded extrinsics into return slice failed: {:?}", e);
+		return sgx_status_t::SGX_ERROR_UNEXPECTED
+	}
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+fn update_oracle_feed_internal(feed_config: OracleFeedConfig) -> Result<Vec<OpaqueExtrinsic>> {
+	let extrinsics_factory = get_extrinsic_factory_from_integritee_solo_or_parachain()?;
+	let ocall_api = GLOBAL_OCALL_API_COMPONENT.get()?;
+
+	let oracle = create_median_exchange_rate_oracle(
+		ocall_api,
+		&feed_config.sources,
+		feed_config.min_sources as usize,
+	)
+	.map_err(|e| Error::Other(e.into()))?;
+
+	let data_source = feed_config.data_source();
+	let trading_pair = TradingPair {
+		crypto_currency: feed_config.crypto_currency,
+		fiat_currency: feed_config.fiat_currency,
+	};
+
+	let aggregated =
+		oracle.get_aggregated_value(&trading_pair).map_err(|e| Error::Other(e.into()))?;
+
+	println!(
+		"Update the exchange rate:  {} = {:?} for source {}, median of {:?}",
+		trading_pair.clone().key(),
+		aggregated.value,
+		data_source,
+		aggregated.sources,
+	);
+
+	let node_metadata_repository =
+		get_node_metadata_repository_from_integritee_solo_or_parachain()?;
+
+	let call_ids = node_metadata_repository
+		.get_from_metadata(|m| m.update_exchange_rate_call_indexes())
+		.map_err(Error::NodeMetadataProvider)?
+		.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+
+	let call = OpaqueCall::from_tuple(&(
+		call_ids,
+		data_source.as_bytes().to_vec(),
+		trading_pair.key().as_bytes().to_vec(),
+		Some(aggregated.value),
+	));
+
+	let extrinsics = extrinsics_factory.create_extrinsics(&[call], None)?;
+	Ok(extrinsics)
+}
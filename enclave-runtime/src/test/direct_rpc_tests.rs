@@ -25,7 +25,7 @@ use itc_direct_rpc_server::{
 };
 use itc_tls_websocket_server::{ConnectionToken, WebSocketMessageHandler};
 use itp_rpc::{RpcRequest, RpcReturnValue};
-use itp_sgx_crypto::get_rsa3072_repository;
+use itp_sgx_crypto::{get_ed25519_repository, get_rsa3072_repository};
 use itp_sgx_temp_dir::TempDir;
 use itp_stf_executor::{getter_executor::GetterExecutor, mocks::GetStateMock};
 use itp_stf_state_observer::mock::ObserveStateMock;
@@ -51,12 +51,18 @@ pub fn get_state_request_works() {
 		Arc::new(GetterExecutor::<_, GetStateMock<TestState>, Getter>::new(state_observer));
 	let top_pool_author = Arc::new(AuthorApiMock::default());
 
-	let io_handler =
-		public_api_rpc_handler(top_pool_author, getter_executor, Arc::new(rsa_repository));
+	let signing_key_repository = get_ed25519_repository(temp_dir.path().to_path_buf()).unwrap();
+	let io_handler = public_api_rpc_handler(
+		top_pool_author,
+		getter_executor,
+		Arc::new(rsa_repository),
+		Arc::new(signing_key_repository),
+	);
 	let rpc_handler = Arc::new(RpcWsHandler::new(io_handler, watch_extractor, connection_registry));
 
 	let getter = Getter::trusted(TrustedGetterSigned::new(
 		TrustedGetter::nonce(AccountId::new([0u8; 32])),
+		u64::MAX,
 		MultiSignature::Ed25519(Signature::from_raw([0u8; 64])),
 	));
 
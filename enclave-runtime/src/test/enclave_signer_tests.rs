@@ -23,7 +23,10 @@ use itp_sgx_crypto::{
 	ed25519_derivation::DeriveEd25519, key_repository::AccessKey, mocks::KeyRepositoryMock,
 };
 use itp_sgx_externalities::SgxExternalities;
-use itp_stf_executor::{enclave_signer::StfEnclaveSigner, traits::StfEnclaveSigning};
+use itp_stf_executor::{
+	enclave_signer::StfEnclaveSigner,
+	traits::{StfEnclaveSigning, StfShardVaultQuery},
+};
 use itp_stf_interface::{
 	mocks::GetterExecutorMock, system_pallet::SystemPalletAccountInterface, InitState,
 	StateCallInterface,
@@ -32,16 +35,34 @@ use itp_stf_primitives::{
 	traits::TrustedCallVerification,
 	types::{AccountId, ShardIdentifier, TrustedOperation},
 };
-use itp_stf_state_observer::mock::ObserveStateMock;
+use itp_stf_state_observer::mock::{ObserveStateMock, RecordedStateObserver};
 use itp_test::mock::onchain_mock::OnchainMock;
 use itp_top_pool_author::{mocks::AuthorApiMock, traits::AuthorApi};
 use sgx_crypto_helper::{rsa3072::Rsa3072KeyPair, RsaKeyPair};
-use sp_core::Pair;
+use sp_core::{ed25519::Pair as Ed25519Pair, Pair};
 use std::{sync::Arc, vec::Vec};
 
 type ShieldingKeyRepositoryMock = KeyRepositoryMock<Rsa3072KeyPair>;
+type DumpedEnclaveKeyRepository = KeyRepositoryMock<DumpedEnclaveKey>;
 type TestStf = Stf<TrustedCallSigned, GetterExecutorMock, SgxExternalities, Runtime>;
 
+/// State of a shard as written by the `dump-state` command of the worker, see
+/// `fixtures/generate_state_dump.py` for its content.
+const STATE_DUMP: &[u8] = include_bytes!("fixtures/state_dump.bin");
+const DUMPED_SHARD: ShardIdentifier = ShardIdentifier::repeat_byte(1);
+const DUMPED_SHARD_VAULT: AccountId = AccountId::new([9u8; 32]);
+const DUMPED_ENCLAVE_ACCOUNT_NONCE: u32 = 5;
+
+/// Stands in for the shielding key of the enclave whose state was dumped.
+#[derive(Clone, Default)]
+struct DumpedEnclaveKey;
+
+impl DeriveEd25519 for DumpedEnclaveKey {
+	fn derive_ed25519(&self) -> itp_sgx_crypto::Result<Ed25519Pair> {
+		Ok(Ed25519Pair::from_seed(&[7u8; 32]))
+	}
+}
+
 pub fn derive_key_is_deterministic() {
 	let rsa_key = Rsa3072KeyPair::new().unwrap();
 
@@ -95,6 +116,7 @@ pub fn nonce_is_computed_correctly() {
 	let state_observer: Arc<ObserveStateMock<SgxExternalities>> =
 		Arc::new(ObserveStateMock::new(state.clone()));
 	let shard = ShardIdentifier::default();
+	let mrenclave = ocall_api.get_mrenclave_of_self().unwrap().m;
 	let enclave_signer = StfEnclaveSigner::<_, _, _, TestStf, _, TrustedCallSigned, Getter>::new(
 		state_observer,
 		ocall_api,
@@ -138,10 +160,56 @@ pub fn nonce_is_computed_correctly() {
 	assert!(TestStf::execute_call(
 		&mut state,
 		trusted_call_1_signed,
+		&mrenclave,
+		&shard,
 		&mut Vec::new(),
 		repo.clone()
 	)
 	.is_ok());
-	assert!(TestStf::execute_call(&mut state, trusted_call_2_signed, &mut Vec::new(), repo).is_ok());
+	assert!(TestStf::execute_call(
+		&mut state,
+		trusted_call_2_signed,
+		&mrenclave,
+		&shard,
+		&mut Vec::new(),
+		repo
+	)
+	.is_ok());
 	assert_eq!(2, TestStf::get_account_nonce(&mut state, &enclave_account));
 }
+
+pub fn nonce_is_read_from_recorded_state() {
+	let shielding_key_repo = Arc::new(DumpedEnclaveKeyRepository::default());
+	let state_observer =
+		Arc::new(RecordedStateObserver::<SgxExternalities>::from_encoded_dump(STATE_DUMP).unwrap());
+
+	let enclave_signer = StfEnclaveSigner::<_, _, _, TestStf, _, TrustedCallSigned, Getter>::new(
+		state_observer,
+		Arc::new(OnchainMock::default()),
+		shielding_key_repo,
+		Arc::new(AuthorApiMock::default()),
+	);
+	let enclave_account = enclave_signer.get_enclave_account().unwrap();
+	let trusted_call =
+		TrustedCall::balance_shield(enclave_account, AccountId::new([3u8; 32]), 200u128);
+
+	let trusted_call_signed =
+		enclave_signer.sign_call_with_self(&trusted_call, &DUMPED_SHARD).unwrap();
+	assert_eq!(DUMPED_ENCLAVE_ACCOUNT_NONCE, trusted_call_signed.nonce);
+}
+
+pub fn shard_vault_is_read_from_recorded_state() {
+	let state_observer =
+		Arc::new(RecordedStateObserver::<SgxExternalities>::from_encoded_dump(STATE_DUMP).unwrap());
+
+	let enclave_signer = StfEnclaveSigner::<_, _, _, TestStf, _, TrustedCallSigned, Getter>::new(
+		state_observer,
+		Arc::new(OnchainMock::default()),
+		Arc::new(DumpedEnclaveKeyRepository::default()),
+		Arc::new(AuthorApiMock::default()),
+	);
+
+	assert_eq!(DUMPED_SHARD_VAULT, enclave_signer.get_shard_vault(&DUMPED_SHARD).unwrap());
+	// Shards that have not been recorded are not silently served with an empty state.
+	assert!(enclave_signer.get_shard_vault(&ShardIdentifier::default()).is_err());
+}
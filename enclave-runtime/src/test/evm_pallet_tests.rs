@@ -78,7 +78,8 @@ pub fn test_evm_call() {
 
 	// when
 	let repo = Arc::new(NodeMetadataRepository::<NodeMetadataMock>::default());
-	TestStf::execute_call(&mut state, trusted_call, &mut opaque_vec, repo).unwrap();
+	TestStf::execute_call(&mut state, trusted_call, &mrenclave, &shard, &mut opaque_vec, repo)
+		.unwrap();
 
 	// then
 	assert_eq!(
@@ -123,7 +124,8 @@ pub fn test_evm_counter() {
 	// when
 	let execution_address = evm_create_address(sender_evm_acc, 0);
 	let repo = Arc::new(NodeMetadataRepository::<NodeMetadataMock>::default());
-	TestStf::execute_call(&mut state, trusted_call, &mut opaque_vec, repo).unwrap();
+	TestStf::execute_call(&mut state, trusted_call, &mrenclave, &shard, &mut opaque_vec, repo)
+		.unwrap();
 
 	// then
 	assert_eq!(
@@ -248,7 +250,7 @@ fn execute_and_verify_evm_call(
 	)
 	.sign(&pair, nonce, &mrenclave, &shard);
 	let repo = Arc::new(NodeMetadataRepository::<NodeMetadataMock>::default());
-	TestStf::execute_call(state, inc_call, calls, repo).unwrap();
+	TestStf::execute_call(state, inc_call, mrenclave, shard, calls, repo).unwrap();
 
 	let counter_value = state
 		.execute_with(|| get_evm_account_storages(&execution_address, &H256::zero()))
@@ -294,7 +296,8 @@ pub fn test_evm_create() {
 	assert_eq!(nonce, 0);
 	let execution_address = evm_create_address(sender_evm_acc, nonce);
 	let repo = Arc::new(NodeMetadataRepository::<NodeMetadataMock>::default());
-	TestStf::execute_call(&mut state, trusted_call, &mut opaque_vec, repo).unwrap();
+	TestStf::execute_call(&mut state, trusted_call, &mrenclave, &shard, &mut opaque_vec, repo)
+		.unwrap();
 
 	assert_eq!(
 		execution_address,
@@ -349,7 +352,8 @@ pub fn test_evm_create2() {
 	let code_hash = create_code_hash(&smart_contract);
 	let execution_address = evm_create2_address(sender_evm_acc, salt, code_hash);
 	let repo = Arc::new(NodeMetadataRepository::<NodeMetadataMock>::default());
-	TestStf::execute_call(&mut state, trusted_call, &mut opaque_vec, repo).unwrap();
+	TestStf::execute_call(&mut state, trusted_call, &mrenclave, &shard, &mut opaque_vec, repo)
+		.unwrap();
 
 	// then
 	assert_eq!(
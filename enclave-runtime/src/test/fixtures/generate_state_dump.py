@@ -0,0 +1,151 @@
+#!/usr/bin/env python3
+"""
+Writes state_dump.bin, a SCALE encoded StateDump in the format of the worker's
+`dump-state` command, for the enclave signer tests.
+
+The dump holds the state of a shard after the enclave account has signed five
+calls and the shard vault was assigned. The enclave account is the ed25519 key
+of TEST_ENCLAVE_SEED, which the tests use in place of the sealed shielding key.
+"""
+
+import hashlib
+import os
+import struct
+
+from cryptography.hazmat.primitives.asymmetric.ed25519 import Ed25519PrivateKey
+from cryptography.hazmat.primitives.serialization import Encoding, PublicFormat
+
+TEST_ENCLAVE_SEED = bytes([7] * 32)
+SHARD = bytes([1] * 32)
+ENCLAVE_ACCOUNT_NONCE = 5
+SHARD_VAULT = bytes([9] * 32)
+ALICE = bytes.fromhex("d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d")
+BOB = bytes.fromhex("8eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48")
+
+MASK64 = (1 << 64) - 1
+P1, P2, P3, P4, P5 = (
+    11400714785074694791,
+    14029467366897019727,
+    1609587929392839161,
+    9650029242287828579,
+    2870177450012600261,
+)
+
+
+def _rotl(x, r):
+    return ((x << r) | (x >> (64 - r))) & MASK64
+
+
+def _round(acc, lane):
+    acc = (acc + lane * P2) & MASK64
+    return (_rotl(acc, 31) * P1) & MASK64
+
+
+def _merge(acc, val):
+    acc ^= _round(0, val)
+    return (acc * P1 + P4) & MASK64
+
+
+def xxh64(data, seed):
+    n = len(data)
+    i = 0
+    if n >= 32:
+        v1 = (seed + P1 + P2) & MASK64
+        v2 = (seed + P2) & MASK64
+        v3 = seed
+        v4 = (seed - P1) & MASK64
+        while i + 32 <= n:
+            v1 = _round(v1, struct.unpack_from("<Q", data, i)[0])
+            v2 = _round(v2, struct.unpack_from("<Q", data, i + 8)[0])
+            v3 = _round(v3, struct.unpack_from("<Q", data, i + 16)[0])
+            v4 = _round(v4, struct.unpack_from("<Q", data, i + 24)[0])
+            i += 32
+        h = (_rotl(v1, 1) + _rotl(v2, 7) + _rotl(v3, 12) + _rotl(v4, 18)) & MASK64
+        for v in (v1, v2, v3, v4):
+            h = _merge(h, v)
+    else:
+        h = (seed + P5) & MASK64
+    h = (h + n) & MASK64
+    while i + 8 <= n:
+        h ^= _round(0, struct.unpack_from("<Q", data, i)[0])
+        h = (_rotl(h, 27) * P1 + P4) & MASK64
+        i += 8
+    if i + 4 <= n:
+        h ^= (struct.unpack_from("<I", data, i)[0] * P1) & MASK64
+        h = (_rotl(h, 23) * P2 + P3) & MASK64
+        i += 4
+    while i < n:
+        h ^= (data[i] * P5) & MASK64
+        h = (_rotl(h, 11) * P1) & MASK64
+        i += 1
+    h ^= h >> 33
+    h = (h * P2) & MASK64
+    h ^= h >> 29
+    h = (h * P3) & MASK64
+    h ^= h >> 32
+    return h
+
+
+def twox128(data):
+    return struct.pack("<QQ", xxh64(data, 0), xxh64(data, 1))
+
+
+def blake2_128_concat(data):
+    return hashlib.blake2b(data, digest_size=16).digest() + data
+
+
+def storage_value_key(module, name):
+    return twox128(module.encode()) + twox128(name.encode())
+
+
+def compact(n):
+    if n < 1 << 6:
+        return bytes([n << 2])
+    if n < 1 << 14:
+        return struct.pack("<H", (n << 2) | 1)
+    if n < 1 << 30:
+        return struct.pack("<I", (n << 2) | 2)
+    raise ValueError("length too big for the fixture")
+
+
+def encode_bytes(data):
+    return compact(len(data)) + data
+
+
+def account_info(nonce, free, providers=1):
+    # AccountInfo { nonce, consumers, providers, sufficients, data: AccountData { free, reserved, frozen, flags } }
+    return struct.pack("<IIII", nonce, 0, providers, 0) + (free.to_bytes(16, "little")) + bytes(16 * 2) + (
+        1 << 127
+    ).to_bytes(16, "little")
+
+
+def main():
+    enclave_account = (
+        Ed25519PrivateKey.from_private_bytes(TEST_ENCLAVE_SEED).public_key().public_bytes(Encoding.Raw, PublicFormat.Raw)
+    )
+    system_account = storage_value_key("System", "Account")
+
+    entries = {
+        storage_value_key("Sudo", "Enclave_Account_Key"): enclave_account,
+        system_account + blake2_128_concat(enclave_account): account_info(ENCLAVE_ACCOUNT_NONCE, 0),
+        system_account + blake2_128_concat(ALICE): account_info(3, 1_000_000_000_000),
+        system_account + blake2_128_concat(BOB): account_info(0, 250_000_000_000),
+        storage_value_key("Balances", "TotalIssuance"): (1_250_000_000_000).to_bytes(16, "little"),
+        storage_value_key("System", "Number"): struct.pack("<I", 1_204),
+        storage_value_key("Timestamp", "Now"): struct.pack("<Q", 1_697_000_000_000),
+        b"ShardVaultPubKey": SHARD_VAULT,
+    }
+
+    encoded = SHARD + compact(len(entries))
+    for key in sorted(entries):
+        encoded += encode_bytes(key) + encode_bytes(entries[key])
+
+    path = os.path.join(os.path.dirname(os.path.abspath(__file__)), "state_dump.bin")
+    with open(path, "wb") as f:
+        f.write(encoded)
+
+
+if __name__ == "__main__":
+    assert twox128(b"System").hex() == "26aa394eea5630e07c48ae0c9558cef7"
+    assert twox128(b"Account").hex() == "b99d880ec681799c0cf30e8886371da9"
+    main()
@@ -16,14 +16,18 @@
 
 */
 
-use codec::Decode;
+use codec::{Decode, Encode};
 use ita_sgx_runtime::Runtime;
 use ita_stf::{
 	test_genesis::{endowed_account, test_genesis_setup, ENDOWED_ACC_FUNDS},
-	Balance, Getter, Stf, TrustedCallSigned, TrustedGetter,
+	Balance, Getter, Stf, TrustedCallSigned, TrustedGetter, DEFAULT_GETTER_VALIDITY,
 };
-use itp_sgx_externalities::SgxExternalities;
-use itp_stf_executor::state_getter::{GetState, StfStateGetter};
+use itp_sgx_externalities::{SgxExternalities, SgxExternalitiesTrait};
+use itp_stf_executor::{
+	error::Error,
+	state_getter::{GetState, StfStateGetter},
+};
+use itp_storage::storage_value_key;
 use sp_core::Pair;
 
 type TestState = SgxExternalities;
@@ -32,7 +36,8 @@ type TestStfStateGetter = StfStateGetter<TestStf>;
 
 pub fn state_getter_works() {
 	let sender = endowed_account();
-	let signed_getter = TrustedGetter::free_balance(sender.public().into()).sign(&sender.into());
+	let signed_getter = TrustedGetter::free_balance(sender.public().into())
+		.sign(&sender.into(), DEFAULT_GETTER_VALIDITY);
 	let mut state = test_state();
 
 	let encoded_balance = TestStfStateGetter::get_state(signed_getter.into(), &mut state)
@@ -44,6 +49,23 @@ pub fn state_getter_works() {
 	assert_eq!(balance, ENDOWED_ACC_FUNDS);
 }
 
+pub fn state_getter_rejects_expired_getter() {
+	let sender = endowed_account();
+	let signed_getter = TrustedGetter::free_balance(sender.public().into())
+		.sign(&sender.into(), DEFAULT_GETTER_VALIDITY);
+	let mut state = test_state();
+	state.execute_with(|| {
+		sp_io::storage::set(
+			&storage_value_key("System", "Timestamp"),
+			&(DEFAULT_GETTER_VALIDITY + 1).encode(),
+		)
+	});
+
+	let result = TestStfStateGetter::get_state(signed_getter.into(), &mut state);
+
+	assert!(matches!(result, Err(Error::GetterHasExpired)));
+}
+
 fn test_state() -> TestState {
 	let mut state = TestState::default();
 	test_genesis_setup(&mut state);
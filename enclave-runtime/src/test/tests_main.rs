@@ -18,7 +18,7 @@
 use crate::test::evm_pallet_tests;
 
 use crate::{
-	rpc,
+	enclave_upgrade, rpc,
 	sync::tests::{enclave_rw_lock_works, sidechain_rw_lock_works},
 	test::{
 		cert_tests::*,
@@ -38,6 +38,7 @@ use ita_stf::{
 	stf_sgx_tests,
 	test_genesis::{endowed_account as funded_pair, unendowed_account},
 	AccountInfo, Getter, State, TrustedCall, TrustedCallSigned, TrustedGetter,
+	DEFAULT_GETTER_VALIDITY,
 };
 use itp_node_api::metadata::{metadata_mocks::NodeMetadataMock, provider::NodeMetadataRepository};
 use itp_sgx_crypto::{Aes, StateCrypto};
@@ -81,6 +82,20 @@ pub extern "C" fn test_main_entrance() -> size_t {
 		stf_sgx_tests::enclave_account_initialization_works,
 		stf_sgx_tests::shield_funds_increments_signer_account_nonce,
 		stf_sgx_tests::test_root_account_exists_after_initialization,
+		stf_sgx_tests::shard_genesis_endows_accounts_and_sets_access_control,
+		stf_sgx_tests::shard_genesis_for_other_stf_version_is_rejected,
+		stf_sgx_tests::session_call_transfers_on_behalf_of_owner,
+		stf_sgx_tests::session_call_outside_of_scope_is_rejected,
+		stf_sgx_tests::session_call_of_delegate_without_shard_access_is_rejected,
+		stf_sgx_tests::sponsored_call_increments_nonces_of_sponsor_and_user,
+		stf_sgx_tests::sponsored_call_with_outdated_user_nonce_is_rejected,
+		stf_sgx_tests::sponsored_call_with_forged_user_signature_is_rejected,
+		stf_sgx_tests::sponsored_call_can_not_be_scheduled,
+		stf_sgx_tests::sponsored_call_can_not_be_submitted_by_a_session_key,
+		stf_sgx_tests::call_fee_is_charged_from_the_sender,
+		stf_sgx_tests::scheduled_call_is_executed_on_behalf_of_its_sender,
+		stf_sgx_tests::scheduled_call_of_sender_without_shard_access_is_dropped,
+		stf_sgx_tests::scheduled_call_fails_if_sender_cannot_pay_the_fee,
 		itp_stf_state_handler::test::sgx_tests::test_write_and_load_state_works,
 		itp_stf_state_handler::test::sgx_tests::test_sgx_state_decode_encode_works,
 		itp_stf_state_handler::test::sgx_tests::test_encrypt_decrypt_state_type_works,
@@ -132,11 +147,17 @@ pub extern "C" fn test_main_entrance() -> size_t {
 		stf_executor_tests::propose_state_update_always_executes_preprocessing_step,
 		stf_executor_tests::propose_state_update_executes_no_trusted_calls_given_no_time,
 		stf_executor_tests::propose_state_update_executes_only_one_trusted_call_given_not_enough_time,
+		stf_executor_tests::propose_state_update_postpones_calls_exceeding_remaining_time,
+		stf_executor_tests::propose_state_update_executes_due_scheduled_calls_after_trusted_calls,
+		stf_executor_tests::propose_state_update_keeps_scheduled_calls_given_no_time,
 		stf_executor_tests::propose_state_update_executes_all_calls_given_enough_time,
 		enclave_signer_tests::enclave_signer_signatures_are_valid,
 		enclave_signer_tests::derive_key_is_deterministic,
 		enclave_signer_tests::nonce_is_computed_correctly,
+		enclave_signer_tests::nonce_is_read_from_recorded_state,
+		enclave_signer_tests::shard_vault_is_read_from_recorded_state,
 		state_getter_tests::state_getter_works,
+		state_getter_tests::state_getter_rejects_expired_getter,
 		// sidechain integration tests
 		sidechain_aura_tests::produce_sidechain_block_and_import_it,
 		sidechain_event_tests::ensure_events_get_reset_upon_block_proposal,
@@ -154,6 +175,9 @@ pub extern "C" fn test_main_entrance() -> size_t {
 		tls_ra::seal_handler::test::unseal_seal_state_works,
 		tls_ra::tests::test_tls_ra_server_client_networking,
 		tls_ra::tests::test_state_and_key_provisioning,
+		enclave_upgrade::tests::key_handover_between_enclaves_of_the_same_signer_works,
+		enclave_upgrade::tests::key_handover_request_for_another_shard_is_rejected,
+		enclave_upgrade::tests::key_handover_response_with_swapped_keys_is_rejected,
 		// RPC tests
 		direct_rpc_tests::get_state_request_works,
 
@@ -263,7 +287,8 @@ fn test_submit_trusted_getter_to_top_pool() {
 
 	let sender = funded_pair();
 
-	let signed_getter = TrustedGetter::free_balance(sender.public().into()).sign(&sender.into());
+	let signed_getter = TrustedGetter::free_balance(sender.public().into())
+		.sign(&sender.into(), DEFAULT_GETTER_VALIDITY);
 
 	// when
 	submit_operation_to_top_pool(
@@ -290,8 +315,8 @@ fn test_differentiate_getter_and_call_works() {
 	// create accounts
 	let sender = funded_pair();
 
-	let signed_getter =
-		TrustedGetter::free_balance(sender.public().into()).sign(&sender.clone().into());
+	let signed_getter = TrustedGetter::free_balance(sender.public().into())
+		.sign(&sender.clone().into(), DEFAULT_GETTER_VALIDITY);
 
 	let signed_call =
 		TrustedCall::balance_set_balance(sender.public().into(), sender.public().into(), 42, 42)
@@ -613,7 +638,8 @@ pub fn test_retrieve_events() {
 	)
 	.sign(&sender.clone().into(), 0, &mrenclave, &shard);
 	let repo = Arc::new(NodeMetadataRepository::<NodeMetadataMock>::default());
-	TestStf::execute_call(&mut state, trusted_call, &mut opaque_vec, repo).unwrap();
+	TestStf::execute_call(&mut state, trusted_call, &mrenclave, &shard, &mut opaque_vec, repo)
+		.unwrap();
 
 	assert_eq!(TestStf::get_events(&mut state).len(), 3);
 }
@@ -637,7 +663,8 @@ pub fn test_retrieve_event_count() {
 
 	// when
 	let repo = Arc::new(NodeMetadataRepository::<NodeMetadataMock>::default());
-	TestStf::execute_call(&mut state, trusted_call, &mut opaque_vec, repo).unwrap();
+	TestStf::execute_call(&mut state, trusted_call, &mrenclave, &shard, &mut opaque_vec, repo)
+		.unwrap();
 
 	let event_count = TestStf::get_event_count(&mut state);
 	assert_eq!(event_count, 3);
@@ -659,7 +686,8 @@ pub fn test_reset_events() {
 	)
 	.sign(&sender.clone().into(), 0, &mrenclave, &shard);
 	let repo = Arc::new(NodeMetadataRepository::<NodeMetadataMock>::default());
-	TestStf::execute_call(&mut state, trusted_call, &mut opaque_vec, repo).unwrap();
+	TestStf::execute_call(&mut state, trusted_call, &mrenclave, &shard, &mut opaque_vec, repo)
+		.unwrap();
 	let receiver_acc_info = TestStf::get_account_data(&mut state, &receiver.public().into());
 	assert_eq!(receiver_acc_info.free, transfer_value);
 	// Ensure that there really have been events generated.
@@ -16,12 +16,16 @@
 */
 
 use crate::{
-	error::Result,
+	error::{Error, Result},
+	heartbeat,
 	initialization::global_components::{
-		GLOBAL_OCALL_API_COMPONENT, GLOBAL_SIDECHAIN_BLOCK_COMPOSER_COMPONENT,
+		EnclaveSecureTimeService, GLOBAL_AUDIT_LOG_COMPONENT, GLOBAL_CROSS_SHARD_ROUTER_COMPONENT,
+		GLOBAL_OCALL_API_COMPONENT, GLOBAL_PERIODIC_TASK_SCHEDULER_COMPONENT,
+		GLOBAL_SECURE_TIME_SERVICE_COMPONENT, GLOBAL_SIDECHAIN_BLOCK_COMPOSER_COMPONENT,
 		GLOBAL_SIDECHAIN_IMPORT_QUEUE_WORKER_COMPONENT, GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT,
 		GLOBAL_STATE_HANDLER_COMPONENT, GLOBAL_TOP_POOL_AUTHOR_COMPONENT,
 	},
+	shard_vault::send_unshield_payout_batch,
 	sync::{EnclaveLock, EnclaveStateRWLock},
 	utils::{
 		get_extrinsic_factory_from_integritee_solo_or_parachain,
@@ -37,14 +41,21 @@ use itc_parentchain::{
 		NumberFor,
 	},
 };
+use itc_secure_time::TrustedTime;
 use itp_component_container::ComponentGetter;
+use itp_enclave_metrics::SLOT_WORKER_THREAD;
 use itp_extrinsics_factory::CreateExtrinsics;
-use itp_ocall_api::{EnclaveOnChainOCallApi, EnclaveSidechainOCallApi};
+use itp_ocall_api::{EnclaveAttestationOCallApi, EnclaveOnChainOCallApi, EnclaveSidechainOCallApi};
 use itp_settings::sidechain::SLOT_DURATION;
 use itp_sgx_crypto::key_repository::AccessKey;
+use itp_stf_executor::traits::ExecutePeriodicTasks;
 use itp_stf_state_handler::query_shard_state::QueryShardState;
+use itp_storage::storage_value_key;
 use itp_time_utils::duration_now;
-use itp_types::{Block, OpaqueCall, H256};
+use itp_types::{
+	parentchain::{Header as ParentchainHeader, ParentchainId},
+	Block, OpaqueCall, H256,
+};
 use its_primitives::{
 	traits::{
 		Block as SidechainBlockTrait, Header as HeaderTrait, ShardIdentifierFor, SignedBlock,
@@ -67,6 +78,8 @@ use std::{sync::Arc, time::Instant, vec::Vec};
 
 #[no_mangle]
 pub unsafe extern "C" fn execute_trusted_calls() -> sgx_status_t {
+	heartbeat::send_heartbeat(SLOT_WORKER_THREAD);
+
 	if let Err(e) = execute_top_pool_trusted_calls_internal() {
 		return e.into()
 	}
@@ -74,6 +87,26 @@ pub unsafe extern "C" fn execute_trusted_calls() -> sgx_status_t {
 	sgx_status_t::SGX_SUCCESS
 }
 
+/// Cross-checks the host clock against the reference time source, if one is configured and due.
+///
+/// Called from a dedicated thread of the worker, the slot only reads the outcome of the last
+/// check, as the HTTPS request would use up the slot otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn refresh_reference_time() -> sgx_status_t {
+	if let Err(e) = refresh_reference_time_internal() {
+		warn!("Reference time check failed: {:?}", e);
+		return e.into()
+	}
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+fn refresh_reference_time_internal() -> Result<()> {
+	// No enclave lock, the secure time service synchronizes itself and must not block the slot.
+	GLOBAL_SECURE_TIME_SERVICE_COMPONENT.get()?.refresh_reference_time()?;
+	Ok(())
+}
+
 /// Internal [`execute_trusted_calls`] function to be able to use the `?` operator.
 ///
 /// Executes `Aura::on_slot() for `slot` if it is this enclave's `Slot`.
@@ -131,6 +164,24 @@ fn execute_top_pool_trusted_calls_internal() -> Result<()> {
 
 	let authority = GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT.get()?.retrieve_key()?;
 
+	let periodic_task_scheduler = GLOBAL_PERIODIC_TASK_SCHEDULER_COMPONENT.get()?;
+
+	let secure_time = GLOBAL_SECURE_TIME_SERVICE_COMPONENT.get()?;
+
+	if let Err(e) =
+		cross_check_host_clock(secure_time.as_ref(), ocall_api.as_ref(), &latest_parentchain_header)
+	{
+		error!("Host clock can't be trusted, halting block production: {:?}", e);
+		return Ok(())
+	}
+
+	// The watchdog logs when it freezes or resumes, no need to repeat it every slot.
+	let parentchain_block_number = latest_parentchain_header.number as u64;
+	if let Err(e) = itp_sync_watchdog::observe(parentchain_block_number, secure_time.now()?) {
+		debug!("Halting block production: {}", e);
+		return Ok(())
+	}
+
 	match yield_next_slot(
 		slot_beginning_timestamp,
 		SLOT_DURATION,
@@ -146,11 +197,34 @@ fn execute_top_pool_trusted_calls_internal() -> Result<()> {
 			log_remaining_slot_duration(&slot, "Before AURA");
 
 			let shards = state_handler.list_shards()?;
+
+			// Submit the calls of the due periodic tasks, so they are included in this slot already.
+			if let Err(e) = periodic_task_scheduler.on_slot(*slot.slot, &shards) {
+				error!("Failed to execute periodic tasks: {:?}", e);
+			}
+
 			let env = ProposerFactory::<Block, _, _, _>::new(
 				top_pool_author,
 				stf_executor,
 				block_composer,
+			)
+			.with_audit_log(GLOBAL_AUDIT_LOG_COMPONENT.get()?)
+			.with_trusted_time(secure_time)
+			.with_cross_shard_router(GLOBAL_CROSS_SHARD_ROUTER_COMPONENT.get()?);
+			#[cfg(feature = "replay-recording")]
+			let env = env.with_replay_recorder(
+				crate::initialization::global_components::GLOBAL_BLOCK_REPLAY_STORE_COMPONENT
+					.get()?,
 			);
+			#[cfg(feature = "pessimistic-execution")]
+			let env = env.with_state_confirmer(Arc::new(
+				its_sidechain::aura::state_confirmation::QuorumStateConfirmer::new(
+					ocall_api.clone(),
+					crate::initialization::global_components::GLOBAL_STATE_KEY_REPOSITORY_COMPONENT
+						.get()?,
+					itp_settings::sidechain::STATE_CONFIRMATION_QUORUM_PERCENT,
+				),
+			));
 
 			let (blocks, opaque_calls) = exec_aura_on_slot::<_, _, SignedSidechainBlock, _, _, _>(
 				slot.clone(),
@@ -163,11 +237,18 @@ fn execute_top_pool_trusted_calls_internal() -> Result<()> {
 
 			debug!("Aura executed successfully");
 
+			if let Err(e) = crate::memory_accounting::report_memory_usage(ocall_api.as_ref()) {
+				warn!("Failed to report enclave memory usage: {:?}", e);
+			}
+
 			// Drop lock as soon as we don't need it anymore.
 			drop(_enclave_write_lock);
 
 			log_remaining_slot_duration(&slot, "After AURA");
 
+			let produced_shards: Vec<_> =
+				blocks.iter().map(|block| block.block().header().shard_id()).collect();
+
 			send_blocks_and_extrinsics::<Block, _, _, _, _>(
 				blocks,
 				opaque_calls,
@@ -176,7 +257,17 @@ fn execute_top_pool_trusted_calls_internal() -> Result<()> {
 				extrinsics_factory.as_ref(),
 			)?;
 
+			for shard in produced_shards.iter() {
+				if let Err(e) = send_unshield_payout_batch(shard) {
+					error!("Failed to send unshield payout batch of shard {:?}: {:?}", shard, e);
+				}
+			}
+
 			log_remaining_slot_duration(&slot, "After broadcasting and sending extrinsic");
+
+			// Written only now, as the block production must not wait for the disk.
+			#[cfg(feature = "replay-recording")]
+			flush_replay_records();
 		},
 		None => {
 			debug!("No slot yielded. Skipping block production.");
@@ -188,6 +279,37 @@ fn execute_top_pool_trusted_calls_internal() -> Result<()> {
 	Ok(())
 }
 
+/// Cross-checks the host clock against the timestamp of the latest finalized parentchain block
+/// and the last reference time check. Fails if the host clock drifted and no blocks must be
+/// produced.
+fn cross_check_host_clock<OCallApi: EnclaveOnChainOCallApi>(
+	secure_time: &EnclaveSecureTimeService,
+	ocall_api: &OCallApi,
+	parentchain_header: &ParentchainHeader,
+) -> Result<()> {
+	let block_number = parentchain_header.number as u64;
+	if !secure_time.is_anchored_at(block_number) {
+		let timestamp: u64 = ocall_api
+			.get_storage_verified(
+				storage_value_key("Timestamp", "Now"),
+				parentchain_header,
+				&ParentchainId::Integritee,
+			)?
+			.into_tuple()
+			.1
+			.ok_or_else(|| Error::Other("No timestamp in the parentchain state".into()))?;
+
+		// A detected drift is recorded by the service and reported below.
+		if let Err(e) = secure_time.observe_parentchain_timestamp(block_number, timestamp) {
+			warn!("Parentchain block {}: {:?}", block_number, e);
+		}
+	}
+
+	// The reference time is refreshed by `refresh_reference_time`, a recorded drift is reported here.
+	secure_time.ensure_within_drift()?;
+	Ok(())
+}
+
 /// Executes aura for the given `slot`.
 pub(crate) fn exec_aura_on_slot<
 	Authority,
@@ -214,7 +336,8 @@ where
 	SignedSidechainBlock::Signature: From<Authority::Signature>,
 	Authority: Pair<Public = sp_core::ed25519::Public>,
 	Authority::Public: Encode + UncheckedFrom<[u8; 32]>,
-	OCallApi: ValidateerFetch + EnclaveOnChainOCallApi + Send + 'static,
+	OCallApi:
+		ValidateerFetch + EnclaveOnChainOCallApi + EnclaveAttestationOCallApi + Send + 'static,
 	NumberFor<ParentchainBlock>: BlockNumberOps,
 	PEnvironment:
 		Environment<ParentchainBlock, SignedSidechainBlock, Error = ConsensusError> + Send + Sync,
@@ -223,13 +346,18 @@ where
 {
 	debug!("[Aura] Executing aura for slot: {:?}", slot);
 
+	// Authoring across enclave upgrades and of shards in maintenance is decided in here, based
+	// on the shard config verified by the light client.
+	let enclave_fingerprint = ocall_api.get_mrenclave_of_self()?.m.into();
+
 	let mut aura = Aura::<_, ParentchainBlock, SignedSidechainBlock, PEnvironment, _, _>::new(
 		authority,
 		ocall_api.as_ref().clone(),
 		block_import_trigger,
 		proposer_environment,
 	)
-	.with_claim_strategy(SlotClaimStrategy::RoundRobin);
+	.with_claim_strategy(SlotClaimStrategy::RoundRobin)
+	.with_enclave_fingerprint(enclave_fingerprint);
 
 	let (blocks, xts): (Vec<_>, Vec<_>) =
 		PerShardSlotWorkerScheduler::on_slot(&mut aura, slot, shards)
@@ -293,3 +421,16 @@ fn log_remaining_slot_duration<B: BlockTrait<Hash = H256>>(
 		},
 	};
 }
+
+#[cfg(feature = "replay-recording")]
+fn flush_replay_records() {
+	use itp_stf_executor::replay::RecordBlockReplay;
+
+	let result = crate::initialization::global_components::GLOBAL_BLOCK_REPLAY_STORE_COMPONENT
+		.get()
+		.map_err(Error::from)
+		.and_then(|store| store.flush().map_err(Error::from));
+	if let Err(e) = result {
+		warn!("Failed to write the replay records: {:?}", e);
+	}
+}
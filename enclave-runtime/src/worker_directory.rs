@@ -0,0 +1,115 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Publication of the enclave-signed record of this worker for the worker directory.
+
+use crate::{
+	error::{Error, Result},
+	initialization::global_components::{
+		GLOBAL_OCALL_API_COMPONENT, GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT,
+		GLOBAL_STATE_HANDLER_COMPONENT,
+	},
+	utils::{
+		get_extrinsic_factory_from_integritee_solo_or_parachain,
+		get_node_metadata_repository_from_integritee_solo_or_parachain,
+	},
+};
+use codec::Encode;
+use ita_stf::migrations::STF_VERSION;
+use itp_component_container::ComponentGetter;
+use itp_extrinsics_factory::CreateExtrinsics;
+use itp_node_api::metadata::{
+	pallet_enclave_bridge::EnclaveBridgeCallIndexes, provider::AccessNodeMetadata,
+};
+use itp_ocall_api::{EnclaveAttestationOCallApi, EnclaveOnChainOCallApi};
+use itp_sgx_crypto::key_repository::AccessKey;
+use itp_stf_state_handler::query_shard_state::QueryShardState;
+use itp_time_utils::now_as_millis;
+use itp_types::{
+	parentchain::ParentchainId,
+	worker_directory::{SignedWorkerRecord, WorkerRecord},
+	OpaqueCall,
+};
+use itp_utils::write_slice_and_whitespace_pad;
+use log::*;
+use sgx_types::sgx_status_t;
+use std::{slice, vec::Vec};
+
+/// Signs and publishes the directory record of this worker, reachable under the given external
+/// URLs, and writes the SCALE encoded [`SignedWorkerRecord`] into `record`.
+#[no_mangle]
+pub unsafe extern "C" fn publish_worker_record(
+	trusted_url: *const u8,
+	trusted_url_size: u32,
+	untrusted_url: *const u8,
+	untrusted_url_size: u32,
+	record: *mut u8,
+	record_size: u32,
+) -> sgx_status_t {
+	let trusted_url = slice::from_raw_parts(trusted_url, trusted_url_size as usize).to_vec();
+	let untrusted_url = slice::from_raw_parts(untrusted_url, untrusted_url_size as usize).to_vec();
+
+	let signed_record = match publish_worker_record_internal(trusted_url, untrusted_url) {
+		Ok(r) => r,
+		Err(e) => {
+			error!("Failed to publish worker record: {:?}", e);
+			return e.into()
+		},
+	};
+
+	let record_slice = slice::from_raw_parts_mut(record, record_size as usize);
+	if let Err(e) = write_slice_and_whitespace_pad(record_slice, signed_record.encode()) {
+		return Error::BufferError(e).into()
+	};
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+fn publish_worker_record_internal(
+	trusted_rpc_url: Vec<u8>,
+	untrusted_rpc_url: Vec<u8>,
+) -> Result<SignedWorkerRecord> {
+	let ocall_api = GLOBAL_OCALL_API_COMPONENT.get()?;
+	let record = WorkerRecord {
+		mrenclave: ocall_api.get_mrenclave_of_self()?.m,
+		trusted_rpc_url,
+		untrusted_rpc_url,
+		shards: GLOBAL_STATE_HANDLER_COMPONENT.get()?.list_shards()?,
+		stf_version: STF_VERSION,
+		published_at: now_as_millis(),
+	};
+	let enclave_signer = GLOBAL_SIGNING_KEY_REPOSITORY_COMPONENT.get()?.retrieve_key()?;
+	let signed_record = SignedWorkerRecord::new(record, &enclave_signer);
+
+	let node_metadata_repo = get_node_metadata_repository_from_integritee_solo_or_parachain()?;
+	let extrinsics_factory = get_extrinsic_factory_from_integritee_solo_or_parachain()?;
+	let publish_hash_call = OpaqueCall::from_tuple(&(
+		node_metadata_repo.get_from_metadata(|m| m.publish_hash_call_indexes())??,
+		signed_record.hash(),
+		signed_record.record.shards.clone(),
+		signed_record.encode(),
+	));
+	let xts = extrinsics_factory.create_extrinsics(&[publish_hash_call], None)?;
+	ocall_api.send_to_parentchain(xts, &ParentchainId::Integritee, false)?;
+
+	info!(
+		"Published worker record for {} shard(s), reachable at {:?}",
+		signed_record.record.shards.len(),
+		signed_record.record.trusted_rpc_url_str()
+	);
+	Ok(signed_record)
+}
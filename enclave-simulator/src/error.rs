@@ -0,0 +1,57 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use std::boxed::Box;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// Enclave simulator error.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	#[error("Light client error: {0}")]
+	LightClient(#[from] itc_parentchain_light_client::error::Error),
+	#[error("Parentchain import error: {0}")]
+	ParentchainImport(#[from] itc_parentchain_block_import_dispatcher::error::Error),
+	#[error("Import queue error: {0}")]
+	ImportQueue(#[from] itp_import_queue::error::Error),
+	#[error("STF executor error: {0}")]
+	StfExecutor(#[from] itp_stf_executor::error::Error),
+	#[error("State handler error: {0}")]
+	StateHandler(#[from] itp_stf_state_handler::error::Error),
+	#[error("State observer error: {0}")]
+	StateObserver(#[from] itp_stf_state_observer::error::Error),
+	#[error("Sidechain consensus error: {0}")]
+	Consensus(#[from] its_sidechain::consensus_common::Error),
+	#[error("Trusted operation pool error: {0}")]
+	TopPool(#[from] jsonrpc_core::Error),
+	#[error("Crypto error: {0:?}")]
+	Crypto(itp_sgx_crypto::Error),
+	#[error("Codec error: {0}")]
+	Codec(#[from] codec::Error),
+	#[error("Lock poisoning")]
+	LockPoisoning,
+	#[error("No sidechain block has been produced for shard {0:?}")]
+	NoBlockProduced(itp_types::ShardIdentifier),
+	#[error(transparent)]
+	Other(#[from] Box<dyn std::error::Error + Sync + Send + 'static>),
+}
+
+impl From<itp_sgx_crypto::Error> for Error {
+	fn from(e: itp_sgx_crypto::Error) -> Self {
+		Self::Crypto(e)
+	}
+}
@@ -0,0 +1,37 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Enclave simulator.
+//!
+//! Runs the worker pipeline — light client, trusted operation pool, STF executor, sidechain
+//! block production and import, and the direct RPC interface — purely in std, without an SGX
+//! toolchain or hardware. Meant for integration tests in CI, e.g. for end-to-end shielding,
+//! getter flows and block production.
+//!
+//! The parentchain is mocked: blocks are produced in-process, and the simulated enclave is the
+//! only validateer of its shard. Remote attestation is not supported.
+
+pub mod error;
+pub mod ocall_api;
+pub mod parentchain;
+pub mod rpc;
+pub mod rpc_responder;
+pub mod simulator;
+pub mod types;
+
+pub use error::{Error, Result};
+pub use simulator::{EnclaveSimulator, SimulatorConfig};
@@ -0,0 +1,250 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! OCall API of the simulated enclave.
+//!
+//! Stands in for both the untrusted worker and the parentchain node: storage queries are answered
+//! from an in-memory map that is independent of the parentchain header, and extrinsics sent to
+//! the parentchain are recorded, such that tests can assert on them.
+
+use codec::{Decode, Encode};
+use itp_enclave_bridge_storage::{EnclaveBridgeStorage, EnclaveBridgeStorageKeys};
+use itp_ocall_api::{
+	EnclaveAttestationOCallApi, EnclaveMetricsOCallApi, EnclaveOnChainOCallApi,
+	EnclaveSidechainOCallApi,
+};
+use itp_types::{
+	parentchain::ParentchainId, storage::StorageEntryVerified, AccountId, BlockHash,
+	EnclaveFingerprint, ShardIdentifier, ShardSignerStatus, WorkerRequest, WorkerResponse,
+};
+use sgx_types::*;
+use sp_core::H256;
+use sp_runtime::{traits::Header as HeaderTrait, OpaqueExtrinsic};
+use std::{
+	collections::HashMap,
+	sync::{Arc, RwLock},
+};
+
+#[derive(Clone, Default)]
+pub struct SimulatedOCallApi {
+	storage: Arc<RwLock<HashMap<Vec<u8>, Vec<u8>>>>,
+	sent_extrinsics: Arc<RwLock<Vec<OpaqueExtrinsic>>>,
+	mr_enclave: [u8; SGX_HASH_SIZE],
+}
+
+impl SimulatedOCallApi {
+	pub fn new(mr_enclave: [u8; SGX_HASH_SIZE]) -> Self {
+		Self { mr_enclave, ..Default::default() }
+	}
+
+	/// Registers the validateer set of a shard, valid for all parentchain headers.
+	pub fn set_validateers(&self, shard: ShardIdentifier, validateers: Vec<AccountId>) {
+		let status: Vec<ShardSignerStatus> = validateers
+			.into_iter()
+			.map(|signer| ShardSignerStatus {
+				signer,
+				fingerprint: EnclaveFingerprint::default(),
+				last_activity: 0,
+			})
+			.collect();
+		self.insert_storage(EnclaveBridgeStorage::shard_status(shard), status.encode());
+	}
+
+	pub fn insert_storage(&self, key: Vec<u8>, value: Vec<u8>) {
+		self.storage.write().expect("storage lock poisoned").insert(key, value);
+	}
+
+	/// All extrinsics the enclave has sent to the parentchain so far.
+	pub fn sent_extrinsics(&self) -> Vec<OpaqueExtrinsic> {
+		self.sent_extrinsics.read().expect("extrinsics lock poisoned").clone()
+	}
+}
+
+impl EnclaveAttestationOCallApi for SimulatedOCallApi {
+	fn sgx_init_quote(&self) -> SgxResult<(sgx_target_info_t, sgx_epid_group_id_t)> {
+		Err(sgx_status_t::SGX_ERROR_FEATURE_NOT_SUPPORTED)
+	}
+
+	fn get_ias_socket(&self) -> SgxResult<i32> {
+		Err(sgx_status_t::SGX_ERROR_FEATURE_NOT_SUPPORTED)
+	}
+
+	fn get_quote(
+		&self,
+		_sig_rl: Vec<u8>,
+		_report: sgx_report_t,
+		_sign_type: sgx_quote_sign_type_t,
+		_spid: sgx_spid_t,
+		_quote_nonce: sgx_quote_nonce_t,
+	) -> SgxResult<(sgx_report_t, Vec<u8>)> {
+		Err(sgx_status_t::SGX_ERROR_FEATURE_NOT_SUPPORTED)
+	}
+
+	fn get_dcap_quote(&self, _report: sgx_report_t, _quote_size: u32) -> SgxResult<Vec<u8>> {
+		Err(sgx_status_t::SGX_ERROR_FEATURE_NOT_SUPPORTED)
+	}
+
+	fn get_qve_report_on_quote(
+		&self,
+		_quote: Vec<u8>,
+		_current_time: i64,
+		_quote_collateral: sgx_ql_qve_collateral_t,
+		_qve_report_info: sgx_ql_qe_report_info_t,
+		_supplemental_data_size: u32,
+	) -> SgxResult<(u32, sgx_ql_qv_result_t, sgx_ql_qe_report_info_t, Vec<u8>)> {
+		Err(sgx_status_t::SGX_ERROR_FEATURE_NOT_SUPPORTED)
+	}
+
+	fn get_update_info(
+		&self,
+		_platform_info: sgx_platform_info_t,
+		_enclave_trusted: i32,
+	) -> SgxResult<sgx_update_info_bit_t> {
+		Err(sgx_status_t::SGX_ERROR_FEATURE_NOT_SUPPORTED)
+	}
+
+	fn get_mrenclave_of_self(&self) -> SgxResult<sgx_measurement_t> {
+		Ok(sgx_measurement_t { m: self.mr_enclave })
+	}
+}
+
+impl EnclaveSidechainOCallApi for SimulatedOCallApi {
+	fn propose_sidechain_blocks<SignedSidechainBlock: Encode>(
+		&self,
+		_signed_blocks: Vec<SignedSidechainBlock>,
+	) -> SgxResult<()> {
+		Ok(())
+	}
+
+	fn store_sidechain_blocks<SignedSidechainBlock: Encode>(
+		&self,
+		_signed_blocks: Vec<SignedSidechainBlock>,
+	) -> SgxResult<()> {
+		Ok(())
+	}
+
+	fn fetch_sidechain_blocks_from_peer<SignedSidechainBlock: Decode>(
+		&self,
+		_last_imported_block_hash: BlockHash,
+		_maybe_until_block_hash: Option<BlockHash>,
+		_shard_identifier: ShardIdentifier,
+	) -> SgxResult<Vec<SignedSidechainBlock>> {
+		// A simulated enclave is always the only validateer, there are no peers to sync from.
+		Ok(Vec::new())
+	}
+}
+
+impl EnclaveMetricsOCallApi for SimulatedOCallApi {
+	fn update_metric<Metric: Encode>(&self, _metric: Metric) -> SgxResult<()> {
+		Ok(())
+	}
+}
+
+impl EnclaveOnChainOCallApi for SimulatedOCallApi {
+	fn send_to_parentchain(
+		&self,
+		extrinsics: Vec<OpaqueExtrinsic>,
+		_: &ParentchainId,
+		_: bool,
+	) -> SgxResult<()> {
+		self.sent_extrinsics
+			.write()
+			.map_err(|_| sgx_status_t::SGX_ERROR_UNEXPECTED)?
+			.extend(extrinsics);
+		Ok(())
+	}
+
+	fn worker_request<V: Encode + Decode>(
+		&self,
+		_req: Vec<WorkerRequest>,
+		_: &ParentchainId,
+	) -> SgxResult<Vec<WorkerResponse<V>>> {
+		Ok(Vec::new())
+	}
+
+	fn get_storage_verified<Header: HeaderTrait<Hash = H256>, V: Decode>(
+		&self,
+		storage_hash: Vec<u8>,
+		header: &Header,
+		parentchain_id: &ParentchainId,
+	) -> Result<StorageEntryVerified<V>, itp_ocall_api::Error> {
+		self.get_multiple_storages_verified(vec![storage_hash], header, parentchain_id)?
+			.into_iter()
+			.next()
+			.ok_or(itp_ocall_api::Error::Storage(itp_storage::Error::StorageValueUnavailable))
+	}
+
+	fn get_multiple_storages_verified<Header: HeaderTrait<Hash = H256>, V: Decode>(
+		&self,
+		storage_hashes: Vec<Vec<u8>>,
+		_header: &Header,
+		_: &ParentchainId,
+	) -> Result<Vec<StorageEntryVerified<V>>, itp_ocall_api::Error> {
+		let storage = self
+			.storage
+			.read()
+			.map_err(|_| itp_ocall_api::Error::Sgx(sgx_status_t::SGX_ERROR_UNEXPECTED))?;
+
+		storage_hashes
+			.into_iter()
+			.map(|hash| {
+				let value = storage
+					.get(&hash)
+					.map(|val| Decode::decode(&mut val.as_slice()))
+					.transpose()
+					.map_err(itp_ocall_api::Error::Codec)?;
+				Ok(StorageEntryVerified::new(hash, value))
+			})
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itc_parentchain_test::ParentchainHeaderBuilder;
+	use its_primitives::types::SignedBlock as SignedSidechainBlock;
+	use its_sidechain::validateer_fetch::ValidateerFetch;
+
+	#[test]
+	fn validateers_are_returned_for_any_header() {
+		let ocall_api = SimulatedOCallApi::default();
+		let shard = ShardIdentifier::default();
+		ocall_api.set_validateers(shard, vec![AccountId::from([1u8; 32])]);
+
+		for number in 0..3 {
+			let header = ParentchainHeaderBuilder::default().with_number(number).build();
+			let validateers = ocall_api
+				.current_validateers::<_, SignedSidechainBlock>(&header, shard)
+				.unwrap();
+			assert_eq!(validateers, vec![AccountId::from([1u8; 32])]);
+		}
+	}
+
+	#[test]
+	fn sent_extrinsics_are_recorded() {
+		let ocall_api = SimulatedOCallApi::default();
+		let xt = OpaqueExtrinsic::from_bytes(&[4u8, 2u8]).unwrap();
+
+		ocall_api
+			.clone()
+			.send_to_parentchain(vec![xt.clone()], &ParentchainId::Integritee, false)
+			.unwrap();
+
+		assert_eq!(ocall_api.sent_extrinsics(), vec![xt]);
+	}
+}
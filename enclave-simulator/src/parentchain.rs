@@ -0,0 +1,313 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Simulated parentchain.
+//!
+//! Produces a chain of mocked parentchain blocks and imports them into the simulated enclave,
+//! the same way the parentchain block import dispatcher and importer do it in a real worker:
+//! The blocks are queued until the sidechain consensus triggers their import, upon which they are
+//! validated by the light client, the STF parentchain storage is updated and the contained
+//! indirect calls are dispatched into the trusted operation pool.
+
+use crate::{
+	error::{Error, Result},
+	types::{
+		SimulatorEnclaveSigner, SimulatorLightClient, SimulatorShieldingKeyRepository,
+		SimulatorStateHandler, SimulatorStateObserver, SimulatorStfExecutor,
+		SimulatorTopPoolAuthor,
+	},
+};
+use codec::{Decode, Encode};
+use ita_stf::{Getter, TrustedCall, TrustedCallSigned};
+use itc_parentchain_block_import_dispatcher::{
+	error::Result as DispatcherResult, triggered_dispatcher::TriggerParentchainBlockImport,
+};
+use itc_parentchain_light_client::{LightClientState, Validator};
+use itc_parentchain_test::{ParentchainBlockBuilder, ParentchainHeaderBuilder};
+use itp_import_queue::{ImportQueue, PeekQueue, PopFromQueue, PushToQueue};
+use itp_sgx_crypto::{key_repository::AccessKey, ShieldingCryptoDecrypt, ShieldingCryptoEncrypt};
+use itp_stf_executor::traits::{StfEnclaveSigning, StfUpdateState};
+use itp_stf_primitives::types::{AccountId, TrustedOperation};
+use itp_stf_state_handler::{handle_state::HandleState, query_shard_state::QueryShardState};
+use itp_stf_state_observer::traits::UpdateState;
+use itp_top_pool_author::traits::AuthorApi;
+use itp_types::{
+	parentchain::ParentchainId, Balance, Header, ShardIdentifier,
+	SignedBlock as SignedParentchainBlock,
+};
+use log::*;
+use sp_runtime::{traits::Header as HeaderTrait, OpaqueExtrinsic};
+use std::sync::{Arc, RwLock};
+
+/// Calls of the simulated parentchain, which are dispatched into the enclave upon block import.
+///
+/// They correspond to the indirect calls a real worker filters from the parentchain blocks.
+#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+pub enum SimulatedIndirectCall {
+	/// Shields funds into a shard. The account is encrypted with the enclave's shielding key.
+	ShieldFunds { shard: ShardIdentifier, account_encrypted: Vec<u8>, amount: Balance },
+}
+
+impl SimulatedIndirectCall {
+	pub fn to_opaque_extrinsic(&self) -> OpaqueExtrinsic {
+		OpaqueExtrinsic::from_bytes(self.encode().encode().as_slice())
+			.expect("A length prefixed byte vector is always a valid opaque extrinsic; qed")
+	}
+
+	pub fn from_opaque_extrinsic(xt: &OpaqueExtrinsic) -> Result<Self> {
+		let encoded_call = Vec::<u8>::decode(&mut xt.encode().as_slice())?;
+		Ok(Self::decode(&mut encoded_call.as_slice())?)
+	}
+}
+
+/// Produces a chain of mocked parentchain blocks.
+pub struct SimulatedParentchain {
+	latest_header: Header,
+	pending_calls: Vec<SimulatedIndirectCall>,
+}
+
+impl SimulatedParentchain {
+	pub fn new(genesis_header: Header) -> Self {
+		Self { latest_header: genesis_header, pending_calls: Vec::new() }
+	}
+
+	pub fn latest_header(&self) -> &Header {
+		&self.latest_header
+	}
+
+	/// Includes a call in the next produced block.
+	pub fn include_call(&mut self, call: SimulatedIndirectCall) {
+		self.pending_calls.push(call);
+	}
+
+	/// Produces a new block on top of the latest one, containing all pending calls.
+	pub fn produce_block(&mut self) -> SignedParentchainBlock {
+		let header = ParentchainHeaderBuilder::default()
+			.with_number(self.latest_header.number + 1)
+			.with_parent_hash(self.latest_header.hash())
+			.build();
+		let extrinsics =
+			self.pending_calls.drain(..).map(|call| call.to_opaque_extrinsic()).collect();
+
+		self.latest_header = header.clone();
+		ParentchainBlockBuilder::default()
+			.with_header(header)
+			.with_extrinsics(extrinsics)
+			.build_signed()
+	}
+}
+
+/// Queues the simulated parentchain blocks and imports them, once triggered.
+pub struct SimulatedParentchainImporter {
+	import_queue: ImportQueue<SignedParentchainBlock>,
+	light_client: RwLock<SimulatorLightClient>,
+	stf_executor: Arc<SimulatorStfExecutor>,
+	enclave_signer: Arc<SimulatorEnclaveSigner>,
+	top_pool_author: Arc<SimulatorTopPoolAuthor>,
+	shielding_key_repository: Arc<SimulatorShieldingKeyRepository>,
+	state_handler: Arc<SimulatorStateHandler>,
+	state_observer: Arc<SimulatorStateObserver>,
+}
+
+impl SimulatedParentchainImporter {
+	#[allow(clippy::too_many_arguments)]
+	pub fn new(
+		light_client: SimulatorLightClient,
+		stf_executor: Arc<SimulatorStfExecutor>,
+		enclave_signer: Arc<SimulatorEnclaveSigner>,
+		top_pool_author: Arc<SimulatorTopPoolAuthor>,
+		shielding_key_repository: Arc<SimulatorShieldingKeyRepository>,
+		state_handler: Arc<SimulatorStateHandler>,
+		state_observer: Arc<SimulatorStateObserver>,
+	) -> Self {
+		Self {
+			import_queue: ImportQueue::default(),
+			light_client: RwLock::new(light_client),
+			stf_executor,
+			enclave_signer,
+			top_pool_author,
+			shielding_key_repository,
+			state_handler,
+			state_observer,
+		}
+	}
+
+	/// Queues a block for import, equivalent to the untrusted worker syncing a parentchain block.
+	pub fn dispatch_import(&self, block: SignedParentchainBlock) -> Result<()> {
+		Ok(self.import_queue.push_single(block)?)
+	}
+
+	/// Latest parentchain header finalized by the light client.
+	pub fn latest_imported_header(&self) -> Result<Header> {
+		let light_client = self.light_client.read().map_err(|_| Error::LockPoisoning)?;
+		Ok(light_client.latest_finalized_header()?)
+	}
+
+	fn import_blocks(&self, blocks: Vec<SignedParentchainBlock>) -> Result<()> {
+		for block in blocks.iter() {
+			self.import_block(block)?;
+		}
+		Ok(())
+	}
+
+	fn import_block(&self, block: &SignedParentchainBlock) -> Result<()> {
+		let header = &block.block.header;
+		debug!("Importing simulated parentchain block {}", header.number);
+
+		self.light_client
+			.write()
+			.map_err(|_| Error::LockPoisoning)?
+			.submit_block(block)?;
+
+		self.stf_executor.update_states(header, &ParentchainId::Integritee)?;
+		sync_state_observer(self.state_handler.as_ref(), self.state_observer.as_ref())?;
+
+		for xt in block.block.extrinsics.iter() {
+			if let Err(e) = SimulatedIndirectCall::from_opaque_extrinsic(xt)
+				.and_then(|call| self.dispatch_indirect_call(call))
+			{
+				error!("Error executing the indirect call: {:?}", e);
+			}
+		}
+		Ok(())
+	}
+
+	fn dispatch_indirect_call(&self, call: SimulatedIndirectCall) -> Result<()> {
+		match call {
+			SimulatedIndirectCall::ShieldFunds { shard, account_encrypted, amount } => {
+				let shielding_key = self.shielding_key_repository.retrieve_key()?;
+				let account_vec = shielding_key.decrypt(&account_encrypted)?;
+				let account = AccountId::decode(&mut account_vec.as_slice())?;
+
+				let enclave_account = self.enclave_signer.get_enclave_account()?;
+				let trusted_call = TrustedCall::balance_shield(enclave_account, account, amount);
+				let signed_trusted_call =
+					self.enclave_signer.sign_call_with_self(&trusted_call, &shard)?;
+				let trusted_operation =
+					TrustedOperation::<TrustedCallSigned, Getter>::indirect_call(
+						signed_trusted_call,
+					);
+
+				let encrypted_trusted_call = shielding_key.encrypt(&trusted_operation.encode())?;
+				futures::executor::block_on(
+					self.top_pool_author.submit_top(encrypted_trusted_call, shard),
+				)?;
+				Ok(())
+			},
+		}
+	}
+}
+
+impl TriggerParentchainBlockImport for SimulatedParentchainImporter {
+	type SignedBlockType = SignedParentchainBlock;
+
+	fn import_all(&self) -> DispatcherResult<Option<SignedParentchainBlock>> {
+		let blocks = self.import_queue.pop_all()?;
+		let latest_imported_block = blocks.last().cloned();
+		self.import_blocks(blocks).map_err(into_dispatcher_error)?;
+		Ok(latest_imported_block)
+	}
+
+	fn import_all_but_latest(&self) -> DispatcherResult<()> {
+		let blocks = self.import_queue.pop_all_but_last()?;
+		self.import_blocks(blocks).map_err(into_dispatcher_error)
+	}
+
+	fn import_until(
+		&self,
+		predicate: impl Fn(&SignedParentchainBlock) -> bool,
+	) -> DispatcherResult<Option<SignedParentchainBlock>> {
+		let blocks = self.import_queue.pop_until(predicate)?;
+		let latest_imported_block = blocks.last().cloned();
+		self.import_blocks(blocks).map_err(into_dispatcher_error)?;
+		Ok(latest_imported_block)
+	}
+
+	fn peek(
+		&self,
+		predicate: impl Fn(&SignedParentchainBlock) -> bool,
+	) -> DispatcherResult<Option<SignedParentchainBlock>> {
+		Ok(self.import_queue.peek_find(predicate)?)
+	}
+
+	fn peek_latest(&self) -> DispatcherResult<Option<SignedParentchainBlock>> {
+		Ok(self.import_queue.peek_last()?)
+	}
+}
+
+fn into_dispatcher_error(e: Error) -> itc_parentchain_block_import_dispatcher::error::Error {
+	itc_parentchain_block_import_dispatcher::error::Error::Other(Box::new(e))
+}
+
+/// Makes the latest state of all shards visible to the state observer,
+/// which is the state getters and the enclave signer operate on.
+pub(crate) fn sync_state_observer(
+	state_handler: &SimulatorStateHandler,
+	state_observer: &SimulatorStateObserver,
+) -> Result<()> {
+	for shard in state_handler.list_shards()? {
+		let (state, _) = state_handler.load_cloned(&shard)?;
+		state_observer.queue_state_update(shard, state)?;
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn indirect_call_survives_opaque_extrinsic_roundtrip() {
+		let call = SimulatedIndirectCall::ShieldFunds {
+			shard: ShardIdentifier::repeat_byte(1),
+			account_encrypted: vec![3u8; 384],
+			amount: 42,
+		};
+
+		let xt = call.to_opaque_extrinsic();
+
+		assert_eq!(SimulatedIndirectCall::from_opaque_extrinsic(&xt).unwrap(), call);
+	}
+
+	#[test]
+	fn produced_blocks_form_a_chain() {
+		let genesis = ParentchainHeaderBuilder::default().build();
+		let mut parentchain = SimulatedParentchain::new(genesis.clone());
+
+		let first = parentchain.produce_block();
+		let second = parentchain.produce_block();
+
+		assert_eq!(first.block.header.parent_hash, genesis.hash());
+		assert_eq!(second.block.header.parent_hash, first.block.header.hash());
+		assert_eq!(second.block.header.number, 2);
+		assert_eq!(parentchain.latest_header(), &second.block.header);
+	}
+
+	#[test]
+	fn pending_calls_are_included_in_next_block_only() {
+		let mut parentchain =
+			SimulatedParentchain::new(ParentchainHeaderBuilder::default().build());
+		parentchain.include_call(SimulatedIndirectCall::ShieldFunds {
+			shard: ShardIdentifier::default(),
+			account_encrypted: vec![],
+			amount: 1,
+		});
+
+		assert_eq!(parentchain.produce_block().block.extrinsics.len(), 1);
+		assert!(parentchain.produce_block().block.extrinsics.is_empty());
+	}
+}
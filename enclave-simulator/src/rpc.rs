@@ -0,0 +1,151 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Direct RPC interface of the simulated enclave.
+//!
+//! Serves the same request and response formats as the enclave's direct RPC server, such that
+//! clients can be tested against the simulator. There is no TLS websocket server, requests are
+//! handled in-process.
+
+use crate::types::{
+	SimulatorGetterExecutor, SimulatorShieldingKeyRepository, SimulatorSigningKeyRepository,
+	SimulatorTopPoolAuthor,
+};
+use codec::Encode;
+use itp_rpc::RpcReturnValue;
+use itp_sgx_crypto::{key_repository::AccessKey, ToPubkey};
+use itp_stf_executor::{
+	error::Error as StfExecutorError,
+	getter_executor::ExecuteGetter,
+	getter_stream::{GetterStreamer, StreamGetter},
+};
+use itp_types::{
+	getter_stream::{GetterStreamChunk, GetterStreamRequest},
+	DirectRequestStatus, Request, ShardIdentifier,
+};
+use itp_utils::{FromHexPrefixed, ToHexPrefixed};
+use its_sidechain::rpc_handler::{
+	direct_top_pool_api::add_top_pool_direct_rpc_methods,
+	submission_receipt_api::add_submission_receipt_rpc_method,
+};
+use jsonrpc_core::{serde_json::json, Error as RpcError, ErrorCode, IoHandler, Params, Value};
+use log::*;
+use std::sync::Arc;
+
+pub fn simulator_rpc_handler(
+	top_pool_author: Arc<SimulatorTopPoolAuthor>,
+	getter_executor: Arc<SimulatorGetterExecutor>,
+	shielding_key_repository: Arc<SimulatorShieldingKeyRepository>,
+	signing_key_repository: Arc<SimulatorSigningKeyRepository>,
+	shard: ShardIdentifier,
+) -> IoHandler {
+	let io = add_top_pool_direct_rpc_methods(top_pool_author.clone(), IoHandler::new());
+	let mut io = add_submission_receipt_rpc_method(top_pool_author, signing_key_repository, io);
+
+	io.add_sync_method("author_getShieldingKey", move |_: Params| {
+		debug!("simulator rpc was called: author_getShieldingKey");
+		let json_value = match shielding_key_repository
+			.retrieve_key()
+			.and_then(|key| key.pubkey())
+			.map_err(|e| format!("Could not get rsa pubkey due to: {:?}", e))
+			.and_then(|pubkey| serde_json::to_string(&pubkey).map_err(|e| e.to_string()))
+		{
+			Ok(rsa_pubkey_json) =>
+				RpcReturnValue::new(rsa_pubkey_json.encode(), false, DirectRequestStatus::Ok)
+					.to_hex(),
+			Err(error) => RpcReturnValue::from_error_message(&error).to_hex(),
+		};
+		Ok(json!(json_value))
+	});
+
+	io.add_sync_method("author_getShard", move |_: Params| {
+		debug!("simulator rpc was called: author_getShard");
+		let json_value = RpcReturnValue::new(shard.encode(), false, DirectRequestStatus::Ok);
+		Ok(json!(json_value.to_hex()))
+	});
+
+	let getter_streamer = GetterStreamer::new(getter_executor.clone());
+	io.add_sync_method("state_executeGetterStream", move |params: Params| {
+		debug!("simulator rpc was called: state_executeGetterStream");
+		let chunk = stream_getter_inner(&getter_streamer, params)?;
+		let json_value = RpcReturnValue::new(chunk.encode(), false, DirectRequestStatus::Ok);
+		Ok(json!(json_value.to_hex()))
+	});
+
+	io.add_sync_method("state_executeGetter", move |params: Params| {
+		debug!("simulator rpc was called: state_executeGetter");
+		let state_getter_value = execute_getter_inner(getter_executor.as_ref(), params)?;
+		let json_value =
+			RpcReturnValue::new(state_getter_value.encode(), false, DirectRequestStatus::Ok);
+		Ok(json!(json_value.to_hex()))
+	});
+
+	io.add_sync_method("system_name", |_: Params| {
+		debug!("simulator rpc was called: system_name");
+		Ok(Value::String(env!("CARGO_PKG_NAME").into()))
+	});
+
+	let rpc_methods_string = format!(
+		"methods: [{}]",
+		io.iter().map(|(name, _)| name.to_owned()).collect::<Vec<_>>().join(", ")
+	);
+	io.add_sync_method("rpc_methods", move |_: Params| {
+		debug!("simulator rpc was called: rpc_methods");
+		Ok(Value::String(rpc_methods_string.to_owned()))
+	});
+
+	io
+}
+
+fn execute_getter_inner<GE: ExecuteGetter>(
+	getter_executor: &GE,
+	params: Params,
+) -> Result<Option<Vec<u8>>, RpcError> {
+	let hex_encoded_params = params.parse::<Vec<String>>()?;
+	let hex_encoded_request = hex_encoded_params
+		.first()
+		.ok_or_else(|| RpcError::invalid_params("Missing request parameter"))?;
+
+	let request = Request::from_hex(hex_encoded_request)
+		.map_err(|e| RpcError::invalid_params(format!("{:?}", e)))?;
+
+	getter_executor
+		.execute_getter(&request.shard, request.cyphertext)
+		.map_err(|e| stf_executor_rpc_error(&e))
+}
+
+fn stream_getter_inner<SG: StreamGetter>(
+	getter_streamer: &SG,
+	params: Params,
+) -> Result<GetterStreamChunk, RpcError> {
+	let hex_encoded_params = params.parse::<Vec<String>>()?;
+	let hex_encoded_request = hex_encoded_params
+		.first()
+		.ok_or_else(|| RpcError::invalid_params("Missing request parameter"))?;
+
+	let stream_request = GetterStreamRequest::from_hex(hex_encoded_request)
+		.map_err(|e| RpcError::invalid_params(format!("{:?}", e)))?;
+	let request = stream_request.request;
+
+	getter_streamer
+		.stream_getter(&request.shard, request.cyphertext, stream_request.chunk)
+		.map_err(|e| stf_executor_rpc_error(&e))
+}
+
+fn stf_executor_rpc_error(error: &StfExecutorError) -> RpcError {
+	RpcError { code: ErrorCode::ServerError(error.code()), message: error.to_string(), data: None }
+}
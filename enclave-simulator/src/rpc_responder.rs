@@ -0,0 +1,71 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! RPC responder that records the status updates and states of trusted operations,
+//! instead of pushing them to a connected client.
+
+use itc_direct_rpc_server::{DirectRpcResult, SendRpcResponse};
+use itp_types::{TrustedOperationStatus, H256};
+use std::{collections::HashMap, sync::RwLock};
+
+#[derive(Default)]
+pub struct RecordingRpcResponder {
+	status_updates: RwLock<HashMap<H256, Vec<TrustedOperationStatus>>>,
+	sent_states: RwLock<HashMap<H256, Vec<u8>>>,
+}
+
+impl RecordingRpcResponder {
+	/// All status updates of a trusted operation, in the order they were sent.
+	pub fn status_updates(&self, hash: &H256) -> Vec<TrustedOperationStatus> {
+		self.status_updates
+			.read()
+			.expect("status lock poisoned")
+			.get(hash)
+			.cloned()
+			.unwrap_or_default()
+	}
+
+	pub fn sent_state(&self, hash: &H256) -> Option<Vec<u8>> {
+		self.sent_states.read().expect("state lock poisoned").get(hash).cloned()
+	}
+}
+
+impl SendRpcResponse for RecordingRpcResponder {
+	type Hash = H256;
+
+	fn update_status_event(
+		&self,
+		hash: Self::Hash,
+		status_update: TrustedOperationStatus,
+	) -> DirectRpcResult<()> {
+		self.status_updates
+			.write()
+			.expect("status lock poisoned")
+			.entry(hash)
+			.or_default()
+			.push(status_update);
+		Ok(())
+	}
+
+	fn send_state(&self, hash: Self::Hash, state_encoded: Vec<u8>) -> DirectRpcResult<()> {
+		self.sent_states
+			.write()
+			.expect("state lock poisoned")
+			.insert(hash, state_encoded);
+		Ok(())
+	}
+}
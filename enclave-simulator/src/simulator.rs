@@ -0,0 +1,379 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! The enclave simulator assembles the worker pipeline in std.
+
+use crate::{
+	error::{Error, Result},
+	ocall_api::SimulatedOCallApi,
+	parentchain::{
+		sync_state_observer, SimulatedIndirectCall, SimulatedParentchain,
+		SimulatedParentchainImporter,
+	},
+	rpc::simulator_rpc_handler,
+	rpc_responder::RecordingRpcResponder,
+	types::*,
+};
+use codec::Encode;
+use ita_stf::{
+	migrations::STF_VERSION, shard_acl::SubmissionAccessControl, Getter, TrustedCallSigned,
+};
+use itc_parentchain_light_client::{
+	finality::{Finality, ParachainFinality},
+	light_validation::LightValidation,
+	light_validation_state::LightValidationState,
+	state::RelayState,
+};
+use itc_parentchain_test::ParentchainHeaderBuilder;
+use itp_node_api::metadata::{metadata_mocks::NodeMetadataMock, provider::NodeMetadataRepository};
+use itp_settings::sidechain::SLOT_DURATION;
+use itp_sgx_crypto::{
+	ed25519_derivation::DeriveEd25519, key_repository::AccessKey, ShieldingCryptoEncrypt,
+};
+use itp_sgx_externalities::SgxExternalitiesTrait;
+use itp_stf_executor::getter_executor::ExecuteGetter;
+use itp_stf_interface::{system_pallet::SystemPalletAccountInterface, InitState};
+use itp_stf_primitives::types::{AccountId, TrustedOperation};
+use itp_stf_state_handler::handle_state::HandleState;
+use itp_stf_state_observer::traits::ObserveState;
+use itp_test::mock::{handle_state_mock::HandleStateMock, metrics_ocall_mock::MetricsOCallMock};
+use itp_time_utils::duration_now;
+use itp_top_pool::pool::Options as PoolOptions;
+use itp_top_pool_author::{
+	api::SidechainApi, top_filter::AllowAllTopsFilter, traits::AuthorApi,
+	validation::ValidationConfig,
+};
+use itp_types::{
+	parentchain::ParentchainId, AccountData, Balance, Block as ParentchainBlock, Header, Index,
+	ShardIdentifier, TrustedOperationStatus, H256,
+};
+use its_block_verification::slot::slot_from_timestamp_and_duration;
+use its_primitives::{
+	traits::SignedBlock as SignedSidechainBlockTrait, types::SignedBlock as SignedSidechainBlock,
+};
+use its_sidechain::{
+	aura::{proposer_factory::ProposerFactory, Aura, SlotClaimStrategy},
+	block_composer::BlockComposer,
+	consensus_common::BlockImport,
+	slots::{PerShardSlotWorkerScheduler, SlotInfo, SlotResult},
+};
+use jsonrpc_core::IoHandler;
+use log::*;
+use sgx_crypto_helper::{rsa3072::Rsa3072KeyPair, RsaKeyPair};
+use sp_core::Pair;
+use sp_runtime::OpaqueExtrinsic;
+use std::{
+	boxed::Box,
+	sync::{Arc, RwLock},
+};
+
+/// Configuration of the simulated enclave.
+#[derive(Clone, Debug)]
+pub struct SimulatorConfig {
+	/// MRENCLAVE reported by the simulated enclave. Also used as shard identifier.
+	pub mr_enclave: [u8; 32],
+	/// Seed of the sidechain block authoring key.
+	pub authority_seed: [u8; 32],
+	/// Key and initialization vector of the state encryption key.
+	pub state_key: ([u8; 16], [u8; 16]),
+}
+
+impl Default for SimulatorConfig {
+	fn default() -> Self {
+		Self {
+			mr_enclave: [1u8; 32],
+			authority_seed: *b"42315678901234567890123456789012",
+			state_key: ([3u8; 16], [1u8; 16]),
+		}
+	}
+}
+
+/// Simulated enclave, running the worker pipeline without SGX.
+///
+/// The light client is fed from a mocked parentchain, and all components from the trusted
+/// operation pool up to the sidechain block import are the ones of a real enclave, instantiated
+/// in std. The simulated enclave is the only validateer of its shard.
+pub struct EnclaveSimulator {
+	shard: ShardIdentifier,
+	authority: SimulatorAuthority,
+	ocall_api: Arc<SimulatedOCallApi>,
+	shielding_key_repository: Arc<SimulatorShieldingKeyRepository>,
+	state_handler: Arc<SimulatorStateHandler>,
+	state_observer: Arc<SimulatorStateObserver>,
+	rpc_responder: Arc<RecordingRpcResponder>,
+	top_pool_author: Arc<SimulatorTopPoolAuthor>,
+	stf_executor: Arc<SimulatorStfExecutor>,
+	getter_executor: Arc<SimulatorGetterExecutor>,
+	block_composer: Arc<SimulatorBlockComposer>,
+	block_importer: SimulatorBlockImporter,
+	parentchain: RwLock<SimulatedParentchain>,
+	parentchain_importer: Arc<SimulatedParentchainImporter>,
+	rpc_handler: IoHandler,
+}
+
+impl EnclaveSimulator {
+	pub fn new(config: SimulatorConfig) -> Result<Self> {
+		let shard = ShardIdentifier::from(config.mr_enclave);
+		let authority = SimulatorAuthority::from_seed(&config.authority_seed);
+
+		let ocall_api = Arc::new(SimulatedOCallApi::new(config.mr_enclave));
+		ocall_api.set_validateers(shard, vec![authority.public().into()]);
+
+		let shielding_key = Rsa3072KeyPair::new()
+			.map_err(|e| Error::Other(format!("Failed to create shielding key: {:?}", e).into()))?;
+		let enclave_account: AccountId = shielding_key.derive_ed25519()?.public().into();
+		let shielding_key_repository =
+			Arc::new(SimulatorShieldingKeyRepository::new(shielding_key));
+		let (key, init_vec) = config.state_key;
+		let state_key_repository =
+			Arc::new(SimulatorStateKeyRepository::new(SimulatorStateKey::new(key, init_vec)));
+
+		let state_handler = Arc::new(HandleStateMock::default());
+		initialize_shard(state_handler.as_ref(), shard, enclave_account)?;
+		let state_observer = Arc::new(SimulatorStateObserver::default());
+		sync_state_observer(state_handler.as_ref(), state_observer.as_ref())?;
+
+		let rpc_responder = Arc::new(RecordingRpcResponder::default());
+		let top_pool = Arc::new(SimulatorTopPool::create(
+			PoolOptions::default(),
+			Arc::new(SidechainApi::<ParentchainBlock, TrustedCallSigned>::new()),
+			rpc_responder.clone(),
+		));
+		let top_pool_author = Arc::new(
+			SimulatorTopPoolAuthor::new(
+				top_pool,
+				AllowAllTopsFilter::<TrustedCallSigned, Getter>::new(),
+				state_handler.clone(),
+				shielding_key_repository.clone(),
+				Arc::new(MetricsOCallMock::default()),
+			)
+			.with_validation(ValidationConfig::new(config.mr_enclave, STF_VERSION))
+			.with_access_control(Arc::new(SubmissionAccessControl)),
+		);
+
+		let enclave_signer = Arc::new(SimulatorEnclaveSigner::new(
+			state_observer.clone(),
+			ocall_api.clone(),
+			shielding_key_repository.clone(),
+			top_pool_author.clone(),
+		));
+		let stf_executor = Arc::new(SimulatorStfExecutor::new(
+			ocall_api.clone(),
+			state_handler.clone(),
+			Arc::new(NodeMetadataRepository::new(NodeMetadataMock::new())),
+		));
+		let getter_executor = Arc::new(SimulatorGetterExecutor::new(state_observer.clone()));
+
+		let genesis_header = ParentchainHeaderBuilder::default().build();
+		let finality: Arc<Box<dyn Finality<ParentchainBlock> + Sync + Send + 'static>> =
+			Arc::new(Box::new(ParachainFinality));
+		let light_client = LightValidation::new(
+			ocall_api.clone(),
+			finality,
+			LightValidationState::new(RelayState::new(genesis_header.clone(), Default::default())),
+			ParentchainId::Integritee,
+		);
+		let parentchain_importer = Arc::new(SimulatedParentchainImporter::new(
+			light_client,
+			stf_executor.clone(),
+			enclave_signer,
+			top_pool_author.clone(),
+			shielding_key_repository.clone(),
+			state_handler.clone(),
+			state_observer.clone(),
+		));
+
+		let block_importer = SimulatorBlockImporter::new(
+			state_handler.clone(),
+			state_key_repository.clone(),
+			top_pool_author.clone(),
+			parentchain_importer.clone(),
+			ocall_api.clone(),
+		);
+		let block_composer = Arc::new(BlockComposer::new(authority.clone(), state_key_repository));
+
+		let rpc_handler = simulator_rpc_handler(
+			top_pool_author.clone(),
+			getter_executor.clone(),
+			shielding_key_repository.clone(),
+			Arc::new(SimulatorSigningKeyRepository::new(authority.clone())),
+			shard,
+		);
+
+		Ok(Self {
+			shard,
+			authority,
+			ocall_api,
+			shielding_key_repository,
+			state_handler,
+			state_observer,
+			rpc_responder,
+			top_pool_author,
+			stf_executor,
+			getter_executor,
+			block_composer,
+			block_importer,
+			parentchain: RwLock::new(SimulatedParentchain::new(genesis_header)),
+			parentchain_importer,
+			rpc_handler,
+		})
+	}
+
+	pub fn shard(&self) -> ShardIdentifier {
+		self.shard
+	}
+
+	pub fn mr_enclave(&self) -> [u8; 32] {
+		self.shard.to_fixed_bytes()
+	}
+
+	/// Encrypts a payload with the shielding key, as a client does with the enclave's public key.
+	pub fn encrypt(&self, payload: &[u8]) -> Result<Vec<u8>> {
+		Ok(self.shielding_key_repository.retrieve_key()?.encrypt(payload)?)
+	}
+
+	/// Includes a `shield_funds` call in the next parentchain block.
+	pub fn shield_funds(&self, account: &AccountId, amount: Balance) -> Result<()> {
+		let account_encrypted = self.encrypt(&account.encode())?;
+		self.parentchain.write().map_err(|_| Error::LockPoisoning)?.include_call(
+			SimulatedIndirectCall::ShieldFunds { shard: self.shard, account_encrypted, amount },
+		);
+		Ok(())
+	}
+
+	/// Produces a parentchain block and queues it for import into the enclave.
+	///
+	/// The block is imported when the next sidechain block is produced.
+	pub fn produce_parentchain_block(&self) -> Result<Header> {
+		let block = self.parentchain.write().map_err(|_| Error::LockPoisoning)?.produce_block();
+		let header = block.block.header.clone();
+		self.parentchain_importer.dispatch_import(block)?;
+		Ok(header)
+	}
+
+	/// Latest parentchain header that has been imported by the light client.
+	pub fn latest_imported_parentchain_header(&self) -> Result<Header> {
+		self.parentchain_importer.latest_imported_header()
+	}
+
+	/// Submits a trusted call to the pool, encrypted like a client would.
+	///
+	/// The operation is watched, so its status updates are recorded. Returns the hash of the
+	/// trusted operation.
+	pub fn submit_trusted_call(&self, call: TrustedCallSigned) -> Result<H256> {
+		let trusted_operation = TrustedOperation::<TrustedCallSigned, Getter>::direct_call(call);
+		self.submit_encrypted_trusted_operation(self.encrypt(&trusted_operation.encode())?)
+	}
+
+	pub fn submit_encrypted_trusted_operation(&self, encrypted: Vec<u8>) -> Result<H256> {
+		Ok(futures::executor::block_on(self.top_pool_author.watch_top(encrypted, self.shard))?)
+	}
+
+	pub fn pending_trusted_calls(&self) -> usize {
+		self.top_pool_author.get_pending_trusted_calls(self.shard).len()
+	}
+
+	/// Status updates the enclave has sent for a watched trusted operation.
+	pub fn trusted_operation_status(&self, hash: &H256) -> Vec<TrustedOperationStatus> {
+		self.rpc_responder.status_updates(hash)
+	}
+
+	/// Runs the sidechain consensus on the current slot and imports the produced block.
+	///
+	/// Queued parentchain blocks are imported, before the trusted calls in the pool are executed.
+	pub fn produce_sidechain_block(&self) -> Result<SlotResult<SignedSidechainBlock>> {
+		let now = duration_now();
+		let slot_info = SlotInfo::new(
+			slot_from_timestamp_and_duration(now, SLOT_DURATION),
+			now,
+			SLOT_DURATION,
+			now + SLOT_DURATION,
+			self.parentchain_importer.latest_imported_header()?,
+		);
+
+		let proposer_environment = ProposerFactory::new(
+			self.top_pool_author.clone(),
+			self.stf_executor.clone(),
+			self.block_composer.clone(),
+		);
+		let mut aura = Aura::<_, ParentchainBlock, SignedSidechainBlock, _, _, _>::new(
+			self.authority.clone(),
+			self.ocall_api.as_ref().clone(),
+			self.parentchain_importer.clone(),
+			proposer_environment,
+		)
+		.with_claim_strategy(SlotClaimStrategy::RoundRobin);
+
+		let slot_result =
+			PerShardSlotWorkerScheduler::on_slot(&mut aura, slot_info, vec![self.shard])
+				.pop()
+				.ok_or(Error::NoBlockProduced(self.shard))?;
+
+		let parentchain_header = self.parentchain_importer.latest_imported_header()?;
+		self.block_importer
+			.import_block(slot_result.block.clone(), &parentchain_header)?;
+		sync_state_observer(self.state_handler.as_ref(), self.state_observer.as_ref())?;
+
+		info!(
+			"Simulated enclave produced and imported sidechain block {}",
+			slot_result.block.hash()
+		);
+		Ok(slot_result)
+	}
+
+	/// Executes a getter on the state, as done by the `state_executeGetter` RPC method.
+	pub fn execute_getter(&self, getter: &Getter) -> Result<Option<Vec<u8>>> {
+		Ok(self.getter_executor.execute_getter(&self.shard, getter.encode())?)
+	}
+
+	/// Reads the account data directly from the state, without a getter.
+	pub fn account_data(&self, account: &AccountId) -> Result<AccountData> {
+		Ok(self
+			.state_observer
+			.observe_state(&self.shard, |state| SimulatorStf::get_account_data(state, account))?)
+	}
+
+	/// Reads the account nonce directly from the state, without a getter.
+	pub fn account_nonce(&self, account: &AccountId) -> Result<Index> {
+		Ok(self
+			.state_observer
+			.observe_state(&self.shard, |state| SimulatorStf::get_account_nonce(state, account))?)
+	}
+
+	/// Extrinsics the enclave has sent to the parentchain.
+	pub fn sent_extrinsics(&self) -> Vec<OpaqueExtrinsic> {
+		self.ocall_api.sent_extrinsics()
+	}
+
+	/// Handles a JSON-RPC request, as the enclave's direct RPC server does.
+	pub fn handle_rpc_request(&self, request: &str) -> Option<String> {
+		self.rpc_handler.handle_request_sync(request)
+	}
+}
+
+fn initialize_shard(
+	state_handler: &SimulatorStateHandler,
+	shard: ShardIdentifier,
+	enclave_account: AccountId,
+) -> Result<()> {
+	state_handler.initialize_shard(shard)?;
+	let (lock, _) = state_handler.load_for_mutation(&shard)?;
+	let mut state = SimulatorStf::init_state(enclave_account);
+	state.prune_state_diff();
+	state_handler.write_after_mutation(state, lock, &shard)?;
+	Ok(())
+}
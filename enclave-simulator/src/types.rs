@@ -0,0 +1,129 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Concrete types of the simulated enclave components.
+
+use crate::{
+	ocall_api::SimulatedOCallApi, parentchain::SimulatedParentchainImporter,
+	rpc_responder::RecordingRpcResponder,
+};
+use ita_sgx_runtime::Runtime;
+use ita_stf::{Getter, Stf, TrustedCallSigned};
+use itc_parentchain_light_client::light_validation::LightValidation;
+use itp_node_api::metadata::{metadata_mocks::NodeMetadataMock, provider::NodeMetadataRepository};
+use itp_sgx_crypto::{mocks::KeyRepositoryMock, Aes};
+use itp_sgx_externalities::SgxExternalities;
+use itp_stf_executor::{
+	enclave_signer::StfEnclaveSigner, executor::StfExecutor, getter_executor::GetterExecutor,
+	state_getter::StfStateGetter,
+};
+use itp_stf_primitives::types::TrustedOperation;
+use itp_stf_state_observer::state_observer::StateObserver;
+use itp_test::mock::{handle_state_mock::HandleStateMock, metrics_ocall_mock::MetricsOCallMock};
+use itp_top_pool::basic_pool::BasicPool;
+use itp_top_pool_author::{api::SidechainApi, author::Author, top_filter::AllowAllTopsFilter};
+use itp_types::Block as ParentchainBlock;
+use its_primitives::types::SignedBlock as SignedSidechainBlock;
+use its_sidechain::{
+	aura::{block_importer::BlockImporter, proposer_factory::ProposerFactory},
+	block_composer::BlockComposer,
+};
+use sgx_crypto_helper::rsa3072::Rsa3072KeyPair;
+use sp_core::ed25519;
+
+pub type SimulatorAuthority = ed25519::Pair;
+pub type SimulatorShieldingKey = Rsa3072KeyPair;
+pub type SimulatorStateKey = Aes;
+
+pub type SimulatorStf = Stf<TrustedCallSigned, Getter, SgxExternalities, Runtime>;
+
+pub type SimulatorShieldingKeyRepository = KeyRepositoryMock<SimulatorShieldingKey>;
+pub type SimulatorStateKeyRepository = KeyRepositoryMock<SimulatorStateKey>;
+pub type SimulatorSigningKeyRepository = KeyRepositoryMock<SimulatorAuthority>;
+
+pub type SimulatorStateHandler = HandleStateMock;
+pub type SimulatorStateObserver = StateObserver<SgxExternalities>;
+
+pub type SimulatorNodeMetadataRepository = NodeMetadataRepository<NodeMetadataMock>;
+
+pub type SimulatorLightClient = LightValidation<ParentchainBlock, SimulatedOCallApi>;
+
+pub type SimulatorTopPool = BasicPool<
+	SidechainApi<ParentchainBlock, TrustedCallSigned>,
+	ParentchainBlock,
+	RecordingRpcResponder,
+	TrustedOperation<TrustedCallSigned, Getter>,
+>;
+
+pub type SimulatorTopPoolAuthor = Author<
+	SimulatorTopPool,
+	AllowAllTopsFilter<TrustedCallSigned, Getter>,
+	SimulatorStateHandler,
+	SimulatorShieldingKeyRepository,
+	MetricsOCallMock,
+	TrustedCallSigned,
+	Getter,
+>;
+
+pub type SimulatorEnclaveSigner = StfEnclaveSigner<
+	SimulatedOCallApi,
+	SimulatorStateObserver,
+	SimulatorShieldingKeyRepository,
+	SimulatorStf,
+	SimulatorTopPoolAuthor,
+	TrustedCallSigned,
+	Getter,
+>;
+
+pub type SimulatorStfExecutor = StfExecutor<
+	SimulatedOCallApi,
+	SimulatorStateHandler,
+	SimulatorNodeMetadataRepository,
+	SimulatorStf,
+	TrustedCallSigned,
+	Getter,
+>;
+
+pub type SimulatorGetterExecutor =
+	GetterExecutor<SimulatorStateObserver, StfStateGetter<SimulatorStf>, Getter>;
+
+pub type SimulatorBlockComposer = BlockComposer<
+	ParentchainBlock,
+	SignedSidechainBlock,
+	SimulatorAuthority,
+	SimulatorStateKeyRepository,
+>;
+
+pub type SimulatorProposerFactory = ProposerFactory<
+	ParentchainBlock,
+	SimulatorTopPoolAuthor,
+	SimulatorStfExecutor,
+	SimulatorBlockComposer,
+>;
+
+pub type SimulatorBlockImporter = BlockImporter<
+	SimulatorAuthority,
+	ParentchainBlock,
+	SignedSidechainBlock,
+	SimulatedOCallApi,
+	SimulatorStateHandler,
+	SimulatorStateKeyRepository,
+	SimulatorTopPoolAuthor,
+	SimulatedParentchainImporter,
+	TrustedCallSigned,
+	Getter,
+>;
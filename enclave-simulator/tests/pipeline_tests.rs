@@ -0,0 +1,318 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! End-to-end tests of the worker pipeline, running in the enclave simulator.
+
+use codec::{Decode, Encode};
+use integritee_enclave_simulator::{EnclaveSimulator, SimulatorConfig};
+use ita_stf::{default_getter_expiry, Getter, TrustedCall, TrustedCallSigned, TrustedGetter};
+use itp_rpc::{
+	error_codes::{GETTER_EXPIRED, GETTER_NOT_AUTHORIZED},
+	RpcErrorResponse, RpcResponse, RpcReturnValue,
+};
+use itp_stf_primitives::{
+	traits::TrustedCallSigning,
+	types::{AccountId, KeyPair, TrustedOperation},
+};
+use itp_types::{
+	getter_stream::{GetterStreamChunk, GetterStreamRequest},
+	submission_receipt::SignedSubmissionReceipt,
+	Balance, Request, TrustedOperationStatus,
+};
+use itp_utils::{FromHexPrefixed, ToHexPrefixed};
+use its_primitives::traits::{
+	Block as SidechainBlockTrait, BlockData, Header as SidechainHeaderTrait,
+	SignedBlock as SignedBlockTrait,
+};
+use sp_core::{ed25519, Pair};
+use sp_keyring::ed25519::Keyring as Ed25519Keyring;
+use sp_runtime::traits::Header as ParentchainHeaderTrait;
+
+fn init() -> EnclaveSimulator {
+	let _ = env_logger::builder().is_test(true).try_init();
+	EnclaveSimulator::new(SimulatorConfig::default()).unwrap()
+}
+
+fn account(pair: &ed25519::Pair) -> AccountId {
+	pair.public().into()
+}
+
+fn sign(
+	simulator: &EnclaveSimulator,
+	call: TrustedCall,
+	pair: ed25519::Pair,
+	nonce: u32,
+) -> TrustedCallSigned {
+	call.sign(&KeyPair::Ed25519(Box::new(pair)), nonce, &simulator.mr_enclave(), &simulator.shard())
+}
+
+fn shield(simulator: &EnclaveSimulator, who: &AccountId, amount: Balance) {
+	simulator.shield_funds(who, amount).unwrap();
+	simulator.produce_parentchain_block().unwrap();
+	simulator.produce_sidechain_block().unwrap();
+}
+
+#[test]
+fn shielding_funds_credits_the_account() {
+	let simulator = init();
+	let alice = account(&Ed25519Keyring::Alice.pair());
+
+	simulator.shield_funds(&alice, 1_000).unwrap();
+	let parentchain_header = simulator.produce_parentchain_block().unwrap();
+
+	// Parentchain blocks are only imported upon sidechain block production.
+	assert_eq!(simulator.account_data(&alice).unwrap().free, 0);
+
+	simulator.produce_sidechain_block().unwrap();
+
+	assert_eq!(simulator.latest_imported_parentchain_header().unwrap(), parentchain_header);
+	assert_eq!(simulator.account_data(&alice).unwrap().free, 1_000);
+	assert_eq!(simulator.pending_trusted_calls(), 0);
+}
+
+#[test]
+fn balance_transfer_is_executed_in_sidechain_block() {
+	let simulator = init();
+	let alice_pair = Ed25519Keyring::Alice.pair();
+	let alice = account(&alice_pair);
+	let bob = account(&Ed25519Keyring::Bob.pair());
+	shield(&simulator, &alice, 1_000);
+
+	let transfer = sign(
+		&simulator,
+		TrustedCall::balance_transfer(alice.clone(), bob.clone(), 400),
+		alice_pair,
+		0,
+	);
+	let top_hash = simulator.submit_trusted_call(transfer).unwrap();
+	assert_eq!(simulator.pending_trusted_calls(), 1);
+
+	let slot_result = simulator.produce_sidechain_block().unwrap();
+
+	assert_eq!(slot_result.block.block().block_data().signed_top_hashes(), &[top_hash]);
+	assert!(simulator
+		.trusted_operation_status(&top_hash)
+		.contains(&TrustedOperationStatus::InSidechainBlock(slot_result.block.hash())));
+	assert_eq!(simulator.pending_trusted_calls(), 0);
+	assert_eq!(simulator.account_data(&alice).unwrap().free, 600);
+	assert_eq!(simulator.account_data(&bob).unwrap().free, 400);
+	assert_eq!(simulator.account_nonce(&alice).unwrap(), 1);
+}
+
+#[test]
+fn trusted_call_with_invalid_signature_is_rejected_on_submission() {
+	let simulator = init();
+	let alice = account(&Ed25519Keyring::Alice.pair());
+	shield(&simulator, &alice, 1_000);
+
+	let transfer = sign(
+		&simulator,
+		TrustedCall::balance_transfer(alice, account(&Ed25519Keyring::Bob.pair()), 400),
+		Ed25519Keyring::Bob.pair(),
+		0,
+	);
+
+	assert!(simulator.submit_trusted_call(transfer).is_err());
+	assert_eq!(simulator.pending_trusted_calls(), 0);
+}
+
+#[test]
+fn submission_receipt_commits_to_operation_hash() {
+	let simulator = init();
+	let alice_pair = Ed25519Keyring::Alice.pair();
+	let alice = account(&alice_pair);
+	shield(&simulator, &alice, 1_000);
+
+	let transfer = sign(
+		&simulator,
+		TrustedCall::balance_transfer(alice, account(&Ed25519Keyring::Bob.pair()), 400),
+		alice_pair,
+		0,
+	);
+	let operation = TrustedOperation::<TrustedCallSigned, Getter>::direct_call(transfer);
+	let request = Request {
+		shard: simulator.shard(),
+		cyphertext: simulator.encrypt(&operation.encode()).unwrap(),
+	};
+	let rpc_request = format!(
+		r#"{{"jsonrpc":"2.0","method":"author_submitExtrinsicWithReceipt","params":["{}"],"id":1}}"#,
+		request.to_hex()
+	);
+
+	let rpc_response: RpcResponse =
+		serde_json::from_str(&simulator.handle_rpc_request(&rpc_request).unwrap()).unwrap();
+	let return_value = RpcReturnValue::from_hex(&rpc_response.result).unwrap();
+	let receipt = SignedSubmissionReceipt::decode(&mut return_value.value.as_slice()).unwrap();
+
+	assert!(receipt.verify());
+	assert_eq!(
+		receipt.signer,
+		ed25519::Pair::from_seed(&SimulatorConfig::default().authority_seed).public()
+	);
+	assert_eq!(receipt.receipt.shard, simulator.shard());
+	assert_eq!(receipt.receipt.operation_hash, operation.hash());
+
+	let slot_result = simulator.produce_sidechain_block().unwrap();
+	assert_eq!(
+		slot_result.block.block().block_data().signed_top_hashes(),
+		&[receipt.receipt.operation_hash]
+	);
+}
+
+#[test]
+fn trusted_getter_returns_balance() {
+	let simulator = init();
+	let alice_pair = Ed25519Keyring::Alice.pair();
+	let alice = account(&alice_pair);
+	shield(&simulator, &alice, 2_000);
+
+	let getter = Getter::trusted(
+		TrustedGetter::free_balance(alice)
+			.sign(&KeyPair::Ed25519(Box::new(alice_pair)), default_getter_expiry()),
+	);
+	let encoded_balance = simulator.execute_getter(&getter).unwrap().unwrap();
+
+	assert_eq!(Balance::decode(&mut encoded_balance.as_slice()).unwrap(), 2_000);
+}
+
+#[test]
+fn trusted_getter_with_invalid_signature_is_rejected() {
+	let simulator = init();
+	let alice = account(&Ed25519Keyring::Alice.pair());
+
+	let getter = Getter::trusted(
+		TrustedGetter::free_balance(alice)
+			.sign(&KeyPair::Ed25519(Box::new(Ed25519Keyring::Bob.pair())), default_getter_expiry()),
+	);
+
+	assert!(simulator.execute_getter(&getter).is_err());
+}
+
+#[test]
+fn getter_is_served_over_rpc() {
+	let simulator = init();
+	let alice_pair = Ed25519Keyring::Alice.pair();
+	let alice = account(&alice_pair);
+	shield(&simulator, &alice, 3_000);
+
+	let getter = Getter::trusted(
+		TrustedGetter::free_balance(alice)
+			.sign(&KeyPair::Ed25519(Box::new(alice_pair)), default_getter_expiry()),
+	);
+	let request = Request { shard: simulator.shard(), cyphertext: getter.encode() };
+	let rpc_request = format!(
+		r#"{{"jsonrpc":"2.0","method":"state_executeGetter","params":["{}"],"id":1}}"#,
+		request.to_hex()
+	);
+
+	let rpc_response: RpcResponse =
+		serde_json::from_str(&simulator.handle_rpc_request(&rpc_request).unwrap()).unwrap();
+	let return_value = RpcReturnValue::from_hex(&rpc_response.result).unwrap();
+	let encoded_balance =
+		Option::<Vec<u8>>::decode(&mut return_value.value.as_slice()).unwrap().unwrap();
+
+	assert_eq!(Balance::decode(&mut encoded_balance.as_slice()).unwrap(), 3_000);
+}
+
+#[test]
+fn getter_is_streamed_over_rpc() {
+	let simulator = init();
+	let alice_pair = Ed25519Keyring::Alice.pair();
+	let alice = account(&alice_pair);
+	shield(&simulator, &alice, 3_000);
+
+	let getter = Getter::trusted(
+		TrustedGetter::free_balance(alice)
+			.sign(&KeyPair::Ed25519(Box::new(alice_pair)), default_getter_expiry()),
+	);
+	let request = GetterStreamRequest {
+		request: Request { shard: simulator.shard(), cyphertext: getter.encode() },
+		chunk: 0,
+	};
+	let rpc_request = format!(
+		r#"{{"jsonrpc":"2.0","method":"state_executeGetterStream","params":["{}"],"id":1}}"#,
+		request.to_hex()
+	);
+
+	let rpc_response: RpcResponse =
+		serde_json::from_str(&simulator.handle_rpc_request(&rpc_request).unwrap()).unwrap();
+	let return_value = RpcReturnValue::from_hex(&rpc_response.result).unwrap();
+	let chunk = GetterStreamChunk::decode(&mut return_value.value.as_slice()).unwrap();
+	assert!(chunk.is_last());
+	let encoded_balance = Option::<Vec<u8>>::decode(&mut chunk.data.as_slice()).unwrap().unwrap();
+
+	assert_eq!(Balance::decode(&mut encoded_balance.as_slice()).unwrap(), 3_000);
+}
+
+#[test]
+fn unauthorized_getter_over_rpc_returns_typed_error_code() {
+	let simulator = init();
+	let alice = account(&Ed25519Keyring::Alice.pair());
+
+	let getter = Getter::trusted(
+		TrustedGetter::free_balance(alice)
+			.sign(&KeyPair::Ed25519(Box::new(Ed25519Keyring::Bob.pair())), default_getter_expiry()),
+	);
+	let request = Request { shard: simulator.shard(), cyphertext: getter.encode() };
+	let rpc_request = format!(
+		r#"{{"jsonrpc":"2.0","method":"state_executeGetter","params":["{}"],"id":1}}"#,
+		request.to_hex()
+	);
+
+	let rpc_response: RpcErrorResponse =
+		serde_json::from_str(&simulator.handle_rpc_request(&rpc_request).unwrap()).unwrap();
+
+	assert_eq!(rpc_response.error.code, GETTER_NOT_AUTHORIZED);
+}
+
+#[test]
+fn expired_getter_over_rpc_returns_typed_error_code() {
+	let simulator = init();
+	let alice_pair = Ed25519Keyring::Alice.pair();
+	let alice = account(&alice_pair);
+	// Produces a sidechain block, which sets the timestamp of the state.
+	shield(&simulator, &alice, 1_000);
+
+	let getter = Getter::trusted(
+		TrustedGetter::free_balance(alice).sign(&KeyPair::Ed25519(Box::new(alice_pair)), 1),
+	);
+	let request = Request { shard: simulator.shard(), cyphertext: getter.encode() };
+	let rpc_request = format!(
+		r#"{{"jsonrpc":"2.0","method":"state_executeGetter","params":["{}"],"id":1}}"#,
+		request.to_hex()
+	);
+
+	let rpc_response: RpcErrorResponse =
+		serde_json::from_str(&simulator.handle_rpc_request(&rpc_request).unwrap()).unwrap();
+
+	assert_eq!(rpc_response.error.code, GETTER_EXPIRED);
+}
+
+#[test]
+fn sidechain_blocks_are_chained_and_follow_the_parentchain() {
+	let simulator = init();
+
+	let first = simulator.produce_sidechain_block().unwrap().block;
+	let parentchain_header = simulator.produce_parentchain_block().unwrap();
+	let second = simulator.produce_sidechain_block().unwrap().block;
+
+	assert_eq!(first.block().header().block_number(), 1);
+	assert_eq!(second.block().header().block_number(), 2);
+	assert_eq!(second.block().header().parent_hash(), first.hash());
+	assert_eq!(second.block().block_data().layer_one_head(), parentchain_header.hash());
+	assert!(second.verify_signature());
+}
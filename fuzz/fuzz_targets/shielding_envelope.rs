@@ -0,0 +1,51 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Opens shielded request envelopes.
+//!
+//! The first byte selects whether the remaining bytes are taken as the encoded request itself,
+//! or as the plaintext to be encrypted with the shielding key. The latter gets past the RSA
+//! decryption and exercises the decoding of decrypted, but malformed, operations.
+
+#![no_main]
+
+use codec::Encode;
+use integritee_worker_fuzz::open_shielding_envelope;
+use itp_sgx_crypto::ShieldingCryptoEncrypt;
+use itp_test::mock::shielding_crypto_mock::ShieldingCryptoMock;
+use itp_types::{Request, ShardIdentifier};
+use lazy_static::lazy_static;
+use libfuzzer_sys::fuzz_target;
+
+lazy_static! {
+	static ref SHIELDING_KEY: ShieldingCryptoMock = ShieldingCryptoMock::default();
+}
+
+fuzz_target!(|data: &[u8]| {
+	let (mode, payload) = match data.split_first() {
+		Some(split) => split,
+		None => return,
+	};
+
+	if mode % 2 == 0 {
+		let _ = open_shielding_envelope(&*SHIELDING_KEY, payload);
+	} else {
+		let cyphertext = SHIELDING_KEY.encrypt(payload).expect("encryption of any payload works");
+		let request = Request { shard: ShardIdentifier::default(), cyphertext };
+		let _ = open_shielding_envelope(&*SHIELDING_KEY, &request.encode());
+	}
+});
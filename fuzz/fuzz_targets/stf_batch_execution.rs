@@ -0,0 +1,58 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Executes a batch of arbitrary trusted calls against the real STF.
+//!
+//! The calls are decoded from the input, so the fuzzer explores call sequences that a client
+//! could submit. Each call is executed with the correct nonce of its sender, whether the call
+//! itself is valid or not is up to the STF.
+
+#![no_main]
+
+use codec::Decode;
+use integritee_worker_fuzz::{execute_batch, genesis_state, nonce, strategies::accounts};
+use ita_stf::TrustedCall;
+use libfuzzer_sys::fuzz_target;
+
+/// Upper bound of calls per batch, to keep the iterations fast.
+const MAX_CALLS: usize = 32;
+
+fuzz_target!(|data: &[u8]| {
+	let mut input = data;
+	let mut calls = Vec::new();
+	while calls.len() < MAX_CALLS {
+		match TrustedCall::decode(&mut input) {
+			Ok(call) => calls.push(call),
+			Err(_) => break,
+		}
+	}
+	if calls.is_empty() {
+		return
+	}
+
+	let mut state = genesis_state(&accounts());
+
+	for call in calls {
+		let sender = call.sender_account().clone();
+		let nonce_before = nonce(&mut state, &sender);
+		execute_batch(&mut state, vec![call]);
+		// The nonce is incremented no matter if the call succeeds or not, unless the call
+		// emptied the sender account and it was removed.
+		let nonce_after = nonce(&mut state, &sender);
+		assert!(nonce_after == nonce_before + 1 || nonce_after == 0);
+	}
+});
@@ -0,0 +1,32 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Decodes arbitrary bytes as trusted operation, the very first step for any client input.
+
+#![no_main]
+
+use codec::Encode;
+use integritee_worker_fuzz::decode_trusted_operation;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+	if let Some(top) = decode_trusted_operation(data) {
+		// Whatever we accept must survive a round trip, otherwise hashes of the same
+		// operation could differ between client and enclave.
+		assert_eq!(decode_trusted_operation(&top.encode()), Some(top));
+	}
+});
@@ -0,0 +1,118 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Fuzz targets and proptest strategies for the client facing input of the worker.
+//!
+//! Everything a client can send ends up in one of three places: the SCALE decoding of a
+//! `TrustedOperation`, the decryption of the shielded request envelope, or the execution of
+//! trusted calls in the STF. None of them must panic on malformed input, since a panic
+//! aborts the enclave.
+
+pub mod strategies;
+
+use codec::Decode;
+use ita_sgx_runtime::Runtime;
+use ita_stf::{Getter, State, Stf, TrustedCall, TrustedCallSigned};
+use itp_node_api::metadata::{metadata_mocks::NodeMetadataMock, provider::NodeMetadataRepository};
+use itp_sgx_crypto::ShieldingCryptoDecrypt;
+use itp_stf_interface::{
+	sudo_pallet::SudoPalletInterface, system_pallet::SystemPalletAccountInterface, InitState,
+	StateCallInterface,
+};
+use itp_stf_primitives::types::{AccountId, Signature, TrustedOperation};
+use itp_types::{Request, ShardIdentifier};
+use sp_core::{ed25519, Pair};
+use std::sync::Arc;
+
+pub type StfState = Stf<TrustedCallSigned, Getter, State, Runtime>;
+pub type StfTrustedOperation = TrustedOperation<TrustedCallSigned, Getter>;
+
+/// Balance every account of [`genesis_state`] is endowed with.
+pub const ENDOWMENT: u128 = 1_000_000_000_000;
+
+/// Account of the enclave signer in the fuzzed state.
+pub fn enclave_account() -> AccountId {
+	ed25519::Pair::from_seed(b"fuzzing-enclave-signer-account-1").public().into()
+}
+
+/// Decodes a trusted operation the same way the top pool author does.
+pub fn decode_trusted_operation(mut bytes: &[u8]) -> Option<StfTrustedOperation> {
+	StfTrustedOperation::decode(&mut bytes).ok()
+}
+
+/// Opens a shielded request envelope the same way the top pool author does:
+/// decode the request, decrypt the cyphertext and decode the trusted operation.
+pub fn open_shielding_envelope<Key: ShieldingCryptoDecrypt>(
+	key: &Key,
+	mut encoded_request: &[u8],
+) -> Option<(ShardIdentifier, StfTrustedOperation)> {
+	let request = Request::decode(&mut encoded_request).ok()?;
+	let plaintext = key.decrypt(&request.cyphertext).ok()?;
+	decode_trusted_operation(&plaintext).map(|top| (request.shard, top))
+}
+
+/// State with the given accounts endowed with [`ENDOWMENT`].
+pub fn genesis_state(endowed: &[AccountId]) -> State {
+	let mut state = StfState::init_state(enclave_account());
+	let root = StfState::get_root(&mut state);
+
+	let calls: Vec<_> = endowed
+		.iter()
+		.map(|who| TrustedCall::balance_set_balance(root.clone(), who.clone(), ENDOWMENT, 0))
+		.collect();
+	execute_batch(&mut state, calls);
+	state
+}
+
+/// Executes the calls one after the other, each with the correct nonce of its sender.
+///
+/// The signatures of the calls are not checked by the STF (but by the executor before), hence
+/// dummy ones are used. Returns for each call whether it was executed successfully.
+pub fn execute_batch(state: &mut State, calls: Vec<TrustedCall>) -> Vec<bool> {
+	let repo = Arc::new(NodeMetadataRepository::new(NodeMetadataMock::new()));
+
+	calls
+		.into_iter()
+		.map(|call| {
+			let nonce = StfState::get_account_nonce(state, call.sender_account());
+			let signed = TrustedCallSigned::new(
+				call,
+				nonce,
+				Signature::Ed25519(ed25519::Signature([0u8; 64])),
+			);
+			StfState::execute_call(
+				state,
+				signed,
+				&[0u8; 32],
+				&ShardIdentifier::default(),
+				&mut Vec::new(),
+				repo.clone(),
+			)
+			.is_ok()
+		})
+		.collect()
+}
+
+/// Free balance of `who`.
+pub fn free_balance(state: &mut State, who: &AccountId) -> u128 {
+	StfState::get_account_data(state, who).free
+}
+
+/// Nonce of `who`.
+pub fn nonce(state: &mut State, who: &AccountId) -> u32 {
+	StfState::get_account_nonce(state, who)
+}
@@ -0,0 +1,224 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Proptest strategies generating client input.
+//!
+//! Accounts are drawn from a small fixed set, so that generated call sequences actually
+//! interact with each other's balances and nonces.
+
+use crate::StfTrustedOperation;
+use ita_stf::{
+	scheduler::ScheduledAt, unshielding::PayoutRemark, Getter, PublicGetter, TrustedCall,
+	TrustedCallSigned, TrustedGetter, TrustedGetterSigned,
+};
+use itp_stf_primitives::{
+	fees::FeeConfig,
+	session_keys::SessionKey,
+	types::{AccountId, Signature, TrustedOperation},
+};
+use itp_types::{parentchain::ParentchainAssetId, ShardIdentifier, H256};
+use proptest::prelude::*;
+use sp_core::{ed25519, sr25519};
+use sp_keyring::AccountKeyring;
+
+/// The accounts the generated calls are sent from and to.
+pub fn accounts() -> Vec<AccountId> {
+	[AccountKeyring::Alice, AccountKeyring::Bob, AccountKeyring::Charlie, AccountKeyring::Dave]
+		.iter()
+		.map(|k| k.to_account_id())
+		.collect()
+}
+
+pub fn account() -> impl Strategy<Value = AccountId> {
+	prop::sample::select(accounts())
+}
+
+/// Amounts around the interesting edges: zero, the endowment and overflowing values.
+pub fn amount() -> impl Strategy<Value = u128> {
+	prop_oneof![
+		Just(0u128),
+		1u128..=crate::ENDOWMENT,
+		Just(crate::ENDOWMENT),
+		Just(u128::MAX),
+		any::<u128>(),
+	]
+}
+
+pub fn shard() -> impl Strategy<Value = ShardIdentifier> {
+	any::<[u8; 32]>().prop_map(ShardIdentifier::from)
+}
+
+pub fn asset_id() -> impl Strategy<Value = ParentchainAssetId> {
+	prop_oneof![
+		any::<u32>().prop_map(ParentchainAssetId::Assets),
+		any::<u32>().prop_map(ParentchainAssetId::Tokens),
+	]
+}
+
+pub fn scheduled_at() -> impl Strategy<Value = ScheduledAt> {
+	prop_oneof![
+		any::<u64>().prop_map(ScheduledAt::SidechainBlock),
+		any::<u64>().prop_map(ScheduledAt::Timestamp),
+	]
+}
+
+/// Remark hashes of the first payouts and batches, and random ones.
+pub fn payout_remark() -> impl Strategy<Value = H256> {
+	prop_oneof![
+		(0u64..4).prop_map(|id| PayoutRemark::Payout(id).hash()),
+		(0u32..2).prop_map(|nonce| PayoutRemark::Batch(nonce).hash()),
+		any::<[u8; 32]>().prop_map(H256::from),
+	]
+}
+
+fn signature_bytes() -> impl Strategy<Value = [u8; 64]> {
+	(any::<[u8; 32]>(), any::<[u8; 32]>()).prop_map(|(head, tail)| {
+		let mut bytes = [0u8; 64];
+		bytes[..32].copy_from_slice(&head);
+		bytes[32..].copy_from_slice(&tail);
+		bytes
+	})
+}
+
+/// Random, hence invalid, signatures. The STF does not check them.
+pub fn signature() -> impl Strategy<Value = Signature> {
+	prop_oneof![
+		signature_bytes().prop_map(|s| Signature::Ed25519(ed25519::Signature(s))),
+		signature_bytes().prop_map(|s| Signature::Sr25519(sr25519::Signature(s))),
+	]
+}
+
+/// Balance transfers between the [`accounts`].
+pub fn balance_transfer() -> impl Strategy<Value = TrustedCall> {
+	(account(), account(), amount())
+		.prop_map(|(from, to, value)| TrustedCall::balance_transfer(from, to, value))
+}
+
+/// Any trusted call a client can submit, without nesting.
+pub fn flat_trusted_call() -> impl Strategy<Value = TrustedCall> {
+	prop_oneof![
+		account().prop_map(TrustedCall::noop),
+		(account(), account(), amount(), amount()).prop_map(|(root, who, free, reserved)| {
+			TrustedCall::balance_set_balance(root, who, free, reserved)
+		}),
+		balance_transfer(),
+		(account(), account(), amount(), shard()).prop_map(|(from, to, value, shard)| {
+			TrustedCall::balance_unshield(from, to, value, shard)
+		}),
+		(account(), account(), amount())
+			.prop_map(|(root, to, value)| TrustedCall::balance_shield(root, to, value)),
+		account().prop_map(TrustedCall::reap_accounts),
+		(account(), account(), amount())
+			.prop_map(|(from, to, value)| TrustedCall::balance_unshield_batched(from, to, value)),
+		account().prop_map(TrustedCall::payout_unshield_batch),
+		(account(), prop::collection::vec(payout_remark(), 0..4))
+			.prop_map(|(enclave, remarks)| TrustedCall::confirm_unshield_payouts(enclave, remarks)),
+		(account(), account(), asset_id(), amount()).prop_map(|(from, to, asset, value)| {
+			TrustedCall::assets_shield(from, to, asset, value)
+		}),
+		(account(), account(), asset_id(), amount()).prop_map(|(from, to, asset, value)| {
+			TrustedCall::assets_transfer(from, to, asset, value)
+		}),
+		(account(), account(), asset_id(), amount()).prop_map(|(from, to, asset, value)| {
+			TrustedCall::assets_unshield(from, to, asset, value)
+		}),
+		(account(), account(), session_key()).prop_map(|(owner, delegate, session_key)| {
+			TrustedCall::session_key_register(owner, delegate, session_key)
+		}),
+		(account(), account())
+			.prop_map(|(owner, delegate)| TrustedCall::session_key_revoke(owner, delegate)),
+		(account(), any::<u128>(), any::<u128>(), any::<u128>()).prop_map(
+			|(root, base_fee, fee_per_byte, fee_per_micros)| {
+				let config =
+					FeeConfig { base_fee, fee_per_byte, fee_per_micros, ..Default::default() };
+				TrustedCall::fees_set_config(root, config)
+			}
+		),
+	]
+}
+
+/// Session keys scoped to calls that are generated, or to arbitrary names.
+pub fn session_key() -> impl Strategy<Value = SessionKey> {
+	let call_name = prop_oneof![
+		Just(b"balance_transfer".to_vec()),
+		Just(b"noop".to_vec()),
+		Just(b"session_call".to_vec()),
+		proptest::collection::vec(any::<u8>(), 0..16),
+	];
+	(any::<u64>(), proptest::collection::vec(call_name, 0..4))
+		.prop_map(|(valid_until, scope)| SessionKey::new(valid_until, scope))
+}
+
+/// Any trusted call a client can submit, including scheduled, session key and sponsored ones.
+pub fn trusted_call() -> impl Strategy<Value = TrustedCall> {
+	prop_oneof![
+		4 => flat_trusted_call(),
+		1 => (account(), scheduled_at(), flat_trusted_call()).prop_map(|(origin, when, call)| {
+			TrustedCall::schedule_call(origin, when, Box::new(call))
+		}),
+		1 => (account(), flat_trusted_call())
+			.prop_map(|(delegate, call)| TrustedCall::session_call(delegate, Box::new(call))),
+		1 => (account(), flat_trusted_call(), any::<u32>(), signature()).prop_map(
+			|(sponsor, call, nonce, signature)| {
+				let signed_call = TrustedCallSigned::new(call, nonce, signature);
+				TrustedCall::sponsored_call(sponsor, Box::new(signed_call))
+			}
+		),
+	]
+}
+
+pub fn trusted_call_signed() -> impl Strategy<Value = TrustedCallSigned> {
+	(trusted_call(), any::<u32>(), signature())
+		.prop_map(|(call, nonce, signature)| TrustedCallSigned::new(call, nonce, signature))
+}
+
+pub fn trusted_getter() -> impl Strategy<Value = TrustedGetter> {
+	prop_oneof![
+		account().prop_map(TrustedGetter::free_balance),
+		account().prop_map(TrustedGetter::reserved_balance),
+		account().prop_map(TrustedGetter::nonce),
+		account().prop_map(TrustedGetter::rent_status),
+		(account(), asset_id()).prop_map(|(who, asset)| TrustedGetter::asset_balance(who, asset)),
+		account().prop_map(TrustedGetter::confidential_events),
+		(account(), proptest::collection::vec(any::<u8>(), 0..16))
+			.prop_map(|(who, key)| TrustedGetter::account_data_export(who, key)),
+	]
+}
+
+pub fn getter() -> impl Strategy<Value = Getter> {
+	prop_oneof![
+		Just(Getter::public(PublicGetter::some_value)),
+		Just(Getter::public(PublicGetter::fee_config)),
+		Just(Getter::public(PublicGetter::reserves_report)),
+		trusted_call().prop_map(|call| Getter::public(PublicGetter::fee_estimate(call))),
+		(trusted_getter(), any::<u64>(), signature()).prop_map(
+			|(getter, valid_until, signature)| Getter::trusted(TrustedGetterSigned::new(
+				getter,
+				valid_until,
+				signature
+			))
+		),
+	]
+}
+
+pub fn trusted_operation() -> impl Strategy<Value = StfTrustedOperation> {
+	prop_oneof![
+		trusted_call_signed().prop_map(TrustedOperation::indirect_call),
+		trusted_call_signed().prop_map(TrustedOperation::direct_call),
+		getter().prop_map(TrustedOperation::get),
+	]
+}
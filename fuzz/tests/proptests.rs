@@ -0,0 +1,106 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use codec::Encode;
+use integritee_worker_fuzz::{
+	decode_trusted_operation, execute_batch, free_balance, genesis_state, nonce,
+	open_shielding_envelope, strategies::*,
+};
+use itp_sgx_crypto::ShieldingCryptoEncrypt;
+use itp_test::mock::shielding_crypto_mock::ShieldingCryptoMock;
+use itp_types::Request;
+use lazy_static::lazy_static;
+use proptest::prelude::*;
+
+lazy_static! {
+	static ref SHIELDING_KEY: ShieldingCryptoMock = ShieldingCryptoMock::default();
+}
+
+proptest! {
+	#[test]
+	fn trusted_operation_encoding_round_trips(top in trusted_operation()) {
+		prop_assert_eq!(decode_trusted_operation(&top.encode()), Some(top));
+	}
+
+	#[test]
+	fn truncated_trusted_operation_is_rejected(top in trusted_operation(), cut in any::<prop::sample::Index>()) {
+		let encoded = top.encode();
+		let truncated = &encoded[..cut.index(encoded.len())];
+
+		prop_assert_eq!(decode_trusted_operation(truncated), None);
+	}
+
+	#[test]
+	fn decoding_arbitrary_bytes_does_not_panic(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
+		let _ = decode_trusted_operation(&bytes);
+	}
+}
+
+proptest! {
+	// Encryption and execution are comparatively slow.
+	#![proptest_config(ProptestConfig::with_cases(32))]
+
+	#[test]
+	fn shielding_envelope_opens_encrypted_operation(top in trusted_operation(), shard in shard()) {
+		let cyphertext = SHIELDING_KEY.encrypt(&top.encode()).unwrap();
+		let request = Request { shard, cyphertext };
+
+		prop_assert_eq!(open_shielding_envelope(&*SHIELDING_KEY, &request.encode()), Some((shard, top)));
+	}
+
+	#[test]
+	fn tampered_shielding_envelope_does_not_panic(
+		top in trusted_operation(),
+		shard in shard(),
+		position in any::<prop::sample::Index>(),
+		flip in 1u8..,
+	) {
+		let mut cyphertext = SHIELDING_KEY.encrypt(&top.encode()).unwrap();
+		let i = position.index(cyphertext.len());
+		cyphertext[i] ^= flip;
+		let request = Request { shard, cyphertext };
+
+		let _ = open_shielding_envelope(&*SHIELDING_KEY, &request.encode());
+	}
+
+	#[test]
+	fn executing_any_calls_increments_the_sender_nonce(calls in prop::collection::vec(trusted_call(), 1..16)) {
+		let mut state = genesis_state(&accounts());
+
+		for call in calls {
+			let sender = call.sender_account().clone();
+			let nonce_before = nonce(&mut state, &sender);
+			execute_batch(&mut state, vec![call]);
+			let nonce_after = nonce(&mut state, &sender);
+
+			// Unless the call emptied the sender account and it was removed.
+			prop_assert!(nonce_after == nonce_before + 1 || nonce_after == 0);
+		}
+	}
+
+	#[test]
+	fn balance_transfers_do_not_create_funds(calls in prop::collection::vec(balance_transfer(), 1..16)) {
+		let accounts = accounts();
+		let mut state = genesis_state(&accounts);
+		let total_before: u128 = accounts.iter().map(|a| free_balance(&mut state, a)).sum();
+
+		execute_batch(&mut state, calls);
+
+		let total_after: u128 = accounts.iter().map(|a| free_balance(&mut state, a)).sum();
+		prop_assert!(total_after <= total_before);
+	}
+}
@@ -0,0 +1,196 @@
+#!/usr/bin/env python3
+"""
+Launch a local network of one integritee-node and N validateers, and run a shielding smoke test.
+
+Example usage: `./local-setup/launch_local_net.py --validateers 3`
+
+The ports of the node and the workers are wired up automatically, the generated config is
+written to `./log/local-net.json` and can be run again with `./local-setup/launch.py`.
+
+The first worker registers itself on the parentchain and creates the shard (the mrenclave),
+the others register and request the shielding key and state from it. Once all of them are
+initialized, the shielding demo runs against the first and the last worker. Afterwards the
+network keeps running until terminated with `Ctrl + c`, unless `--exit` is given.
+
+The node and workers logs are piped to `./log/node1.log`, `./log/worker1.log` etc.
+
+"""
+import argparse
+import json
+import signal
+import socket
+import subprocess
+import sys
+import urllib.request
+from time import sleep, time
+
+from launch import run_node, run_worker, log_dir
+from py.helpers import GracefulKiller
+
+NODE_WS_PORT = 9944
+NODE_P2P_PORT = 30390
+NODE_RPC_PORT = 9933
+
+# Ports of worker i are offset by i * WORKER_PORT_OFFSET, apart from the `is_initialized` port.
+WORKER_PORT_OFFSET = 1000
+TRUSTED_WORKER_PORT = 2000
+UNTRUSTED_WORKER_PORT = 2001
+MU_RA_PORT = 3490
+IS_INITIALIZED_PORT = 4545
+
+STARTUP_TIMEOUT_SECS = 600
+
+
+def node_config(node_bin: str):
+    return {
+        "bin": node_bin,
+        "flags": [
+            "--tmp",
+            "--dev",
+            "-lruntime=info",
+            "-lteerex=debug",
+            "--ws-port",
+            str(NODE_WS_PORT),
+            "--port",
+            str(NODE_P2P_PORT),
+            "--rpc-port",
+            str(NODE_RPC_PORT),
+            "--ws-external",
+            "--rpc-external"
+        ]
+    }
+
+
+def worker_config(i: int, source: str):
+    """ Config of the i-th worker, counting from 0. The first one is the primary validateer. """
+    offset = i * WORKER_PORT_OFFSET
+    subcommand_flags = ["--skip-ra", "--dev"]
+    if i > 0:
+        subcommand_flags.append("--request-state")
+
+    return {
+        "source": source,
+        "flags": [
+            "--clean-reset",
+            "-P",
+            str(TRUSTED_WORKER_PORT + offset),
+            "-p",
+            str(NODE_WS_PORT),
+            "-r",
+            str(MU_RA_PORT + offset),
+            "-w",
+            str(UNTRUSTED_WORKER_PORT + offset),
+            "-h",
+            str(IS_INITIALIZED_PORT + i),
+            "--ws-external",
+            "--data-dir",
+            f"/tmp/data-dir-local-net-{i + 1}"
+        ],
+        "subcommand_flags": subcommand_flags
+    }
+
+
+def local_net_config(validateers: int, node_bin: str, source: str):
+    return {
+        "nodes": [node_config(node_bin)],
+        "workers": [worker_config(i, source) for i in range(validateers)]
+    }
+
+
+def wait_until(condition, what: str, timeout: int = STARTUP_TIMEOUT_SECS):
+    print(f'Waiting for {what}')
+    deadline = time() + timeout
+    while not condition():
+        if time() > deadline:
+            raise TimeoutError(f'{what} not ready after {timeout}s, check the logs in ./{log_dir}')
+        sleep(2)
+    print(f'{what} is ready')
+
+
+def port_is_open(port: int):
+    with socket.socket(socket.AF_INET, socket.SOCK_STREAM) as s:
+        return s.connect_ex(('127.0.0.1', port)) == 0
+
+
+def worker_is_initialized(i: int):
+    try:
+        with urllib.request.urlopen(f'http://127.0.0.1:{IS_INITIALIZED_PORT + i}/is_initialized') as response:
+            return response.status == 200
+    except OSError:
+        return False
+
+
+def run_smoke_test(validateers: int, client_bin: str):
+    """ Shields and unshields funds through the first worker, and checks the balances on the last one. """
+    first_port = TRUSTED_WORKER_PORT
+    last_port = TRUSTED_WORKER_PORT + (validateers - 1) * WORKER_PORT_OFFSET
+
+    if validateers > 1:
+        cmd = ['./demo_shielding_unshielding_multiworker.sh', '-p', str(NODE_WS_PORT),
+               '-A', str(first_port), '-B', str(last_port), '-C', client_bin]
+    else:
+        cmd = ['./demo_shielding_unshielding.sh', '-p', str(NODE_WS_PORT),
+               '-P', str(first_port), '-C', client_bin, '-t', 'first']
+
+    print(f'Running smoke test: {cmd}')
+    return subprocess.run(cmd, cwd='cli').returncode == 0
+
+
+def main(processes, args):
+    config = local_net_config(args.validateers, args.node_bin, args.source)
+    with open(f'{log_dir}/local-net.json', 'w') as config_file:
+        json.dump(config, config_file, indent=2)
+
+    try:
+        print('Starting integritee-node-process in background')
+        processes.append(run_node(config["nodes"][0], 1))
+        wait_until(lambda: port_is_open(NODE_WS_PORT), 'integritee-node')
+
+        for i, w_conf in enumerate(config["workers"]):
+            processes.append(run_worker(w_conf, i + 1))
+            # The primary has to be registered before the others can request the shielding key and state,
+            # waiting for each one also prevents nonce clashes when bootstrapping the enclave accounts.
+            wait_until(lambda: worker_is_initialized(i), f'worker {i + 1}')
+    except TimeoutError as e:
+        print(e)
+        return 1
+
+    print(f'Local network with {args.validateers} validateer(s) is up, config in ./{log_dir}/local-net.json')
+
+    if not args.skip_smoke_test:
+        if not run_smoke_test(args.validateers, args.client_bin):
+            print('Smoke test failed')
+            return 1
+        print('Smoke test passed')
+
+    if args.exit:
+        return 0
+
+    # keep script alive until terminated
+    signal.pause()
+
+
+if __name__ == '__main__':
+    parser = argparse.ArgumentParser(description='Run a local network of a node and N validateers')
+    parser.add_argument('--validateers', type=int, default=2, help='Number of workers to launch')
+    parser.add_argument('--node-bin', type=str, default='../integritee-node/target/release/integritee-node',
+                        help='Path to the integritee-node binary')
+    parser.add_argument('--source', type=str, default='bin',
+                        help='Directory containing the worker binaries, built with `SGX_MODE=SW make`')
+    parser.add_argument('--client-bin', type=str, default='../bin/integritee-cli',
+                        help='Path to the integritee-cli binary, relative to the `cli` directory')
+    parser.add_argument('--skip-smoke-test', action='store_true', help='Do not run the shielding smoke test')
+    parser.add_argument('--exit', action='store_true', help='Stop the network after the smoke test')
+    args = parser.parse_args()
+
+    if args.validateers < 1:
+        parser.error('at least one validateer is needed')
+
+    process_list = []
+    killer = GracefulKiller(process_list)
+    exit_code = main(process_list, args)
+
+    print("Cleaning up processes.")
+    for p in process_list:
+        p.kill()
+    sys.exit(exit_code)
@@ -17,7 +17,17 @@
 
 use clap::ArgMatches;
 use itc_rest_client::rest_client::Url;
-use itp_settings::teeracle::{DEFAULT_MARKET_DATA_UPDATE_INTERVAL, ONE_DAY, THIRTY_MINUTES};
+use itp_memory_accounting::{MemoryLimits, Subsystem};
+use itp_settings::{
+	files::SIDECHAIN_PURGE_LIMIT,
+	teeracle::{
+		DEFAULT_MARKET_DATA_UPDATE_INTERVAL, DEFAULT_ORACLE_FEED, DEFAULT_ORACLE_MIN_SOURCES,
+		DEFAULT_ORACLE_SOURCES, ONE_DAY, THIRTY_MINUTES,
+	},
+	worker::ENCLAVE_MEMORY_LIMIT_MB,
+	worker_directory::DEFAULT_PUBLISH_INTERVAL,
+};
+use itp_types::oracle::OracleFeedConfig;
 use parse_duration::parse;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -273,8 +283,24 @@ pub struct RunConfig {
 	teeracle_update_interval: Option<Duration>,
 	/// Optional teeracle reregistration interval
 	reregister_teeracle_interval: Option<Duration>,
+	/// Exchange rates the teeracle publishes, as (crypto, fiat) currency pairs.
+	oracle_feeds: Option<Vec<(String, String)>>,
+	/// Data sources the teeracle publishes the median of.
+	oracle_sources: Option<Vec<String>>,
+	/// Minimal number of data sources that must respond for a value to be published.
+	oracle_min_sources: Option<u32>,
 	/// Marblerun's Prometheus endpoint base URL
 	marblerun_base_url: Option<String>,
+	/// Number of sidechain blocks per shard kept in the block storage, older ones are pruned.
+	sidechain_block_retention: Option<u64>,
+	/// Interval in which state snapshots are published on IPFS, disabled if not set.
+	state_snapshot_interval: Option<Duration>,
+	/// Interval in which the worker directory record is published, zero disables it.
+	worker_record_interval: Option<Duration>,
+	/// Enclave heap usage in MiB above which new trusted operations and getters are rejected.
+	enclave_memory_limit: Option<u64>,
+	/// Heap usage of the top pool in MiB above which new trusted operations are rejected.
+	top_pool_memory_limit: Option<u64>,
 }
 
 impl RunConfig {
@@ -307,12 +333,70 @@ impl RunConfig {
 		self.reregister_teeracle_interval.unwrap_or(ONE_DAY - THIRTY_MINUTES)
 	}
 
+	/// The exchange rates the teeracle publishes, each as median over the configured sources.
+	pub fn oracle_feeds(&self) -> Vec<OracleFeedConfig> {
+		let sources = self
+			.oracle_sources
+			.clone()
+			.unwrap_or_else(|| DEFAULT_ORACLE_SOURCES.iter().map(|s| s.to_string()).collect());
+		let min_sources = self.oracle_min_sources.unwrap_or(DEFAULT_ORACLE_MIN_SOURCES);
+
+		self.oracle_feeds
+			.clone()
+			.unwrap_or_else(|| vec![parse_trading_pair(DEFAULT_ORACLE_FEED)])
+			.into_iter()
+			.map(|(crypto, fiat)| OracleFeedConfig::new(crypto, fiat, sources.clone(), min_sources))
+			.collect()
+	}
+
 	pub fn marblerun_base_url(&self) -> &str {
 		// This conflicts with the default port of a substrate node, but it is indeed the
 		// default port of marblerun too:
 		// https://github.com/edgelesssys/marblerun/blob/master/docs/docs/workflows/monitoring.md?plain=1#L26
 		self.marblerun_base_url.as_deref().unwrap_or("http://localhost:9944")
 	}
+
+	pub fn sidechain_block_retention(&self) -> u64 {
+		self.sidechain_block_retention.unwrap_or(SIDECHAIN_PURGE_LIMIT)
+	}
+
+	pub fn state_snapshot_interval(&self) -> Option<Duration> {
+		self.state_snapshot_interval
+	}
+
+	/// Interval in which the worker directory record is published, `None` if disabled.
+	pub fn worker_record_interval(&self) -> Option<Duration> {
+		match self.worker_record_interval {
+			Some(interval) if interval.is_zero() => None,
+			Some(interval) => Some(interval),
+			None => Some(DEFAULT_PUBLISH_INTERVAL),
+		}
+	}
+
+	/// Heap limits of the enclave in bytes.
+	pub fn memory_limits(&self) -> MemoryLimits {
+		let total = self.enclave_memory_limit.unwrap_or(ENCLAVE_MEMORY_LIMIT_MB);
+		MemoryLimits {
+			total: Some(mebibytes(total)),
+			subsystems: self
+				.top_pool_memory_limit
+				.map(|l| vec![(Subsystem::TopPool, mebibytes(l))])
+				.unwrap_or_default(),
+		}
+	}
+}
+
+fn mebibytes(value: u64) -> u64 {
+	value.saturating_mul(1024 * 1024)
+}
+
+/// Parses a trading pair given as `<crypto>/<fiat>`, e.g. `TEER/USD`.
+fn parse_trading_pair(pair: &str) -> (String, String) {
+	match pair.split_once('/') {
+		Some((crypto, fiat)) if !crypto.is_empty() && !fiat.is_empty() =>
+			(crypto.to_string(), fiat.to_string()),
+		_ => panic!("oracle-feed parsing error: expected <crypto>/<fiat>, got {}", pair),
+	}
 }
 
 impl From<&ArgMatches<'_>> for RunConfig {
@@ -328,12 +412,45 @@ impl From<&ArgMatches<'_>> for RunConfig {
 			parse(i).unwrap_or_else(|e| panic!("teeracle-interval parsing error {:?}", e))
 		});
 
+		let oracle_feeds =
+			m.values_of("oracle-feed").map(|feeds| feeds.map(parse_trading_pair).collect());
+		let oracle_sources = m
+			.value_of("oracle-sources")
+			.map(|s| s.split(',').map(|source| source.trim().to_string()).collect());
+		let oracle_min_sources = m.value_of("oracle-min-sources").map(|n| {
+			n.parse::<u32>()
+				.unwrap_or_else(|e| panic!("oracle-min-sources parsing error {:?}", e))
+		});
+
 		let marblerun_base_url = m.value_of("marblerun-url").map(|i| {
 			Url::parse(i)
 				.unwrap_or_else(|e| panic!("marblerun-url parsing error: {:?}", e))
 				.to_string()
 		});
 
+		let sidechain_block_retention = m.value_of("sidechain-block-retention").map(|r| {
+			r.parse::<u64>()
+				.unwrap_or_else(|e| panic!("sidechain-block-retention parsing error {:?}", e))
+		});
+
+		let state_snapshot_interval = m.value_of("state-snapshot-interval").map(|i| {
+			parse(i).unwrap_or_else(|e| panic!("state-snapshot-interval parsing error {:?}", e))
+		});
+
+		let worker_record_interval = m.value_of("worker-record-interval").map(|i| {
+			parse(i).unwrap_or_else(|e| panic!("worker-record-interval parsing error {:?}", e))
+		});
+
+		let enclave_memory_limit = m.value_of("enclave-memory-limit").map(|l| {
+			l.parse::<u64>()
+				.unwrap_or_else(|e| panic!("enclave-memory-limit parsing error {:?}", e))
+		});
+
+		let top_pool_memory_limit = m.value_of("top-pool-memory-limit").map(|l| {
+			l.parse::<u64>()
+				.unwrap_or_else(|e| panic!("top-pool-memory-limit parsing error {:?}", e))
+		});
+
 		Self {
 			skip_ra,
 			dev,
@@ -341,7 +458,15 @@ impl From<&ArgMatches<'_>> for RunConfig {
 			shard,
 			teeracle_update_interval,
 			reregister_teeracle_interval,
+			oracle_feeds,
+			oracle_sources,
+			oracle_min_sources,
 			marblerun_base_url,
+			sidechain_block_retention,
+			state_snapshot_interval,
+			worker_record_interval,
+			enclave_memory_limit,
+			top_pool_memory_limit,
 		}
 	}
 }
@@ -469,6 +594,57 @@ mod test {
 		assert_eq!(run_config.skip_ra, false);
 		assert!(run_config.shard.is_none());
 		assert!(run_config.teeracle_update_interval.is_none());
+		assert_eq!(run_config.sidechain_block_retention(), SIDECHAIN_PURGE_LIMIT);
+		assert!(run_config.state_snapshot_interval().is_none());
+		assert_eq!(run_config.worker_record_interval(), Some(DEFAULT_PUBLISH_INTERVAL));
+		assert_eq!(
+			run_config.oracle_feeds(),
+			vec![OracleFeedConfig::new(
+				"TEER".into(),
+				"USD".into(),
+				vec!["coin_gecko".into(), "coin_market_cap".into()],
+				1
+			)]
+		);
+		assert_eq!(
+			run_config.memory_limits(),
+			MemoryLimits { total: Some(ENCLAVE_MEMORY_LIMIT_MB * 1024 * 1024), subsystems: vec![] }
+		);
+	}
+
+	#[test]
+	fn oracle_feeds_parsing_works() {
+		let mut args = ArgMatches::default();
+		args.args = HashMap::from([
+			("oracle-feed", Default::default()),
+			("oracle-sources", Default::default()),
+			("oracle-min-sources", Default::default()),
+		]);
+		args.args.get_mut("oracle-feed").unwrap().vals = vec!["DOT/USD".into(), "KSM/CHF".into()];
+		args.args.get_mut("oracle-sources").unwrap().vals =
+			vec!["coin_gecko, coin_market_cap".into()];
+		args.args.get_mut("oracle-min-sources").unwrap().vals = vec!["2".into()];
+
+		let feeds = RunConfig::from(&args).oracle_feeds();
+
+		let sources: Vec<String> = vec!["coin_gecko".into(), "coin_market_cap".into()];
+		assert_eq!(
+			feeds,
+			vec![
+				OracleFeedConfig::new("DOT".into(), "USD".into(), sources.clone(), 2),
+				OracleFeedConfig::new("KSM".into(), "CHF".into(), sources, 2),
+			]
+		);
+	}
+
+	#[test]
+	fn oracle_feed_parsing_panics_if_format_is_invalid() {
+		let mut args = ArgMatches::default();
+		args.args = HashMap::from([("oracle-feed", Default::default())]);
+		args.args.get_mut("oracle-feed").unwrap().vals = vec!["TEERUSD".into()];
+
+		let result = std::panic::catch_unwind(|| RunConfig::from(&args));
+		assert!(result.is_err());
 	}
 
 	#[test]
@@ -482,10 +658,20 @@ mod test {
 			("skip-ra", Default::default()),
 			("shard", Default::default()),
 			("teeracle-interval", Default::default()),
+			("sidechain-block-retention", Default::default()),
+			("state-snapshot-interval", Default::default()),
+			("worker-record-interval", Default::default()),
+			("enclave-memory-limit", Default::default()),
+			("top-pool-memory-limit", Default::default()),
 		]);
 		// Workaround because MatchedArg is private.
 		args.args.get_mut("shard").unwrap().vals = vec![shard_identifier.into()];
 		args.args.get_mut("teeracle-interval").unwrap().vals = vec!["42s".into()];
+		args.args.get_mut("sidechain-block-retention").unwrap().vals = vec!["5000".into()];
+		args.args.get_mut("state-snapshot-interval").unwrap().vals = vec!["1h".into()];
+		args.args.get_mut("worker-record-interval").unwrap().vals = vec!["5m".into()];
+		args.args.get_mut("enclave-memory-limit").unwrap().vals = vec!["256".into()];
+		args.args.get_mut("top-pool-memory-limit").unwrap().vals = vec!["64".into()];
 
 		let run_config = RunConfig::from(&args);
 
@@ -494,6 +680,25 @@ mod test {
 		assert_eq!(run_config.skip_ra, true);
 		assert_eq!(run_config.shard.unwrap(), shard_identifier.to_string());
 		assert_eq!(run_config.teeracle_update_interval.unwrap(), Duration::from_secs(42));
+		assert_eq!(run_config.sidechain_block_retention(), 5000);
+		assert_eq!(run_config.state_snapshot_interval(), Some(Duration::from_secs(3600)));
+		assert_eq!(run_config.worker_record_interval(), Some(Duration::from_secs(300)));
+		assert_eq!(
+			run_config.memory_limits(),
+			MemoryLimits {
+				total: Some(256 * 1024 * 1024),
+				subsystems: vec![(Subsystem::TopPool, 64 * 1024 * 1024)]
+			}
+		);
+	}
+
+	#[test]
+	fn worker_record_publication_can_be_disabled() {
+		let mut args = ArgMatches::default();
+		args.args = HashMap::from([("worker-record-interval", Default::default())]);
+		args.args.get_mut("worker-record-interval").unwrap().vals = vec!["0s".into()];
+
+		assert!(RunConfig::from(&args).worker_record_interval().is_none());
 	}
 
 	#[test]
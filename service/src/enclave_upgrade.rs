@@ -0,0 +1,95 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Hand over of the keys to the new enclave of an enclave upgrade announced on the parentchain.
+//!
+//! The authorship itself is handed over inside the enclaves, based on the shard config
+//! verified by their light clients. The host only relays the local attestation messages
+//! between the old and the new enclave, which have to run on the same platform.
+
+use crate::error::{Error, ServiceResult as Result};
+use itc_rpc_client::direct_client::DirectApi;
+use itp_enclave_api::enclave_base::EnclaveBase;
+use itp_node_api::api_client::PalletTeerexApi;
+use itp_types::{enclave_upgrade::UpgradableShardConfig, EnclaveFingerprint, ShardIdentifier};
+
+/// Whether our enclave is the new enclave of an upgrade of the shard that is pending on the
+/// parentchain, and has to obtain its keys from the old one.
+pub(crate) fn is_pending_upgrade_target<NodeApi: PalletTeerexApi, E: EnclaveBase>(
+	node_api: &NodeApi,
+	shard: &ShardIdentifier,
+	enclave_api: &E,
+) -> Result<bool> {
+	let config = node_api.shard_config(shard, None)?;
+	Ok(is_new_enclave_of_upgrade(config.as_ref(), &enclave_api.get_fingerprint()?))
+}
+
+/// Relays the key handover between the old enclave, reachable via `old_worker_api`, and ours.
+pub(crate) fn hand_over_keys_from_old_enclave<E: EnclaveBase, Api: DirectApi>(
+	enclave_api: &E,
+	old_worker_api: &Api,
+	shard: &ShardIdentifier,
+) -> Result<()> {
+	let target_info = old_worker_api.get_enclave_target_info()?;
+	let request = enclave_api.request_key_handover(shard, &target_info)?;
+	let response = old_worker_api.hand_over_keys(&request)?;
+	enclave_api.import_handed_over_keys(shard, &response).map_err(Error::from)
+}
+
+fn is_new_enclave_of_upgrade(
+	config: Option<&UpgradableShardConfig>,
+	fingerprint: &EnclaveFingerprint,
+) -> bool {
+	config
+		.and_then(|c| c.pending_enclave_upgrade())
+		.map(|upgrade| &upgrade.new_fingerprint == fingerprint)
+		.unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use itp_types::enclave_upgrade::ShardConfig;
+
+	fn config(active: u8, pending: Option<(u8, u32)>) -> UpgradableShardConfig {
+		let shard_config = |f: u8| ShardConfig {
+			enclave_fingerprint: [f; 32].into(),
+			max_instances: None,
+			authorities: None,
+			maintenance_mode: false,
+		};
+		UpgradableShardConfig {
+			active_config: shard_config(active),
+			pending_upgrade: pending.map(|(f, _)| shard_config(f)),
+			upgrade_at: pending.map(|(_, at)| at),
+		}
+	}
+
+	#[test]
+	fn only_new_enclave_of_pending_upgrade_requests_key_handover() {
+		let config = config(1, Some((2, 10)));
+
+		assert!(is_new_enclave_of_upgrade(Some(&config), &[2u8; 32].into()));
+		assert!(!is_new_enclave_of_upgrade(Some(&config), &[1u8; 32].into()));
+	}
+
+	#[test]
+	fn no_key_handover_without_pending_upgrade() {
+		assert!(!is_new_enclave_of_upgrade(None, &[2u8; 32].into()));
+		assert!(!is_new_enclave_of_upgrade(Some(&config(2, None)), &[2u8; 32].into()));
+		assert!(!is_new_enclave_of_upgrade(Some(&config(2, Some((2, 10)))), &[2u8; 32].into()));
+	}
+}
@@ -21,6 +21,7 @@
 mod account_funding;
 mod config;
 mod enclave;
+mod enclave_upgrade;
 mod error;
 mod globals;
 mod initialized_service;
@@ -28,6 +29,7 @@ mod ocall_bridge;
 mod parentchain_handler;
 mod prometheus_metrics;
 mod setup;
+mod shard_lifecycle;
 mod sidechain_setup;
 mod sync_block_broadcaster;
 mod sync_state;
@@ -35,6 +37,7 @@ mod sync_state;
 mod teeracle;
 mod tests;
 mod utils;
+mod watchdog;
 mod worker;
 mod worker_peers_updater;
 
@@ -18,14 +18,18 @@ use crate::{
 	},
 	ocall_bridge::{
 		bridge_api::Bridge as OCallBridge, component_factory::OCallBridgeComponentFactory,
+		ipfs_ocall::read_from_ipfs,
 	},
 	parentchain_handler::{HandleParentchain, ParentchainHandler},
 	prometheus_metrics::{start_metrics_server, EnclaveMetricsReceiver, MetricsHandler},
-	setup,
+	setup, shard_lifecycle,
 	sidechain_setup::{sidechain_init_block_production, sidechain_start_untrusted_rpc_server},
 	sync_block_broadcaster::SyncBlockBroadcaster,
 	sync_state, tests,
 	utils::extract_shard,
+	watchdog::{
+		start_watchdog, Lease, Watchdog, DIRECT_RPC_SERVER_TIMEOUT, PARENTCHAIN_SYNC_TIMEOUT,
+	},
 	worker::Worker,
 	worker_peers_updater::WorkerPeersUpdater,
 };
@@ -39,12 +43,17 @@ use itp_enclave_api::{
 	sidechain::Sidechain,
 	teeracle_api::TeeracleApi,
 };
+use itp_enclave_metrics::{parentchain_sync_thread, DIRECT_RPC_SERVER_THREAD};
 use itp_node_api::{
 	api_client::{AccountApi, PalletTeerexApi, ParentchainApi},
 	metadata::NodeMetadata,
 	node_api_factory::{CreateNodeApi, NodeApiFactory},
 };
 use itp_settings::worker_mode::{ProvideWorkerMode, WorkerMode, WorkerModeProvider};
+use itp_types::{
+	shard_lifecycle::{ShardGenesisConfig, VaultMode},
+	state_snapshot::{StateSnapshotCommitment, IPFS_CID_LENGTH},
+};
 use its_peer_fetch::{
 	block_fetch_client::BlockFetcher, untrusted_peer_fetch::UntrustedPeerFetcher,
 };
@@ -68,10 +77,11 @@ use itp_enclave_api::Enclave;
 
 use enclave_bridge_primitives::ShardIdentifier;
 use itc_parentchain::primitives::ParentchainId;
+use parking_lot::Mutex;
 use sp_core::crypto::{AccountId32, Ss58Codec};
 use sp_keyring::AccountKeyring;
 use sp_runtime::MultiSigner;
-use std::{str, sync::Arc, thread, time::Duration};
+use std::{str, str::FromStr, sync::Arc, thread, time::Duration};
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -132,7 +142,8 @@ pub(crate) fn main() {
 	let untrusted_peer_fetcher = UntrustedPeerFetcher::new(node_api_factory.clone());
 	let peer_sidechain_block_fetcher =
 		Arc::new(BlockFetcher::<SignedSidechainBlock, _>::new(untrusted_peer_fetcher));
-	let enclave_metrics_receiver = Arc::new(EnclaveMetricsReceiver {});
+	let watchdog = Arc::new(Watchdog::default());
+	let enclave_metrics_receiver = Arc::new(EnclaveMetricsReceiver::new(watchdog.clone()));
 
 	let maybe_target_a_parentchain_api_factory = config
 		.target_a_parentchain_rpc_endpoint()
@@ -202,6 +213,7 @@ pub(crate) fn main() {
 			initialization_handler,
 			quoting_enclave_target_info,
 			quote_size,
+			watchdog,
 		);
 	} else if let Some(smatches) = matches.subcommand_matches("request-state") {
 		println!("*** Requesting state from a registered worker \n");
@@ -232,9 +244,55 @@ pub(crate) fn main() {
 	} else if matches.is_present("mrenclave") {
 		println!("{}", enclave.get_fingerprint().unwrap().encode().to_base58());
 	} else if let Some(sub_matches) = matches.subcommand_matches("init-shard") {
-		setup::init_shard(
-			enclave.as_ref(),
-			&extract_shard(sub_matches.value_of("shard"), enclave.as_ref()),
+		let shard = extract_shard(sub_matches.value_of("shard"), enclave.as_ref());
+		match sub_matches.value_of("genesis-config") {
+			Some(path) => {
+				let genesis_config = shard_lifecycle::read_genesis_config(path)
+					.unwrap_or_else(|e| panic!("Invalid genesis config {}: {}", path, e));
+				let node_api =
+					node_api_factory.create_api().expect("Failed to create parentchain node API");
+				init_shard_with_genesis(&enclave, &node_api, &shard, &genesis_config);
+			},
+			None => setup::init_shard(enclave.as_ref(), &shard),
+		}
+	} else if let Some(sub_matches) = matches.subcommand_matches("retire-shard") {
+		let shard = extract_shard(sub_matches.value_of("shard"), enclave.as_ref());
+		let node_api =
+			node_api_factory.create_api().expect("Failed to create parentchain node API");
+		retire_shard(&enclave, &node_api, &shard, sub_matches.is_present("unshield-vault-funds"));
+	} else if let Some(sub_matches) = matches.subcommand_matches("replay-block") {
+		let shard = extract_shard(sub_matches.value_of("shard"), enclave.as_ref());
+		let block_number: u64 = sub_matches
+			.value_of("block-number")
+			.and_then(|n| n.parse().ok())
+			.expect("block number must be an unsigned integer");
+		let node_api =
+			node_api_factory.create_api().expect("Failed to create parentchain node API");
+		replay_block(&enclave, &node_api, &shard, block_number);
+	} else if let Some(sub_matches) = matches.subcommand_matches("dump-state") {
+		let shard = extract_shard(sub_matches.value_of("shard"), enclave.as_ref());
+		let output = sub_matches
+			.value_of("output")
+			.map(str::to_string)
+			.unwrap_or_else(|| format!("state_dump_{}.bin", shard.encode().to_base58()));
+		dump_state(enclave.as_ref(), &shard, &output);
+	} else if let Some(sub_matches) = matches.subcommand_matches("bootstrap-shard") {
+		let shard = extract_shard(sub_matches.value_of("shard"), enclave.as_ref());
+		let cid = sub_matches.value_of("cid").unwrap();
+		let state_hash = sub_matches.value_of("state-hash").unwrap();
+		let publication_block: u32 = sub_matches
+			.value_of("publication-block")
+			.and_then(|n| n.parse().ok())
+			.expect("publication block must be an unsigned integer");
+		let node_api =
+			node_api_factory.create_api().expect("Failed to create parentchain node API");
+		bootstrap_shard_from_state_snapshot(
+			&enclave,
+			&node_api,
+			&shard,
+			cid,
+			state_hash,
+			publication_block,
 		);
 	} else if let Some(sub_matches) = matches.subcommand_matches("test") {
 		if sub_matches.is_present("provisioning-server") {
@@ -280,6 +338,7 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 	initialization_handler: Arc<InitializationHandler>,
 	quoting_enclave_target_info: Option<sgx_target_info_t>,
 	quote_size: Option<u32>,
+	watchdog: Arc<Watchdog>,
 ) where
 	T: GetTokioHandle,
 	E: EnclaveBase
@@ -333,6 +392,10 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 	println!("MRENCLAVE={}", mrenclave.0.to_base58());
 	println!("MRENCLAVE in hex {:?}", hex::encode(mrenclave));
 
+	let memory_limits = run_config.memory_limits();
+	info!("Setting enclave memory limits: {:?}", memory_limits);
+	enclave.set_memory_limits(&memory_limits).unwrap();
+
 	// ------------------------------------------------------------------------
 	// let new workers call us for key provisioning
 	println!("MU-RA server listening on {}", config.mu_ra_url());
@@ -394,6 +457,10 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 		});
 	}
 
+	// ------------------------------------------------------------------------
+	// Restart enclave threads that got stuck.
+	start_watchdog(watchdog.clone());
+
 	// ------------------------------------------------------------------------
 	// Start trusted worker rpc server
 	if WorkerModeProvider::worker_mode() == WorkerMode::Sidechain
@@ -401,15 +468,23 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 	{
 		let direct_invocation_server_addr = config.trusted_worker_url_internal();
 		let enclave_for_direct_invocation = enclave.clone();
-		thread::spawn(move || {
-			println!(
-				"[+] Trusted RPC direct invocation server listening on {}",
-				direct_invocation_server_addr
-			);
-			enclave_for_direct_invocation
-				.init_direct_invocation_server(direct_invocation_server_addr)
-				.unwrap();
-			println!("[+] RPC direct invocation server shut down");
+		// A restart only succeeds once the previous instance has released the port.
+		watchdog.supervise(DIRECT_RPC_SERVER_THREAD, DIRECT_RPC_SERVER_TIMEOUT, move |_lease| {
+			let enclave = enclave_for_direct_invocation.clone();
+			let server_addr = direct_invocation_server_addr.clone();
+			thread::Builder::new()
+				.name(DIRECT_RPC_SERVER_THREAD.to_owned())
+				.spawn(move || {
+					println!(
+						"[+] Trusted RPC direct invocation server listening on {}",
+						server_addr
+					);
+					if let Err(e) = enclave.init_direct_invocation_server(server_addr) {
+						error!("RPC direct invocation server failed: {:?}", e);
+					}
+					println!("[+] RPC direct invocation server shut down");
+				})
+				.expect("Failed to spawn RPC direct invocation server thread");
 		});
 	}
 
@@ -421,6 +496,7 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 			&config,
 			enclave.clone(),
 			sidechain_storage.clone(),
+			*shard,
 			tokio_handle,
 		);
 	}
@@ -520,6 +596,7 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 		start_periodic_market_update(
 			&integritee_rpc_api,
 			run_config.teeracle_update_interval(),
+			run_config.oracle_feeds(),
 			enclave.as_ref(),
 			&teeracle_tokio_handle,
 		);
@@ -542,23 +619,35 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 				parentchain_handler.clone(),
 				sidechain_storage,
 				&last_synced_header,
+				run_config.sidechain_block_retention(),
+				&watchdog,
 			)
 			.unwrap();
 		}
 
 		// ------------------------------------------------------------------------
 		// start parentchain syncing loop (subscribe to header updates)
-		thread::Builder::new()
-			.name("parentchain_sync_loop".to_owned())
-			.spawn(move || {
-				if let Err(e) =
-					subscribe_to_parentchain_new_headers(parentchain_handler, last_synced_header)
-				{
-					error!("Parentchain block syncing terminated with a failure: {:?}", e);
-				}
-				println!("[!] Parentchain block syncing has terminated");
-			})
-			.unwrap();
+		supervise_parentchain_sync(
+			&watchdog,
+			ParentchainId::Integritee,
+			parentchain_handler,
+			last_synced_header,
+		);
+
+		if let Some(interval) = run_config.state_snapshot_interval() {
+			if we_are_primary_validateer {
+				spawn_state_snapshot_publication(enclave.clone(), *shard, interval);
+			}
+		}
+
+		if let Some(interval) = run_config.worker_record_interval() {
+			spawn_worker_record_publication(
+				enclave.clone(),
+				config.trusted_worker_url_external(),
+				config.untrusted_worker_url_external(),
+				interval,
+			);
+		}
 
 		if WorkerModeProvider::worker_mode() == WorkerMode::OffChainWorker {
 			info!("skipping shard vault check because not yet supported for offchain worker");
@@ -567,6 +656,8 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 				"shard vault account is already initialized in state: {}",
 				shard_vault.to_ss58check()
 			);
+		} else if let Ok(VaultMode::NoVault) = enclave.get_shard_vault_mode(shard) {
+			println!("shard has been created without a vault account");
 		} else if we_are_primary_validateer {
 			println!("initializing proxied shard vault account now");
 			enclave.init_proxied_shard_vault(shard).unwrap();
@@ -591,6 +682,7 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 			url,
 			ParentchainId::TargetA,
 			is_development_mode,
+			&watchdog,
 		)
 	}
 
@@ -601,6 +693,7 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 			url,
 			ParentchainId::TargetB,
 			is_development_mode,
+			&watchdog,
 		)
 	}
 
@@ -622,6 +715,7 @@ fn init_target_parentchain<E>(
 	url: String,
 	parentchain_id: ParentchainId,
 	is_development_mode: bool,
+	watchdog: &Watchdog,
 ) where
 	E: EnclaveBase + Sidechain,
 {
@@ -650,20 +744,12 @@ fn init_target_parentchain<E>(
 			parentchain_handler.sync_parentchain(last_synched_header).unwrap();
 
 		// start parentchain syncing loop (subscribe to header updates)
-		thread::Builder::new()
-			.name(format!("{:?}_parentchain_sync_loop", parentchain_id))
-			.spawn(move || {
-				if let Err(e) =
-					subscribe_to_parentchain_new_headers(parentchain_handler, last_synched_header)
-				{
-					error!(
-						"[{:?}] parentchain block syncing terminated with a failure: {:?}",
-						parentchain_id, e
-					);
-				}
-				println!("[!] [{:?}] parentchain block syncing has terminated", parentchain_id);
-			})
-			.unwrap();
+		supervise_parentchain_sync(
+			watchdog,
+			parentchain_id,
+			parentchain_handler,
+			last_synched_header,
+		);
 	}
 
 	// Subscribe to events and print them.
@@ -681,6 +767,245 @@ fn init_target_parentchain<E>(
 		.unwrap();
 }
 
+/// Re-executes a recorded sidechain block in the enclave and prints the differences to the
+/// recorded execution.
+fn replay_block<E>(
+	enclave: &Arc<E>,
+	node_api: &ParentchainApi,
+	shard: &ShardIdentifier,
+	block_number: u64,
+) where
+	E: EnclaveBase + Sidechain,
+{
+	// The STF executor is part of the parentchain components.
+	let tee_accountid = enclave_account(enclave.as_ref());
+	init_parentchain(enclave, node_api, &tee_accountid, ParentchainId::Integritee);
+
+	let report = enclave.replay_block(shard, block_number).unwrap_or_else(|e| {
+		panic!("Failed to replay sidechain block {} of shard {:?}: {:?}", block_number, shard, e)
+	});
+
+	println!("Recorded state hash: {:?}", report.recorded_state_hash);
+	println!("Replayed state hash: {:?}", report.replayed_state_hash);
+	for mismatch in report.mismatches.iter() {
+		println!(
+			"Mismatch at key 0x{}: recorded {:?}, replayed {:?}",
+			hex::encode(&mismatch.key),
+			mismatch.recorded.as_ref().map(|v| v.as_ref().map(hex::encode)),
+			mismatch.replayed.as_ref().map(|v| v.as_ref().map(hex::encode)),
+		);
+	}
+	if report.is_deterministic() {
+		println!("Sidechain block {} replayed deterministically", block_number);
+	} else {
+		println!(
+			"Sidechain block {} replay diverged at {} keys",
+			block_number,
+			report.mismatches.len()
+		);
+	}
+}
+
+/// Writes the SCALE encoded state dump of `shard` to `output`.
+fn dump_state<E: EnclaveBase>(enclave: &E, shard: &ShardIdentifier, output: &str) {
+	let state_dump = enclave
+		.dump_state(shard)
+		.unwrap_or_else(|e| panic!("Failed to dump state of shard {:?}: {:?}", shard, e));
+
+	std::fs::write(output, state_dump.encode())
+		.unwrap_or_else(|e| panic!("Failed to write state dump to {}: {:?}", output, e));
+
+	println!(
+		"Dumped {} storage entries of shard {:?} to {}",
+		state_dump.entries.len(),
+		shard,
+		output
+	);
+}
+
+/// Creates `shard` with the genesis config, registers its config on the parentchain and
+/// initializes its vault.
+fn init_shard_with_genesis<E>(
+	enclave: &Arc<E>,
+	node_api: &ParentchainApi,
+	shard: &ShardIdentifier,
+	genesis_config: &ShardGenesisConfig,
+) where
+	E: EnclaveBase + Sidechain,
+{
+	// Sending extrinsics requires the parentchain components.
+	let tee_accountid = enclave_account(enclave.as_ref());
+	init_parentchain(enclave, node_api, &tee_accountid, ParentchainId::Integritee);
+
+	enclave
+		.init_shard_with_genesis(shard, genesis_config)
+		.unwrap_or_else(|e| panic!("Failed to initialize shard {:?}: {:?}", shard, e));
+	println!(
+		"Initialized shard {} with {} authorities and registered its config on the parentchain",
+		shard.encode().to_base58(),
+		genesis_config.authorities.len()
+	);
+
+	if genesis_config.vault_mode == VaultMode::Proxied {
+		enclave
+			.init_proxied_shard_vault(shard)
+			.unwrap_or_else(|e| panic!("Failed to initialize shard vault: {:?}", e));
+		println!(
+			"initialized shard vault account: {}",
+			enclave.get_ecc_vault_pubkey(shard).unwrap().to_ss58check()
+		);
+	}
+}
+
+/// Stops the authoring of `shard`, commits to its final state and optionally pays out the
+/// funds of its accounts from the shard vault.
+fn retire_shard<E>(
+	enclave: &Arc<E>,
+	node_api: &ParentchainApi,
+	shard: &ShardIdentifier,
+	unshield_vault_funds: bool,
+) where
+	E: EnclaveBase + Sidechain,
+{
+	const POLL_INTERVAL_SECS: u64 = 6;
+
+	let tee_accountid = enclave_account(enclave.as_ref());
+	let (parentchain_handler, mut last_synced_header) =
+		init_parentchain(enclave, node_api, &tee_accountid, ParentchainId::Integritee);
+	// The last sidechain blocks are imported from a peer before committing to the final state.
+	enclave.init_enclave_sidechain_components().unwrap();
+
+	// The enclave only advances the retirement with the parentchain blocks it has been synced to.
+	let report = loop {
+		last_synced_header = parentchain_handler
+			.sync_parentchain(last_synced_header)
+			.unwrap_or_else(|e| panic!("Failed to sync the parentchain: {:?}", e));
+		match enclave
+			.retire_shard(shard, unshield_vault_funds)
+			.unwrap_or_else(|e| panic!("Failed to retire shard {:?}: {:?}", shard, e))
+		{
+			Some(report) => break report,
+			None => {
+				println!(
+					"Waiting for shard {} to settle in maintenance mode ({} seconds interval)",
+					shard.encode().to_base58(),
+					POLL_INTERVAL_SECS
+				);
+				thread::sleep(Duration::from_secs(POLL_INTERVAL_SECS));
+			},
+		}
+	};
+
+	println!("Retired shard {}", shard.encode().to_base58());
+	println!(
+		"Final state hash: {:?}, snapshot CID: {}",
+		report.final_state.state_hash,
+		report.final_state.cid_str().unwrap_or_default()
+	);
+	if unshield_vault_funds {
+		println!(
+			"Unshielded {} from the shard vault to {} accounts",
+			report.unshielded_amount, report.unshielded_accounts
+		);
+	}
+	if report.unconfirmed_payouts > 0 {
+		println!(
+			"{} payouts of {} in total have been sent before the retirement, but are unconfirmed",
+			report.unconfirmed_payouts, report.unconfirmed_amount
+		);
+	}
+}
+
+/// Replaces the state of `shard` with the state snapshot committed with `cid` and `state_hash`
+/// in the parentchain block `publication_block`.
+fn bootstrap_shard_from_state_snapshot<E>(
+	enclave: &Arc<E>,
+	node_api: &ParentchainApi,
+	shard: &ShardIdentifier,
+	cid: &str,
+	state_hash: &str,
+	publication_block: u32,
+) where
+	E: EnclaveBase + Sidechain,
+{
+	let cid: [u8; IPFS_CID_LENGTH] = cid
+		.as_bytes()
+		.try_into()
+		.unwrap_or_else(|_| panic!("CID must be {} characters long", IPFS_CID_LENGTH));
+	let state_hash = Hash::from_str(state_hash.trim_start_matches("0x"))
+		.unwrap_or_else(|e| panic!("Invalid state hash {}: {:?}", state_hash, e));
+	let commitment = StateSnapshotCommitment::new(*shard, state_hash, cid);
+
+	let snapshot = read_from_ipfs(cid)
+		.unwrap_or_else(|e| panic!("Failed to read state snapshot from IPFS: {}", e));
+
+	// The enclave verifies the publication with its light client, which must have
+	// finalized the publication block.
+	let tee_accountid = enclave_account(enclave.as_ref());
+	let (parentchain_handler, last_synced_header) =
+		init_parentchain(enclave, node_api, &tee_accountid, ParentchainId::Integritee);
+	parentchain_handler
+		.sync_parentchain(last_synced_header)
+		.unwrap_or_else(|e| panic!("Failed to sync the parentchain: {:?}", e));
+	let publication_header = node_api
+		.get_block_hash(Some(publication_block))
+		.ok()
+		.flatten()
+		.and_then(|hash| node_api.get_header(Some(hash)).ok().flatten())
+		.unwrap_or_else(|| panic!("Parentchain block {} not found", publication_block));
+	// TODO: #1451: Fix api-client type hacks
+	let publication_header = Header::decode(&mut publication_header.encode().as_slice())
+		.expect("Can decode previously encoded header; qed");
+
+	enclave
+		.bootstrap_shard_from_state_snapshot(&commitment, &publication_header, &snapshot)
+		.unwrap_or_else(|e| panic!("Failed to bootstrap shard {:?}: {:?}", shard, e));
+
+	println!("Bootstrapped shard {:?} from state snapshot with state hash {:?}", shard, state_hash);
+}
+
+/// Periodically publishes a state snapshot of `shard` on IPFS.
+fn spawn_state_snapshot_publication<E: EnclaveBase>(
+	enclave: Arc<E>,
+	shard: ShardIdentifier,
+	interval: Duration,
+) {
+	thread::Builder::new()
+		.name("state_snapshot_publication".to_owned())
+		.spawn(move || loop {
+			thread::sleep(interval);
+			match enclave.publish_state_snapshot(&shard) {
+				Ok(commitment) => info!(
+					"Published state snapshot with state hash {:?}, CID {:?}",
+					commitment.state_hash,
+					commitment.cid_str()
+				),
+				Err(e) => error!("Failed to publish state snapshot: {:?}", e),
+			}
+		})
+		.unwrap();
+}
+
+/// Periodically publishes the enclave-signed record of this worker for the worker directory.
+fn spawn_worker_record_publication<E: EnclaveBase>(
+	enclave: Arc<E>,
+	trusted_url: String,
+	untrusted_url: String,
+	interval: Duration,
+) {
+	thread::Builder::new()
+		.name("worker_record_publication".to_owned())
+		.spawn(move || loop {
+			match enclave.publish_worker_record(&trusted_url, &untrusted_url) {
+				Ok(signed_record) =>
+					info!("Published worker record for shards {:?}", signed_record.record.shards),
+				Err(e) => error!("Failed to publish worker record: {:?}", e),
+			}
+			thread::sleep(interval);
+		})
+		.unwrap();
+}
+
 fn init_parentchain<E>(
 	enclave: &Arc<E>,
 	node_api: &ParentchainApi,
@@ -1028,9 +1353,47 @@ fn send_extrinsic(
 
 /// Subscribe to the node API finalized heads stream and trigger a parent chain sync
 /// upon receiving a new header.
+/// Spawns the loop syncing the parentchain with its new finalized headers, which is restarted
+/// by the watchdog when it gets stuck.
+fn supervise_parentchain_sync<E: EnclaveBase + Sidechain>(
+	watchdog: &Watchdog,
+	parentchain_id: ParentchainId,
+	parentchain_handler: Arc<ParentchainHandler<ParentchainApi, E>>,
+	last_synced_header: Header,
+) {
+	let last_synced_header = Arc::new(Mutex::new(last_synced_header));
+	watchdog.supervise(
+		&parentchain_sync_thread(&parentchain_id),
+		PARENTCHAIN_SYNC_TIMEOUT,
+		move |lease| {
+			let parentchain_handler = parentchain_handler.clone();
+			let last_synced_header = last_synced_header.clone();
+			thread::Builder::new()
+				.name(format!("{:?}_parentchain_sync_loop", parentchain_id))
+				.spawn(move || {
+					if let Err(e) = subscribe_to_parentchain_new_headers(
+						parentchain_handler,
+						last_synced_header,
+						lease,
+					) {
+						error!(
+							"[{:?}] parentchain block syncing terminated with a failure: {:?}",
+							parentchain_id, e
+						);
+					}
+					println!("[!] [{:?}] parentchain block syncing has terminated", parentchain_id);
+				})
+				.expect("Failed to spawn parentchain sync loop");
+		},
+	);
+}
+
+/// Syncs the parentchain until the `lease` expires. The last synced header is shared with the
+/// instances that the watchdog starts after this one.
 fn subscribe_to_parentchain_new_headers<E: EnclaveBase + Sidechain>(
 	parentchain_handler: Arc<ParentchainHandler<ParentchainApi, E>>,
-	mut last_synced_header: Header,
+	last_synced_header: Arc<Mutex<Header>>,
+	lease: Lease,
 ) -> Result<(), Error> {
 	// TODO: this should be implemented by parentchain_handler directly, and not via
 	// exposed parentchain_api
@@ -1045,12 +1408,21 @@ fn subscribe_to_parentchain_new_headers<E: EnclaveBase + Sidechain>(
 			.ok_or(Error::ApiSubscriptionDisconnected)?
 			.map_err(|e| Error::ApiClient(e.into()))?;
 
+		if !lease.is_current() {
+			return Ok(())
+		}
 		println!(
 			"[+] Received finalized header update ({}), syncing parent chain...",
 			new_header.number
 		);
 
-		last_synced_header = parentchain_handler.sync_parentchain(last_synced_header)?;
+		let header = last_synced_header.lock().clone();
+		let synced_header = parentchain_handler.sync_parentchain(header)?;
+		// The watchdog has restarted the sync while we were stuck, the new instance takes over.
+		if !lease.is_current() {
+			return Ok(())
+		}
+		*last_synced_header.lock() = synced_header;
 	}
 }
 
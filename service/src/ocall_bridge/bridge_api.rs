@@ -16,7 +16,9 @@
 
 */
 
+use crate::ocall_bridge::dispatcher::{Cancellation, OCallDispatcher, OCallType};
 use itp_enclave_api::remote_attestation::QveReport;
+use itp_ocall_api::{OCallBridgeStatus, TcpConnectionId};
 use lazy_static::lazy_static;
 use log::*;
 use parking_lot::RwLock;
@@ -80,6 +82,14 @@ impl Bridge {
 			.get_ipfs_api()
 	}
 
+	pub fn get_https_client_api() -> Arc<dyn HttpsClientBridge> {
+		COMPONENT_FACTORY
+			.read()
+			.as_ref()
+			.expect("Component factory has not been set. Use `initialize()`")
+			.get_https_client_api()
+	}
+
 	pub fn get_metrics_api() -> Arc<dyn MetricsBridge> {
 		COMPONENT_FACTORY
 			.read()
@@ -88,6 +98,14 @@ impl Bridge {
 			.get_metrics_api()
 	}
 
+	pub fn get_ocall_dispatcher() -> Arc<OCallDispatcher> {
+		COMPONENT_FACTORY
+			.read()
+			.as_ref()
+			.expect("Component factory has not been set. Use `initialize()`")
+			.get_ocall_dispatcher()
+	}
+
 	pub fn initialize(component_factory: Arc<dyn GetOCallBridgeComponents + Send + Sync>) {
 		debug!("Initializing OCall bridge with component factory");
 
@@ -112,6 +130,12 @@ pub trait GetOCallBridgeComponents {
 
 	/// Metrics OCall API.
 	fn get_metrics_api(&self) -> Arc<dyn MetricsBridge>;
+
+	/// TCP relay of the enclave's HTTPS client.
+	fn get_https_client_api(&self) -> Arc<dyn HttpsClientBridge>;
+
+	/// Dispatcher running the worker-api o-calls on the async runtime.
+	fn get_ocall_dispatcher(&self) -> Arc<OCallDispatcher>;
 }
 
 /// OCall bridge errors
@@ -131,12 +155,20 @@ pub enum OCallBridgeError {
 	ProposeSidechainBlock(String),
 	#[error("Failed to fetch sidechain blocks from peer: {0}")]
 	FetchSidechainBlocksFromPeer(String),
+	#[error("Failed to request state confirmations: {0}")]
+	RequestStateConfirmations(String),
 	#[error("Sending extrinsics to parentchain failed: {0}")]
 	SendExtrinsicsToParentchain(String),
 	#[error("IPFS Error: {0}")]
 	IpfsError(String),
+	#[error("TCP relay error: {0}")]
+	TcpRelay(String),
 	#[error("DirectInvocation Error: {0}")]
 	DirectInvocationError(String),
+	#[error("Queue of {0} o-calls is saturated")]
+	QueueSaturated(OCallType),
+	#[error("{0} o-call timed out")]
+	TimedOut(OCallType),
 	#[error(transparent)]
 	Codec(#[from] codec::Error),
 	#[error("Node API factory error: {0}")]
@@ -153,6 +185,8 @@ impl From<OCallBridgeError> for sgx_status_t {
 			OCallBridgeError::GetQuote(s) => s,
 			OCallBridgeError::InitQuote(s) => s,
 			OCallBridgeError::GetUpdateInfo(s) => s,
+			OCallBridgeError::QueueSaturated(_) => OCallBridgeStatus::QueueSaturated.into(),
+			OCallBridgeError::TimedOut(_) => OCallBridgeStatus::TimedOut.into(),
 			_ => sgx_status_t::SGX_ERROR_UNEXPECTED,
 		}
 	}
@@ -202,7 +236,7 @@ pub trait RemoteAttestationBridge {
 
 /// Trait for all the OCalls related to parentchain operations
 #[cfg_attr(test, automock)]
-pub trait WorkerOnChainBridge {
+pub trait WorkerOnChainBridge: Send + Sync {
 	fn worker_request(
 		&self,
 		request: Vec<u8>,
@@ -214,12 +248,13 @@ pub trait WorkerOnChainBridge {
 		extrinsics_encoded: Vec<u8>,
 		parentchain_id: Vec<u8>,
 		await_each_inclusion: bool,
+		cancellation: Cancellation,
 	) -> OCallBridgeResult<()>;
 }
 
 /// Trait for updating metrics from inside the enclave.
 #[cfg_attr(test, automock)]
-pub trait MetricsBridge {
+pub trait MetricsBridge: Send + Sync {
 	fn update_metric(&self, metric_encoded: Vec<u8>) -> OCallBridgeResult<()>;
 }
 
@@ -236,6 +271,12 @@ pub trait SidechainBridge {
 		maybe_until_block_hash_encoded: Vec<u8>,
 		shard_identifier_encoded: Vec<u8>,
 	) -> OCallBridgeResult<Vec<u8>>;
+
+	fn request_state_confirmations(
+		&self,
+		shard_identifier_encoded: Vec<u8>,
+		encrypted_request: Vec<u8>,
+	) -> OCallBridgeResult<Vec<u8>>;
 }
 
 /// type for IPFS
@@ -243,12 +284,31 @@ pub type Cid = [u8; 46];
 
 /// Trait for all the OCalls related to IPFS
 #[cfg_attr(test, automock)]
-pub trait IpfsBridge {
-	fn write_to_ipfs(&self, data: &'static [u8]) -> OCallBridgeResult<Cid>;
+pub trait IpfsBridge: Send + Sync {
+	fn write_to_ipfs(&self, data: Vec<u8>) -> OCallBridgeResult<Cid>;
 
 	fn read_from_ipfs(&self, cid: Cid) -> OCallBridgeResult<()>;
 }
 
+/// Trait for the o-calls relaying the TCP connections of the enclave's HTTPS client.
+///
+/// The TLS session is terminated inside the enclave, only encrypted bytes pass the bridge.
+#[cfg_attr(test, automock)]
+pub trait HttpsClientBridge: Send + Sync {
+	fn tcp_connect(&self, host: String, port: u16) -> OCallBridgeResult<TcpConnectionId>;
+
+	fn tcp_send(&self, connection: TcpConnectionId, data: Vec<u8>) -> OCallBridgeResult<()>;
+
+	/// Receives at most `max_len` bytes, no bytes once the peer closed the connection.
+	fn tcp_receive(
+		&self,
+		connection: TcpConnectionId,
+		max_len: usize,
+	) -> OCallBridgeResult<Vec<u8>>;
+
+	fn tcp_close(&self, connection: TcpConnectionId) -> OCallBridgeResult<()>;
+}
+
 /// Trait for the direct invocation OCalls
 #[cfg_attr(test, automock)]
 pub trait DirectInvocationBridge {
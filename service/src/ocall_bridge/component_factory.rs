@@ -20,9 +20,11 @@ use crate::{
 	globals::tokio_handle::GetTokioHandle,
 	ocall_bridge::{
 		bridge_api::{
-			GetOCallBridgeComponents, IpfsBridge, MetricsBridge, RemoteAttestationBridge,
-			SidechainBridge, WorkerOnChainBridge,
+			GetOCallBridgeComponents, HttpsClientBridge, IpfsBridge, MetricsBridge,
+			RemoteAttestationBridge, SidechainBridge, WorkerOnChainBridge,
 		},
+		dispatcher::OCallDispatcher,
+		https_client_ocall::HttpsClientOCall,
 		ipfs_ocall::IpfsOCall,
 		metrics_ocall::MetricsOCall,
 		remote_attestation_ocall::RemoteAttestationOCall,
@@ -30,7 +32,7 @@ use crate::{
 		worker_on_chain_ocall::WorkerOnChainOCall,
 	},
 	prometheus_metrics::ReceiveEnclaveMetrics,
-	sync_block_broadcaster::BroadcastBlocks,
+	sync_block_broadcaster::{BroadcastBlocks, RequestStateConfirmations},
 	worker_peers_updater::UpdateWorkerPeers,
 };
 use itp_enclave_api::remote_attestation::RemoteAttestationCallBacks;
@@ -63,6 +65,8 @@ pub struct OCallBridgeComponentFactory<
 	peer_block_fetcher: Arc<PeerBlockFetcher>,
 	tokio_handle: Arc<TokioHandle>,
 	metrics_receiver: Arc<MetricsReceiver>,
+	ocall_dispatcher: Arc<OCallDispatcher>,
+	https_client_api: Arc<HttpsClientOCall>,
 }
 
 impl<
@@ -98,7 +102,11 @@ impl<
 		peer_block_fetcher: Arc<PeerBlockFetcher>,
 		tokio_handle: Arc<TokioHandle>,
 		metrics_receiver: Arc<MetricsReceiver>,
-	) -> Self {
+	) -> Self
+	where
+		TokioHandle: GetTokioHandle,
+	{
+		let ocall_dispatcher = Arc::new(OCallDispatcher::new(tokio_handle.get_handle()));
 		OCallBridgeComponentFactory {
 			integritee_rpc_api_factory,
 			target_a_parentchain_rpc_api_factory,
@@ -110,6 +118,8 @@ impl<
 			peer_block_fetcher,
 			tokio_handle,
 			metrics_receiver,
+			ocall_dispatcher,
+			https_client_api: Arc::new(HttpsClientOCall::default()),
 		}
 	}
 }
@@ -133,15 +143,16 @@ impl<
 		PeerBlockFetcher,
 		TokioHandle,
 		MetricsReceiver,
-	> where
-	NodeApi: CreateNodeApi + 'static,
-	Broadcaster: BroadcastBlocks + 'static,
+	>
+where
+	NodeApi: CreateNodeApi + Send + Sync + 'static,
+	Broadcaster: BroadcastBlocks + RequestStateConfirmations + 'static,
 	EnclaveApi: RemoteAttestationCallBacks + 'static,
 	Storage: BlockStorage<SignedSidechainBlock> + 'static,
 	PeerUpdater: UpdateWorkerPeers + 'static,
 	PeerBlockFetcher: FetchBlocksFromPeer<SignedBlockType = SignedSidechainBlock> + 'static,
 	TokioHandle: GetTokioHandle + 'static,
-	MetricsReceiver: ReceiveEnclaveMetrics + 'static,
+	MetricsReceiver: ReceiveEnclaveMetrics + Send + Sync + 'static,
 {
 	fn get_ra_api(&self) -> Arc<dyn RemoteAttestationBridge> {
 		Arc::new(RemoteAttestationOCall::new(self.enclave_api.clone()))
@@ -172,4 +183,12 @@ impl<
 	fn get_metrics_api(&self) -> Arc<dyn MetricsBridge> {
 		Arc::new(MetricsOCall::new(self.metrics_receiver.clone()))
 	}
+
+	fn get_https_client_api(&self) -> Arc<dyn HttpsClientBridge> {
+		self.https_client_api.clone()
+	}
+
+	fn get_ocall_dispatcher(&self) -> Arc<OCallDispatcher> {
+		self.ocall_dispatcher.clone()
+	}
 }
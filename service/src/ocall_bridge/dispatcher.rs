@@ -0,0 +1,470 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Runs worker-api o-calls (extrinsic submission, IPFS, metrics, TCP relay) on the tokio
+//! runtime, instead of on the calling enclave thread.
+//!
+//! Every o-call type has its own concurrency limit and bounded queue. O-calls that do not
+//! return data to the enclave are pipelined: they are queued and the enclave thread returns
+//! immediately. All other o-calls wait for their result, at most for the timeout of their type.
+//! A full queue is reported to the enclave as [`OCallBridgeStatus::QueueSaturated`] instead
+//! of blocking the enclave thread.
+//!
+//! O-calls whose order matters (e.g. extrinsics signed with consecutive nonces) are dispatched
+//! in a lane: o-calls of the same lane are executed one after another, in the order they were
+//! dispatched. When the caller of a lane o-call times out, the o-call is cancelled: it still
+//! runs, but it can check its [`Cancellation`] to skip work the caller is no longer waiting for.
+//!
+//! [`OCallBridgeStatus::QueueSaturated`]: itp_ocall_api::OCallBridgeStatus::QueueSaturated
+
+use crate::ocall_bridge::bridge_api::{OCallBridgeError, OCallBridgeResult};
+use log::*;
+use parking_lot::Mutex;
+use std::{
+	collections::HashMap,
+	fmt::{Display, Formatter},
+	sync::{
+		atomic::{AtomicBool, AtomicUsize, Ordering},
+		mpsc::channel,
+		Arc,
+	},
+	time::Duration,
+};
+use tokio::{
+	runtime::Handle,
+	sync::{
+		mpsc::{unbounded_channel, UnboundedSender},
+		Semaphore,
+	},
+};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// O-call types with their own concurrency limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OCallType {
+	SendToParentchain,
+	Ipfs,
+	UpdateMetric,
+	TcpRelay,
+}
+
+impl OCallType {
+	pub const ALL: [OCallType; 4] = [
+		OCallType::SendToParentchain,
+		OCallType::Ipfs,
+		OCallType::UpdateMetric,
+		OCallType::TcpRelay,
+	];
+
+	pub fn default_limits(&self) -> OCallLimits {
+		match self {
+			OCallType::SendToParentchain =>
+				OCallLimits { max_concurrent: 4, max_queued: 64, timeout: Duration::from_secs(60) },
+			OCallType::Ipfs =>
+				OCallLimits { max_concurrent: 2, max_queued: 8, timeout: Duration::from_secs(30) },
+			OCallType::UpdateMetric =>
+				OCallLimits { max_concurrent: 2, max_queued: 256, timeout: Duration::from_secs(5) },
+			// Slightly above the read timeout of the relayed connections.
+			OCallType::TcpRelay =>
+				OCallLimits { max_concurrent: 16, max_queued: 64, timeout: Duration::from_secs(35) },
+		}
+	}
+}
+
+impl Display for OCallType {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let name = match self {
+			OCallType::SendToParentchain => "send_to_parentchain",
+			OCallType::Ipfs => "ipfs",
+			OCallType::UpdateMetric => "update_metric",
+			OCallType::TcpRelay => "tcp_relay",
+		};
+		write!(f, "{}", name)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OCallLimits {
+	/// Number of o-calls of a type that are executed concurrently.
+	pub max_concurrent: usize,
+	/// Number of o-calls of a type waiting for execution, before new ones are rejected.
+	pub max_queued: usize,
+	/// Time an enclave thread waits for the result of an o-call.
+	pub timeout: Duration,
+}
+
+/// Set when the caller of an o-call stopped waiting for its result.
+#[derive(Debug, Clone, Default)]
+pub struct Cancellation(Arc<AtomicBool>);
+
+impl Cancellation {
+	pub fn is_cancelled(&self) -> bool {
+		self.0.load(Ordering::SeqCst)
+	}
+
+	fn cancel(&self) {
+		self.0.store(true, Ordering::SeqCst)
+	}
+}
+
+struct OCallQueue {
+	limits: OCallLimits,
+	permits: Arc<Semaphore>,
+	pending: Arc<AtomicUsize>,
+}
+
+impl OCallQueue {
+	fn new(limits: OCallLimits) -> Self {
+		OCallQueue {
+			limits,
+			permits: Arc::new(Semaphore::new(limits.max_concurrent)),
+			pending: Arc::new(AtomicUsize::new(0)),
+		}
+	}
+}
+
+pub struct OCallDispatcher {
+	tokio_handle: Handle,
+	queues: HashMap<OCallType, OCallQueue>,
+	lanes: Mutex<HashMap<(OCallType, Vec<u8>), UnboundedSender<Job>>>,
+}
+
+impl OCallDispatcher {
+	pub fn new(tokio_handle: Handle) -> Self {
+		Self::with_limits(tokio_handle, [])
+	}
+
+	/// Creates a dispatcher with custom limits, o-call types that are not given use their
+	/// default limits.
+	pub fn with_limits(
+		tokio_handle: Handle,
+		limits: impl IntoIterator<Item = (OCallType, OCallLimits)>,
+	) -> Self {
+		let mut queues: HashMap<_, _> = OCallType::ALL
+			.iter()
+			.map(|t| (*t, OCallQueue::new(t.default_limits())))
+			.collect();
+		queues.extend(limits.into_iter().map(|(t, l)| (t, OCallQueue::new(l))));
+
+		OCallDispatcher { tokio_handle, queues, lanes: Default::default() }
+	}
+
+	/// Runs the o-call and waits for its result, at most for the timeout of its type.
+	pub fn dispatch<F, R>(&self, ocall_type: OCallType, ocall: F) -> OCallBridgeResult<R>
+	where
+		F: FnOnce() -> OCallBridgeResult<R> + Send + 'static,
+		R: Send + 'static,
+	{
+		let (sender, receiver) = channel();
+		self.enqueue(ocall_type, move || {
+			// The receiver is gone if the caller timed out already.
+			let _ = sender.send(ocall());
+		})?;
+
+		receiver
+			.recv_timeout(self.queue(ocall_type).limits.timeout)
+			.map_err(|_| OCallBridgeError::TimedOut(ocall_type))?
+	}
+
+	/// Queues the o-call and returns immediately. Errors of the o-call itself are only logged.
+	pub fn dispatch_detached<F>(&self, ocall_type: OCallType, ocall: F) -> OCallBridgeResult<()>
+	where
+		F: FnOnce() -> OCallBridgeResult<()> + Send + 'static,
+	{
+		self.enqueue(ocall_type, move || {
+			if let Err(e) = ocall() {
+				error!("Detached {} o-call failed: {:?}", ocall_type, e);
+			}
+		})
+	}
+
+	/// Runs the o-call after all o-calls previously dispatched in the same lane, and waits
+	/// for its result, at most for the timeout of its type. The o-call is cancelled on timeout.
+	pub fn dispatch_in_lane<F, R>(
+		&self,
+		ocall_type: OCallType,
+		lane: Vec<u8>,
+		ocall: F,
+	) -> OCallBridgeResult<R>
+	where
+		F: FnOnce(&Cancellation) -> OCallBridgeResult<R> + Send + 'static,
+		R: Send + 'static,
+	{
+		let (sender, receiver) = channel();
+		let cancellation = Cancellation::default();
+		let job_cancellation = cancellation.clone();
+		self.enqueue_in_lane(
+			ocall_type,
+			lane,
+			Box::new(move || {
+				// The receiver is gone if the caller timed out already.
+				let _ = sender.send(ocall(&job_cancellation));
+			}),
+		)?;
+
+		receiver.recv_timeout(self.queue(ocall_type).limits.timeout).map_err(|_| {
+			cancellation.cancel();
+			OCallBridgeError::TimedOut(ocall_type)
+		})?
+	}
+
+	/// Queues the o-call in the given lane and returns immediately. Errors of the o-call itself
+	/// are only logged.
+	pub fn dispatch_detached_in_lane<F>(
+		&self,
+		ocall_type: OCallType,
+		lane: Vec<u8>,
+		ocall: F,
+	) -> OCallBridgeResult<()>
+	where
+		F: FnOnce(&Cancellation) -> OCallBridgeResult<()> + Send + 'static,
+	{
+		self.enqueue_in_lane(
+			ocall_type,
+			lane,
+			Box::new(move || {
+				if let Err(e) = ocall(&Cancellation::default()) {
+					error!("Detached {} o-call failed: {:?}", ocall_type, e);
+				}
+			}),
+		)
+	}
+
+	fn enqueue<F>(&self, ocall_type: OCallType, ocall: F) -> OCallBridgeResult<()>
+	where
+		F: FnOnce() + Send + 'static,
+	{
+		self.reserve(ocall_type)?;
+
+		let queue = self.queue(ocall_type);
+		let permits = queue.permits.clone();
+		let pending = queue.pending.clone();
+		self.tokio_handle.spawn(async move {
+			// The permit is held until the o-call has finished, even if the caller timed out.
+			if let Ok(_permit) = permits.acquire_owned().await {
+				if let Err(e) = tokio::task::spawn_blocking(ocall).await {
+					error!("{} o-call panicked: {:?}", ocall_type, e);
+				}
+			}
+			pending.fetch_sub(1, Ordering::SeqCst);
+		});
+
+		Ok(())
+	}
+
+	fn enqueue_in_lane(
+		&self,
+		ocall_type: OCallType,
+		lane: Vec<u8>,
+		ocall: Job,
+	) -> OCallBridgeResult<()> {
+		self.reserve(ocall_type)?;
+
+		let mut lanes = self.lanes.lock();
+		let sender = lanes.entry((ocall_type, lane)).or_insert_with(|| self.spawn_lane(ocall_type));
+		if sender.send(ocall).is_err() {
+			// Only happens if the runtime is shutting down.
+			self.queue(ocall_type).pending.fetch_sub(1, Ordering::SeqCst);
+			return Err(OCallBridgeError::QueueSaturated(ocall_type))
+		}
+
+		Ok(())
+	}
+
+	/// Spawns the task executing the o-calls of a lane, one after another.
+	fn spawn_lane(&self, ocall_type: OCallType) -> UnboundedSender<Job> {
+		let (sender, mut receiver) = unbounded_channel::<Job>();
+		let queue = self.queue(ocall_type);
+		let permits = queue.permits.clone();
+		let pending = queue.pending.clone();
+		self.tokio_handle.spawn(async move {
+			while let Some(ocall) = receiver.recv().await {
+				if let Ok(_permit) = permits.clone().acquire_owned().await {
+					if let Err(e) = tokio::task::spawn_blocking(ocall).await {
+						error!("{} o-call panicked: {:?}", ocall_type, e);
+					}
+				}
+				pending.fetch_sub(1, Ordering::SeqCst);
+			}
+		});
+		sender
+	}
+
+	/// Reserves a place in the queue of the o-call type, the caller has to release it once
+	/// the o-call has finished.
+	fn reserve(&self, ocall_type: OCallType) -> OCallBridgeResult<()> {
+		let queue = self.queue(ocall_type);
+		let capacity = queue.limits.max_concurrent + queue.limits.max_queued;
+
+		if queue
+			.pending
+			.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |p| (p < capacity).then(|| p + 1))
+			.is_err()
+		{
+			warn!("Rejecting {} o-call, {} o-calls are pending", ocall_type, capacity);
+			return Err(OCallBridgeError::QueueSaturated(ocall_type))
+		}
+		Ok(())
+	}
+
+	fn queue(&self, ocall_type: OCallType) -> &OCallQueue {
+		self.queues.get(&ocall_type).expect("Queues exist for all o-call types; qed")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{sync::mpsc::sync_channel, thread};
+	use tokio::runtime::Runtime;
+
+	fn limits(max_concurrent: usize, max_queued: usize) -> OCallLimits {
+		OCallLimits { max_concurrent, max_queued, timeout: Duration::from_millis(500) }
+	}
+
+	#[test]
+	fn dispatch_returns_result_of_ocall() {
+		let runtime = Runtime::new().unwrap();
+		let dispatcher = OCallDispatcher::new(runtime.handle().clone());
+
+		assert_eq!(dispatcher.dispatch(OCallType::Ipfs, || Ok(42u32)).unwrap(), 42);
+		assert!(matches!(
+			dispatcher.dispatch::<_, ()>(OCallType::Ipfs, || Err(OCallBridgeError::IpfsError(
+				"failed".to_string()
+			))),
+			Err(OCallBridgeError::IpfsError(_))
+		));
+	}
+
+	#[test]
+	fn dispatch_times_out_on_slow_ocall() {
+		let runtime = Runtime::new().unwrap();
+		let dispatcher = OCallDispatcher::with_limits(
+			runtime.handle().clone(),
+			[(OCallType::Ipfs, limits(1, 1))],
+		);
+
+		let result = dispatcher.dispatch(OCallType::Ipfs, || {
+			thread::sleep(Duration::from_secs(1));
+			Ok(())
+		});
+
+		assert!(matches!(result, Err(OCallBridgeError::TimedOut(OCallType::Ipfs))));
+	}
+
+	#[test]
+	fn saturated_queue_rejects_ocalls_until_pending_ones_finished() {
+		let runtime = Runtime::new().unwrap();
+		let dispatcher = OCallDispatcher::with_limits(
+			runtime.handle().clone(),
+			[(OCallType::UpdateMetric, limits(1, 1))],
+		);
+		let (release_sender, release_receiver) = sync_channel::<()>(0);
+		let release_receiver = Arc::new(std::sync::Mutex::new(release_receiver));
+
+		for _ in 0..2 {
+			let receiver = release_receiver.clone();
+			dispatcher
+				.dispatch_detached(OCallType::UpdateMetric, move || {
+					receiver.lock().unwrap().recv().unwrap();
+					Ok(())
+				})
+				.unwrap();
+		}
+
+		assert!(matches!(
+			dispatcher.dispatch_detached(OCallType::UpdateMetric, || Ok(())),
+			Err(OCallBridgeError::QueueSaturated(OCallType::UpdateMetric))
+		));
+		// Other o-call types are not affected.
+		assert!(dispatcher.dispatch(OCallType::Ipfs, || Ok(())).is_ok());
+
+		release_sender.send(()).unwrap();
+		release_sender.send(()).unwrap();
+
+		let pending = &dispatcher.queue(OCallType::UpdateMetric).pending;
+		while pending.load(Ordering::SeqCst) > 0 {
+			thread::sleep(Duration::from_millis(10));
+		}
+		assert_eq!(dispatcher.dispatch(OCallType::UpdateMetric, || Ok(1u8)).unwrap(), 1);
+	}
+
+	#[test]
+	fn ocalls_of_a_lane_are_executed_in_dispatch_order() {
+		let runtime = Runtime::new().unwrap();
+		let dispatcher = OCallDispatcher::with_limits(
+			runtime.handle().clone(),
+			[(OCallType::SendToParentchain, limits(4, 16))],
+		);
+		let executed = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+		for i in 0..8u8 {
+			let executed = executed.clone();
+			dispatcher
+				.dispatch_detached_in_lane(OCallType::SendToParentchain, vec![0], move |_| {
+					// Earlier o-calls take longer, they would finish last if run concurrently.
+					thread::sleep(Duration::from_millis(5 * (8 - i) as u64));
+					executed.lock().unwrap().push(i);
+					Ok(())
+				})
+				.unwrap();
+		}
+		// Awaited o-calls queue up behind the detached ones.
+		dispatcher
+			.dispatch_in_lane(OCallType::SendToParentchain, vec![0], |_| Ok(()))
+			.unwrap();
+
+		assert_eq!(*executed.lock().unwrap(), (0..8).collect::<Vec<u8>>());
+	}
+
+	#[test]
+	fn lane_ocall_is_cancelled_when_its_caller_timed_out() {
+		let runtime = Runtime::new().unwrap();
+		let dispatcher = OCallDispatcher::with_limits(
+			runtime.handle().clone(),
+			[(OCallType::SendToParentchain, limits(1, 2))],
+		);
+		let (release_sender, release_receiver) = sync_channel::<()>(0);
+		let cancelled = Arc::new(AtomicBool::new(false));
+
+		dispatcher
+			.dispatch_detached_in_lane(OCallType::SendToParentchain, vec![0], move |_| {
+				release_receiver.recv().unwrap();
+				Ok(())
+			})
+			.unwrap();
+
+		let ocall_cancelled = cancelled.clone();
+		let result = dispatcher.dispatch_in_lane(
+			OCallType::SendToParentchain,
+			vec![0],
+			move |cancellation| {
+				ocall_cancelled.store(cancellation.is_cancelled(), Ordering::SeqCst);
+				Ok(())
+			},
+		);
+		assert!(matches!(result, Err(OCallBridgeError::TimedOut(OCallType::SendToParentchain))));
+
+		release_sender.send(()).unwrap();
+		let pending = &dispatcher.queue(OCallType::SendToParentchain).pending;
+		while pending.load(Ordering::SeqCst) > 0 {
+			thread::sleep(Duration::from_millis(10));
+		}
+		assert!(cancelled.load(Ordering::SeqCst));
+	}
+}
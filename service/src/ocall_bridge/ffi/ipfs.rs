@@ -16,7 +16,10 @@
 
 */
 
-use crate::ocall_bridge::bridge_api::{Bridge, Cid, IpfsBridge};
+use crate::ocall_bridge::{
+	bridge_api::{Bridge, Cid, IpfsBridge},
+	dispatcher::{OCallDispatcher, OCallType},
+};
 use log::*;
 use sgx_types::sgx_status_t;
 use std::{slice, sync::Arc};
@@ -29,13 +32,20 @@ pub unsafe extern "C" fn ocall_write_ipfs(
 	cid: *mut u8,
 	cid_size: u32,
 ) -> sgx_status_t {
-	write_ipfs(enc_state, enc_state_size, cid, cid_size, Bridge::get_ipfs_api())
+	write_ipfs(
+		enc_state,
+		enc_state_size,
+		cid,
+		cid_size,
+		Bridge::get_ipfs_api(),
+		Bridge::get_ocall_dispatcher(),
+	)
 }
 
 /// C-API exposed for o-call from enclave
 #[no_mangle]
 pub unsafe extern "C" fn ocall_read_ipfs(cid: *const u8, cid_size: u32) -> sgx_status_t {
-	read_ipfs(cid, cid_size, Bridge::get_ipfs_api())
+	read_ipfs(cid, cid_size, Bridge::get_ipfs_api(), Bridge::get_ocall_dispatcher())
 }
 
 fn write_ipfs(
@@ -44,33 +54,40 @@ fn write_ipfs(
 	cid: *mut u8,
 	cid_size: u32,
 	ipfs_api: Arc<dyn IpfsBridge>,
+	dispatcher: Arc<OCallDispatcher>,
 ) -> sgx_status_t {
-	let state = unsafe { slice::from_raw_parts(enc_state, enc_state_size as usize) };
+	// Copied, because the enclave buffer is gone once the o-call returned (e.g. on timeout).
+	let state = unsafe { slice::from_raw_parts(enc_state, enc_state_size as usize) }.to_vec();
 	let cid = unsafe { slice::from_raw_parts_mut(cid, cid_size as usize) };
 
-	return match ipfs_api.write_to_ipfs(state) {
+	return match dispatcher.dispatch(OCallType::Ipfs, move || ipfs_api.write_to_ipfs(state)) {
 		Ok(r) => {
 			cid.clone_from_slice(&r);
 			sgx_status_t::SGX_SUCCESS
 		},
 		Err(e) => {
 			error!("OCall to write_ipfs failed: {:?}", e);
-			sgx_status_t::SGX_ERROR_UNEXPECTED
+			e.into()
 		},
 	}
 }
 
-fn read_ipfs(cid: *const u8, cid_size: u32, ipfs_api: Arc<dyn IpfsBridge>) -> sgx_status_t {
+fn read_ipfs(
+	cid: *const u8,
+	cid_size: u32,
+	ipfs_api: Arc<dyn IpfsBridge>,
+	dispatcher: Arc<OCallDispatcher>,
+) -> sgx_status_t {
 	let _cid = unsafe { slice::from_raw_parts(cid, cid_size as usize) };
 
 	let mut cid: Cid = [0; 46];
 	cid.clone_from_slice(_cid);
 
-	match ipfs_api.read_from_ipfs(cid) {
+	match dispatcher.dispatch(OCallType::Ipfs, move || ipfs_api.read_from_ipfs(cid)) {
 		Ok(_) => sgx_status_t::SGX_SUCCESS,
 		Err(e) => {
 			error!("OCall to read_ipfs failed: {:?}", e);
-			sgx_status_t::SGX_ERROR_UNEXPECTED
+			e.into()
 		},
 	}
 }
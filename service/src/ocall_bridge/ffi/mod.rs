@@ -29,7 +29,9 @@ pub mod get_update_info;
 pub mod init_quote;
 pub mod ipfs;
 pub mod propose_sidechain_blocks;
+pub mod request_state_confirmations;
 pub mod send_to_parentchain;
 pub mod store_sidechain_blocks;
+pub mod tcp_relay;
 pub mod update_metric;
 pub mod worker_request;
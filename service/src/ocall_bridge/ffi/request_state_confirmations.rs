@@ -0,0 +1,126 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+use crate::ocall_bridge::bridge_api::{Bridge, SidechainBridge};
+use itp_utils::write_slice_and_whitespace_pad;
+use log::*;
+use sgx_types::sgx_status_t;
+use std::{slice, sync::Arc};
+
+/// # Safety
+///
+/// FFI are always unsafe
+#[no_mangle]
+pub unsafe extern "C" fn ocall_request_state_confirmations(
+	shard_identifier_ptr: *const u8,
+	shard_identifier_size: u32,
+	request_ptr: *const u8,
+	request_size: u32,
+	confirmations_ptr: *mut u8,
+	confirmations_size: u32,
+) -> sgx_status_t {
+	request_state_confirmations(
+		shard_identifier_ptr,
+		shard_identifier_size,
+		request_ptr,
+		request_size,
+		confirmations_ptr,
+		confirmations_size,
+		Bridge::get_sidechain_api(),
+	)
+}
+
+fn request_state_confirmations(
+	shard_identifier_ptr: *const u8,
+	shard_identifier_size: u32,
+	request_ptr: *const u8,
+	request_size: u32,
+	confirmations_ptr: *mut u8,
+	confirmations_size: u32,
+	sidechain_api: Arc<dyn SidechainBridge>,
+) -> sgx_status_t {
+	let shard_identifier_encoded = unsafe {
+		Vec::from(slice::from_raw_parts(shard_identifier_ptr, shard_identifier_size as usize))
+	};
+	let encrypted_request =
+		unsafe { Vec::from(slice::from_raw_parts(request_ptr, request_size as usize)) };
+
+	let confirmations_encoded = match sidechain_api
+		.request_state_confirmations(shard_identifier_encoded, encrypted_request)
+	{
+		Ok(r) => r,
+		Err(e) => {
+			error!("request state confirmations failed: {:?}", e);
+			return sgx_status_t::SGX_ERROR_UNEXPECTED
+		},
+	};
+
+	let confirmations_slice =
+		unsafe { slice::from_raw_parts_mut(confirmations_ptr, confirmations_size as usize) };
+	if let Err(e) = write_slice_and_whitespace_pad(confirmations_slice, confirmations_encoded) {
+		error!("Failed to transfer encoded state confirmations to o-call buffer: {:?}", e);
+		return sgx_status_t::SGX_ERROR_UNEXPECTED
+	}
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::ocall_bridge::test::mocks::sidechain_bridge_mock::SidechainBridgeMock;
+	use codec::{Decode, Encode};
+	use itp_types::{
+		state_confirmation::{ConfirmedStateUpdate, StateConfirmation},
+		ShardIdentifier,
+	};
+	use primitive_types::H256;
+	use sp_core::{ed25519, Pair};
+
+	#[test]
+	fn request_state_confirmations_works() {
+		let update = ConfirmedStateUpdate {
+			shard: ShardIdentifier::default(),
+			block_number: 1,
+			state_hash_before_execution: H256::random(),
+			state_hash_after_execution: H256::random(),
+		};
+		let confirmations =
+			vec![StateConfirmation::new(update, &ed25519::Pair::from_seed(&[1u8; 32]))];
+		let sidechain_bridge_mock = Arc::new(
+			SidechainBridgeMock::default().with_state_confirmations(confirmations.encode()),
+		);
+
+		let shard_identifier_encoded = ShardIdentifier::default().encode();
+		let request = vec![1u8, 2, 3];
+		let mut buffer = vec![0u8; 4096];
+
+		let result = request_state_confirmations(
+			shard_identifier_encoded.as_ptr(),
+			shard_identifier_encoded.len() as u32,
+			request.as_ptr(),
+			request.len() as u32,
+			buffer.as_mut_ptr(),
+			buffer.len() as u32,
+			sidechain_bridge_mock,
+		);
+
+		let decoded: Vec<StateConfirmation> = Decode::decode(&mut buffer.as_slice()).unwrap();
+
+		assert_eq!(result, sgx_status_t::SGX_SUCCESS);
+		assert_eq!(confirmations, decoded);
+	}
+}
@@ -16,7 +16,10 @@
 
 */
 
-use crate::ocall_bridge::bridge_api::{Bridge, WorkerOnChainBridge};
+use crate::ocall_bridge::{
+	bridge_api::{Bridge, WorkerOnChainBridge},
+	dispatcher::{Cancellation, OCallDispatcher, OCallType},
+};
 use log::*;
 use sgx_types::{c_int, sgx_status_t};
 use std::{slice, sync::Arc, vec::Vec};
@@ -39,6 +42,7 @@ pub unsafe extern "C" fn ocall_send_to_parentchain(
 		parentchain_id_size,
 		await_each_inclusion == 1,
 		Bridge::get_oc_api(),
+		Bridge::get_ocall_dispatcher(),
 	)
 }
 
@@ -49,6 +53,7 @@ fn send_to_parentchain(
 	parentchain_id_size: u32,
 	await_each_inclusion: bool,
 	oc_api: Arc<dyn WorkerOnChainBridge>,
+	dispatcher: Arc<OCallDispatcher>,
 ) -> sgx_status_t {
 	let extrinsics_encoded_vec: Vec<u8> = unsafe {
 		Vec::from(slice::from_raw_parts(extrinsics_encoded, extrinsics_encoded_size as usize))
@@ -57,11 +62,30 @@ fn send_to_parentchain(
 	let parentchain_id: Vec<u8> =
 		unsafe { Vec::from(slice::from_raw_parts(parentchain_id, parentchain_id_size as usize)) };
 
-	match oc_api.send_to_parentchain(extrinsics_encoded_vec, parentchain_id, await_each_inclusion) {
+	// Extrinsics are signed with consecutive nonces, so the sends to one parentchain must not
+	// overtake each other.
+	let lane = parentchain_id.clone();
+	let send = move |cancellation: &Cancellation| {
+		oc_api.send_to_parentchain(
+			extrinsics_encoded_vec,
+			parentchain_id,
+			await_each_inclusion,
+			cancellation.clone(),
+		)
+	};
+
+	// Without awaiting the inclusion, there is nothing the enclave has to wait for.
+	let result = if await_each_inclusion {
+		dispatcher.dispatch_in_lane(OCallType::SendToParentchain, lane, send)
+	} else {
+		dispatcher.dispatch_detached_in_lane(OCallType::SendToParentchain, lane, send)
+	};
+
+	match result {
 		Ok(_) => sgx_status_t::SGX_SUCCESS,
 		Err(e) => {
 			error!("send extrinsics_encoded failed: {:?}", e);
-			sgx_status_t::SGX_ERROR_UNEXPECTED
+			e.into()
 		},
 	}
 }
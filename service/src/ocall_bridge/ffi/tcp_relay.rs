@@ -0,0 +1,176 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+use crate::ocall_bridge::{
+	bridge_api::{Bridge, HttpsClientBridge},
+	dispatcher::{OCallDispatcher, OCallType},
+};
+use log::*;
+use sgx_types::sgx_status_t;
+use std::{slice, str, sync::Arc};
+
+/// C-API exposed for o-call from enclave
+#[no_mangle]
+pub unsafe extern "C" fn ocall_tcp_connect(
+	host: *const u8,
+	host_size: u32,
+	port: u16,
+	connection: *mut u32,
+) -> sgx_status_t {
+	tcp_connect(
+		host,
+		host_size,
+		port,
+		connection,
+		Bridge::get_https_client_api(),
+		Bridge::get_ocall_dispatcher(),
+	)
+}
+
+/// C-API exposed for o-call from enclave
+#[no_mangle]
+pub unsafe extern "C" fn ocall_tcp_send(
+	connection: u32,
+	data: *const u8,
+	data_size: u32,
+) -> sgx_status_t {
+	tcp_send(
+		connection,
+		data,
+		data_size,
+		Bridge::get_https_client_api(),
+		Bridge::get_ocall_dispatcher(),
+	)
+}
+
+/// C-API exposed for o-call from enclave
+#[no_mangle]
+pub unsafe extern "C" fn ocall_tcp_receive(
+	connection: u32,
+	buffer: *mut u8,
+	buffer_size: u32,
+	received: *mut u32,
+) -> sgx_status_t {
+	tcp_receive(
+		connection,
+		buffer,
+		buffer_size,
+		received,
+		Bridge::get_https_client_api(),
+		Bridge::get_ocall_dispatcher(),
+	)
+}
+
+/// C-API exposed for o-call from enclave
+#[no_mangle]
+pub unsafe extern "C" fn ocall_tcp_close(connection: u32) -> sgx_status_t {
+	tcp_close(connection, Bridge::get_https_client_api(), Bridge::get_ocall_dispatcher())
+}
+
+fn tcp_connect(
+	host: *const u8,
+	host_size: u32,
+	port: u16,
+	connection: *mut u32,
+	https_client_api: Arc<dyn HttpsClientBridge>,
+	dispatcher: Arc<OCallDispatcher>,
+) -> sgx_status_t {
+	let host_slice = unsafe { slice::from_raw_parts(host, host_size as usize) };
+	let host = match str::from_utf8(host_slice) {
+		Ok(host) => host.to_string(),
+		Err(e) => {
+			error!("OCall to tcp_connect failed, host is not valid UTF-8: {:?}", e);
+			return sgx_status_t::SGX_ERROR_INVALID_PARAMETER
+		},
+	};
+
+	match dispatcher.dispatch(OCallType::TcpRelay, move || https_client_api.tcp_connect(host, port))
+	{
+		Ok(id) => {
+			unsafe { *connection = id };
+			sgx_status_t::SGX_SUCCESS
+		},
+		Err(e) => {
+			error!("OCall to tcp_connect failed: {:?}", e);
+			e.into()
+		},
+	}
+}
+
+fn tcp_send(
+	connection: u32,
+	data: *const u8,
+	data_size: u32,
+	https_client_api: Arc<dyn HttpsClientBridge>,
+	dispatcher: Arc<OCallDispatcher>,
+) -> sgx_status_t {
+	// Copied, because the enclave buffer is gone once the o-call returned (e.g. on timeout).
+	let data = unsafe { slice::from_raw_parts(data, data_size as usize) }.to_vec();
+
+	match dispatcher
+		.dispatch(OCallType::TcpRelay, move || https_client_api.tcp_send(connection, data))
+	{
+		Ok(_) => sgx_status_t::SGX_SUCCESS,
+		Err(e) => {
+			error!("OCall to tcp_send failed: {:?}", e);
+			e.into()
+		},
+	}
+}
+
+fn tcp_receive(
+	connection: u32,
+	buffer: *mut u8,
+	buffer_size: u32,
+	received: *mut u32,
+	https_client_api: Arc<dyn HttpsClientBridge>,
+	dispatcher: Arc<OCallDispatcher>,
+) -> sgx_status_t {
+	let max_len = buffer_size as usize;
+
+	match dispatcher
+		.dispatch(OCallType::TcpRelay, move || https_client_api.tcp_receive(connection, max_len))
+	{
+		Ok(data) if data.len() <= max_len => {
+			let buffer = unsafe { slice::from_raw_parts_mut(buffer, max_len) };
+			buffer[..data.len()].copy_from_slice(&data);
+			unsafe { *received = data.len() as u32 };
+			sgx_status_t::SGX_SUCCESS
+		},
+		Ok(data) => {
+			error!("OCall to tcp_receive returned {} bytes, at most {} fit", data.len(), max_len);
+			sgx_status_t::SGX_ERROR_UNEXPECTED
+		},
+		Err(e) => {
+			error!("OCall to tcp_receive failed: {:?}", e);
+			e.into()
+		},
+	}
+}
+
+fn tcp_close(
+	connection: u32,
+	https_client_api: Arc<dyn HttpsClientBridge>,
+	dispatcher: Arc<OCallDispatcher>,
+) -> sgx_status_t {
+	match dispatcher.dispatch(OCallType::TcpRelay, move || https_client_api.tcp_close(connection)) {
+		Ok(_) => sgx_status_t::SGX_SUCCESS,
+		Err(e) => {
+			error!("OCall to tcp_close failed: {:?}", e);
+			e.into()
+		},
+	}
+}
@@ -16,7 +16,10 @@
 
 */
 
-use crate::ocall_bridge::bridge_api::{Bridge, MetricsBridge};
+use crate::ocall_bridge::{
+	bridge_api::{Bridge, MetricsBridge},
+	dispatcher::{OCallDispatcher, OCallType},
+};
 use log::*;
 use sgx_types::sgx_status_t;
 use std::{slice, sync::Arc};
@@ -29,22 +32,30 @@ pub unsafe extern "C" fn ocall_update_metric(
 	metric_ptr: *const u8,
 	metric_size: u32,
 ) -> sgx_status_t {
-	update_metric(metric_ptr, metric_size, Bridge::get_metrics_api())
+	update_metric(
+		metric_ptr,
+		metric_size,
+		Bridge::get_metrics_api(),
+		Bridge::get_ocall_dispatcher(),
+	)
 }
 
 fn update_metric(
 	metric_ptr: *const u8,
 	metric_size: u32,
 	oc_api: Arc<dyn MetricsBridge>,
+	dispatcher: Arc<OCallDispatcher>,
 ) -> sgx_status_t {
 	let metric_encoded: Vec<u8> =
 		unsafe { Vec::from(slice::from_raw_parts(metric_ptr, metric_size as usize)) };
 
-	match oc_api.update_metric(metric_encoded) {
+	match dispatcher
+		.dispatch_detached(OCallType::UpdateMetric, move || oc_api.update_metric(metric_encoded))
+	{
 		Ok(_) => sgx_status_t::SGX_SUCCESS,
 		Err(e) => {
 			error!("update_metric o-call failed: {:?}", e);
-			sgx_status_t::SGX_ERROR_UNEXPECTED
+			e.into()
 		},
 	}
 }
@@ -0,0 +1,175 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+use crate::ocall_bridge::bridge_api::{HttpsClientBridge, OCallBridgeError, OCallBridgeResult};
+use itp_ocall_api::TcpConnectionId;
+use log::*;
+use parking_lot::RwLock;
+use std::{
+	collections::HashMap,
+	io::{ErrorKind, Read, Write},
+	net::{TcpStream, ToSocketAddrs},
+	sync::{
+		atomic::{AtomicU32, Ordering},
+		Arc,
+	},
+	time::Duration,
+};
+
+/// Maximal number of connections the enclave can have open at the same time.
+const MAX_OPEN_CONNECTIONS: usize = 64;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const READ_WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Opens TCP connections on behalf of the enclave and relays their bytes.
+///
+/// The connections are kept until the enclave closes them, so the same instance must serve
+/// all the relay o-calls.
+pub struct HttpsClientOCall {
+	connections: RwLock<HashMap<TcpConnectionId, Arc<TcpStream>>>,
+	next_connection: AtomicU32,
+}
+
+impl Default for HttpsClientOCall {
+	fn default() -> Self {
+		HttpsClientOCall { connections: Default::default(), next_connection: AtomicU32::new(1) }
+	}
+}
+
+impl HttpsClientOCall {
+	fn connection(&self, connection: TcpConnectionId) -> OCallBridgeResult<Arc<TcpStream>> {
+		self.connections
+			.read()
+			.get(&connection)
+			.cloned()
+			.ok_or_else(|| OCallBridgeError::TcpRelay(format!("Unknown connection {}", connection)))
+	}
+}
+
+fn relay_error(context: &str, e: std::io::Error) -> OCallBridgeError {
+	OCallBridgeError::TcpRelay(format!("{}: {}", context, e))
+}
+
+impl HttpsClientBridge for HttpsClientOCall {
+	fn tcp_connect(&self, host: String, port: u16) -> OCallBridgeResult<TcpConnectionId> {
+		if self.connections.read().len() >= MAX_OPEN_CONNECTIONS {
+			return Err(OCallBridgeError::TcpRelay(format!(
+				"Too many open connections ({})",
+				MAX_OPEN_CONNECTIONS
+			)))
+		}
+
+		let addresses = (host.as_str(), port)
+			.to_socket_addrs()
+			.map_err(|e| relay_error(&format!("Resolving {}", host), e))?;
+
+		let mut last_error = None;
+		let stream = addresses
+			.into_iter()
+			.find_map(|address| match TcpStream::connect_timeout(&address, CONNECT_TIMEOUT) {
+				Ok(stream) => Some(stream),
+				Err(e) => {
+					last_error = Some(e);
+					None
+				},
+			})
+			.ok_or_else(|| {
+				OCallBridgeError::TcpRelay(format!(
+					"Connecting to {}:{} failed: {:?}",
+					host, port, last_error
+				))
+			})?;
+
+		stream
+			.set_read_timeout(Some(READ_WRITE_TIMEOUT))
+			.and_then(|_| stream.set_write_timeout(Some(READ_WRITE_TIMEOUT)))
+			.map_err(|e| relay_error("Setting timeouts", e))?;
+
+		let connection = self.next_connection.fetch_add(1, Ordering::SeqCst);
+		self.connections.write().insert(connection, Arc::new(stream));
+		debug!("Opened TCP connection {} to {}:{} for the enclave", connection, host, port);
+		Ok(connection)
+	}
+
+	fn tcp_send(&self, connection: TcpConnectionId, data: Vec<u8>) -> OCallBridgeResult<()> {
+		let stream = self.connection(connection)?;
+		(&*stream).write_all(&data).map_err(|e| relay_error("Sending", e))
+	}
+
+	fn tcp_receive(
+		&self,
+		connection: TcpConnectionId,
+		max_len: usize,
+	) -> OCallBridgeResult<Vec<u8>> {
+		let stream = self.connection(connection)?;
+		let mut buffer = vec![0u8; max_len];
+		loop {
+			match (&*stream).read(&mut buffer) {
+				Ok(received) => {
+					buffer.truncate(received);
+					return Ok(buffer)
+				},
+				Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+				Err(e) => return Err(relay_error("Receiving", e)),
+			}
+		}
+	}
+
+	fn tcp_close(&self, connection: TcpConnectionId) -> OCallBridgeResult<()> {
+		// The stream is closed once the last reference is dropped.
+		self.connections.write().remove(&connection);
+		debug!("Closed TCP connection {} of the enclave", connection);
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{net::TcpListener, thread};
+
+	#[test]
+	fn bytes_are_relayed_until_the_connection_is_closed() {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let port = listener.local_addr().unwrap().port();
+		let server = thread::spawn(move || {
+			let (mut stream, _) = listener.accept().unwrap();
+			let mut request = [0u8; 5];
+			stream.read_exact(&mut request).unwrap();
+			stream.write_all(b"echo ").unwrap();
+			stream.write_all(&request).unwrap();
+		});
+
+		let relay = HttpsClientOCall::default();
+		let connection = relay.tcp_connect("127.0.0.1".to_string(), port).unwrap();
+		relay.tcp_send(connection, b"hello".to_vec()).unwrap();
+		server.join().unwrap();
+
+		let mut received = Vec::new();
+		loop {
+			let chunk = relay.tcp_receive(connection, 4).unwrap();
+			if chunk.is_empty() {
+				break
+			}
+			assert!(chunk.len() <= 4);
+			received.extend(chunk);
+		}
+		assert_eq!(received, b"echo hello".to_vec());
+
+		relay.tcp_close(connection).unwrap();
+		assert!(relay.tcp_receive(connection, 4).is_err());
+	}
+}
@@ -36,7 +36,7 @@ impl<MetricsReceiver> MetricsOCall<MetricsReceiver> {
 
 impl<MetricsReceiver> MetricsBridge for MetricsOCall<MetricsReceiver>
 where
-	MetricsReceiver: ReceiveEnclaveMetrics,
+	MetricsReceiver: ReceiveEnclaveMetrics + Send + Sync,
 {
 	fn update_metric(&self, metric_encoded: Vec<u8>) -> OCallBridgeResult<()> {
 		let metric: EnclaveMetric =
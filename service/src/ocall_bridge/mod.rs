@@ -20,9 +20,11 @@
 
 pub mod bridge_api;
 pub mod component_factory;
+pub mod dispatcher;
 
 mod ffi;
-mod ipfs_ocall;
+mod https_client_ocall;
+pub mod ipfs_ocall;
 mod metrics_ocall;
 mod remote_attestation_ocall;
 mod sidechain_ocall;
@@ -19,7 +19,7 @@
 use crate::{
 	globals::tokio_handle::GetTokioHandle,
 	ocall_bridge::bridge_api::{OCallBridgeError, OCallBridgeResult, SidechainBridge},
-	sync_block_broadcaster::BroadcastBlocks,
+	sync_block_broadcaster::{BroadcastBlocks, RequestStateConfirmations},
 	worker_peers_updater::UpdateWorkerPeers,
 };
 use codec::{Decode, Encode};
@@ -61,7 +61,7 @@ impl<BlockBroadcaster, Storage, PeerUpdater, PeerBlockFetcher, TokioHandle>
 impl<BlockBroadcaster, Storage, PeerUpdater, PeerBlockFetcher, TokioHandle> SidechainBridge
 	for SidechainOCall<BlockBroadcaster, Storage, PeerUpdater, PeerBlockFetcher, TokioHandle>
 where
-	BlockBroadcaster: BroadcastBlocks,
+	BlockBroadcaster: BroadcastBlocks + RequestStateConfirmations,
 	Storage: BlockStorage<SignedSidechainBlock>,
 	PeerUpdater: UpdateWorkerPeers,
 	PeerBlockFetcher: FetchBlocksFromPeer<SignedBlockType = SignedSidechainBlock>,
@@ -187,6 +187,35 @@ where
 
 		Ok(signed_sidechain_blocks.encode())
 	}
+
+	fn request_state_confirmations(
+		&self,
+		shard_identifier_encoded: Vec<u8>,
+		encrypted_request: Vec<u8>,
+	) -> OCallBridgeResult<Vec<u8>> {
+		let shard_identifier: ShardIdentifier =
+			Decode::decode(&mut shard_identifier_encoded.as_slice()).map_err(|_| {
+				OCallBridgeError::RequestStateConfirmations(
+					"Failed to decode shard identifier".to_string(),
+				)
+			})?;
+
+		info!("[O-call] requesting state update confirmations for shard {:?}", shard_identifier);
+
+		let confirmations = self
+			.block_broadcaster
+			.request_state_confirmations(encrypted_request)
+			.map_err(|e| {
+			OCallBridgeError::RequestStateConfirmations(format!(
+				"Failed to request state confirmations from peers: {:?}",
+				e
+			))
+		})?;
+
+		info!("[O-call] received {} state update confirmations", confirmations.len());
+
+		Ok(confirmations.encode())
+	}
 }
 
 #[cfg(test)]
@@ -21,6 +21,7 @@ use crate::ocall_bridge::bridge_api::{OCallBridgeResult, SidechainBridge};
 #[derive(Default)]
 pub struct SidechainBridgeMock {
 	peer_blocks_encoded: Vec<u8>,
+	state_confirmations_encoded: Vec<u8>,
 }
 
 impl SidechainBridgeMock {
@@ -28,6 +29,11 @@ impl SidechainBridgeMock {
 		self.peer_blocks_encoded = blocks_encoded;
 		self
 	}
+
+	pub fn with_state_confirmations(mut self, confirmations_encoded: Vec<u8>) -> Self {
+		self.state_confirmations_encoded = confirmations_encoded;
+		self
+	}
 }
 
 impl SidechainBridge for SidechainBridgeMock {
@@ -47,4 +53,12 @@ impl SidechainBridge for SidechainBridgeMock {
 	) -> OCallBridgeResult<Vec<u8>> {
 		Ok(self.peer_blocks_encoded.clone())
 	}
+
+	fn request_state_confirmations(
+		&self,
+		_shard_identifier_encoded: Vec<u8>,
+		_encrypted_request: Vec<u8>,
+	) -> OCallBridgeResult<Vec<u8>> {
+		Ok(self.state_confirmations_encoded.clone())
+	}
 }
@@ -16,7 +16,10 @@
 
 */
 
-use crate::ocall_bridge::bridge_api::{OCallBridgeError, OCallBridgeResult, WorkerOnChainBridge};
+use crate::ocall_bridge::{
+	bridge_api::{OCallBridgeError, OCallBridgeResult, WorkerOnChainBridge},
+	dispatcher::Cancellation,
+};
 use codec::{Decode, Encode};
 use itp_api_client_types::ParentchainApi;
 use itp_node_api::node_api_factory::CreateNodeApi;
@@ -68,7 +71,7 @@ impl<F: CreateNodeApi> WorkerOnChainOCall<F> {
 
 impl<F> WorkerOnChainBridge for WorkerOnChainOCall<F>
 where
-	F: CreateNodeApi,
+	F: CreateNodeApi + Send + Sync,
 {
 	fn worker_request(
 		&self,
@@ -110,6 +113,7 @@ where
 		extrinsics_encoded: Vec<u8>,
 		parentchain_id: Vec<u8>,
 		await_each_inlcusion: bool,
+		cancellation: Cancellation,
 	) -> OCallBridgeResult<()> {
 		// TODO: improve error handling, using a mut status is not good design?
 		let mut status: OCallBridgeResult<()> = Ok(());
@@ -134,7 +138,9 @@ where
 			);
 			let api = self.create_api(parentchain_id)?;
 			for call in extrinsics.into_iter() {
-				if await_each_inlcusion {
+				// Once the enclave stopped waiting, the remaining extrinsics are still submitted,
+				// dropping them would leave a gap in the nonces of the enclave account.
+				if await_each_inlcusion && !cancellation.is_cancelled() {
 					if let Err(e) = api.submit_and_watch_opaque_extrinsic_until(
 						&call.encode().into(),
 						XtStatus::InBlock,
@@ -23,6 +23,7 @@ use crate::teeracle::teeracle_metrics::update_teeracle_metrics;
 use crate::{
 	account_funding::EnclaveAccountInfo,
 	error::{Error, ServiceResult},
+	watchdog::Watchdog,
 };
 use async_trait::async_trait;
 use codec::{Decode, Encode};
@@ -38,7 +39,10 @@ use itc_rest_client::{
 use itp_enclave_metrics::EnclaveMetric;
 use lazy_static::lazy_static;
 use log::*;
-use prometheus::{proto::MetricFamily, register_int_gauge, IntGauge};
+use prometheus::{
+	proto::MetricFamily, register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
+	IntCounterVec, IntGauge, IntGaugeVec,
+};
 use serde::{Deserialize, Serialize};
 use std::{net::SocketAddr, sync::Arc};
 use warp::{Filter, Rejection, Reply};
@@ -55,6 +59,16 @@ lazy_static! {
 	static ref ENCLAVE_SIDECHAIN_TOP_POOL_SIZE: IntGauge =
 		register_int_gauge!("integritee_worker_enclave_sidechain_top_pool_size", "Enclave sidechain top pool size")
 			.unwrap();
+	static ref ENCLAVE_HEAP_USAGE: IntGaugeVec =
+		register_int_gauge_vec!("integritee_worker_enclave_heap_usage", "Enclave heap usage in bytes per subsystem", &["subsystem"])
+			.unwrap();
+	static ref ENCLAVE_THREAD_RESTARTS: IntCounterVec =
+		register_int_counter_vec!("integritee_worker_enclave_thread_restarts", "Number of restarts of stuck enclave threads by the watchdog", &["thread"])
+			.unwrap();
+}
+
+pub(crate) fn record_enclave_thread_restart(thread: &str) {
+	ENCLAVE_THREAD_RESTARTS.with_label_values(&[thread]).inc();
 }
 
 pub async fn start_metrics_server<MetricsHandler>(
@@ -154,7 +168,15 @@ pub trait ReceiveEnclaveMetrics {
 	fn receive_enclave_metric(&self, metric: EnclaveMetric) -> ServiceResult<()>;
 }
 
-pub struct EnclaveMetricsReceiver;
+pub struct EnclaveMetricsReceiver {
+	watchdog: Arc<Watchdog>,
+}
+
+impl EnclaveMetricsReceiver {
+	pub fn new(watchdog: Arc<Watchdog>) -> Self {
+		EnclaveMetricsReceiver { watchdog }
+	}
+}
 
 impl ReceiveEnclaveMetrics for EnclaveMetricsReceiver {
 	fn receive_enclave_metric(&self, metric: EnclaveMetric) -> ServiceResult<()> {
@@ -171,6 +193,12 @@ impl ReceiveEnclaveMetrics for EnclaveMetricsReceiver {
 			EnclaveMetric::TopPoolSizeDecrement => {
 				ENCLAVE_SIDECHAIN_TOP_POOL_SIZE.dec();
 			},
+			EnclaveMetric::HeapUsage(subsystem, usage) => {
+				ENCLAVE_HEAP_USAGE.with_label_values(&[subsystem.as_str()]).set(usage as i64);
+			},
+			EnclaveMetric::Heartbeat(thread) => {
+				self.watchdog.heartbeat(&thread);
+			},
 			#[cfg(feature = "teeracle")]
 			EnclaveMetric::ExchangeRateOracle(m) => update_teeracle_metrics(m)?,
 			#[cfg(not(feature = "teeracle"))]
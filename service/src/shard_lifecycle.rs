@@ -0,0 +1,220 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Genesis config file of the `init-shard` command, in JSON or, with a `.toml` extension, TOML.
+//!
+//! ```json
+//! {
+//!   "authorities": ["5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"],
+//!   "vault_mode": "proxied",
+//!   "endowed_accounts": [{ "account": "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty", "free": 1000000 }],
+//!   "access_policy": { "require-role": 1 },
+//!   "members": [{ "account": "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty", "roles": [1] }],
+//!   "stf_version": 1
+//! }
+//! ```
+//!
+//! All fields are optional. The authorities are the ss58 encoded enclave accounts that may author
+//! sidechain blocks of the shard, all enclaves may author if there are none. The vault mode is
+//! either `proxied` (the default) or `no-vault`. The endowed accounts are funded with the free
+//! balance in the initial state of the shard. The access policy is `open` (the default),
+//! `allow-list` or `require-role`, see `itp_stf_primitives::shard_acl`. If the STF version is set,
+//! the shard is only created by an enclave with that STF version.
+
+use crate::error::{Error, ServiceResult};
+use itp_stf_primitives::shard_acl::{AccessPolicy, MemberRoles, ShardRole};
+use itp_types::{
+	shard_lifecycle::{ShardGenesisConfig, VaultMode},
+	AccountId, Balance,
+};
+use serde::Deserialize;
+use sp_core::crypto::Ss58Codec;
+use std::{fs, path::Path};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GenesisConfigFile {
+	#[serde(default)]
+	authorities: Vec<String>,
+	#[serde(default)]
+	vault_mode: VaultModeFile,
+	#[serde(default)]
+	endowed_accounts: Vec<EndowedAccountFile>,
+	#[serde(default)]
+	access_policy: AccessPolicyFile,
+	#[serde(default)]
+	members: Vec<MemberFile>,
+	stf_version: Option<u32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum VaultModeFile {
+	#[default]
+	Proxied,
+	NoVault,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EndowedAccountFile {
+	account: String,
+	free: Balance,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum AccessPolicyFile {
+	#[default]
+	Open,
+	AllowList,
+	RequireRole(ShardRole),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MemberFile {
+	account: String,
+	#[serde(default)]
+	roles: MemberRoles,
+}
+
+pub(crate) fn read_genesis_config(path: &str) -> ServiceResult<ShardGenesisConfig> {
+	let content = fs::read_to_string(path).map_err(|e| Error::Custom(Box::new(e)))?;
+	let file = if Path::new(path).extension().map_or(false, |ext| ext == "toml") {
+		toml::from_str(&content).map_err(|e| Error::Custom(Box::new(e)))?
+	} else {
+		serde_json::from_str(&content)?
+	};
+	genesis_config_from_file(file)
+}
+
+fn genesis_config_from_file(file: GenesisConfigFile) -> ServiceResult<ShardGenesisConfig> {
+	let authorities = file
+		.authorities
+		.iter()
+		.map(|a| parse_account(a))
+		.collect::<ServiceResult<Vec<_>>>()?;
+	let vault_mode = match file.vault_mode {
+		VaultModeFile::Proxied => VaultMode::Proxied,
+		VaultModeFile::NoVault => VaultMode::NoVault,
+	};
+	let endowed_accounts = file
+		.endowed_accounts
+		.into_iter()
+		.map(|e| Ok((parse_account(&e.account)?, e.free)))
+		.collect::<ServiceResult<Vec<_>>>()?;
+	let access_policy = match file.access_policy {
+		AccessPolicyFile::Open => AccessPolicy::Open,
+		AccessPolicyFile::AllowList => AccessPolicy::AllowList,
+		AccessPolicyFile::RequireRole(role) => AccessPolicy::RequireRole(role),
+	};
+	let members = file
+		.members
+		.into_iter()
+		.map(|m| Ok((parse_account(&m.account)?, m.roles)))
+		.collect::<ServiceResult<Vec<_>>>()?;
+
+	Ok(ShardGenesisConfig {
+		authorities,
+		vault_mode,
+		endowed_accounts,
+		access_policy,
+		members,
+		stf_version: file.stf_version,
+	})
+}
+
+fn parse_account(account: &str) -> ServiceResult<AccountId> {
+	AccountId::from_ss58check(account)
+		.map_err(|e| Error::Custom(format!("Invalid account {}: {:?}", account, e).into()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_keyring::AccountKeyring;
+
+	fn parse_json(json: &str) -> ServiceResult<ShardGenesisConfig> {
+		genesis_config_from_file(serde_json::from_str(json)?)
+	}
+
+	#[test]
+	fn empty_genesis_config_is_default() {
+		assert_eq!(parse_json("{}").unwrap(), ShardGenesisConfig::default());
+	}
+
+	#[test]
+	fn genesis_config_is_parsed() {
+		let alice = AccountKeyring::Alice.to_account_id();
+		let bob = AccountKeyring::Bob.to_account_id();
+		let json = format!(
+			r#"{{
+				"authorities": ["{alice}"],
+				"vault_mode": "no-vault",
+				"endowed_accounts": [{{ "account": "{bob}", "free": 1000 }}],
+				"access_policy": {{ "require-role": 2 }},
+				"members": [{{ "account": "{bob}", "roles": [2] }}],
+				"stf_version": 1
+			}}"#,
+			alice = alice.to_ss58check(),
+			bob = bob.to_ss58check()
+		);
+
+		assert_eq!(
+			parse_json(&json).unwrap(),
+			ShardGenesisConfig {
+				authorities: vec![alice],
+				vault_mode: VaultMode::NoVault,
+				endowed_accounts: vec![(bob.clone(), 1000)],
+				access_policy: AccessPolicy::RequireRole(2),
+				members: vec![(bob, vec![2])],
+				stf_version: Some(1),
+			}
+		);
+	}
+
+	#[test]
+	fn toml_genesis_config_is_parsed() {
+		let bob = AccountKeyring::Bob.to_account_id();
+		let toml = format!(
+			r#"
+			access_policy = "allow-list"
+
+			[[members]]
+			account = "{bob}"
+			"#,
+			bob = bob.to_ss58check()
+		);
+
+		let config = genesis_config_from_file(toml::from_str(&toml).unwrap()).unwrap();
+
+		assert_eq!(config.access_policy, AccessPolicy::AllowList);
+		assert_eq!(config.members, vec![(bob, vec![])]);
+	}
+
+	#[test]
+	fn invalid_account_is_rejected() {
+		assert!(parse_json(r#"{ "authorities": ["alice"] }"#).is_err());
+		assert!(parse_json(r#"{ "endowed_accounts": [{ "account": "bob", "free": 1 }] }"#).is_err());
+	}
+
+	#[test]
+	fn unknown_field_is_rejected() {
+		assert!(parse_json(r#"{ "vault": "proxied" }"#).is_err());
+	}
+}
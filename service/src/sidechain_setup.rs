@@ -19,17 +19,19 @@ use crate::{
 	config::Config,
 	error::{Error, ServiceResult},
 	parentchain_handler::HandleParentchain,
+	watchdog::{Watchdog, SLOT_WORKER_TIMEOUT},
 };
 use futures::executor::block_on;
 use itp_enclave_api::{
 	direct_request::DirectRequest, enclave_base::EnclaveBase, sidechain::Sidechain,
 };
+use itp_enclave_metrics::SLOT_WORKER_THREAD;
 use itp_settings::{
-	files::{SIDECHAIN_PURGE_INTERVAL, SIDECHAIN_PURGE_LIMIT},
+	files::SIDECHAIN_PURGE_INTERVAL, secure_time::REFERENCE_TIME_REFRESH_INTERVAL,
 	sidechain::SLOT_DURATION,
 };
-use itp_types::Header;
-use its_consensus_slots::start_slot_worker;
+use itp_types::{Header, ShardIdentifier};
+use its_consensus_slots::SlotStream;
 use its_primitives::types::block::SignedBlock as SignedSidechainBlock;
 use its_storage::{interface::FetchBlocks, start_sidechain_pruning_loop, BlockPruner};
 use log::*;
@@ -40,6 +42,7 @@ pub(crate) fn sidechain_start_untrusted_rpc_server<Enclave, SidechainStorage>(
 	config: &Config,
 	enclave: Arc<Enclave>,
 	sidechain_storage: Arc<SidechainStorage>,
+	shard: ShardIdentifier,
 	tokio_handle: Handle,
 ) where
 	Enclave: DirectRequest + Clone,
@@ -48,7 +51,7 @@ pub(crate) fn sidechain_start_untrusted_rpc_server<Enclave, SidechainStorage>(
 	let untrusted_url = config.untrusted_worker_url();
 	println!("[+] Untrusted RPC server listening on {}", &untrusted_url);
 	let _untrusted_rpc_join_handle = tokio_handle.spawn(async move {
-		itc_rpc_server::run_server(&untrusted_url, enclave, sidechain_storage)
+		itc_rpc_server::run_server(&untrusted_url, enclave, sidechain_storage, shard)
 			.await
 			.unwrap();
 	});
@@ -61,6 +64,8 @@ pub(crate) fn sidechain_init_block_production<Enclave, SidechainStorage, Parentc
 	parentchain_handler: Arc<ParentchainHandler>,
 	sidechain_storage: Arc<SidechainStorage>,
 	last_synced_header: &Header,
+	block_retention: u64,
+	watchdog: &Arc<Watchdog>,
 ) -> ServiceResult<Header>
 where
 	Enclave: EnclaveBase + Sidechain,
@@ -87,17 +92,39 @@ where
 
 	// ------------------------------------------------------------------------
 	// Start interval sidechain block production (execution of trusted calls, sidechain block production).
+	let reference_time_enclave_api = enclave.clone();
 	let sidechain_enclave_api = enclave;
 	println!("[+] Spawning thread for sidechain block production");
+	watchdog.supervise(SLOT_WORKER_THREAD, SLOT_WORKER_TIMEOUT, move |lease| {
+		let sidechain_enclave_api = sidechain_enclave_api.clone();
+		thread::Builder::new()
+			.name("interval_block_production_timer".to_owned())
+			.spawn(move || {
+				block_on(async {
+					let mut slot_stream = SlotStream::new(SLOT_DURATION);
+					loop {
+						slot_stream.next_slot().await;
+						// Stop once the watchdog has restarted us, the new instance takes over.
+						if !lease.is_current() {
+							break
+						}
+						execute_trusted_calls(sidechain_enclave_api.as_ref())
+					}
+				});
+				println!("[!] Sidechain block production loop has terminated");
+			})
+			.expect("Failed to spawn sidechain block production thread");
+	});
+
+	// ------------------------------------------------------------------------
+	// Refresh the reference time off the slot path, the block production only reads the outcome.
 	thread::Builder::new()
-		.name("interval_block_production_timer".to_owned())
-		.spawn(move || {
-			let future = start_slot_worker(
-				|| execute_trusted_calls(sidechain_enclave_api.as_ref()),
-				SLOT_DURATION,
-			);
-			block_on(future);
-			println!("[!] Sidechain block production loop has terminated");
+		.name("reference_time_refresh".to_owned())
+		.spawn(move || loop {
+			if let Err(e) = reference_time_enclave_api.refresh_reference_time() {
+				warn!("Reference time check failed: {:?}", e);
+			}
+			thread::sleep(REFERENCE_TIME_REFRESH_INTERVAL);
 		})
 		.map_err(|e| Error::Custom(Box::new(e)))?;
 
@@ -109,7 +136,7 @@ where
 			start_sidechain_pruning_loop(
 				&sidechain_storage,
 				SIDECHAIN_PURGE_INTERVAL,
-				SIDECHAIN_PURGE_LIMIT,
+				block_retention,
 			);
 		})
 		.map_err(|e| Error::Custom(Box::new(e)))?;
@@ -23,8 +23,9 @@ use mockall::*;
 
 use crate::{
 	globals::tokio_handle::GetTokioHandle,
-	worker::{AsyncBlockBroadcaster, WorkerResult},
+	worker::{AsyncBlockBroadcaster, AsyncStateConfirmationRequester, WorkerResult},
 };
+use itp_types::state_confirmation::StateConfirmation;
 use its_primitives::types::block::SignedBlock as SignedSidechainBlock;
 use std::sync::Arc;
 
@@ -34,6 +35,16 @@ pub trait BroadcastBlocks {
 	fn broadcast_blocks(&self, blocks: Vec<SignedSidechainBlock>) -> WorkerResult<()>;
 }
 
+/// Allows to request the confirmation of a state update from the peers, in a synchronous
+/// (i.e. blocking) manner.
+#[cfg_attr(test, automock)]
+pub trait RequestStateConfirmations {
+	fn request_state_confirmations(
+		&self,
+		encrypted_request: Vec<u8>,
+	) -> WorkerResult<Vec<StateConfirmation>>;
+}
+
 pub struct SyncBlockBroadcaster<T, W> {
 	tokio_handle: Arc<T>,
 	worker: Arc<W>,
@@ -55,3 +66,17 @@ where
 		handle.block_on(self.worker.broadcast_blocks(blocks))
 	}
 }
+
+impl<T, W> RequestStateConfirmations for SyncBlockBroadcaster<T, W>
+where
+	T: GetTokioHandle,
+	W: AsyncStateConfirmationRequester,
+{
+	fn request_state_confirmations(
+		&self,
+		encrypted_request: Vec<u8>,
+	) -> WorkerResult<Vec<StateConfirmation>> {
+		let handle = self.tokio_handle.get_handle();
+		handle.block_on(self.worker.request_state_confirmations(encrypted_request))
+	}
+}
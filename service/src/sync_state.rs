@@ -18,6 +18,7 @@
 
 use crate::{
 	enclave::tls_ra::enclave_request_state_provisioning,
+	enclave_upgrade::{hand_over_keys_from_old_enclave, is_pending_upgrade_target},
 	error::{Error, ServiceResult as Result},
 };
 use futures::executor;
@@ -42,6 +43,21 @@ pub(crate) fn sync_state<
 	enclave_api: &E,
 	skip_ra: bool,
 ) {
+	// The mutual RA provisioning requires an equal MRENCLAVE, the keys of an upgraded enclave
+	// are handed over by local attestation instead.
+	if is_pending_upgrade_target(node_api, shard, enclave_api)
+		.expect("Could not read the shard config from the parentchain")
+	{
+		let old_worker_url = executor::block_on(get_url_of_primary_worker(node_api, shard))
+			.expect("Worker of the upgraded enclave could not be found");
+		println!("Requesting the keys of the upgraded enclave from worker at {}", &old_worker_url);
+
+		hand_over_keys_from_old_enclave(enclave_api, &DirectWorkerApi::new(old_worker_url), shard)
+			.unwrap();
+		println!("[+] Keys and state successfully handed over by the upgraded enclave.");
+		return
+	}
+
 	// FIXME: we now assume that keys are equal for all shards.
 	let provider_url = match WorkerModeProvider::worker_mode() {
 		WorkerMode::Sidechain =>
@@ -73,13 +89,21 @@ pub(crate) fn sync_state<
 async fn get_author_url_of_last_finalized_sidechain_block<NodeApi: PalletTeerexApi>(
 	node_api: &NodeApi,
 	shard: &ShardIdentifier,
+) -> Result<String> {
+	let worker_api_direct = DirectWorkerApi::new(get_url_of_primary_worker(node_api, shard).await?);
+	Ok(worker_api_direct.get_mu_ra_url()?)
+}
+
+/// Returns the trusted url of the worker that has been stored in the parentchain state as
+/// "worker for shard".
+async fn get_url_of_primary_worker<NodeApi: PalletTeerexApi>(
+	node_api: &NodeApi,
+	shard: &ShardIdentifier,
 ) -> Result<String> {
 	let enclave = node_api
 		.primary_worker_for_shard(shard, None)?
 		.ok_or_else(|| Error::NoWorkerForShardFound(*shard))?;
-	let worker_api_direct =
-		DirectWorkerApi::new(String::from_utf8(enclave.instance_url().unwrap()).unwrap());
-	Ok(worker_api_direct.get_mu_ra_url()?)
+	Ok(String::from_utf8(enclave.instance_url().unwrap()).unwrap())
 }
 
 /// Returns the url of the first Enclave that matches our own MRENCLAVE.
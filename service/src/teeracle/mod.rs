@@ -19,7 +19,7 @@ use crate::{error::ServiceResult, teeracle::schedule_periodic::schedule_periodic
 use codec::{Decode, Encode};
 use itp_enclave_api::teeracle_api::TeeracleApi;
 use itp_node_api::api_client::ParentchainApi;
-use itp_types::parentchain::Hash;
+use itp_types::{oracle::OracleFeedConfig, parentchain::Hash};
 use itp_utils::hex::hex_encode;
 use log::*;
 use sp_runtime::OpaqueExtrinsic;
@@ -67,19 +67,23 @@ pub(crate) fn schedule_periodic_reregistration_thread(
 
 /// Executes a periodic teeracle data update and sends the new data to the parentchain.
 ///
+/// Every feed is published as the median over its configured data sources.
+///
 /// Note: Puts the current thread to sleep for `period`.
 pub(crate) fn start_periodic_market_update<E: TeeracleApi>(
 	api: &ParentchainApi,
 	period: Duration,
+	feeds: Vec<OracleFeedConfig>,
 	enclave_api: &E,
 	tokio_handle: &Handle,
 ) {
 	let updates_to_run = || {
-		if let Err(e) = execute_oracle_update(api, tokio_handle, || {
-			// Get market data for usd (hardcoded)
-			enclave_api.update_market_data_xt("TEER", "USD")
-		}) {
-			error!("Error running market update {:?}", e)
+		for feed in feeds.iter() {
+			if let Err(e) =
+				execute_oracle_update(api, tokio_handle, || enclave_api.update_oracle_feed_xt(feed))
+			{
+				error!("Error running market update for {:?}: {:?}", feed, e)
+			}
 		}
 
 		// TODO: Refactor and add this back according to ISSUE: https://github.com/integritee-network/worker/issues/1300
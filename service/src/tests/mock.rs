@@ -18,8 +18,8 @@
 use codec::Encode;
 use itp_node_api::api_client::{ApiResult, PalletTeerexApi};
 use itp_types::{
-	AccountId, MultiEnclave, SgxBuildMode, SgxEnclave, SgxReportData, SgxStatus, ShardIdentifier,
-	H256 as Hash,
+	enclave_upgrade::UpgradableShardConfig, AccountId, MultiEnclave, SgxBuildMode, SgxEnclave,
+	SgxReportData, SgxStatus, ShardIdentifier, H256 as Hash,
 };
 
 pub struct TestNodeApi;
@@ -86,4 +86,11 @@ impl PalletTeerexApi for TestNodeApi {
 	) -> ApiResult<Option<[u8; 46]>> {
 		unreachable!()
 	}
+	fn shard_config(
+		&self,
+		_: &ShardIdentifier,
+		_at_block: Option<Hash>,
+	) -> ApiResult<Option<UpgradableShardConfig>> {
+		unreachable!()
+	}
 }
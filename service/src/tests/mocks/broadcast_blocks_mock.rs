@@ -15,7 +15,11 @@
 
 */
 
-use crate::{sync_block_broadcaster::BroadcastBlocks, worker::WorkerResult};
+use crate::{
+	sync_block_broadcaster::{BroadcastBlocks, RequestStateConfirmations},
+	worker::WorkerResult,
+};
+use itp_types::state_confirmation::StateConfirmation;
 use its_primitives::types::block::SignedBlock as SignedSidechainBlock;
 use std::vec::Vec;
 
@@ -26,3 +30,12 @@ impl BroadcastBlocks for BroadcastBlocksMock {
 		Ok(())
 	}
 }
+
+impl RequestStateConfirmations for BroadcastBlocksMock {
+	fn request_state_confirmations(
+		&self,
+		_encrypted_request: Vec<u8>,
+	) -> WorkerResult<Vec<StateConfirmation>> {
+		Ok(Vec::new())
+	}
+}
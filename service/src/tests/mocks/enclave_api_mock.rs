@@ -23,9 +23,18 @@ use itc_parentchain::primitives::{
 	ParentchainInitParams::{Parachain, Solochain},
 };
 use itp_enclave_api::{enclave_base::EnclaveBase, sidechain::Sidechain, EnclaveResult};
+use itp_memory_accounting::MemoryLimits;
 use itp_settings::worker::MR_ENCLAVE_SIZE;
 use itp_storage::StorageProof;
-use itp_types::ShardIdentifier;
+use itp_types::{
+	enclave_upgrade::{KeyHandoverRequest, KeyHandoverResponse},
+	replay::BlockReplayReport,
+	shard_lifecycle::{ShardGenesisConfig, ShardRetirementReport, VaultMode},
+	state_dump::StateDump,
+	state_snapshot::StateSnapshotCommitment,
+	worker_directory::SignedWorkerRecord,
+	ShardIdentifier,
+};
 use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
 use sp_core::ed25519;
 
@@ -92,6 +101,74 @@ impl EnclaveBase for EnclaveMock {
 	fn get_fingerprint(&self) -> EnclaveResult<EnclaveFingerprint> {
 		Ok([1u8; MR_ENCLAVE_SIZE].into())
 	}
+
+	fn dump_state(&self, _shard: &ShardIdentifier) -> EnclaveResult<StateDump> {
+		unimplemented!()
+	}
+
+	fn publish_state_snapshot(
+		&self,
+		_shard: &ShardIdentifier,
+	) -> EnclaveResult<StateSnapshotCommitment> {
+		unimplemented!()
+	}
+
+	fn bootstrap_shard_from_state_snapshot<Header: Encode>(
+		&self,
+		_commitment: &StateSnapshotCommitment,
+		_publication_header: &Header,
+		_snapshot: &[u8],
+	) -> EnclaveResult<()> {
+		unimplemented!()
+	}
+
+	fn publish_worker_record(
+		&self,
+		_trusted_url: &str,
+		_untrusted_url: &str,
+	) -> EnclaveResult<SignedWorkerRecord> {
+		unimplemented!()
+	}
+
+	fn init_shard_with_genesis(
+		&self,
+		_shard: &ShardIdentifier,
+		_genesis_config: &ShardGenesisConfig,
+	) -> EnclaveResult<()> {
+		unimplemented!()
+	}
+
+	fn get_shard_vault_mode(&self, _shard: &ShardIdentifier) -> EnclaveResult<VaultMode> {
+		Ok(VaultMode::Proxied)
+	}
+
+	fn retire_shard(
+		&self,
+		_shard: &ShardIdentifier,
+		_unshield_vault_funds: bool,
+	) -> EnclaveResult<Option<ShardRetirementReport>> {
+		unimplemented!()
+	}
+
+	fn request_key_handover(
+		&self,
+		_shard: &ShardIdentifier,
+		_target_info: &[u8],
+	) -> EnclaveResult<KeyHandoverRequest> {
+		unimplemented!()
+	}
+
+	fn import_handed_over_keys(
+		&self,
+		_shard: &ShardIdentifier,
+		_response: &KeyHandoverResponse,
+	) -> EnclaveResult<()> {
+		unimplemented!()
+	}
+
+	fn set_memory_limits(&self, _limits: &MemoryLimits) -> EnclaveResult<()> {
+		unimplemented!()
+	}
 }
 
 impl Sidechain for EnclaveMock {
@@ -108,4 +185,16 @@ impl Sidechain for EnclaveMock {
 	fn execute_trusted_calls(&self) -> EnclaveResult<()> {
 		todo!()
 	}
+
+	fn refresh_reference_time(&self) -> EnclaveResult<()> {
+		Ok(())
+	}
+
+	fn replay_block(
+		&self,
+		_shard: &ShardIdentifier,
+		_block_number: u64,
+	) -> EnclaveResult<BlockReplayReport> {
+		unimplemented!()
+	}
 }
@@ -0,0 +1,227 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Supervision of the long-running threads that enter the enclave.
+//!
+//! The enclave sends a heartbeat over the metrics o-call whenever such a thread makes progress.
+//! A thread without a heartbeat for longer than its timeout is considered stuck: a thread can't
+//! be aborted while it's inside the enclave, so the watchdog abandons it and spawns a new one,
+//! which re-enters the enclave. The abandoned thread exits as soon as it returns from the
+//! enclave, see [Lease].
+
+use crate::prometheus_metrics::record_enclave_thread_restart;
+use log::*;
+use parking_lot::Mutex;
+use std::{
+	collections::HashMap,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc,
+	},
+	thread,
+	time::{Duration, Instant},
+};
+
+/// Interval in which the watchdog checks the heartbeats.
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The slot worker beats every slot, but a slot may be used up by a peer sync.
+pub(crate) const SLOT_WORKER_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// The direct invocation server beats every 10s, also when idle.
+pub(crate) const DIRECT_RPC_SERVER_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// The parentchain sync beats with every finalized parentchain block, a restart
+/// also renews the subscription to the finalized heads.
+pub(crate) const PARENTCHAIN_SYNC_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Spawns an instance of a supervised thread, which should run as long as its lease is current.
+pub(crate) type SpawnThread = Arc<dyn Fn(Lease) + Send + Sync>;
+
+/// Handed to each instance of a supervised thread. The lease of an instance expires when the
+/// watchdog restarts the thread, the instance should then stop without touching shared state.
+#[derive(Clone, Debug)]
+pub(crate) struct Lease {
+	generation: u64,
+	current_generation: Arc<AtomicU64>,
+}
+
+impl Lease {
+	pub fn is_current(&self) -> bool {
+		self.current_generation.load(Ordering::SeqCst) == self.generation
+	}
+}
+
+struct SupervisedThread {
+	timeout: Duration,
+	last_heartbeat: Instant,
+	current_generation: Arc<AtomicU64>,
+	restarts: u64,
+	spawn: SpawnThread,
+}
+
+impl SupervisedThread {
+	fn next_lease(&self) -> Lease {
+		let generation = self.current_generation.fetch_add(1, Ordering::SeqCst) + 1;
+		Lease { generation, current_generation: self.current_generation.clone() }
+	}
+}
+
+#[derive(Default)]
+pub(crate) struct Watchdog {
+	threads: Mutex<HashMap<String, SupervisedThread>>,
+}
+
+impl Watchdog {
+	/// Spawns the thread `name` and restarts it whenever it has not sent a heartbeat
+	/// for `timeout`.
+	pub fn supervise<F>(&self, name: &str, timeout: Duration, spawn: F)
+	where
+		F: Fn(Lease) + Send + Sync + 'static,
+	{
+		let thread = SupervisedThread {
+			timeout,
+			last_heartbeat: Instant::now(),
+			current_generation: Default::default(),
+			restarts: 0,
+			spawn: Arc::new(spawn),
+		};
+		let lease = thread.next_lease();
+		let spawn = thread.spawn.clone();
+		self.threads.lock().insert(name.to_owned(), thread);
+
+		info!("Watchdog supervises thread {} (timeout {:?})", name, timeout);
+		spawn(lease);
+	}
+
+	/// Records a heartbeat of the thread `name`, unknown threads are ignored.
+	pub fn heartbeat(&self, name: &str) {
+		if let Some(thread) = self.threads.lock().get_mut(name) {
+			thread.last_heartbeat = Instant::now();
+		}
+	}
+
+	/// Restarts all threads whose heartbeats are overdue at `now` and returns their names.
+	pub fn restart_stuck_threads(&self, now: Instant) -> Vec<String> {
+		// Collect first and spawn without holding the lock, the new instances send heartbeats.
+		let stuck: Vec<(String, Lease, SpawnThread)> = self
+			.threads
+			.lock()
+			.iter_mut()
+			.filter(|(_, thread)| {
+				now.saturating_duration_since(thread.last_heartbeat) > thread.timeout
+			})
+			.map(|(name, thread)| {
+				error!(
+					"Thread {} has not sent a heartbeat for {:?}, restarting it",
+					name,
+					now.saturating_duration_since(thread.last_heartbeat)
+				);
+				thread.last_heartbeat = now;
+				thread.restarts += 1;
+				(name.clone(), thread.next_lease(), thread.spawn.clone())
+			})
+			.collect();
+
+		stuck
+			.into_iter()
+			.map(|(name, lease, spawn)| {
+				record_enclave_thread_restart(&name);
+				spawn(lease);
+				name
+			})
+			.collect()
+	}
+}
+
+/// Periodically restarts the stuck threads supervised by `watchdog`.
+pub(crate) fn start_watchdog(watchdog: Arc<Watchdog>) {
+	thread::Builder::new()
+		.name("enclave_thread_watchdog".to_owned())
+		.spawn(move || loop {
+			thread::sleep(CHECK_INTERVAL);
+			watchdog.restart_stuck_threads(Instant::now());
+		})
+		.expect("Failed to spawn enclave thread watchdog");
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TIMEOUT: Duration = Duration::from_secs(60);
+
+	fn restarts(watchdog: &Watchdog, name: &str) -> Option<u64> {
+		watchdog.threads.lock().get(name).map(|thread| thread.restarts)
+	}
+
+	fn supervised(watchdog: &Watchdog) -> Arc<Mutex<Vec<Lease>>> {
+		let leases = Arc::new(Mutex::new(Vec::new()));
+		let leases_clone = leases.clone();
+		watchdog.supervise("worker", TIMEOUT, move |lease| leases_clone.lock().push(lease));
+		leases
+	}
+
+	#[test]
+	fn supervised_thread_is_spawned_immediately() {
+		let watchdog = Watchdog::default();
+		let leases = supervised(&watchdog);
+
+		assert_eq!(leases.lock().len(), 1);
+		assert!(leases.lock()[0].is_current());
+		assert_eq!(restarts(&watchdog, "worker"), Some(0));
+	}
+
+	#[test]
+	fn thread_with_heartbeats_is_not_restarted() {
+		let watchdog = Watchdog::default();
+		let leases = supervised(&watchdog);
+
+		watchdog.heartbeat("worker");
+
+		assert!(watchdog.restart_stuck_threads(Instant::now() + TIMEOUT / 2).is_empty());
+		assert_eq!(leases.lock().len(), 1);
+	}
+
+	#[test]
+	fn stuck_thread_is_restarted_and_its_lease_expires() {
+		let watchdog = Watchdog::default();
+		let leases = supervised(&watchdog);
+		let now = Instant::now() + TIMEOUT * 2;
+
+		assert_eq!(watchdog.restart_stuck_threads(now), vec!["worker".to_string()]);
+
+		{
+			let leases = leases.lock();
+			assert_eq!(leases.len(), 2);
+			assert!(!leases[0].is_current());
+			assert!(leases[1].is_current());
+		}
+		assert_eq!(restarts(&watchdog, "worker"), Some(1));
+
+		// The restarted thread gets a full timeout to send its first heartbeat.
+		assert!(watchdog.restart_stuck_threads(now + TIMEOUT / 2).is_empty());
+	}
+
+	#[test]
+	fn heartbeats_of_unknown_threads_are_ignored() {
+		let watchdog = Watchdog::default();
+		watchdog.heartbeat("unknown");
+
+		assert_eq!(restarts(&watchdog, "unknown"), None);
+	}
+}
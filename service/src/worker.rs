@@ -22,10 +22,16 @@
 /// multiple traits.
 use crate::{config::Config, error::Error, initialized_service::TrackInitialization};
 use async_trait::async_trait;
+use codec::Decode;
 use itc_rpc_client::direct_client::{DirectApi, DirectClient as DirectWorkerApi};
 use itp_node_api::{api_client::PalletTeerexApi, node_api_factory::CreateNodeApi};
+use itp_rpc::RpcResponse;
+use itp_types::state_confirmation::StateConfirmation;
+use itp_utils::{FromHexPrefixed, ToHexPrefixed};
 use its_primitives::types::SignedBlock as SignedSidechainBlock;
-use its_rpc_handler::constants::RPC_METHOD_NAME_IMPORT_BLOCKS;
+use its_rpc_handler::constants::{
+	RPC_METHOD_NAME_CONFIRM_STATE_UPDATE, RPC_METHOD_NAME_IMPORT_BLOCKS,
+};
 use jsonrpsee::{
 	types::{to_json_value, traits::Client},
 	ws_client::WsClientBuilder,
@@ -123,6 +129,71 @@ where
 	}
 }
 
+#[async_trait]
+/// Requests peers to confirm a proposed state update (pessimistic execution mode).
+pub trait AsyncStateConfirmationRequester {
+	/// Returns the confirmations of all peers that confirmed, failures are only logged.
+	async fn request_state_confirmations(
+		&self,
+		encrypted_request: Vec<u8>,
+	) -> WorkerResult<Vec<StateConfirmation>>;
+}
+
+#[async_trait]
+impl<NodeApiFactory, Enclave, InitializationHandler> AsyncStateConfirmationRequester
+	for Worker<Config, NodeApiFactory, Enclave, InitializationHandler>
+where
+	NodeApiFactory: CreateNodeApi + Send + Sync,
+	Enclave: Send + Sync,
+	InitializationHandler: TrackInitialization + Send + Sync,
+{
+	async fn request_state_confirmations(
+		&self,
+		encrypted_request: Vec<u8>,
+	) -> WorkerResult<Vec<StateConfirmation>> {
+		let params = vec![to_json_value(encrypted_request.to_hex())?];
+		let peers = self
+			.peers
+			.read()
+			.map_err(|e| {
+				Error::Custom(format!("Encountered poisoned lock for peers: {:?}", e).into())
+			})
+			.map(|l| l.clone())?;
+
+		let requests = peers.into_iter().map(|url| {
+			let params = params.clone();
+			async move {
+				match request_state_confirmation(&url, params).await {
+					Ok(confirmation) => Some(confirmation),
+					Err(e) => {
+						warn!("Peer {} did not confirm the state update: {:?}", url, e);
+						None
+					},
+				}
+			}
+		});
+
+		Ok(futures::future::join_all(requests).await.into_iter().flatten().collect())
+	}
+}
+
+async fn request_state_confirmation(
+	url: &str,
+	params: Vec<serde_json::Value>,
+) -> WorkerResult<StateConfirmation> {
+	let client = WsClientBuilder::default().build(url).await?;
+
+	let response_bytes = client
+		.request::<Vec<u8>>(RPC_METHOD_NAME_CONFIRM_STATE_UPDATE, params.into())
+		.await?;
+
+	// The peer forwards the response of its enclave as it is.
+	let response: RpcResponse = serde_json::from_slice(&response_bytes)?;
+	let encoded_confirmation = Vec::<u8>::from_hex(&response.result)
+		.map_err(|e| Error::Custom(format!("{:?}", e).into()))?;
+	Ok(StateConfirmation::decode(&mut encoded_confirmation.as_slice())?)
+}
+
 /// Looks for new peers and updates them.
 pub trait UpdatePeers {
 	fn search_peers(&self) -> WorkerResult<Vec<Url>>;
@@ -21,13 +21,18 @@ use codec::{Decode, Encode};
 use core::fmt::Debug;
 // Reexport BlockImport trait which implements fn block_import()
 use crate::{AuraVerifier, EnclaveOnChainOCallApi, SidechainBlockTrait};
+use ita_stf::migrations::{state_stf_version, STF_VERSION};
 use itc_parentchain_block_import_dispatcher::triggered_dispatcher::TriggerParentchainBlockImport;
 use itp_enclave_metrics::EnclaveMetric;
 use itp_ocall_api::{EnclaveMetricsOCallApi, EnclaveSidechainOCallApi};
 use itp_settings::sidechain::SLOT_DURATION;
 use itp_sgx_crypto::{key_repository::AccessKey, StateCrypto};
 use itp_sgx_externalities::SgxExternalities;
-use itp_stf_primitives::{traits::TrustedCallVerification, types::TrustedOperationOrHash};
+use itp_stf_executor::replay::{ImportedBlock, RecordBlockReplay};
+use itp_stf_primitives::{
+	traits::TrustedCallVerification,
+	types::{TrustedOperation, TrustedOperationOrHash},
+};
 use itp_stf_state_handler::handle_state::HandleState;
 use itp_top_pool_author::traits::{AuthorApi, OnBlockImported};
 use itp_types::H256;
@@ -36,6 +41,7 @@ use its_consensus_common::Error as ConsensusError;
 use its_primitives::traits::{
 	BlockData, Header as HeaderTrait, ShardIdentifierFor, SignedBlock as SignedBlockTrait,
 };
+use its_state::StateUpdate;
 use its_validateer_fetch::ValidateerFetch;
 use log::*;
 use sp_core::{crypto::UncheckedFrom, Pair};
@@ -43,7 +49,10 @@ use sp_runtime::{
 	generic::SignedBlock as SignedParentchainBlock,
 	traits::{Block as ParentchainBlockTrait, Header},
 };
-use std::{marker::PhantomData, sync::Arc};
+use std::{marker::PhantomData, sync::Arc, vec::Vec};
+
+pub type BlockReplayRecorderFor<ParentchainBlock, TCS, G> =
+	dyn RecordBlockReplay<<ParentchainBlock as ParentchainBlockTrait>::Header, TCS, G>;
 
 /// Implements `BlockImport`.
 #[derive(Clone)]
@@ -64,6 +73,7 @@ pub struct BlockImporter<
 	top_pool_author: Arc<TopPoolAuthor>,
 	parentchain_block_importer: Arc<ParentchainBlockImporter>,
 	ocall_api: Arc<OCallApi>,
+	replay_recorder: Option<Arc<BlockReplayRecorderFor<ParentchainBlock, TCS, G>>>,
 	_phantom: PhantomData<(Authority, ParentchainBlock, SignedSidechainBlock, TCS, G)>,
 }
 
@@ -126,10 +136,54 @@ impl<
 			top_pool_author,
 			parentchain_block_importer,
 			ocall_api,
+			replay_recorder: None,
 			_phantom: Default::default(),
 		}
 	}
 
+	/// Records every imported block, so its execution can be replayed later.
+	pub fn with_replay_recorder(
+		mut self,
+		replay_recorder: Arc<BlockReplayRecorderFor<ParentchainBlock, TCS, G>>,
+	) -> Self {
+		self.replay_recorder = Some(replay_recorder);
+		self
+	}
+
+	/// The operations of `sidechain_block` in execution order, as far as they are known locally,
+	/// and the hashes of the unknown ones.
+	///
+	/// Must be called before the executed operations are removed from the pool.
+	fn known_operations(
+		&self,
+		recorder: &BlockReplayRecorderFor<ParentchainBlock, TCS, G>,
+		sidechain_block: &SignedSidechainBlock::Block,
+	) -> (Vec<TrustedOperation<TCS, G>>, Vec<H256>) {
+		let shard = sidechain_block.header().shard_id();
+		match recorder.take_proposed_operations(&shard, sidechain_block.header().block_number()) {
+			Ok(Some(proposed_operations)) => return (proposed_operations, Vec::new()),
+			Ok(None) => {},
+			Err(e) => warn!("Failed to get the proposed operations of the block: {:?}", e),
+		}
+
+		let pending_operations: Vec<_> = self
+			.top_pool_author
+			.get_pending_trusted_calls(shard)
+			.into_iter()
+			.map(|top| (self.top_pool_author.hash_of(&top), top))
+			.collect();
+
+		let mut known_operations = Vec::new();
+		let mut missing_operations = Vec::new();
+		for hash in sidechain_block.block_data().signed_top_hashes() {
+			match pending_operations.iter().find(|(h, _)| h == hash) {
+				Some((_, top)) => known_operations.push(top.clone()),
+				None => missing_operations.push(*hash),
+			}
+		}
+		(known_operations, missing_operations)
+	}
+
 	fn update_top_pool(&self, sidechain_block: &SignedSidechainBlock::Block) {
 		// Notify pool about imported block for status updates of the calls.
 		self.top_pool_author.on_block_imported(
@@ -236,6 +290,13 @@ impl<
 		// the state back to the state handler, and thus guaranteeing state integrity.
 		let updated_state = mutating_function(state)?;
 
+		// A block produced by an older STF, which has not migrated the state, would write
+		// state with outdated semantics.
+		let block_stf_version = state_stf_version(&updated_state);
+		if block_stf_version < STF_VERSION {
+			return Err(ConsensusError::OutdatedStfVersion(block_stf_version, STF_VERSION))
+		}
+
 		self.state_handler
 			.write_after_mutation(updated_state, write_lock, shard)
 			.map_err(|e| ConsensusError::Other(format!("{:?}", e).into()))?;
@@ -348,4 +409,38 @@ impl<
 
 		Ok(())
 	}
+
+	fn records_imported_blocks(&self) -> bool {
+		self.replay_recorder.is_some()
+	}
+
+	fn record_imported_block(
+		&self,
+		sidechain_block: &SignedSidechainBlock::Block,
+		parentchain_header: &ParentchainBlock::Header,
+		state_before_import: Self::SidechainState,
+		state_update: &StateUpdate,
+	) {
+		let recorder = match &self.replay_recorder {
+			Some(recorder) => recorder,
+			None => return,
+		};
+		let (trusted_operations, missing_operations) =
+			self.known_operations(recorder.as_ref(), sidechain_block);
+
+		let block_number = sidechain_block.header().block_number();
+		let imported_block = ImportedBlock {
+			shard: sidechain_block.header().shard_id(),
+			block_number,
+			timestamp: sidechain_block.block_data().timestamp(),
+			parentchain_header: parentchain_header.clone(),
+			trusted_operations,
+			missing_operations,
+			state_hash_after_execution: state_update.state_hash_aposteriori(),
+			state_changes: state_update.state_update().clone().into_iter().collect(),
+		};
+		if let Err(e) = recorder.record(imported_block, state_before_import) {
+			warn!("Failed to record sidechain block {} for replay: {:?}", block_number, e);
+		}
+	}
 }
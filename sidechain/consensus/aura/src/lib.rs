@@ -33,6 +33,7 @@ use core::marker::PhantomData;
 use itc_parentchain_block_import_dispatcher::triggered_dispatcher::TriggerParentchainBlockImport;
 use itp_ocall_api::EnclaveOnChainOCallApi;
 use itp_time_utils::duration_now;
+use itp_types::{enclave_upgrade::may_author, EnclaveFingerprint};
 use its_block_verification::slot::slot_author;
 use its_consensus_common::{Environment, Error as ConsensusError, Proposer};
 use its_consensus_slots::{SimpleSlotWorker, Slot, SlotInfo};
@@ -45,13 +46,16 @@ use sp_core::crypto::UncheckedFrom;
 use sp_runtime::{
 	app_crypto::{sp_core::H256, Pair},
 	generic::SignedBlock as SignedParentchainBlock,
-	traits::{Block as ParentchainBlockTrait, Header as ParentchainHeaderTrait},
+	traits::{
+		Block as ParentchainBlockTrait, Header as ParentchainHeaderTrait, UniqueSaturatedInto,
+	},
 };
 use std::{string::ToString, sync::Arc, time::Duration, vec::Vec};
 
 pub mod block_importer;
 pub mod proposer_factory;
 pub mod slot_proposer;
+pub mod state_confirmation;
 mod verifier;
 
 pub use verifier::*;
@@ -73,6 +77,7 @@ pub struct Aura<
 	parentchain_import_trigger: Arc<ImportTrigger>,
 	environment: Environment,
 	claim_strategy: SlotClaimStrategy,
+	enclave_fingerprint: Option<EnclaveFingerprint>,
 	_phantom: PhantomData<(AuthorityPair, ParentchainBlock, SidechainBlock)>,
 }
 
@@ -91,6 +96,7 @@ impl<AuthorityPair, ParentchainBlock, SidechainBlock, Environment, OcallApi, Imp
 			parentchain_import_trigger,
 			environment,
 			claim_strategy: SlotClaimStrategy::RoundRobin,
+			enclave_fingerprint: None,
 			_phantom: Default::default(),
 		}
 	}
@@ -100,6 +106,14 @@ impl<AuthorityPair, ParentchainBlock, SidechainBlock, Environment, OcallApi, Imp
 
 		self
 	}
+
+	/// Only author shards whose config on the parentchain permits the enclave with the
+	/// given fingerprint to do so, see [may_author].
+	pub fn with_enclave_fingerprint(mut self, fingerprint: EnclaveFingerprint) -> Self {
+		self.enclave_fingerprint = Some(fingerprint);
+
+		self
+	}
 }
 
 /// The fraction of total block time we are allowed to be producing the block. So that we have
@@ -149,6 +163,19 @@ where
 		shard: ShardIdentifierFor<Self::Output>,
 		_slot: Slot,
 	) -> Result<Self::EpochData, ConsensusError> {
+		if let Some(fingerprint) = self.enclave_fingerprint.as_ref() {
+			// Checked against the verified config at the parentchain block the sidechain block
+			// builds on, so all validateers agree on who authors across an enclave upgrade.
+			let config = self
+				.ocall_api
+				.shard_config::<ParentchainBlock::Header, SignedSidechainBlock>(header, shard)
+				.map_err(|e| ConsensusError::ChainLookup(e.to_string()))?;
+			if !may_author(config.as_ref(), fingerprint, (*header.number()).unique_saturated_into())
+			{
+				return Err(ConsensusError::AuthoringNotPermitted(format!("{:?}", shard)))
+			}
+		}
+
 		authorities::<_, AuthorityPair, SignedSidechainBlock, ParentchainBlock::Header>(
 			&self.ocall_api,
 			header,
@@ -270,8 +297,10 @@ mod tests {
 	};
 	use itc_parentchain_block_import_dispatcher::trigger_parentchain_block_import_mock::TriggerParentchainBlockImportMock;
 	use itc_parentchain_test::{ParentchainBlockBuilder, ParentchainHeaderBuilder};
+	use itp_enclave_bridge_storage::{EnclaveBridgeStorage, EnclaveBridgeStorageKeys};
 	use itp_test::mock::onchain_mock::OnchainMock;
 	use itp_types::{
+		enclave_upgrade::{ShardConfig, UpgradableShardConfig},
 		AccountId, Block as ParentchainBlock, Header as ParentchainHeader, ShardIdentifier,
 		SignedBlock as SignedParentchainBlock,
 	};
@@ -449,6 +478,59 @@ mod tests {
 		assert!(parentchain_block_import_trigger.has_import_been_called());
 	}
 
+	fn onchain_mock_with_upgrade_at(
+		parentchain_header: &ParentchainHeader,
+		activation_block: u32,
+	) -> OnchainMock {
+		let shard_config = |f: u8| ShardConfig {
+			enclave_fingerprint: [f; 32].into(),
+			max_instances: None,
+			authorities: None,
+			maintenance_mode: false,
+		};
+		let config = UpgradableShardConfig {
+			active_config: shard_config(1),
+			pending_upgrade: Some(shard_config(2)),
+			upgrade_at: Some(activation_block),
+		};
+		onchain_mock(parentchain_header, default_authorities()).with_storage_entries_at_header(
+			parentchain_header,
+			vec![(EnclaveBridgeStorage::shard_config(ShardIdentifier::default()), config)],
+		)
+	}
+
+	#[test]
+	fn on_slot_is_claimed_by_old_enclave_before_upgrade_activation() {
+		let latest_parentchain_header = ParentchainHeaderBuilder::default().with_number(84).build();
+		let mut aura = get_aura(
+			onchain_mock_with_upgrade_at(&latest_parentchain_header, 85),
+			create_import_trigger_with_header(latest_parentchain_header.clone()),
+		)
+		.with_enclave_fingerprint([1u8; 32].into());
+
+		let slot_info = now_slot(0.into(), &latest_parentchain_header);
+
+		assert!(SimpleSlotWorker::on_slot(&mut aura, slot_info, Default::default()).is_some());
+	}
+
+	#[test]
+	fn on_slot_is_not_claimed_by_old_enclave_from_upgrade_activation_on() {
+		let latest_parentchain_header = ParentchainHeaderBuilder::default().with_number(84).build();
+		let parentchain_block_import_trigger =
+			create_import_trigger_with_header(latest_parentchain_header.clone());
+		let mut aura = get_aura(
+			onchain_mock_with_upgrade_at(&latest_parentchain_header, 84),
+			parentchain_block_import_trigger.clone(),
+		)
+		.with_enclave_fingerprint([1u8; 32].into())
+		.with_claim_strategy(SlotClaimStrategy::Always);
+
+		let slot_info = now_slot(0.into(), &latest_parentchain_header);
+
+		assert!(SimpleSlotWorker::on_slot(&mut aura, slot_info, Default::default()).is_none());
+		assert!(!parentchain_block_import_trigger.has_import_been_called());
+	}
+
 	#[test]
 	fn on_slot_does_not_trigger_parentchain_block_import_if_slot_is_not_claimed() {
 		let _ = env_logger::builder().is_test(true).try_init();
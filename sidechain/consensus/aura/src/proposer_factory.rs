@@ -15,12 +15,14 @@
 
 */
 
-use crate::slot_proposer::{ExternalitiesFor, SlotProposer};
+use crate::slot_proposer::{ExternalitiesFor, ReplayRecorderFor, SlotProposer, StateConfirmerFor};
 use codec::Encode;
 use finality_grandpa::BlockNumberOps;
 use ita_stf::{Getter, TrustedCallSigned};
+use itc_secure_time::TrustedTime;
+use itp_audit_log::RecordDroppedOperation;
 use itp_sgx_externalities::{SgxExternalitiesTrait, StateHash};
-use itp_stf_executor::traits::StateUpdateProposer;
+use itp_stf_executor::traits::{RouteCrossShardMessages, StateUpdateProposer};
 use itp_top_pool_author::traits::AuthorApi;
 use itp_types::H256;
 use its_block_composer::ComposeBlock;
@@ -42,6 +44,11 @@ pub struct ProposerFactory<ParentchainBlock: Block, TopPoolAuthor, StfExecutor,
 	top_pool_author: Arc<TopPoolAuthor>,
 	stf_executor: Arc<StfExecutor>,
 	block_composer: Arc<BlockComposer>,
+	replay_recorder: Option<Arc<ReplayRecorderFor<ParentchainBlock>>>,
+	state_confirmer: Option<Arc<StateConfirmerFor<ParentchainBlock>>>,
+	audit_log: Option<Arc<dyn RecordDroppedOperation>>,
+	trusted_time: Option<Arc<dyn TrustedTime>>,
+	cross_shard_router: Option<Arc<dyn RouteCrossShardMessages>>,
 	_phantom: PhantomData<ParentchainBlock>,
 }
 
@@ -57,9 +64,53 @@ impl<ParentchainBlock: Block, TopPoolAuthor, StfExecutor, BlockComposer>
 			top_pool_author: top_pool_executor,
 			stf_executor,
 			block_composer,
+			replay_recorder: None,
+			state_confirmer: None,
+			audit_log: None,
+			trusted_time: None,
+			cross_shard_router: None,
 			_phantom: Default::default(),
 		}
 	}
+
+	/// Keeps the operations of every proposed block for the replay recording on import.
+	pub fn with_replay_recorder(
+		mut self,
+		replay_recorder: Arc<ReplayRecorderFor<ParentchainBlock>>,
+	) -> Self {
+		self.replay_recorder = Some(replay_recorder);
+		self
+	}
+
+	/// Pessimistic execution: only applies state updates that were confirmed by the peers.
+	pub fn with_state_confirmer(
+		mut self,
+		state_confirmer: Arc<StateConfirmerFor<ParentchainBlock>>,
+	) -> Self {
+		self.state_confirmer = Some(state_confirmer);
+		self
+	}
+
+	/// Records the operations that are skipped because the slot time ran out.
+	pub fn with_audit_log(mut self, audit_log: Arc<dyn RecordDroppedOperation>) -> Self {
+		self.audit_log = Some(audit_log);
+		self
+	}
+
+	/// Timestamps the blocks with the drift-checked secure time instead of the host clock.
+	pub fn with_trusted_time(mut self, trusted_time: Arc<dyn TrustedTime>) -> Self {
+		self.trusted_time = Some(trusted_time);
+		self
+	}
+
+	/// Delivers the cross-shard messages emitted by the proposed blocks.
+	pub fn with_cross_shard_router(
+		mut self,
+		cross_shard_router: Arc<dyn RouteCrossShardMessages>,
+	) -> Self {
+		self.cross_shard_router = Some(cross_shard_router);
+		self
+	}
 }
 
 impl<
@@ -111,6 +162,11 @@ where
 			block_composer: self.block_composer.clone(),
 			parentchain_header: parent_header,
 			shard,
+			replay_recorder: self.replay_recorder.clone(),
+			state_confirmer: self.state_confirmer.clone(),
+			audit_log: self.audit_log.clone(),
+			trusted_time: self.trusted_time.clone(),
+			cross_shard_router: self.cross_shard_router.clone(),
 			_phantom: PhantomData,
 		})
 	}
@@ -15,11 +15,21 @@
 
 */
 
+use crate::state_confirmation::{
+	prepare_sidechain_state, ConfirmStateUpdate, StateConfirmationRequest,
+};
 use codec::Encode;
 use finality_grandpa::BlockNumberOps;
 use ita_stf::{Getter, TrustedCallSigned};
+use itc_secure_time::TrustedTime;
+use itp_audit_log::{DropReason, RecordDroppedOperation};
 use itp_sgx_externalities::{SgxExternalitiesTrait, StateHash};
-use itp_stf_executor::traits::StateUpdateProposer;
+use itp_stf_executor::{
+	replay::RecordBlockReplay,
+	traits::{RouteCrossShardMessages, StateUpdateProposer},
+	BatchExecutionResult,
+};
+use itp_stf_primitives::types::{TrustedOperation, TrustedOperationOrHash};
 use itp_time_utils::now_as_millis;
 use itp_top_pool_author::traits::AuthorApi;
 use itp_types::H256;
@@ -31,6 +41,7 @@ use its_primitives::traits::{
 };
 use its_state::{SidechainState, SidechainSystemExt};
 use log::*;
+use sp_core::blake2_256;
 use sp_runtime::{
 	traits::{Block, NumberFor},
 	MultiSignature,
@@ -38,6 +49,11 @@ use sp_runtime::{
 use std::{marker::PhantomData, string::ToString, sync::Arc, time::Duration, vec::Vec};
 
 pub type ExternalitiesFor<T> = <T as StateUpdateProposer<TrustedCallSigned, Getter>>::Externalities;
+pub type ReplayRecorderFor<ParentchainBlock> =
+	dyn RecordBlockReplay<<ParentchainBlock as Block>::Header, TrustedCallSigned, Getter>;
+pub type StateConfirmerFor<ParentchainBlock> =
+	dyn ConfirmStateUpdate<<ParentchainBlock as Block>::Header, TrustedCallSigned, Getter>;
+
 ///! `SlotProposer` instance that has access to everything needed to propose a sidechain block.
 pub struct SlotProposer<
 	ParentchainBlock: Block,
@@ -51,6 +67,11 @@ pub struct SlotProposer<
 	pub(crate) block_composer: Arc<BlockComposer>,
 	pub(crate) parentchain_header: ParentchainBlock::Header,
 	pub(crate) shard: ShardIdentifierFor<SignedSidechainBlock>,
+	pub(crate) replay_recorder: Option<Arc<ReplayRecorderFor<ParentchainBlock>>>,
+	pub(crate) state_confirmer: Option<Arc<StateConfirmerFor<ParentchainBlock>>>,
+	pub(crate) audit_log: Option<Arc<dyn RecordDroppedOperation>>,
+	pub(crate) trusted_time: Option<Arc<dyn TrustedTime>>,
+	pub(crate) cross_shard_router: Option<Arc<dyn RouteCrossShardMessages>>,
 	pub(crate) _phantom: PhantomData<ParentchainBlock>,
 }
 
@@ -84,7 +105,9 @@ where
 	/// This includes the following steps:
 	/// 1) Retrieve all trusted calls from the top pool.
 	/// 2) Calculate a new state that will be proposed in the sidechain block.
-	/// 3) Compose the sidechain block and the parentchain confirmation.
+	/// 3) In pessimistic execution mode, have the new state confirmed by the peer validateers.
+	/// 4) Compose the sidechain block and the parentchain confirmation.
+	/// 5) Route the emitted cross-shard messages to their destination shards.
 	fn propose(
 		&self,
 		max_duration: Duration,
@@ -99,6 +122,12 @@ where
 		}
 
 		// 2) Execute trusted calls.
+		let timestamp = match &self.trusted_time {
+			Some(trusted_time) =>
+				trusted_time.now().map_err(|e| ConsensusError::Other(e.to_string().into()))?,
+			None => now_as_millis(),
+		};
+		let mut block_number = 0;
 		let batch_execution_result = self
 			.stf_executor
 			.propose_state_update(
@@ -106,12 +135,9 @@ where
 				latest_parentchain_header,
 				&self.shard,
 				max_duration,
-				|mut sidechain_db| {
-					sidechain_db.reset_events();
-					sidechain_db
-						.set_block_number(&sidechain_db.get_block_number().map_or(1, |n| n + 1));
-					sidechain_db.set_timestamp(&now_as_millis());
-					sidechain_db
+				|sidechain_db| {
+					block_number = sidechain_db.get_block_number().map_or(1, |n| n + 1);
+					prepare_sidechain_state(sidechain_db, block_number, timestamp)
 				},
 			)
 			.map_err(|e| ConsensusError::Other(e.to_string().into()))?;
@@ -122,20 +148,49 @@ where
 			batch_execution_result.get_executed_operation_hashes().to_vec();
 		let number_executed_transactions = executed_operation_hashes.len();
 
-		// Remove all not successfully executed operations from the top pool.
-		let failed_operations = batch_execution_result.get_failed_operations();
-		self.top_pool_author.remove_calls_from_pool(
+		// 3) Have the state update confirmed, the executed operations stay in the pool otherwise.
+		if let Some(confirmer) = &self.state_confirmer {
+			confirmer.confirm_state_update(&StateConfirmationRequest {
+				shard: self.shard,
+				block_number,
+				timestamp,
+				parentchain_header: latest_parentchain_header.clone(),
+				trusted_operations: executed_trusted_operations(
+					&trusted_calls,
+					&batch_execution_result,
+				),
+				state_hash_before_execution: batch_execution_result.state_hash_before_execution,
+				state_hash_after_execution: batch_execution_result.state_after_execution.hash(),
+			})?;
+		}
+
+		// Remove all not successfully executed operations from the top pool. Only now, as they
+		// would be lost if the slot is skipped for lack of confirmations.
+		let failed_operations: Vec<_> = batch_execution_result
+			.get_failed_operations()
+			.into_iter()
+			.map(|e| e.trusted_operation_or_hash)
+			.collect();
+		let failed_to_remove = self.top_pool_author.remove_calls_from_pool(
 			self.shard,
-			failed_operations
-				.into_iter()
-				.map(|e| {
-					let is_success = e.is_success();
-					(e.trusted_operation_or_hash, is_success)
-				})
-				.collect(),
+			failed_operations.iter().cloned().map(|op| (op, false)).collect(),
 		);
 
-		// 3) Compose sidechain block.
+		// Operations that did not fit into the slot stay in the pool, only the removed ones
+		// are recorded.
+		if let Some(audit_log) = &self.audit_log {
+			for removed in failed_operations.iter().filter(|op| !failed_to_remove.contains(op)) {
+				if let Err(e) = audit_log.record_dropped(
+					&self.shard,
+					&operation_hash(removed),
+					DropReason::ExecutionFailed,
+				) {
+					warn!("Failed to record removed operation in the audit log: {:?}", e);
+				}
+			}
+		}
+
+		// 4) Compose sidechain block.
 		let sidechain_block = self
 			.block_composer
 			.compose_block(
@@ -147,6 +202,24 @@ where
 			)
 			.map_err(|e| ConsensusError::Other(e.to_string().into()))?;
 
+		// The block is recorded on import, the failed operations are only known here.
+		if let Some(recorder) = &self.replay_recorder {
+			if let Err(e) = recorder.note_proposed_operations(
+				&self.shard,
+				sidechain_block.block().header().block_number(),
+				// Only the attempted operations, the slot time might have run out before the rest.
+				executed_trusted_operations(&trusted_calls, &batch_execution_result),
+			) {
+				warn!("Failed to note the operations of the proposed block: {:?}", e);
+			}
+		}
+
+		if let Some(router) = &self.cross_shard_router {
+			if let Err(e) = router.route(&self.shard, &batch_execution_result.outgoing_messages) {
+				error!("Failed to route cross-shard messages of shard {:?}: {:?}", self.shard, e);
+			}
+		}
+
 		info!(
 			"Queue/Timeslot/Transactions: {:?};{};{}",
 			trusted_calls.len(),
@@ -157,3 +230,28 @@ where
 		Ok(Proposal { block: sidechain_block, parentchain_effects: parentchain_extrinsics })
 	}
 }
+
+fn operation_hash(operation: &TrustedOperationOrHash<TrustedCallSigned, Getter>) -> H256 {
+	match operation {
+		TrustedOperationOrHash::Hash(hash) => *hash,
+		TrustedOperationOrHash::OperationEncoded(encoded) => blake2_256(encoded).into(),
+		TrustedOperationOrHash::Operation(top) => top.hash(),
+	}
+}
+
+/// The operations of `trusted_calls` that were attempted in the batch execution, in order.
+fn executed_trusted_operations<Externalities: SgxExternalitiesTrait + Encode>(
+	trusted_calls: &[TrustedOperation<TrustedCallSigned, Getter>],
+	batch_execution_result: &BatchExecutionResult<Externalities, TrustedCallSigned, Getter>,
+) -> Vec<TrustedOperation<TrustedCallSigned, Getter>> {
+	trusted_calls
+		.iter()
+		.filter(|top| {
+			batch_execution_result.executed_operations.iter().any(|e| {
+				matches!(&e.trusted_operation_or_hash,
+					TrustedOperationOrHash::Operation(op) if op.as_ref() == *top)
+			})
+		})
+		.cloned()
+		.collect()
+}
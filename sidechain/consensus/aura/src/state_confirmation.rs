@@ -0,0 +1,307 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Pessimistic execution mode.
+//!
+//! Before the proposer applies a state update and publishes its block, the state update is
+//! sent to the peer validateers of the shard. They re-execute the same trusted operations on
+//! their own state and sign the resulting state hash if it matches the proposed one. Only with
+//! a quorum of confirmations is the block composed, otherwise the slot is skipped and the
+//! operations remain in the pool.
+
+use codec::{Decode, Encode};
+use core::fmt::Debug;
+use itp_ocall_api::{EnclaveOnChainOCallApi, EnclaveStateConfirmationOCallApi};
+use itp_sgx_crypto::{key_repository::AccessKey, StateAead};
+use itp_sgx_externalities::StateHash;
+use itp_stf_executor::traits::StateUpdateProposer;
+use itp_stf_primitives::types::{ShardIdentifier, TrustedOperation};
+use itp_types::{
+	state_confirmation::{count_confirmations, required_confirmations, ConfirmedStateUpdate},
+	H256,
+};
+use its_consensus_common::Error as ConsensusError;
+use its_primitives::types::{
+	block::{BlockNumber, Timestamp},
+	SignedBlock,
+};
+use its_state::SidechainSystemExt;
+use its_validateer_fetch::ValidateerFetch;
+use log::*;
+use sp_runtime::traits::Header as HeaderTrait;
+use std::{format, string::ToString, sync::Arc, time::Duration, vec::Vec};
+
+/// Execution time granted to a peer for the re-execution. It is not bound to a slot, but the
+/// proposer waits for the confirmations within its own slot.
+pub const REEXECUTION_MAX_DURATION: Duration = Duration::from_secs(5);
+
+/// Everything a peer needs to re-execute a proposed state update.
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+pub struct StateConfirmationRequest<PH, TCS, G>
+where
+	TCS: PartialEq + Encode + Decode + Debug + Send + Sync,
+	G: PartialEq + Encode + Decode + Debug + Send + Sync,
+{
+	pub shard: ShardIdentifier,
+	pub block_number: BlockNumber,
+	pub timestamp: Timestamp,
+	pub parentchain_header: PH,
+	/// The operations attempted by the proposer, in execution order.
+	pub trusted_operations: Vec<TrustedOperation<TCS, G>>,
+	pub state_hash_before_execution: H256,
+	pub state_hash_after_execution: H256,
+}
+
+impl<PH, TCS, G> StateConfirmationRequest<PH, TCS, G>
+where
+	TCS: PartialEq + Encode + Decode + Debug + Send + Sync,
+	G: PartialEq + Encode + Decode + Debug + Send + Sync,
+{
+	pub fn update(&self) -> ConfirmedStateUpdate {
+		ConfirmedStateUpdate {
+			shard: self.shard,
+			block_number: self.block_number,
+			state_hash_before_execution: self.state_hash_before_execution,
+			state_hash_after_execution: self.state_hash_after_execution,
+		}
+	}
+}
+
+/// Confirms a proposed state update before it is applied.
+pub trait ConfirmStateUpdate<PH, TCS, G>: Send + Sync
+where
+	TCS: PartialEq + Encode + Decode + Debug + Send + Sync,
+	G: PartialEq + Encode + Decode + Debug + Send + Sync,
+{
+	/// Returns an error if the state update must not be applied.
+	fn confirm_state_update(
+		&self,
+		request: &StateConfirmationRequest<PH, TCS, G>,
+	) -> Result<(), ConsensusError>;
+}
+
+/// Prepares the sidechain state for the execution of the block with `block_number`.
+///
+/// Shared by the block proposer and the re-execution, as both must start from the same state.
+pub fn prepare_sidechain_state<Externalities: SidechainSystemExt>(
+	mut sidechain_db: Externalities,
+	block_number: BlockNumber,
+	timestamp: Timestamp,
+) -> Externalities {
+	sidechain_db.reset_events();
+	sidechain_db.set_block_number(&block_number);
+	sidechain_db.set_timestamp(&timestamp);
+	sidechain_db
+}
+
+/// Re-executes a proposed state update on the own state of the shard and returns the update
+/// if it results in the proposed state. The shard state is not modified.
+pub fn reexecute_state_update<Executor, PH, TCS, G>(
+	executor: &Executor,
+	request: &StateConfirmationRequest<PH, TCS, G>,
+) -> Result<ConfirmedStateUpdate, ConsensusError>
+where
+	Executor: StateUpdateProposer<TCS, G>,
+	Executor::Externalities: SidechainSystemExt + StateHash,
+	PH: HeaderTrait<Hash = H256>,
+	TCS: PartialEq + Encode + Decode + Debug + Send + Sync,
+	G: PartialEq + Encode + Decode + Debug + Send + Sync,
+{
+	let result = executor
+		.propose_state_update(
+			&request.trusted_operations,
+			&request.parentchain_header,
+			&request.shard,
+			REEXECUTION_MAX_DURATION,
+			|sidechain_db| {
+				prepare_sidechain_state(sidechain_db, request.block_number, request.timestamp)
+			},
+		)
+		.map_err(|e| ConsensusError::Other(e.to_string().into()))?;
+
+	if result.state_hash_before_execution != request.state_hash_before_execution {
+		return Err(ConsensusError::StateUpdateMismatch(
+			result.state_hash_before_execution,
+			request.state_hash_before_execution,
+		))
+	}
+
+	let state_hash_after_execution = result.state_after_execution.hash();
+	if state_hash_after_execution != request.state_hash_after_execution {
+		return Err(ConsensusError::StateUpdateMismatch(
+			state_hash_after_execution,
+			request.state_hash_after_execution,
+		))
+	}
+
+	Ok(request.update())
+}
+
+/// Requests the confirmations of the peer validateers and requires `quorum_percent` of the
+/// shard's validateers to confirm, the proposer itself included.
+pub struct QuorumStateConfirmer<OCallApi, StateKeyRepository> {
+	ocall_api: Arc<OCallApi>,
+	state_key_repository: Arc<StateKeyRepository>,
+	quorum_percent: u64,
+}
+
+impl<OCallApi, StateKeyRepository> QuorumStateConfirmer<OCallApi, StateKeyRepository> {
+	pub fn new(
+		ocall_api: Arc<OCallApi>,
+		state_key_repository: Arc<StateKeyRepository>,
+		quorum_percent: u64,
+	) -> Self {
+		Self { ocall_api, state_key_repository, quorum_percent }
+	}
+}
+
+impl<PH, TCS, G, OCallApi, StateKeyRepository> ConfirmStateUpdate<PH, TCS, G>
+	for QuorumStateConfirmer<OCallApi, StateKeyRepository>
+where
+	PH: HeaderTrait<Hash = H256> + Encode,
+	TCS: PartialEq + Encode + Decode + Debug + Send + Sync,
+	G: PartialEq + Encode + Decode + Debug + Send + Sync,
+	OCallApi: EnclaveOnChainOCallApi + EnclaveStateConfirmationOCallApi,
+	StateKeyRepository: AccessKey + Send + Sync,
+	<StateKeyRepository as AccessKey>::KeyType: StateAead,
+{
+	fn confirm_state_update(
+		&self,
+		request: &StateConfirmationRequest<PH, TCS, G>,
+	) -> Result<(), ConsensusError> {
+		let validateers = self
+			.ocall_api
+			.current_validateers::<PH, SignedBlock>(&request.parentchain_header, request.shard)
+			.map_err(|e| ConsensusError::CouldNotGetAuthorities(e.to_string()))?;
+
+		let required = required_confirmations(validateers.len() as u64, self.quorum_percent);
+		// The proposer confirms its own update.
+		if required <= 1 {
+			return Ok(())
+		}
+
+		let mut nonce = [0u8; 12];
+		nonce.copy_from_slice(&sp_io::offchain::random_seed()[..12]);
+		let encrypted_request = self
+			.state_key_repository
+			.retrieve_key()
+			.map_err(|e| ConsensusError::Other(format!("{:?}", e).into()))?
+			.seal(&request.encode(), nonce)
+			.map_err(|e| ConsensusError::Other(format!("{:?}", e).into()))?;
+
+		let confirmations =
+			self.ocall_api.request_state_confirmations(request.shard, encrypted_request)?;
+
+		let confirmed = count_confirmations(&request.update(), &confirmations, &validateers) + 1;
+		debug!(
+			"State update of block {} confirmed by {} of {} validateers",
+			request.block_number,
+			confirmed,
+			validateers.len()
+		);
+
+		if confirmed < required {
+			return Err(ConsensusError::StateUpdateNotConfirmed(confirmed, required))
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ita_stf::{Getter, TrustedCallSigned};
+	use itc_parentchain_test::ParentchainHeaderBuilder;
+	use itp_sgx_crypto::{aes::Aes, mocks::KeyRepositoryMock};
+	use itp_test::mock::onchain_mock::OnchainMock;
+	use itp_types::{
+		state_confirmation::StateConfirmation, AccountId, Header as ParentchainHeader,
+	};
+	use sp_core::{ed25519, Pair};
+
+	type TestConfirmer = QuorumStateConfirmer<OnchainMock, KeyRepositoryMock<Aes>>;
+	type TestRequest = StateConfirmationRequest<ParentchainHeader, TrustedCallSigned, Getter>;
+
+	fn request() -> TestRequest {
+		StateConfirmationRequest {
+			shard: ShardIdentifier::default(),
+			block_number: 4,
+			timestamp: 1000,
+			parentchain_header: ParentchainHeaderBuilder::default().build(),
+			trusted_operations: Vec::new(),
+			state_hash_before_execution: H256::repeat_byte(1),
+			state_hash_after_execution: H256::repeat_byte(2),
+		}
+	}
+
+	fn validateers(count: u8) -> Vec<ed25519::Pair> {
+		(0..count).map(|i| ed25519::Pair::from_seed(&[i; 32])).collect()
+	}
+
+	fn confirmer(
+		validateers: &[ed25519::Pair],
+		confirmations: Vec<StateConfirmation>,
+	) -> TestConfirmer {
+		let ocall_api = OnchainMock::default()
+			.add_validateer_set(
+				&request().parentchain_header,
+				request().shard,
+				Some(validateers.iter().map(|p| AccountId::from(p.public())).collect()),
+			)
+			.with_state_confirmations(confirmations);
+		let state_key_repository = KeyRepositoryMock::new(Aes::new([3u8; 16], [0u8; 16]));
+
+		QuorumStateConfirmer::new(Arc::new(ocall_api), Arc::new(state_key_repository), 67)
+	}
+
+	#[test]
+	fn single_validateer_confirms_its_own_update() {
+		let confirmer = confirmer(&validateers(1), Vec::new());
+
+		assert!(confirmer.confirm_state_update(&request()).is_ok());
+	}
+
+	#[test]
+	fn update_is_confirmed_with_quorum_of_peers() {
+		let validateers = validateers(4);
+		let confirmations = validateers[1..3]
+			.iter()
+			.map(|p| StateConfirmation::new(request().update(), p))
+			.collect();
+		let confirmer = confirmer(&validateers, confirmations);
+
+		assert!(confirmer.confirm_state_update(&request()).is_ok());
+	}
+
+	#[test]
+	fn update_is_not_confirmed_without_quorum() {
+		let validateers = validateers(4);
+		let mut other_update = request().update();
+		other_update.state_hash_after_execution = H256::repeat_byte(3);
+		let confirmations = vec![
+			StateConfirmation::new(request().update(), &validateers[1]),
+			StateConfirmation::new(other_update, &validateers[2]),
+			// Not a validateer of the shard.
+			StateConfirmation::new(request().update(), &ed25519::Pair::from_seed(&[9u8; 32])),
+		];
+		let confirmer = confirmer(&validateers, confirmations);
+
+		assert!(matches!(
+			confirmer.confirm_state_update(&request()),
+			Err(ConsensusError::StateUpdateNotConfirmed(2, 3))
+		));
+	}
+}
@@ -18,10 +18,13 @@
 use crate::{block_importer::BlockImporter, ShardIdentifierFor};
 use codec::Encode;
 use core::assert_matches::assert_matches;
+use ita_stf::migrations::STF_VERSION;
 use itc_parentchain_block_import_dispatcher::trigger_parentchain_block_import_mock::TriggerParentchainBlockImportMock;
 use itc_parentchain_test::{ParentchainBlockBuilder, ParentchainHeaderBuilder};
+use itp_enclave_bridge_storage::{EnclaveBridgeStorage, EnclaveBridgeStorageKeys};
 use itp_sgx_crypto::{aes::Aes, mocks::KeyRepositoryMock, StateCrypto};
-use itp_sgx_externalities::SgxExternalitiesDiffType;
+use itp_sgx_externalities::{SgxExternalitiesDiffType, SgxExternalitiesTrait};
+use itp_stf_interface::{StfVersion, STF_VERSION_KEY};
 use itp_stf_state_handler::handle_state::HandleState;
 use itp_test::mock::{
 	handle_state_mock::HandleStateMock,
@@ -30,7 +33,10 @@ use itp_test::mock::{
 };
 use itp_time_utils::{duration_now, now_as_millis};
 use itp_top_pool_author::mocks::AuthorApiMock;
-use itp_types::{Block as ParentchainBlock, Header as ParentchainHeader, H256};
+use itp_types::{
+	enclave_upgrade::{ShardConfig, UpgradableShardConfig},
+	Block as ParentchainBlock, Header as ParentchainHeader, H256,
+};
 use its_consensus_common::{BlockImport, Error as ConsensusError};
 use its_primitives::{
 	traits::{SignBlock, SignedBlock},
@@ -80,13 +86,25 @@ fn test_fixtures(
 	parentchain_header: &ParentchainHeader,
 	parentchain_block_import_trigger: Arc<TestParentchainBlockImportTrigger>,
 ) -> (TestBlockImporter, Arc<HandleStateMock>, Arc<TestTopPoolAuthor>) {
-	let state_handler = Arc::new(HandleStateMock::from_shard(shard()).unwrap());
-	let top_pool_author = Arc::new(TestTopPoolAuthor::default());
-	let ocall_api = Arc::new(OnchainMock::default().add_validateer_set(
+	test_fixtures_with_ocall_api(onchain_mock(parentchain_header), parentchain_block_import_trigger)
+}
+
+fn onchain_mock(parentchain_header: &ParentchainHeader) -> OnchainMock {
+	OnchainMock::default().add_validateer_set(
 		parentchain_header,
 		shard(),
 		Some(vec![Keyring::Alice.public().into()]),
-	));
+	)
+}
+
+fn test_fixtures_with_ocall_api(
+	ocall_api: OnchainMock,
+	parentchain_block_import_trigger: Arc<TestParentchainBlockImportTrigger>,
+) -> (TestBlockImporter, Arc<HandleStateMock>, Arc<TestTopPoolAuthor>) {
+	let state_handler = Arc::new(HandleStateMock::from_shard(shard()).unwrap());
+	set_state_stf_version(&state_handler, STF_VERSION);
+	let top_pool_author = Arc::new(TestTopPoolAuthor::default());
+	let ocall_api = Arc::new(ocall_api);
 	let state_key_repository = Arc::new(TestStateKeyRepo::new(state_key()));
 
 	let block_importer = TestBlockImporter::new(
@@ -106,6 +124,12 @@ fn test_fixtures_with_default_import_trigger(
 	test_fixtures(parentchain_header, Arc::new(TestParentchainBlockImportTrigger::default()))
 }
 
+fn set_state_stf_version(state_handler: &HandleStateMock, version: StfVersion) {
+	let (state_lock, mut state) = state_handler.load_for_mutation(&shard()).unwrap();
+	state.insert(STF_VERSION_KEY.into(), version.encode());
+	state_handler.write_after_mutation(state, state_lock, &shard()).unwrap();
+}
+
 fn empty_encrypted_state_update(state_handler: &HandleStateMock) -> Vec<u8> {
 	let (_, apriori_state_hash) = state_handler.load_cloned(&shard()).unwrap();
 	let empty_state_diff = SgxExternalitiesDiffType::default();
@@ -161,6 +185,52 @@ fn simple_block_import_works() {
 		.unwrap();
 }
 
+#[test]
+fn block_import_of_outdated_stf_fails() {
+	let parentchain_header = ParentchainHeaderBuilder::default().build();
+	let (block_importer, state_handler, _) =
+		test_fixtures_with_default_import_trigger(&parentchain_header);
+	// The state is not migrated by the block, so it keeps the version of the producing STF.
+	set_state_stf_version(&state_handler, STF_VERSION - 1);
+	let signed_sidechain_block =
+		default_authority_signed_block(&parentchain_header, state_handler.as_ref());
+
+	assert_matches!(
+		block_importer.import_block(signed_sidechain_block, &parentchain_header),
+		Err(ConsensusError::OutdatedStfVersion(_, STF_VERSION))
+	);
+}
+
+#[test]
+fn block_import_of_shard_in_maintenance_mode_fails() {
+	let parentchain_header = ParentchainHeaderBuilder::default().build();
+	let config = UpgradableShardConfig {
+		active_config: ShardConfig {
+			enclave_fingerprint: [1u8; 32].into(),
+			max_instances: None,
+			authorities: None,
+			maintenance_mode: true,
+		},
+		pending_upgrade: None,
+		upgrade_at: None,
+	};
+	let ocall_api = onchain_mock(&parentchain_header).with_storage_entries_at_header(
+		&parentchain_header,
+		vec![(EnclaveBridgeStorage::shard_config(shard()), config)],
+	);
+	let (block_importer, state_handler, _) = test_fixtures_with_ocall_api(
+		ocall_api,
+		Arc::new(TestParentchainBlockImportTrigger::default()),
+	);
+	let signed_sidechain_block =
+		default_authority_signed_block(&parentchain_header, state_handler.as_ref());
+
+	assert_matches!(
+		block_importer.import_block(signed_sidechain_block, &parentchain_header),
+		Err(ConsensusError::BadSidechainBlock(..))
+	);
+}
+
 #[test]
 fn block_import_with_invalid_signature_fails() {
 	let parentchain_header = ParentchainHeaderBuilder::default().build();
@@ -17,6 +17,7 @@
 
 use crate::{authorities, EnclaveOnChainOCallApi, ShardIdentifierFor};
 use core::marker::PhantomData;
+use itp_types::BlockNumber;
 use its_block_verification::verify_sidechain_block;
 use its_consensus_common::{Error as ConsensusError, Verifier};
 use its_primitives::{
@@ -25,8 +26,13 @@ use its_primitives::{
 };
 use its_validateer_fetch::ValidateerFetch;
 use sp_core::crypto::UncheckedFrom;
-use sp_runtime::{app_crypto::Pair, traits::Block as ParentchainBlockTrait};
-use std::{fmt::Debug, time::Duration};
+use sp_runtime::{
+	app_crypto::Pair,
+	traits::{
+		Block as ParentchainBlockTrait, Header as ParentchainHeaderTrait, UniqueSaturatedInto,
+	},
+};
+use std::{fmt::Debug, string::ToString, time::Duration};
 
 #[derive(Default)]
 pub struct AuraVerifier<AuthorityPair, ParentchainBlock, SignedSidechainBlock, Context>
@@ -83,12 +89,31 @@ where
 			ParentchainBlock::Header,
 		>(ctx, parentchain_header, shard)?;
 
-		Ok(verify_sidechain_block::<AuthorityPair, ParentchainBlock, SignedSidechainBlock>(
-			signed_block,
-			self.slot_duration,
-			&self.last_sidechain_block,
-			parentchain_header,
-			&authorities,
-		)?)
+		let signed_block =
+			verify_sidechain_block::<AuthorityPair, ParentchainBlock, SignedSidechainBlock>(
+				signed_block,
+				self.slot_duration,
+				&self.last_sidechain_block,
+				parentchain_header,
+				&authorities,
+			)?;
+
+		// No blocks are authored on a parentchain block at which the shard is in maintenance
+		// mode, so the state of a retired shard can't change after its final state was taken.
+		let config = ctx
+			.shard_config::<ParentchainBlock::Header, SignedSidechainBlock>(
+				parentchain_header,
+				shard,
+			)
+			.map_err(|e| ConsensusError::ChainLookup(e.to_string()))?;
+		let block_number: BlockNumber = (*parentchain_header.number()).unique_saturated_into();
+		if config.map_or(false, |c| c.config_at(block_number).maintenance_mode) {
+			return Err(ConsensusError::BadSidechainBlock(
+				signed_block.block().hash(),
+				"shard is in maintenance mode".into(),
+			))
+		}
+
+		Ok(signed_block)
 	}
 }
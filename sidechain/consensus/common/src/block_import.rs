@@ -105,6 +105,22 @@ where
 	/// Cleanup task after import is done.
 	fn cleanup(&self, signed_sidechain_block: &SignedSidechainBlock) -> Result<(), Error>;
 
+	/// Whether the imported blocks are recorded with [`Self::record_imported_block`]. Recording
+	/// costs a copy of the state for every imported block.
+	fn records_imported_blocks(&self) -> bool {
+		false
+	}
+
+	/// Records a block after its state update was applied on `state_before_import`.
+	fn record_imported_block(
+		&self,
+		_sidechain_block: &SignedSidechainBlock::Block,
+		_parentchain_header: &ParentchainBlock::Header,
+		_state_before_import: Self::SidechainState,
+		_state_update: &<Self::SidechainState as SidechainState>::StateUpdate,
+	) {
+	}
+
 	/// Import a sidechain block and mutate state by `apply_state_update`.
 	fn import_block(
 		&self,
@@ -147,6 +163,7 @@ where
 		let state_key = self.state_key()?;
 
 		let state_update_start_time = Instant::now();
+		let mut recorded_import = None;
 		self.apply_state_update(&shard, |mut state| {
 			let encrypted_state_diff =
 				block_import_params.block().block_data().encrypted_state_diff();
@@ -159,12 +176,23 @@ where
 
 			let update = state_update_from_encrypted(encrypted_state_diff, state_key)?;
 
+			let state_before_import = self.records_imported_blocks().then(|| state.clone());
+
 			state.apply_state_update(&update).map_err(|e| Error::Other(e.into()))?;
 
 			state.set_last_block(block_import_params.block());
 
+			recorded_import = state_before_import.map(|state_before| (state_before, update));
 			Ok(state)
 		})?;
+		if let Some((state_before_import, update)) = recorded_import {
+			self.record_imported_block(
+				&sidechain_block,
+				&peeked_parentchain_header,
+				state_before_import,
+				&update,
+			);
+		}
 		info!(
 			"Applying state update from block {} took {} ms",
 			block_number,
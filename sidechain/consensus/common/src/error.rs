@@ -17,7 +17,7 @@
 
 //! Error types in sidechain consensus
 
-use itp_types::BlockHash as ParentchainBlockHash;
+use itp_types::{BlockHash as ParentchainBlockHash, H256};
 use its_block_verification::error::Error as VerificationError;
 use its_primitives::types::{block::BlockHash as SidechainBlockHash, BlockNumber};
 use sgx_types::sgx_status_t;
@@ -62,6 +62,16 @@ pub enum Error {
 	BlockAlreadyImported(BlockNumber, BlockNumber),
 	#[error("Failed to pop from block import queue: {0}")]
 	FailedToPopBlockImportQueue(#[from] itp_import_queue::error::Error),
+	#[error(
+		"Block was produced by the outdated STF version {0}, this enclave runs STF version {1}"
+	)]
+	OutdatedStfVersion(u32, u32),
+	#[error("State update was confirmed by {0} validateers, but {1} confirmations are required")]
+	StateUpdateNotConfirmed(u64, u64),
+	#[error("Re-executed state hash {0:?} does not match the proposed state hash {1:?}")]
+	StateUpdateMismatch(H256, H256),
+	#[error("This enclave may not author blocks of shard {0} at the latest parentchain block")]
+	AuthoringNotPermitted(String),
 	#[error("Verification Error: {0}")]
 	VerificationError(its_block_verification::error::Error),
 	#[error(transparent)]
@@ -73,7 +73,8 @@ impl<
 		BlockImporter,
 		SidechainOCallApi,
 		ImportConfirmationHandler,
-	> where
+	>
+where
 	ParentchainBlock: ParentchainBlockTrait,
 	SignedSidechainBlock: SignedSidechainBlockTrait,
 	<<SignedSidechainBlock as SignedSidechainBlockTrait>::Block as BlockTrait>::HeaderType:
@@ -97,10 +98,26 @@ impl<
 		}
 	}
 
+	/// Imports all blocks a peer has on top of `last_imported_sidechain_block_hash`, e.g. to
+	/// catch up with the last block of a shard that isn't authored anymore.
+	pub fn import_latest_blocks_from_peer(
+		&self,
+		last_imported_sidechain_block_hash: BlockHash,
+		current_parentchain_header: &ParentchainBlock::Header,
+		shard_identifier: ShardIdentifierFor<SignedSidechainBlock>,
+	) -> Result<ParentchainBlock::Header> {
+		self.fetch_and_import_blocks_from_peer(
+			last_imported_sidechain_block_hash,
+			None,
+			current_parentchain_header,
+			shard_identifier,
+		)
+	}
+
 	fn fetch_and_import_blocks_from_peer(
 		&self,
 		last_imported_sidechain_block_hash: BlockHash,
-		import_until_block_hash: BlockHash,
+		import_until_block_hash: Option<BlockHash>,
 		current_parentchain_header: &ParentchainBlock::Header,
 		shard_identifier: ShardIdentifierFor<SignedSidechainBlock>,
 	) -> Result<ParentchainBlock::Header> {
@@ -112,7 +129,7 @@ impl<
 		let blocks_to_import: Vec<SignedSidechainBlock> =
 			self.sidechain_ocall_api.fetch_sidechain_blocks_from_peer(
 				last_imported_sidechain_block_hash,
-				Some(import_until_block_hash),
+				import_until_block_hash,
 				shard_identifier,
 			)?;
 
@@ -174,7 +191,7 @@ where
 					warn!("Got ancestry mismatch error upon block import. Attempting to fetch missing blocks from peer");
 					let updated_parentchain_header = self.fetch_and_import_blocks_from_peer(
 						block_hash,
-						sidechain_block_hash,
+						Some(sidechain_block_hash),
 						current_parentchain_header,
 						shard_identifier,
 					)?;
@@ -186,7 +203,7 @@ where
 							Attempting to fetch missing blocks from peer", block_number);
 					let updated_parentchain_header = self.fetch_and_import_blocks_from_peer(
 						Default::default(), // This is the parent hash of the first block. So we import everything.
-						sidechain_block_hash,
+						Some(sidechain_block_hash),
 						current_parentchain_header,
 						shard_identifier,
 					)?;
@@ -95,7 +95,13 @@ mod tests {
 		block_fetch_server::BlockFetchServerModuleBuilder,
 		mocks::untrusted_peer_fetch_mock::UntrustedPeerFetcherMock,
 	};
-	use its_primitives::types::block::SignedBlock;
+	use its_primitives::{
+		traits::{Block as BlockTrait, Header as HeaderTrait},
+		types::{block::SignedBlock, header::SidechainHeader},
+	};
+	use its_rpc_handler::constants::{
+		RPC_METHOD_NAME_GET_BLOCK_BY_NUMBER, RPC_METHOD_NAME_GET_HEADER,
+	};
 	use its_storage::fetch_blocks_mock::FetchBlocksMock;
 	use its_test::sidechain_block_builder::{SidechainBlockBuilder, SidechainBlockBuilderTrait};
 	use jsonrpsee::ws_server::WsServerBuilder;
@@ -138,4 +144,36 @@ mod tests {
 
 		assert_eq!(blocks_to_fetch, blocks_fetched);
 	}
+	#[tokio::test]
+	async fn get_block_by_number_and_header_from_peer_works() {
+		const W2_URL: &str = "127.0.0.1:2234";
+
+		let blocks = vec![
+			SidechainBlockBuilder::random().build_signed(),
+			SidechainBlockBuilder::random().build_signed(),
+		];
+		run_server(blocks.clone(), W2_URL).await.unwrap();
+
+		let client = WsClientBuilder::default().build(&format!("ws://{}", W2_URL)).await.unwrap();
+		let block_number = blocks[0].block().header().block_number();
+
+		let fetched_block = client
+			.request::<Option<SignedBlock>>(
+				RPC_METHOD_NAME_GET_BLOCK_BY_NUMBER,
+				vec![to_json_value((ShardIdentifier::default(), block_number)).unwrap()].into(),
+			)
+			.await
+			.unwrap();
+		let latest_header = client
+			.request::<Option<SidechainHeader>>(
+				RPC_METHOD_NAME_GET_HEADER,
+				vec![to_json_value((ShardIdentifier::default(), None::<BlockHash>)).unwrap()]
+					.into(),
+			)
+			.await
+			.unwrap();
+
+		assert_eq!(Some(blocks[0].clone()), fetched_block);
+		assert_eq!(Some(blocks[1].block().header), latest_header);
+	}
 }
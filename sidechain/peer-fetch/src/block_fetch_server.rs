@@ -16,14 +16,22 @@
 */
 
 use crate::error::Result;
-use its_primitives::types::{BlockHash, ShardIdentifier, SignedBlock};
-use its_rpc_handler::constants::RPC_METHOD_NAME_FETCH_BLOCKS_FROM_PEER;
+use its_primitives::{
+	traits::SignedBlock as SignedBlockT,
+	types::{BlockHash, BlockNumber, ShardIdentifier, SignedBlock},
+};
+use its_rpc_handler::constants::{
+	RPC_METHOD_NAME_FETCH_BLOCKS_FROM_PEER, RPC_METHOD_NAME_GET_BLOCK_BY_HASH,
+	RPC_METHOD_NAME_GET_BLOCK_BY_NUMBER, RPC_METHOD_NAME_GET_HEADER,
+};
 use its_storage::interface::FetchBlocks;
 use jsonrpsee::{types::error::CallError, RpcModule};
 use log::*;
 use std::sync::Arc;
 
 /// RPC server module builder for fetching sidechain blocks from peers.
+///
+/// Also serves the stored block history (within the pruning window) to explorers.
 pub struct BlockFetchServerModuleBuilder<FetchBlocksFromStorage> {
 	sidechain_block_fetcher: Arc<FetchBlocksFromStorage>,
 }
@@ -71,6 +79,57 @@ where
 				}
 			},
 		)?;
+
+		fetch_sidechain_blocks_module.register_method(
+			RPC_METHOD_NAME_GET_BLOCK_BY_HASH,
+			|params, sidechain_block_fetcher| {
+				debug!("{}: {:?}", RPC_METHOD_NAME_GET_BLOCK_BY_HASH, params);
+
+				let block_hash = params.one::<BlockHash>()?;
+				sidechain_block_fetcher.fetch_block_by_hash(&block_hash).map_err(|e| {
+					error!("Failed to fetch sidechain block from storage: {:?}", e);
+					CallError::Failed(e.into())
+				})
+			},
+		)?;
+
+		fetch_sidechain_blocks_module.register_method(
+			RPC_METHOD_NAME_GET_BLOCK_BY_NUMBER,
+			|params, sidechain_block_fetcher| {
+				debug!("{}: {:?}", RPC_METHOD_NAME_GET_BLOCK_BY_NUMBER, params);
+
+				let (shard_identifier, block_number) =
+					params.one::<(ShardIdentifier, BlockNumber)>()?;
+				sidechain_block_fetcher
+					.fetch_block_by_number(&shard_identifier, block_number)
+					.map_err(|e| {
+						error!("Failed to fetch sidechain block from storage: {:?}", e);
+						CallError::Failed(e.into())
+					})
+			},
+		)?;
+
+		fetch_sidechain_blocks_module.register_method(
+			RPC_METHOD_NAME_GET_HEADER,
+			|params, sidechain_block_fetcher| {
+				debug!("{}: {:?}", RPC_METHOD_NAME_GET_HEADER, params);
+
+				// Returns the header of the latest block, if no block hash is given.
+				let (shard_identifier, maybe_block_hash) =
+					params.one::<(ShardIdentifier, Option<BlockHash>)>()?;
+				let maybe_block = match maybe_block_hash {
+					Some(block_hash) => sidechain_block_fetcher.fetch_block_by_hash(&block_hash),
+					None => sidechain_block_fetcher.fetch_latest_block(&shard_identifier),
+				}
+				.map_err(|e| {
+					error!("Failed to fetch sidechain block from storage: {:?}", e);
+					CallError::Failed(e.into())
+				})?;
+
+				Ok(maybe_block.map(|b| b.block().header))
+			},
+		)?;
+
 		Ok(fetch_sidechain_blocks_module)
 	}
 }
@@ -0,0 +1,106 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use crate::sgx_reexport_prelude::*;
+
+use crate::constants::RPC_METHOD_NAME_CONFIRM_STATE_UPDATE;
+use itp_utils::{FromHexPrefixed, ToHexPrefixed};
+use jsonrpc_core::{IoHandler, Params, Value};
+use log::*;
+use std::{fmt::Debug, format, string::String, vec::Vec};
+
+/// Adds the RPC method with which peer validateers request the confirmation of a proposed
+/// state update (pessimistic execution mode). The parameter is the hex encoded, state key
+/// encrypted confirmation request. The result is the hex encoded confirmation.
+pub fn add_confirm_state_update_rpc_method<ConfirmFn, Error>(
+	confirm_fn: ConfirmFn,
+	mut io_handler: IoHandler,
+) -> IoHandler
+where
+	ConfirmFn: Fn(Vec<u8>) -> Result<Vec<u8>, Error> + Sync + Send + 'static,
+	Error: Debug,
+{
+	io_handler.add_sync_method(RPC_METHOD_NAME_CONFIRM_STATE_UPDATE, move |params: Params| {
+		debug!("{} rpc. Params: {:?}", RPC_METHOD_NAME_CONFIRM_STATE_UPDATE, params);
+
+		let hex_encoded_params: Vec<String> = params.parse()?;
+
+		let encrypted_request = hex_encoded_params
+			.first()
+			.and_then(|p| Vec::<u8>::from_hex(p).ok())
+			.ok_or_else(|| {
+				jsonrpc_core::error::Error::invalid_params_with_details(
+					"Could not decode state confirmation request",
+					hex_encoded_params.clone(),
+				)
+			})?;
+
+		let confirmation = confirm_fn(encrypted_request).map_err(|e| {
+			warn!("Refusing to confirm state update: {:?}", e);
+			jsonrpc_core::error::Error::invalid_params(format!(
+				"State update not confirmed: {:?}",
+				e
+			))
+		})?;
+
+		Ok(Value::String(confirmation.to_hex()))
+	});
+
+	io_handler
+}
+
+#[cfg(test)]
+pub mod tests {
+	use super::*;
+
+	fn io_handler() -> IoHandler {
+		add_confirm_state_update_rpc_method(
+			|request: Vec<u8>| if request.is_empty() { Err("empty") } else { Ok(request) },
+			IoHandler::new(),
+		)
+	}
+
+	#[test]
+	pub fn confirmation_is_returned_hex_encoded() {
+		let io = io_handler();
+		let request = vec![1u8, 2, 3].to_hex();
+		let enclave_req = format!(
+			r#"{{"jsonrpc":"2.0","method":"sidechain_confirmStateUpdate","params":["{}"],"id":1}}"#,
+			request
+		);
+
+		let response_string = io.handle_request_sync(&enclave_req).unwrap();
+
+		assert_eq!(
+			response_string,
+			format!(r#"{{"jsonrpc":"2.0","result":"{}","id":1}}"#, vec![1u8, 2, 3].to_hex())
+		);
+	}
+
+	#[test]
+	pub fn refused_confirmation_returns_error() {
+		let io = io_handler();
+		let enclave_req = format!(
+			r#"{{"jsonrpc":"2.0","method":"sidechain_confirmStateUpdate","params":["{}"],"id":1}}"#,
+			Vec::<u8>::new().to_hex()
+		);
+
+		let response_string = io.handle_request_sync(&enclave_req).unwrap();
+
+		assert!(response_string.contains("State update not confirmed"));
+	}
+}
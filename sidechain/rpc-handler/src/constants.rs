@@ -20,3 +20,17 @@
 // RPC method names.
 pub const RPC_METHOD_NAME_IMPORT_BLOCKS: &str = "sidechain_importBlock";
 pub const RPC_METHOD_NAME_FETCH_BLOCKS_FROM_PEER: &str = "sidechain_fetchBlocksFromPeer";
+pub const RPC_METHOD_NAME_CONFIRM_STATE_UPDATE: &str = "sidechain_confirmStateUpdate";
+pub const RPC_METHOD_NAME_GET_BLOCK_BY_HASH: &str = "sidechain_getBlockByHash";
+pub const RPC_METHOD_NAME_GET_BLOCK_BY_NUMBER: &str = "sidechain_getBlockByNumber";
+pub const RPC_METHOD_NAME_GET_HEADER: &str = "sidechain_getHeader";
+pub const RPC_METHOD_NAME_SUBMIT_WITH_RECEIPT: &str = "author_submitExtrinsicWithReceipt";
+
+// Substrate compatible RPC method names, served with sidechain blocks.
+pub const RPC_METHOD_NAME_CHAIN_GET_HEADER: &str = "chain_getHeader";
+pub const RPC_METHOD_NAME_CHAIN_GET_BLOCK: &str = "chain_getBlock";
+pub const RPC_METHOD_NAME_CHAIN_GET_BLOCK_HASH: &str = "chain_getBlockHash";
+pub const RPC_METHOD_NAME_CHAIN_GET_FINALIZED_HEAD: &str = "chain_getFinalizedHead";
+pub const RPC_METHOD_NAME_CHAIN_SUBSCRIBE_NEW_HEADS: &str = "chain_subscribeNewHeads";
+pub const RPC_METHOD_NAME_CHAIN_UNSUBSCRIBE_NEW_HEADS: &str = "chain_unsubscribeNewHeads";
+pub const RPC_METHOD_NAME_STATE_GET_RUNTIME_VERSION: &str = "state_getRuntimeVersion";
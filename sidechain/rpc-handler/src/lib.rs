@@ -31,6 +31,8 @@ pub mod sgx_reexport_prelude {
 	pub use rust_base58_sgx as base58;
 }
 
+pub mod confirm_state_update_api;
 pub mod constants;
 pub mod direct_top_pool_api;
 pub mod import_block_api;
+pub mod submission_receipt_api;
@@ -0,0 +1,146 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use crate::sgx_reexport_prelude::*;
+
+use crate::constants::RPC_METHOD_NAME_SUBMIT_WITH_RECEIPT;
+use codec::{Decode, Encode};
+use core::fmt::Debug;
+use itp_rpc::RpcReturnValue;
+use itp_sgx_crypto::key_repository::AccessKey;
+use itp_time_utils::now_as_millis;
+use itp_top_pool_author::traits::AuthorApi;
+use itp_types::{
+	submission_receipt::{SignedSubmissionReceipt, SubmissionReceipt},
+	DirectRequestStatus, Request, TrustedOperationStatus,
+};
+use itp_utils::{FromHexPrefixed, ToHexPrefixed};
+use jsonrpc_core::{futures::executor, serde_json::json, IoHandler, Params};
+use log::*;
+use sp_core::{ed25519, H256};
+use std::{format, string::String, sync::Arc, vec::Vec};
+
+/// Adds the RPC method to submit a trusted operation without watching it. Instead of the
+/// operation hash, a [SignedSubmissionReceipt] is returned, signed with the enclave's signing key.
+pub fn add_submission_receipt_rpc_method<R, TCS, G, SigningKeyRepository>(
+	top_pool_author: Arc<R>,
+	signing_key_repository: Arc<SigningKeyRepository>,
+	mut io_handler: IoHandler,
+) -> IoHandler
+where
+	R: AuthorApi<H256, H256, TCS, G> + Send + Sync + 'static,
+	TCS: PartialEq + Encode + Decode + Debug + Send + Sync + 'static,
+	G: PartialEq + Encode + Decode + Debug + Send + Sync + 'static,
+	SigningKeyRepository: AccessKey<KeyType = ed25519::Pair> + Send + Sync + 'static,
+{
+	io_handler.add_sync_method(RPC_METHOD_NAME_SUBMIT_WITH_RECEIPT, move |params: Params| {
+		debug!("worker_api_direct rpc was called: {}", RPC_METHOD_NAME_SUBMIT_WITH_RECEIPT);
+		let json_value = match submit_with_receipt_inner(
+			top_pool_author.as_ref(),
+			signing_key_repository.as_ref(),
+			params,
+		) {
+			Ok(receipt) => RpcReturnValue {
+				do_watch: false,
+				value: receipt.encode(),
+				status: DirectRequestStatus::TrustedOperationStatus(
+					TrustedOperationStatus::Submitted,
+				),
+			}
+			.to_hex(),
+			Err(error) => RpcReturnValue::from_error_message(error.as_str()).to_hex(),
+		};
+		Ok(json!(json_value))
+	});
+
+	io_handler
+}
+
+fn submit_with_receipt_inner<R, TCS, G, SigningKeyRepository>(
+	author: &R,
+	signing_key_repository: &SigningKeyRepository,
+	params: Params,
+) -> Result<SignedSubmissionReceipt, String>
+where
+	R: AuthorApi<H256, H256, TCS, G>,
+	TCS: PartialEq + Encode + Decode + Debug + Send + Sync + 'static,
+	G: PartialEq + Encode + Decode + Debug + Send + Sync + 'static,
+	SigningKeyRepository: AccessKey<KeyType = ed25519::Pair>,
+{
+	let hex_encoded_params = params.parse::<Vec<String>>().map_err(|e| format!("{:?}", e))?;
+	let request = hex_encoded_params
+		.first()
+		.ok_or_else(|| String::from("Missing request parameter"))
+		.and_then(|p| Request::from_hex(p).map_err(|e| format!("{:?}", e)))?;
+
+	// Retrieve the key first, there is no point in submitting if we can't sign the receipt.
+	let signer = signing_key_repository.retrieve_key().map_err(|e| format!("{:?}", e))?;
+
+	let operation_hash = executor::block_on(author.submit_top(request.cyphertext, request.shard))
+		.map_err(|e| {
+		warn!("Submitting trusted operation failed: {:?}", e);
+		format!("{:?}", e)
+	})?;
+	debug!("Trusted operation submitted successfully ({:?}), signing receipt", operation_hash);
+
+	let receipt =
+		SubmissionReceipt { shard: request.shard, operation_hash, accepted_at: now_as_millis() };
+	Ok(SignedSubmissionReceipt::new(receipt, &signer))
+}
+
+#[cfg(test)]
+pub mod tests {
+	use super::*;
+	use itp_rpc::RpcResponse;
+	use itp_sgx_crypto::mocks::KeyRepositoryMock;
+	use itp_stf_primitives::types::ShardIdentifier;
+	use itp_test::mock::stf_mock::{GetterMock, TrustedCallSignedMock};
+	use itp_top_pool_author::mocks::AuthorApiMock;
+	use sp_core::Pair;
+
+	type TestAuthor = AuthorApiMock<H256, H256, TrustedCallSignedMock, GetterMock>;
+
+	#[test]
+	pub fn submission_returns_signed_receipt() {
+		let author = Arc::new(TestAuthor::default());
+		let signer = ed25519::Pair::from_seed(&[7u8; 32]);
+		let io = add_submission_receipt_rpc_method(
+			author.clone(),
+			Arc::new(KeyRepositoryMock::new(signer.clone())),
+			IoHandler::new(),
+		);
+		let shard = ShardIdentifier::repeat_byte(2);
+		let request = Request { shard, cyphertext: vec![1u8, 2, 3] };
+		let rpc_request = format!(
+			r#"{{"jsonrpc":"2.0","method":"{}","params":["{}"],"id":1}}"#,
+			RPC_METHOD_NAME_SUBMIT_WITH_RECEIPT,
+			request.to_hex()
+		);
+
+		let response: RpcResponse =
+			jsonrpc_core::serde_json::from_str(&io.handle_request_sync(&rpc_request).unwrap())
+				.unwrap();
+		let return_value = RpcReturnValue::from_hex(&response.result).unwrap();
+		let receipt = SignedSubmissionReceipt::decode(&mut return_value.value.as_slice()).unwrap();
+
+		assert!(receipt.verify());
+		assert_eq!(receipt.signer, signer.public());
+		assert_eq!(receipt.receipt.shard, shard);
+		assert_eq!(author.pending_tops(shard).unwrap(), vec![vec![1u8, 2, 3]]);
+	}
+}
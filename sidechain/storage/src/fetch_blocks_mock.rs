@@ -17,8 +17,8 @@
 
 use crate::{error::Result, interface::FetchBlocks};
 use its_primitives::{
-	traits::ShardIdentifierFor,
-	types::{BlockHash, SignedBlock},
+	traits::{Block as BlockT, Header as HeaderT, ShardIdentifierFor, SignedBlock as SignedBlockT},
+	types::{BlockHash, BlockNumber, SignedBlock},
 };
 
 #[derive(Default)]
@@ -50,4 +50,26 @@ impl FetchBlocks<SignedBlock> for FetchBlocksMock {
 	) -> Result<Vec<SignedBlock>> {
 		Ok(self.blocks_to_be_fetched.clone())
 	}
+	fn fetch_block_by_hash(&self, block_hash: &BlockHash) -> Result<Option<SignedBlock>> {
+		Ok(self.blocks_to_be_fetched.iter().find(|b| &b.hash() == block_hash).cloned())
+	}
+
+	fn fetch_block_by_number(
+		&self,
+		_shard_identifier: &ShardIdentifierFor<SignedBlock>,
+		block_number: BlockNumber,
+	) -> Result<Option<SignedBlock>> {
+		Ok(self
+			.blocks_to_be_fetched
+			.iter()
+			.find(|b| b.block().header().block_number() == block_number)
+			.cloned())
+	}
+
+	fn fetch_latest_block(
+		&self,
+		_shard_identifier: &ShardIdentifierFor<SignedBlock>,
+	) -> Result<Option<SignedBlock>> {
+		Ok(self.blocks_to_be_fetched.last().cloned())
+	}
 }
@@ -77,6 +77,26 @@ pub trait FetchBlocks<SignedBlock: SignedBlockT> {
 		block_hash_until: &BlockHash,
 		shard_identifier: &ShardIdentifierFor<SignedBlock>,
 	) -> Result<Vec<SignedBlock>>;
+
+	/// Fetch a block by its hash.
+	///
+	/// Returns `None` if the block is unknown or has already been pruned.
+	fn fetch_block_by_hash(&self, block_hash: &BlockHash) -> Result<Option<SignedBlock>>;
+
+	/// Fetch the block of a shard with the given block number.
+	///
+	/// Returns `None` if the block is unknown or has already been pruned.
+	fn fetch_block_by_number(
+		&self,
+		shard_identifier: &ShardIdentifierFor<SignedBlock>,
+		block_number: BlockNumber,
+	) -> Result<Option<SignedBlock>>;
+
+	/// Fetch the most recent block of a shard.
+	fn fetch_latest_block(
+		&self,
+		shard_identifier: &ShardIdentifierFor<SignedBlock>,
+	) -> Result<Option<SignedBlock>>;
 }
 
 impl<SignedBlock: SignedBlockT> BlockStorage<SignedBlock> for SidechainStorageLock<SignedBlock> {
@@ -110,4 +130,27 @@ impl<SignedBlock: SignedBlockT> FetchBlocks<SignedBlock> for SidechainStorageLoc
 			.read()
 			.get_blocks_in_range(block_hash_from, block_hash_until, shard_identifier)
 	}
+
+	fn fetch_block_by_hash(&self, block_hash: &BlockHash) -> Result<Option<SignedBlock>> {
+		self.storage.read().get_block(block_hash)
+	}
+
+	fn fetch_block_by_number(
+		&self,
+		shard_identifier: &ShardIdentifierFor<SignedBlock>,
+		block_number: BlockNumber,
+	) -> Result<Option<SignedBlock>> {
+		self.storage.read().get_block_by_number(shard_identifier, block_number)
+	}
+
+	fn fetch_latest_block(
+		&self,
+		shard_identifier: &ShardIdentifierFor<SignedBlock>,
+	) -> Result<Option<SignedBlock>> {
+		let storage = self.storage.read();
+		match storage.last_block_of_shard(shard_identifier) {
+			Some(last_block) => storage.get_block(&last_block.hash),
+			None => Ok(None),
+		}
+	}
 }
@@ -101,11 +101,22 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 	}
 
 	/// gets the block of the given blockhash, if there is such a block
-	#[allow(unused)]
 	pub fn get_block(&self, block_hash: &BlockHash) -> Result<Option<SignedBlock>> {
 		self.db.get(block_hash)
 	}
 
+	/// gets the sidechain block of the given shard and block number, if there is such a block
+	pub fn get_block_by_number(
+		&self,
+		shard: &ShardIdentifierFor<SignedBlock>,
+		block_number: BlockNumber,
+	) -> Result<Option<SignedBlock>> {
+		match self.get_block_hash(shard, block_number)? {
+			Some(block_hash) => self.get_block(&block_hash),
+			None => Ok(None),
+		}
+	}
+
 	/// Get all blocks after (i.e. children of) a specified block.
 	pub fn get_blocks_after(
 		&self,
@@ -238,7 +249,12 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 		for shard in self.shards().clone() {
 			// get last block:
 			if let Some(last_block) = self.last_block_of_shard(&shard) {
-				let threshold_block = last_block.number - number_of_blocks_to_keep;
+				let threshold_block = match last_block.number.checked_sub(number_of_blocks_to_keep)
+				{
+					Some(threshold) if threshold > 0 => threshold,
+					// Fewer blocks than the retention window, nothing to prune.
+					_ => continue,
+				};
 				if let Err(e) = self.prune_shard_from_block_number(&shard, threshold_block) {
 					error!("Could not purge shard {:?} due to {:?}", shard, e);
 				}
@@ -1172,4 +1188,38 @@ mod test {
 			assert!(updated_sidechain_db.get_block(&block_two_s.hash()).unwrap().is_none());
 		}
 	}
+	#[test]
+	fn prune_shards_keeps_chains_shorter_than_retention_window() {
+		let temp_dir = create_temp_dir();
+		let shard = H256::from_low_u64_be(1);
+		let block_one = create_signed_block(1, shard);
+		let block_two = create_signed_block(2, shard);
+		{
+			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			sidechain_db.store_blocks(vec![block_one.clone(), block_two.clone()]).unwrap();
+
+			sidechain_db.prune_shards(5);
+		}
+
+		let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		assert_eq!(updated_sidechain_db.get_block(&block_one.hash()).unwrap().unwrap(), block_one);
+		assert_eq!(updated_sidechain_db.get_block(&block_two.hash()).unwrap().unwrap(), block_two);
+	}
+
+	#[test]
+	fn get_block_by_number_works() {
+		let temp_dir = create_temp_dir();
+		let shard = H256::from_low_u64_be(1);
+		let block_one = create_signed_block(1, shard);
+		let block_two = create_signed_block(2, shard);
+		let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		sidechain_db.store_blocks(vec![block_one.clone(), block_two.clone()]).unwrap();
+
+		assert_eq!(sidechain_db.get_block_by_number(&shard, 2).unwrap().unwrap(), block_two);
+		assert!(sidechain_db.get_block_by_number(&shard, 3).unwrap().is_none());
+		assert!(sidechain_db
+			.get_block_by_number(&H256::from_low_u64_be(2), 1)
+			.unwrap()
+			.is_none());
+	}
 }
@@ -19,6 +19,7 @@ use crate::error::{Error, Result};
 use itp_enclave_bridge_storage::{EnclaveBridgeStorage, EnclaveBridgeStorageKeys};
 use itp_ocall_api::EnclaveOnChainOCallApi;
 use itp_types::{
+	enclave_upgrade::UpgradableShardConfig,
 	parentchain::{AccountId, ParentchainId},
 	ShardSignerStatus,
 };
@@ -48,6 +49,15 @@ pub trait ValidateerFetch {
 		latest_header: &Header,
 		shard: ShardIdentifierFor<SignedSidechainBlock>,
 	) -> Result<u64>;
+	/// Config of the shard in the `EnclaveBridge` pallet, `None` if the shard has none.
+	fn shard_config<
+		Header: HeaderT<Hash = H256>,
+		SignedSidechainBlock: its_primitives::traits::SignedBlock,
+	>(
+		&self,
+		latest_header: &Header,
+		shard: ShardIdentifierFor<SignedSidechainBlock>,
+	) -> Result<Option<UpgradableShardConfig>>;
 }
 
 impl<OnchainStorage: EnclaveOnChainOCallApi> ValidateerFetch for OnchainStorage {
@@ -84,6 +94,26 @@ impl<OnchainStorage: EnclaveOnChainOCallApi> ValidateerFetch for OnchainStorage
 	) -> Result<u64> {
 		Ok(self.current_validateers::<Header, SignedSidechainBlock>(header, shard)?.len() as u64)
 	}
+
+	fn shard_config<
+		Header: HeaderT<Hash = H256>,
+		SignedSidechainBlock: its_primitives::traits::SignedBlock,
+	>(
+		&self,
+		header: &Header,
+		shard: ShardIdentifierFor<SignedSidechainBlock>,
+	) -> Result<Option<UpgradableShardConfig>> {
+		Ok(self
+			.get_storage_verified(
+				EnclaveBridgeStorage::shard_config::<ShardIdentifierFor<SignedSidechainBlock>>(
+					shard,
+				),
+				header,
+				&ParentchainId::Integritee,
+			)?
+			.into_tuple()
+			.1)
+	}
 }
 
 #[cfg(test)]
@@ -92,7 +122,7 @@ mod tests {
 
 	use itc_parentchain_test::ParentchainHeaderBuilder;
 	use itp_test::mock::onchain_mock::{validateer_set, OnchainMock};
-	use itp_types::ShardIdentifier;
+	use itp_types::{enclave_upgrade::ShardConfig, ShardIdentifier};
 
 	#[test]
 	pub fn get_validateer_count_works() {
@@ -124,4 +154,32 @@ mod tests {
 			validateers
 		);
 	}
+
+	#[test]
+	pub fn get_shard_config_works() {
+		let header = ParentchainHeaderBuilder::default().build();
+		let shard = ShardIdentifier::default();
+		let config = UpgradableShardConfig {
+			active_config: ShardConfig {
+				enclave_fingerprint: [1u8; 32].into(),
+				max_instances: None,
+				authorities: None,
+				maintenance_mode: false,
+			},
+			pending_upgrade: None,
+			upgrade_at: None,
+		};
+		let mock = OnchainMock::default().with_storage_entries_at_header(
+			&header,
+			vec![(EnclaveBridgeStorage::shard_config(shard), config.clone())],
+		);
+
+		assert_eq!(
+			mock.shard_config::<itp_types::Header, its_primitives::types::SignedBlock>(
+				&header, shard
+			)
+			.unwrap(),
+			Some(config)
+		);
+	}
 }